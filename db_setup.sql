@@ -15,6 +15,56 @@ CREATE TABLE IF NOT EXISTS passkeys (
     credential_id BYTEA NOT NULL
 );
 
+CREATE TABLE IF NOT EXISTS user_totp (
+    user_id TEXT PRIMARY KEY
+);
+
+CREATE TABLE IF NOT EXISTS digest_preferences (
+    user_id TEXT PRIMARY KEY,
+    frequency TEXT NOT NULL
+);
+
+CREATE TABLE IF NOT EXISTS timezone_preferences (
+    user_id TEXT PRIMARY KEY,
+    timezone TEXT NOT NULL
+);
+
+CREATE TABLE IF NOT EXISTS theme_preferences (
+    user_id TEXT PRIMARY KEY,
+    theme TEXT NOT NULL
+);
+
+CREATE TABLE IF NOT EXISTS shortcut_preferences (
+    user_id TEXT PRIMARY KEY,
+    shortcuts TEXT NOT NULL
+);
+
+CREATE TABLE IF NOT EXISTS display_names (
+    user_id TEXT PRIMARY KEY,
+    display_name TEXT NOT NULL
+);
+
+CREATE TABLE IF NOT EXISTS avatar_preferences (
+    user_id TEXT PRIMARY KEY,
+    gravatar BOOLEAN NOT NULL
+);
+
+CREATE TABLE IF NOT EXISTS signin_attempts (
+    id BIGSERIAL PRIMARY KEY,
+    user_id TEXT NOT NULL,
+    method TEXT NOT NULL,
+    succeeded BOOLEAN NOT NULL,
+    ip TEXT,
+    user_agent TEXT,
+    attempted_at TIMESTAMPTZ NOT NULL
+);
+
+CREATE TABLE IF NOT EXISTS signin_lockouts (
+    user_id TEXT PRIMARY KEY,
+    failures BIGINT NOT NULL,
+    locked_until TIMESTAMPTZ
+);
+
 CREATE TABLE IF NOT EXISTS authz_role (
     id TEXT PRIMARY KEY,
     name BYTEA NOT NULL,
@@ -30,7 +80,13 @@ CREATE TABLE IF NOT EXISTS authz_role_actor (
 CREATE TABLE IF NOT EXISTS journals (
     id TEXT PRIMARY KEY,
     owner_id TEXT NOT NULL,
-    name TEXT NOT NULL
+    name TEXT NOT NULL,
+    fiscal_year_start SMALLINT NOT NULL DEFAULT 1,
+    archived BOOLEAN NOT NULL DEFAULT FALSE,
+    default_cash_account TEXT,
+    default_expense_account TEXT,
+    color TEXT NOT NULL DEFAULT 'gray',
+    icon TEXT
 );
 
 CREATE TABLE IF NOT EXISTS journal_members (
@@ -43,13 +99,126 @@ CREATE TABLE IF NOT EXISTS accounts (
     id TEXT PRIMARY KEY,
     journal_id TEXT NOT NULL,
     name TEXT NOT NULL,
-    balance BIGINT NOT NULL
+    balance BIGINT NOT NULL,
+    name_search TSVECTOR GENERATED ALWAYS AS (to_tsvector('simple', name)) STORED,
+    position INTEGER,
+    group_name TEXT
 );
 
 CREATE TABLE IF NOT EXISTS transactions (
     id TEXT PRIMARY KEY,
     journal_id TEXT NOT NULL,
-    updates BYTEA NOT NULL
+    number BIGINT NOT NULL,
+    entries BYTEA NOT NULL,
+    effective_date DATE NOT NULL,
+    reverses TEXT,
+    author_id TEXT,
+    payee TEXT
+);
+
+CREATE TABLE IF NOT EXISTS pending_transactions (
+    id TEXT PRIMARY KEY,
+    journal_id TEXT NOT NULL,
+    entries BYTEA NOT NULL,
+    effective_date DATE NOT NULL
+);
+
+CREATE TABLE IF NOT EXISTS transaction_comments (
+    id TEXT PRIMARY KEY,
+    journal_id TEXT NOT NULL,
+    transaction_id TEXT NOT NULL,
+    parent_id TEXT,
+    body TEXT NOT NULL
+);
+
+CREATE TABLE IF NOT EXISTS account_period_totals (
+    account_id TEXT NOT NULL,
+    journal_id TEXT NOT NULL,
+    period DATE NOT NULL,
+    debit_total BIGINT NOT NULL,
+    credit_total BIGINT NOT NULL,
+    PRIMARY KEY (account_id, period)
+);
+
+CREATE TABLE IF NOT EXISTS webhooks (
+    id TEXT PRIMARY KEY,
+    journal_id TEXT NOT NULL,
+    url TEXT NOT NULL
+);
+
+CREATE TABLE IF NOT EXISTS import_rules (
+    id TEXT PRIMARY KEY,
+    journal_id TEXT NOT NULL,
+    pattern TEXT NOT NULL,
+    min_amount BIGINT,
+    max_amount BIGINT,
+    account_id TEXT NOT NULL,
+    position BIGINT NOT NULL
+);
+
+CREATE TABLE IF NOT EXISTS transaction_templates (
+    id TEXT PRIMARY KEY,
+    journal_id TEXT NOT NULL,
+    name TEXT NOT NULL,
+    entries BYTEA NOT NULL,
+    position BIGINT NOT NULL
+);
+
+CREATE TABLE IF NOT EXISTS bank_feeds (
+    id TEXT PRIMARY KEY,
+    journal_id TEXT NOT NULL,
+    provider TEXT NOT NULL,
+    external_account TEXT NOT NULL,
+    account_id TEXT NOT NULL
+);
+
+CREATE TABLE IF NOT EXISTS bank_feed_syncs (
+    bank_feed_id TEXT PRIMARY KEY,
+    last_synced_at TIMESTAMPTZ
+);
+
+CREATE TABLE IF NOT EXISTS secrets (
+    name TEXT PRIMARY KEY,
+    value BYTEA NOT NULL
+);
+
+CREATE TABLE IF NOT EXISTS bank_feed_lines (
+    bank_feed_id TEXT NOT NULL,
+    external_id TEXT NOT NULL,
+    booked DATE NOT NULL,
+    amount BIGINT NOT NULL,
+    description TEXT NOT NULL,
+    reviewed_at TIMESTAMPTZ,
+    PRIMARY KEY (bank_feed_id, external_id)
+);
+
+CREATE TABLE IF NOT EXISTS webhook_outbox (
+    id BIGSERIAL PRIMARY KEY,
+    webhook_id TEXT NOT NULL,
+    event_id BIGINT NOT NULL,
+    body BYTEA NOT NULL,
+    attempts INTEGER NOT NULL DEFAULT 0,
+    next_attempt_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+    delivered_at TIMESTAMPTZ,
+    UNIQUE (webhook_id, event_id)
+);
+
+CREATE TABLE IF NOT EXISTS email_outbox (
+    id BIGSERIAL PRIMARY KEY,
+    message_key TEXT NOT NULL UNIQUE,
+    recipient TEXT NOT NULL,
+    subject TEXT NOT NULL,
+    body TEXT NOT NULL,
+    attempts INTEGER NOT NULL DEFAULT 0,
+    next_attempt_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+    sent_at TIMESTAMPTZ
+);
+
+-- stub for disintegrate's listener positions
+CREATE TABLE IF NOT EXISTS event_listener (
+    id TEXT PRIMARY KEY,
+    last_processed_event_id BIGINT,
+    updated_at TIMESTAMP DEFAULT now()
 );
 
 -- stub that includes indexes for the journal store
@@ -61,5 +230,61 @@ CREATE TABLE IF NOT EXISTS event (
     journal_id TEXT,
     user_id TEXT,
     account_id TEXT,
-    transaction_id TEXT
-);
\ No newline at end of file
+    transaction_id TEXT,
+    webhook_id TEXT,
+    rule_id TEXT,
+    bank_feed_id TEXT,
+    template_id TEXT,
+    comment_id TEXT,
+    email TEXT,
+    passkey_id TEXT
+);
+
+CREATE TABLE IF NOT EXISTS snapshot (
+    id UUID PRIMARY KEY,
+    name TEXT,
+    query TEXT,
+    version BIGINT,
+    payload TEXT,
+    inserted_at TIMESTAMP
+);
+
+-- stub for disintegrate's highest event id below which every event has committed
+CREATE OR REPLACE FUNCTION event_store_current_epoch()
+RETURNS BIGINT AS $$
+    SELECT COALESCE(MAX(event_id), 0) FROM event;
+$$ LANGUAGE sql;
+
+CREATE TABLE IF NOT EXISTS notifications (
+    id BIGSERIAL PRIMARY KEY,
+    user_id TEXT NOT NULL,
+    journal_id TEXT NOT NULL,
+    event_id BIGINT NOT NULL,
+    message TEXT NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL,
+    read_at TIMESTAMPTZ,
+    UNIQUE (user_id, event_id)
+);
+
+CREATE TABLE IF NOT EXISTS digest_deliveries (
+    user_id TEXT PRIMARY KEY,
+    last_event_id BIGINT NOT NULL,
+    sent_at TIMESTAMPTZ NOT NULL
+);
+
+CREATE TABLE IF NOT EXISTS journal_versions (
+    journal_id TEXT PRIMARY KEY,
+    version BIGINT NOT NULL,
+    last_activity TIMESTAMPTZ
+);
+
+CREATE TABLE IF NOT EXISTS journal_pins (
+    journal_id TEXT NOT NULL,
+    user_id TEXT NOT NULL,
+    PRIMARY KEY (journal_id, user_id)
+);
+
+CREATE TABLE IF NOT EXISTS deleted_journals (
+    journal_id TEXT PRIMARY KEY,
+    deleted_at TIMESTAMPTZ NOT NULL
+);
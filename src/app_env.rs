@@ -0,0 +1,42 @@
+use std::env;
+use std::str::FromStr;
+use thiserror::Error;
+
+#[derive(Debug, Error, PartialEq)]
+#[error("APP_ENV must be development or production, found {0}")]
+pub struct AppEnvError(String);
+
+/// the environment the server runs in, read from `APP_ENV`.
+/// anything that would be unsafe to ship, like seeded users and dev login, is only enabled in development
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default)]
+pub enum AppEnv {
+    Development,
+    #[default]
+    Production,
+}
+
+impl AppEnv {
+    /// defaults to production so that a deployment missing the variable stays locked down
+    pub fn from_env() -> Result<Self, AppEnvError> {
+        match env::var("APP_ENV") {
+            Ok(value) => Self::from_str(&value),
+            Err(_) => Ok(Self::default()),
+        }
+    }
+
+    pub fn dev_mode(self) -> bool {
+        self == Self::Development
+    }
+}
+
+impl FromStr for AppEnv {
+    type Err = AppEnvError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "development" | "dev" => Ok(Self::Development),
+            "production" | "prod" => Ok(Self::Production),
+            _ => Err(AppEnvError(s.to_string())),
+        }
+    }
+}
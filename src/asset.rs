@@ -0,0 +1,108 @@
+use crate::StateType;
+use crate::app_env::AppEnv;
+use axum::Router;
+use axum::http::HeaderValue;
+use axum::http::header::CACHE_CONTROL;
+use axum_login::tracing;
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+use tower_http::services::ServeFile;
+use tower_http::set_header::SetResponseHeader;
+
+/// the files built into `{SITE_ROOT}/pkg`, by the name pages refer to them with
+const ASSETS: &[&str] = &["monkesto.css"];
+
+/// hashed urls change whenever their file does, so browsers can keep them for a year
+const IMMUTABLE: &str = "public, max-age=31536000, immutable";
+
+/// the unhashed fallback can change at any time, so browsers revalidate it on every load
+const REVALIDATE: &str = "no-cache";
+
+/// how many hex digits of the content hash go into the filename
+const HASH_LEN: usize = 16;
+
+/// the url each asset is served under, worked out once at startup
+static URLS: OnceLock<HashMap<&'static str, String>> = OnceLock::new();
+
+/// the url to link an asset with. assets that weren't hashed, like in development or before
+/// startup, keep their plain name
+pub fn asset_url(name: &str) -> String {
+    URLS.get()
+        .and_then(|urls| urls.get(name))
+        .cloned()
+        .unwrap_or_else(|| format!("/{name}"))
+}
+
+/// `monkesto.css` becomes `monkesto.<hash>.css`
+fn hashed_name(name: &str, contents: &[u8]) -> String {
+    let hash = hex::encode(Sha256::digest(contents));
+    let hash = &hash[..HASH_LEN];
+
+    match name.rsplit_once('.') {
+        Some((stem, extension)) => format!("{stem}.{hash}.{extension}"),
+        None => format!("{name}.{hash}"),
+    }
+}
+
+/// serves a file along with the `.gz` and `.br` copies the build leaves beside it, picking
+/// whichever the browser accepts
+fn serve(path: &Path, cache_control: &'static str) -> SetResponseHeader<ServeFile, HeaderValue> {
+    SetResponseHeader::overriding(
+        ServeFile::new(path).precompressed_br().precompressed_gzip(),
+        CACHE_CONTROL,
+        HeaderValue::from_static(cache_control),
+    )
+}
+
+/// routes the assets under `/assets/<hashed name>`, and under their plain name for anything
+/// still linking to it. development skips the hashing, since the stylesheet is rebuilt while the
+/// server runs
+pub fn router(site_root: &str, app_env: AppEnv) -> Router<StateType> {
+    let mut router = Router::new();
+    let mut urls = HashMap::new();
+
+    for &name in ASSETS {
+        let path = PathBuf::from(format!("{site_root}/pkg/{name}"));
+        router = router.route_service(&format!("/{name}"), serve(&path, REVALIDATE));
+
+        if app_env.dev_mode() {
+            continue;
+        }
+
+        match fs::read(&path) {
+            Ok(contents) => {
+                let url = format!("/assets/{}", hashed_name(name, &contents));
+                router = router.route_service(&url, serve(&path, IMMUTABLE));
+                urls.insert(name, url);
+            }
+            Err(error) => {
+                tracing::warn!(?error, ?path, "couldn't hash an asset, serving it unhashed");
+            }
+        }
+    }
+
+    _ = URLS.set(urls);
+
+    router
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn hashed_names_change_with_the_contents() {
+        let name = hashed_name("monkesto.css", b"body { color: red }");
+
+        assert!(name.starts_with("monkesto."));
+        assert!(name.ends_with(".css"));
+        assert_eq!(name.len(), "monkesto..css".len() + HASH_LEN);
+        assert_eq!(name, hashed_name("monkesto.css", b"body { color: red }"));
+        assert_ne!(name, hashed_name("monkesto.css", b"body { color: blue }"));
+
+        assert_eq!(asset_url("unknown.js"), "/unknown.js");
+    }
+}
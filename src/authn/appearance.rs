@@ -0,0 +1,162 @@
+use super::user::{User, UserError};
+use super::{AuthSession, AuthnEvent, AuthnService, UserId};
+use crate::authority::{Actor, Authority};
+use crate::monkesto_error::{ErrorRedirect, OrRedirect};
+use crate::time_provider::{TimeProvider, Timestamp};
+use axum::extract::{Extension, Form};
+use axum::response::Redirect;
+use disintegrate::Decision;
+use serde::{Deserialize, Serialize};
+use sqlx::error::BoxDynError;
+use sqlx::{Database, Postgres};
+use std::fmt::Display;
+use std::str::FromStr;
+
+/// whether pages are shown light or dark
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Theme {
+    /// follows the operating system's setting
+    #[default]
+    System,
+    Light,
+    Dark,
+}
+
+impl Theme {
+    pub const ALL: [Self; 3] = [Self::System, Self::Light, Self::Dark];
+
+    /// the class put on the html element, or none to let the operating system decide
+    pub fn class(self) -> Option<&'static str> {
+        match self {
+            Self::System => None,
+            Self::Light => Some("light"),
+            Self::Dark => Some("dark"),
+        }
+    }
+}
+
+impl Display for Theme {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::System => write!(f, "system"),
+            Self::Light => write!(f, "light"),
+            Self::Dark => write!(f, "dark"),
+        }
+    }
+}
+
+impl FromStr for Theme {
+    type Err = UserError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::ALL
+            .into_iter()
+            .find(|theme| theme.to_string() == s)
+            .ok_or_else(|| UserError::InvalidTheme(s.to_string()))
+    }
+}
+
+impl sqlx::Type<Postgres> for Theme {
+    fn type_info() -> <Postgres as Database>::TypeInfo {
+        <&str as sqlx::Type<Postgres>>::type_info()
+    }
+}
+
+impl<'r> sqlx::Decode<'r, Postgres> for Theme {
+    fn decode(value: <Postgres as Database>::ValueRef<'r>) -> Result<Self, BoxDynError> {
+        Ok(<&str as sqlx::Decode<Postgres>>::decode(value)?.parse()?)
+    }
+}
+
+pub struct SetTheme {
+    user_id: UserId,
+    theme: Theme,
+    authority: Authority,
+    timestamp: Timestamp,
+}
+
+impl SetTheme {
+    pub fn new(user_id: UserId, theme: Theme, authority: Authority, timestamp: Timestamp) -> Self {
+        Self {
+            user_id,
+            theme,
+            authority,
+            timestamp,
+        }
+    }
+}
+
+impl Decision for SetTheme {
+    type Event = AuthnEvent;
+    type StateQuery = User;
+    type Error = UserError;
+
+    fn state_query(&self) -> Self::StateQuery {
+        User::new(self.user_id)
+    }
+
+    fn process(&self, user: &Self::StateQuery) -> Result<Vec<Self::Event>, Self::Error> {
+        if !user.status.valid() {
+            return Err(UserError::UserDoesntExist(self.user_id));
+        }
+
+        Ok(vec![AuthnEvent::ThemeChanged {
+            user_id: self.user_id,
+            theme: self.theme,
+            authority: self.authority.clone(),
+            timestamp: self.timestamp,
+        }])
+    }
+}
+
+#[derive(Deserialize)]
+pub struct ThemeForm {
+    theme: String,
+}
+
+pub async fn theme_post(
+    Extension(authn_service): Extension<AuthnService>,
+    auth_session: AuthSession,
+    Form(form): Form<ThemeForm>,
+) -> Result<Redirect, ErrorRedirect> {
+    const CALLBACK_URL: &str = "/me";
+
+    let user_id = auth_session
+        .user
+        .map(|user| user.id)
+        .ok_or(UserError::SessionNotFound)
+        .or_redirect(CALLBACK_URL)?;
+
+    let theme = Theme::from_str(&form.theme).or_redirect(CALLBACK_URL)?;
+
+    let event_id = authn_service
+        .set_theme(
+            user_id,
+            theme,
+            Authority::Direct(Actor::User(user_id)),
+            authn_service.clock().get_time(),
+        )
+        .await
+        .or_redirect(CALLBACK_URL)?;
+
+    authn_service.wait_for(event_id).await;
+
+    Ok(Redirect::to(CALLBACK_URL))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn themes_round_trip_through_their_names() {
+        for theme in Theme::ALL {
+            assert_eq!(theme.to_string().parse(), Ok(theme));
+        }
+
+        assert_eq!(
+            Theme::from_str("sepia"),
+            Err(UserError::InvalidTheme("sepia".to_string()))
+        );
+    }
+}
@@ -0,0 +1,454 @@
+use super::signin::SigninError;
+use super::user::{User, UserError};
+use super::{AuthnEvent, AuthnService, UserId};
+use crate::authority::{Actor, Authority};
+use crate::time_provider::{TimeProvider, Timestamp};
+use axum::extract::{ConnectInfo, FromRequestParts};
+use axum::http::header::USER_AGENT;
+use axum::http::request::Parts;
+use chrono::TimeDelta;
+use disintegrate::Decision;
+use serde::{Deserialize, Serialize};
+use sqlx::error::BoxDynError;
+use sqlx::{Database, Postgres};
+use std::convert::Infallible;
+use std::env;
+use std::fmt::Display;
+use std::net::{IpAddr, SocketAddr};
+use std::time::Duration;
+use thiserror::Error;
+
+/// user agents are client controlled, so only this much of one is kept
+const MAX_USER_AGENT_LEN: usize = 256;
+
+#[derive(Debug, Error)]
+pub enum LockoutConfigError {
+    #[error("SIGNIN_MAX_FAILURES must be a whole number, found {0}")]
+    MaxFailures(String),
+
+    #[error("SIGNIN_LOCKOUT_SECS must be a whole number of seconds, found {0}")]
+    Duration(String),
+}
+
+/// how many failed signins in a row lock an account, and for how long.
+/// a limit of zero turns the lockout off
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct LockoutConfig {
+    pub max_failures: u32,
+    pub duration: Duration,
+}
+
+impl Default for LockoutConfig {
+    fn default() -> Self {
+        Self {
+            max_failures: 5,
+            duration: Duration::from_secs(15 * 60),
+        }
+    }
+}
+
+impl LockoutConfig {
+    pub fn from_env() -> Result<Self, LockoutConfigError> {
+        let default = Self::default();
+
+        let max_failures = match env::var("SIGNIN_MAX_FAILURES") {
+            Ok(value) => value
+                .parse::<u32>()
+                .map_err(|_| LockoutConfigError::MaxFailures(value))?,
+            Err(_) => default.max_failures,
+        };
+
+        let duration = match env::var("SIGNIN_LOCKOUT_SECS") {
+            Ok(value) => Duration::from_secs(
+                value
+                    .parse::<u64>()
+                    .map_err(|_| LockoutConfigError::Duration(value))?,
+            ),
+            Err(_) => default.duration,
+        };
+
+        Ok(Self {
+            max_failures,
+            duration,
+        })
+    }
+
+    /// where the user's run of failures stands after another attempt. attempts while the
+    /// account is locked don't count, and only failures after the last lock expired count
+    /// toward the next one, so a single failure can't keep relocking the account
+    pub fn record(&self, lockout: Lockout, succeeded: bool, attempted_at: Timestamp) -> Lockout {
+        if self.max_failures == 0 || lockout.locked_at(attempted_at) {
+            return lockout;
+        }
+
+        if succeeded {
+            return Lockout::default();
+        }
+
+        let failures = lockout.failures + 1;
+        if failures < self.max_failures {
+            return Lockout {
+                failures,
+                ..lockout
+            };
+        }
+
+        Lockout {
+            failures: 0,
+            locked_until: TimeDelta::from_std(self.duration)
+                .ok()
+                .map(|duration| attempted_at + duration),
+        }
+    }
+
+    /// when the account unlocks, if it's locked at `now`
+    pub fn locked_until(&self, lockout: Lockout, now: Timestamp) -> Option<Timestamp> {
+        if self.max_failures == 0 {
+            return None;
+        }
+
+        lockout.locked_until.filter(|until| *until > now)
+    }
+}
+
+/// the failed signins since the user last signed in or their last lock expired, and the lock
+/// they led to
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Lockout {
+    pub failures: u32,
+    pub locked_until: Option<Timestamp>,
+}
+
+impl Lockout {
+    fn locked_at(&self, at: Timestamp) -> bool {
+        self.locked_until.is_some_and(|until| until > at)
+    }
+}
+
+/// how the user tried to sign in
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum SigninMethod {
+    Passkey,
+    SingleSignOn,
+    TwoFactor,
+}
+
+impl Display for SigninMethod {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Passkey => write!(f, "passkey"),
+            Self::SingleSignOn => write!(f, "sso"),
+            Self::TwoFactor => write!(f, "two_factor"),
+        }
+    }
+}
+
+impl sqlx::Type<Postgres> for SigninMethod {
+    fn type_info() -> <Postgres as Database>::TypeInfo {
+        <&str as sqlx::Type<Postgres>>::type_info()
+    }
+}
+
+impl<'r> sqlx::Decode<'r, Postgres> for SigninMethod {
+    fn decode(value: <Postgres as Database>::ValueRef<'r>) -> Result<Self, BoxDynError> {
+        match <&str as sqlx::Decode<Postgres>>::decode(value)? {
+            "passkey" => Ok(Self::Passkey),
+            "sso" => Ok(Self::SingleSignOn),
+            "two_factor" => Ok(Self::TwoFactor),
+            other => Err(format!("{other} isn't a signin method").into()),
+        }
+    }
+}
+
+#[derive(Debug, Error)]
+#[error("TRUSTED_PROXIES must be a whole number, found {0}")]
+pub struct TrustedProxiesError(String);
+
+/// how many proxies in front of the server append the address they saw to X-Forwarded-For.
+/// anything further left in the header came from the client, so it can't be trusted
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct TrustedProxies(pub usize);
+
+impl TrustedProxies {
+    pub fn from_env() -> Result<Self, TrustedProxiesError> {
+        match env::var("TRUSTED_PROXIES") {
+            Ok(value) => value
+                .parse::<usize>()
+                .map(Self)
+                .map_err(|_| TrustedProxiesError(value)),
+            Err(_) => Ok(Self::default()),
+        }
+    }
+
+    /// the address the outermost trusted proxy saw the request come from. without trusted
+    /// proxies, or when the header has fewer hops than there are proxies, that's the peer
+    pub fn client_ip(&self, forwarded: Option<&str>, peer: Option<IpAddr>) -> Option<String> {
+        let hops: Vec<&str> = forwarded
+            .map(|value| value.split(',').map(str::trim).collect())
+            .unwrap_or_default();
+
+        let client = self
+            .0
+            .checked_sub(1)
+            .and_then(|skipped| hops.len().checked_sub(skipped + 1))
+            .map(|index| hops[index])
+            .filter(|ip| !ip.is_empty());
+
+        client
+            .map(str::to_string)
+            .or_else(|| peer.map(|ip| ip.to_string()))
+    }
+}
+
+/// where a request came from, as far as the trusted proxies in front of the server can tell
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ClientInfo {
+    pub ip: Option<String>,
+    pub user_agent: Option<String>,
+}
+
+impl<S: Send + Sync> FromRequestParts<S> for ClientInfo {
+    type Rejection = Infallible;
+
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+        let trusted = parts
+            .extensions
+            .get::<TrustedProxies>()
+            .copied()
+            .unwrap_or_default();
+
+        let forwarded = parts
+            .headers
+            .get("x-forwarded-for")
+            .and_then(|value| value.to_str().ok());
+
+        let peer = parts
+            .extensions
+            .get::<ConnectInfo<SocketAddr>>()
+            .map(|ConnectInfo(addr)| addr.ip());
+
+        let ip = trusted.client_ip(forwarded, peer);
+
+        let user_agent = parts
+            .headers
+            .get(USER_AGENT)
+            .and_then(|value| value.to_str().ok())
+            .map(|value| value.chars().take(MAX_USER_AGENT_LEN).collect());
+
+        Ok(Self { ip, user_agent })
+    }
+}
+
+/// a line of the user's signin history
+#[derive(Clone, Debug)]
+pub struct SigninAttempt {
+    pub method: SigninMethod,
+    pub succeeded: bool,
+    pub ip: Option<String>,
+    pub user_agent: Option<String>,
+    pub attempted_at: Timestamp,
+}
+
+pub struct RecordSigninAttempt {
+    user_id: UserId,
+    method: SigninMethod,
+    succeeded: bool,
+    client: ClientInfo,
+    timestamp: Timestamp,
+}
+
+impl RecordSigninAttempt {
+    pub fn new(
+        user_id: UserId,
+        method: SigninMethod,
+        succeeded: bool,
+        client: ClientInfo,
+        timestamp: Timestamp,
+    ) -> Self {
+        Self {
+            user_id,
+            method,
+            succeeded,
+            client,
+            timestamp,
+        }
+    }
+}
+
+impl Decision for RecordSigninAttempt {
+    type Event = AuthnEvent;
+    type StateQuery = User;
+    type Error = UserError;
+
+    fn state_query(&self) -> Self::StateQuery {
+        User::new(self.user_id)
+    }
+
+    fn process(&self, user: &Self::StateQuery) -> Result<Vec<Self::Event>, Self::Error> {
+        if !user.status.valid() {
+            return Err(UserError::UserDoesntExist(self.user_id));
+        }
+
+        Ok(vec![AuthnEvent::SigninAttempted {
+            user_id: self.user_id,
+            method: self.method,
+            succeeded: self.succeeded,
+            ip: self.client.ip.clone(),
+            user_agent: self.client.user_agent.clone(),
+            // nobody is signed in yet, so the attempt is the client's
+            authority: Authority::Direct(Actor::Anonymous),
+            timestamp: self.timestamp,
+        }])
+    }
+}
+
+/// refuses to sign the user in while their account is locked
+pub async fn ensure_unlocked(
+    authn_service: &AuthnService,
+    user_id: UserId,
+) -> Result<(), SigninError> {
+    match authn_service
+        .locked_until(user_id, authn_service.clock().get_time())
+        .await
+        .map_err(|e| SigninError::StoreError(e.to_string()))?
+    {
+        Some(_) => Err(SigninError::AccountLocked),
+        None => Ok(()),
+    }
+}
+
+/// records the attempt, so failures count toward a lockout and the user can see it on `/me`
+pub async fn record(
+    authn_service: &AuthnService,
+    user_id: UserId,
+    method: SigninMethod,
+    succeeded: bool,
+    client: &ClientInfo,
+) -> Result<(), SigninError> {
+    let event_id = authn_service
+        .record_signin_attempt(
+            user_id,
+            method,
+            succeeded,
+            client.clone(),
+            authn_service.clock().get_time(),
+        )
+        .await
+        .map_err(|e| SigninError::StoreError(e.to_string()))?;
+
+    authn_service.wait_for(event_id).await;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{TimeZone, Utc};
+
+    #[test]
+    fn enough_failures_in_a_row_lock_the_account_for_a_while() {
+        let config = LockoutConfig {
+            max_failures: 3,
+            duration: Duration::from_secs(15 * 60),
+        };
+        let start = Utc
+            .with_ymd_and_hms(2026, 10, 17, 12, 0, 0)
+            .single()
+            .expect("invalid time");
+        let minutes = |n| start + TimeDelta::minutes(n);
+        let replay = |config: &LockoutConfig, attempts: &[(bool, Timestamp)]| {
+            attempts
+                .iter()
+                .fold(Lockout::default(), |lockout, (succeeded, at)| {
+                    config.record(lockout, *succeeded, *at)
+                })
+        };
+
+        // a success breaks up the failures
+        let broken_up = replay(
+            &config,
+            &[
+                (false, start),
+                (true, minutes(1)),
+                (false, minutes(2)),
+                (false, minutes(3)),
+            ],
+        );
+        assert_eq!(config.locked_until(broken_up, minutes(3)), None);
+
+        let in_a_row = [
+            (false, minutes(1)),
+            (false, minutes(2)),
+            (false, minutes(3)),
+        ];
+        let locked = replay(&config, &in_a_row);
+        assert_eq!(config.locked_until(locked, minutes(4)), Some(minutes(18)));
+        assert_eq!(config.locked_until(locked, minutes(18)), None);
+
+        let off = LockoutConfig {
+            max_failures: 0,
+            ..config
+        };
+        assert_eq!(off.locked_until(replay(&off, &in_a_row), minutes(4)), None);
+    }
+
+    #[test]
+    fn a_failure_after_the_lock_expires_starts_a_new_count() {
+        let config = LockoutConfig {
+            max_failures: 3,
+            duration: Duration::from_secs(15 * 60),
+        };
+        let start = Utc
+            .with_ymd_and_hms(2026, 10, 17, 12, 0, 0)
+            .single()
+            .expect("invalid time");
+        let minutes = |n| start + TimeDelta::minutes(n);
+
+        let mut lockout = Lockout::default();
+        for n in 0..3 {
+            lockout = config.record(lockout, false, minutes(n));
+        }
+        assert_eq!(config.locked_until(lockout, minutes(3)), Some(minutes(17)));
+
+        // failures while locked don't count, and a single one once it expires doesn't relock
+        lockout = config.record(lockout, false, minutes(10));
+        lockout = config.record(lockout, false, minutes(20));
+        assert_eq!(config.locked_until(lockout, minutes(20)), None);
+
+        lockout = config.record(lockout, false, minutes(21));
+        assert_eq!(config.locked_until(lockout, minutes(21)), None);
+
+        lockout = config.record(lockout, false, minutes(22));
+        assert_eq!(config.locked_until(lockout, minutes(22)), Some(minutes(37)));
+    }
+
+    #[test]
+    fn only_hops_added_by_trusted_proxies_are_believed() {
+        let peer = Some(IpAddr::from([10, 0, 0, 2]));
+        let spoofed = Some("6.6.6.6, 203.0.113.7, 10.0.0.1");
+
+        assert_eq!(
+            TrustedProxies(0).client_ip(spoofed, peer),
+            Some("10.0.0.2".to_string())
+        );
+        assert_eq!(
+            TrustedProxies(1).client_ip(spoofed, peer),
+            Some("10.0.0.1".to_string())
+        );
+        assert_eq!(
+            TrustedProxies(2).client_ip(spoofed, peer),
+            Some("203.0.113.7".to_string())
+        );
+
+        // the request went around the proxies
+        assert_eq!(
+            TrustedProxies(2).client_ip(Some("6.6.6.6"), peer),
+            Some("10.0.0.2".to_string())
+        );
+        assert_eq!(
+            TrustedProxies(1).client_ip(None, peer),
+            Some("10.0.0.2".to_string())
+        );
+    }
+}
@@ -0,0 +1,156 @@
+use super::user::{User, UserError};
+use super::{AuthSession, AuthnEvent, AuthnService, UserId};
+use crate::authority::{Actor, Authority};
+use crate::monkesto_error::{ErrorRedirect, OrRedirect};
+use crate::time_provider::{TimeProvider, Timestamp};
+use axum::extract::{Extension, Form};
+use axum::response::Redirect;
+use chrono::TimeDelta;
+use disintegrate::Decision;
+use serde::{Deserialize, Serialize};
+use std::fmt::Display;
+use std::str::FromStr;
+
+/// how often a user is emailed a summary of activity in their journals
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum DigestFrequency {
+    #[default]
+    Never,
+    Daily,
+    Weekly,
+}
+
+impl DigestFrequency {
+    pub const ALL: [Self; 3] = [Self::Never, Self::Daily, Self::Weekly];
+
+    /// the time between digests, or none if the user doesn't want them
+    pub fn period(self) -> Option<TimeDelta> {
+        match self {
+            Self::Never => None,
+            Self::Daily => Some(TimeDelta::days(1)),
+            Self::Weekly => Some(TimeDelta::weeks(1)),
+        }
+    }
+}
+
+impl Display for DigestFrequency {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Never => write!(f, "never"),
+            Self::Daily => write!(f, "daily"),
+            Self::Weekly => write!(f, "weekly"),
+        }
+    }
+}
+
+impl FromStr for DigestFrequency {
+    type Err = UserError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::ALL
+            .into_iter()
+            .find(|frequency| frequency.to_string() == s)
+            .ok_or_else(|| UserError::InvalidDigestFrequency(s.to_string()))
+    }
+}
+
+pub struct SetDigestFrequency {
+    user_id: UserId,
+    frequency: DigestFrequency,
+    authority: Authority,
+    timestamp: Timestamp,
+}
+
+impl SetDigestFrequency {
+    pub fn new(
+        user_id: UserId,
+        frequency: DigestFrequency,
+        authority: Authority,
+        timestamp: Timestamp,
+    ) -> Self {
+        Self {
+            user_id,
+            frequency,
+            authority,
+            timestamp,
+        }
+    }
+}
+
+impl Decision for SetDigestFrequency {
+    type Event = AuthnEvent;
+    type StateQuery = User;
+    type Error = UserError;
+
+    fn state_query(&self) -> Self::StateQuery {
+        User::new(self.user_id)
+    }
+
+    fn process(&self, user: &Self::StateQuery) -> Result<Vec<Self::Event>, Self::Error> {
+        if !user.status.valid() {
+            return Err(UserError::UserDoesntExist(self.user_id));
+        }
+
+        Ok(vec![AuthnEvent::DigestFrequencyChanged {
+            user_id: self.user_id,
+            frequency: self.frequency,
+            authority: self.authority.clone(),
+            timestamp: self.timestamp,
+        }])
+    }
+}
+
+#[derive(Deserialize)]
+pub struct DigestForm {
+    frequency: String,
+}
+
+pub async fn digest_post(
+    Extension(authn_service): Extension<AuthnService>,
+    auth_session: AuthSession,
+    Form(form): Form<DigestForm>,
+) -> Result<Redirect, ErrorRedirect> {
+    const CALLBACK_URL: &str = "/me";
+
+    let user_id = auth_session
+        .user
+        .map(|user| user.id)
+        .ok_or(UserError::SessionNotFound)
+        .or_redirect(CALLBACK_URL)?;
+
+    let frequency = DigestFrequency::from_str(&form.frequency).or_redirect(CALLBACK_URL)?;
+
+    let event_id = authn_service
+        .set_digest_frequency(
+            user_id,
+            frequency,
+            Authority::Direct(Actor::User(user_id)),
+            authn_service.clock().get_time(),
+        )
+        .await
+        .or_redirect(CALLBACK_URL)?;
+
+    authn_service.wait_for(event_id).await;
+
+    Ok(Redirect::to(CALLBACK_URL))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn frequencies_round_trip_through_their_names() {
+        for frequency in DigestFrequency::ALL {
+            assert_eq!(
+                DigestFrequency::from_str(&frequency.to_string()),
+                Ok(frequency)
+            );
+        }
+
+        assert_eq!(
+            DigestFrequency::from_str("hourly"),
+            Err(UserError::InvalidDigestFrequency("hourly".to_string()))
+        );
+    }
+}
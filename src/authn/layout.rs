@@ -1,5 +1,8 @@
+use crate::i18n::t;
+use crate::shortcuts::shortcut_layer;
 use crate::theme::theme_with_head;
 use maud::Markup;
+use maud::PreEscaped;
 use maud::html;
 
 pub fn layout(nav_title: Option<Markup>, content: Markup) -> Markup {
@@ -24,16 +27,27 @@ pub fn layout(nav_title: Option<Markup>, content: Markup) -> Markup {
                                 @if let Some(title_markup) = nav_title {
                                     (title_markup)
                                 }
+                                a
+                                    href="/notifications"
+                                    aria-label=(t("nav.notifications"))
+                                    class="relative text-gray-500 hover:text-gray-700 dark:text-gray-400 dark:hover:text-gray-200 p-1" {
+                                    svg class="h-5 w-5" fill="none" viewBox="0 0 24 24" stroke-width="1.5" stroke="currentColor" {
+                                        path stroke-linecap="round" stroke-linejoin="round" d="M14.857 17.082a23.848 23.848 0 0 0 5.454-1.31A8.967 8.967 0 0 1 18 9.75V9A6 6 0 0 0 6 9v.75a8.967 8.967 0 0 1-2.312 6.022c1.733.64 3.56 1.085 5.455 1.31m5.714 0a24.255 24.255 0 0 1-5.714 0m5.714 0a3 3 0 1 1-5.714 0" {}
+                                    }
+                                    span
+                                        id="notification-count"
+                                        class="hidden absolute -top-1 -right-1 min-w-4 h-4 px-1 rounded-full bg-red-600 text-[10px] font-semibold leading-4 text-center text-white" {}
+                                }
                                 a
                                     href="/me"
                                     class="text-xs text-gray-500 hover:text-gray-700 dark:text-gray-400 dark:hover:text-gray-200 px-2 py-1" {
-                                    "Profile"
+                                    (t("nav.profile"))
                                 }
                                 form action="/signout" method="post" {
                                     button
                                         class="text-xs text-gray-500 hover:text-gray-700 dark:text-gray-400 dark:hover:text-gray-200 px-2 py-1"
                                         type="submit" {
-                                        "Sign out"
+                                        (t("nav.sign_out"))
                                     }
                                 }
                             }
@@ -41,6 +55,24 @@ pub fn layout(nav_title: Option<Markup>, content: Markup) -> Markup {
                     }
                 }
 
+                // the page is rendered without the count, so the badge fills itself in
+                script {
+                    (PreEscaped(r#"
+                        fetch('/notifications/unread', { headers: { 'Accept': 'application/json' } })
+                            .then((res) => res.ok ? res.json() : null)
+                            .then((data) => {
+                                if (data && data.unread > 0) {
+                                    const badge = document.getElementById('notification-count');
+                                    badge.textContent = data.unread > 99 ? '99+' : data.unread;
+                                    badge.classList.remove('hidden');
+                                }
+                            })
+                            .catch(() => {});
+                    "#))
+                }
+
+                (shortcut_layer())
+
                 // Main Content
                 div class="flex-1 p-6" {
                     div class="max-w-7xl mx-auto" {
@@ -1,16 +1,46 @@
 use axum::extract::Extension;
+use axum::extract::Query;
+use axum::extract::State;
 use axum::http::StatusCode;
 use axum::http::header;
 use axum::response::IntoResponse;
 use maud::Markup;
 use maud::html;
 
+use super::appearance::Theme;
+use super::attempt::{SigninAttempt, SigninMethod};
+use super::digest::DigestFrequency;
 use super::layout::layout;
 use super::passkey::PasskeyState;
+use super::profile::avatar;
+use super::shortcuts::ShortcutAction;
+use super::timezone::Timezone;
+use super::user::UserState;
 use super::{AuthSession, AuthnService};
+use crate::StateType;
+use crate::i18n::t;
+use crate::journal::quota::QuotaUsage;
+use crate::monkesto_error::{MonkestoError, UrlError};
 use crate::theme::theme_with_head;
 
-fn me_page(email: &str, passkeys: &[PasskeyState]) -> Markup {
+/// how many signin attempts are shown on the profile
+const RECENT_SIGNINS: i64 = 10;
+
+fn format_limit(limit: Option<u64>) -> String {
+    limit.map_or("unlimited".to_string(), |limit| limit.to_string())
+}
+
+fn me_page(
+    passkeys: &[PasskeyState],
+    totp_enabled: bool,
+    digest_frequency: DigestFrequency,
+    user: &UserState,
+    usage: Option<&QuotaUsage>,
+    attempts: &[SigninAttempt],
+    err: Option<String>,
+) -> Markup {
+    let shortcuts = user.shortcuts();
+
     let content = html! {
         div class="flex flex-col gap-6 sm:mx-auto sm:w-full sm:max-w-sm" {
         div class="bg-white dark:bg-gray-800 rounded-lg shadow p-6 space-y-4" {
@@ -19,7 +49,62 @@ fn me_page(email: &str, passkeys: &[PasskeyState]) -> Markup {
                     "Your Account"
                 }
                 p class="text-sm text-gray-600 dark:text-gray-400" {
-                    (email)
+                    (user.email)
+                }
+            }
+
+            div {
+                h4 class="text-md font-medium text-gray-900 dark:text-white mb-3" {
+                    "Display Name"
+                }
+
+                p class="text-sm text-gray-500 dark:text-gray-400 mb-3" {
+                    "What people in your shared journals see you as. Leave it blank to show your email."
+                }
+                form method="POST" action="me/display-name" class="flex gap-2" {
+                    input
+                        type="text"
+                        name="display_name"
+                        value=(user.name.as_ref().map(ToString::to_string).unwrap_or_default())
+                        maxlength="64"
+                        placeholder=(user.email)
+                        class="block w-full rounded-md bg-white px-3 py-1.5 text-sm text-gray-900 outline-1 -outline-offset-1 outline-gray-300 dark:bg-white/5 dark:text-white dark:outline-white/10";
+                    button
+                        type="submit"
+                        class="text-xs px-2 py-1 bg-indigo-600 text-white rounded hover:bg-indigo-500 focus:outline-none focus:ring-2 focus:ring-indigo-500 focus:ring-offset-1" {
+                        "Save"
+                    }
+                }
+            }
+
+            div {
+                h4 class="text-md font-medium text-gray-900 dark:text-white mb-3" {
+                    "Avatar"
+                }
+
+                p class="text-sm text-gray-500 dark:text-gray-400 mb-3" {
+                    "Shown next to your name. Without a Gravatar, it's your initial. "
+                    a
+                        href="https://gravatar.com"
+                        class="font-semibold text-indigo-600 hover:text-indigo-500 dark:text-indigo-400 dark:hover:text-indigo-300" {
+                        "Change your Gravatar"
+                    }
+                }
+                form method="POST" action="me/avatar" class="flex items-center gap-3" {
+                    (avatar(user))
+                    label class="flex flex-1 items-center gap-2 text-sm text-gray-700 dark:text-gray-300" {
+                        input
+                            type="checkbox"
+                            name="gravatar"
+                            checked[user.gravatar]
+                            class="h-4 w-4 rounded border-gray-300 text-indigo-600 focus:ring-indigo-600 dark:border-gray-700 dark:bg-gray-900";
+                        "Use my Gravatar"
+                    }
+                    button
+                        type="submit"
+                        class="text-xs px-2 py-1 bg-indigo-600 text-white rounded hover:bg-indigo-500 focus:outline-none focus:ring-2 focus:ring-indigo-500 focus:ring-offset-1" {
+                        "Save"
+                    }
                 }
             }
 
@@ -62,6 +147,255 @@ fn me_page(email: &str, passkeys: &[PasskeyState]) -> Markup {
                 }
             }
 
+            div {
+                h4 class="text-md font-medium text-gray-900 dark:text-white mb-3" {
+                    "Two-factor Authentication"
+                }
+
+                @if totp_enabled {
+                    p class="text-sm text-gray-600 dark:text-gray-400 mb-3" {
+                        "Enabled. Enter a code from your authenticator app or a recovery code to turn it off."
+                    }
+                    form method="POST" action="totp/disable" class="flex gap-2" {
+                        input
+                            type="text"
+                            name="code"
+                            required
+                            autocomplete="one-time-code"
+                            class="block w-full rounded-md bg-white px-3 py-1.5 text-sm text-gray-900 outline-1 -outline-offset-1 outline-gray-300 dark:bg-white/5 dark:text-white dark:outline-white/10";
+                        button
+                            type="submit"
+                            class="text-xs px-2 py-1 bg-red-600 text-white rounded hover:bg-red-500 focus:outline-none focus:ring-2 focus:ring-red-500 focus:ring-offset-1" {
+                            "Disable"
+                        }
+                    }
+                } @else {
+                    p class="text-sm text-gray-500 dark:text-gray-400 mb-3" {
+                        "Require a code from an authenticator app when signing in with single sign-on."
+                    }
+                    a
+                        href="totp/setup"
+                        class="text-sm font-semibold text-indigo-600 hover:text-indigo-500 dark:text-indigo-400 dark:hover:text-indigo-300" {
+                        "Set up an authenticator app"
+                    }
+                }
+            }
+
+            div {
+                h4 class="text-md font-medium text-gray-900 dark:text-white mb-3" {
+                    "Digest Emails"
+                }
+
+                p class="text-sm text-gray-500 dark:text-gray-400 mb-3" {
+                    "A summary of new transactions and balance changes in your journals."
+                }
+                form method="POST" action="me/digest" class="flex gap-2" {
+                    select
+                        name="frequency"
+                        class="block w-full rounded-md bg-white px-3 py-1.5 text-sm text-gray-900 outline-1 -outline-offset-1 outline-gray-300 dark:bg-white/5 dark:text-white dark:outline-white/10" {
+                        @for frequency in DigestFrequency::ALL {
+                            option value=(frequency) selected[frequency == digest_frequency] {
+                                (match frequency {
+                                    DigestFrequency::Never => "Never",
+                                    DigestFrequency::Daily => "Daily",
+                                    DigestFrequency::Weekly => "Weekly",
+                                })
+                            }
+                        }
+                    }
+                    button
+                        type="submit"
+                        class="text-xs px-2 py-1 bg-indigo-600 text-white rounded hover:bg-indigo-500 focus:outline-none focus:ring-2 focus:ring-indigo-500 focus:ring-offset-1" {
+                        "Save"
+                    }
+                }
+            }
+
+            div {
+                h4 class="text-md font-medium text-gray-900 dark:text-white mb-3" {
+                    "Timezone"
+                }
+
+                p class="text-sm text-gray-500 dark:text-gray-400 mb-3" {
+                    "Times are shown in this timezone, and it decides which day counts as today."
+                }
+                form method="POST" action="me/timezone" class="flex gap-2" {
+                    select
+                        name="timezone"
+                        class="block w-full rounded-md bg-white px-3 py-1.5 text-sm text-gray-900 outline-1 -outline-offset-1 outline-gray-300 dark:bg-white/5 dark:text-white dark:outline-white/10" {
+                        @for zone in Timezone::all() {
+                            option value=(zone) selected[zone == user.timezone()] {
+                                (zone)
+                            }
+                        }
+                    }
+                    button
+                        type="submit"
+                        class="text-xs px-2 py-1 bg-indigo-600 text-white rounded hover:bg-indigo-500 focus:outline-none focus:ring-2 focus:ring-indigo-500 focus:ring-offset-1" {
+                        "Save"
+                    }
+                }
+            }
+
+            div {
+                h4 class="text-md font-medium text-gray-900 dark:text-white mb-3" {
+                    "Theme"
+                }
+
+                p class="text-sm text-gray-500 dark:text-gray-400 mb-3" {
+                    "Show pages light or dark, or follow your device's setting."
+                }
+                form method="POST" action="me/theme" class="flex gap-2" {
+                    select
+                        name="theme"
+                        class="block w-full rounded-md bg-white px-3 py-1.5 text-sm text-gray-900 outline-1 -outline-offset-1 outline-gray-300 dark:bg-white/5 dark:text-white dark:outline-white/10" {
+                        @for choice in Theme::ALL {
+                            option value=(choice) selected[choice == user.theme()] {
+                                (match choice {
+                                    Theme::System => "System",
+                                    Theme::Light => "Light",
+                                    Theme::Dark => "Dark",
+                                })
+                            }
+                        }
+                    }
+                    button
+                        type="submit"
+                        class="text-xs px-2 py-1 bg-indigo-600 text-white rounded hover:bg-indigo-500 focus:outline-none focus:ring-2 focus:ring-indigo-500 focus:ring-offset-1" {
+                        "Save"
+                    }
+                }
+            }
+
+            div id="shortcuts" {
+                h4 class="text-md font-medium text-gray-900 dark:text-white mb-3" {
+                    "Keyboard Shortcuts"
+                }
+
+                p class="text-sm text-gray-500 dark:text-gray-400 mb-3" {
+                    "A shortcut is one or two keys pressed in turn, like "
+                    kbd { "g j" }
+                    ". Leave one empty to turn it off."
+                }
+                form method="POST" action="me/shortcuts" class="space-y-2" {
+                    label class="flex items-center gap-2 text-sm text-gray-700 dark:text-gray-300" {
+                        input type="checkbox" name="enabled" value="on" checked[shortcuts.enabled];
+                        "Use keyboard shortcuts"
+                    }
+                    @for action in ShortcutAction::ALL {
+                        label class="flex items-center justify-between gap-4 text-sm text-gray-700 dark:text-gray-300" {
+                            (t(action.label()))
+                            input
+                                type="text"
+                                name=(action)
+                                value=(shortcuts.picked(action))
+                                placeholder=(action.default_keys())
+                                maxlength="3"
+                                class="w-20 rounded-md bg-white px-2 py-1 font-mono text-sm text-gray-900 outline-1 -outline-offset-1 outline-gray-300 dark:bg-white/5 dark:text-white dark:outline-white/10";
+                        }
+                    }
+                    button
+                        type="submit"
+                        class="text-xs px-2 py-1 bg-indigo-600 text-white rounded hover:bg-indigo-500 focus:outline-none focus:ring-2 focus:ring-indigo-500 focus:ring-offset-1" {
+                        "Save"
+                    }
+                }
+            }
+
+            div {
+                h4 class="text-md font-medium text-gray-900 dark:text-white mb-3" {
+                    "Recent Sign-ins"
+                }
+
+                @if attempts.is_empty() {
+                    p class="text-sm text-gray-500 dark:text-gray-400" {
+                        "No sign-ins recorded yet"
+                    }
+                } @else {
+                    p class="text-sm text-gray-500 dark:text-gray-400 mb-3" {
+                        "If you don't recognize one of these, delete your passkeys and add new ones."
+                    }
+                    ul class="space-y-2" {
+                        @for attempt in attempts {
+                            li class="border border-gray-200 dark:border-gray-600 rounded p-3 text-xs space-y-1" {
+                                div class="flex justify-between gap-2" {
+                                    @if attempt.succeeded {
+                                        span class="font-medium text-green-700 dark:text-green-400" { "Succeeded" }
+                                    } @else {
+                                        span class="font-medium text-red-600 dark:text-red-400" { "Failed" }
+                                    }
+                                    span class="text-gray-500 dark:text-gray-400" {
+                                        (user.timezone().format(attempt.attempted_at))
+                                    }
+                                }
+                                p class="text-gray-700 dark:text-gray-300" {
+                                    (match attempt.method {
+                                        SigninMethod::Passkey => "Passkey",
+                                        SigninMethod::SingleSignOn => "Single sign-on",
+                                        SigninMethod::TwoFactor => "Authenticator code",
+                                    })
+                                    " from " (attempt.ip.as_deref().unwrap_or("an unknown address"))
+                                }
+                                @if let Some(user_agent) = &attempt.user_agent {
+                                    p class="text-gray-500 dark:text-gray-400 break-all" { (user_agent) }
+                                }
+                            }
+                        }
+                    }
+                }
+            }
+
+            div {
+                h4 class="text-md font-medium text-gray-900 dark:text-white mb-3" {
+                    "Your Data"
+                }
+
+                p class="text-sm text-gray-500 dark:text-gray-400 mb-3" {
+                    "Download everything you own, or delete your account and erase your details."
+                }
+                div class="flex gap-4" {
+                    a
+                        href="me/export"
+                        class="text-sm font-semibold text-indigo-600 hover:text-indigo-500 dark:text-indigo-400 dark:hover:text-indigo-300" {
+                        "Download your data"
+                    }
+                    a
+                        href="me/delete-account"
+                        class="text-sm font-semibold text-red-600 hover:text-red-500 dark:text-red-400 dark:hover:text-red-300" {
+                        "Delete account"
+                    }
+                }
+            }
+
+            @if let Some(e) = err {
+                p class="text-sm text-red-500" {
+                    (MonkestoError::decode(&e).to_string())
+                }
+            }
+
+            @if let Some(usage) = usage {
+                div {
+                    h4 class="text-md font-medium text-gray-900 dark:text-white mb-3" {
+                        "Usage"
+                    }
+
+                    dl class="space-y-1 text-sm" {
+                        div class="flex justify-between" {
+                            dt class="text-gray-600 dark:text-gray-400" { "Journals" }
+                            dd class="text-gray-900 dark:text-white" {
+                                (usage.journals) " of " (format_limit(usage.quotas.max_journals))
+                            }
+                        }
+                        div class="flex justify-between" {
+                            dt class="text-gray-600 dark:text-gray-400" { "Transactions per journal" }
+                            dd class="text-gray-900 dark:text-white" {
+                                (format_limit(usage.quotas.max_transactions_per_journal))
+                            }
+                        }
+                    }
+                }
+            }
+
             // Add new passkey button (below all passkeys)
             div class="mt-4 pt-4 border-t border-gray-200 dark:border-gray-600" {
                 form method="POST" action="passkey" {
@@ -116,12 +450,14 @@ fn not_logged_in_page() -> Markup {
 }
 
 pub async fn me_get(
+    State(state): State<StateType>,
     Extension(authn_service): Extension<AuthnService>,
     auth_session: AuthSession,
+    Query(err): Query<UrlError>,
 ) -> impl IntoResponse {
     // Check if user is logged in
-    let user_id = match auth_session.user {
-        Some(ref user) => user.id,
+    let user = match auth_session.user {
+        Some(user) => user,
         None => {
             // Not logged in
             return (
@@ -132,20 +468,40 @@ pub async fn me_get(
         }
     };
 
+    let user_id = user.id;
+
     // Get user passkeys
     let passkeys = authn_service
         .get_user_passkeys(user_id)
         .await
         .unwrap_or_default();
 
-    // Get the email for this user
-    let email = authn_service
-        .fetch_user(user_id)
+    let totp_enabled = authn_service
+        .totp_secret(user_id)
+        .await
+        .is_ok_and(|secret| secret.is_some());
+
+    let digest_frequency = authn_service
+        .digest_frequency(user_id)
+        .await
+        .unwrap_or_default();
+
+    let usage = state.journal_service.quota_usage(user_id).await.ok();
+
+    let attempts = authn_service
+        .signin_attempts(user_id, RECENT_SIGNINS)
         .await
-        .map(|usr| usr.email.to_string())
-        .unwrap_or_else(|_| "unknown@example.com".to_string());
+        .unwrap_or_default();
 
-    let markup = me_page(&email, &passkeys);
+    let markup = me_page(
+        &passkeys,
+        totp_enabled,
+        digest_frequency,
+        &user,
+        usage.as_ref(),
+        &attempts,
+        err.err,
+    );
     (
         StatusCode::OK,
         [(header::CONTENT_TYPE, "text/html")],
@@ -1,28 +1,49 @@
+pub mod appearance;
+pub mod attempt;
 mod corepasskey;
+pub mod digest;
 mod layout;
 mod me;
+pub mod oidc;
 pub mod passkey;
+mod privacy;
+pub mod profile;
+pub mod shortcuts;
 mod signin;
 mod signout;
 mod signup;
 mod store;
+pub mod timezone;
+mod totp;
 pub mod user;
 
-use crate::id::Ident;
+use crate::id::{IdGenerator, Ident};
 
+use crate::app_env::AppEnv;
+use crate::authn::appearance::{SetTheme, Theme};
+use crate::authn::attempt::{
+    ClientInfo, Lockout, LockoutConfig, RecordSigninAttempt, SigninAttempt, SigninMethod,
+};
 use crate::authn::corepasskey::CorePasskey;
+use crate::authn::digest::{DigestFrequency, SetDigestFrequency};
+use crate::authn::oidc::Oidc;
 use crate::authn::passkey::{CreatePasskey, DeletePasskey, PasskeyError, PasskeyState};
-use crate::authn::user::{CreateUser, DEV_USERS, UserError, UserResult, UserState};
+use crate::authn::profile::{SetDisplayName, SetGravatar};
+use crate::authn::shortcuts::{SetShortcuts, Shortcuts};
+use crate::authn::timezone::{SetTimezone, Timezone};
+use crate::authn::totp::{DisableTotp, EnableTotp, UseRecoveryCode};
+use crate::authn::user::{CreateUser, DEV_USERS, DeleteUser, UserError, UserResult, UserState};
 use crate::authority::Authority;
 use crate::email::Email;
 use crate::event_id::GetEventId;
-use crate::monkesto_error::OrRedirect;
-use crate::time_provider::Timestamp;
+use crate::monkesto_error::{ErrorRedirect, OrRedirect};
+use crate::name::Name;
+use crate::secret::{SecretError, SecretStore};
+use crate::time_provider::{Clock, Timestamp};
 use crate::{id, shutdown};
 use async_trait::async_trait;
 use axum::Router;
 use axum::extract::Extension;
-use axum::response::Redirect;
 use axum::routing::get;
 use axum::routing::post;
 use axum_login::{AuthnBackend, login_required, tracing};
@@ -74,8 +95,24 @@ id!(PasskeyId, Ident::new16());
 type PgAuthnDecisionMaker = PgDecisionMaker<AuthnEvent, MessagePack<AuthnEvent>, WithPgSnapshot>;
 
 #[derive(Debug, Clone, PartialEq, Event, Serialize, Deserialize)]
-#[stream(UserEvent, [UserCreated, UserDeleted])]
+#[stream(
+    UserEvent,
+    [
+        UserCreated,
+        UserDeleted,
+        TotpEnabled,
+        TotpDisabled,
+        RecoveryCodeUsed,
+        DigestFrequencyChanged,
+        TimezoneChanged,
+        ThemeChanged,
+        ShortcutsChanged,
+        DisplayNameChanged,
+        AvatarChanged
+    ]
+)]
 #[stream(PasskeyEvent, [PasskeyCreated, PasskeyDeleted])]
+#[stream(AuthAttemptEvent, [SigninAttempted])]
 pub enum AuthnEvent {
     UserCreated {
         #[id]
@@ -92,6 +129,71 @@ pub enum AuthnEvent {
         authority: Authority,
         timestamp: Timestamp,
     },
+    TotpEnabled {
+        #[id]
+        user_id: UserId,
+        /// hashes of the recovery codes, which are only shown to the user once
+        recovery_codes: Vec<String>,
+        authority: Authority,
+        timestamp: Timestamp,
+    },
+    TotpDisabled {
+        #[id]
+        user_id: UserId,
+        authority: Authority,
+        timestamp: Timestamp,
+    },
+    RecoveryCodeUsed {
+        #[id]
+        user_id: UserId,
+        recovery_code: String,
+        authority: Authority,
+        timestamp: Timestamp,
+    },
+    DigestFrequencyChanged {
+        #[id]
+        user_id: UserId,
+        frequency: DigestFrequency,
+        authority: Authority,
+        timestamp: Timestamp,
+    },
+    TimezoneChanged {
+        #[id]
+        user_id: UserId,
+        timezone: Timezone,
+        authority: Authority,
+        timestamp: Timestamp,
+    },
+    ThemeChanged {
+        #[id]
+        user_id: UserId,
+        theme: Theme,
+        authority: Authority,
+        timestamp: Timestamp,
+    },
+    ShortcutsChanged {
+        #[id]
+        user_id: UserId,
+        shortcuts: Shortcuts,
+        authority: Authority,
+        timestamp: Timestamp,
+    },
+    /// none goes back to showing the user's email
+    DisplayNameChanged {
+        #[id]
+        user_id: UserId,
+        display_name: Option<Name>,
+        authority: Authority,
+        timestamp: Timestamp,
+    },
+    /// whether the user's avatar is their gravatar, rather than their initial
+    AvatarChanged {
+        #[id]
+        user_id: UserId,
+        gravatar: bool,
+        authority: Authority,
+        timestamp: Timestamp,
+    },
     PasskeyCreated {
         #[id]
         passkey_id: PasskeyId,
@@ -106,6 +208,38 @@ pub enum AuthnEvent {
         authority: Authority,
         timestamp: Timestamp,
     },
+    SigninAttempted {
+        #[id]
+        user_id: UserId,
+        method: SigninMethod,
+        succeeded: bool,
+        ip: Option<String>,
+        user_agent: Option<String>,
+        authority: Authority,
+        timestamp: Timestamp,
+    },
+}
+
+impl AuthnEvent {
+    /// when this event was recorded
+    pub fn timestamp(&self) -> Timestamp {
+        match self {
+            Self::UserCreated { timestamp, .. }
+            | Self::UserDeleted { timestamp, .. }
+            | Self::TotpEnabled { timestamp, .. }
+            | Self::TotpDisabled { timestamp, .. }
+            | Self::RecoveryCodeUsed { timestamp, .. }
+            | Self::DigestFrequencyChanged { timestamp, .. }
+            | Self::TimezoneChanged { timestamp, .. }
+            | Self::ThemeChanged { timestamp, .. }
+            | Self::ShortcutsChanged { timestamp, .. }
+            | Self::DisplayNameChanged { timestamp, .. }
+            | Self::AvatarChanged { timestamp, .. }
+            | Self::PasskeyCreated { timestamp, .. }
+            | Self::PasskeyDeleted { timestamp, .. }
+            | Self::SigninAttempted { timestamp, .. } => *timestamp,
+        }
+    }
 }
 
 #[derive(Clone)]
@@ -114,6 +248,10 @@ pub struct AuthnService {
     projection_pool: PgPool,
     decision_maker: PgAuthnDecisionMaker,
     current_event: watch::Sender<PgEventId>,
+    lockout: LockoutConfig,
+    clock: Clock,
+    ids: IdGenerator,
+    secrets: SecretStore,
 }
 
 impl PgHasArrayType for UserId {
@@ -126,6 +264,10 @@ impl AuthnService {
     pub async fn try_new(
         pool: PgPool,
         event_store: &AuthnEventStore,
+        lockout: LockoutConfig,
+        clock: Clock,
+        ids: IdGenerator,
+        secrets: SecretStore,
     ) -> Result<Self, AuthConnectError> {
         sqlx::query!(
             r#"
@@ -139,6 +281,82 @@ impl AuthnService {
         .execute(&pool)
         .await?;
 
+        sqlx::query!(
+            r#"
+            CREATE TABLE IF NOT EXISTS user_totp (
+                user_id TEXT PRIMARY KEY
+            )
+        "#
+        )
+        .execute(&pool)
+        .await?;
+
+        sqlx::query!(
+            r#"
+            CREATE TABLE IF NOT EXISTS digest_preferences (
+                user_id TEXT PRIMARY KEY,
+                frequency TEXT NOT NULL
+            )
+        "#
+        )
+        .execute(&pool)
+        .await?;
+
+        sqlx::query!(
+            r#"
+            CREATE TABLE IF NOT EXISTS timezone_preferences (
+                user_id TEXT PRIMARY KEY,
+                timezone TEXT NOT NULL
+            )
+        "#
+        )
+        .execute(&pool)
+        .await?;
+
+        sqlx::query!(
+            r#"
+            CREATE TABLE IF NOT EXISTS theme_preferences (
+                user_id TEXT PRIMARY KEY,
+                theme TEXT NOT NULL
+            )
+        "#
+        )
+        .execute(&pool)
+        .await?;
+
+        sqlx::query!(
+            r#"
+            CREATE TABLE IF NOT EXISTS shortcut_preferences (
+                user_id TEXT PRIMARY KEY,
+                shortcuts TEXT NOT NULL
+            )
+        "#
+        )
+        .execute(&pool)
+        .await?;
+
+        sqlx::query!(
+            r#"
+            CREATE TABLE IF NOT EXISTS display_names (
+                user_id TEXT PRIMARY KEY,
+                display_name TEXT NOT NULL
+            )
+        "#
+        )
+        .execute(&pool)
+        .await?;
+
+        sqlx::query!(
+            r#"
+            CREATE TABLE IF NOT EXISTS avatar_preferences (
+                user_id TEXT PRIMARY KEY,
+                gravatar BOOLEAN NOT NULL
+            )
+        "#
+        )
+        .execute(&pool)
+        .await?;
+
         sqlx::query!(
             r#"
             CREATE TABLE IF NOT EXISTS passkeys (
@@ -152,6 +370,34 @@ impl AuthnService {
         .execute(&pool)
         .await?;
 
+        sqlx::query!(
+            r#"
+            CREATE TABLE IF NOT EXISTS signin_attempts (
+                id BIGSERIAL PRIMARY KEY,
+                user_id TEXT NOT NULL,
+                method TEXT NOT NULL,
+                succeeded BOOLEAN NOT NULL,
+                ip TEXT,
+                user_agent TEXT,
+                attempted_at TIMESTAMPTZ NOT NULL
+            )
+        "#
+        )
+        .execute(&pool)
+        .await?;
+
+        sqlx::query!(
+            r#"
+            CREATE TABLE IF NOT EXISTS signin_lockouts (
+                user_id TEXT PRIMARY KEY,
+                failures BIGINT NOT NULL,
+                locked_until TIMESTAMPTZ
+            )
+        "#
+        )
+        .execute(&pool)
+        .await?;
+
         let snapshotter = PgSnapshotter::try_new(pool.clone(), 10)
             .await
             .map_err(|error| AuthConnectError::Disintegrate(error.to_string()))?;
@@ -169,9 +415,21 @@ impl AuthnService {
             projection_pool: pool,
             decision_maker,
             current_event: sender,
+            lockout,
+            clock,
+            ids,
+            secrets,
         })
     }
 
+    pub fn clock(&self) -> &Clock {
+        &self.clock
+    }
+
+    pub fn ids(&self) -> &IdGenerator {
+        &self.ids
+    }
+
     pub async fn create_user(
         &self,
         user_id: UserId,
@@ -193,6 +451,182 @@ impl AuthnService {
             .event_id())
     }
 
+    pub async fn delete_user(
+        &self,
+        user_id: UserId,
+        authority: Authority,
+        timestamp: Timestamp,
+    ) -> Result<PgEventId, DecisionError<UserError>> {
+        Ok(self
+            .decision_maker
+            .make(DeleteUser::new(user_id, authority, timestamp))
+            .await?
+            .event_id())
+    }
+
+    /// every event about the user or their passkeys, oldest first
+    pub async fn user_events(&self, user_id: UserId) -> UserResult<Vec<AuthnEvent>> {
+        // passkey events only name the user in their payload
+        let id = user_id.to_string();
+        let payloads = sqlx::query_scalar!(
+            r#"
+            SELECT payload as "payload!"
+            FROM event
+            WHERE user_id = $1
+                OR passkey_id IN (
+                    SELECT passkey_id FROM event
+                    WHERE event_type = 'PasskeyCreated' AND position($2 IN payload) > 0
+                )
+            ORDER BY event_id
+        "#,
+            user_id as UserId,
+            id.as_bytes()
+        )
+        .fetch_all(&self.projection_pool)
+        .await?;
+
+        payloads
+            .iter()
+            .map(|payload| {
+                rmp_serde::from_slice(payload).map_err(|e| UserError::EventDecode(e.to_string()))
+            })
+            .collect()
+    }
+
+    /// permanently removes the user's events, projections and snapshots, so nothing is left that
+    /// identifies them. the user should be deleted first, so no decision is still using them
+    pub async fn erase_user(&self, user_id: UserId) -> UserResult<()> {
+        let id = user_id.to_string();
+        let mut tx = self.projection_pool.begin().await?;
+
+        // the projection keeps user ids as bytes, so the passkeys are found by their own ids,
+        // which are only recorded in their events
+        sqlx::query!(
+            r#"
+            DELETE FROM passkeys
+            WHERE id IN (
+                SELECT passkey_id FROM event
+                WHERE event_type = 'PasskeyCreated' AND position($1 IN payload) > 0
+            )
+        "#,
+            id.as_bytes()
+        )
+        .execute(&mut *tx)
+        .await?;
+
+        sqlx::query!(
+            r#"
+            DELETE FROM event
+            WHERE user_id = $1
+                OR passkey_id IN (
+                    SELECT passkey_id FROM event
+                    WHERE event_type = 'PasskeyCreated' AND position($2 IN payload) > 0
+                )
+        "#,
+            user_id as UserId,
+            id.as_bytes()
+        )
+        .execute(&mut *tx)
+        .await?;
+
+        // snapshots of the user's state hold their email alongside their id
+        sqlx::query!(
+            r#"
+            DELETE FROM snapshot WHERE position($1 IN payload) > 0
+        "#,
+            id
+        )
+        .execute(&mut *tx)
+        .await?;
+
+        sqlx::query!(
+            r#"
+            DELETE FROM user_totp WHERE user_id = $1
+        "#,
+            user_id as UserId
+        )
+        .execute(&mut *tx)
+        .await?;
+
+        sqlx::query!(
+            r#"
+            DELETE FROM digest_preferences WHERE user_id = $1
+        "#,
+            user_id as UserId
+        )
+        .execute(&mut *tx)
+        .await?;
+
+        sqlx::query!(
+            r#"
+            DELETE FROM timezone_preferences WHERE user_id = $1
+        "#,
+            user_id as UserId
+        )
+        .execute(&mut *tx)
+        .await?;
+
+        sqlx::query!(
+            r#"
+            DELETE FROM theme_preferences WHERE user_id = $1
+        "#,
+            user_id as UserId
+        )
+        .execute(&mut *tx)
+        .await?;
+
+        sqlx::query!(
+            r#"
+            DELETE FROM shortcut_preferences WHERE user_id = $1
+        "#,
+            user_id as UserId
+        )
+        .execute(&mut *tx)
+        .await?;
+
+        sqlx::query!(
+            r#"
+            DELETE FROM display_names WHERE user_id = $1
+        "#,
+            user_id as UserId
+        )
+        .execute(&mut *tx)
+        .await?;
+
+        sqlx::query!(
+            r#"
+            DELETE FROM avatar_preferences WHERE user_id = $1
+        "#,
+            user_id as UserId
+        )
+        .execute(&mut *tx)
+        .await?;
+
+        sqlx::query!(
+            r#"
+            DELETE FROM signin_attempts WHERE user_id = $1
+        "#,
+            user_id as UserId
+        )
+        .execute(&mut *tx)
+        .await?;
+
+        sqlx::query!(
+            r#"
+            DELETE FROM signin_lockouts WHERE user_id = $1
+        "#,
+            user_id as UserId
+        )
+        .execute(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+
+        self.secrets.remove(&totp::totp_seed(user_id)).await?;
+
+        Ok(())
+    }
+
     pub async fn create_passkey(
         &self,
         passkey_id: PasskeyId,
@@ -242,7 +676,16 @@ impl AuthnService {
         let user = sqlx::query_as!(
             UserState,
             r#"
-            SELECT id as "id: UserId", email as "email: Email", webauthn_uuid FROM users WHERE id = $1
+            SELECT u.id as "id: UserId", u.email as "email: Email", u.webauthn_uuid, tp.timezone as "timezone_preference?: Timezone",
+                th.theme as "theme_preference?: Theme", sp.shortcuts as "shortcuts_preference?: Shortcuts",
+                dn.display_name as "name?: Name", COALESCE(av.gravatar, FALSE) as "gravatar!"
+            FROM users u
+            LEFT JOIN timezone_preferences tp ON tp.user_id = u.id
+            LEFT JOIN theme_preferences th ON th.user_id = u.id
+            LEFT JOIN shortcut_preferences sp ON sp.user_id = u.id
+            LEFT JOIN display_names dn ON dn.user_id = u.id
+            LEFT JOIN avatar_preferences av ON av.user_id = u.id
+            WHERE u.id = $1
         "#,
             user_id as UserId
         )
@@ -260,7 +703,16 @@ impl AuthnService {
         let users = sqlx::query_as!(
             UserState,
             r#"
-            SELECT id as "id: UserId", email as "email: Email", webauthn_uuid FROM users WHERE id = ANY($1)
+            SELECT u.id as "id: UserId", u.email as "email: Email", u.webauthn_uuid, tp.timezone as "timezone_preference?: Timezone",
+                th.theme as "theme_preference?: Theme", sp.shortcuts as "shortcuts_preference?: Shortcuts",
+                dn.display_name as "name?: Name", COALESCE(av.gravatar, FALSE) as "gravatar!"
+            FROM users u
+            LEFT JOIN timezone_preferences tp ON tp.user_id = u.id
+            LEFT JOIN theme_preferences th ON th.user_id = u.id
+            LEFT JOIN shortcut_preferences sp ON sp.user_id = u.id
+            LEFT JOIN display_names dn ON dn.user_id = u.id
+            LEFT JOIN avatar_preferences av ON av.user_id = u.id
+            WHERE u.id = ANY($1)
         "#,
             ids as &[UserId]
         )
@@ -270,6 +722,12 @@ impl AuthnService {
         Ok(users)
     }
 
+    /// what the user is called on pages other people see, which is their email until they
+    /// choose a name
+    pub async fn get_display_name(&self, user_id: UserId) -> UserResult<String> {
+        Ok(self.fetch_user(user_id).await?.display_name())
+    }
+
     pub async fn lookup_user_id(&self, email: &Email) -> UserResult<UserId> {
         let id = sqlx::query_scalar!(
             r#"
@@ -343,16 +801,310 @@ impl AuthnService {
             .map(|pk| (pk.user_id, pk.id)))
     }
 
-    pub async fn wait_for(&self, event_id: PgEventId) {
-        self.current_event
-            .subscribe()
-            .wait_for(|curr_id| *curr_id >= event_id)
+    pub async fn enable_totp(
+        &self,
+        user_id: UserId,
+        secret: String,
+        recovery_codes: Vec<String>,
+        authority: Authority,
+        timestamp: Timestamp,
+    ) -> Result<PgEventId, DecisionError<UserError>> {
+        let seed = totp::totp_seed(user_id);
+
+        // the secret is stored before the event, so it's there once the projection sees it.
+        // if totp was already enabled, the decision fails and the secret in use is put back
+        let previous = self
+            .secrets
+            .get(&seed)
             .await
-            .expect("auth service eventid sender closed");
-    }
-}
+            .map_err(|e| DecisionError::Domain(e.into()))?;
 
-impl AuthnBackend for AuthnService {
+        self.secrets
+            .set(&seed, &secret)
+            .await
+            .map_err(|e| DecisionError::Domain(e.into()))?;
+
+        let result = self
+            .decision_maker
+            .make(EnableTotp::new(
+                user_id,
+                recovery_codes,
+                authority,
+                timestamp,
+            ))
+            .await;
+
+        if result.is_err() {
+            match previous {
+                Some(previous) => self.secrets.set(&seed, &previous).await,
+                None => self.secrets.remove(&seed).await.map_err(SecretError::from),
+            }
+            .map_err(|e| DecisionError::Domain(e.into()))?;
+        }
+
+        Ok(result?.event_id())
+    }
+
+    pub async fn disable_totp(
+        &self,
+        user_id: UserId,
+        authority: Authority,
+        timestamp: Timestamp,
+    ) -> Result<PgEventId, DecisionError<UserError>> {
+        Ok(self
+            .decision_maker
+            .make(DisableTotp::new(user_id, authority, timestamp))
+            .await?
+            .event_id())
+    }
+
+    /// consumes one of the user's recovery codes, failing if it doesn't exist or was already used
+    pub async fn use_recovery_code(
+        &self,
+        user_id: UserId,
+        recovery_code: &str,
+        authority: Authority,
+        timestamp: Timestamp,
+    ) -> Result<PgEventId, DecisionError<UserError>> {
+        Ok(self
+            .decision_maker
+            .make(UseRecoveryCode::new(
+                user_id,
+                totp::hash_recovery_code(recovery_code),
+                authority,
+                timestamp,
+            ))
+            .await?
+            .event_id())
+    }
+
+    /// returns the user's TOTP secret if they've enabled a second factor
+    pub async fn totp_secret(&self, user_id: UserId) -> UserResult<Option<String>> {
+        let enabled = sqlx::query_scalar!(
+            r#"
+            SELECT EXISTS(SELECT 1 FROM user_totp WHERE user_id = $1) as "enabled!"
+        "#,
+            user_id as UserId
+        )
+        .fetch_one(&self.projection_pool)
+        .await?;
+
+        if !enabled {
+            return Ok(None);
+        }
+
+        // a missing secret mustn't read as totp being off, or the second factor is skipped
+        match self.secrets.get(&totp::totp_seed(user_id)).await? {
+            Some(secret) => Ok(Some(secret)),
+            None => Err(UserError::Secret(format!(
+                "no totp secret is stored for {user_id}"
+            ))),
+        }
+    }
+
+    pub async fn set_digest_frequency(
+        &self,
+        user_id: UserId,
+        frequency: DigestFrequency,
+        authority: Authority,
+        timestamp: Timestamp,
+    ) -> Result<PgEventId, DecisionError<UserError>> {
+        Ok(self
+            .decision_maker
+            .make(SetDigestFrequency::new(
+                user_id, frequency, authority, timestamp,
+            ))
+            .await?
+            .event_id())
+    }
+
+    pub async fn digest_frequency(&self, user_id: UserId) -> UserResult<DigestFrequency> {
+        sqlx::query_scalar!(
+            r#"
+            SELECT frequency FROM digest_preferences WHERE user_id = $1
+        "#,
+            user_id as UserId
+        )
+        .fetch_optional(&self.projection_pool)
+        .await?
+        .map_or(Ok(DigestFrequency::default()), |frequency| {
+            frequency.parse()
+        })
+    }
+
+    pub async fn set_timezone(
+        &self,
+        user_id: UserId,
+        timezone: Timezone,
+        authority: Authority,
+        timestamp: Timestamp,
+    ) -> Result<PgEventId, DecisionError<UserError>> {
+        Ok(self
+            .decision_maker
+            .make(SetTimezone::new(user_id, timezone, authority, timestamp))
+            .await?
+            .event_id())
+    }
+
+    pub async fn set_theme(
+        &self,
+        user_id: UserId,
+        theme: Theme,
+        authority: Authority,
+        timestamp: Timestamp,
+    ) -> Result<PgEventId, DecisionError<UserError>> {
+        Ok(self
+            .decision_maker
+            .make(SetTheme::new(user_id, theme, authority, timestamp))
+            .await?
+            .event_id())
+    }
+
+    pub async fn set_shortcuts(
+        &self,
+        user_id: UserId,
+        shortcuts: Shortcuts,
+        authority: Authority,
+        timestamp: Timestamp,
+    ) -> Result<PgEventId, DecisionError<UserError>> {
+        Ok(self
+            .decision_maker
+            .make(SetShortcuts::new(user_id, shortcuts, authority, timestamp))
+            .await?
+            .event_id())
+    }
+
+    pub async fn set_display_name(
+        &self,
+        user_id: UserId,
+        display_name: Option<Name>,
+        authority: Authority,
+        timestamp: Timestamp,
+    ) -> Result<PgEventId, DecisionError<UserError>> {
+        Ok(self
+            .decision_maker
+            .make(SetDisplayName::new(
+                user_id,
+                display_name,
+                authority,
+                timestamp,
+            ))
+            .await?
+            .event_id())
+    }
+
+    pub async fn set_gravatar(
+        &self,
+        user_id: UserId,
+        gravatar: bool,
+        authority: Authority,
+        timestamp: Timestamp,
+    ) -> Result<PgEventId, DecisionError<UserError>> {
+        Ok(self
+            .decision_maker
+            .make(SetGravatar::new(user_id, gravatar, authority, timestamp))
+            .await?
+            .event_id())
+    }
+
+    pub async fn record_signin_attempt(
+        &self,
+        user_id: UserId,
+        method: SigninMethod,
+        succeeded: bool,
+        client: ClientInfo,
+        timestamp: Timestamp,
+    ) -> Result<PgEventId, DecisionError<UserError>> {
+        Ok(self
+            .decision_maker
+            .make(RecordSigninAttempt::new(
+                user_id, method, succeeded, client, timestamp,
+            ))
+            .await?
+            .event_id())
+    }
+
+    /// the user's latest signin attempts, newest first
+    pub async fn signin_attempts(
+        &self,
+        user_id: UserId,
+        limit: i64,
+    ) -> UserResult<Vec<SigninAttempt>> {
+        Ok(sqlx::query_as!(
+            SigninAttempt,
+            r#"
+            SELECT method as "method: SigninMethod", succeeded, ip, user_agent, attempted_at as "attempted_at: Timestamp"
+            FROM signin_attempts
+            WHERE user_id = $1
+            ORDER BY id DESC
+            LIMIT $2
+        "#,
+            user_id as UserId,
+            limit
+        )
+        .fetch_all(&self.projection_pool)
+        .await?)
+    }
+
+    /// where the user's run of failed signins stands, as of the last one projected
+    async fn lockout(&self, user_id: UserId) -> Result<Lockout, sqlx::Error> {
+        let row = sqlx::query!(
+            r#"
+            SELECT failures, locked_until as "locked_until: Timestamp"
+            FROM signin_lockouts
+            WHERE user_id = $1
+        "#,
+            user_id as UserId
+        )
+        .fetch_optional(&self.projection_pool)
+        .await?;
+
+        Ok(row.map_or_else(Lockout::default, |row| Lockout {
+            failures: u32::try_from(row.failures).unwrap_or(u32::MAX),
+            locked_until: row.locked_until,
+        }))
+    }
+
+    /// when the user's account unlocks, if too many signins have failed in a row
+    pub async fn locked_until(
+        &self,
+        user_id: UserId,
+        now: Timestamp,
+    ) -> UserResult<Option<Timestamp>> {
+        let lockout = self.lockout(user_id).await?;
+
+        Ok(self.lockout.locked_until(lockout, now))
+    }
+
+    /// returns every user who wants digest emails, along with how often they want them
+    pub async fn digest_subscribers(&self) -> UserResult<Vec<(UserId, Email, DigestFrequency)>> {
+        let rows = sqlx::query!(
+            r#"
+            SELECT u.id as "id: UserId", u.email as "email: Email", d.frequency
+            FROM digest_preferences d
+            INNER JOIN users u ON u.id = d.user_id
+            WHERE d.frequency <> $1
+        "#,
+            DigestFrequency::Never.to_string()
+        )
+        .fetch_all(&self.projection_pool)
+        .await?;
+
+        rows.into_iter()
+            .map(|row| Ok((row.id, row.email, row.frequency.parse()?)))
+            .collect()
+    }
+
+    pub async fn wait_for(&self, event_id: PgEventId) {
+        self.current_event
+            .subscribe()
+            .wait_for(|curr_id| *curr_id >= event_id)
+            .await
+            .expect("auth service eventid sender closed");
+    }
+}
+
+impl AuthnBackend for AuthnService {
     type User = UserState;
     type Credentials = ();
     type Error = UserError;
@@ -364,7 +1116,12 @@ impl AuthnBackend for AuthnService {
     }
 
     async fn get_user(&self, user_id: &axum_login::UserId<Self>) -> UserResult<Option<UserState>> {
-        Ok(Some(self.fetch_user(*user_id).await?))
+        // sessions can outlive an erased account, and are then treated as signed out
+        match self.fetch_user(*user_id).await {
+            Ok(user) => Ok(Some(user)),
+            Err(UserError::UserDoesntExist(_)) => Ok(None),
+            Err(e) => Err(e),
+        }
     }
 }
 
@@ -413,6 +1170,144 @@ impl EventListener<PgEventId, AuthnEvent> for AuthnService {
                 .execute(&self.projection_pool)
                 .await?;
             }
+            AuthnEvent::TotpEnabled { user_id, .. } => {
+                sqlx::query!(
+                    r#"
+                    INSERT INTO user_totp (user_id) VALUES($1) ON CONFLICT DO NOTHING
+                "#,
+                    user_id as UserId
+                )
+                .execute(&self.projection_pool)
+                .await?;
+            }
+            AuthnEvent::TotpDisabled { user_id, .. } => {
+                sqlx::query!(
+                    r#"
+                    DELETE FROM user_totp WHERE user_id = $1
+                "#,
+                    user_id as UserId
+                )
+                .execute(&self.projection_pool)
+                .await?;
+
+                // a secret stored since belongs to totp being enabled again, so it's kept
+                let enabled_since = sqlx::query_scalar!(
+                    r#"
+                    SELECT EXISTS(
+                        SELECT 1 FROM event
+                        WHERE user_id = $1 AND event_type = 'TotpEnabled' AND event_id > $2
+                    ) as "enabled_since!"
+                "#,
+                    user_id as UserId,
+                    event_id
+                )
+                .fetch_one(&self.projection_pool)
+                .await?;
+
+                if !enabled_since {
+                    self.secrets.remove(&totp::totp_seed(user_id)).await?;
+                }
+            }
+            // recovery codes are only checked by decisions, so there's nothing to project
+            AuthnEvent::RecoveryCodeUsed { .. } => {}
+            AuthnEvent::DigestFrequencyChanged {
+                user_id, frequency, ..
+            } => {
+                sqlx::query!(
+                    r#"
+                    INSERT INTO digest_preferences (user_id, frequency) VALUES($1, $2)
+                    ON CONFLICT (user_id) DO UPDATE SET frequency = EXCLUDED.frequency
+                "#,
+                    user_id as UserId,
+                    frequency.to_string()
+                )
+                .execute(&self.projection_pool)
+                .await?;
+            }
+            AuthnEvent::TimezoneChanged {
+                user_id, timezone, ..
+            } => {
+                sqlx::query!(
+                    r#"
+                    INSERT INTO timezone_preferences (user_id, timezone) VALUES($1, $2)
+                    ON CONFLICT (user_id) DO UPDATE SET timezone = EXCLUDED.timezone
+                "#,
+                    user_id as UserId,
+                    timezone.to_string()
+                )
+                .execute(&self.projection_pool)
+                .await?;
+            }
+            AuthnEvent::ThemeChanged { user_id, theme, .. } => {
+                sqlx::query!(
+                    r#"
+                    INSERT INTO theme_preferences (user_id, theme) VALUES($1, $2)
+                    ON CONFLICT (user_id) DO UPDATE SET theme = EXCLUDED.theme
+                "#,
+                    user_id as UserId,
+                    theme.to_string()
+                )
+                .execute(&self.projection_pool)
+                .await?;
+            }
+            AuthnEvent::ShortcutsChanged {
+                user_id, shortcuts, ..
+            } => {
+                sqlx::query!(
+                    r#"
+                    INSERT INTO shortcut_preferences (user_id, shortcuts) VALUES($1, $2)
+                    ON CONFLICT (user_id) DO UPDATE SET shortcuts = EXCLUDED.shortcuts
+                "#,
+                    user_id as UserId,
+                    shortcuts as Shortcuts
+                )
+                .execute(&self.projection_pool)
+                .await?;
+            }
+            AuthnEvent::DisplayNameChanged {
+                user_id,
+                display_name: Some(display_name),
+                ..
+            } => {
+                sqlx::query!(
+                    r#"
+                    INSERT INTO display_names (user_id, display_name) VALUES($1, $2)
+                    ON CONFLICT (user_id) DO UPDATE SET display_name = EXCLUDED.display_name
+                "#,
+                    user_id as UserId,
+                    display_name as Name
+                )
+                .execute(&self.projection_pool)
+                .await?;
+            }
+            AuthnEvent::DisplayNameChanged {
+                user_id,
+                display_name: None,
+                ..
+            } => {
+                sqlx::query!(
+                    r#"
+                    DELETE FROM display_names WHERE user_id = $1
+                "#,
+                    user_id as UserId
+                )
+                .execute(&self.projection_pool)
+                .await?;
+            }
+            AuthnEvent::AvatarChanged {
+                user_id, gravatar, ..
+            } => {
+                sqlx::query!(
+                    r#"
+                    INSERT INTO avatar_preferences (user_id, gravatar) VALUES($1, $2)
+                    ON CONFLICT (user_id) DO UPDATE SET gravatar = EXCLUDED.gravatar
+                "#,
+                    user_id as UserId,
+                    gravatar
+                )
+                .execute(&self.projection_pool)
+                .await?;
+            }
             AuthnEvent::PasskeyCreated {
                 passkey_id,
                 user_id,
@@ -439,6 +1334,48 @@ impl EventListener<PgEventId, AuthnEvent> for AuthnService {
                 .execute(&self.projection_pool)
                 .await?;
             }
+            AuthnEvent::SigninAttempted {
+                user_id,
+                method,
+                succeeded,
+                ip,
+                user_agent,
+                timestamp,
+                ..
+            } => {
+                sqlx::query!(
+                    r#"
+                    INSERT INTO signin_attempts (user_id, method, succeeded, ip, user_agent, attempted_at)
+                    VALUES ($1, $2, $3, $4, $5, $6)
+                "#,
+                    user_id as UserId,
+                    method.to_string(),
+                    succeeded,
+                    ip,
+                    user_agent,
+                    timestamp as Timestamp
+                )
+                .execute(&self.projection_pool)
+                .await?;
+
+                let lockout =
+                    self.lockout
+                        .record(self.lockout(user_id).await?, succeeded, timestamp);
+
+                sqlx::query!(
+                    r#"
+                    INSERT INTO signin_lockouts (user_id, failures, locked_until)
+                    VALUES ($1, $2, $3)
+                    ON CONFLICT (user_id) DO UPDATE
+                    SET failures = EXCLUDED.failures, locked_until = EXCLUDED.locked_until
+                "#,
+                    user_id as UserId,
+                    i64::from(lockout.failures),
+                    lockout.locked_until as Option<Timestamp>
+                )
+                .execute(&self.projection_pool)
+                .await?;
+            }
         }
 
         self.current_event
@@ -463,7 +1400,7 @@ pub(crate) async fn event_listener(event_store: AuthnEventStore, service: AuthnS
         .expect("event listener failed");
 }
 
-pub fn get_user(session: AuthSession) -> Result<UserState, Redirect> {
+pub fn get_user(session: AuthSession) -> Result<UserState, ErrorRedirect> {
     session
         .user
         .ok_or(UserError::SessionNotFound)
@@ -478,16 +1415,23 @@ fn handle_event_listener_retry(
     RetryAction::Abort
 }
 
-pub fn router<S: Clone + Send + Sync + 'static>(
-    authn_service: AuthnService,
-) -> Result<Router<S>, AuthConfigError> {
+/// the public url of the site, used for WebAuthn and OIDC redirects
+pub fn base_url() -> String {
     // Get base URL from environment variable, defaulting to localhost:3000
-    let base_url = env::var("RAILWAY_PUBLIC_DOMAIN")
+    env::var("RAILWAY_PUBLIC_DOMAIN")
         .ok()
         .map(|f| format!("https://{}", f))
         .unwrap_or_else(|| {
             env::var("BASE_URL").unwrap_or_else(|_| "http://localhost:3000".to_string())
-        });
+        })
+}
+
+pub fn router(
+    authn_service: AuthnService,
+    app_env: AppEnv,
+    oidc: Option<Oidc>,
+) -> Result<Router<crate::StateType>, AuthConfigError> {
+    let base_url = base_url();
 
     let webauthn_url = format!("{}/", base_url);
 
@@ -507,17 +1451,41 @@ pub fn router<S: Clone + Send + Sync + 'static>(
         .route("/me", get(me::me_get))
         .route("/passkey", post(passkey::create_passkey_post))
         .route("/passkey/{id}/delete", post(passkey::delete_passkey_post))
+        .route("/totp/setup", get(totp::totp_setup_get))
+        .route("/totp/enable", post(totp::totp_enable_post))
+        .route("/totp/disable", post(totp::totp_disable_post))
+        .route("/me/digest", post(digest::digest_post))
+        .route("/me/timezone", post(timezone::timezone_post))
+        .route("/me/theme", post(appearance::theme_post))
+        .route("/me/shortcuts", post(shortcuts::shortcuts_post))
+        .route("/me/display-name", post(profile::display_name_post))
+        .route("/me/avatar", post(profile::avatar_post))
+        .route("/me/export", get(privacy::export_get))
+        .route(
+            "/me/delete-account",
+            get(privacy::delete_account_get).post(privacy::delete_account_post),
+        )
         .route("/signout", get(signout::signout_get))
         .route("/signout", post(signout::signout_post))
         .route_layer(login_required!(AuthnService, login_url = "/signin"));
 
     // Public routes (no login required)
-    let public_routes = Router::new()
-        .route("/signin", get(signin::signin_get).post(signin::signin_post))
-        .route("/signup", get(signup::signup_get).post(signup::signup_post));
+    let mut public_routes = signin::routes(app_env)
+        .route("/signup", get(signup::signup_get).post(signup::signup_post))
+        .route(
+            "/signin/totp",
+            get(totp::totp_signin_get).post(totp::totp_signin_post),
+        );
+
+    // single sign-on is only available when a provider is configured
+    if oidc.is_some() {
+        public_routes = public_routes.merge(oidc::routes());
+    }
 
     Ok(public_routes
         .merge(protected_routes)
+        .layer(Extension(oidc))
+        .layer(Extension(app_env))
         .layer(Extension(webauthn_url))
         .layer(Extension(webauthn))
         .layer(Extension(authn_service)))
@@ -0,0 +1,279 @@
+use super::attempt::{ClientInfo, SigninMethod};
+use super::signin::SigninError;
+use super::totp;
+use super::user::{UserError, UserState};
+use super::{AuthSession, AuthnService, UserId};
+use crate::authority::{Actor, Authority};
+use crate::email::Email;
+use crate::time_provider::TimeProvider;
+use axum::Router;
+use axum::extract::{Extension, Query};
+use axum::response::Redirect;
+use axum::routing::get;
+use axum_login::tracing;
+use openidconnect::core::{CoreAuthenticationFlow, CoreClient, CoreProviderMetadata};
+use openidconnect::{
+    AuthorizationCode, ClientId, ClientSecret, CsrfToken, EndpointMaybeSet, EndpointNotSet,
+    EndpointSet, IssuerUrl, Nonce, PkceCodeChallenge, PkceCodeVerifier, RedirectUrl, Scope,
+    TokenResponse, reqwest,
+};
+use serde::{Deserialize, Serialize};
+use std::env;
+use std::sync::Arc;
+use thiserror::Error;
+use webauthn_rs::prelude::Uuid;
+
+const PENDING_SIGNIN_KEY: &str = "oidc_pending_signin";
+
+type OidcClient = CoreClient<
+    EndpointSet,
+    EndpointNotSet,
+    EndpointNotSet,
+    EndpointNotSet,
+    EndpointMaybeSet,
+    EndpointMaybeSet,
+>;
+
+#[derive(Debug, Error)]
+pub enum OidcConfigError {
+    #[error("{0} must be set when OIDC_ISSUER_URL is set")]
+    Missing(&'static str),
+    #[error("invalid oidc url: {0}")]
+    Url(#[from] url::ParseError),
+    #[error("failed to build the oidc http client: {0}")]
+    HttpClient(#[from] reqwest::Error),
+    #[error("failed to discover the oidc provider: {0}")]
+    Discovery(String),
+}
+
+/// an OpenID Connect provider that users can sign in with instead of a passkey
+#[derive(Clone)]
+pub struct Oidc {
+    client: Arc<OidcClient>,
+    http_client: reqwest::Client,
+    pub provider_name: String,
+}
+
+impl Oidc {
+    /// discovers the provider configured in the environment.
+    /// returns `None` if `OIDC_ISSUER_URL` isn't set, leaving passkeys as the only way to sign in
+    pub async fn from_env(base_url: &str) -> Result<Option<Self>, OidcConfigError> {
+        let Ok(issuer_url) = env::var("OIDC_ISSUER_URL") else {
+            return Ok(None);
+        };
+
+        let client_id =
+            env::var("OIDC_CLIENT_ID").map_err(|_| OidcConfigError::Missing("OIDC_CLIENT_ID"))?;
+        let client_secret = env::var("OIDC_CLIENT_SECRET")
+            .map_err(|_| OidcConfigError::Missing("OIDC_CLIENT_SECRET"))?;
+        let provider_name = env::var("OIDC_PROVIDER_NAME").unwrap_or("SSO".to_string());
+
+        // following redirects would let the provider point requests at internal services
+        let http_client = reqwest::ClientBuilder::new()
+            .redirect(reqwest::redirect::Policy::none())
+            .build()?;
+
+        let metadata =
+            CoreProviderMetadata::discover_async(IssuerUrl::new(issuer_url)?, &http_client)
+                .await
+                .map_err(|e| OidcConfigError::Discovery(e.to_string()))?;
+
+        let client = CoreClient::from_provider_metadata(
+            metadata,
+            ClientId::new(client_id),
+            Some(ClientSecret::new(client_secret)),
+        )
+        .set_redirect_uri(RedirectUrl::new(format!(
+            "{}/signin/oidc/callback",
+            base_url
+        ))?);
+
+        Ok(Some(Self {
+            client: Arc::new(client),
+            http_client,
+            provider_name,
+        }))
+    }
+}
+
+/// the state of a signin that has been sent to the provider, kept in the session until it returns
+#[derive(Serialize, Deserialize)]
+struct PendingSignin {
+    csrf_token: String,
+    nonce: String,
+    pkce_verifier: String,
+    next: Option<String>,
+}
+
+pub fn routes<S: Clone + Send + Sync + 'static>() -> Router<S> {
+    Router::new()
+        .route("/signin/oidc", get(oidc_signin_get))
+        .route("/signin/oidc/callback", get(oidc_callback_get))
+}
+
+#[derive(Deserialize)]
+pub struct OidcSigninQuery {
+    next: Option<String>,
+}
+
+pub async fn oidc_signin_get(
+    Extension(oidc): Extension<Option<Oidc>>,
+    auth_session: AuthSession,
+    Query(query): Query<OidcSigninQuery>,
+) -> Result<Redirect, SigninError> {
+    let oidc = oidc.ok_or(SigninError::AuthenticationFailed)?;
+
+    let (pkce_challenge, pkce_verifier) = PkceCodeChallenge::new_random_sha256();
+
+    let (authorize_url, csrf_token, nonce) = oidc
+        .client
+        .authorize_url(
+            CoreAuthenticationFlow::AuthorizationCode,
+            CsrfToken::new_random,
+            Nonce::new_random,
+        )
+        .add_scope(Scope::new("email".to_string()))
+        .set_pkce_challenge(pkce_challenge)
+        .url();
+
+    auth_session
+        .session
+        .insert(
+            PENDING_SIGNIN_KEY,
+            PendingSignin {
+                csrf_token: csrf_token.secret().clone(),
+                nonce: nonce.secret().clone(),
+                pkce_verifier: pkce_verifier.secret().clone(),
+                next: query.next,
+            },
+        )
+        .await?;
+
+    Ok(Redirect::to(authorize_url.as_str()))
+}
+
+#[derive(Deserialize)]
+pub struct OidcCallbackQuery {
+    code: Option<String>,
+    state: Option<String>,
+}
+
+pub async fn oidc_callback_get(
+    Extension(oidc): Extension<Option<Oidc>>,
+    Extension(authn_service): Extension<AuthnService>,
+    mut auth_session: AuthSession,
+    client: ClientInfo,
+    Query(query): Query<OidcCallbackQuery>,
+) -> Result<Redirect, SigninError> {
+    let oidc = oidc.ok_or(SigninError::AuthenticationFailed)?;
+
+    // the pending signin is single use, whether or not it succeeds
+    let pending: PendingSignin = auth_session
+        .session
+        .remove(PENDING_SIGNIN_KEY)
+        .await?
+        .ok_or(SigninError::SessionExpired)?;
+
+    // the provider sends the user back without a code if they cancelled or were refused
+    let (Some(code), Some(state)) = (query.code, query.state) else {
+        return Err(SigninError::AuthenticationFailed);
+    };
+
+    if state != pending.csrf_token {
+        return Err(SigninError::AuthenticationFailed);
+    }
+
+    let email = verified_email(&oidc, code, &pending)
+        .await
+        .map_err(|error| {
+            tracing::warn!(%error, "oidc signin failed");
+            SigninError::AuthenticationFailed
+        })?;
+
+    let user = find_or_create_user(&authn_service, email).await?;
+
+    totp::complete_signin(
+        &authn_service,
+        &mut auth_session,
+        user,
+        pending.next,
+        SigninMethod::SingleSignOn,
+        &client,
+    )
+    .await
+}
+
+/// exchanges the authorization code and returns the email from the verified id token
+async fn verified_email(
+    oidc: &Oidc,
+    code: String,
+    pending: &PendingSignin,
+) -> Result<Email, String> {
+    let token_response = oidc
+        .client
+        .exchange_code(AuthorizationCode::new(code))
+        .map_err(|e| e.to_string())?
+        .set_pkce_verifier(PkceCodeVerifier::new(pending.pkce_verifier.clone()))
+        .request_async(&oidc.http_client)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    let id_token = token_response
+        .id_token()
+        .ok_or("the provider didn't return an id token")?;
+
+    let claims = id_token
+        .claims(
+            &oidc.client.id_token_verifier(),
+            &Nonce::new(pending.nonce.clone()),
+        )
+        .map_err(|e| e.to_string())?;
+
+    // an unverified email could be used to take over the account that owns it
+    if claims.email_verified() != Some(true) {
+        return Err(format!(
+            "the provider hasn't verified the email for {}",
+            claims.subject().as_str()
+        ));
+    }
+
+    let email = claims
+        .email()
+        .ok_or("the provider didn't return an email")?;
+
+    Email::try_new(email.as_str()).map_err(|e| e.to_string())
+}
+
+/// signs in the user who owns the email, creating them on their first signin
+async fn find_or_create_user(
+    authn_service: &AuthnService,
+    email: Email,
+) -> Result<UserState, SigninError> {
+    let user_id = match authn_service.lookup_user_id(&email).await {
+        Ok(user_id) => user_id,
+        Err(UserError::EmailDoesntExist(_)) => {
+            let user_id: UserId = authn_service.ids().generate();
+
+            let event_id = authn_service
+                .create_user(
+                    user_id,
+                    email,
+                    Uuid::new_v4(),
+                    Authority::Direct(Actor::Anonymous),
+                    authn_service.clock().get_time(),
+                )
+                .await
+                .map_err(|e| SigninError::StoreError(e.to_string()))?;
+
+            authn_service.wait_for(event_id).await;
+
+            user_id
+        }
+        Err(e) => return Err(SigninError::StoreError(e.to_string())),
+    };
+
+    authn_service
+        .fetch_user(user_id)
+        .await
+        .map_err(|_| SigninError::UserNotFound)
+}
@@ -5,7 +5,7 @@ use super::user::User;
 pub(crate) use super::{AuthSession, AuthnEvent, AuthnService, PasskeyId};
 use crate::authority::Actor;
 use crate::authority::Authority;
-use crate::time_provider::{DefaultTimeProvider, TimeProvider, Timestamp};
+use crate::time_provider::{TimeProvider, Timestamp};
 use axum::extract::Extension;
 use axum::extract::Form;
 use axum::extract::Path;
@@ -261,7 +261,7 @@ pub async fn delete_passkey_post(
             passkey_id,
             user_id,
             Authority::Direct(Actor::User(user_id)),
-            DefaultTimeProvider.get_time(),
+            service.clock().get_time(),
         )
         .await
     {
@@ -307,7 +307,7 @@ pub async fn create_passkey_post(
                 _ = session.remove_value("add_passkey_reg_state").await;
 
                 // Generate a PasskeyId for this passkey
-                let passkey_id = PasskeyId::new();
+                let passkey_id: PasskeyId = authn_service.ids().generate();
 
                 // Add the new passkey to the user's existing passkeys
                 if let Ok(ev_id) = authn_service
@@ -316,7 +316,7 @@ pub async fn create_passkey_post(
                         user_id,
                         CorePasskey(passkey),
                         Authority::Direct(Actor::User(user_id)),
-                        DefaultTimeProvider.get_time(),
+                        authn_service.clock().get_time(),
                     )
                     .await
                 {
@@ -384,9 +384,7 @@ pub async fn create_passkey_post(
 fn add_passkey_challenge_page(email: &str, challenge_data: &str) -> maud::Markup {
     let content = html! {
         div class="flex flex-col gap-6 sm:mx-auto sm:w-full sm:max-w-sm" {
-        script
-            src="https://cdn.jsdelivr.net/npm/js-base64@3.7.4/base64.min.js"
-            crossorigin="anonymous" {}
+        script src="/base64.js" {}
         script id="challenge-data" type="application/json" {
             (PreEscaped(challenge_data))
         }
@@ -0,0 +1,403 @@
+use super::appearance::Theme;
+use super::digest::DigestFrequency;
+use super::layout::layout;
+use super::shortcuts::Shortcuts;
+use super::timezone::Timezone;
+use super::user::{UserError, UserState};
+use super::{AuthSession, AuthnEvent, AuthnService, PasskeyId, UserId};
+use crate::StateType;
+use crate::authority::{Actor, Authority};
+use crate::email::Email;
+use crate::journal::JournalId;
+use crate::journal::domain::JournalDomainEvent;
+use crate::journal::service::JournalState;
+use crate::monkesto_error::{ErrorRedirect, MonkestoError, OrRedirect, UrlError};
+use crate::name::Name;
+use crate::time_provider::{TimeProvider, Timestamp};
+use axum::Json;
+use axum::extract::{Extension, Form, Query, State};
+use axum::http::header::CONTENT_DISPOSITION;
+use axum::response::{IntoResponse, Redirect, Response};
+use disintegrate::Event;
+use futures::TryStreamExt;
+use maud::{Markup, html};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// everything a user owns, as downloaded from `/me/export`
+#[derive(Serialize)]
+struct AccountExport {
+    exported_at: Timestamp,
+    user: ExportedUser,
+    passkeys: Vec<ExportedPasskey>,
+    events: Vec<ExportedEvent>,
+    journals: Vec<ExportedJournal>,
+}
+
+#[derive(Serialize)]
+struct ExportedUser {
+    id: UserId,
+    email: Email,
+    timezone: Timezone,
+    theme: Theme,
+    shortcuts: Shortcuts,
+    digest_frequency: DigestFrequency,
+    two_factor_enabled: bool,
+}
+
+#[derive(Serialize)]
+struct ExportedPasskey {
+    id: PasskeyId,
+    credential_id: String,
+    added: Option<Timestamp>,
+}
+
+/// what happened to the account and when. the events themselves hold the recovery code hashes
+/// and passkey keys, which would help anyone holding the export sign in as the user
+#[derive(Serialize)]
+struct ExportedEvent {
+    event: &'static str,
+    timestamp: Timestamp,
+}
+
+impl From<&AuthnEvent> for ExportedEvent {
+    fn from(event: &AuthnEvent) -> Self {
+        Self {
+            event: event.name(),
+            timestamp: event.timestamp(),
+        }
+    }
+}
+
+#[derive(Serialize)]
+struct ExportedJournal {
+    id: JournalId,
+    name: Name,
+    events: Vec<JournalDomainEvent>,
+}
+
+/// the journals the user owns, and the ones they're only a member of
+async fn user_journals(
+    state: &StateType,
+    user_id: UserId,
+) -> Result<(Vec<JournalState>, Vec<JournalState>), MonkestoError> {
+    Ok(state
+        .journal_service
+        .list_accessible_journals(user_id)
+        .await?
+        .into_iter()
+        .map(|(journal, _, _)| journal)
+        .partition(|journal| journal.owner_id == user_id))
+}
+
+async fn account_export(
+    state: &StateType,
+    authn_service: &AuthnService,
+    user: &UserState,
+) -> Result<AccountExport, MonkestoError> {
+    let authority = Authority::Direct(Actor::User(user.id));
+    let events = authn_service.user_events(user.id).await?;
+
+    let added: HashMap<PasskeyId, Timestamp> = events
+        .iter()
+        .filter_map(|event| match event {
+            AuthnEvent::PasskeyCreated {
+                passkey_id,
+                timestamp,
+                ..
+            } => Some((*passkey_id, *timestamp)),
+            _ => None,
+        })
+        .collect();
+
+    let passkeys = authn_service
+        .get_user_passkeys(user.id)
+        .await
+        .map_err(|e| UserError::PasskeyDecode(e.to_string()))?
+        .into_iter()
+        .map(|stored| ExportedPasskey {
+            id: stored.id,
+            credential_id: hex::encode(stored.passkey.cred_id().as_ref()),
+            added: added.get(&stored.id).copied(),
+        })
+        .collect();
+
+    let (owned, _) = user_journals(state, user.id).await?;
+    let mut journals = Vec::with_capacity(owned.len());
+
+    for journal in owned {
+        let events = state
+            .journal_service
+            .stream_journal_events(journal.id, &authority)
+            .await?
+            .map_ok(|envelope| envelope.event)
+            .try_collect()
+            .await?;
+
+        journals.push(ExportedJournal {
+            id: journal.id,
+            name: journal.name,
+            events,
+        });
+    }
+
+    Ok(AccountExport {
+        exported_at: state.clock.get_time(),
+        user: ExportedUser {
+            id: user.id,
+            email: user.email.clone(),
+            timezone: user.timezone(),
+            theme: user.theme(),
+            shortcuts: user.shortcuts(),
+            digest_frequency: authn_service.digest_frequency(user.id).await?,
+            two_factor_enabled: authn_service.totp_secret(user.id).await?.is_some(),
+        },
+        passkeys,
+        events: events.iter().map(ExportedEvent::from).collect(),
+        journals,
+    })
+}
+
+pub async fn export_get(
+    State(state): State<StateType>,
+    Extension(authn_service): Extension<AuthnService>,
+    auth_session: AuthSession,
+) -> Result<Response, ErrorRedirect> {
+    const CALLBACK_URL: &str = "/me";
+
+    let user = auth_session
+        .user
+        .ok_or(UserError::SessionNotFound)
+        .or_redirect(CALLBACK_URL)?;
+
+    let export = account_export(&state, &authn_service, &user)
+        .await
+        .or_redirect(CALLBACK_URL)?;
+
+    let filename = format!(
+        "monkesto-export-{}.json",
+        user.timezone().date(export.exported_at).format("%Y-%m-%d")
+    );
+
+    Ok((
+        [(
+            CONTENT_DISPOSITION,
+            format!("attachment; filename=\"{filename}\""),
+        )],
+        Json(export),
+    )
+        .into_response())
+}
+
+fn nav_title() -> Option<Markup> {
+    Some(html! {
+        span class="text-sm font-medium text-gray-700 dark:text-gray-300" {
+            "Delete account"
+        }
+    })
+}
+
+fn delete_account_page(
+    owned: &[JournalState],
+    joined: &[JournalState],
+    retention_days: u32,
+    err: Option<String>,
+) -> Markup {
+    let content = html! {
+        div class="flex flex-col gap-6 sm:mx-auto sm:w-full sm:max-w-sm" {
+        div class="bg-white dark:bg-gray-800 rounded-lg shadow p-6 space-y-4" {
+            h3 class="text-lg font-medium text-gray-900 dark:text-white" {
+                "Delete your account"
+            }
+            p class="text-sm text-gray-600 dark:text-gray-400" {
+                "Your passkeys, preferences and sign-in history are erased for good. Anything you recorded in other people's journals stays, but is shown as from a deleted user."
+            }
+
+            @if !owned.is_empty() {
+                div {
+                    h4 class="text-md font-medium text-gray-900 dark:text-white mb-2" {
+                        "Journals you own"
+                    }
+                    p class="text-sm text-gray-500 dark:text-gray-400 mb-2" {
+                        "These are deleted for everyone, and purged after " (retention_days) " days."
+                    }
+                    ul class="list-disc pl-5 text-sm text-gray-900 dark:text-white" {
+                        @for journal in owned {
+                            li { (journal.name) }
+                        }
+                    }
+                }
+            }
+
+            @if !joined.is_empty() {
+                div {
+                    h4 class="text-md font-medium text-gray-900 dark:text-white mb-2" {
+                        "Journals you'll leave"
+                    }
+                    ul class="list-disc pl-5 text-sm text-gray-900 dark:text-white" {
+                        @for journal in joined {
+                            li { (journal.name) }
+                        }
+                    }
+                }
+            }
+
+            p class="text-sm text-gray-600 dark:text-gray-400" {
+                "You may want to "
+                a
+                    href="/me/export"
+                    class="font-semibold text-indigo-600 hover:text-indigo-500 dark:text-indigo-400 dark:hover:text-indigo-300" {
+                    "download your data"
+                }
+                " first."
+            }
+
+            form method="POST" action="/me/delete-account" class="space-y-4" {
+                label for="email" class="block text-sm/6 font-medium text-gray-900 dark:text-gray-100" {
+                    "Enter your email to confirm"
+                }
+                input
+                    type="email"
+                    id="email"
+                    name="email"
+                    required
+                    autocomplete="off"
+                    class="block w-full rounded-md bg-white px-3 py-1.5 text-sm text-gray-900 outline-1 -outline-offset-1 outline-gray-300 dark:bg-white/5 dark:text-white dark:outline-white/10";
+                button
+                    type="submit"
+                    onclick="return confirm('Delete your account? This can\\'t be undone.')"
+                    class="flex w-full justify-center rounded-md bg-red-600 px-3 py-1.5 text-sm/6 font-semibold text-white shadow-xs hover:bg-red-500 focus-visible:outline-2 focus-visible:outline-offset-2 focus-visible:outline-red-600" {
+                    "Delete my account"
+                }
+            }
+
+            @if let Some(e) = err {
+                p class="text-sm text-red-500" {
+                    (MonkestoError::decode(&e).to_string())
+                }
+            }
+        }
+        }
+    };
+
+    layout(nav_title(), content)
+}
+
+pub async fn delete_account_get(
+    State(state): State<StateType>,
+    auth_session: AuthSession,
+    Query(err): Query<UrlError>,
+) -> Result<Markup, ErrorRedirect> {
+    const CALLBACK_URL: &str = "/me";
+
+    let user = auth_session
+        .user
+        .ok_or(UserError::SessionNotFound)
+        .or_redirect(CALLBACK_URL)?;
+
+    let (owned, joined) = user_journals(&state, user.id)
+        .await
+        .or_redirect(CALLBACK_URL)?;
+
+    Ok(delete_account_page(
+        &owned,
+        &joined,
+        state.journal_service.retention_days(),
+        err.err,
+    ))
+}
+
+#[derive(Deserialize)]
+pub struct DeleteAccountForm {
+    email: String,
+}
+
+pub async fn delete_account_post(
+    State(state): State<StateType>,
+    Extension(authn_service): Extension<AuthnService>,
+    mut auth_session: AuthSession,
+    Form(form): Form<DeleteAccountForm>,
+) -> Result<Redirect, ErrorRedirect> {
+    const CALLBACK_URL: &str = "/me/delete-account";
+
+    let user = auth_session
+        .user
+        .clone()
+        .ok_or(UserError::SessionNotFound)
+        .or_redirect(CALLBACK_URL)?;
+
+    if Email::try_new(form.email).ok().as_ref() != Some(&user.email) {
+        return Err(ErrorRedirect::new(
+            UserError::DeletionNotConfirmed,
+            CALLBACK_URL,
+        ));
+    }
+
+    let authority = Authority::Direct(Actor::User(user.id));
+    let (owned, joined) = user_journals(&state, user.id)
+        .await
+        .or_redirect(CALLBACK_URL)?;
+
+    // the journals are let go of first, while the user can still act on them
+    for journal in owned {
+        let event_id = state
+            .journal_service
+            .delete_journal(journal.id, authority.clone(), state.clock.get_time())
+            .await
+            .or_redirect(CALLBACK_URL)?;
+        state.journal_service.wait_for(event_id).await;
+    }
+
+    for journal in joined {
+        let event_id = state
+            .journal_service
+            .leave_journal(journal.id, authority.clone(), state.clock.get_time())
+            .await
+            .or_redirect(CALLBACK_URL)?;
+        state.journal_service.wait_for(event_id).await;
+    }
+
+    let event_id = authn_service
+        .delete_user(user.id, authority, state.clock.get_time())
+        .await
+        .or_redirect(CALLBACK_URL)?;
+    authn_service.wait_for(event_id).await;
+
+    state
+        .journal_service
+        .forget_user(user.id)
+        .await
+        .or_redirect(CALLBACK_URL)?;
+
+    authn_service
+        .erase_user(user.id)
+        .await
+        .or_redirect(CALLBACK_URL)?;
+
+    _ = auth_session.logout().await;
+
+    Ok(Redirect::to("/signin"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Utc;
+
+    #[test]
+    fn exported_events_leave_out_secrets() {
+        let user_id = UserId::new();
+        let event = AuthnEvent::TotpEnabled {
+            user_id,
+            recovery_codes: vec!["recovery-code-hash".to_string()],
+            authority: Authority::Direct(Actor::User(user_id)),
+            timestamp: Utc::now(),
+        };
+
+        let exported = serde_json::to_string(&ExportedEvent::from(&event))
+            .expect("failed to serialize the event");
+
+        assert!(exported.contains("TotpEnabled"));
+        assert!(!exported.contains("recovery-code-hash"));
+    }
+}
@@ -0,0 +1,284 @@
+use super::user::{User, UserError, UserState};
+use super::{AuthSession, AuthnEvent, AuthnService, UserId};
+use crate::authority::{Actor, Authority};
+use crate::monkesto_error::{ErrorRedirect, OrRedirect};
+use crate::name::Name;
+use crate::time_provider::{TimeProvider, Timestamp};
+use axum::extract::{Extension, Form};
+use axum::response::Redirect;
+use disintegrate::Decision;
+use maud::{Markup, html};
+use serde::Deserialize;
+use sha2::{Digest, Sha256};
+
+pub struct SetDisplayName {
+    user_id: UserId,
+    display_name: Option<Name>,
+    authority: Authority,
+    timestamp: Timestamp,
+}
+
+impl SetDisplayName {
+    pub fn new(
+        user_id: UserId,
+        display_name: Option<Name>,
+        authority: Authority,
+        timestamp: Timestamp,
+    ) -> Self {
+        Self {
+            user_id,
+            display_name,
+            authority,
+            timestamp,
+        }
+    }
+}
+
+impl Decision for SetDisplayName {
+    type Event = AuthnEvent;
+    type StateQuery = User;
+    type Error = UserError;
+
+    fn state_query(&self) -> Self::StateQuery {
+        User::new(self.user_id)
+    }
+
+    fn process(&self, user: &Self::StateQuery) -> Result<Vec<Self::Event>, Self::Error> {
+        if !user.status.valid() {
+            return Err(UserError::UserDoesntExist(self.user_id));
+        }
+
+        Ok(vec![AuthnEvent::DisplayNameChanged {
+            user_id: self.user_id,
+            display_name: self.display_name.clone(),
+            authority: self.authority.clone(),
+            timestamp: self.timestamp,
+        }])
+    }
+}
+
+#[derive(Deserialize)]
+pub struct DisplayNameForm {
+    display_name: String,
+}
+
+pub async fn display_name_post(
+    Extension(authn_service): Extension<AuthnService>,
+    auth_session: AuthSession,
+    Form(form): Form<DisplayNameForm>,
+) -> Result<Redirect, ErrorRedirect> {
+    const CALLBACK_URL: &str = "/me";
+
+    let user_id = auth_session
+        .user
+        .map(|user| user.id)
+        .ok_or(UserError::SessionNotFound)
+        .or_redirect(CALLBACK_URL)?;
+
+    // a blank name goes back to showing the email
+    let display_name = match form.display_name.trim() {
+        "" => None,
+        name => Some(Name::try_new(name.to_string()).or_redirect(CALLBACK_URL)?),
+    };
+
+    let event_id = authn_service
+        .set_display_name(
+            user_id,
+            display_name,
+            Authority::Direct(Actor::User(user_id)),
+            authn_service.clock().get_time(),
+        )
+        .await
+        .or_redirect(CALLBACK_URL)?;
+
+    authn_service.wait_for(event_id).await;
+
+    Ok(Redirect::to(CALLBACK_URL))
+}
+
+pub struct SetGravatar {
+    user_id: UserId,
+    gravatar: bool,
+    authority: Authority,
+    timestamp: Timestamp,
+}
+
+impl SetGravatar {
+    pub fn new(
+        user_id: UserId,
+        gravatar: bool,
+        authority: Authority,
+        timestamp: Timestamp,
+    ) -> Self {
+        Self {
+            user_id,
+            gravatar,
+            authority,
+            timestamp,
+        }
+    }
+}
+
+impl Decision for SetGravatar {
+    type Event = AuthnEvent;
+    type StateQuery = User;
+    type Error = UserError;
+
+    fn state_query(&self) -> Self::StateQuery {
+        User::new(self.user_id)
+    }
+
+    fn process(&self, user: &Self::StateQuery) -> Result<Vec<Self::Event>, Self::Error> {
+        if !user.status.valid() {
+            return Err(UserError::UserDoesntExist(self.user_id));
+        }
+
+        Ok(vec![AuthnEvent::AvatarChanged {
+            user_id: self.user_id,
+            gravatar: self.gravatar,
+            authority: self.authority.clone(),
+            timestamp: self.timestamp,
+        }])
+    }
+}
+
+#[derive(Deserialize)]
+pub struct AvatarForm {
+    /// only sent when the box is checked
+    gravatar: Option<String>,
+}
+
+pub async fn avatar_post(
+    Extension(authn_service): Extension<AuthnService>,
+    auth_session: AuthSession,
+    Form(form): Form<AvatarForm>,
+) -> Result<Redirect, ErrorRedirect> {
+    const CALLBACK_URL: &str = "/me";
+
+    let user_id = auth_session
+        .user
+        .map(|user| user.id)
+        .ok_or(UserError::SessionNotFound)
+        .or_redirect(CALLBACK_URL)?;
+
+    let event_id = authn_service
+        .set_gravatar(
+            user_id,
+            form.gravatar.is_some(),
+            Authority::Direct(Actor::User(user_id)),
+            authn_service.clock().get_time(),
+        )
+        .await
+        .or_redirect(CALLBACK_URL)?;
+
+    authn_service.wait_for(event_id).await;
+
+    Ok(Redirect::to(CALLBACK_URL))
+}
+
+/// the user's gravatar, hashed the way gravatar expects. gravatar resizes it to the size asked
+/// for, and shows its default silhouette when the email has no picture
+pub fn gravatar_url(user: &UserState, size: u32) -> String {
+    let email = user.email.as_ref().trim().to_lowercase();
+    let hash = hex::encode(Sha256::digest(email.as_bytes()));
+    format!("https://gravatar.com/avatar/{hash}?s={size}&d=mp")
+}
+
+/// the first letter of the user's display name
+fn initial(user: &UserState) -> String {
+    user.display_name()
+        .chars()
+        .next()
+        .map(|c| c.to_uppercase().to_string())
+        .unwrap_or_default()
+}
+
+/// a small round picture of the user, shown next to their name. users who haven't turned on
+/// their gravatar get their initial instead, so nothing is fetched from gravatar for them
+pub fn avatar(user: &UserState) -> Markup {
+    html! {
+        @if user.gravatar {
+            img
+                src=(gravatar_url(user, 48))
+                alt=""
+                width="24"
+                height="24"
+                loading="lazy"
+                class="inline-block h-6 w-6 shrink-0 rounded-full bg-gray-100 dark:bg-gray-700";
+        } @else {
+            span
+                aria-hidden="true"
+                class="inline-flex h-6 w-6 shrink-0 items-center justify-center rounded-full bg-indigo-100 text-xs font-semibold text-indigo-700 dark:bg-indigo-900 dark:text-indigo-300" {
+                (initial(user))
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::email::Email;
+    use crate::status::Status;
+    use chrono::Utc;
+
+    #[test]
+    fn users_are_shown_by_their_email_until_they_choose_a_name() {
+        let email = Email::try_new("ada@example.com".to_string()).expect("invalid email");
+        let mut user = UserState {
+            id: UserId::new(),
+            email: email.clone(),
+            webauthn_uuid: Default::default(),
+            timezone_preference: None,
+            theme_preference: None,
+            shortcuts_preference: None,
+            name: None,
+            gravatar: false,
+        };
+        assert_eq!(user.display_name(), "ada@example.com");
+
+        user.name = Some(Name::try_new("Ada".to_string()).expect("invalid name"));
+        assert_eq!(user.display_name(), "Ada");
+    }
+
+    #[test]
+    fn deleted_users_cant_change_their_name() {
+        let user_id = UserId::new();
+        let mut user = User::new(user_id);
+        user.status = Status::Deleted;
+
+        let decision = SetDisplayName::new(
+            user_id,
+            None,
+            Authority::Direct(Actor::User(user_id)),
+            Utc::now(),
+        );
+
+        assert!(matches!(
+            decision.process(&user),
+            Err(UserError::UserDoesntExist(id)) if id == user_id
+        ));
+    }
+
+    #[test]
+    fn gravatars_are_found_by_the_hash_of_the_trimmed_lowercase_email() {
+        let user = UserState {
+            id: UserId::new(),
+            email: Email::try_new("MyEmailAddress@example.com ".to_string())
+                .expect("invalid email"),
+            webauthn_uuid: Default::default(),
+            timezone_preference: None,
+            theme_preference: None,
+            shortcuts_preference: None,
+            name: None,
+            gravatar: true,
+        };
+
+        // the example from gravatar's documentation
+        assert_eq!(
+            gravatar_url(&user, 48),
+            "https://gravatar.com/avatar/84059b07d4be67b806386c0aad8070a23f18836bbaae342275dc0a83414c32ee?s=48&d=mp"
+        );
+        assert_eq!(initial(&user), "M");
+    }
+}
@@ -0,0 +1,354 @@
+use super::user::{User, UserError};
+use super::{AuthSession, AuthnEvent, AuthnService, UserId};
+use crate::authority::{Actor, Authority};
+use crate::monkesto_error::{ErrorRedirect, OrRedirect};
+use crate::time_provider::{TimeProvider, Timestamp};
+use axum::extract::{Extension, Form};
+use axum::response::Redirect;
+use disintegrate::Decision;
+use serde::{Deserialize, Serialize};
+use sqlx::encode::IsNull;
+use sqlx::error::BoxDynError;
+use sqlx::{Database, Postgres};
+use std::collections::{BTreeMap, HashMap};
+use std::fmt::Display;
+
+/// what a keyboard shortcut does
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+pub enum ShortcutAction {
+    Dashboard,
+    Journals,
+    Notifications,
+    /// focuses the page's search box, or opens the journal's search page
+    Search,
+    NewTransaction,
+    QuickEntry,
+    /// lists the shortcuts
+    Help,
+}
+
+impl ShortcutAction {
+    pub const ALL: [Self; 7] = [
+        Self::Dashboard,
+        Self::Journals,
+        Self::Notifications,
+        Self::Search,
+        Self::NewTransaction,
+        Self::QuickEntry,
+        Self::Help,
+    ];
+
+    /// the keys pressed in turn that run the action, until the user picks others
+    pub fn default_keys(self) -> &'static str {
+        match self {
+            Self::Dashboard => "g d",
+            Self::Journals => "g j",
+            Self::Notifications => "g n",
+            Self::Search => "/",
+            Self::NewTransaction => "n t",
+            Self::QuickEntry => "q",
+            Self::Help => "?",
+        }
+    }
+
+    /// the i18n key of what the action does
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::Dashboard => "shortcut.dashboard",
+            Self::Journals => "shortcut.journals",
+            Self::Notifications => "shortcut.notifications",
+            Self::Search => "shortcut.search",
+            Self::NewTransaction => "shortcut.new_transaction",
+            Self::QuickEntry => "shortcut.quick_entry",
+            Self::Help => "shortcut.help",
+        }
+    }
+}
+
+impl Display for ShortcutAction {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Dashboard => write!(f, "dashboard"),
+            Self::Journals => write!(f, "journals"),
+            Self::Notifications => write!(f, "notifications"),
+            Self::Search => write!(f, "search"),
+            Self::NewTransaction => write!(f, "new_transaction"),
+            Self::QuickEntry => write!(f, "quick_entry"),
+            Self::Help => write!(f, "help"),
+        }
+    }
+}
+
+/// the user's keyboard shortcuts. each is a sequence of single keys pressed in turn, written
+/// with spaces between them, like `g j`
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Shortcuts {
+    pub enabled: bool,
+    /// the keys for each action the user changed. no keys turns the action off
+    keys: BTreeMap<ShortcutAction, String>,
+}
+
+impl Default for Shortcuts {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            keys: BTreeMap::new(),
+        }
+    }
+}
+
+impl Shortcuts {
+    /// checks the picked keys can be told apart. a sequence is at most two keys, and none
+    /// can be the same as another or start with one, since that one would always run first
+    pub fn new(enabled: bool, picked: HashMap<ShortcutAction, String>) -> Result<Self, UserError> {
+        let mut keys = BTreeMap::new();
+
+        for (action, sequence) in picked {
+            let presses: Vec<&str> = sequence.split_whitespace().collect();
+            if presses.len() > 2 || presses.iter().any(|key| key.chars().count() != 1) {
+                return Err(UserError::InvalidShortcut(sequence));
+            }
+
+            let sequence = presses.join(" ");
+            if sequence != action.default_keys() {
+                keys.insert(action, sequence);
+            }
+        }
+
+        let shortcuts = Self { enabled, keys };
+
+        let sequences: Vec<&str> = ShortcutAction::ALL
+            .into_iter()
+            .map(|action| shortcuts.picked(action))
+            .filter(|sequence| !sequence.is_empty())
+            .collect();
+        for (i, sequence) in sequences.iter().enumerate() {
+            let clashes = sequences.iter().enumerate().any(|(j, other)| {
+                i != j && (other == sequence || other.starts_with(&format!("{sequence} ")))
+            });
+            if clashes {
+                return Err(UserError::InvalidShortcut(sequence.to_string()));
+            }
+        }
+
+        Ok(shortcuts)
+    }
+
+    /// the keys that run the action, or none when it's turned off
+    pub fn keys(&self, action: ShortcutAction) -> Option<&str> {
+        let keys = self
+            .keys
+            .get(&action)
+            .map_or(action.default_keys(), String::as_str);
+
+        (self.enabled && !keys.is_empty()).then_some(keys)
+    }
+
+    /// the keys for the action whether or not shortcuts are turned on, for the profile form
+    pub fn picked(&self, action: ShortcutAction) -> &str {
+        self.keys
+            .get(&action)
+            .map_or(action.default_keys(), String::as_str)
+    }
+}
+
+impl sqlx::Type<Postgres> for Shortcuts {
+    fn type_info() -> <Postgres as Database>::TypeInfo {
+        <&str as sqlx::Type<Postgres>>::type_info()
+    }
+}
+
+impl<'q> sqlx::Encode<'q, Postgres> for Shortcuts {
+    fn encode_by_ref(
+        &self,
+        buf: &mut <Postgres as Database>::ArgumentBuffer<'q>,
+    ) -> Result<IsNull, BoxDynError> {
+        <String as sqlx::Encode<Postgres>>::encode(serde_json::to_string(self)?, buf)
+    }
+}
+
+impl<'r> sqlx::Decode<'r, Postgres> for Shortcuts {
+    fn decode(value: <Postgres as Database>::ValueRef<'r>) -> Result<Self, BoxDynError> {
+        Ok(serde_json::from_str(
+            <&str as sqlx::Decode<Postgres>>::decode(value)?,
+        )?)
+    }
+}
+
+pub struct SetShortcuts {
+    user_id: UserId,
+    shortcuts: Shortcuts,
+    authority: Authority,
+    timestamp: Timestamp,
+}
+
+impl SetShortcuts {
+    pub fn new(
+        user_id: UserId,
+        shortcuts: Shortcuts,
+        authority: Authority,
+        timestamp: Timestamp,
+    ) -> Self {
+        Self {
+            user_id,
+            shortcuts,
+            authority,
+            timestamp,
+        }
+    }
+}
+
+impl Decision for SetShortcuts {
+    type Event = AuthnEvent;
+    type StateQuery = User;
+    type Error = UserError;
+
+    fn state_query(&self) -> Self::StateQuery {
+        User::new(self.user_id)
+    }
+
+    fn process(&self, user: &Self::StateQuery) -> Result<Vec<Self::Event>, Self::Error> {
+        if !user.status.valid() {
+            return Err(UserError::UserDoesntExist(self.user_id));
+        }
+
+        Ok(vec![AuthnEvent::ShortcutsChanged {
+            user_id: self.user_id,
+            shortcuts: self.shortcuts.clone(),
+            authority: self.authority.clone(),
+            timestamp: self.timestamp,
+        }])
+    }
+}
+
+/// a field for each action's keys, named after the action, and `enabled` when the
+/// shortcuts are turned on
+pub async fn shortcuts_post(
+    Extension(authn_service): Extension<AuthnService>,
+    auth_session: AuthSession,
+    Form(form): Form<HashMap<String, String>>,
+) -> Result<Redirect, ErrorRedirect> {
+    const CALLBACK_URL: &str = "/me";
+
+    let user_id = auth_session
+        .user
+        .map(|user| user.id)
+        .ok_or(UserError::SessionNotFound)
+        .or_redirect(CALLBACK_URL)?;
+
+    let picked = ShortcutAction::ALL
+        .into_iter()
+        .filter_map(|action| {
+            form.get(&action.to_string())
+                .map(|keys| (action, keys.clone()))
+        })
+        .collect();
+
+    let shortcuts =
+        Shortcuts::new(form.contains_key("enabled"), picked).or_redirect(CALLBACK_URL)?;
+
+    let event_id = authn_service
+        .set_shortcuts(
+            user_id,
+            shortcuts,
+            Authority::Direct(Actor::User(user_id)),
+            authn_service.clock().get_time(),
+        )
+        .await
+        .or_redirect(CALLBACK_URL)?;
+
+    authn_service.wait_for(event_id).await;
+
+    Ok(Redirect::to(CALLBACK_URL))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_app::TestApp;
+
+    #[test]
+    fn picked_keys_replace_the_defaults() {
+        let shortcuts = Shortcuts::new(
+            true,
+            HashMap::from([
+                (ShortcutAction::Journals, "  j  ".to_string()),
+                (ShortcutAction::QuickEntry, String::new()),
+                (ShortcutAction::Search, "/".to_string()),
+            ]),
+        )
+        .expect("the shortcuts were rejected");
+
+        assert_eq!(shortcuts.keys(ShortcutAction::Journals), Some("j"));
+        assert_eq!(shortcuts.keys(ShortcutAction::QuickEntry), None);
+        assert_eq!(shortcuts.keys(ShortcutAction::Dashboard), Some("g d"));
+        // keys left at their default aren't kept, so they follow the default
+        assert_eq!(shortcuts.keys.len(), 2);
+
+        let off = Shortcuts::new(false, HashMap::new()).expect("the shortcuts were rejected");
+        assert_eq!(off.keys(ShortcutAction::Help), None);
+        assert_eq!(off.picked(ShortcutAction::Help), "?");
+    }
+
+    #[test]
+    fn keys_that_cant_be_told_apart_are_rejected() {
+        let rejected = |action, keys: &str| {
+            Shortcuts::new(true, HashMap::from([(action, keys.to_string())])).err()
+        };
+
+        // g already starts g d, g j and g n
+        assert_eq!(
+            rejected(ShortcutAction::Help, "g"),
+            Some(UserError::InvalidShortcut("g".to_string()))
+        );
+        assert_eq!(
+            rejected(ShortcutAction::Help, "/"),
+            Some(UserError::InvalidShortcut("/".to_string()))
+        );
+        assert!(rejected(ShortcutAction::Help, "g d x").is_some());
+        assert!(rejected(ShortcutAction::Help, "gd").is_some());
+        assert_eq!(rejected(ShortcutAction::Help, "h"), None);
+    }
+
+    #[tokio::test]
+    async fn pages_listen_for_the_shortcuts_the_user_picked() {
+        let Some(app) = TestApp::spawn().await else {
+            return;
+        };
+
+        let user = app.signup("shortcutter").await;
+        let journal_id = user.create_journal(&app, "Shortcuts").await;
+        let page = || async {
+            user.client
+                .get(&format!("/journal/{journal_id}"))
+                .clear_headers()
+                .add_header("accept", "text/html")
+                .await
+                .text()
+        };
+
+        assert!(page().await.contains(r#"{"keys":"g j","href":"/journal"}"#));
+
+        user.client
+            .post("/me/shortcuts")
+            .form(&[("enabled", "on"), ("journals", "j"), ("quick_entry", "")])
+            .await;
+        let picked = page().await;
+        assert!(picked.contains(r#"{"keys":"j","href":"/journal"}"#));
+        assert!(!picked.contains("{journal}/quick"));
+        assert!(picked.contains(&format!(r#"data-journal-id="{journal_id}""#)));
+
+        // g would run before g d could be finished, so it's turned down
+        user.client
+            .post("/me/shortcuts")
+            .form(&[("enabled", "on"), ("help", "g")])
+            .await;
+        assert!(page().await.contains(r#"{"keys":"j","href":"/journal"}"#));
+
+        user.client
+            .post("/me/shortcuts")
+            .form(&[("journals", "j")])
+            .await;
+        assert!(!page().await.contains(r#"id="shortcuts""#));
+    }
+}
@@ -1,9 +1,14 @@
+use super::attempt::{self, ClientInfo, SigninMethod};
+use super::oidc::Oidc;
 use super::user::DEV_USERS;
 use super::user::UserId;
 use super::user::UserState;
 use super::{AuthSession, AuthnService};
+use crate::app_env::AppEnv;
+use crate::i18n::{t, tf};
 use crate::monkesto_error::OrRedirect;
 use crate::theme::theme_with_head;
+use axum::Router;
 use axum::extract::Extension;
 use axum::extract::Form;
 use axum::extract::Query;
@@ -12,6 +17,7 @@ use axum::http::header;
 use axum::response::IntoResponse;
 use axum::response::Redirect;
 use axum::response::Response;
+use axum::routing::{get, post};
 use axum_login::AuthnBackend;
 use maud::Markup;
 use maud::PreEscaped;
@@ -20,7 +26,9 @@ use serde::Deserialize;
 use std::collections::HashMap;
 use std::sync::Arc;
 use thiserror::Error;
+use url::form_urlencoded;
 use webauthn_rs::prelude::AuthenticationResult;
+use webauthn_rs::prelude::CredentialID;
 use webauthn_rs::prelude::PasskeyAuthentication;
 use webauthn_rs::prelude::PublicKeyCredential;
 use webauthn_rs::prelude::RequestChallengeResponse;
@@ -43,6 +51,8 @@ pub enum SigninError {
     StoreError(String),
     #[error("Login failed: {0}")]
     LoginFailed(String),
+    #[error("Too many failed signins, so the account is locked for now")]
+    AccountLocked,
 }
 
 impl IntoResponse for SigninError {
@@ -54,6 +64,7 @@ impl IntoResponse for SigninError {
             SigninError::AuthenticationFailed => {
                 Redirect::to("/signin?error=auth_failed").into_response()
             }
+            SigninError::AccountLocked => Redirect::to("/signin?error=locked").into_response(),
             SigninError::InvalidInput => (StatusCode::BAD_REQUEST, "Invalid input").into_response(),
             SigninError::UserNotFound => (StatusCode::NOT_FOUND, "User not found").into_response(),
             SigninError::SessionError(_) => {
@@ -147,13 +158,12 @@ fn auth_page(
     error_message: Option<&str>,
     next: Option<&str>,
     dev_users: &[UserState],
+    sso_provider: Option<&str>,
 ) -> Markup {
     theme_with_head(
-        Some("Sign in"),
+        Some(t("signin.title")),
         html! {
-            script
-                src="https://cdn.jsdelivr.net/npm/js-base64@3.7.4/base64.min.js"
-                crossorigin="anonymous" {}
+            script src="/base64.js" {}
             meta name="webauthn_url" content=(webauthn_url);
             @if let Some(challenge_data) = challenge_data {
                 script id="challenge-data" type="application/json" {
@@ -219,7 +229,7 @@ fn auth_page(
                         img src="/logo.svg" alt="Monkesto" class="mx-auto h-36 w-auto";
 
                         h2 class="mt-10 text-center text-2xl/9 font-bold tracking-tight text-gray-900 dark:text-white" {
-                            "Sign in"
+                            (t("signin.title"))
                         }
                     }
 
@@ -230,7 +240,7 @@ fn auth_page(
                                 button
                                 onclick="signin()"
                                 class="flex w-full justify-center rounded-md bg-indigo-600 px-3 py-1.5 text-sm/6 font-semibold text-white shadow-xs hover:bg-indigo-500 focus-visible:outline-2 focus-visible:outline-offset-2 focus-visible:outline-indigo-600 dark:bg-indigo-500 dark:shadow-none dark:hover:bg-indigo-400 dark:focus-visible:outline-indigo-500" {
-                                    "Sign in with Passkey"
+                                    (t("signin.passkey"))
                                 }
                             }
                         }
@@ -244,12 +254,12 @@ fn auth_page(
                         }
 
                         p class="mt-6 text-center text-sm/6 text-gray-500 dark:text-gray-400" {
-                            "Don't have an account? "
+                            (t("signin.no_account")) " "
                             @let signup_url = next.map(|n| format!("signup?next={}", n)).unwrap_or_else(|| "signup".to_string());
                             a
                             href=(signup_url)
                             class="font-semibold text-indigo-600 hover:text-indigo-500 dark:text-indigo-400 dark:hover:text-indigo-300" {
-                                "Sign up here"
+                                (t("signin.sign_up_link"))
                             }
                         }
 
@@ -263,15 +273,28 @@ fn auth_page(
                             }
                         }
 
+                        @if let Some(sso_provider) = sso_provider {
+                            div class="mt-6" {
+                                @let oidc_url = next
+                                    .map(|n| format!("/signin/oidc?next={}", form_urlencoded::byte_serialize(n.as_bytes()).collect::<String>()))
+                                    .unwrap_or_else(|| "/signin/oidc".to_string());
+                                a
+                                    href=(oidc_url)
+                                    class="flex w-full justify-center rounded-md bg-white px-3 py-1.5 text-sm/6 font-semibold text-gray-900 shadow-xs outline-1 -outline-offset-1 outline-gray-300 hover:bg-gray-50 dark:bg-white/10 dark:text-white dark:outline-white/10 dark:hover:bg-white/20" {
+                                    (tf("signin.sso", &[("provider", &sso_provider)]))
+                                }
+                            }
+                        }
+
                         // Dev login section (only shown if dev users exist)
                         @if !dev_users.is_empty() {
                             div class="mt-10 border-t border-gray-200 dark:border-gray-700" {}
                             p style="margin-top: 1rem; margin-bottom: 1rem;" class="text-center text-xs text-gray-400 dark:text-gray-500" {
-                                "Dev Login"
+                                (t("signin.dev_login"))
                             }
                             div class="space-y-2" {
                                 @for user in dev_users {
-                                    form method="POST" action="/signin" {
+                                    form method="POST" action="/signin/dev" {
                                         input type="hidden" name="dev_user_id" value=(user.id.to_string());
                                         @if let Some(next) = next {
                                             input type="hidden" name="next" value=(next);
@@ -294,11 +317,14 @@ fn auth_page(
 async fn handle_signin_page(
     webauthn: Arc<Webauthn>,
     authn_service: AuthnService,
+    app_env: AppEnv,
+    oidc: Option<Oidc>,
     auth_session: AuthSession,
     webauthn_url: String,
     query: Query<SigninQuery>,
-    next: Option<String>,
 ) -> impl IntoResponse {
+    let next = query.next.clone();
+
     // Clear any previous auth state
     let session = auth_session.session;
     _ = session.remove_value("auth_state").await;
@@ -324,15 +350,18 @@ async fn handle_signin_page(
 
     // Handle error messages from query parameters
     let error_message = error_message.or_else(|| match query.error.as_deref() {
-        Some("session_expired") => {
-            Some("Your authentication session has expired. Please try again.")
-        }
-        Some("auth_failed") => Some("Authentication failed. Please try again."),
+        Some("session_expired") => Some(t("signin.session_expired")),
+        Some("auth_failed") => Some(t("signin.auth_failed")),
+        Some("locked") => Some(t("signin.locked")),
         _ => None,
     });
 
     // Get dev users for the dev login form
-    let dev_users = authn_service.get_dev_users().await;
+    let dev_users = if app_env.dev_mode() {
+        authn_service.get_dev_users().await
+    } else {
+        Vec::new()
+    };
 
     let markup = auth_page(
         &webauthn_url,
@@ -340,6 +369,7 @@ async fn handle_signin_page(
         error_message,
         next.as_deref(),
         &dev_users,
+        oidc.as_ref().map(|oidc| oidc.provider_name.as_str()),
     );
     (
         StatusCode::OK,
@@ -352,6 +382,7 @@ async fn handle_signin_completion(
     webauthn: Arc<Webauthn>,
     authn_service: AuthnService,
     mut auth_session: AuthSession,
+    client: ClientInfo,
     form_data: Form<HashMap<String, String>>,
     next: Option<String>,
 ) -> Result<Response, SigninError> {
@@ -376,6 +407,17 @@ async fn handle_signin_completion(
         })
         .ok_or(SigninError::SessionExpired)?;
 
+    // the credential names its owner before it's verified, so a locked account is refused up front
+    let owner = authn_service
+        .find_user_by_credential(&CredentialID::from(credential.raw_id.to_vec()))
+        .await
+        .map_err(|e| SigninError::StoreError(e.to_string()))?
+        .map(|(user_id, _)| user_id);
+
+    if let Some(user_id) = owner {
+        attempt::ensure_unlocked(&authn_service, user_id).await?;
+    }
+
     // Verify the authentication using SigninAuthenticator
     let authenticator = SigninAuthenticator::new(&webauthn, &authn_service);
     match authenticator.finish(&credential, &auth_state).await {
@@ -384,6 +426,15 @@ async fn handle_signin_completion(
             _ = session.remove_value("identifierless_auth_state").await;
             _ = session.remove_value("auth_state").await;
 
+            attempt::record(
+                &authn_service,
+                user_id,
+                SigninMethod::Passkey,
+                true,
+                &client,
+            )
+            .await?;
+
             // Get the user and log them in via axum_login
             let user = authn_service
                 .get_user(&user_id)
@@ -397,7 +448,7 @@ async fn handle_signin_completion(
                 .map_err(|e| SigninError::LoginFailed(e.to_string()))?;
 
             // Redirect to next or default
-            let redirect_to = next.as_deref().unwrap_or("/journal");
+            let redirect_to = next.as_deref().unwrap_or("/dashboard");
             Ok(Redirect::to(redirect_to).into_response())
         }
         Err(_) => {
@@ -405,6 +456,17 @@ async fn handle_signin_completion(
             _ = session.remove_value("identifierless_auth_state").await;
             _ = session.remove_value("auth_state").await;
 
+            if let Some(user_id) = owner {
+                attempt::record(
+                    &authn_service,
+                    user_id,
+                    SigninMethod::Passkey,
+                    false,
+                    &client,
+                )
+                .await?;
+            }
+
             // Redirect back to login with error
             Ok(Redirect::to("/signin?error=auth_failed").into_response())
         }
@@ -415,17 +477,19 @@ pub async fn signin_get(
     Extension(webauthn): Extension<Arc<Webauthn>>,
     Extension(authn_service): Extension<AuthnService>,
     Extension(webauthn_url): Extension<String>,
+    Extension(app_env): Extension<AppEnv>,
+    Extension(oidc): Extension<Option<Oidc>>,
     auth_session: AuthSession,
     query: Query<SigninQuery>,
 ) -> impl IntoResponse {
-    let next = query.next.clone();
     handle_signin_page(
         webauthn,
         authn_service,
+        app_env,
+        oidc,
         auth_session,
         webauthn_url,
         query,
-        next,
     )
     .await
 }
@@ -434,33 +498,45 @@ pub async fn signin_post(
     Extension(webauthn): Extension<Arc<Webauthn>>,
     Extension(authn_service): Extension<AuthnService>,
     auth_session: AuthSession,
+    client: ClientInfo,
     form: Form<HashMap<String, String>>,
-) -> Result<impl IntoResponse, impl IntoResponse> {
+) -> Result<Response, SigninError> {
     let next = form.get("next").cloned();
 
-    // Check for dev login first
-    if let Some(dev_user_id) = form.get("dev_user_id") {
-        return Ok(
-            handle_dev_login(authn_service, auth_session, dev_user_id, next)
-                .await
-                .into_response(),
-        );
+    handle_signin_completion(webauthn, authn_service, auth_session, client, form, next).await
+}
+
+/// the signin routes. dev login is only registered in development,
+/// so production deployments have no way to sign in without a passkey
+pub fn routes<S: Clone + Send + Sync + 'static>(app_env: AppEnv) -> Router<S> {
+    let routes = Router::new().route("/signin", get(signin_get).post(signin_post));
+
+    if app_env.dev_mode() {
+        routes.route("/signin/dev", post(dev_signin_post))
+    } else {
+        routes
     }
+}
 
-    handle_signin_completion(webauthn, authn_service, auth_session, form, next).await
+#[derive(Deserialize)]
+pub struct DevSigninForm {
+    dev_user_id: String,
+    next: Option<String>,
 }
 
-async fn handle_dev_login(
-    authn_service: AuthnService,
+pub async fn dev_signin_post(
+    Extension(authn_service): Extension<AuthnService>,
     mut auth_session: AuthSession,
-    dev_user_id: &str,
-    next: Option<String>,
-) -> Result<impl IntoResponse, Redirect> {
+    Form(form): Form<DevSigninForm>,
+) -> Result<Response, Redirect> {
     use super::user::UserId;
     use std::str::FromStr;
 
+    let next = form.next;
+
     // Parse the user ID
-    let user_id = UserId::from_str(dev_user_id).or_redirect("/signin?error=invalid_userid")?;
+    let user_id =
+        UserId::from_str(&form.dev_user_id).or_redirect("/signin?error=invalid_userid")?;
 
     // Look up the user
     let user = authn_service
@@ -479,6 +555,39 @@ async fn handle_dev_login(
     }
 
     // Redirect to next or default
-    let redirect_to = next.as_deref().unwrap_or("/journal");
+    let redirect_to = next.as_deref().unwrap_or("/dashboard");
     Ok(Redirect::to(redirect_to).into_response())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum_test::TestServer;
+
+    const DEV_LOGIN_FORM: [(&str, &str); 1] = [("dev_user_id", "0000000000000000")];
+
+    #[tokio::test]
+    async fn production_router_rejects_dev_login() {
+        let server = TestServer::new(routes::<()>(AppEnv::Production));
+
+        server
+            .post("/signin/dev")
+            .form(&DEV_LOGIN_FORM)
+            .expect_failure()
+            .await
+            .assert_status(StatusCode::NOT_FOUND);
+    }
+
+    #[tokio::test]
+    async fn development_router_registers_dev_login() {
+        let server = TestServer::new(routes::<()>(AppEnv::Development));
+
+        let response = server
+            .post("/signin/dev")
+            .form(&DEV_LOGIN_FORM)
+            .expect_failure()
+            .await;
+
+        assert_ne!(response.status_code(), StatusCode::NOT_FOUND);
+    }
+}
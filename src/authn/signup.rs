@@ -1,5 +1,5 @@
 use super::passkey::PasskeyId;
-use super::user::UserId;
+use super::user::{UserError, UserId};
 use super::{AuthSession, AuthnService};
 use crate::authn::corepasskey::CorePasskey;
 use axum::extract::Extension;
@@ -27,8 +27,11 @@ use webauthn_rs_proto::ResidentKeyRequirement;
 use crate::authority::Actor;
 use crate::authority::Authority;
 use crate::email::Email;
+use crate::i18n::t;
+use crate::monkesto_error::MonkestoError;
 use crate::theme::theme_with_head;
-use crate::time_provider::{DefaultTimeProvider, TimeProvider};
+use crate::time_provider::TimeProvider;
+use crate::validation::FormState;
 
 /// Errors that occur during the signup flow.
 #[derive(Error, Debug)]
@@ -71,9 +74,22 @@ pub struct SignupQuery {
     next: Option<String>,
 }
 
-fn email_form_page(webauthn_url: &str, error_message: Option<&str>, next: Option<&str>) -> Markup {
+/// the email field's error, in the language of the page
+fn email_error_message(error: &MonkestoError) -> &'static str {
+    match error {
+        MonkestoError::User(UserError::EmailConflict(_)) => t("signup.email_taken"),
+        _ => t("signup.invalid_email"),
+    }
+}
+
+fn email_form_page(
+    webauthn_url: &str,
+    form: &FormState,
+    error_message: Option<&str>,
+    next: Option<&str>,
+) -> Markup {
     theme_with_head(
-        Some("Sign up"),
+        Some(t("signup.title")),
         html! {
             meta name="webauthn_url" content=(webauthn_url);
         },
@@ -82,7 +98,7 @@ fn email_form_page(webauthn_url: &str, error_message: Option<&str>, next: Option
                     div class="sm:mx-auto sm:w-full sm:max-w-sm" {
                         img src="/logo.svg" alt="Monkesto" class="mx-auto h-36 w-auto";
                         h2 class="mt-10 text-center text-2xl/9 font-bold tracking-tight text-gray-900 dark:text-white" {
-                            "Sign up"
+                            (t("signup.title"))
                         }
                     }
 
@@ -92,16 +108,24 @@ fn email_form_page(webauthn_url: &str, error_message: Option<&str>, next: Option
                                 label
                                 for="email"
                                 class="block text-sm/6 font-medium text-gray-900 dark:text-gray-100" {
-                                    "Email"
+                                    (t("signup.email"))
                                 }
                                 div class="mt-2" {
                                     input
                                     id="email"
                                     name="email"
                                     type="email"
+                                    value=[form.value("email")]
                                     required
+                                    aria-invalid=[form.invalid("email")]
+                                    aria-describedby=[form.described_by("email")]
                                     class="block w-full rounded-md bg-white px-3 py-1.5 text-base text-gray-900 outline-1 -outline-offset-1 outline-gray-300 placeholder:text-gray-400 focus:outline-2 focus:-outline-offset-2 focus:outline-indigo-600 sm:text-sm/6 dark:bg-white/5 dark:text-white dark:outline-white/10 dark:placeholder:text-gray-500 dark:focus:outline-indigo-500";
                                 }
+                                @if let Some(error) = form.error("email") {
+                                    p id=[form.described_by("email")] class="mt-2 text-sm text-red-600 dark:text-red-400" {
+                                        (email_error_message(error))
+                                    }
+                                }
                             }
 
                             @if let Some(next) = next {
@@ -112,18 +136,18 @@ fn email_form_page(webauthn_url: &str, error_message: Option<&str>, next: Option
                                 button
                                 type="submit"
                                 class="flex w-full justify-center rounded-md bg-indigo-600 px-3 py-1.5 text-sm/6 font-semibold text-white shadow-xs hover:bg-indigo-500 focus-visible:outline-2 focus-visible:outline-offset-2 focus-visible:outline-indigo-600 dark:bg-indigo-500 dark:shadow-none dark:hover:bg-indigo-400 dark:focus-visible:outline-indigo-500" {
-                                    "Continue"
+                                    (t("signup.continue"))
                                 }
                             }
                         }
 
                         p class="mt-6 text-center text-sm/6 text-gray-500 dark:text-gray-400" {
-                            "Already have an account? "
+                            (t("signup.have_account")) " "
                             @let signin_url = next.map(|n| format!("signin?next={}", n)).unwrap_or_else(|| "signin".to_string());
                             a
                             href=(signin_url)
                             class="font-semibold text-indigo-600 hover:text-indigo-500 dark:text-indigo-400 dark:hover:text-indigo-300" {
-                                "Sign in here"
+                                (t("signup.sign_in_link"))
                             }
                         }
 
@@ -147,11 +171,9 @@ fn challenge_page(
     next: Option<&str>,
 ) -> Markup {
     theme_with_head(
-        Some("Create Passkey"),
+        Some(t("signup.passkey_title")),
         html! {
-            script
-                src="https://cdn.jsdelivr.net/npm/js-base64@3.7.4/base64.min.js"
-                crossorigin="anonymous" {}
+            script src="/base64.js" {}
             meta name="webauthn_url" content=(webauthn_url);
             script id="challenge-data" type="application/json" {
                 (PreEscaped(challenge_data))
@@ -221,10 +243,10 @@ fn challenge_page(
                     div class="sm:mx-auto sm:w-full sm:max-w-sm" {
                         img src="/logo.svg" alt="Monkesto" class="mx-auto h-36 w-auto";
                         h2 class="mt-10 text-center text-2xl/9 font-bold tracking-tight text-gray-900 dark:text-white" {
-                            "Create Your Passkey"
+                            (t("signup.passkey_heading"))
                         }
                         p class="mt-2 text-center text-sm/6 text-gray-600 dark:text-gray-400" {
-                            "Email: " strong { (email) }
+                            (t("signup.email")) ": " strong { (email) }
                         }
                     }
 
@@ -240,7 +262,7 @@ fn challenge_page(
 
                         div class="text-center" {
                             p id="status_message" class="text-lg text-gray-900 dark:text-white" {
-                                "Please follow your device's prompts to create your passkey"
+                                (t("signup.follow_prompts"))
                             }
 
                             div class="mt-6" {
@@ -260,16 +282,17 @@ async fn handle_signup_get(
 ) -> impl IntoResponse {
     // Handle error messages from query parameters
     let error_message = match query.error.as_deref() {
-        Some("email_taken") => {
-            Some("Email is already registered. Please use another email address.")
-        }
-        Some("invalid_email") => Some("Invalid email format. Please enter a valid email address."),
-        Some("session_expired") => Some("Your sign up session has expired. Please try again."),
-        Some("registration_failed") => Some("Sign up failed. Please try again."),
+        Some("session_expired") => Some(t("signup.session_expired")),
+        Some("registration_failed") => Some(t("signup.failed")),
         _ => None,
     };
 
-    let markup = email_form_page(&webauthn_url, error_message, next.as_deref());
+    let markup = email_form_page(
+        &webauthn_url,
+        &FormState::default(),
+        error_message,
+        next.as_deref(),
+    );
     (
         StatusCode::OK,
         [(header::CONTENT_TYPE, "text/html")],
@@ -279,22 +302,16 @@ async fn handle_signup_get(
 
 async fn handle_email_submission(
     webauthn: Arc<Webauthn>,
-    authn_service: AuthnService,
     auth_session: AuthSession,
     webauthn_url: String,
     email: Email,
     next: Option<String>,
 ) -> Result<Response, SignupError> {
-    // Check if email is already taken
-    if authn_service.email_exists(&email).await.unwrap_or(false) {
-        return Ok(Redirect::to("/signup?error=email_taken").into_response());
-    }
-
     // Get existing credentials for exclusion
     let exclude_credentials = None; // New user, no existing credentials to exclude
 
     // Generate new user ID (our internal identifier)
-    let user_id = UserId::new();
+    let user_id: UserId = auth_session.backend.ids().generate();
 
     // Generate webauthn UUID (for webauthn-rs compatibility)
     let webauthn_uuid = Uuid::new_v4();
@@ -385,7 +402,7 @@ async fn handle_credential_submission(
             _ = session.remove_value("reg_state").await;
 
             // Generate a PasskeyId for this passkey
-            let passkey_id = PasskeyId::new();
+            let passkey_id: PasskeyId = authn_service.ids().generate();
 
             // Store the new user and their passkey
             let email_validated = Email::try_new(&email).map_err(|_| SignupError::InvalidInput)?;
@@ -396,7 +413,7 @@ async fn handle_credential_submission(
                     email_validated.clone(),
                     webauthn_uuid,
                     Authority::Direct(Actor::Anonymous),
-                    DefaultTimeProvider.get_time(),
+                    authn_service.clock().get_time(),
                 )
                 .await
                 .map_err(|e| SignupError::LoginFailed(e.to_string()))?;
@@ -407,7 +424,7 @@ async fn handle_credential_submission(
                     user_id,
                     CorePasskey(passkey),
                     Authority::Direct(Actor::User(user_id)),
-                    DefaultTimeProvider.get_time(),
+                    authn_service.clock().get_time(),
                 )
                 .await
                 .map_err(|e| SignupError::LoginFailed(e.to_string()))?;
@@ -417,6 +434,11 @@ async fn handle_credential_submission(
                 id: user_id,
                 webauthn_uuid,
                 email: email_validated,
+                timezone_preference: None,
+                theme_preference: None,
+                shortcuts_preference: None,
+                name: None,
+                gravatar: false,
             };
             auth_session
                 .login(&user)
@@ -426,7 +448,7 @@ async fn handle_credential_submission(
             authn_service.wait_for(ev_id).await;
 
             // Redirect to next or default
-            let redirect_to = next.as_deref().unwrap_or("/journal");
+            let redirect_to = next.as_deref().unwrap_or("/dashboard");
             Ok(Redirect::to(redirect_to).into_response())
         }
         Err(_) => {
@@ -457,20 +479,20 @@ pub async fn signup_post(
     if let Some(_credential_json) = form.get("credential") {
         handle_credential_submission(webauthn, authn_service, auth_session, form, next).await
     } else if let Some(email_str) = form.get("email") {
-        let email = match Email::try_new(email_str) {
-            Ok(em) => em,
-            Err(_) => return Err(SignupError::InvalidInput),
-        };
-
-        handle_email_submission(
-            webauthn,
-            authn_service,
-            auth_session,
-            webauthn_url,
-            email,
-            next,
-        )
-        .await
+        let mut form_state = FormState::default();
+        form_state.keep("email", email_str.as_str());
+
+        if let Some(email) = form_state.check("email", Email::try_new(email_str)) {
+            if authn_service.email_exists(&email).await.unwrap_or(false) {
+                form_state.reject("email", UserError::EmailConflict(email));
+            } else {
+                return handle_email_submission(webauthn, auth_session, webauthn_url, email, next)
+                    .await;
+            }
+        }
+
+        let page = email_form_page(&webauthn_url, &form_state, None, next.as_deref());
+        Ok(form_state.rejected(page).into_response())
     } else {
         Err(SignupError::InvalidInput)
     }
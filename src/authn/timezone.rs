@@ -0,0 +1,206 @@
+use super::user::{User, UserError};
+use super::{AuthSession, AuthnEvent, AuthnService, UserId};
+use crate::authority::{Actor, Authority};
+use crate::monkesto_error::{ErrorRedirect, OrRedirect};
+use crate::time_provider::{TimeProvider, Timestamp};
+use axum::extract::{Extension, Form};
+use axum::response::Redirect;
+use chrono::NaiveDate;
+use chrono_tz::Tz;
+use disintegrate::Decision;
+use serde::{Deserialize, Serialize};
+use sqlx::error::BoxDynError;
+use sqlx::{Database, Postgres};
+use std::fmt::Display;
+use std::str::FromStr;
+
+/// the timezone a user sees times in, and which decides what day it is for them
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct Timezone(Tz);
+
+impl Timezone {
+    /// every timezone a user can choose
+    pub fn all() -> impl Iterator<Item = Self> {
+        chrono_tz::TZ_VARIANTS.into_iter().map(Self)
+    }
+
+    pub fn tz(self) -> Tz {
+        self.0
+    }
+
+    /// the user's date at the instant
+    pub fn date(self, at: Timestamp) -> NaiveDate {
+        at.with_timezone(&self.0).date_naive()
+    }
+
+    /// the instant as the user's local date and time
+    pub fn format(self, at: Timestamp) -> String {
+        at.with_timezone(&self.0)
+            .format("%Y-%m-%d %H:%M:%S %Z")
+            .to_string()
+    }
+}
+
+/// times were shown in central time before users could choose
+impl Default for Timezone {
+    fn default() -> Self {
+        Self(chrono_tz::America::Chicago)
+    }
+}
+
+impl Display for Timezone {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.0.name())
+    }
+}
+
+impl FromStr for Timezone {
+    type Err = UserError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Tz::from_str(s)
+            .map(Self)
+            .map_err(|_| UserError::InvalidTimezone(s.to_string()))
+    }
+}
+
+impl TryFrom<String> for Timezone {
+    type Error = UserError;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        value.parse()
+    }
+}
+
+impl From<Timezone> for String {
+    fn from(value: Timezone) -> Self {
+        value.to_string()
+    }
+}
+
+impl sqlx::Type<Postgres> for Timezone {
+    fn type_info() -> <Postgres as Database>::TypeInfo {
+        <&str as sqlx::Type<Postgres>>::type_info()
+    }
+}
+
+impl<'r> sqlx::Decode<'r, Postgres> for Timezone {
+    fn decode(value: <Postgres as Database>::ValueRef<'r>) -> Result<Self, BoxDynError> {
+        Ok(<&str as sqlx::Decode<Postgres>>::decode(value)?.parse()?)
+    }
+}
+
+pub struct SetTimezone {
+    user_id: UserId,
+    timezone: Timezone,
+    authority: Authority,
+    timestamp: Timestamp,
+}
+
+impl SetTimezone {
+    pub fn new(
+        user_id: UserId,
+        timezone: Timezone,
+        authority: Authority,
+        timestamp: Timestamp,
+    ) -> Self {
+        Self {
+            user_id,
+            timezone,
+            authority,
+            timestamp,
+        }
+    }
+}
+
+impl Decision for SetTimezone {
+    type Event = AuthnEvent;
+    type StateQuery = User;
+    type Error = UserError;
+
+    fn state_query(&self) -> Self::StateQuery {
+        User::new(self.user_id)
+    }
+
+    fn process(&self, user: &Self::StateQuery) -> Result<Vec<Self::Event>, Self::Error> {
+        if !user.status.valid() {
+            return Err(UserError::UserDoesntExist(self.user_id));
+        }
+
+        Ok(vec![AuthnEvent::TimezoneChanged {
+            user_id: self.user_id,
+            timezone: self.timezone,
+            authority: self.authority.clone(),
+            timestamp: self.timestamp,
+        }])
+    }
+}
+
+#[derive(Deserialize)]
+pub struct TimezoneForm {
+    timezone: String,
+}
+
+pub async fn timezone_post(
+    Extension(authn_service): Extension<AuthnService>,
+    auth_session: AuthSession,
+    Form(form): Form<TimezoneForm>,
+) -> Result<Redirect, ErrorRedirect> {
+    const CALLBACK_URL: &str = "/me";
+
+    let user_id = auth_session
+        .user
+        .map(|user| user.id)
+        .ok_or(UserError::SessionNotFound)
+        .or_redirect(CALLBACK_URL)?;
+
+    let timezone = Timezone::from_str(&form.timezone).or_redirect(CALLBACK_URL)?;
+
+    let event_id = authn_service
+        .set_timezone(
+            user_id,
+            timezone,
+            Authority::Direct(Actor::User(user_id)),
+            authn_service.clock().get_time(),
+        )
+        .await
+        .or_redirect(CALLBACK_URL)?;
+
+    authn_service.wait_for(event_id).await;
+
+    Ok(Redirect::to(CALLBACK_URL))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{TimeZone, Utc};
+
+    #[test]
+    fn the_date_depends_on_the_users_timezone() {
+        let tokyo = Timezone::from_str("Asia/Tokyo").expect("invalid timezone");
+        let los_angeles = Timezone::from_str("America/Los_Angeles").expect("invalid timezone");
+
+        // late on the 17th in los angeles is already the 18th in tokyo
+        let at = Utc
+            .with_ymd_and_hms(2026, 10, 18, 5, 30, 0)
+            .single()
+            .expect("invalid time");
+
+        assert_eq!(
+            tokyo.date(at),
+            NaiveDate::from_ymd_opt(2026, 10, 18).expect("invalid date")
+        );
+        assert_eq!(
+            los_angeles.date(at),
+            NaiveDate::from_ymd_opt(2026, 10, 17).expect("invalid date")
+        );
+
+        assert_eq!(tokyo.to_string().parse(), Ok(tokyo));
+        assert_eq!(
+            Timezone::from_str("Mars/Olympus_Mons"),
+            Err(UserError::InvalidTimezone("Mars/Olympus_Mons".to_string()))
+        );
+    }
+}
@@ -0,0 +1,803 @@
+use super::attempt::{self, ClientInfo, SigninMethod};
+use super::layout::layout;
+use super::signin::SigninError;
+use super::user::{User, UserError, UserState};
+pub(crate) use super::{AuthSession, AuthnEvent, AuthnService, UserEvent, UserId};
+use crate::authority::{Actor, Authority};
+use crate::monkesto_error::{ErrorRedirect, MonkestoError, OrRedirect, UrlError};
+use crate::theme::theme_with_head;
+use crate::time_provider::{TimeProvider, Timestamp};
+use axum::extract::{Extension, Form, Query};
+use axum::response::Redirect;
+use disintegrate::{Decision, StateMutate, StateQuery};
+use maud::{Markup, PreEscaped, html};
+use qrcode::QrCode;
+use qrcode::render::svg;
+use rand::Rng;
+use rand::distr::Alphanumeric;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use totp_rs::{Algorithm, Secret, TOTP};
+
+const ISSUER: &str = "Monkesto";
+const RECOVERY_CODE_COUNT: usize = 10;
+/// failed codes allowed before the user has to start their signin over
+const MAX_SIGNIN_ATTEMPTS: u32 = 5;
+
+const PENDING_SECRET_KEY: &str = "totp_pending_secret";
+const PENDING_SIGNIN_KEY: &str = "totp_pending_signin";
+
+/// where a user's TOTP secret is kept. it's named for the user, since each has at most one
+pub fn totp_seed(user_id: UserId) -> crate::secret::Secret<String> {
+    crate::secret::Secret::new(format!("user/{user_id}/totp_seed"))
+}
+
+pub fn generate_secret() -> String {
+    Secret::generate_secret().to_encoded().to_string()
+}
+
+fn totp(secret: &str, account_name: &str) -> Option<TOTP> {
+    let secret = Secret::Encoded(secret.to_string()).to_bytes().ok()?;
+
+    TOTP::new(
+        Algorithm::SHA1,
+        6,
+        1,
+        30,
+        secret,
+        Some(ISSUER.to_string()),
+        account_name.to_string(),
+    )
+    .ok()
+}
+
+/// checks a code from an authenticator app, allowing one step of clock drift either way
+pub fn verify_code(secret: &str, code: &str) -> bool {
+    let code: String = code.chars().filter(|c| !c.is_whitespace()).collect();
+
+    totp(secret, "")
+        .and_then(|totp| totp.check_current(&code).ok())
+        .unwrap_or(false)
+}
+
+pub fn generate_recovery_codes() -> Vec<String> {
+    (0..RECOVERY_CODE_COUNT)
+        .map(|_| {
+            let code: String = rand::rng()
+                .sample_iter(&Alphanumeric)
+                .take(10)
+                .map(|c| char::from(c).to_ascii_lowercase())
+                .collect();
+            format!("{}-{}", &code[..5], &code[5..])
+        })
+        .collect()
+}
+
+/// recovery codes are stored hashed, so they're normalized first to tolerate how people retype them
+pub fn hash_recovery_code(code: &str) -> String {
+    let normalized: String = code
+        .chars()
+        .filter(|c| c.is_ascii_alphanumeric())
+        .map(|c| c.to_ascii_lowercase())
+        .collect();
+
+    hex::encode(Sha256::digest(normalized.as_bytes()))
+}
+
+#[derive(Debug, StateQuery, Clone, Serialize, Deserialize, Default)]
+#[state_query(UserEvent)]
+pub struct UserTotp {
+    #[id]
+    user_id: UserId,
+    enabled: bool,
+    recovery_codes: Vec<String>,
+}
+
+impl UserTotp {
+    fn new(user_id: UserId) -> Self {
+        Self {
+            user_id,
+            ..Default::default()
+        }
+    }
+}
+
+impl StateMutate for UserTotp {
+    fn mutate(&mut self, event: Self::Event) {
+        match event {
+            UserEvent::TotpEnabled { recovery_codes, .. } => {
+                self.enabled = true;
+                self.recovery_codes = recovery_codes;
+            }
+            UserEvent::TotpDisabled { .. } => {
+                self.enabled = false;
+                self.recovery_codes.clear();
+            }
+            UserEvent::RecoveryCodeUsed { recovery_code, .. } => {
+                self.recovery_codes.retain(|code| *code != recovery_code);
+            }
+            UserEvent::UserCreated { .. }
+            | UserEvent::UserDeleted { .. }
+            | UserEvent::DigestFrequencyChanged { .. }
+            | UserEvent::TimezoneChanged { .. }
+            | UserEvent::ThemeChanged { .. }
+            | UserEvent::ShortcutsChanged { .. }
+            | UserEvent::DisplayNameChanged { .. }
+            | UserEvent::AvatarChanged { .. } => {}
+        }
+    }
+}
+
+pub struct EnableTotp {
+    user_id: UserId,
+    recovery_codes: Vec<String>,
+    authority: Authority,
+    timestamp: Timestamp,
+}
+
+impl EnableTotp {
+    pub fn new(
+        user_id: UserId,
+        recovery_codes: Vec<String>,
+        authority: Authority,
+        timestamp: Timestamp,
+    ) -> Self {
+        Self {
+            user_id,
+            recovery_codes,
+            authority,
+            timestamp,
+        }
+    }
+}
+
+impl Decision for EnableTotp {
+    type Event = AuthnEvent;
+    type StateQuery = (User, UserTotp);
+    type Error = UserError;
+
+    fn state_query(&self) -> Self::StateQuery {
+        (User::new(self.user_id), UserTotp::new(self.user_id))
+    }
+
+    fn process(&self, (user, totp): &Self::StateQuery) -> Result<Vec<Self::Event>, Self::Error> {
+        if !user.status.valid() {
+            return Err(UserError::UserDoesntExist(self.user_id));
+        }
+
+        if totp.enabled {
+            return Err(UserError::TotpAlreadyEnabled(self.user_id));
+        }
+
+        Ok(vec![AuthnEvent::TotpEnabled {
+            user_id: self.user_id,
+            recovery_codes: self.recovery_codes.clone(),
+            authority: self.authority.clone(),
+            timestamp: self.timestamp,
+        }])
+    }
+}
+
+pub struct DisableTotp {
+    user_id: UserId,
+    authority: Authority,
+    timestamp: Timestamp,
+}
+
+impl DisableTotp {
+    pub fn new(user_id: UserId, authority: Authority, timestamp: Timestamp) -> Self {
+        Self {
+            user_id,
+            authority,
+            timestamp,
+        }
+    }
+}
+
+impl Decision for DisableTotp {
+    type Event = AuthnEvent;
+    type StateQuery = UserTotp;
+    type Error = UserError;
+
+    fn state_query(&self) -> Self::StateQuery {
+        UserTotp::new(self.user_id)
+    }
+
+    fn process(&self, totp: &Self::StateQuery) -> Result<Vec<Self::Event>, Self::Error> {
+        if !totp.enabled {
+            return Err(UserError::TotpNotEnabled(self.user_id));
+        }
+
+        Ok(vec![AuthnEvent::TotpDisabled {
+            user_id: self.user_id,
+            authority: self.authority.clone(),
+            timestamp: self.timestamp,
+        }])
+    }
+}
+
+pub struct UseRecoveryCode {
+    user_id: UserId,
+    /// the hash of the code the user entered
+    recovery_code: String,
+    authority: Authority,
+    timestamp: Timestamp,
+}
+
+impl UseRecoveryCode {
+    pub fn new(
+        user_id: UserId,
+        recovery_code: String,
+        authority: Authority,
+        timestamp: Timestamp,
+    ) -> Self {
+        Self {
+            user_id,
+            recovery_code,
+            authority,
+            timestamp,
+        }
+    }
+}
+
+impl Decision for UseRecoveryCode {
+    type Event = AuthnEvent;
+    type StateQuery = UserTotp;
+    type Error = UserError;
+
+    fn state_query(&self) -> Self::StateQuery {
+        UserTotp::new(self.user_id)
+    }
+
+    fn process(&self, totp: &Self::StateQuery) -> Result<Vec<Self::Event>, Self::Error> {
+        if !totp.enabled {
+            return Err(UserError::TotpNotEnabled(self.user_id));
+        }
+
+        if !totp.recovery_codes.contains(&self.recovery_code) {
+            return Err(UserError::InvalidTotpCode);
+        }
+
+        Ok(vec![AuthnEvent::RecoveryCodeUsed {
+            user_id: self.user_id,
+            recovery_code: self.recovery_code.clone(),
+            authority: self.authority.clone(),
+            timestamp: self.timestamp,
+        }])
+    }
+}
+
+/// checks a code from the user's authenticator app, falling back to consuming a recovery code
+async fn verify_second_factor(
+    authn_service: &AuthnService,
+    user_id: UserId,
+    code: &str,
+) -> Result<(), MonkestoError> {
+    let secret = authn_service
+        .totp_secret(user_id)
+        .await?
+        .ok_or(UserError::TotpNotEnabled(user_id))?;
+
+    if verify_code(&secret, code) {
+        return Ok(());
+    }
+
+    let event_id = authn_service
+        .use_recovery_code(
+            user_id,
+            code,
+            Authority::Direct(Actor::User(user_id)),
+            authn_service.clock().get_time(),
+        )
+        .await?;
+
+    authn_service.wait_for(event_id).await;
+
+    Ok(())
+}
+
+/// a user who has passed their first factor, but still needs to enter a code
+#[derive(Serialize, Deserialize)]
+struct PendingSignin {
+    user_id: UserId,
+    next: Option<String>,
+    attempts: u32,
+}
+
+/// logs the user in, or sends them to enter a code first if they've enabled a second factor
+pub async fn complete_signin(
+    authn_service: &AuthnService,
+    auth_session: &mut AuthSession,
+    user: UserState,
+    next: Option<String>,
+    method: SigninMethod,
+    client: &ClientInfo,
+) -> Result<Redirect, SigninError> {
+    attempt::ensure_unlocked(authn_service, user.id).await?;
+
+    let totp_enabled = authn_service
+        .totp_secret(user.id)
+        .await
+        .map_err(|e| SigninError::StoreError(e.to_string()))?
+        .is_some();
+
+    if totp_enabled {
+        auth_session
+            .session
+            .insert(
+                PENDING_SIGNIN_KEY,
+                PendingSignin {
+                    user_id: user.id,
+                    next,
+                    attempts: 0,
+                },
+            )
+            .await?;
+
+        return Ok(Redirect::to("/signin/totp"));
+    }
+
+    attempt::record(authn_service, user.id, method, true, client).await?;
+
+    auth_session
+        .login(&user)
+        .await
+        .map_err(|e| SigninError::LoginFailed(e.to_string()))?;
+
+    Ok(Redirect::to(next.as_deref().unwrap_or("/dashboard")))
+}
+
+#[derive(Deserialize)]
+pub struct CodeForm {
+    code: String,
+}
+
+fn code_input() -> Markup {
+    html! {
+        input
+            type="text"
+            name="code"
+            required
+            autocomplete="one-time-code"
+            class="block w-full rounded-md bg-white px-3 py-1.5 text-base text-gray-900 outline-1 -outline-offset-1 outline-gray-300 placeholder:text-gray-400 focus:outline-2 focus:-outline-offset-2 focus:outline-indigo-600 sm:text-sm/6 dark:bg-white/5 dark:text-white dark:outline-white/10 dark:placeholder:text-gray-500 dark:focus:outline-indigo-500";
+    }
+}
+
+fn error_message(err: Option<String>) -> Markup {
+    html! {
+        @if let Some(e) = err {
+            p class="text-center text-sm/6 text-red-500" {
+                (MonkestoError::decode(&e).to_string())
+            }
+        }
+    }
+}
+
+fn totp_signin_page(err: Option<String>) -> Markup {
+    theme_with_head(
+        Some("Two-factor authentication"),
+        html! {},
+        html! {
+            div class="flex min-h-full flex-col justify-center px-6 py-12 lg:px-8" {
+                div class="sm:mx-auto sm:w-full sm:max-w-sm" {
+                    img src="/logo.svg" alt="Monkesto" class="mx-auto h-36 w-auto";
+
+                    h2 class="mt-10 text-center text-2xl/9 font-bold tracking-tight text-gray-900 dark:text-white" {
+                        "Two-factor authentication"
+                    }
+                }
+
+                div class="mt-10 sm:mx-auto sm:w-full sm:max-w-sm space-y-6" {
+                    form method="POST" action="/signin/totp" class="space-y-6" {
+                        label for="code" class="block text-sm/6 font-medium text-gray-900 dark:text-gray-100" {
+                            "Enter the code from your authenticator app, or one of your recovery codes"
+                        }
+                        (code_input())
+                        button
+                            type="submit"
+                            class="flex w-full justify-center rounded-md bg-indigo-600 px-3 py-1.5 text-sm/6 font-semibold text-white shadow-xs hover:bg-indigo-500 focus-visible:outline-2 focus-visible:outline-offset-2 focus-visible:outline-indigo-600 dark:bg-indigo-500 dark:shadow-none dark:hover:bg-indigo-400 dark:focus-visible:outline-indigo-500" {
+                            "Verify"
+                        }
+                    }
+
+                    (error_message(err))
+                }
+            }
+        },
+    )
+}
+
+pub async fn totp_signin_get(
+    auth_session: AuthSession,
+    Query(err): Query<UrlError>,
+) -> Result<Markup, SigninError> {
+    auth_session
+        .session
+        .get::<PendingSignin>(PENDING_SIGNIN_KEY)
+        .await?
+        .ok_or(SigninError::SessionExpired)?;
+
+    Ok(totp_signin_page(err.err))
+}
+
+pub async fn totp_signin_post(
+    Extension(authn_service): Extension<AuthnService>,
+    mut auth_session: AuthSession,
+    client: ClientInfo,
+    Form(form): Form<CodeForm>,
+) -> Result<Redirect, SigninError> {
+    let mut pending: PendingSignin = auth_session
+        .session
+        .remove(PENDING_SIGNIN_KEY)
+        .await?
+        .ok_or(SigninError::SessionExpired)?;
+
+    // a locked account's codes aren't checked, so guessing can't continue through the lock
+    attempt::ensure_unlocked(&authn_service, pending.user_id).await?;
+
+    if let Err(e) = verify_second_factor(&authn_service, pending.user_id, &form.code).await {
+        attempt::record(
+            &authn_service,
+            pending.user_id,
+            SigninMethod::TwoFactor,
+            false,
+            &client,
+        )
+        .await?;
+
+        pending.attempts += 1;
+
+        // codes are short enough to guess, so the first factor has to be repeated after a few misses
+        if pending.attempts >= MAX_SIGNIN_ATTEMPTS {
+            return Err(SigninError::AuthenticationFailed);
+        }
+
+        auth_session
+            .session
+            .insert(PENDING_SIGNIN_KEY, pending)
+            .await?;
+
+        return Ok(e.redirect("/signin/totp"));
+    }
+
+    attempt::record(
+        &authn_service,
+        pending.user_id,
+        SigninMethod::TwoFactor,
+        true,
+        &client,
+    )
+    .await?;
+
+    let user = authn_service
+        .fetch_user(pending.user_id)
+        .await
+        .map_err(|_| SigninError::UserNotFound)?;
+
+    auth_session
+        .login(&user)
+        .await
+        .map_err(|e| SigninError::LoginFailed(e.to_string()))?;
+
+    Ok(Redirect::to(
+        pending.next.as_deref().unwrap_or("/dashboard"),
+    ))
+}
+
+fn nav_title() -> Option<Markup> {
+    Some(html! {
+        span class="text-sm font-medium text-gray-700 dark:text-gray-300" {
+            "Two-factor authentication"
+        }
+    })
+}
+
+fn setup_page(qr_code: &str, secret: &str, err: Option<String>) -> Markup {
+    let content = html! {
+        div class="flex flex-col gap-6 sm:mx-auto sm:w-full sm:max-w-sm" {
+        div class="bg-white dark:bg-gray-800 rounded-lg shadow p-6 space-y-4" {
+            p class="text-sm text-gray-600 dark:text-gray-400" {
+                "Scan this code with your authenticator app, then enter the code it shows to finish setting up two-factor authentication."
+            }
+
+            div class="flex justify-center bg-white p-2 rounded" {
+                (PreEscaped(qr_code))
+            }
+
+            p class="text-xs text-gray-500 dark:text-gray-400 break-all" {
+                "Can't scan it? Enter this key instead: "
+                span class="font-mono" { (secret) }
+            }
+
+            form method="POST" action="/totp/enable" class="space-y-4" {
+                (code_input())
+                button
+                    type="submit"
+                    class="flex w-full justify-center rounded-md bg-green-600 px-3 py-1.5 text-sm/6 font-semibold text-white shadow-xs hover:bg-green-500 focus-visible:outline-2 focus-visible:outline-offset-2 focus-visible:outline-green-600 dark:bg-green-500 dark:shadow-none dark:hover:bg-green-400 dark:focus-visible:outline-green-500" {
+                    "Enable"
+                }
+            }
+
+            (error_message(err))
+        }
+        }
+    };
+
+    layout(nav_title(), content)
+}
+
+pub async fn totp_setup_get(
+    Extension(authn_service): Extension<AuthnService>,
+    auth_session: AuthSession,
+    Query(err): Query<UrlError>,
+) -> Result<Markup, ErrorRedirect> {
+    let user = auth_session
+        .user
+        .ok_or(UserError::SessionNotFound)
+        .or_redirect("/me")?;
+
+    if authn_service
+        .totp_secret(user.id)
+        .await
+        .or_redirect("/me")?
+        .is_some()
+    {
+        return Err(ErrorRedirect::new(
+            UserError::TotpAlreadyEnabled(user.id),
+            "/me",
+        ));
+    }
+
+    // the secret is kept in the session until the user proves their app has it
+    let session = &auth_session.session;
+    let secret = match session.get::<String>(PENDING_SECRET_KEY).await {
+        Ok(Some(secret)) => secret,
+        _ => {
+            let secret = generate_secret();
+            _ = session.insert(PENDING_SECRET_KEY, &secret).await;
+            secret
+        }
+    };
+
+    let qr_code = totp(&secret, user.email.as_ref())
+        .and_then(|totp| QrCode::new(totp.get_url()).ok())
+        .map(|qr| qr.render::<svg::Color>().min_dimensions(200, 200).build())
+        .unwrap_or_default();
+
+    Ok(setup_page(&qr_code, &secret, err.err))
+}
+
+fn recovery_codes_page(codes: &[String]) -> Markup {
+    let content = html! {
+        div class="flex flex-col gap-6 sm:mx-auto sm:w-full sm:max-w-sm" {
+        div class="bg-white dark:bg-gray-800 rounded-lg shadow p-6 space-y-4" {
+            h3 class="text-lg font-medium text-gray-900 dark:text-white" {
+                "Save your recovery codes"
+            }
+            p class="text-sm text-gray-600 dark:text-gray-400" {
+                "Each code can be used once to sign in if you lose your authenticator app. They won't be shown again."
+            }
+            ul class="grid grid-cols-2 gap-2 font-mono text-sm text-gray-900 dark:text-white" {
+                @for code in codes {
+                    li { (code) }
+                }
+            }
+            a
+                href="/me"
+                class="flex w-full justify-center rounded-md bg-indigo-600 px-3 py-1.5 text-sm/6 font-semibold text-white shadow-xs hover:bg-indigo-500 focus-visible:outline-2 focus-visible:outline-offset-2 focus-visible:outline-indigo-600 dark:bg-indigo-500 dark:shadow-none dark:hover:bg-indigo-400 dark:focus-visible:outline-indigo-500" {
+                "Done"
+            }
+        }
+        }
+    };
+
+    layout(nav_title(), content)
+}
+
+pub async fn totp_enable_post(
+    Extension(authn_service): Extension<AuthnService>,
+    auth_session: AuthSession,
+    Form(form): Form<CodeForm>,
+) -> Result<Markup, ErrorRedirect> {
+    const CALLBACK_URL: &str = "/totp/setup";
+
+    let user_id = auth_session
+        .user
+        .map(|user| user.id)
+        .ok_or(UserError::SessionNotFound)
+        .or_redirect("/me")?;
+
+    let secret = match auth_session.session.get::<String>(PENDING_SECRET_KEY).await {
+        Ok(Some(secret)) => secret,
+        _ => return Err(ErrorRedirect::new(UserError::InvalidTotpCode, CALLBACK_URL)),
+    };
+
+    if !verify_code(&secret, &form.code) {
+        return Err(ErrorRedirect::new(UserError::InvalidTotpCode, CALLBACK_URL));
+    }
+
+    let recovery_codes = generate_recovery_codes();
+
+    let event_id = authn_service
+        .enable_totp(
+            user_id,
+            secret,
+            recovery_codes
+                .iter()
+                .map(|code| hash_recovery_code(code))
+                .collect(),
+            Authority::Direct(Actor::User(user_id)),
+            authn_service.clock().get_time(),
+        )
+        .await
+        .or_redirect(CALLBACK_URL)?;
+
+    authn_service.wait_for(event_id).await;
+
+    _ = auth_session.session.remove_value(PENDING_SECRET_KEY).await;
+
+    Ok(recovery_codes_page(&recovery_codes))
+}
+
+pub async fn totp_disable_post(
+    Extension(authn_service): Extension<AuthnService>,
+    auth_session: AuthSession,
+    Form(form): Form<CodeForm>,
+) -> Result<Redirect, ErrorRedirect> {
+    const CALLBACK_URL: &str = "/me";
+
+    let user_id = auth_session
+        .user
+        .map(|user| user.id)
+        .ok_or(UserError::SessionNotFound)
+        .or_redirect(CALLBACK_URL)?;
+
+    // turning the second factor off needs the second factor, so a hijacked session can't do it
+    verify_second_factor(&authn_service, user_id, &form.code)
+        .await
+        .or_redirect(CALLBACK_URL)?;
+
+    let event_id = authn_service
+        .disable_totp(
+            user_id,
+            Authority::Direct(Actor::User(user_id)),
+            authn_service.clock().get_time(),
+        )
+        .await
+        .or_redirect(CALLBACK_URL)?;
+
+    authn_service.wait_for(event_id).await;
+
+    Ok(Redirect::to(CALLBACK_URL))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::authn::AuthnEvent;
+    use crate::authority::{Actor, Authority};
+    use crate::test_app::TestApp;
+    use chrono::Utc;
+
+    fn enabled(user_id: UserId, codes: &[String]) -> UserTotp {
+        let mut totp = UserTotp::new(user_id);
+        totp.mutate(UserEvent::TotpEnabled {
+            user_id,
+            recovery_codes: codes.iter().map(|code| hash_recovery_code(code)).collect(),
+            authority: Authority::Direct(Actor::User(user_id)),
+            timestamp: Utc::now(),
+        });
+        totp
+    }
+
+    #[test]
+    fn recovery_codes_can_only_be_used_once() {
+        let user_id = UserId::new();
+        let codes = generate_recovery_codes();
+        let mut totp = enabled(user_id, &codes);
+
+        // codes are accepted however they're capitalized or punctuated
+        let retyped = codes[0].to_uppercase().replace('-', " ");
+        let decision = UseRecoveryCode::new(
+            user_id,
+            hash_recovery_code(&retyped),
+            Authority::Direct(Actor::User(user_id)),
+            Utc::now(),
+        );
+
+        for event in decision
+            .process(&totp)
+            .expect("the recovery code was rejected")
+        {
+            let AuthnEvent::RecoveryCodeUsed {
+                user_id,
+                recovery_code,
+                authority,
+                timestamp,
+            } = event
+            else {
+                panic!("unexpected event");
+            };
+
+            totp.mutate(UserEvent::RecoveryCodeUsed {
+                user_id,
+                recovery_code,
+                authority,
+                timestamp,
+            });
+        }
+
+        assert_eq!(decision.process(&totp), Err(UserError::InvalidTotpCode));
+        assert_eq!(totp.recovery_codes.len(), RECOVERY_CODE_COUNT - 1);
+    }
+
+    #[test]
+    fn codes_from_the_current_window_are_accepted() {
+        let secret = generate_secret();
+        let code = totp(&secret, "")
+            .and_then(|totp| totp.generate_current().ok())
+            .expect("failed to generate a code");
+
+        assert!(verify_code(&secret, &code));
+        assert!(!verify_code(&secret, "not a code"));
+    }
+
+    #[tokio::test]
+    async fn totp_secrets_are_kept_out_of_the_events() {
+        let Some(app) = TestApp::spawn().await else {
+            return;
+        };
+
+        let user = app.signup("totp").await;
+        let authn = &app.state.authn_service;
+        let authority = Authority::Direct(Actor::User(user.id));
+        let secret = "JBSWY3DPEHPK3PXPJBSWY3DPEHPK3PXP".to_string();
+
+        let event_id = authn
+            .enable_totp(
+                user.id,
+                secret.clone(),
+                vec![],
+                authority.clone(),
+                app.state.clock.get_time(),
+            )
+            .await
+            .expect("failed to enable totp");
+        authn.wait_for(event_id).await;
+
+        assert_eq!(
+            authn.totp_secret(user.id).await.unwrap(),
+            Some(secret.clone())
+        );
+        let events = authn.user_events(user.id).await.unwrap();
+        assert!(
+            events
+                .iter()
+                .any(|event| matches!(event, AuthnEvent::TotpEnabled { .. }))
+        );
+        assert!(!format!("{events:?}").contains(&secret));
+
+        // enabling it twice fails without replacing the secret in use
+        assert!(
+            authn
+                .enable_totp(
+                    user.id,
+                    "KRSXG5CTMVRXEZLU".to_string(),
+                    vec![],
+                    authority.clone(),
+                    app.state.clock.get_time(),
+                )
+                .await
+                .is_err()
+        );
+        assert_eq!(authn.totp_secret(user.id).await.unwrap(), Some(secret));
+
+        let event_id = authn
+            .disable_totp(user.id, authority, app.state.clock.get_time())
+            .await
+            .expect("failed to disable totp");
+        authn.wait_for(event_id).await;
+
+        assert_eq!(authn.totp_secret(user.id).await.unwrap(), None);
+    }
+}
@@ -1,13 +1,17 @@
+use super::appearance::Theme;
+use super::shortcuts::Shortcuts;
+use super::timezone::Timezone;
 pub(crate) use super::{AuthnEvent, UserEvent, UserId};
 use crate::authority::Authority;
 use crate::email::Email;
+use crate::name::Name;
+use crate::secret::SecretError;
 use crate::time_provider::Timestamp;
 use disintegrate::{Decision, StateMutate, StateQuery};
 use serde::Deserialize;
 use serde::Serialize;
 use sqlx::FromRow;
 use std::collections::HashMap;
-use std::str::FromStr;
 use std::sync::LazyLock;
 
 #[derive(Debug, Clone, FromRow)]
@@ -15,6 +19,40 @@ pub struct UserState {
     pub id: UserId,
     pub email: Email,
     pub webauthn_uuid: Uuid,
+    /// none until the user picks a timezone
+    pub timezone_preference: Option<Timezone>,
+    /// none until the user picks a theme
+    pub theme_preference: Option<Theme>,
+    /// none until the user changes their keyboard shortcuts
+    pub shortcuts_preference: Option<Shortcuts>,
+    /// none until the user picks a name to be shown by
+    pub name: Option<Name>,
+    /// whether the user's avatar is their gravatar, which they have to turn on
+    pub gravatar: bool,
+}
+
+impl UserState {
+    /// the timezone the user sees times in
+    pub fn timezone(&self) -> Timezone {
+        self.timezone_preference.unwrap_or_default()
+    }
+
+    /// whether the user sees pages light, dark, or as their system prefers
+    pub fn theme(&self) -> Theme {
+        self.theme_preference.unwrap_or_default()
+    }
+
+    /// the keyboard shortcuts the user's pages answer to
+    pub fn shortcuts(&self) -> Shortcuts {
+        self.shortcuts_preference.clone().unwrap_or_default()
+    }
+
+    /// what other people see the user as, which is their email until they choose a name
+    pub fn display_name(&self) -> String {
+        self.name
+            .as_ref()
+            .map_or_else(|| self.email.to_string(), ToString::to_string)
+    }
 }
 
 impl axum_login::AuthUser for UserState {
@@ -48,6 +86,26 @@ pub enum UserError {
     SeedFailure(Email),
     #[error("failed to decode a passkey: {0}")]
     PasskeyDecode(String),
+    #[error("failed to decode an event: {0}")]
+    EventDecode(String),
+    #[error("the user {0} already has two-factor authentication enabled")]
+    TotpAlreadyEnabled(UserId),
+    #[error("the user {0} doesn't have two-factor authentication enabled")]
+    TotpNotEnabled(UserId),
+    #[error("the authentication code is invalid")]
+    InvalidTotpCode,
+    #[error("{0} isn't a digest frequency")]
+    InvalidDigestFrequency(String),
+    #[error("{0} isn't a timezone")]
+    InvalidTimezone(String),
+    #[error("{0} isn't a theme")]
+    InvalidTheme(String),
+    #[error("{0} can't be a shortcut, since it's more than two keys or clashes with another")]
+    InvalidShortcut(String),
+    #[error("enter your email to confirm deleting your account")]
+    DeletionNotConfirmed,
+    #[error("failed to read a stored secret: {0}")]
+    Secret(String),
 }
 
 impl From<sqlx::Error> for UserError {
@@ -56,6 +114,15 @@ impl From<sqlx::Error> for UserError {
     }
 }
 
+impl From<SecretError> for UserError {
+    fn from(value: SecretError) -> Self {
+        match value {
+            SecretError::Sqlx(e) => e.into(),
+            e => Self::Secret(e.to_string()),
+        }
+    }
+}
+
 pub type UserResult<T> = Result<T, UserError>;
 
 #[derive(Debug, StateQuery, Clone, Serialize, Deserialize, Default)]
@@ -109,6 +176,15 @@ impl StateMutate for User {
                 self.webauthn_uuid = webauthn_uuid;
             }
             UserEvent::UserDeleted { .. } => self.status = Status::Deleted,
+            UserEvent::TotpEnabled { .. }
+            | UserEvent::TotpDisabled { .. }
+            | UserEvent::RecoveryCodeUsed { .. }
+            | UserEvent::DigestFrequencyChanged { .. }
+            | UserEvent::TimezoneChanged { .. }
+            | UserEvent::ThemeChanged { .. }
+            | UserEvent::ShortcutsChanged { .. }
+            | UserEvent::DisplayNameChanged { .. }
+            | UserEvent::AvatarChanged { .. } => {}
         }
     }
 }
@@ -126,6 +202,15 @@ impl StateMutate for UserEmail {
                 self.webauthn_uuid = webauthn_uuid;
             }
             UserEvent::UserDeleted { .. } => self.status = Status::Deleted,
+            UserEvent::TotpEnabled { .. }
+            | UserEvent::TotpDisabled { .. }
+            | UserEvent::RecoveryCodeUsed { .. }
+            | UserEvent::DigestFrequencyChanged { .. }
+            | UserEvent::TimezoneChanged { .. }
+            | UserEvent::ThemeChanged { .. }
+            | UserEvent::ShortcutsChanged { .. }
+            | UserEvent::DisplayNameChanged { .. }
+            | UserEvent::AvatarChanged { .. } => {}
         }
     }
 }
@@ -196,8 +281,7 @@ pub struct DeleteUser {
 }
 
 impl DeleteUser {
-    #[expect(unused)]
-    fn new(user_id: UserId, authority: Authority, timestamp: Timestamp) -> Self {
+    pub fn new(user_id: UserId, authority: Authority, timestamp: Timestamp) -> Self {
         Self {
             user_id,
             authority,
@@ -228,28 +312,16 @@ impl Decision for DeleteUser {
     }
 }
 
+use crate::seed::DEV_FIXTURE;
 use crate::status::Status;
 use webauthn_rs::prelude::Uuid;
 
-/// The list of dev user emails (stable across restarts).
+/// The list of dev user emails (stable across restarts), taken from the dev fixture.
 pub static DEV_USERS: LazyLock<HashMap<Email, (UserId, Uuid)>> = LazyLock::new(|| {
-    let mut map = HashMap::new();
-
-    map.insert(
-        Email::try_new("pacioli@monkesto.com").expect("valid dev email"),
-        (
-            UserId::from_str("zk8m3p5q7r2n4v6x").expect("valid dev id"),
-            Uuid::parse_str("a1b2c3d4-e5f6-4a5b-8c9d-0e1f2a3b4c5d").expect("valid dev uuid"),
-        ),
-    );
-
-    map.insert(
-        Email::try_new("wedgwood@monkesto.com").expect("valid dev email"),
-        (
-            UserId::from_str("yj7l2o4p6q8s0u1w").expect("valid dev id"),
-            Uuid::parse_str("b2c3d4e5-f6a7-5b6c-9d0e-1f2a3b4c5d6e").expect("valid dev uuid"),
-        ),
-    );
-
-    map
+    DEV_FIXTURE
+        .users()
+        .expect("the dev fixture has invalid users")
+        .into_iter()
+        .map(|(email, user_id, webauthn_uuid)| (email, (user_id, webauthn_uuid)))
+        .collect()
 });
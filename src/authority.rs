@@ -5,12 +5,16 @@ use serde::Serialize;
 use sqlx::encode::IsNull;
 use sqlx::error::BoxDynError;
 use sqlx::{Database, Decode, Encode, Postgres, Type};
+use std::fmt::Display;
+use std::fmt::Formatter;
 
 #[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub enum Actor {
     User(UserId),
     System,
     Anonymous,
+    /// stands in for a user whose account was erased
+    Deleted,
 }
 
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
@@ -23,6 +27,18 @@ pub enum Authority {
     },
 }
 
+/// how the actor is named in logs
+impl Display for Actor {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Actor::User(user_id) => write!(f, "user:{user_id}"),
+            Actor::System => write!(f, "system"),
+            Actor::Anonymous => write!(f, "anonymous"),
+            Actor::Deleted => write!(f, "deleted"),
+        }
+    }
+}
+
 impl Authority {
     pub fn actor(&self) -> &Actor {
         match self {
@@ -35,9 +51,29 @@ impl Authority {
         match self.actor() {
             Actor::Anonymous => None,
             Actor::System => None,
+            Actor::Deleted => None,
             Actor::User(user_id) => Some(*user_id),
         }
     }
+
+    /// replaces every mention of the user with the tombstone actor, returning whether any changed
+    pub fn forget(&mut self, user_id: UserId) -> bool {
+        let actors = match self {
+            Authority::Direct(actor) => vec![actor],
+            Authority::Delegated {
+                grantor, grantee, ..
+            } => vec![grantor, grantee],
+        };
+
+        let mut forgotten = false;
+        for actor in actors {
+            if *actor == Actor::User(user_id) {
+                *actor = Actor::Deleted;
+                forgotten = true;
+            }
+        }
+        forgotten
+    }
 }
 
 impl Type<Postgres> for Actor {
@@ -86,4 +122,26 @@ mod tests {
         let id2 = UserId::new();
         assert_ne!(id1, id2);
     }
+
+    #[test]
+    fn forgetting_a_user_only_replaces_their_mentions() {
+        let user_id = UserId::new();
+        let other_id = UserId::new();
+
+        let mut authority = Authority::Delegated {
+            grantor: Actor::User(other_id),
+            grant: GrantId::new(),
+            grantee: Actor::User(user_id),
+        };
+
+        assert!(authority.forget(user_id));
+        assert_eq!(authority.actor(), &Actor::Deleted);
+        assert_eq!(authority.user_id(), None);
+        assert!(
+            matches!(authority, Authority::Delegated { grantor: Actor::User(id), .. } if id == other_id)
+        );
+
+        assert!(!authority.forget(user_id));
+        assert!(!Authority::Direct(Actor::System).forget(user_id));
+    }
 }
@@ -7,7 +7,7 @@ mod store;
 pub use grant::GrantId;
 pub use role::{RoleId, RoleIndex};
 pub use service::AuthzService;
-pub use store::AuthzEventStore;
+pub use store::{AuthzConnectError, AuthzEventStore};
 
 use axum::Router;
 use axum_login::login_required;
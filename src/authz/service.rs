@@ -8,8 +8,9 @@ use super::role::{
 };
 use super::store::AuthzEventStore;
 use crate::authority::{Actor, Authority};
+use crate::id::IdGenerator;
 use crate::name::Name;
-use chrono::Utc;
+use crate::time_provider::{Clock, TimeProvider};
 use disintegrate::{DecisionError, PersistedEvent};
 use disintegrate_postgres::PgEventId;
 use std::collections::HashSet;
@@ -33,13 +34,22 @@ pub enum AuthzError {
 pub struct AuthzService {
     event_store: AuthzEventStore,
     role_index: RoleIndex,
+    clock: Clock,
+    ids: IdGenerator,
 }
 
 impl AuthzService {
-    pub fn new(event_store: AuthzEventStore, role_index: RoleIndex) -> Self {
+    pub fn new(
+        event_store: AuthzEventStore,
+        role_index: RoleIndex,
+        clock: Clock,
+        ids: IdGenerator,
+    ) -> Self {
         Self {
             event_store,
             role_index,
+            clock,
+            ids,
         }
     }
 
@@ -48,11 +58,16 @@ impl AuthzService {
         authority: Authority,
         name: Name,
     ) -> Result<RoleId, AuthzError> {
-        let role_id = RoleId::new();
+        let role_id: RoleId = self.ids.generate();
         let events = self
             .event_store
             .decision_maker
-            .make(CreateRole::new(role_id, name, authority, Utc::now()))
+            .make(CreateRole::new(
+                role_id,
+                name,
+                authority,
+                self.clock.get_time(),
+            ))
             .await
             .map_err(map_role_decision_error)?;
         self.project(events).await?;
@@ -64,11 +79,16 @@ impl AuthzService {
         authority: Authority,
         role_id: RoleId,
     ) -> Result<GrantId, AuthzError> {
-        let grant_id = GrantId::new();
+        let grant_id: GrantId = self.ids.generate();
         let events = self
             .event_store
             .decision_maker
-            .make(CreateGrant::new(grant_id, role_id, authority, Utc::now()))
+            .make(CreateGrant::new(
+                grant_id,
+                role_id,
+                authority,
+                self.clock.get_time(),
+            ))
             .await
             .map_err(map_grant_decision_error)?;
         self.project(events).await?;
@@ -83,7 +103,7 @@ impl AuthzService {
         let events = self
             .event_store
             .decision_maker
-            .make(RevokeGrant::new(grant_id, authority, Utc::now()))
+            .make(RevokeGrant::new(grant_id, authority, self.clock.get_time()))
             .await
             .map_err(map_grant_decision_error)?;
         self.project(events).await
@@ -124,7 +144,7 @@ impl AuthzService {
                 actor,
                 add,
                 authority,
-                Utc::now(),
+                self.clock.get_time(),
             ))
             .await
             .map_err(map_role_decision_error)?;
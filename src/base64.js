@@ -0,0 +1,27 @@
+// the subset of js-base64 that the passkey pages use, served locally so signing in
+// doesn't depend on a third-party CDN
+var Base64 = (function () {
+    function toUint8Array(encoded) {
+        // accepts standard and url-safe base64, with or without padding
+        var standard = encoded.replace(/-/g, '+').replace(/_/g, '/').replace(/=+$/, '');
+        var binary = atob(standard + '==='.slice((standard.length + 3) % 4));
+        var bytes = new Uint8Array(binary.length);
+        for (var i = 0; i < binary.length; i++) {
+            bytes[i] = binary.charCodeAt(i);
+        }
+        return bytes;
+    }
+
+    function fromUint8Array(bytes, urlsafe) {
+        var binary = '';
+        for (var i = 0; i < bytes.length; i++) {
+            binary += String.fromCharCode(bytes[i]);
+        }
+        var encoded = btoa(binary);
+        return urlsafe
+            ? encoded.replace(/\+/g, '-').replace(/\//g, '_').replace(/=+$/, '')
+            : encoded;
+    }
+
+    return { toUint8Array: toUint8Array, fromUint8Array: fromUint8Array };
+})();
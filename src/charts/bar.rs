@@ -0,0 +1,44 @@
+use super::{Chart, HEIGHT, LEFT, PADDING, Scale, WIDTH, axes, color, frame, label, series_names};
+use maud::{Markup, html};
+
+/// how much of each label's slot its bars fill, leaving a gap between the groups
+const GROUP_FILL: f64 = 0.8;
+
+/// a group of bars for each label, one from each series, rising from zero or hanging below it
+pub fn bar_chart(chart: &Chart) -> Markup {
+    let scale = Scale::new(chart);
+    let slot = (WIDTH - LEFT - PADDING) / chart.labels.len().max(1) as f64;
+    let bar = slot * GROUP_FILL / chart.series.len().max(1) as f64;
+
+    let centre = |i: usize| LEFT + (i as f64 + 0.5) * slot;
+
+    let body = html! {
+        (axes(chart, &scale, centre))
+        @for (s, series) in chart.series.iter().enumerate() {
+            g class=(color(s)) {
+                @for (i, value) in series.values.iter().enumerate() {
+                    @let top = scale.y((*value).max(0));
+                    @let bottom = scale.y((*value).min(0));
+                    rect
+                    x=(format!("{:.1}", centre(i) - slot * GROUP_FILL / 2.0 + s as f64 * bar))
+                    y=(format!("{:.1}", top))
+                    width=(format!("{:.1}", bar))
+                    height=(format!("{:.1}", (bottom - top).max(1.0)))
+                    rx="2"
+                    fill="currentColor" {
+                        title {
+                            (series.name) ", " (label(chart, i)) ": " ((chart.format)(*value))
+                        }
+                    }
+                }
+            }
+        }
+    };
+
+    frame(
+        chart,
+        &format!("0 0 {WIDTH} {HEIGHT}"),
+        body,
+        &series_names(chart),
+    )
+}
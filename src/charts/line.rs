@@ -0,0 +1,44 @@
+use super::{Chart, HEIGHT, LEFT, PADDING, Scale, WIDTH, axes, color, frame, label, series_names};
+use maud::{Markup, html};
+
+/// a line for each series through its values, with a dot on each that names it
+pub fn line_chart(chart: &Chart) -> Markup {
+    let scale = Scale::new(chart);
+    let count = chart.labels.len();
+
+    let x = |i: usize| {
+        if count < 2 {
+            (LEFT + WIDTH - PADDING) / 2.0
+        } else {
+            LEFT + i as f64 * (WIDTH - LEFT - PADDING) / (count - 1) as f64
+        }
+    };
+
+    let body = html! {
+        (axes(chart, &scale, x))
+        @for (s, series) in chart.series.iter().enumerate() {
+            g class=(color(s)) {
+                polyline
+                points=(series.values.iter().enumerate().map(|(i, value)| format!("{:.1},{:.1}", x(i), scale.y(*value))).collect::<Vec<_>>().join(" "))
+                fill="none"
+                stroke="currentColor"
+                stroke-width="2"
+                stroke-linejoin="round" {}
+                @for (i, value) in series.values.iter().enumerate() {
+                    circle cx=(format!("{:.1}", x(i))) cy=(format!("{:.1}", scale.y(*value))) r="3" fill="currentColor" {
+                        title {
+                            (series.name) ", " (label(chart, i)) ": " ((chart.format)(*value))
+                        }
+                    }
+                }
+            }
+        }
+    };
+
+    frame(
+        chart,
+        &format!("0 0 {WIDTH} {HEIGHT}"),
+        body,
+        &series_names(chart),
+    )
+}
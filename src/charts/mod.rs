@@ -0,0 +1,189 @@
+//! charts drawn on the server as inline svg, so pages show them without any script
+
+mod bar;
+mod line;
+mod pie;
+mod sparkline;
+
+pub use bar::bar_chart;
+pub use line::line_chart;
+pub use pie::pie_chart;
+pub use sparkline::sparkline;
+
+use maud::{Markup, html};
+
+/// a line, a bar in each group, or a pie's slices
+pub struct Series {
+    pub name: String,
+    pub values: Vec<i64>,
+}
+
+/// what a chart shows. each series has a value for each label
+pub struct Chart {
+    /// read out in place of the chart
+    pub title: String,
+    pub labels: Vec<String>,
+    pub series: Vec<Series>,
+    /// formats a value for the axis and the tooltips
+    pub format: fn(i64) -> String,
+}
+
+/// the chart's size in svg units. it's scaled to the width of the page
+const WIDTH: f64 = 640.0;
+const HEIGHT: f64 = 240.0;
+/// room for the values on the left and the labels underneath
+const LEFT: f64 = 80.0;
+const BOTTOM: f64 = 24.0;
+const PADDING: f64 = 8.0;
+
+/// at most this many labels are written under a chart, so they don't overlap
+const MAX_LABELS: usize = 8;
+
+/// each series' color, spelled out in full so tailwind finds them. once they run out the
+/// colors start over
+const COLORS: [&str; 6] = [
+    "text-indigo-600 dark:text-indigo-400",
+    "text-teal-600 dark:text-teal-400",
+    "text-amber-500 dark:text-amber-400",
+    "text-pink-600 dark:text-pink-400",
+    "text-blue-600 dark:text-blue-400",
+    "text-purple-600 dark:text-purple-400",
+];
+
+const GRID: &str = "stroke-gray-200 dark:stroke-gray-700";
+const TEXT: &str = "fill-gray-500 dark:fill-gray-400";
+
+fn color(index: usize) -> &'static str {
+    COLORS[index % COLORS.len()]
+}
+
+/// maps values onto the chart's height. zero is always in range, so bars have a baseline
+struct Scale {
+    low: i64,
+    high: i64,
+}
+
+impl Scale {
+    fn new(chart: &Chart) -> Self {
+        let values = chart.series.iter().flat_map(|series| &series.values);
+
+        Self {
+            low: values.clone().copied().min().unwrap_or(0).min(0),
+            high: values.copied().max().unwrap_or(0).max(0),
+        }
+    }
+
+    fn y(&self, value: i64) -> f64 {
+        let range = (self.high - self.low).max(1) as f64;
+        PADDING + (self.high - value) as f64 * (HEIGHT - BOTTOM - PADDING) / range
+    }
+}
+
+/// the indices of the labels written under the chart, spread evenly from the first
+fn shown_labels(count: usize) -> impl Iterator<Item = usize> {
+    (0..count).step_by(count.div_ceil(MAX_LABELS).max(1))
+}
+
+/// the line at zero, the highest and lowest values on the left, and the labels underneath
+/// centred on `x`
+fn axes(chart: &Chart, scale: &Scale, x: impl Fn(usize) -> f64) -> Markup {
+    let mut values = vec![scale.high, 0, scale.low];
+    values.dedup();
+
+    html! {
+        line x1=(LEFT) x2=(WIDTH - PADDING) y1=(scale.y(0)) y2=(scale.y(0)) class=(GRID) {}
+        @for value in values {
+            text x=(LEFT - 6.0) y=(scale.y(value)) text-anchor="end" dominant-baseline="middle" font-size="10" class=(TEXT) {
+                ((chart.format)(value))
+            }
+        }
+        @for i in shown_labels(chart.labels.len()) {
+            text x=(x(i)) y=(HEIGHT - 6.0) text-anchor="middle" font-size="10" class=(TEXT) {
+                (chart.labels[i])
+            }
+        }
+    }
+}
+
+/// the series' names and colors, when there's more than one to tell apart
+fn legend(names: &[&str]) -> Markup {
+    html! {
+        @if names.len() > 1 {
+            ul class="flex flex-wrap gap-x-4 gap-y-1 mt-2 text-xs text-gray-600 dark:text-gray-300" {
+                @for (i, name) in names.iter().enumerate() {
+                    li class="flex items-center gap-1.5" {
+                        svg viewBox="0 0 10 10" class=(format!("w-2.5 h-2.5 {}", color(i))) aria-hidden="true" {
+                            rect width="10" height="10" rx="2" fill="currentColor" {}
+                        }
+                        (name)
+                    }
+                }
+            }
+        }
+    }
+}
+
+fn frame(chart: &Chart, view_box: &str, body: Markup, names: &[&str]) -> Markup {
+    html! {
+        figure {
+            svg viewBox=(view_box) class="w-full h-auto" role="img" aria-label=(chart.title) {
+                (body)
+            }
+            (legend(names))
+        }
+    }
+}
+
+/// the label for the `index`th value, or nothing for values past the last label
+fn label(chart: &Chart, index: usize) -> &str {
+    chart.labels.get(index).map_or("", String::as_str)
+}
+
+fn series_names(chart: &Chart) -> Vec<&str> {
+    chart
+        .series
+        .iter()
+        .map(|series| series.name.as_str())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn chart(values: Vec<i64>) -> Chart {
+        Chart {
+            title: "Test".to_string(),
+            labels: (0..values.len()).map(|i| i.to_string()).collect(),
+            series: vec![Series {
+                name: "Values".to_string(),
+                values,
+            }],
+            format: |value| value.to_string(),
+        }
+    }
+
+    #[test]
+    fn the_scale_always_reaches_zero() {
+        let scale = Scale::new(&chart(vec![300, 500]));
+        assert_eq!((scale.low, scale.high), (0, 500));
+        assert_eq!(scale.y(500), PADDING);
+        assert_eq!(scale.y(0), HEIGHT - BOTTOM);
+
+        let scale = Scale::new(&chart(vec![-200, 100]));
+        assert_eq!((scale.low, scale.high), (-200, 100));
+
+        // nothing to chart still has a height to draw in
+        assert!(Scale::new(&chart(Vec::new())).y(0).is_finite());
+    }
+
+    #[test]
+    fn labels_are_thinned_to_fit() {
+        assert_eq!(shown_labels(5).collect::<Vec<_>>(), vec![0, 1, 2, 3, 4]);
+        assert_eq!(
+            shown_labels(24).collect::<Vec<_>>(),
+            vec![0, 3, 6, 9, 12, 15, 18, 21]
+        );
+        assert_eq!(shown_labels(0).count(), 0);
+    }
+}
@@ -0,0 +1,82 @@
+use super::{Chart, color, frame, label};
+use maud::{Markup, html};
+use std::f64::consts::TAU;
+
+const SIZE: f64 = 200.0;
+const RADIUS: f64 = 96.0;
+
+/// the point on the pie's edge `fraction` of the way round, clockwise from the top
+fn edge(fraction: f64) -> (f64, f64) {
+    let angle = fraction * TAU;
+    (
+        SIZE / 2.0 + RADIUS * angle.sin(),
+        SIZE / 2.0 - RADIUS * angle.cos(),
+    )
+}
+
+/// a slice for each label, sized by its value in the first series. labels without a positive
+/// value are left out, since they have no share to show
+pub fn pie_chart(chart: &Chart) -> Markup {
+    let values = chart
+        .series
+        .first()
+        .map_or(&[][..], |series| &series.values);
+    let slices: Vec<(usize, i64)> = values
+        .iter()
+        .copied()
+        .enumerate()
+        .filter(|(_, value)| *value > 0)
+        .collect();
+    let total: i64 = slices.iter().map(|(_, value)| value).sum();
+
+    // where each slice starts and ends, as fractions of the way round
+    let mut start = 0.0;
+    let arcs: Vec<(f64, f64)> = slices
+        .iter()
+        .map(|(_, value)| {
+            let end = start + *value as f64 / total as f64;
+            (std::mem::replace(&mut start, end), end)
+        })
+        .collect();
+
+    let body = html! {
+        @for (n, ((i, value), (from, to))) in slices.iter().zip(&arcs).enumerate() {
+            @let (x0, y0) = edge(*from);
+            @let (x1, y1) = edge(*to);
+            g class=(color(n)) {
+                @if slices.len() == 1 {
+                    circle cx=(SIZE / 2.0) cy=(SIZE / 2.0) r=(RADIUS) fill="currentColor" {
+                        title { (label(chart, *i)) ": " ((chart.format)(*value)) }
+                    }
+                } @else {
+                    path
+                    d=(format!(
+                        "M {c} {c} L {x0:.1} {y0:.1} A {RADIUS} {RADIUS} 0 {} 1 {x1:.1} {y1:.1} Z",
+                        u8::from(to - from > 0.5),
+                        c = SIZE / 2.0,
+                    ))
+                    fill="currentColor"
+                    class="stroke-white dark:stroke-gray-800" {
+                        title { (label(chart, *i)) ": " ((chart.format)(*value)) }
+                    }
+                }
+            }
+        }
+    };
+
+    let names: Vec<String> = slices
+        .iter()
+        .map(|(i, value)| format!("{} ({}%)", label(chart, *i), value * 100 / total.max(1)))
+        .collect();
+
+    html! {
+        div class="max-w-48 mx-auto" {
+            (frame(
+                chart,
+                &format!("0 0 {SIZE} {SIZE}"),
+                body,
+                &names.iter().map(String::as_str).collect::<Vec<_>>(),
+            ))
+        }
+    }
+}
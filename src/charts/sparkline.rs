@@ -0,0 +1,58 @@
+use super::{Chart, color, label};
+use maud::{Markup, html};
+
+/// the sparkline's size in svg units. it's drawn at this size rather than scaled to the page
+const WIDTH: f64 = 96.0;
+const HEIGHT: f64 = 24.0;
+/// keeps the line's stroke inside the box at the highest and lowest values
+const INSET: f64 = 2.0;
+
+/// a small line through the first series' values, without axes or labels, for showing a trend
+/// next to a number. it spans the values' own range rather than reaching zero, so a small change
+/// to a large amount still shows
+pub fn sparkline(chart: &Chart) -> Markup {
+    let values = chart
+        .series
+        .first()
+        .map_or(&[][..], |series| series.values.as_slice());
+    let low = values.iter().copied().min().unwrap_or(0);
+    let high = values.iter().copied().max().unwrap_or(0);
+    let count = values.len();
+
+    let x = |i: usize| {
+        if count < 2 {
+            WIDTH / 2.0
+        } else {
+            INSET + i as f64 * (WIDTH - 2.0 * INSET) / (count - 1) as f64
+        }
+    };
+    // a line that never changes is drawn across the middle
+    let y = |value: i64| {
+        if high == low {
+            HEIGHT / 2.0
+        } else {
+            INSET + (high - value) as f64 * (HEIGHT - 2.0 * INSET) / (high - low) as f64
+        }
+    };
+
+    html! {
+        svg
+        viewBox=(format!("0 0 {WIDTH} {HEIGHT}"))
+        width=(WIDTH)
+        height=(HEIGHT)
+        class=(format!("shrink-0 {}", color(0)))
+        role="img"
+        aria-label=(chart.title) {
+            @if let Some(last) = values.last() {
+                title { (chart.title) ", " (label(chart, count - 1)) ": " ((chart.format)(*last)) }
+            }
+            polyline
+            points=(values.iter().enumerate().map(|(i, value)| format!("{:.1},{:.1}", x(i), y(*value))).collect::<Vec<_>>().join(" "))
+            fill="none"
+            stroke="currentColor"
+            stroke-width="1.5"
+            stroke-linejoin="round"
+            stroke-linecap="round" {}
+        }
+    }
+}
@@ -0,0 +1,291 @@
+//! a typed client for the json api, so integrators don't have to write its requests and
+//! responses themselves. responses are read into the same structs the server writes them from,
+//! so the two can't drift apart. enabled by the `client` feature
+
+pub use crate::journal::domain::JournalDomainEvent;
+pub use crate::journal::feed::{FeedEvent, FeedPage};
+pub use crate::journal::json::{
+    AccountJson, CreatedTransactionsJson, EntryJson, JournalJson, TransactionJson,
+};
+pub use crate::journal::notification::views::UnreadCount;
+pub use crate::journal::transaction::bulk::{BatchEntry, BatchTransaction};
+
+use reqwest::StatusCode;
+use reqwest::header::{ACCEPT, CONTENT_TYPE, COOKIE};
+use reqwest::redirect::Policy;
+use serde::Deserialize;
+use serde::de::DeserializeOwned;
+use serde_json::Value;
+use std::time::Duration;
+use thiserror::Error;
+
+/// the session cookie's name, unless the server sets SESSION_COOKIE_NAME
+pub const DEFAULT_COOKIE_NAME: &str = "monkesto.sid";
+
+#[derive(Debug, Error)]
+pub enum ClientError {
+    #[error("failed to reach the server: {0}")]
+    Http(#[from] reqwest::Error),
+    #[error("the server sent a response that couldn't be read: {0}")]
+    Decode(#[from] serde_json::Error),
+    /// a failure the server explained, with the same code and message as its Error body
+    #[error("{message} ({code})")]
+    Api {
+        status: StatusCode,
+        code: String,
+        message: String,
+        details: Option<Value>,
+    },
+    /// a failure without an explanation, such as a redirect to sign in
+    #[error("the server answered with {0}")]
+    Rejected(StatusCode),
+}
+
+/// the Error body the server sends with a failure
+#[derive(Deserialize)]
+struct ErrorJson {
+    code: String,
+    message: String,
+    details: Option<Value>,
+}
+
+/// a session on a Monkesto server. the api authenticates with the session cookie the server set
+/// when its user signed in, so every request is made as that user
+#[derive(Clone)]
+pub struct Client {
+    http: reqwest::Client,
+    base_url: String,
+    cookie: String,
+}
+
+impl Client {
+    /// a client for the server at `base_url`, such as `https://monkesto.example.com`, signed in
+    /// with the value of its session cookie
+    pub fn new(base_url: &str, session: &str) -> Result<Self, ClientError> {
+        Self::with_cookie_name(base_url, DEFAULT_COOKIE_NAME, session)
+    }
+
+    /// a client for a server that renamed its session cookie
+    pub fn with_cookie_name(
+        base_url: &str,
+        cookie_name: &str,
+        session: &str,
+    ) -> Result<Self, ClientError> {
+        Ok(Self {
+            // a redirect means the session wasn't accepted, which following it would hide
+            http: reqwest::Client::builder()
+                .timeout(Duration::from_secs(30))
+                .redirect(Policy::none())
+                .build()?,
+            base_url: base_url.trim_end_matches('/').to_string(),
+            cookie: format!("{cookie_name}={session}"),
+        })
+    }
+
+    /// the journal and its accounts
+    pub async fn journal(&self, journal_id: &str) -> Result<JournalJson, ClientError> {
+        self.send(
+            self.http
+                .get(format!("{}/journal/{journal_id}", self.base_url)),
+        )
+        .await
+    }
+
+    /// the journal's transactions, by effective date and then the order they were entered
+    pub async fn transactions(
+        &self,
+        journal_id: &str,
+    ) -> Result<Vec<TransactionJson>, ClientError> {
+        self.send(self.http.get(format!(
+            "{}/journal/{journal_id}/transaction",
+            self.base_url
+        )))
+        .await
+    }
+
+    /// the accounts whose names match the query, best match first
+    pub async fn search_accounts(
+        &self,
+        journal_id: &str,
+        query: &str,
+    ) -> Result<Vec<AccountJson>, ClientError> {
+        self.send(
+            self.http
+                .get(format!(
+                    "{}/journal/{journal_id}/account/search",
+                    self.base_url
+                ))
+                .query(&[("q", query)]),
+        )
+        .await
+    }
+
+    /// records every transaction, or none of them if any is invalid
+    pub async fn record_transactions(
+        &self,
+        journal_id: &str,
+        transactions: &[BatchTransaction],
+    ) -> Result<CreatedTransactionsJson, ClientError> {
+        self.send(
+            self.http
+                .post(format!(
+                    "{}/journal/{journal_id}/transaction/bulk",
+                    self.base_url
+                ))
+                .header(CONTENT_TYPE, "application/json")
+                .body(serde_json::to_vec(transactions)?),
+        )
+        .await
+    }
+
+    /// the journal's events after the `after` event, or from the beginning without one. pass
+    /// the page's `next` to read the events that followed it
+    pub async fn events(
+        &self,
+        journal_id: &str,
+        after: Option<i64>,
+        limit: Option<i64>,
+    ) -> Result<FeedPage, ClientError> {
+        let mut query = Vec::new();
+        query.extend(after.map(|after| ("after", after)));
+        query.extend(limit.map(|limit| ("limit", limit)));
+
+        self.send(
+            self.http
+                .get(format!(
+                    "{}/api/v1/journal/{journal_id}/events",
+                    self.base_url
+                ))
+                .query(&query),
+        )
+        .await
+    }
+
+    /// how many of the user's notifications are unread
+    pub async fn unread_notifications(&self) -> Result<u64, ClientError> {
+        self.send::<UnreadCount>(
+            self.http
+                .get(format!("{}/notifications/unread", self.base_url)),
+        )
+        .await
+        .map(|count| count.unread)
+    }
+
+    async fn send<T: DeserializeOwned>(
+        &self,
+        request: reqwest::RequestBuilder,
+    ) -> Result<T, ClientError> {
+        let res = request
+            .header(ACCEPT, "application/json")
+            .header(COOKIE, &self.cookie)
+            .send()
+            .await?;
+        let status = res.status();
+        let body = res.bytes().await?;
+
+        if status.is_success() {
+            return Ok(serde_json::from_slice(&body)?);
+        }
+
+        Err(match serde_json::from_slice::<ErrorJson>(&body) {
+            Ok(error) => ClientError::Api {
+                status,
+                code: error.code,
+                message: error.message,
+                details: error.details,
+            },
+            Err(_) => ClientError::Rejected(status),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::journal::account::AccountId;
+    use crate::test_app::{TestApp, assert_accepted};
+
+    #[tokio::test]
+    async fn the_client_reads_what_the_api_sends() {
+        let Some(app) = TestApp::spawn().await else {
+            return;
+        };
+
+        let user = app.signup("integrator").await;
+        let journal_id = user.create_journal(&app, "Integrated").await;
+        for name in ["Cash", "Food"] {
+            assert_accepted(&user.create_account(journal_id, name).await);
+        }
+        let cash = app.account_id(journal_id, "Cash").await;
+        let food = app.account_id(journal_id, "Food").await;
+
+        // the client needs a real socket, and a session of its own to send
+        let server = app.serve_http();
+        let session = server
+            .post(&format!("/test/signin/{}", user.id))
+            .await
+            .cookie("monkesto.sid");
+        let address = server.server_address().expect("the server has no address");
+        let client =
+            Client::new(address.as_str(), session.value()).expect("failed to build the client");
+
+        let journal_id = journal_id.to_string();
+        let journal = client
+            .journal(&journal_id)
+            .await
+            .expect("failed to read the journal");
+        assert_eq!(journal.name, "Integrated");
+        assert_eq!(journal.accounts.len(), 2);
+
+        let entry = |account: AccountId, entry_type: &str| BatchEntry {
+            account_id: account.to_string(),
+            amount: "5.00".to_string(),
+            entry_type: entry_type.to_string(),
+        };
+        let created = client
+            .record_transactions(
+                &journal_id,
+                &[BatchTransaction {
+                    effective_date: "2026-10-17".to_string(),
+                    entries: vec![entry(food, "Dr"), entry(cash, "Cr")],
+                }],
+            )
+            .await
+            .expect("failed to record the transaction");
+
+        let transactions = client
+            .transactions(&journal_id)
+            .await
+            .expect("failed to list the transactions");
+        assert_eq!(transactions.len(), 1);
+        assert_eq!(transactions[0].id, created.transaction_ids[0]);
+        assert_eq!(transactions[0].entries[0].amount, 500);
+
+        let found = client
+            .search_accounts(&journal_id, "foo")
+            .await
+            .expect("failed to search the accounts");
+        assert_eq!(found[0].id, food.to_string());
+
+        let page = client
+            .events(&journal_id, None, None)
+            .await
+            .expect("failed to read the events");
+        assert!(!page.more);
+        assert_eq!(
+            page.next,
+            page.events.last().expect("there are no events").id
+        );
+
+        assert_eq!(client.unread_notifications().await.ok(), Some(0));
+
+        // someone else's journal fails with the server's code
+        let stranger = app.signup("stranger").await;
+        let hidden = stranger.create_journal(&app, "Hidden").await;
+        assert!(matches!(
+            client.journal(&hidden.to_string()).await,
+            Err(ClientError::Api { status: StatusCode::NOT_FOUND, ref code, .. })
+                if code == "journal_not_found"
+        ));
+    }
+}
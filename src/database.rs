@@ -0,0 +1,201 @@
+use axum_login::tracing;
+use sqlx::postgres::PgPoolOptions;
+use std::env;
+use std::fmt::Display;
+use std::time::Duration;
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+pub enum DatabaseConfigError {
+    #[error("{0} must be a whole number, found {1}")]
+    Number(&'static str, String),
+
+    #[error("{0} must be a whole number of seconds, found {1}")]
+    Seconds(&'static str, String),
+
+    #[error("DATABASE_CONNECT_ATTEMPTS must be at least 1")]
+    NoAttempts,
+}
+
+/// the size of each schema's connection pool, and how patiently the server waits for postgres
+/// when it starts
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct DatabaseConfig {
+    /// the most connections each schema's pool opens
+    pub max_connections: u32,
+    /// how long a query waits for a free connection before it fails
+    pub acquire_timeout: Duration,
+    /// idle connections are closed after this long
+    pub idle_timeout: Duration,
+    /// how many times the first connection and migrations are tried before giving up
+    pub connect_attempts: u32,
+    /// the wait after the first failed attempt, doubled after each one after that
+    pub initial_backoff: Duration,
+    /// the longest wait between attempts
+    pub max_backoff: Duration,
+}
+
+impl Default for DatabaseConfig {
+    fn default() -> Self {
+        Self {
+            max_connections: 10,
+            acquire_timeout: Duration::from_secs(30),
+            idle_timeout: Duration::from_secs(10 * 60),
+            connect_attempts: 10,
+            initial_backoff: Duration::from_secs(1),
+            max_backoff: Duration::from_secs(30),
+        }
+    }
+}
+
+fn number_from_env(key: &'static str, default: u32) -> Result<u32, DatabaseConfigError> {
+    match env::var(key) {
+        Ok(value) => value
+            .parse::<u32>()
+            .map_err(|_| DatabaseConfigError::Number(key, value)),
+        Err(_) => Ok(default),
+    }
+}
+
+fn seconds_from_env(key: &'static str, default: Duration) -> Result<Duration, DatabaseConfigError> {
+    match env::var(key) {
+        Ok(value) => value
+            .parse::<u64>()
+            .map(Duration::from_secs)
+            .map_err(|_| DatabaseConfigError::Seconds(key, value)),
+        Err(_) => Ok(default),
+    }
+}
+
+impl DatabaseConfig {
+    pub fn from_env() -> Result<Self, DatabaseConfigError> {
+        let default = Self::default();
+
+        let connect_attempts =
+            number_from_env("DATABASE_CONNECT_ATTEMPTS", default.connect_attempts)?;
+
+        if connect_attempts == 0 {
+            return Err(DatabaseConfigError::NoAttempts);
+        }
+
+        Ok(Self {
+            max_connections: number_from_env("DATABASE_MAX_CONNECTIONS", default.max_connections)?,
+            acquire_timeout: seconds_from_env(
+                "DATABASE_ACQUIRE_TIMEOUT_SECS",
+                default.acquire_timeout,
+            )?,
+            idle_timeout: seconds_from_env("DATABASE_IDLE_TIMEOUT_SECS", default.idle_timeout)?,
+            connect_attempts,
+            initial_backoff: seconds_from_env(
+                "DATABASE_CONNECT_BACKOFF_SECS",
+                default.initial_backoff,
+            )?,
+            max_backoff: seconds_from_env(
+                "DATABASE_CONNECT_MAX_BACKOFF_SECS",
+                default.max_backoff,
+            )?,
+        })
+    }
+
+    /// the options every schema's pool is built from
+    pub fn pool_options(&self) -> PgPoolOptions {
+        PgPoolOptions::new()
+            .max_connections(self.max_connections)
+            .acquire_timeout(self.acquire_timeout)
+            .idle_timeout(self.idle_timeout)
+    }
+
+    /// how long to wait after the given failed attempt, counting from 1
+    pub fn backoff(&self, attempt: u32) -> Duration {
+        self.initial_backoff
+            .saturating_mul(2u32.saturating_pow(attempt.saturating_sub(1)))
+            .min(self.max_backoff)
+    }
+
+    /// runs the step until it succeeds or the attempts run out, so the server can start before
+    /// postgres is accepting connections
+    pub async fn retry<T, E: Display, F: Future<Output = Result<T, E>>>(
+        &self,
+        step: &'static str,
+        mut attempt: impl FnMut() -> F,
+    ) -> Result<T, E> {
+        let mut attempts = 1;
+
+        loop {
+            match attempt().await {
+                Ok(value) => return Ok(value),
+                Err(error) if attempts < self.connect_attempts => {
+                    let backoff = self.backoff(attempts);
+                    tracing::warn!(
+                        %error,
+                        attempt = attempts,
+                        retry_in = ?backoff,
+                        "failed to {step}, retrying"
+                    );
+                    tokio::time::sleep(backoff).await;
+                    attempts += 1;
+                }
+                Err(error) => return Err(error),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn the_backoff_doubles_up_to_its_limit() {
+        let config = DatabaseConfig {
+            initial_backoff: Duration::from_secs(1),
+            max_backoff: Duration::from_secs(10),
+            ..DatabaseConfig::default()
+        };
+
+        let backoffs: Vec<u64> = (1..=6).map(|n| config.backoff(n).as_secs()).collect();
+
+        assert_eq!(backoffs, vec![1, 2, 4, 8, 10, 10]);
+        assert_eq!(config.backoff(100), Duration::from_secs(10));
+    }
+
+    #[tokio::test]
+    async fn a_step_is_retried_until_it_succeeds() {
+        let config = DatabaseConfig {
+            connect_attempts: 3,
+            initial_backoff: Duration::ZERO,
+            ..DatabaseConfig::default()
+        };
+        let mut calls = 0;
+
+        let result = config
+            .retry("count", || {
+                calls += 1;
+                let calls = calls;
+                async move { if calls < 3 { Err("not yet") } else { Ok(calls) } }
+            })
+            .await;
+
+        assert_eq!(result, Ok(3));
+    }
+
+    #[tokio::test]
+    async fn a_step_gives_up_once_the_attempts_run_out() {
+        let config = DatabaseConfig {
+            connect_attempts: 2,
+            initial_backoff: Duration::ZERO,
+            ..DatabaseConfig::default()
+        };
+        let mut calls = 0;
+
+        let result: Result<(), &str> = config
+            .retry("fail", || {
+                calls += 1;
+                async { Err("down") }
+            })
+            .await;
+
+        assert_eq!(result, Err("down"));
+        assert_eq!(calls, 2);
+    }
+}
@@ -1,16 +1,25 @@
-use disintegrate::PersistedEvent;
+use disintegrate::{Event, PersistedEvent};
 use disintegrate_postgres::PgEventId;
 
 pub trait GetEventId {
     fn event_id(&self) -> PgEventId;
 }
 
-impl<T: disintegrate::Event> GetEventId for Vec<PersistedEvent<PgEventId, T>> {
-    /// Returns the latest eventid in the set
+impl<T: Event> GetEventId for Vec<PersistedEvent<PgEventId, T>> {
+    /// Returns the latest eventid in the set, logging each recorded event under the
+    /// span of the command that recorded it
     ///
     /// Safety: This function assumes that the set has at least one event,
     /// it will panic otherwise
     fn event_id(&self) -> PgEventId {
+        for event in self {
+            tracing::info!(
+                event_id = event.id(),
+                event_type = event.name(),
+                "recorded an event"
+            );
+        }
+
         self.last()
             .expect("the decision maker should always return at least one event")
             .id()
@@ -0,0 +1,198 @@
+use crate::BackendType;
+use crate::StateType;
+use crate::authn::get_user;
+use crate::authority::Actor;
+use crate::authority::Authority;
+use crate::journal::JournalId;
+use crate::journal::service::{AccountState, JournalState, TransactionState};
+use crate::journal::transaction::format_voucher_number;
+use async_graphql::{Context, EmptyMutation, EmptySubscription, ID, Object, Schema};
+use axum::Json;
+use axum::Router;
+use axum::extract::State;
+use axum::response::Redirect;
+use axum::routing::post;
+use axum_login::AuthSession;
+use std::str::FromStr;
+
+pub type MonkestoSchema = Schema<QueryRoot, EmptyMutation, EmptySubscription>;
+
+pub fn router() -> Router<StateType> {
+    Router::new().route("/api/graphql", post(graphql))
+}
+
+pub fn schema() -> MonkestoSchema {
+    Schema::build(QueryRoot, EmptyMutation, EmptySubscription)
+        .limit_depth(8)
+        .finish()
+}
+
+/// answers a query on behalf of the signed in user. every resolver reads through the journal
+/// service with the user's authority, so a query sees exactly what the pages would show them
+pub async fn graphql(
+    State(state): State<StateType>,
+    session: AuthSession<BackendType>,
+    Json(request): Json<async_graphql::Request>,
+) -> Result<Json<async_graphql::Response>, Redirect> {
+    let user = get_user(session)?;
+    let authority = Authority::Direct(Actor::User(user.id));
+
+    let request = request.data(state).data(authority);
+
+    Ok(Json(schema().execute(request).await))
+}
+
+fn context<'a>(ctx: &'a Context<'_>) -> async_graphql::Result<(&'a StateType, &'a Authority)> {
+    Ok((ctx.data::<StateType>()?, ctx.data::<Authority>()?))
+}
+
+pub struct QueryRoot;
+
+#[Object]
+impl QueryRoot {
+    /// the journals the user can read
+    async fn journals(&self, ctx: &Context<'_>) -> async_graphql::Result<Vec<Journal>> {
+        let (state, authority) = context(ctx)?;
+        let Some(user_id) = authority.user_id() else {
+            return Ok(Vec::new());
+        };
+
+        Ok(state
+            .journal_service
+            .list_accessible_journals(user_id)
+            .await?
+            .into_iter()
+            .map(|(journal, _, _)| Journal(journal))
+            .collect())
+    }
+
+    async fn journal(&self, ctx: &Context<'_>, id: ID) -> async_graphql::Result<Journal> {
+        let (state, authority) = context(ctx)?;
+        let journal_id = JournalId::from_str(&id)?;
+
+        let (journal, _, _) = state
+            .journal_service
+            .get_journal(journal_id, authority)
+            .await?;
+
+        Ok(Journal(journal))
+    }
+}
+
+pub struct Journal(JournalState);
+
+#[Object]
+impl Journal {
+    async fn id(&self) -> ID {
+        ID(self.0.id.to_string())
+    }
+
+    async fn name(&self) -> String {
+        self.0.name.to_string()
+    }
+
+    /// the month the fiscal year begins in, from 1 for January to 12 for December
+    async fn fiscal_year_start(&self) -> u32 {
+        self.0.fiscal_year_start.month()
+    }
+
+    async fn accounts(&self, ctx: &Context<'_>) -> async_graphql::Result<Vec<Account>> {
+        let (state, authority) = context(ctx)?;
+
+        Ok(state
+            .journal_service
+            .list_journal_accounts(self.0.id, authority)
+            .await?
+            .into_iter()
+            .map(|(account, _, _)| Account(account))
+            .collect())
+    }
+
+    /// ordered by effective date, then by the order they were entered
+    async fn transactions(&self, ctx: &Context<'_>) -> async_graphql::Result<Vec<Transaction>> {
+        let (state, authority) = context(ctx)?;
+
+        Ok(state
+            .journal_service
+            .list_journal_transactions(self.0.id, authority)
+            .await?
+            .into_iter()
+            .map(|(transaction, _, _)| Transaction(transaction))
+            .collect())
+    }
+}
+
+pub struct Account(AccountState);
+
+#[Object]
+impl Account {
+    async fn id(&self) -> ID {
+        ID(self.0.id.to_string())
+    }
+
+    async fn name(&self) -> String {
+        self.0.name.to_string()
+    }
+
+    /// in cents, where credits are positive
+    async fn balance(&self) -> i64 {
+        self.0.balance
+    }
+}
+
+pub struct Transaction(TransactionState);
+
+#[Object]
+impl Transaction {
+    async fn id(&self) -> ID {
+        ID(self.0.id.to_string())
+    }
+
+    async fn number(&self) -> String {
+        format_voucher_number(self.0.number)
+    }
+
+    /// formatted as YYYY-MM-DD
+    async fn effective_date(&self) -> String {
+        self.0.effective_date.to_string()
+    }
+
+    async fn reverses(&self) -> Option<ID> {
+        self.0.reverses.map(|id| ID(id.to_string()))
+    }
+
+    async fn entries(&self) -> Vec<Entry> {
+        self.0
+            .entries
+            .iter()
+            .map(|entry| Entry {
+                account_id: ID(entry.account_id.to_string()),
+                amount: entry.amount,
+                entry_type: entry.entry_type.to_string(),
+            })
+            .collect()
+    }
+}
+
+#[derive(async_graphql::SimpleObject)]
+pub struct Entry {
+    account_id: ID,
+    /// in cents
+    amount: u64,
+    /// Dr or Cr
+    entry_type: String,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn the_schema_exposes_nested_journal_data() {
+        let sdl = schema().sdl();
+
+        assert!(sdl.contains("journals: [Journal!]!"));
+        assert!(sdl.contains("accounts: [Account!]!"));
+        assert!(sdl.contains("transactions: [Transaction!]!"));
+    }
+}
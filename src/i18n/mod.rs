@@ -0,0 +1,180 @@
+use axum::extract::Request;
+use axum::http::header::ACCEPT_LANGUAGE;
+use axum::middleware::Next;
+use axum::response::Response;
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::LazyLock;
+
+/// a language the views are translated into
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Locale {
+    #[default]
+    En,
+    Es,
+}
+
+tokio::task_local! {
+    static LOCALE: Locale;
+}
+
+static EN: LazyLock<HashMap<&'static str, &'static str>> =
+    LazyLock::new(|| parse(include_str!("en.txt")));
+static ES: LazyLock<HashMap<&'static str, &'static str>> =
+    LazyLock::new(|| parse(include_str!("es.txt")));
+
+impl Locale {
+    const ALL: [Self; 2] = [Self::En, Self::Es];
+
+    /// the language subtag, as used in Accept-Language and the html lang attribute
+    pub fn tag(self) -> &'static str {
+        match self {
+            Self::En => "en",
+            Self::Es => "es",
+        }
+    }
+
+    fn catalog(self) -> &'static HashMap<&'static str, &'static str> {
+        match self {
+            Self::En => &EN,
+            Self::Es => &ES,
+        }
+    }
+
+    /// the locale of the request being handled, or english outside of one
+    pub fn current() -> Self {
+        LOCALE.try_with(|locale| *locale).unwrap_or_default()
+    }
+
+    /// the supported language the client prefers most, or english if it accepts none of them
+    pub fn from_accept_language(header: &str) -> Self {
+        let mut ranges: Vec<(f32, &str)> = header
+            .split(',')
+            .filter_map(|range| {
+                let mut parts = range.split(';');
+                let tag = parts.next()?.trim();
+                let quality = match parts.find_map(|param| param.trim().strip_prefix("q=")) {
+                    Some(quality) => quality.trim().parse().ok()?,
+                    None => 1.0,
+                };
+                (quality > 0.0).then_some((quality, tag))
+            })
+            .collect();
+
+        // the sort is stable, so ranges of equal quality keep the client's order
+        ranges.sort_by(|a, b| b.0.total_cmp(&a.0));
+
+        ranges
+            .into_iter()
+            .find_map(|(_, tag)| {
+                let primary = tag.split('-').next()?;
+                Self::ALL
+                    .into_iter()
+                    .find(|locale| locale.tag().eq_ignore_ascii_case(primary))
+            })
+            .unwrap_or_default()
+    }
+}
+
+impl fmt::Display for Locale {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.tag())
+    }
+}
+
+/// reads a catalog of `key = string` lines. blank lines and lines starting with # are skipped
+fn parse(source: &'static str) -> HashMap<&'static str, &'static str> {
+    source
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .filter_map(|line| line.split_once('='))
+        .map(|(key, string)| (key.trim(), string.trim()))
+        .collect()
+}
+
+/// the string in the current request's language. strings missing from a translation fall
+/// back to english, and strings missing from english fall back to their key
+pub fn t(key: &'static str) -> &'static str {
+    Locale::current()
+        .catalog()
+        .get(key)
+        .or_else(|| EN.get(key))
+        .copied()
+        .unwrap_or(key)
+}
+
+/// like `t`, with each `{name}` in the string replaced by its argument
+pub fn tf(key: &'static str, args: &[(&str, &dyn fmt::Display)]) -> String {
+    args.iter()
+        .fold(t(key).to_string(), |string, (name, value)| {
+            string.replace(&format!("{{{name}}}"), &value.to_string())
+        })
+}
+
+/// handles the rest of the request in the language its Accept-Language header prefers
+pub async fn set_locale(request: Request, next: Next) -> Response {
+    let locale = request
+        .headers()
+        .get(ACCEPT_LANGUAGE)
+        .and_then(|header| header.to_str().ok())
+        .map(Locale::from_accept_language)
+        .unwrap_or_default();
+
+    LOCALE.scope(locale, next.run(request)).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashSet;
+
+    #[test]
+    fn clients_get_the_language_they_prefer_most() {
+        assert_eq!(
+            Locale::from_accept_language("es-MX,es;q=0.9,en;q=0.8"),
+            Locale::Es
+        );
+        assert_eq!(
+            Locale::from_accept_language("fr-CA,en;q=0.5,es;q=0.7"),
+            Locale::Es
+        );
+        assert_eq!(Locale::from_accept_language("en-US,es"), Locale::En);
+        assert_eq!(Locale::from_accept_language("es;q=0,fr"), Locale::En);
+        assert_eq!(Locale::from_accept_language(""), Locale::En);
+        assert_eq!(Locale::from_accept_language("*"), Locale::En);
+    }
+
+    #[test]
+    fn every_translation_covers_the_english_strings() {
+        let placeholders = |string: &str| -> HashSet<String> {
+            string
+                .split('{')
+                .skip(1)
+                .filter_map(|rest| rest.split_once('}').map(|(name, _)| name.to_string()))
+                .collect()
+        };
+
+        for locale in Locale::ALL {
+            let catalog = locale.catalog();
+
+            for (key, english) in EN.iter() {
+                let translated = catalog
+                    .get(key)
+                    .unwrap_or_else(|| panic!("{locale} is missing {key}"));
+                assert_eq!(
+                    placeholders(translated),
+                    placeholders(english),
+                    "{locale} {key} has different placeholders"
+                );
+            }
+
+            for key in catalog.keys() {
+                assert!(
+                    EN.contains_key(key),
+                    "{locale} has {key}, which english doesn't"
+                );
+            }
+        }
+    }
+}
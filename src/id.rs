@@ -11,6 +11,8 @@ use sqlx::{Database, Decode, Encode, Postgres, Type};
 use std::fmt::Display;
 use std::fmt::{self};
 use std::str::FromStr;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU64, Ordering};
 use thiserror::Error;
 
 #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
@@ -61,6 +63,59 @@ impl Default for Ident {
     }
 }
 
+/// where the ids of new users, journals, accounts and so on come from
+pub trait IdProvider {
+    fn next_ident(&self) -> Ident;
+}
+
+pub struct RandomIdProvider;
+
+impl IdProvider for RandomIdProvider {
+    fn next_ident(&self) -> Ident {
+        Ident::new16()
+    }
+}
+
+/// hands out `t000000000000001`, `t000000000000002` and so on, so a test knows its ids up front
+#[derive(Default)]
+pub struct SequentialIdProvider(AtomicU64);
+
+impl IdProvider for SequentialIdProvider {
+    fn next_ident(&self) -> Ident {
+        let next = self.0.fetch_add(1, Ordering::Relaxed) + 1;
+
+        Ident::from_str(&format!("t{next:015}")).expect("sequential ids are valid cuids")
+    }
+}
+
+/// makes ids for the handlers and services. the ids are random unless a test swaps in a
+/// [`SequentialIdProvider`]. creating anything with an id that's already taken is rejected by
+/// its decision, so a reused id fails loudly rather than overwriting
+#[derive(Clone)]
+pub struct IdGenerator(Arc<dyn IdProvider + Send + Sync>);
+
+impl IdGenerator {
+    pub fn new(provider: impl IdProvider + Send + Sync + 'static) -> Self {
+        Self(Arc::new(provider))
+    }
+
+    pub fn generate<T: From<Ident>>(&self) -> T {
+        T::from(self.0.next_ident())
+    }
+}
+
+impl Default for IdGenerator {
+    fn default() -> Self {
+        Self::new(RandomIdProvider)
+    }
+}
+
+impl From<SequentialIdProvider> for IdGenerator {
+    fn from(provider: SequentialIdProvider) -> Self {
+        Self::new(provider)
+    }
+}
+
 // all of these ids must be exactly 5 ascii characters
 static VALID_CUSTOM_IDENTS: phf::Set<&'static str> = phf_set! {
     "dylan",
@@ -182,6 +237,12 @@ macro_rules! id {
             }
         }
 
+        impl From<$crate::id::Ident> for $id_name {
+            fn from(value: $crate::id::Ident) -> Self {
+                Self(value)
+            }
+        }
+
         impl From<String> for $id_name {
             fn from(value: String) -> Self {
                 Self(<Ident as From<String>>::from(value))
@@ -235,3 +296,21 @@ macro_rules! id {
         }
     };
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::journal::JournalId;
+
+    #[test]
+    fn sequential_ids_are_predictable_and_valid() {
+        let ids = IdGenerator::from(SequentialIdProvider::default());
+
+        let first: JournalId = ids.generate();
+        let second: JournalId = ids.generate();
+
+        assert_eq!(first.to_string(), "t000000000000001");
+        assert_eq!(second.to_string(), "t000000000000002");
+        assert_eq!(JournalId::from_str(&second.to_string()), Ok(second));
+    }
+}
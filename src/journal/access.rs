@@ -0,0 +1,198 @@
+use crate::BackendType;
+use crate::StateType;
+use crate::authn::get_user;
+use crate::authn::user::UserId;
+use crate::authority::{Actor, Authority};
+use crate::journal::service::JournalState;
+use crate::journal::{JournalError, JournalId, JournalResult, Permissions};
+use crate::monkesto_error::ErrorPage;
+use crate::time_provider::Timestamp;
+use axum::extract::{FromRequestParts, Path};
+use axum::http::request::Parts;
+use axum::response::{IntoResponse, Response};
+use axum_login::AuthSession;
+use std::collections::HashMap;
+use std::marker::PhantomData;
+use std::str::FromStr;
+
+/// the permission a route needs on the journal in its `{id}`
+pub trait Requirement {
+    const PERMISSIONS: Permissions;
+}
+
+pub struct Read;
+pub struct AddAccount;
+pub struct AppendTransaction;
+pub struct Invite;
+pub struct Owner;
+/// recording a transaction, or submitting one for approval
+pub struct Submit;
+pub struct Approve;
+
+impl Requirement for Read {
+    const PERMISSIONS: Permissions = Permissions::READ;
+}
+
+impl Requirement for AddAccount {
+    const PERMISSIONS: Permissions = Permissions::ADD_ACCOUNT;
+}
+
+impl Requirement for AppendTransaction {
+    const PERMISSIONS: Permissions = Permissions::APPEND_TRANSACTION;
+}
+
+impl Requirement for Invite {
+    const PERMISSIONS: Permissions = Permissions::INVITE;
+}
+
+impl Requirement for Owner {
+    const PERMISSIONS: Permissions = Permissions::OWNER;
+}
+
+impl Requirement for Submit {
+    const PERMISSIONS: Permissions =
+        Permissions::APPEND_TRANSACTION.union(Permissions::SUBMIT_ONLY);
+}
+
+impl Requirement for Approve {
+    const PERMISSIONS: Permissions = Permissions::APPROVE;
+}
+
+/// the journal a request is about, loaded once its user is known to hold the permission the
+/// route asked for. journals the user can't read are turned away like unknown ones, with a
+/// 404, and missing permissions on a readable journal with a 403
+pub struct JournalAccess<R: Requirement = Read> {
+    pub journal: JournalState,
+    pub permissions: Permissions,
+    pub actor: UserId,
+    pub created_by: Authority,
+    pub created_at: Timestamp,
+    required: PhantomData<R>,
+}
+
+impl<R: Requirement> JournalAccess<R> {
+    pub fn authority(&self) -> Authority {
+        Authority::Direct(Actor::User(self.actor))
+    }
+}
+
+/// whether permissions cover a requirement. owners hold every permission, even ones they
+/// weren't granted one by one, and a requirement of several permissions is met by any of them
+pub fn satisfies(permissions: Permissions, required: Permissions) -> bool {
+    permissions.contains(Permissions::OWNER) || permissions.intersects(required)
+}
+
+/// a journal is hidden from anyone who can't read it, so they're told it doesn't exist (404)
+/// rather than that they aren't allowed (403). only readers learn which permission they lack
+pub fn check_access(
+    journal_id: JournalId,
+    permissions: Permissions,
+    required: Permissions,
+) -> JournalResult<()> {
+    if satisfies(permissions, required) {
+        Ok(())
+    } else if satisfies(permissions, Permissions::READ) {
+        Err(JournalError::Permissions(required))
+    } else {
+        Err(JournalError::InvalidJournal(journal_id))
+    }
+}
+
+impl<R: Requirement> FromRequestParts<StateType> for JournalAccess<R> {
+    type Rejection = Response;
+
+    async fn from_request_parts(
+        parts: &mut Parts,
+        state: &StateType,
+    ) -> Result<Self, Self::Rejection> {
+        let session = AuthSession::<BackendType>::from_request_parts(parts, state)
+            .await
+            .map_err(IntoResponse::into_response)?;
+        let user = get_user(session).map_err(IntoResponse::into_response)?;
+
+        let Path(params) = Path::<HashMap<String, String>>::from_request_parts(parts, state)
+            .await
+            .map_err(IntoResponse::into_response)?;
+
+        // a route without an `{id}` fails to parse the same way a malformed one does
+        let journal_id = JournalId::from_str(params.get("id").map_or("", String::as_str))
+            .map_err(|e| ErrorPage::new(e).into_response())?;
+
+        // permissions come first, so a hidden journal isn't looked up at all
+        let permissions = state
+            .journal_service
+            .get_effective_permissions(journal_id, &Authority::Direct(Actor::User(user.id)))
+            .await
+            .and_then(|permissions| {
+                check_access(journal_id, permissions, R::PERMISSIONS).map(|_| permissions)
+            })
+            .map_err(|e| ErrorPage::new(e).into_response())?;
+
+        let (journal, created_by, created_at) = state
+            .journal_service
+            .load_journal(journal_id)
+            .await
+            .map_err(|e| ErrorPage::new(e).into_response())?;
+
+        Ok(Self {
+            journal,
+            permissions,
+            actor: user.id,
+            created_by,
+            created_at,
+            required: PhantomData,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn owners_satisfy_every_requirement() {
+        let member = Permissions::READ | Permissions::APPEND_TRANSACTION;
+
+        assert!(satisfies(member, AppendTransaction::PERMISSIONS));
+        assert!(!satisfies(member, AddAccount::PERMISSIONS));
+        assert!(!satisfies(member, Owner::PERMISSIONS));
+        assert!(!satisfies(Permissions::empty(), Read::PERMISSIONS));
+        assert!(satisfies(member, Submit::PERMISSIONS));
+        assert!(satisfies(Permissions::SUBMIT_ONLY, Submit::PERMISSIONS));
+        assert!(!satisfies(Permissions::SUBMIT_ONLY, Approve::PERMISSIONS));
+
+        for required in [
+            Read::PERMISSIONS,
+            AddAccount::PERMISSIONS,
+            AppendTransaction::PERMISSIONS,
+            Invite::PERMISSIONS,
+            Owner::PERMISSIONS,
+            Submit::PERMISSIONS,
+            Approve::PERMISSIONS,
+        ] {
+            assert!(satisfies(Permissions::OWNER, required));
+        }
+    }
+
+    #[test]
+    fn only_readers_are_told_what_they_lack() {
+        let journal_id = JournalId::from_str("t000000000000001").unwrap();
+
+        assert_eq!(
+            check_access(journal_id, Permissions::READ, Permissions::ADD_ACCOUNT),
+            Err(JournalError::Permissions(Permissions::ADD_ACCOUNT))
+        );
+        assert_eq!(
+            check_access(journal_id, Permissions::empty(), Permissions::ADD_ACCOUNT),
+            Err(JournalError::InvalidJournal(journal_id))
+        );
+        assert_eq!(
+            check_access(
+                journal_id,
+                Permissions::ADD_ACCOUNT,
+                Permissions::ADD_ACCOUNT
+            ),
+            Ok(())
+        );
+    }
+}
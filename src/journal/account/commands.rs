@@ -1,18 +1,14 @@
-use crate::BackendType;
 use crate::StateType;
-use crate::authn::get_user;
-use crate::authority::Actor;
-use crate::authority::Authority;
-use crate::journal::JournalId;
-use crate::journal::account::AccountId;
-use crate::monkesto_error::OrRedirect;
+use crate::journal::access::{AddAccount, JournalAccess, Owner};
+use crate::journal::account::views::account_list;
+use crate::journal::account::{AccountId, AccountPlacement};
+use crate::monkesto_error::{ErrorRedirect, FormRejection, OrRedirect};
 use crate::name::Name;
-use crate::time_provider::{DefaultTimeProvider, TimeProvider};
-use axum::extract::Path;
+use crate::time_provider::TimeProvider;
+use crate::validation::FormState;
 use axum::extract::State;
 use axum::response::Redirect;
 use axum_extra::extract::Form;
-use axum_login::AuthSession;
 use serde::Deserialize;
 use std::str::FromStr;
 
@@ -23,26 +19,94 @@ pub struct CreateAccountForm {
 
 pub async fn create_account(
     State(state): State<StateType>,
-    session: AuthSession<BackendType>,
-    Path(id): Path<String>,
+    access: JournalAccess<AddAccount>,
     Form(form): Form<CreateAccountForm>,
-) -> Result<Redirect, Redirect> {
-    let callback_url = &format!("/journal/{}/account", id);
+) -> Result<Redirect, FormRejection> {
+    let callback_url = &format!("/journal/{}/account", access.journal.id);
 
-    let journal_id = JournalId::from_str(&id).or_redirect(callback_url)?;
+    let mut form_state = FormState::default();
+    form_state.keep("account_name", form.account_name.as_str());
 
-    let user = get_user(session)?;
+    if let Some(name) = form_state.check("account_name", Name::try_new(form.account_name)) {
+        match state
+            .journal_service
+            .create_account(
+                state.ids.generate(),
+                access.journal.id,
+                name,
+                access.authority(),
+                state.clock.get_time(),
+            )
+            .await
+        {
+            Ok(event_id) => {
+                state.journal_service.wait_for(event_id).await;
+                return Ok(Redirect::to(callback_url));
+            }
+            Err(e) => form_state.fail(e),
+        }
+    }
 
-    let name = Name::try_new(form.account_name).or_redirect(callback_url)?;
+    let page = account_list(&state, &access, None, &form_state).await;
+
+    Err(form_state.rejected(page).into())
+}
+
+/// each account's position and group, in the order the arrange form lists them
+#[derive(Deserialize)]
+pub struct ArrangeAccountsForm {
+    #[serde(default)]
+    account: Vec<String>,
+    #[serde(default)]
+    position: Vec<String>,
+    #[serde(default)]
+    group: Vec<String>,
+}
+
+pub async fn arrange_accounts(
+    State(state): State<StateType>,
+    access: JournalAccess<Owner>,
+    Form(form): Form<ArrangeAccountsForm>,
+) -> Result<Redirect, ErrorRedirect> {
+    let callback_url = &format!("/journal/{}/account", access.journal.id);
+
+    let mut placements = Vec::with_capacity(form.account.len());
+
+    for (index, account) in form.account.iter().enumerate() {
+        let account_id = AccountId::from_str(account).or_redirect(callback_url)?;
+
+        // an account without a readable position stays where the form listed it
+        let position = form
+            .position
+            .get(index)
+            .and_then(|position| position.trim().parse::<usize>().ok())
+            .unwrap_or(index + 1);
+
+        let group = form
+            .group
+            .get(index)
+            .map(|group| group.trim())
+            .filter(|group| !group.is_empty())
+            .map(|group| Name::try_new(group.to_string()))
+            .transpose()
+            .or_redirect(callback_url)?;
+
+        placements.push((position, AccountPlacement { account_id, group }));
+    }
+
+    // accounts given the same position keep the order they were in
+    placements.sort_by_key(|(position, _)| *position);
 
     let event_id = state
         .journal_service
-        .create_account(
-            AccountId::new(),
-            journal_id,
-            name,
-            Authority::Direct(Actor::User(user.id)),
-            DefaultTimeProvider.get_time(),
+        .arrange_accounts(
+            access.journal.id,
+            placements
+                .into_iter()
+                .map(|(_, placement)| placement)
+                .collect(),
+            access.authority(),
+            state.clock.get_time(),
         )
         .await
         .or_redirect(callback_url)?;
@@ -51,3 +115,59 @@ pub async fn create_account(
 
     Ok(Redirect::to(callback_url))
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::name::Name;
+    use crate::test_app::{TestApp, assert_accepted};
+
+    #[tokio::test]
+    async fn arranged_accounts_are_listed_in_their_order() {
+        let Some(app) = TestApp::spawn().await else {
+            return;
+        };
+
+        let owner = app.signup("arranger").await;
+        let journal_id = owner.create_journal(&app, "Arranged").await;
+        for name in ["Cash", "Food", "Rent"] {
+            assert_accepted(&owner.create_account(journal_id, name).await);
+        }
+        let name = |name: &str| Name::try_new(name.to_string()).expect("invalid name");
+        assert_eq!(
+            app.accounts(journal_id).await,
+            vec![name("Cash"), name("Food"), name("Rent")]
+        );
+
+        let (cash, food, rent) = (
+            app.account_id(journal_id, "Cash").await.to_string(),
+            app.account_id(journal_id, "Food").await.to_string(),
+            app.account_id(journal_id, "Rent").await.to_string(),
+        );
+        assert_accepted(
+            &owner
+                .client
+                .post(&format!("/journal/{journal_id}/account/arrange"))
+                .form(&[
+                    ("account", cash.as_str()),
+                    ("position", "3"),
+                    ("group", "Assets"),
+                    ("account", food.as_str()),
+                    ("position", "2"),
+                    ("group", "Spending"),
+                    ("account", rent.as_str()),
+                    ("position", "1"),
+                    ("group", "Spending"),
+                ])
+                .await,
+        );
+
+        assert_eq!(
+            app.accounts(journal_id).await,
+            vec![name("Rent"), name("Food"), name("Cash")]
+        );
+
+        // accounts created since the arrangement follow it
+        assert_accepted(&owner.create_account(journal_id, "Bank").await);
+        assert_eq!(app.accounts(journal_id).await.last(), Some(&name("Bank")));
+    }
+}
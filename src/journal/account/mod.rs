@@ -9,10 +9,15 @@ use std::convert::From;
 pub fn router() -> Router<crate::StateType> {
     Router::new()
         .route("/journal/{id}/account", get(views::account_list_page))
+        .route("/journal/{id}/account/search", get(views::account_search))
         .route(
             "/journal/{id}/createaccount",
             axum::routing::post(commands::create_account),
         )
+        .route(
+            "/journal/{id}/account/arrange",
+            axum::routing::post(commands::arrange_accounts),
+        )
         .route_layer(login_required!(crate::BackendType, login_url = "/signin"))
 }
 
@@ -21,7 +26,8 @@ use crate::id;
 use crate::id::Ident;
 use crate::journal::domain::{AccountEvent, JournalDomainEvent};
 use crate::journal::member::JournalMember;
-use crate::journal::{Journal, Permissions, validate_permissions};
+use crate::journal::transaction::AllJournalAccounts;
+use crate::journal::{Journal, Permissions, check_permissions};
 use crate::journal::{JournalError, JournalId};
 use crate::name::Name;
 use crate::status::Status;
@@ -125,14 +131,7 @@ impl Decision for CreateAccount {
             return Err(JournalError::InvalidJournal(self.journal_id));
         }
 
-        if !validate_permissions(
-            actor,
-            &self.authority,
-            journal.owner,
-            Permissions::ADD_ACCOUNT,
-        ) {
-            return Err(JournalError::Permissions(Permissions::ADD_ACCOUNT));
-        }
+        check_permissions(actor, &self.authority, journal, Permissions::ADD_ACCOUNT)?;
 
         Ok(vec![JournalDomainEvent::AccountCreated {
             account_id: self.account_id,
@@ -152,7 +151,6 @@ pub struct RenameAccount {
     timestamp: Timestamp,
 }
 
-#[expect(unused)]
 impl RenameAccount {
     pub fn new(
         account_id: AccountId,
@@ -199,9 +197,7 @@ impl Decision for RenameAccount {
             return Err(JournalError::InvalidJournal(self.journal_id));
         }
 
-        if !validate_permissions(actor, &self.authority, journal.owner, Permissions::OWNER) {
-            return Err(JournalError::Permissions(Permissions::OWNER));
-        }
+        check_permissions(actor, &self.authority, journal, Permissions::OWNER)?;
 
         Ok(vec![JournalDomainEvent::AccountRenamed {
             account_id: self.account_id,
@@ -264,9 +260,7 @@ impl Decision for DeleteAccount {
             return Err(JournalError::InvalidJournal(self.journal_id));
         }
 
-        if !validate_permissions(actor, &self.authority, journal.owner, Permissions::OWNER) {
-            return Err(JournalError::Permissions(Permissions::OWNER));
-        }
+        check_permissions(actor, &self.authority, journal, Permissions::OWNER)?;
 
         Ok(vec![JournalDomainEvent::AccountDeleted {
             account_id: self.account_id,
@@ -275,3 +269,75 @@ impl Decision for DeleteAccount {
         }])
     }
 }
+
+/// where an account is listed: the accounts are listed in the order of their placements,
+/// under a heading for each run of accounts in the same group
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AccountPlacement {
+    pub account_id: AccountId,
+    pub group: Option<Name>,
+}
+
+pub struct ArrangeAccounts {
+    journal_id: JournalId,
+    arrangement: Vec<AccountPlacement>,
+    authority: Authority,
+    timestamp: Timestamp,
+}
+
+impl ArrangeAccounts {
+    pub fn new(
+        journal_id: JournalId,
+        arrangement: Vec<AccountPlacement>,
+        authority: Authority,
+        timestamp: Timestamp,
+    ) -> Self {
+        Self {
+            journal_id,
+            arrangement,
+            authority,
+            timestamp,
+        }
+    }
+}
+
+impl Decision for ArrangeAccounts {
+    type Event = JournalDomainEvent;
+    type StateQuery = (AllJournalAccounts, Journal, JournalMember);
+    type Error = JournalError;
+
+    fn state_query(&self) -> Self::StateQuery {
+        (
+            AllJournalAccounts::new(self.journal_id),
+            Journal::new(self.journal_id),
+            JournalMember::new(
+                self.journal_id,
+                self.authority.user_id().unwrap_or_default(),
+            ),
+        )
+    }
+
+    fn process(
+        &self,
+        (accounts, journal, actor): &Self::StateQuery,
+    ) -> Result<Vec<Self::Event>, Self::Error> {
+        if !journal.status.valid() {
+            return Err(JournalError::InvalidJournal(self.journal_id));
+        }
+
+        check_permissions(actor, &self.authority, journal, Permissions::OWNER)?;
+
+        for placement in &self.arrangement {
+            if !accounts.contains(&placement.account_id) {
+                return Err(JournalError::InvalidAccount(placement.account_id));
+            }
+        }
+
+        Ok(vec![JournalDomainEvent::AccountsArranged {
+            journal_id: self.journal_id,
+            arrangement: self.arrangement.clone(),
+            authority: self.authority.clone(),
+            timestamp: self.timestamp,
+        }])
+    }
+}
@@ -1,21 +1,35 @@
-use crate::BackendType;
 use crate::StateType;
-use crate::authn::get_user;
-use crate::authority::Actor;
-use crate::authority::Authority;
+use crate::charts::{Chart, Series, sparkline};
 use crate::id::Ident;
-use crate::journal::JournalId;
-use crate::journal::layout::layout;
-use crate::monkesto_error::MonkestoError;
+use crate::journal::Permissions;
+use crate::journal::access::{JournalAccess, Requirement, check_access};
+use crate::journal::account::AccountId;
+use crate::journal::fiscal;
+use crate::journal::json::AccountJson;
+use crate::journal::layout::journal_layout;
+use crate::journal::provenance::{self, LastChange};
+use crate::journal::service::AccountState;
+use crate::journal::transaction::views::format_amount;
 use crate::monkesto_error::UrlError;
-use axum::extract::Path;
+use crate::monkesto_error::{MonkestoError, MonkestoResult};
+use crate::negotiate::WantsJson;
+use crate::time_provider::TimeProvider;
+use crate::validation::FormState;
+use axum::Json;
 use axum::extract::Query;
 use axum::extract::State;
-use axum::response::Redirect;
-use axum_login::AuthSession;
+use axum::response::{IntoResponse, Response};
+use chrono::{Months, NaiveDate};
 use maud::Markup;
 use maud::html;
-use std::str::FromStr;
+use serde::Deserialize;
+use std::collections::HashMap;
+
+/// how many matches the account picker is offered at once
+const SEARCH_LIMIT: usize = 20;
+
+/// how many months of balances each account's sparkline covers, through the current one
+const SPARKLINE_MONTHS: u32 = 12;
 
 #[expect(dead_code)]
 struct AccountItem {
@@ -24,47 +38,271 @@ struct AccountItem {
     pub balance: i64, // in cents
 }
 
+/// an option for each account in the journal's order, with each group's accounts under
+/// its heading. the option whose value matches `selected` is selected
+pub fn account_options(accounts: &[AccountState], selected: Option<&str>) -> Markup {
+    let option = |account: &AccountState| {
+        html! {
+            option value=(account.id) selected[selected == Some(account.id.to_string().as_str())] { (account.name) }
+        }
+    };
+
+    html! {
+        @for run in accounts.chunk_by(|a, b| a.group == b.group) {
+            @if let Some(group) = &run[0].group {
+                optgroup label=(group) {
+                    @for account in run { (option(account)) }
+                }
+            } @else {
+                @for account in run { (option(account)) }
+            }
+        }
+    }
+}
+
+/// each account's balance at the end of the `SPARKLINE_MONTHS` months through the one `today`
+/// is in, oldest first, from the accounts' monthly changes. an account whose balance hasn't
+/// changed by the end of the last month is left out, since there's no trend to show
+fn balance_history(
+    changes: &[(AccountId, NaiveDate, i64)],
+    today: NaiveDate,
+) -> (Vec<NaiveDate>, HashMap<AccountId, Vec<i64>>) {
+    let last = fiscal::period_start(today);
+    let months: Vec<NaiveDate> = (0..SPARKLINE_MONTHS)
+        .rev()
+        .filter_map(|back| last.checked_sub_months(Months::new(back)))
+        .collect();
+
+    let mut histories: HashMap<AccountId, Vec<i64>> = HashMap::new();
+    for (account_id, period, change) in changes {
+        let history = histories
+            .entry(*account_id)
+            .or_insert_with(|| vec![0; months.len()]);
+
+        // a change counts towards the balance at the end of its month and every one after it
+        for (month, balance) in months.iter().zip(history.iter_mut()) {
+            if period <= month {
+                *balance += change;
+            }
+        }
+    }
+    histories.retain(|_, history| history.iter().any(|balance| *balance != 0));
+
+    (months, histories)
+}
+
+/// a sparkline's amount, negative once the balance has crossed to the other side
+fn format_trend(amount: i64) -> String {
+    let formatted = format_amount(amount.unsigned_abs());
+    if amount < 0 {
+        format!("-{formatted}")
+    } else {
+        formatted
+    }
+}
+
+#[derive(Deserialize)]
+pub struct AccountSearchQuery {
+    #[serde(default)]
+    q: String,
+}
+
+/// the accounts whose names match `q`, best match first. the transaction form's account
+/// picker gets them as options, and clients that ask for json get the accounts themselves
+pub async fn account_search(
+    State(state): State<StateType>,
+    access: JournalAccess,
+    WantsJson(json): WantsJson,
+    Query(query): Query<AccountSearchQuery>,
+) -> Result<Response, MonkestoError> {
+    let mut accounts = state
+        .journal_service
+        .search_accounts(access.journal.id, &access.authority(), query.q.trim())
+        .await?;
+    accounts.truncate(SEARCH_LIMIT);
+
+    if json {
+        return Ok(
+            Json(accounts.iter().map(AccountJson::from).collect::<Vec<_>>()).into_response(),
+        );
+    }
+
+    Ok(html! {
+        @for account in &accounts {
+            li
+            role="option"
+            aria-selected="false"
+            data-account-id=(account.id)
+            data-account-name=(account.name)
+            class="cursor-pointer px-3 py-1.5 text-sm text-gray-900 aria-selected:bg-indigo-600 aria-selected:text-white dark:text-white" {
+                (account.name)
+                @if let Some(group) = &account.group {
+                    span class="ml-2 text-xs opacity-60" { (group) }
+                }
+            }
+        }
+    }
+    .into_response())
+}
+
 pub async fn account_list_page(
     State(state): State<StateType>,
-    session: AuthSession<BackendType>,
-    Path(id): Path<String>,
+    access: JournalAccess,
     Query(err): Query<UrlError>,
-) -> Result<Markup, Redirect> {
-    let user = get_user(session)?;
-    let authority = Authority::Direct(Actor::User(user.id));
-    let journal_id_res = JournalId::from_str(&id);
+) -> Markup {
+    account_list(&state, &access, err.err, &FormState::default()).await
+}
+
+/// the account list, with the create account form filled in from a rejected submission
+pub async fn account_list<R: Requirement>(
+    state: &StateType,
+    access: &JournalAccess<R>,
+    err: Option<String>,
+    form: &FormState,
+) -> Markup {
+    let journal_id = access.journal.id;
+    let id = &journal_id.to_string();
+    let now = state.clock.get_time();
+
+    let accounts_res: MonkestoResult<Vec<AccountState>> = state
+        .journal_service
+        .list_journal_accounts(journal_id, &access.authority())
+        .await
+        .map(|accounts| {
+            accounts
+                .into_iter()
+                .map(|(account, _, _)| account)
+                .collect()
+        })
+        .map_err(|e| e.into());
+
+    // the list is still useful without the sparklines, so they're left off if they can't be read
+    let (months, histories) = state
+        .journal_service
+        .monthly_balance_changes(journal_id, &access.authority())
+        .await
+        .map(|changes| balance_history(&changes, now.date_naive()))
+        .unwrap_or_default();
+
+    let changes: HashMap<AccountId, LastChange> = match state
+        .journal_service
+        .last_account_changes(journal_id, &access.authority())
+        .await
+    {
+        Ok(changes) => {
+            let names = provenance::actor_names(state, changes.values()).await;
+            changes
+                .iter()
+                .map(|(account_id, change)| (*account_id, LastChange::new(change, &names)))
+                .collect()
+        }
+        Err(_) => HashMap::new(),
+    };
+    let timezone = state
+        .authn_service
+        .fetch_user(access.actor)
+        .await
+        .map(|user| user.timezone())
+        .unwrap_or_default();
+
+    let can_arrange = check_access(journal_id, access.permissions, Permissions::OWNER).is_ok();
 
     let content = html! {
-        @if let Ok(journal_id) = journal_id_res {
-            @match state.journal_service.list_journal_accounts(journal_id, &authority).await {
-                Ok(journal_accounts) => {
-                     @for (acc, _, _) in journal_accounts {
-                        a
-                        href=(format!("/journal/{}/account/{}", journal_id, acc.id))
-                        class="block p-4 bg-white dark:bg-gray-800 border border-gray-200 dark:border-gray-700 rounded-xl hover:bg-gray-50 dark:hover:bg-gray-700 transition-colors" {
+        @match &accounts_res {
+            Ok(accounts) => {
+                @for run in accounts.chunk_by(|a, b| a.group == b.group) {
+                    @if let Some(group) = &run[0].group {
+                        h2 class="mt-4 text-sm font-semibold uppercase tracking-wide text-gray-500 dark:text-gray-400" { (group) }
+                    }
+                    @for acc in run {
+                        // the whole card links to the account, except the link to its last change
+                        div class="relative p-4 bg-white dark:bg-gray-800 border border-gray-200 dark:border-gray-700 rounded-xl hover:bg-gray-50 dark:hover:bg-gray-700 transition-colors" {
                             div class="flex justify-between items-center" {
-                                h3 class="text-lg font-semibold text-gray-900 dark:text-white" { (acc.name) }
+                                div {
+                                    h3 class="text-lg font-semibold text-gray-900 dark:text-white" {
+                                        a href=(format!("/journal/{}/account/{}", journal_id, acc.id)) class="after:absolute after:inset-0" { (acc.name) }
+                                    }
+                                    @if let Some(change) = changes.get(&acc.id) {
+                                        div class="relative" { (provenance::last_change_link(journal_id, change, timezone, now)) }
+                                    }
+                                }
                                 @let balance = acc.balance.abs();
-                                div class="text-right" {
-                                    div class="text-lg font-medium text-gray-900 dark:text-white" {
-                                        (format!("${}.{:02} {}", balance / 100, balance % 100, if acc.balance < 0 { "Dr" } else { "Cr" }))
+                                div class="flex items-center gap-4" {
+                                    @if let Some(history) = histories.get(&acc.id) {
+                                        // drawn on the side the balance is on, so it rises as the balance grows
+                                        (sparkline(&Chart {
+                                            title: format!("{} balance by month", acc.name),
+                                            labels: months.iter().map(|month| month.format("%b %Y").to_string()).collect(),
+                                            series: vec![Series {
+                                                name: acc.name.to_string(),
+                                                values: history.iter().map(|amount| if acc.balance < 0 { -amount } else { *amount }).collect(),
+                                            }],
+                                            format: format_trend,
+                                        }))
+                                    }
+                                    div class="text-right" {
+                                        div class="text-lg font-medium text-gray-900 dark:text-white" {
+                                            (format!("${}.{:02} {}", balance / 100, balance % 100, if acc.balance < 0 { "Dr" } else { "Cr" }))
+                                        }
                                     }
                                 }
                             }
                         }
                     }
-                },
-                Err(e) => {
-                    p {
-                        "failed to get the accounts for " (journal_id) ": " (e)
-                    }
+                }
+            },
+            Err(e) => {
+                p {
+                    "failed to get the accounts for " (journal_id) ": " (e)
                 }
             }
         }
-        @else {
-            div class="flex justify-center items-center h-full" {
-                p class="text-gray-500 dark:text-gray-400" {
-                    "Invalid journal Id"
+
+        a
+        href=(format!("/journal/{}/report/trial-balance.pdf", id))
+        class="self-end text-sm font-medium text-indigo-600 hover:text-indigo-500 dark:text-indigo-400 dark:hover:text-indigo-300" {
+            "Download trial balance (PDF)"
+        }
+
+        a
+        href=(format!("/journal/{}/account/export.csv", id))
+        class="self-end text-sm font-medium text-indigo-600 hover:text-indigo-500 dark:text-indigo-400 dark:hover:text-indigo-300" {
+            "Download accounts (CSV)"
+        }
+
+        @if can_arrange && let Ok(accounts) = &accounts_res && !accounts.is_empty() {
+            details class="p-4 bg-gray-50 dark:bg-gray-800 border border-gray-200 dark:border-gray-700 rounded-xl" {
+                summary class="cursor-pointer text-sm font-medium text-gray-900 dark:text-white" { "Arrange accounts" }
+                form action=(format!("/journal/{}/account/arrange", id)) method="post" class="mt-4 space-y-3" {
+                    p class="text-sm text-gray-500 dark:text-gray-400" {
+                        "Accounts are listed by their number, lowest first, under their group's heading. Leave the group empty to list an account without one."
+                    }
+                    @for (i, acc) in accounts.iter().enumerate() {
+                        div class="grid grid-cols-12 gap-3 items-center" {
+                            input type="hidden" name="account" value=(acc.id);
+                            label for=(format!("position-{i}")) class="col-span-6 text-sm text-gray-900 dark:text-white" { (acc.name) }
+                            input
+                            id=(format!("position-{i}"))
+                            type="number"
+                            name="position"
+                            value=(i + 1)
+                            min="1"
+                            aria-label=(format!("{} position", acc.name))
+                            class="col-span-2 rounded-md border border-gray-300 dark:border-gray-600 bg-white dark:bg-gray-800 px-2 py-1 text-sm text-gray-900 dark:text-white";
+                            input
+                            type="text"
+                            name="group"
+                            value=[acc.group.as_ref()]
+                            placeholder="Group"
+                            aria-label=(format!("{} group", acc.name))
+                            class="col-span-4 rounded-md border border-gray-300 dark:border-gray-600 bg-white dark:bg-gray-800 px-2 py-1 text-sm text-gray-900 dark:text-white";
+                        }
+                    }
+                    button
+                    type="submit"
+                    class="px-4 py-2 text-sm font-medium bg-white dark:bg-gray-700 border border-gray-300 dark:border-gray-600 rounded-md text-gray-700 dark:text-gray-200 hover:bg-gray-100 dark:hover:bg-gray-600" {
+                        "Save arrangement"
+                    }
                 }
             }
         }
@@ -87,12 +325,18 @@ pub async fn account_list_page(
                         id="account_name"
                         type="text"
                         name="account_name"
+                        value=[form.value("account_name")]
                         required
+                        aria-invalid=[form.invalid("account_name")]
+                        aria-describedby=[form.described_by("account_name")]
                         class="block w-full rounded-md bg-white px-3 py-1.5 text-base text-gray-900 outline-1 -outline-offset-1 outline-gray-300 placeholder:text-gray-400 focus:outline-2 focus:-outline-offset-2 focus:outline-indigo-600 sm:text-sm/6 dark:bg-white/5 dark:text-white dark:outline-white/10 dark:placeholder:text-gray-500 dark:focus:outline-indigo-500"
                         ;
                     }
+                    (form.message("account_name"))
                 }
 
+                (form.summary())
+
                 div {
                     button
                     type="submit"
@@ -104,7 +348,7 @@ pub async fn account_list_page(
         }
 
 
-        @if let Some(e) = err.err {
+        @if let Some(e) = err {
             p {
                 (format!("An error occurred: {:?}", MonkestoError::decode(&e)))
             }
@@ -117,21 +361,78 @@ pub async fn account_list_page(
         }
     };
 
-    let journal_name = if let Ok(id) = journal_id_res {
-        state
-            .journal_service
-            .get_journal(id, &authority)
+    journal_layout(&access.journal, wrapped_content)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(s: &str) -> NaiveDate {
+        NaiveDate::parse_from_str(s, "%Y-%m-%d").expect("invalid date")
+    }
+    use crate::test_app::{TestApp, assert_accepted};
+    use serde_json::Value;
+
+    #[test]
+    fn balances_carry_forward_from_before_the_window() {
+        let cash = AccountId::new();
+        let idle = AccountId::new();
+        let changes = vec![
+            (cash, date("2024-03-01"), 10000),
+            (cash, date("2026-09-01"), -2500),
+            (cash, date("2026-10-01"), 500),
+            (idle, date("2025-01-01"), 700),
+            (idle, date("2025-02-01"), -700),
+        ];
+
+        let (months, histories) = balance_history(&changes, date("2026-10-17"));
+        assert_eq!(months.len(), 12);
+        assert_eq!(months.first(), Some(&date("2025-11-01")));
+        assert_eq!(months.last(), Some(&date("2026-10-01")));
+
+        let history = &histories[&cash];
+        assert_eq!(history[..10], [10000; 10]);
+        assert_eq!(history[10..], [7500, 8000]);
+        assert!(!histories.contains_key(&idle));
+    }
+
+    #[tokio::test]
+    async fn account_search_finds_accounts_by_part_of_their_name() {
+        let Some(app) = TestApp::spawn().await else {
+            return;
+        };
+
+        let user = app.signup("searcher").await;
+        let journal_id = user.create_journal(&app, "Searched").await;
+        for name in ["Cash", "Rent", "Rental Income"] {
+            assert_accepted(&user.create_account(journal_id, name).await);
+        }
+        let rent = app.account_id(journal_id, "Rent").await;
+
+        let found = user
+            .client
+            .get(&format!("/journal/{journal_id}/account/search?q=ren"))
             .await
-            .map(|(j, _, _)| j.name.to_string())
-            .unwrap_or_else(|e| format!("failed to fetch the journal name: {e}"))
-    } else {
-        "invalid journal id".to_string()
-    };
+            .json::<Value>();
+        let names: Vec<&str> = found
+            .as_array()
+            .expect("the matches aren't a list")
+            .iter()
+            .filter_map(|account| account["name"].as_str())
+            .collect();
+        assert_eq!(names.len(), 2);
+        assert!(!names.contains(&"Cash"));
 
-    Ok(layout(
-        Some(&journal_name),
-        true,
-        Some(&id),
-        wrapped_content,
-    ))
+        let options = user
+            .client
+            .get(&format!("/journal/{journal_id}/account/search?q=rent"))
+            // the picker asks for the options rather than json
+            .clear_headers()
+            .add_header("accept", "text/html")
+            .await
+            .text();
+        assert!(options.contains(&format!(r#"data-account-id="{rent}""#)));
+        assert!(options.contains(r#"role="option""#));
+    }
 }
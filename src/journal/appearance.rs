@@ -0,0 +1,228 @@
+use crate::journal::JournalError;
+use crate::name::Name;
+use maud::Markup;
+use maud::html;
+use serde::Deserialize;
+use serde::Serialize;
+use sqlx::encode::IsNull;
+use sqlx::error::BoxDynError;
+use sqlx::{Database, Decode, Encode, Postgres, Type};
+use std::fmt::Display;
+use std::fmt::Formatter;
+use std::str::FromStr;
+
+/// the most characters an icon can have. a single emoji can take several, joined or modified
+pub const MAX_ICON_LENGTH: usize = 8;
+
+/// the color a journal's badge is drawn in
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum JournalColor {
+    #[default]
+    Gray,
+    Red,
+    Orange,
+    Amber,
+    Green,
+    Teal,
+    Blue,
+    Indigo,
+    Purple,
+    Pink,
+}
+
+impl JournalColor {
+    pub const ALL: [Self; 10] = [
+        Self::Gray,
+        Self::Red,
+        Self::Orange,
+        Self::Amber,
+        Self::Green,
+        Self::Teal,
+        Self::Blue,
+        Self::Indigo,
+        Self::Purple,
+        Self::Pink,
+    ];
+
+    /// the badge's colors, spelled out in full so tailwind finds them
+    pub fn class(self) -> &'static str {
+        match self {
+            Self::Gray => "bg-gray-100 text-gray-700 dark:bg-gray-700 dark:text-gray-200",
+            Self::Red => "bg-red-100 text-red-700 dark:bg-red-900 dark:text-red-200",
+            Self::Orange => "bg-orange-100 text-orange-700 dark:bg-orange-900 dark:text-orange-200",
+            Self::Amber => "bg-amber-100 text-amber-700 dark:bg-amber-900 dark:text-amber-200",
+            Self::Green => "bg-green-100 text-green-700 dark:bg-green-900 dark:text-green-200",
+            Self::Teal => "bg-teal-100 text-teal-700 dark:bg-teal-900 dark:text-teal-200",
+            Self::Blue => "bg-blue-100 text-blue-700 dark:bg-blue-900 dark:text-blue-200",
+            Self::Indigo => "bg-indigo-100 text-indigo-700 dark:bg-indigo-900 dark:text-indigo-200",
+            Self::Purple => "bg-purple-100 text-purple-700 dark:bg-purple-900 dark:text-purple-200",
+            Self::Pink => "bg-pink-100 text-pink-700 dark:bg-pink-900 dark:text-pink-200",
+        }
+    }
+
+    /// the i18n key of the color's name
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::Gray => "journal.color.gray",
+            Self::Red => "journal.color.red",
+            Self::Orange => "journal.color.orange",
+            Self::Amber => "journal.color.amber",
+            Self::Green => "journal.color.green",
+            Self::Teal => "journal.color.teal",
+            Self::Blue => "journal.color.blue",
+            Self::Indigo => "journal.color.indigo",
+            Self::Purple => "journal.color.purple",
+            Self::Pink => "journal.color.pink",
+        }
+    }
+}
+
+impl Display for JournalColor {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Gray => write!(f, "gray"),
+            Self::Red => write!(f, "red"),
+            Self::Orange => write!(f, "orange"),
+            Self::Amber => write!(f, "amber"),
+            Self::Green => write!(f, "green"),
+            Self::Teal => write!(f, "teal"),
+            Self::Blue => write!(f, "blue"),
+            Self::Indigo => write!(f, "indigo"),
+            Self::Purple => write!(f, "purple"),
+            Self::Pink => write!(f, "pink"),
+        }
+    }
+}
+
+impl FromStr for JournalColor {
+    type Err = JournalError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::ALL
+            .into_iter()
+            .find(|color| color.to_string() == s)
+            .ok_or_else(|| JournalError::InvalidJournalColor(s.to_string()))
+    }
+}
+
+impl Type<Postgres> for JournalColor {
+    fn type_info() -> <Postgres as Database>::TypeInfo {
+        <String as Type<Postgres>>::type_info()
+    }
+}
+
+impl<'q> Encode<'q, Postgres> for JournalColor {
+    fn encode_by_ref(
+        &self,
+        buf: &mut <Postgres as Database>::ArgumentBuffer<'q>,
+    ) -> Result<IsNull, BoxDynError> {
+        <String as Encode<Postgres>>::encode(self.to_string(), buf)
+    }
+}
+
+impl<'r> Decode<'r, Postgres> for JournalColor {
+    fn decode(value: <Postgres as Database>::ValueRef<'r>) -> Result<Self, BoxDynError> {
+        Ok(<&str as Decode<Postgres>>::decode(value)?.parse()?)
+    }
+}
+
+/// an emoji shown on the journal's badge in place of its initial
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct JournalIcon(String);
+
+impl JournalIcon {
+    /// letters, digits and spaces are turned away, so the icon can't be mistaken for text
+    pub fn try_new(icon: &str) -> Result<Self, JournalError> {
+        let icon = icon.trim();
+        let length = icon.chars().count();
+
+        if length == 0
+            || length > MAX_ICON_LENGTH
+            || icon
+                .chars()
+                .any(|c| c.is_alphanumeric() || c.is_whitespace() || c.is_ascii())
+        {
+            return Err(JournalError::InvalidJournalIcon(icon.to_string()));
+        }
+
+        Ok(Self(icon.to_string()))
+    }
+}
+
+impl Display for JournalIcon {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+impl Type<Postgres> for JournalIcon {
+    fn type_info() -> <Postgres as Database>::TypeInfo {
+        <String as Type<Postgres>>::type_info()
+    }
+}
+
+impl<'q> Encode<'q, Postgres> for JournalIcon {
+    fn encode_by_ref(
+        &self,
+        buf: &mut <Postgres as Database>::ArgumentBuffer<'q>,
+    ) -> Result<IsNull, BoxDynError> {
+        <String as Encode<Postgres>>::encode_by_ref(&self.0, buf)
+    }
+}
+
+impl<'r> Decode<'r, Postgres> for JournalIcon {
+    fn decode(value: <Postgres as Database>::ValueRef<'r>) -> Result<Self, BoxDynError> {
+        Ok(Self::try_new(<&str as Decode<Postgres>>::decode(value)?)?)
+    }
+}
+
+/// how the journal is told apart from the others its members belong to
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct JournalAppearance {
+    pub color: JournalColor,
+    pub icon: Option<JournalIcon>,
+}
+
+/// a square in the journal's color with its icon, or the first letter of its name
+pub fn journal_badge(name: &Name, appearance: &JournalAppearance) -> Markup {
+    let initial = name
+        .as_ref()
+        .chars()
+        .next()
+        .map(|c| c.to_uppercase().to_string())
+        .unwrap_or_default();
+
+    html! {
+        span
+        class=(format!("inline-flex shrink-0 items-center justify-center size-8 rounded-lg text-base font-semibold {}", appearance.color.class()))
+        aria-hidden="true" {
+            @match &appearance.icon {
+                Some(icon) => (icon),
+                None => (initial),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn icons_must_be_emoji() {
+        assert!(JournalIcon::try_new("🏠").is_ok());
+        assert!(JournalIcon::try_new(" 👩‍👩‍👧 ").is_ok());
+        assert!(JournalIcon::try_new("").is_err());
+        assert!(JournalIcon::try_new("A").is_err());
+        assert!(JournalIcon::try_new("🏠 🚗").is_err());
+        assert!(JournalIcon::try_new("🏠🚗🏠🚗🏠🚗🏠🚗🏠").is_err());
+    }
+
+    #[test]
+    fn colors_round_trip_through_their_names() {
+        for color in JournalColor::ALL {
+            assert_eq!(color.to_string().parse::<JournalColor>(), Ok(color));
+        }
+        assert!("chartreuse".parse::<JournalColor>().is_err());
+    }
+}
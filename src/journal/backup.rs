@@ -0,0 +1,230 @@
+use crate::journal::JournalService;
+use crate::journal::domain::JournalDomainEvent;
+use crate::journal::store::PgJournalEventStore;
+use crate::shutdown;
+use crate::time_provider::{TimeProvider, Timestamp};
+use axum_login::tracing;
+use disintegrate::{EventStore, StreamItem, query};
+use futures::StreamExt;
+use serde_json::json;
+use std::env;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use thiserror::Error;
+use tokio::io::{AsyncWriteExt, BufWriter};
+
+#[derive(Debug, Error)]
+pub enum BackupConfigError {
+    #[error("BACKUP_INTERVAL_SECS must be a whole number of seconds, found {0}")]
+    Interval(String),
+
+    #[error("BACKUP_KEEP must be a whole number of at least one, found {0}")]
+    Keep(String),
+}
+
+#[derive(Debug, Error)]
+pub enum BackupError {
+    #[error("failed to write the backup: {0}")]
+    Io(#[from] std::io::Error),
+
+    #[error("failed to read the journal events: {0}")]
+    EventStore(#[from] disintegrate_postgres::Error),
+
+    #[error("failed to serialize a journal event: {0}")]
+    Json(#[from] serde_json::Error),
+}
+
+/// where the backups of the journal events are written, how often they're taken, and how
+/// many are kept before the oldest are deleted. there are no backups without a directory
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct BackupConfig {
+    pub directory: Option<PathBuf>,
+    pub interval: Duration,
+    pub keep: usize,
+}
+
+impl Default for BackupConfig {
+    fn default() -> Self {
+        Self {
+            directory: None,
+            interval: Duration::from_secs(7 * 24 * 60 * 60),
+            keep: 4,
+        }
+    }
+}
+
+impl BackupConfig {
+    pub fn from_env() -> Result<Self, BackupConfigError> {
+        let default = Self::default();
+
+        let directory = env::var("BACKUP_DIR")
+            .ok()
+            .filter(|dir| !dir.is_empty())
+            .map(PathBuf::from);
+
+        let interval = match env::var("BACKUP_INTERVAL_SECS") {
+            Ok(value) => Duration::from_secs(
+                value
+                    .parse::<u64>()
+                    .map_err(|_| BackupConfigError::Interval(value))?,
+            ),
+            Err(_) => default.interval,
+        };
+
+        let keep = match env::var("BACKUP_KEEP") {
+            Ok(value) => value
+                .parse::<usize>()
+                .ok()
+                .filter(|keep| *keep > 0)
+                .ok_or(BackupConfigError::Keep(value))?,
+            Err(_) => default.keep,
+        };
+
+        Ok(Self {
+            directory,
+            interval,
+            keep,
+        })
+    }
+}
+
+const PREFIX: &str = "journal-events-";
+const EXTENSION: &str = ".jsonl";
+
+/// the name of a backup taken at the time. names sort in the order the backups were taken
+fn backup_name(at: Timestamp) -> String {
+    format!("{PREFIX}{}{EXTENSION}", at.format("%Y%m%dT%H%M%SZ"))
+}
+
+/// the backups to delete so that only the newest `keep` are left
+fn expired(mut names: Vec<String>, keep: usize) -> Vec<String> {
+    names.retain(|name| name.starts_with(PREFIX) && name.ends_with(EXTENSION));
+    names.sort();
+    names.truncate(names.len().saturating_sub(keep));
+
+    names
+}
+
+/// writes every journal event to a new backup in the directory, one json object per line in
+/// the order they were recorded, and returns where it was written and how many events it has.
+/// the events are written as they're read back from the store, so they're no longer encrypted
+/// and the directory should be kept as private as the database
+pub async fn write_backup(
+    event_store: &PgJournalEventStore,
+    directory: &Path,
+    at: Timestamp,
+) -> Result<(PathBuf, u64), BackupError> {
+    tokio::fs::create_dir_all(directory).await?;
+
+    let path = directory.join(backup_name(at));
+    // it's only renamed once it's complete, so an interrupted backup is never kept as one
+    let partial = path.with_extension("partial");
+
+    let mut file = BufWriter::new(tokio::fs::File::create(&partial).await?);
+    let mut written = 0;
+
+    let query = query!(JournalDomainEvent);
+    let mut events = event_store.stream(&query);
+    while let Some(item) = events.next().await {
+        if let StreamItem::Event(event) = item? {
+            let line = json!({ "id": event.id(), "event": event.into_inner() });
+            file.write_all(serde_json::to_string(&line)?.as_bytes())
+                .await?;
+            file.write_all(b"\n").await?;
+            written += 1;
+        }
+    }
+
+    file.flush().await?;
+    file.into_inner().sync_all().await?;
+    tokio::fs::rename(&partial, &path).await?;
+
+    Ok((path, written))
+}
+
+/// deletes the oldest backups in the directory beyond the newest `keep`
+async fn rotate(directory: &Path, keep: usize) -> Result<Vec<String>, BackupError> {
+    let mut names = Vec::new();
+    let mut entries = tokio::fs::read_dir(directory).await?;
+    while let Some(entry) = entries.next_entry().await? {
+        if let Ok(name) = entry.file_name().into_string() {
+            names.push(name);
+        }
+    }
+
+    let expired = expired(names, keep);
+    for name in &expired {
+        tokio::fs::remove_file(directory.join(name)).await?;
+    }
+
+    Ok(expired)
+}
+
+/// periodically backs up the journal events to the configured directory, so an instance can
+/// be restored without any tooling besides the app
+pub async fn back_up_journals(
+    journal_service: JournalService,
+    event_store: PgJournalEventStore,
+    config: BackupConfig,
+) {
+    let Some(directory) = config.directory else {
+        return;
+    };
+
+    let mut shutdown = Box::pin(shutdown());
+
+    loop {
+        tokio::select! {
+            _ = tokio::time::sleep(config.interval) => {},
+            _ = &mut shutdown => return,
+        }
+
+        match write_backup(&event_store, &directory, journal_service.clock().get_time()).await {
+            Ok((path, events)) => {
+                tracing::info!(path = %path.display(), events, "backed up the journal events");
+            }
+            Err(error) => {
+                tracing::error!(?error, "failed to back up the journal events");
+                continue;
+            }
+        }
+
+        match rotate(&directory, config.keep).await {
+            Ok(deleted) => {
+                for name in deleted {
+                    tracing::info!(name, "deleted an old backup");
+                }
+            }
+            Err(error) => tracing::error!(?error, "failed to delete the old backups"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{TimeZone, Utc};
+
+    #[test]
+    fn only_the_newest_backups_are_kept() {
+        let week = |day| {
+            backup_name(
+                Utc.with_ymd_and_hms(2026, 10, day, 3, 0, 0)
+                    .single()
+                    .expect("invalid time"),
+            )
+        };
+
+        let names = vec![
+            week(17),
+            week(3),
+            "notes.txt".to_string(),
+            week(10),
+            // an interrupted backup isn't one of the backups being kept
+            week(24).replace(EXTENSION, ".partial"),
+        ];
+
+        assert_eq!(expired(names.clone(), 2), vec![week(3)]);
+        assert_eq!(expired(names, 5), Vec::<String>::new());
+    }
+}
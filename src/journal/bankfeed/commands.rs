@@ -0,0 +1,247 @@
+use crate::StateType;
+use crate::journal::JournalError;
+use crate::journal::access::{AppendTransaction, JournalAccess, Owner};
+use crate::journal::account::AccountId;
+use crate::journal::bankfeed::BankFeedId;
+use crate::journal::bankfeed::provider::FeedCredentials;
+use crate::journal::bankfeed::sync;
+use crate::journal::service::BankFeedLine;
+use crate::journal::transaction::bulk;
+use crate::journal::transaction::views::format_amount;
+use crate::monkesto_error::{ErrorRedirect, MonkestoResult, OrRedirect};
+use crate::time_provider::TimeProvider;
+use axum::extract::Path;
+use axum::extract::State;
+use axum::response::Redirect;
+use axum_extra::extract::Form;
+use maud::Markup;
+use serde::Deserialize;
+use std::str::FromStr;
+
+#[derive(Deserialize)]
+pub struct LinkBankFeedForm {
+    provider: String,
+    external_account: String,
+    account_id: String,
+    credential_id: String,
+    credential_secret: String,
+}
+
+pub async fn link_bank_feed(
+    State(state): State<StateType>,
+    access: JournalAccess<Owner>,
+    Form(form): Form<LinkBankFeedForm>,
+) -> Result<Redirect, ErrorRedirect> {
+    let journal_id = access.journal.id;
+    let callback_url = &format!("/journal/{}/bankfeed", journal_id);
+
+    let account_id = AccountId::from_str(&form.account_id).or_redirect(callback_url)?;
+
+    let provider = state
+        .bank_feeds
+        .provider(&form.provider)
+        .ok_or_else(|| JournalError::UnknownBankFeedProvider(form.provider.clone()))
+        .or_redirect(callback_url)?;
+
+    let credentials = FeedCredentials {
+        id: form.credential_id.trim().to_string(),
+        secret: form.credential_secret.trim().to_string(),
+    };
+
+    let event_id = state
+        .journal_service
+        .link_bank_feed(
+            state.ids.generate(),
+            journal_id,
+            provider.name().to_string(),
+            form.external_account.trim().to_string(),
+            account_id,
+            credentials,
+            access.authority(),
+            state.clock.get_time(),
+        )
+        .await
+        .or_redirect(callback_url)?;
+
+    state.journal_service.wait_for(event_id).await;
+
+    Ok(Redirect::to(callback_url))
+}
+
+pub async fn unlink_bank_feed(
+    State(state): State<StateType>,
+    access: JournalAccess<Owner>,
+    Path((id, bank_feed_id)): Path<(String, String)>,
+) -> Result<Redirect, ErrorRedirect> {
+    let callback_url = &format!("/journal/{}/bankfeed", id);
+
+    let journal_id = access.journal.id;
+    let bank_feed_id = BankFeedId::from_str(&bank_feed_id).or_redirect(callback_url)?;
+
+    let event_id = state
+        .journal_service
+        .unlink_bank_feed(
+            bank_feed_id,
+            journal_id,
+            access.authority(),
+            state.clock.get_time(),
+        )
+        .await
+        .or_redirect(callback_url)?;
+
+    state.journal_service.wait_for(event_id).await;
+
+    Ok(Redirect::to(callback_url))
+}
+
+/// pulls the feed now instead of waiting for its next scheduled pull
+pub async fn sync_bank_feed(
+    State(state): State<StateType>,
+    access: JournalAccess<AppendTransaction>,
+    Path((id, bank_feed_id)): Path<(String, String)>,
+) -> Result<Redirect, ErrorRedirect> {
+    let callback_url = &format!("/journal/{}/bankfeed/{}", id, bank_feed_id);
+
+    let journal_id = access.journal.id;
+    let bank_feed_id = BankFeedId::from_str(&bank_feed_id).or_redirect(callback_url)?;
+
+    let authority = access.authority();
+
+    let feed = state
+        .journal_service
+        .bank_feed_sync(bank_feed_id, journal_id, &authority)
+        .await
+        .or_redirect(callback_url)?;
+
+    sync::sync(
+        &state.journal_service,
+        &state.bank_feeds,
+        &feed,
+        state.clock.get_time().date_naive(),
+    )
+    .await
+    .or_redirect(callback_url)?;
+
+    Ok(Redirect::to(callback_url))
+}
+
+/// writes each pulled line as a transaction between the linked account and a blank account,
+/// with the line's description in the sixth column for the journal's rules to match
+pub(crate) fn review_csv(lines: &[BankFeedLine], account_name: &str) -> String {
+    let mut writer = csv::WriterBuilder::new()
+        .flexible(true)
+        .from_writer(Vec::new());
+
+    for (index, line) in lines.iter().enumerate() {
+        let reference = format!("FEED-{}", index + 1);
+        let amount = format_amount(line.amount.unsigned_abs());
+        // money into the bank account is a debit to it
+        let (bank, other) = if line.amount > 0 {
+            ("Dr", "Cr")
+        } else {
+            ("Cr", "Dr")
+        };
+
+        writer
+            .write_record([
+                reference.as_str(),
+                &line.booked.to_string(),
+                account_name,
+                &amount,
+                bank,
+            ])
+            .expect("writing csv to memory can't fail");
+        writer
+            .write_record([
+                reference.as_str(),
+                "",
+                "",
+                &amount,
+                other,
+                &line.description,
+            ])
+            .expect("writing csv to memory can't fail");
+    }
+
+    String::from_utf8(
+        writer
+            .into_inner()
+            .expect("writing csv to memory can't fail"),
+    )
+    .expect("the csv was written from strings")
+}
+
+/// sends the feed's pending lines to bulk entry, where the journal's rules choose their
+/// accounts and they're checked before any transaction is created
+pub async fn review_bank_feed(
+    State(state): State<StateType>,
+    access: JournalAccess<AppendTransaction>,
+    Path((id, bank_feed_id)): Path<(String, String)>,
+) -> Result<Markup, ErrorRedirect> {
+    let callback_url = &format!("/journal/{}/bankfeed/{}", id, bank_feed_id);
+
+    let journal_id = access.journal.id;
+    let bank_feed_id = BankFeedId::from_str(&bank_feed_id).or_redirect(callback_url)?;
+
+    let authority = access.authority();
+
+    let result: MonkestoResult<Markup> = async {
+        let feed = state
+            .journal_service
+            .get_bank_feed(bank_feed_id, journal_id, &authority)
+            .await?;
+
+        let account_name = state
+            .journal_service
+            .list_journal_accounts(journal_id, &authority)
+            .await?
+            .into_iter()
+            .find(|(account, _, _)| account.id == feed.account_id)
+            .map(|(account, _, _)| account.name.to_string())
+            .ok_or(JournalError::InvalidAccount(feed.account_id))?;
+
+        let lines = state
+            .journal_service
+            .take_bank_feed_lines(bank_feed_id, journal_id, &authority)
+            .await?;
+
+        let csv = review_csv(&lines, &account_name);
+
+        bulk::review_page(&state, journal_id, &csv, &authority).await
+    }
+    .await;
+
+    result.or_redirect(callback_url)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::NaiveDate;
+
+    #[test]
+    fn lines_balance_against_the_linked_account() {
+        let line = |external_id: &str, amount, description: &str| BankFeedLine {
+            external_id: external_id.to_string(),
+            booked: NaiveDate::from_ymd_opt(2026, 10, 1).expect("invalid date"),
+            amount,
+            description: description.to_string(),
+        };
+
+        let csv = review_csv(
+            &[
+                line("tx-1", -4520, "Corner Shop, Card payment"),
+                line("tx-2", 150000, "Employer"),
+            ],
+            "Checking",
+        );
+
+        assert_eq!(
+            csv,
+            "FEED-1,2026-10-01,Checking,$45.20,Cr\n\
+             FEED-1,,,$45.20,Dr,\"Corner Shop, Card payment\"\n\
+             FEED-2,2026-10-01,Checking,$1500.00,Dr\n\
+             FEED-2,,,$1500.00,Cr,Employer\n"
+        );
+    }
+}
@@ -0,0 +1,223 @@
+pub mod commands;
+pub mod provider;
+pub mod sync;
+pub mod views;
+
+use axum::Router;
+use axum::routing::{get, post};
+use axum_login::login_required;
+
+pub fn router() -> Router<crate::StateType> {
+    Router::new()
+        .route("/journal/{id}/bankfeed", get(views::bank_feed_list_page))
+        .route("/journal/{id}/bankfeed", post(commands::link_bank_feed))
+        .route(
+            "/journal/{id}/bankfeed/{bank_feed_id}",
+            get(views::bank_feed_page),
+        )
+        .route(
+            "/journal/{id}/bankfeed/{bank_feed_id}/sync",
+            post(commands::sync_bank_feed),
+        )
+        .route(
+            "/journal/{id}/bankfeed/{bank_feed_id}/review",
+            post(commands::review_bank_feed),
+        )
+        .route(
+            "/journal/{id}/bankfeed/{bank_feed_id}/unlink",
+            post(commands::unlink_bank_feed),
+        )
+        .route_layer(login_required!(crate::BackendType, login_url = "/signin"))
+}
+
+use crate::authority::Authority;
+use crate::id;
+use crate::id::Ident;
+use crate::journal::account::AccountId;
+use crate::journal::bankfeed::provider::FeedCredentials;
+use crate::journal::domain::{BankFeedEvent, JournalDomainEvent};
+use crate::journal::member::JournalMember;
+use crate::journal::transaction::AllJournalAccounts;
+use crate::journal::{Journal, Permissions, check_permissions};
+use crate::journal::{JournalError, JournalId};
+use crate::secret::Secret;
+use crate::status::Status;
+use crate::time_provider::Timestamp;
+use disintegrate::{Decision, StateMutate, StateQuery};
+use serde::Deserialize;
+use serde::Serialize;
+
+id!(BankFeedId, Ident::new16());
+
+/// where a feed's provider credentials are kept
+pub fn feed_credentials(bank_feed_id: BankFeedId) -> Secret<FeedCredentials> {
+    Secret::new(format!("bank_feed/{bank_feed_id}/credentials"))
+}
+
+#[derive(StateQuery, Clone, Default, Serialize, Deserialize)]
+#[state_query(BankFeedEvent)]
+pub struct BankFeed {
+    #[id]
+    bank_feed_id: BankFeedId,
+    journal_id: JournalId,
+    status: Status,
+}
+
+impl BankFeed {
+    fn new(bank_feed_id: BankFeedId) -> Self {
+        Self {
+            bank_feed_id,
+            ..Default::default()
+        }
+    }
+}
+
+impl StateMutate for BankFeed {
+    fn mutate(&mut self, event: Self::Event) {
+        match event {
+            BankFeedEvent::BankFeedLinked { journal_id, .. } => {
+                self.journal_id = journal_id;
+                self.status = Status::Valid;
+            }
+            BankFeedEvent::BankFeedUnlinked { .. } => self.status = Status::Deleted,
+        }
+    }
+}
+
+pub struct LinkBankFeed {
+    bank_feed_id: BankFeedId,
+    journal_id: JournalId,
+    provider: String,
+    external_account: String,
+    account_id: AccountId,
+    authority: Authority,
+    timestamp: Timestamp,
+}
+
+impl LinkBankFeed {
+    pub fn new(
+        bank_feed_id: BankFeedId,
+        journal_id: JournalId,
+        provider: String,
+        external_account: String,
+        account_id: AccountId,
+        authority: Authority,
+        timestamp: Timestamp,
+    ) -> Self {
+        Self {
+            bank_feed_id,
+            journal_id,
+            provider,
+            external_account,
+            account_id,
+            authority,
+            timestamp,
+        }
+    }
+}
+
+impl Decision for LinkBankFeed {
+    type Event = JournalDomainEvent;
+    type StateQuery = (BankFeed, AllJournalAccounts, Journal, JournalMember);
+    type Error = JournalError;
+
+    fn state_query(&self) -> Self::StateQuery {
+        (
+            BankFeed::new(self.bank_feed_id),
+            AllJournalAccounts::new(self.journal_id),
+            Journal::new(self.journal_id),
+            JournalMember::new(
+                self.journal_id,
+                self.authority.user_id().unwrap_or_default(),
+            ),
+        )
+    }
+
+    fn process(
+        &self,
+        (bank_feed, accounts, journal, actor): &Self::StateQuery,
+    ) -> Result<Vec<Self::Event>, Self::Error> {
+        if bank_feed.status.found() {
+            return Err(JournalError::BankFeedIdCollision(self.bank_feed_id));
+        }
+
+        if !journal.status.valid() {
+            return Err(JournalError::InvalidJournal(self.journal_id));
+        }
+
+        // a feed holds credentials to the journal owner's bank, so only owners may link one
+        check_permissions(actor, &self.authority, journal, Permissions::OWNER)?;
+
+        if !accounts.contains(&self.account_id) {
+            return Err(JournalError::InvalidAccount(self.account_id));
+        }
+
+        Ok(vec![JournalDomainEvent::BankFeedLinked {
+            bank_feed_id: self.bank_feed_id,
+            journal_id: self.journal_id,
+            provider: self.provider.clone(),
+            external_account: self.external_account.clone(),
+            account_id: self.account_id,
+            authority: self.authority.clone(),
+            timestamp: self.timestamp,
+        }])
+    }
+}
+
+pub struct UnlinkBankFeed {
+    bank_feed_id: BankFeedId,
+    journal_id: JournalId,
+    authority: Authority,
+    timestamp: Timestamp,
+}
+
+impl UnlinkBankFeed {
+    pub fn new(
+        bank_feed_id: BankFeedId,
+        journal_id: JournalId,
+        authority: Authority,
+        timestamp: Timestamp,
+    ) -> Self {
+        Self {
+            bank_feed_id,
+            journal_id,
+            authority,
+            timestamp,
+        }
+    }
+}
+
+impl Decision for UnlinkBankFeed {
+    type Event = JournalDomainEvent;
+    type StateQuery = (BankFeed, Journal, JournalMember);
+    type Error = JournalError;
+
+    fn state_query(&self) -> Self::StateQuery {
+        (
+            BankFeed::new(self.bank_feed_id),
+            Journal::new(self.journal_id),
+            JournalMember::new(
+                self.journal_id,
+                self.authority.user_id().unwrap_or_default(),
+            ),
+        )
+    }
+
+    fn process(
+        &self,
+        (bank_feed, journal, actor): &Self::StateQuery,
+    ) -> Result<Vec<Self::Event>, Self::Error> {
+        if !bank_feed.status.valid() || bank_feed.journal_id != self.journal_id {
+            return Err(JournalError::InvalidBankFeed(self.bank_feed_id));
+        }
+
+        check_permissions(actor, &self.authority, journal, Permissions::OWNER)?;
+
+        Ok(vec![JournalDomainEvent::BankFeedUnlinked {
+            bank_feed_id: self.bank_feed_id,
+            journal_id: self.journal_id,
+            authority: self.authority.clone(),
+            timestamp: self.timestamp,
+        }])
+    }
+}
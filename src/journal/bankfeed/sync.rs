@@ -0,0 +1,108 @@
+use crate::journal::JournalError;
+use crate::journal::JournalService;
+use crate::journal::bankfeed::feed_credentials;
+use crate::journal::bankfeed::provider::BankFeeds;
+use crate::journal::service::BankFeedSync;
+use crate::shutdown;
+use crate::time_provider::TimeProvider;
+use axum_login::tracing;
+use chrono::{Days, NaiveDate};
+use std::time::Duration;
+
+/// how long a feed goes between pulls
+const SYNC_INTERVAL: chrono::Duration = chrono::Duration::hours(6);
+const POLL_INTERVAL: Duration = Duration::from_secs(15 * 60);
+
+/// how far back the first pull of a feed reaches
+const FIRST_SYNC_DAYS: u64 = 90;
+
+/// banks can book a transaction a few days after its date, so each pull overlaps the last
+const SYNC_OVERLAP_DAYS: u64 = 7;
+
+/// where a feed's next pull should start
+fn sync_from(last_booked: Option<NaiveDate>, today: NaiveDate) -> NaiveDate {
+    match last_booked {
+        Some(last_booked) => last_booked - Days::new(SYNC_OVERLAP_DAYS),
+        None => today - Days::new(FIRST_SYNC_DAYS),
+    }
+}
+
+/// pulls a feed's new transactions into its review queue and returns how many there were
+pub async fn sync(
+    journal_service: &JournalService,
+    bank_feeds: &BankFeeds,
+    feed: &BankFeedSync,
+    today: NaiveDate,
+) -> Result<u64, JournalError> {
+    let provider = bank_feeds
+        .provider(&feed.provider)
+        .ok_or_else(|| JournalError::UnknownBankFeedProvider(feed.provider.clone()))?;
+
+    let credentials = journal_service
+        .secrets()
+        .get(&feed_credentials(feed.id))
+        .await?
+        .ok_or_else(|| JournalError::BankFeedSync("the feed's credentials are missing".into()))?;
+
+    let lines = provider
+        .transactions(
+            &credentials,
+            &feed.external_account,
+            sync_from(feed.last_booked, today),
+        )
+        .await
+        .map_err(|e| JournalError::BankFeedSync(e.to_string()))?;
+
+    Ok(journal_service
+        .record_bank_feed_lines(feed.id, &lines)
+        .await?)
+}
+
+/// pulls every feed that's due until shutdown. a failed pull is retried on the next poll
+pub async fn sync_bank_feeds(journal_service: JournalService, bank_feeds: BankFeeds) {
+    let mut shutdown = Box::pin(shutdown());
+
+    loop {
+        let now = journal_service.clock().get_time();
+
+        match journal_service.due_bank_feeds(now - SYNC_INTERVAL).await {
+            Ok(feeds) => {
+                for feed in feeds {
+                    match sync(&journal_service, &bank_feeds, &feed, now.date_naive()).await {
+                        Ok(recorded) => {
+                            tracing::info!(bank_feed_id = %feed.id, journal_id = %feed.journal_id, recorded, "pulled a bank feed")
+                        }
+                        Err(error) => {
+                            tracing::warn!(bank_feed_id = %feed.id, journal_id = %feed.journal_id, %error, "failed to pull a bank feed")
+                        }
+                    }
+                }
+            }
+            Err(error) => tracing::error!(?error, "failed to fetch the bank feeds that are due"),
+        }
+
+        tokio::select! {
+            _ = tokio::time::sleep(POLL_INTERVAL) => {},
+            _ = &mut shutdown => return,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn pulls_overlap_the_last_booked_transaction() {
+        let today = NaiveDate::from_ymd_opt(2026, 10, 17).expect("invalid date");
+
+        assert_eq!(
+            sync_from(None, today),
+            NaiveDate::from_ymd_opt(2026, 7, 19).expect("invalid date")
+        );
+        assert_eq!(
+            sync_from(NaiveDate::from_ymd_opt(2026, 10, 3), today),
+            NaiveDate::from_ymd_opt(2026, 9, 26).expect("invalid date")
+        );
+    }
+}
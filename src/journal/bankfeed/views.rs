@@ -0,0 +1,289 @@
+use crate::BackendType;
+use crate::StateType;
+use crate::authn::get_user;
+use crate::journal::access::JournalAccess;
+use crate::journal::bankfeed::BankFeedId;
+use crate::journal::layout::journal_layout;
+use crate::journal::transaction::views::format_amount;
+use crate::monkesto_error::MonkestoError;
+use crate::monkesto_error::UrlError;
+use axum::extract::Path;
+use axum::extract::Query;
+use axum::extract::State;
+use axum::response::Redirect;
+use axum_login::AuthSession;
+use maud::Markup;
+use maud::html;
+use std::collections::HashMap;
+use std::str::FromStr;
+
+const INPUT_CLASS: &str = "block w-full rounded-md bg-white px-3 py-1.5 text-base text-gray-900 outline-1 -outline-offset-1 outline-gray-300 placeholder:text-gray-400 focus:outline-2 focus:-outline-offset-2 focus:outline-indigo-600 sm:text-sm/6 dark:bg-white/5 dark:text-white dark:outline-white/10 dark:placeholder:text-gray-500 dark:focus:outline-indigo-500";
+
+pub async fn bank_feed_list_page(
+    State(state): State<StateType>,
+    session: AuthSession<BackendType>,
+    access: JournalAccess,
+    Query(err): Query<UrlError>,
+) -> Result<Markup, Redirect> {
+    let user = get_user(session)?;
+    let authority = access.authority();
+    let journal_id = access.journal.id;
+    let id = journal_id.to_string();
+
+    let accounts = state
+        .journal_service
+        .list_journal_accounts(journal_id, &authority)
+        .await
+        .map(|accounts| {
+            accounts
+                .into_iter()
+                .map(|(account, _, _)| (account.id, account.name))
+                .collect::<Vec<_>>()
+        })
+        .unwrap_or_default();
+    let account_names: HashMap<_, _> = accounts.iter().cloned().collect();
+
+    let content = html! {
+        p class="text-sm text-gray-500 dark:text-gray-400" {
+            "A bank feed pulls the booked transactions of a bank account every few hours. "
+            "They wait on the feed until they're sent to bulk entry, where the journal's "
+            a href=(format!("/journal/{}/rule", id)) class="font-medium text-indigo-600 hover:text-indigo-500 dark:text-indigo-400 dark:hover:text-indigo-300" { "import rules" }
+            " choose their accounts."
+        }
+
+        @match state.journal_service.list_bank_feeds(journal_id, &authority).await {
+            Ok(feeds) => {
+                @for feed in feeds {
+                    a
+                    href=(format!("/journal/{}/bankfeed/{}", id, feed.id))
+                    class="block p-4 bg-white dark:bg-gray-800 border border-gray-200 dark:border-gray-700 rounded-xl hover:bg-gray-50 dark:hover:bg-gray-700" {
+                        div class="flex justify-between items-center gap-4" {
+                            div class="min-w-0 space-y-1" {
+                                h3 class="text-base font-semibold text-gray-900 dark:text-white break-all" {
+                                    (account_names.get(&feed.account_id).map(|name| name.as_ref()).unwrap_or("Deleted account"))
+                                }
+                                p class="text-xs text-gray-500 dark:text-gray-400 break-all" {
+                                    (state.bank_feeds.provider(&feed.provider).map(|provider| provider.display_name()).unwrap_or(&feed.provider))
+                                    " account " (feed.external_account) ", "
+                                    @match feed.last_synced_at {
+                                        Some(synced) => { "last pulled " (user.timezone().format(synced)) }
+                                        None => "not pulled yet",
+                                    }
+                                }
+                            }
+                            @if feed.pending > 0 {
+                                span class="shrink-0 px-2 py-0.5 text-xs font-semibold rounded bg-indigo-100 text-indigo-700 dark:bg-indigo-900 dark:text-indigo-300" {
+                                    (feed.pending) " to review"
+                                }
+                            }
+                        }
+                    }
+                }
+            },
+            Err(e) => {
+                p {
+                    "failed to get the bank feeds for " (journal_id) ": " (e)
+                }
+            }
+        }
+
+        hr class="mt-8 mb-6 border-gray-300 dark:border-gray-600";
+
+        div class="mt-10" {
+            form action=(format!("/journal/{}/bankfeed", id)) method="post" class="space-y-4" {
+                h3 class="text-base font-semibold text-gray-900 dark:text-gray-100" { "Link Bank Feed" }
+
+                div {
+                    label
+                    for="provider"
+                    class="block text-sm/6 font-medium text-gray-900 dark:text-gray-100" {
+                        "Provider"
+                    }
+                    div class="mt-2" {
+                        select id="provider" name="provider" required class=(INPUT_CLASS) {
+                            @for provider in state.bank_feeds.providers() {
+                                option value=(provider.name()) { (provider.display_name()) }
+                            }
+                        }
+                    }
+                }
+
+                @for (name, label, kind) in [
+                    ("credential_id", "Secret id", "text"),
+                    ("credential_secret", "Secret key", "password"),
+                    ("external_account", "Provider account id", "text"),
+                ] {
+                    div {
+                        label
+                        for=(name)
+                        class="block text-sm/6 font-medium text-gray-900 dark:text-gray-100" {
+                            (label)
+                        }
+                        div class="mt-2" {
+                            input
+                            id=(name)
+                            type=(kind)
+                            name=(name)
+                            required
+                            autocomplete="off"
+                            class=(INPUT_CLASS)
+                            ;
+                        }
+                    }
+                }
+
+                div {
+                    label
+                    for="account_id"
+                    class="block text-sm/6 font-medium text-gray-900 dark:text-gray-100" {
+                        "Bank account in this journal"
+                    }
+                    div class="mt-2" {
+                        select id="account_id" name="account_id" required class=(INPUT_CLASS) {
+                            option value="" { "Select account..." }
+                            @for (account_id, name) in &accounts {
+                                option value=(account_id) { (name) }
+                            }
+                        }
+                    }
+                }
+
+                div {
+                    button
+                    type="submit"
+                    class="flex w-full justify-center rounded-md bg-indigo-600 px-3 py-1.5 text-sm/6 font-semibold text-white shadow-xs hover:bg-indigo-500 focus-visible:outline-2 focus-visible:outline-offset-2 focus-visible:outline-indigo-600 dark:bg-indigo-500 dark:shadow-none dark:hover:bg-indigo-400 dark:focus-visible:outline-indigo-500" {
+                        "Link Feed"
+                    }
+                }
+            }
+        }
+
+        @if let Some(e) = err.err {
+            p {
+                (format!("An error occurred: {:?}", MonkestoError::decode(&e)))
+            }
+        }
+    };
+
+    let wrapped_content = html! {
+        div class="flex flex-col gap-6 mx-auto w-full max-w-4xl" {
+            (content)
+        }
+    };
+
+    Ok(journal_layout(&access.journal, wrapped_content))
+}
+
+pub async fn bank_feed_page(
+    State(state): State<StateType>,
+    session: AuthSession<BackendType>,
+    access: JournalAccess,
+    Path((id, bank_feed_id)): Path<(String, String)>,
+    Query(err): Query<UrlError>,
+) -> Result<Markup, Redirect> {
+    let user = get_user(session)?;
+    let authority = access.authority();
+    let journal_id = access.journal.id;
+
+    let Ok(bank_feed_id) = BankFeedId::from_str(&bank_feed_id) else {
+        return Err(Redirect::to(&format!("/journal/{}/bankfeed", id)));
+    };
+
+    let feed = state
+        .journal_service
+        .get_bank_feed(bank_feed_id, journal_id, &authority)
+        .await;
+    let lines = state
+        .journal_service
+        .bank_feed_lines(bank_feed_id, journal_id, &authority)
+        .await;
+
+    let content = html! {
+        @match (feed, lines) {
+            (Ok(feed), Ok(lines)) => {
+                div class="flex flex-wrap justify-between items-center gap-4" {
+                    p class="text-sm text-gray-500 dark:text-gray-400 break-all" {
+                        (state.bank_feeds.provider(&feed.provider).map(|provider| provider.display_name()).unwrap_or(&feed.provider))
+                        " account " (feed.external_account) ", "
+                        @match feed.last_synced_at {
+                            Some(synced) => { "last pulled " (user.timezone().format(synced)) }
+                            None => "not pulled yet",
+                        }
+                    }
+                    div class="flex gap-2" {
+                        form method="post" action=(format!("/journal/{}/bankfeed/{}/sync", id, feed.id)) {
+                            button
+                            type="submit"
+                            class="rounded-md bg-white px-3 py-1.5 text-sm font-semibold text-gray-900 shadow-xs ring-1 ring-gray-300 ring-inset hover:bg-gray-50 dark:bg-white/10 dark:text-white dark:ring-white/10 dark:hover:bg-white/20" {
+                                "Pull now"
+                            }
+                        }
+                        form method="post" action=(format!("/journal/{}/bankfeed/{}/unlink", id, feed.id)) {
+                            button
+                            type="submit"
+                            class="rounded-md px-3 py-1.5 text-sm font-semibold text-red-600 hover:bg-red-50 dark:text-red-400 dark:hover:bg-red-950" {
+                                "Unlink"
+                            }
+                        }
+                    }
+                }
+
+                @if lines.is_empty() {
+                    p class="text-sm text-gray-500 dark:text-gray-400" { "Nothing is waiting to be reviewed." }
+                } @else {
+                    div class="overflow-x-auto" {
+                        table class="min-w-full text-sm" {
+                            thead {
+                                tr class="text-left text-gray-500 dark:text-gray-400" {
+                                    th class="py-2 pr-4 font-medium" { "Booked" }
+                                    th class="py-2 pr-4 font-medium" { "Description" }
+                                    th class="py-2 text-right font-medium" { "Amount" }
+                                }
+                            }
+                            tbody class="divide-y divide-gray-200 dark:divide-gray-700" {
+                                @for line in &lines {
+                                    tr class="text-gray-900 dark:text-gray-100" {
+                                        td class="py-2 pr-4 whitespace-nowrap" { (line.booked) }
+                                        td class="py-2 pr-4" { (line.description) }
+                                        td class="py-2 text-right whitespace-nowrap" {
+                                            @if line.amount < 0 { "-" }
+                                            (format_amount(line.amount.unsigned_abs()))
+                                        }
+                                    }
+                                }
+                            }
+                        }
+                    }
+
+                    form method="post" action=(format!("/journal/{}/bankfeed/{}/review", id, feed.id)) {
+                        p class="mb-4 text-xs text-gray-500 dark:text-gray-400" {
+                            "The lines leave this list once they're sent to bulk entry."
+                        }
+                        button
+                        type="submit"
+                        class="flex w-full justify-center rounded-md bg-indigo-600 px-3 py-1.5 text-sm/6 font-semibold text-white shadow-xs hover:bg-indigo-500 focus-visible:outline-2 focus-visible:outline-offset-2 focus-visible:outline-indigo-600 dark:bg-indigo-500 dark:shadow-none dark:hover:bg-indigo-400 dark:focus-visible:outline-indigo-500" {
+                            "Review in Bulk Entry"
+                        }
+                    }
+                }
+            }
+            (Err(e), _) | (_, Err(e)) => {
+                p { "failed to get the bank feed: " (e) }
+            }
+        }
+
+        @if let Some(e) = err.err {
+            p {
+                (format!("An error occurred: {:?}", MonkestoError::decode(&e)))
+            }
+        }
+    };
+
+    let wrapped_content = html! {
+        div class="flex flex-col gap-6 mx-auto w-full max-w-4xl" {
+            (content)
+        }
+    };
+
+    Ok(journal_layout(&access.journal, wrapped_content))
+}
@@ -0,0 +1,146 @@
+use crate::authn::user::UserId;
+use crate::authority::Authority;
+use crate::journal::service::JournalState;
+use crate::journal::{JournalId, Permissions};
+use crate::time_provider::Timestamp;
+use lru::LruCache;
+use std::collections::HashMap;
+use std::num::NonZeroUsize;
+use std::sync::{Arc, Mutex, MutexGuard};
+
+/// how many journals are kept in memory
+pub const DEFAULT_CAPACITY: NonZeroUsize = NonZeroUsize::new(1024).expect("the capacity is zero");
+
+pub type CachedJournalState = (JournalState, Authority, Timestamp);
+
+#[derive(Default)]
+struct CachedJournal {
+    journal: Option<CachedJournalState>,
+    permissions: HashMap<UserId, Permissions>,
+}
+
+struct Entries {
+    journals: LruCache<JournalId, CachedJournal>,
+    /// bumped on every invalidation, so reads that raced with one can be discarded
+    generation: u64,
+}
+
+/// the journal lookups that nearly every page makes, kept in memory in front of the projection.
+/// the projection invalidates a journal as it applies each of the journal's events. only the
+/// instance projecting an event sees it, so the others are told by a notification instead
+#[derive(Clone)]
+pub struct JournalCache {
+    entries: Arc<Mutex<Entries>>,
+}
+
+impl JournalCache {
+    pub fn new(capacity: NonZeroUsize) -> Self {
+        Self {
+            entries: Arc::new(Mutex::new(Entries {
+                journals: LruCache::new(capacity),
+                generation: 0,
+            })),
+        }
+    }
+
+    fn lock(&self) -> MutexGuard<'_, Entries> {
+        // the entries are only ever replaced whole, so a panic can't leave them inconsistent
+        self.entries
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    /// taken before reading from the projection and passed back when caching what was read
+    pub fn generation(&self) -> u64 {
+        self.lock().generation
+    }
+
+    pub fn journal(&self, journal_id: JournalId) -> Option<CachedJournalState> {
+        self.lock()
+            .journals
+            .get(&journal_id)
+            .and_then(|cached| cached.journal.clone())
+    }
+
+    pub fn insert_journal(
+        &self,
+        generation: u64,
+        journal_id: JournalId,
+        journal: CachedJournalState,
+    ) {
+        let mut entries = self.lock();
+
+        // the journal may have changed while it was being read
+        if entries.generation != generation {
+            return;
+        }
+
+        entries
+            .journals
+            .get_or_insert_mut(journal_id, CachedJournal::default)
+            .journal = Some(journal);
+    }
+
+    pub fn permissions(&self, journal_id: JournalId, user_id: UserId) -> Option<Permissions> {
+        self.lock()
+            .journals
+            .get(&journal_id)
+            .and_then(|cached| cached.permissions.get(&user_id).copied())
+    }
+
+    pub fn insert_permissions(
+        &self,
+        generation: u64,
+        journal_id: JournalId,
+        user_id: UserId,
+        permissions: Permissions,
+    ) {
+        let mut entries = self.lock();
+
+        if entries.generation != generation {
+            return;
+        }
+
+        entries
+            .journals
+            .get_or_insert_mut(journal_id, CachedJournal::default)
+            .permissions
+            .insert(user_id, permissions);
+    }
+
+    pub fn invalidate(&self, journal_id: JournalId) {
+        let mut entries = self.lock();
+        entries.journals.pop(&journal_id);
+        entries.generation += 1;
+    }
+
+    pub fn clear(&self) {
+        let mut entries = self.lock();
+        entries.journals.clear();
+        entries.generation += 1;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reads_that_race_an_invalidation_are_not_cached() {
+        let cache = JournalCache::new(DEFAULT_CAPACITY);
+        let journal_id = JournalId::new();
+        let user_id = UserId::new();
+
+        let generation = cache.generation();
+        cache.insert_permissions(generation, journal_id, user_id, Permissions::READ);
+        assert_eq!(
+            cache.permissions(journal_id, user_id),
+            Some(Permissions::READ)
+        );
+
+        let stale = cache.generation();
+        cache.invalidate(journal_id);
+        cache.insert_permissions(stale, journal_id, user_id, Permissions::READ);
+        assert_eq!(cache.permissions(journal_id, user_id), None);
+    }
+}
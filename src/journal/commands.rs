@@ -5,10 +5,18 @@ use crate::authn::user::UserId;
 use crate::authority::Actor;
 use crate::authority::Authority;
 use crate::email::Email;
-use crate::journal::{JournalId, Permissions};
-use crate::monkesto_error::OrRedirect;
+use crate::id::IdentError;
+use crate::journal::access::{Invite, JournalAccess, Owner};
+use crate::journal::account::AccountId;
+use crate::journal::appearance::{JournalAppearance, JournalColor, JournalIcon};
+use crate::journal::fiscal::FiscalYearStart;
+use crate::journal::person::people_list;
+use crate::journal::undo::{self, Compensation};
+use crate::journal::{JournalError, JournalId, Permissions};
+use crate::monkesto_error::{ErrorRedirect, FormRejection, OrRedirect};
 use crate::name::Name;
-use crate::time_provider::{DefaultTimeProvider, TimeProvider};
+use crate::time_provider::TimeProvider;
+use crate::validation::FormState;
 use axum::extract::Path;
 use axum::extract::State;
 use axum::response::Redirect;
@@ -20,26 +28,46 @@ use std::str::FromStr;
 #[derive(Deserialize)]
 pub struct CreateJournalForm {
     journal_name: String,
+    #[serde(default)]
+    color: String,
+    #[serde(default)]
+    icon: String,
+}
+
+/// a blank color is the default gray, and a blank icon shows the journal's initial
+fn appearance(color: &str, icon: &str) -> Result<JournalAppearance, JournalError> {
+    let color = match color.trim() {
+        "" => JournalColor::default(),
+        color => JournalColor::from_str(color)?,
+    };
+    let icon = match icon.trim() {
+        "" => None,
+        icon => Some(JournalIcon::try_new(icon)?),
+    };
+
+    Ok(JournalAppearance { color, icon })
 }
 pub async fn create_journal(
     State(state): State<StateType>,
     session: AuthSession<BackendType>,
     Form(form): Form<CreateJournalForm>,
-) -> Result<Redirect, Redirect> {
+) -> Result<Redirect, ErrorRedirect> {
     const CALLBACK_URL: &str = "/journal";
 
     let user = get_user(session)?;
 
     let name = Name::try_new(form.journal_name).or_redirect(CALLBACK_URL)?;
+    let appearance = appearance(&form.color, &form.icon).or_redirect(CALLBACK_URL)?;
 
     let event_id = state
         .journal_service
         .create_journal(
-            JournalId::new(),
+            state.ids.generate(),
             user.id,
             name,
+            appearance,
             Authority::Direct(Actor::User(user.id)),
-            DefaultTimeProvider.get_time(),
+            state.clock.get_time(),
         )
         .await
         .or_redirect(CALLBACK_URL)?;
@@ -55,58 +83,71 @@ pub struct InviteUserForm {
     pub read: Option<String>,
     pub add_account: Option<String>,
     pub append_transaction: Option<String>,
+    pub submit_only: Option<String>,
+    pub approve: Option<String>,
     pub invite: Option<String>,
 }
 
 pub async fn invite_member(
     State(state): State<StateType>,
     session: AuthSession<BackendType>,
-    Path(id): Path<String>,
+    access: JournalAccess<Invite>,
     Form(form): Form<InviteUserForm>,
-) -> Result<Redirect, Redirect> {
+) -> Result<Redirect, FormRejection> {
+    let id = access.journal.id.to_string();
     let callback_url = &format!("/journal/{}/person", id);
 
-    let email = Email::try_new(form.email).or_redirect(callback_url)?;
-
-    let user = get_user(session)?;
-
-    let journal_id = JournalId::from_str(&id).or_redirect(callback_url)?;
+    let mut form_state = FormState::default();
+    form_state.keep("email", form.email.as_str());
 
     let mut invitee_permissions = Permissions::empty();
-    if form.read.is_some() {
-        invitee_permissions.insert(Permissions::READ);
-    }
-    if form.add_account.is_some() {
-        invitee_permissions.insert(Permissions::ADD_ACCOUNT);
-    }
-    if form.append_transaction.is_some() {
-        invitee_permissions.insert(Permissions::APPEND_TRANSACTION);
-    }
-    if form.invite.is_some() {
-        invitee_permissions.insert(Permissions::INVITE);
+    for (field, value, permission) in [
+        ("read", &form.read, Permissions::READ),
+        ("add_account", &form.add_account, Permissions::ADD_ACCOUNT),
+        (
+            "append_transaction",
+            &form.append_transaction,
+            Permissions::APPEND_TRANSACTION,
+        ),
+        ("submit_only", &form.submit_only, Permissions::SUBMIT_ONLY),
+        ("approve", &form.approve, Permissions::APPROVE),
+        ("invite", &form.invite, Permissions::INVITE),
+    ] {
+        if let Some(value) = value {
+            form_state.keep(field, value.as_str());
+            invitee_permissions.insert(permission);
+        }
     }
 
-    let invitee_id = state
-        .authn_service
-        .lookup_user_id(&email)
-        .await
-        .or_redirect(callback_url)?;
+    // an unknown email is a problem with the field, while anything else is about the invite
+    let invitee_id = match form_state.check("email", Email::try_new(form.email)) {
+        Some(email) => form_state.check("email", state.authn_service.lookup_user_id(&email).await),
+        None => None,
+    };
 
-    let event_id = state
-        .journal_service
-        .add_member(
-            journal_id,
-            invitee_id,
-            invitee_permissions,
-            Authority::Direct(Actor::User(user.id)),
-            DefaultTimeProvider.get_time(),
-        )
-        .await
-        .or_redirect(callback_url)?;
+    if let Some(invitee_id) = invitee_id {
+        match state
+            .journal_service
+            .add_member(
+                access.journal.id,
+                invitee_id,
+                invitee_permissions,
+                access.authority(),
+                state.clock.get_time(),
+            )
+            .await
+        {
+            Ok(event_id) => {
+                state.journal_service.wait_for(event_id).await;
+                return Ok(Redirect::to(callback_url));
+            }
+            Err(e) => form_state.fail(e),
+        }
+    }
 
-    state.journal_service.wait_for(event_id).await;
+    let page = people_list(&state, &session.session, &access, None, &form_state).await;
 
-    Ok(Redirect::to(callback_url))
+    Err(form_state.rejected(page).into())
 }
 
 #[derive(Deserialize)]
@@ -114,19 +155,19 @@ pub struct UpdatePermissionsForm {
     pub read: Option<String>,
     pub add_account: Option<String>,
     pub append_transaction: Option<String>,
+    pub submit_only: Option<String>,
+    pub approve: Option<String>,
     pub invite: Option<String>,
 }
 
 pub async fn update_permissions(
     State(state): State<StateType>,
-    session: AuthSession<BackendType>,
+    access: JournalAccess<Owner>,
     Path((id, person_id)): Path<(String, String)>,
     Form(form): Form<UpdatePermissionsForm>,
-) -> Result<Redirect, Redirect> {
+) -> Result<Redirect, ErrorRedirect> {
     let callback_url = &format!("/journal/{}/person/{}", id, person_id);
 
-    let user = get_user(session)?;
-    let journal_id = JournalId::from_str(&id).or_redirect(callback_url)?;
     let target_user_id = UserId::from_str(&person_id).or_redirect(callback_url)?;
 
     let mut new_permissions = Permissions::empty();
@@ -139,6 +180,12 @@ pub async fn update_permissions(
     if form.append_transaction.is_some() {
         new_permissions.insert(Permissions::APPEND_TRANSACTION);
     }
+    if form.submit_only.is_some() {
+        new_permissions.insert(Permissions::SUBMIT_ONLY);
+    }
+    if form.approve.is_some() {
+        new_permissions.insert(Permissions::APPROVE);
+    }
     if form.invite.is_some() {
         new_permissions.insert(Permissions::INVITE);
     }
@@ -146,11 +193,11 @@ pub async fn update_permissions(
     let event_id = state
         .journal_service
         .update_member(
-            journal_id,
+            access.journal.id,
             target_user_id,
             new_permissions,
-            Authority::Direct(Actor::User(user.id)),
-            DefaultTimeProvider.get_time(),
+            access.authority(),
+            state.clock.get_time(),
         )
         .await
         .or_redirect(callback_url)?;
@@ -163,22 +210,174 @@ pub async fn update_permissions(
 pub async fn remove_member(
     State(state): State<StateType>,
     session: AuthSession<BackendType>,
+    access: JournalAccess<Owner>,
     Path((id, person_id)): Path<(String, String)>,
-) -> Result<Redirect, Redirect> {
+) -> Result<Redirect, ErrorRedirect> {
     let callback_url = &format!("/journal/{}/person", id);
     let person_detail_url = &format!("/journal/{}/person/{}", id, person_id);
 
-    let user = get_user(session)?;
-    let journal_id = JournalId::from_str(&id).or_redirect(person_detail_url)?;
+    let browser_session = session.session.clone();
+    let journal_id = access.journal.id;
     let target_user_id = UserId::from_str(&person_id).or_redirect(person_detail_url)?;
 
+    // the member's permissions are needed to restore them if the removal is undone
+    let permissions = state
+        .journal_service
+        .get_effective_permissions(journal_id, &Authority::Direct(Actor::User(target_user_id)))
+        .await
+        .or_redirect(person_detail_url)?;
+
     let event_id = state
         .journal_service
         .remove_member(
             journal_id,
             target_user_id,
+            access.authority(),
+            state.clock.get_time(),
+        )
+        .await
+        .or_redirect(callback_url)?;
+
+    state.journal_service.wait_for(event_id).await;
+
+    let email = state
+        .authn_service
+        .fetch_user(target_user_id)
+        .await
+        .map(|user| user.email.to_string())
+        .unwrap_or_else(|_| target_user_id.to_string());
+
+    undo::record(
+        &browser_session,
+        event_id,
+        journal_id,
+        format!("Removed {email} from the journal"),
+        Compensation::RestoreMember {
+            user_id: target_user_id,
+            permissions,
+        },
+        state.clock.get_time(),
+    )
+    .await;
+
+    Ok(Redirect::to(callback_url))
+}
+
+pub async fn leave_journal(
+    State(state): State<StateType>,
+    session: AuthSession<BackendType>,
+    Path(id): Path<String>,
+) -> Result<Redirect, ErrorRedirect> {
+    const CALLBACK_URL: &str = "/journal";
+    let journal_url = &format!("/journal/{}", id);
+
+    let user = get_user(session)?;
+    let journal_id = JournalId::from_str(&id).or_redirect(journal_url)?;
+
+    let event_id = state
+        .journal_service
+        .leave_journal(
+            journal_id,
             Authority::Direct(Actor::User(user.id)),
-            DefaultTimeProvider.get_time(),
+            state.clock.get_time(),
+        )
+        .await
+        .or_redirect(journal_url)?;
+
+    state.journal_service.wait_for(event_id).await;
+
+    Ok(Redirect::to(CALLBACK_URL))
+}
+
+pub async fn delete_journal(
+    State(state): State<StateType>,
+    access: JournalAccess<Owner>,
+) -> Result<Redirect, ErrorRedirect> {
+    const CALLBACK_URL: &str = "/journal";
+    let journal_url = &format!("/journal/{}", access.journal.id);
+
+    let event_id = state
+        .journal_service
+        .delete_journal(
+            access.journal.id,
+            access.authority(),
+            state.clock.get_time(),
+        )
+        .await
+        .or_redirect(journal_url)?;
+
+    state.journal_service.wait_for(event_id).await;
+
+    Ok(Redirect::to(CALLBACK_URL))
+}
+
+#[derive(Deserialize)]
+pub struct JournalSettingsForm {
+    fiscal_year_start: String,
+    #[serde(default)]
+    default_cash_account: String,
+    #[serde(default)]
+    default_expense_account: String,
+    #[serde(default)]
+    color: String,
+    #[serde(default)]
+    icon: String,
+}
+
+/// a blank choice clears the default
+fn default_account(value: &str) -> Result<Option<AccountId>, IdentError> {
+    match value.trim() {
+        "" => Ok(None),
+        value => Ok(Some(AccountId::from_str(value)?)),
+    }
+}
+
+pub async fn update_settings(
+    State(state): State<StateType>,
+    access: JournalAccess<Owner>,
+    Form(form): Form<JournalSettingsForm>,
+) -> Result<Redirect, ErrorRedirect> {
+    let callback_url = &format!("/journal/{}", access.journal.id);
+
+    let fiscal_year_start =
+        FiscalYearStart::from_str(&form.fiscal_year_start).or_redirect(callback_url)?;
+    let default_cash_account =
+        default_account(&form.default_cash_account).or_redirect(callback_url)?;
+    let default_expense_account =
+        default_account(&form.default_expense_account).or_redirect(callback_url)?;
+    let appearance = appearance(&form.color, &form.icon).or_redirect(callback_url)?;
+
+    let event_id = state
+        .journal_service
+        .update_journal_settings(
+            access.journal.id,
+            fiscal_year_start,
+            default_cash_account,
+            default_expense_account,
+            appearance,
+            access.authority(),
+            state.clock.get_time(),
+        )
+        .await
+        .or_redirect(callback_url)?;
+
+    state.journal_service.wait_for(event_id).await;
+
+    Ok(Redirect::to(callback_url))
+}
+
+pub async fn archive_journal(
+    State(state): State<StateType>,
+    access: JournalAccess<Owner>,
+) -> Result<Redirect, ErrorRedirect> {
+    let callback_url = &format!("/journal/{}", access.journal.id);
+
+    let event_id = state
+        .journal_service
+        .archive_journal(
+            access.journal.id,
+            access.authority(),
+            state.clock.get_time(),
         )
         .await
         .or_redirect(callback_url)?;
@@ -187,3 +386,61 @@ pub async fn remove_member(
 
     Ok(Redirect::to(callback_url))
 }
+
+pub async fn unarchive_journal(
+    State(state): State<StateType>,
+    access: JournalAccess<Owner>,
+) -> Result<Redirect, ErrorRedirect> {
+    let callback_url = &format!("/journal/{}", access.journal.id);
+
+    let event_id = state
+        .journal_service
+        .unarchive_journal(
+            access.journal.id,
+            access.authority(),
+            state.clock.get_time(),
+        )
+        .await
+        .or_redirect(callback_url)?;
+
+    state.journal_service.wait_for(event_id).await;
+
+    Ok(Redirect::to(callback_url))
+}
+
+pub async fn pin_journal(
+    State(state): State<StateType>,
+    access: JournalAccess,
+) -> Result<Redirect, ErrorRedirect> {
+    set_pinned(state, access, true).await
+}
+
+pub async fn unpin_journal(
+    State(state): State<StateType>,
+    access: JournalAccess,
+) -> Result<Redirect, ErrorRedirect> {
+    set_pinned(state, access, false).await
+}
+
+async fn set_pinned(
+    state: StateType,
+    access: JournalAccess,
+    pinned: bool,
+) -> Result<Redirect, ErrorRedirect> {
+    const CALLBACK_URL: &str = "/journal";
+
+    let event_id = state
+        .journal_service
+        .pin_journal(
+            access.journal.id,
+            pinned,
+            access.authority(),
+            state.clock.get_time(),
+        )
+        .await
+        .or_redirect(CALLBACK_URL)?;
+
+    state.journal_service.wait_for(event_id).await;
+
+    Ok(Redirect::to(CALLBACK_URL))
+}
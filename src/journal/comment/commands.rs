@@ -0,0 +1,83 @@
+use crate::StateType;
+use crate::authn::UserId;
+use crate::email::Email;
+use crate::journal::access::JournalAccess;
+use crate::journal::comment::CommentId;
+use crate::journal::comment::mention::{parse_mentions, resolve_mentions};
+use crate::journal::transaction::TransactionId;
+use crate::monkesto_error::{ErrorRedirect, OrRedirect};
+use crate::time_provider::TimeProvider;
+use axum::extract::Path;
+use axum::extract::State;
+use axum::response::Redirect;
+use axum_extra::extract::Form;
+use serde::Deserialize;
+use std::str::FromStr;
+
+#[derive(Deserialize)]
+pub struct CommentForm {
+    body: String,
+    /// set when the comment replies to another one
+    parent_id: Option<String>,
+}
+
+pub async fn add_comment(
+    State(state): State<StateType>,
+    access: JournalAccess,
+    Path((id, transaction_id)): Path<(String, String)>,
+    Form(form): Form<CommentForm>,
+) -> Result<Redirect, ErrorRedirect> {
+    let callback_url = &format!("/journal/{}/transaction/{}", id, transaction_id);
+
+    let transaction_id = TransactionId::from_str(&transaction_id).or_redirect(callback_url)?;
+
+    let parent_id = form
+        .parent_id
+        .filter(|parent_id| !parent_id.is_empty())
+        .map(|parent_id| CommentId::from_str(&parent_id))
+        .transpose()
+        .or_redirect(callback_url)?;
+
+    let body = form.body.trim().to_string();
+
+    let handles = parse_mentions(&body);
+    let mentions = if handles.is_empty() {
+        Vec::new()
+    } else {
+        let user_ids = state
+            .journal_service
+            .list_mentionable_users(access.journal.id, &access.authority())
+            .await
+            .or_redirect(callback_url)?;
+
+        let members: Vec<(UserId, Email)> = state
+            .authn_service
+            .fetch_users(&user_ids)
+            .await
+            .or_redirect(callback_url)?
+            .into_iter()
+            .map(|user| (user.id, user.email))
+            .collect();
+
+        resolve_mentions(&handles, &members)
+    };
+
+    let event_id = state
+        .journal_service
+        .add_comment(
+            state.ids.generate(),
+            transaction_id,
+            access.journal.id,
+            parent_id,
+            body,
+            mentions,
+            access.authority(),
+            state.clock.get_time(),
+        )
+        .await
+        .or_redirect(callback_url)?;
+
+    state.journal_service.wait_for(event_id).await;
+
+    Ok(Redirect::to(callback_url))
+}
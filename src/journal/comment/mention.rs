@@ -0,0 +1,90 @@
+use crate::authn::UserId;
+use crate::email::Email;
+
+fn is_handle_char(c: char) -> bool {
+    c.is_alphanumeric() || matches!(c, '.' | '_' | '-' | '+' | '@')
+}
+
+/// the `@handles` in a comment, lowercased and without repeats. an `@` in the middle of a word,
+/// like the one in a plain email address, doesn't start a mention
+pub fn parse_mentions(body: &str) -> Vec<String> {
+    let mut handles: Vec<String> = Vec::new();
+    let mut previous: Option<char> = None;
+
+    for (i, c) in body.char_indices() {
+        if c == '@' && !previous.is_some_and(is_handle_char) {
+            let rest = &body[i + c.len_utf8()..];
+            let end = rest.find(|c| !is_handle_char(c)).unwrap_or(rest.len());
+            // a mention at the end of a sentence shouldn't take the full stop with it
+            let handle = rest[..end]
+                .trim_end_matches(|c: char| !c.is_alphanumeric())
+                .to_lowercase();
+
+            if !handle.is_empty() && !handles.contains(&handle) {
+                handles.push(handle);
+            }
+        }
+
+        previous = Some(c);
+    }
+
+    handles
+}
+
+/// the members that the handles name. a handle is either a member's whole email address or the
+/// part before the `@`, which only counts when a single member's address starts with it
+pub fn resolve_mentions(handles: &[String], members: &[(UserId, Email)]) -> Vec<UserId> {
+    let mut mentioned = Vec::new();
+
+    for handle in handles {
+        let matches: Vec<UserId> = members
+            .iter()
+            .filter(|(_, email)| {
+                let email = email.as_ref();
+                if handle.contains('@') {
+                    email.eq_ignore_ascii_case(handle)
+                } else {
+                    email
+                        .split_once('@')
+                        .is_some_and(|(local, _)| local.eq_ignore_ascii_case(handle))
+                }
+            })
+            .map(|(user_id, _)| *user_id)
+            .collect();
+
+        if let [user_id] = matches[..]
+            && !mentioned.contains(&user_id)
+        {
+            mentioned.push(user_id);
+        }
+    }
+
+    mentioned
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn mentions_resolve_by_address_or_unique_name() {
+        let handles = parse_mentions(
+            "@Alice and @bob@work.example, what was this? cc @bob. mail carol@example.com @nobody",
+        );
+        assert_eq!(handles, vec!["alice", "bob@work.example", "bob", "nobody"]);
+
+        let alice = UserId::new();
+        let bob = UserId::new();
+        let other_bob = UserId::new();
+        let carol = UserId::new();
+        let members = vec![
+            (alice, Email::try_new("alice@example.com").unwrap()),
+            (bob, Email::try_new("bob@work.example").unwrap()),
+            (other_bob, Email::try_new("bob@home.example").unwrap()),
+            (carol, Email::try_new("carol@example.com").unwrap()),
+        ];
+
+        // plain "bob" names two members, so it doesn't resolve to either
+        assert_eq!(resolve_mentions(&handles, &members), vec![alice, bob]);
+    }
+}
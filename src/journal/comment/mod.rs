@@ -0,0 +1,248 @@
+pub mod commands;
+pub mod mention;
+
+use axum::Router;
+use axum::routing::post;
+use axum_login::login_required;
+
+pub fn router() -> Router<crate::StateType> {
+    Router::new()
+        .route(
+            "/journal/{id}/transaction/{transaction_id}/comment",
+            post(commands::add_comment),
+        )
+        .route_layer(login_required!(crate::BackendType, login_url = "/signin"))
+}
+
+use crate::authn::UserId;
+use crate::authority::Authority;
+use crate::id;
+use crate::id::Ident;
+use crate::journal::domain::{CommentEvent, JournalDomainEvent};
+use crate::journal::member::JournalMember;
+use crate::journal::service::CommentState;
+use crate::journal::transaction::{Transaction, TransactionId};
+use crate::journal::{Journal, Permissions, check_permissions};
+use crate::journal::{JournalError, JournalId};
+use crate::time_provider::Timestamp;
+use disintegrate::{Decision, StateMutate, StateQuery};
+use serde::Deserialize;
+use serde::Serialize;
+use std::collections::HashSet;
+
+id!(CommentId, Ident::new16());
+
+/// the longest comment, in characters
+pub const MAX_COMMENT_LENGTH: usize = 2000;
+
+pub fn validate_comment(body: &str) -> Result<(), JournalError> {
+    match body.chars().count() {
+        0 => Err(JournalError::CommentLength(0)),
+        length if length > MAX_COMMENT_LENGTH => Err(JournalError::CommentLength(length)),
+        _ => Ok(()),
+    }
+}
+
+/// orders comments for display, each followed by its replies, along with how deeply it's nested.
+/// comments must be in the order they were posted, and a reply whose parent is missing is shown
+/// at the top level rather than hidden
+pub fn thread(comments: Vec<CommentState>) -> Vec<(usize, CommentState)> {
+    let ids: HashSet<CommentId> = comments.iter().map(|comment| comment.id).collect();
+
+    let (roots, replies): (Vec<_>, Vec<_>) = comments.into_iter().partition(|comment| {
+        comment
+            .parent_id
+            .is_none_or(|parent_id| !ids.contains(&parent_id))
+    });
+
+    let mut replies: Vec<Option<CommentState>> = replies.into_iter().map(Some).collect();
+    let mut threaded = Vec::new();
+    let mut stack: Vec<(usize, CommentState)> =
+        roots.into_iter().rev().map(|root| (0, root)).collect();
+
+    while let Some((depth, comment)) = stack.pop() {
+        let children: Vec<CommentState> = replies
+            .iter_mut()
+            .filter(|reply| {
+                reply
+                    .as_ref()
+                    .is_some_and(|reply| reply.parent_id == Some(comment.id))
+            })
+            .filter_map(Option::take)
+            .collect();
+
+        stack.extend(children.into_iter().rev().map(|child| (depth + 1, child)));
+        threaded.push((depth, comment));
+    }
+
+    threaded
+}
+
+/// the ids of every comment on a transaction, so that replies can be checked against them
+#[derive(StateQuery, Clone, Default, Serialize, Deserialize)]
+#[state_query(CommentEvent)]
+pub struct TransactionComments {
+    #[id]
+    transaction_id: TransactionId,
+    comments: HashSet<CommentId>,
+}
+
+impl TransactionComments {
+    fn new(transaction_id: TransactionId) -> Self {
+        Self {
+            transaction_id,
+            ..Default::default()
+        }
+    }
+}
+
+impl StateMutate for TransactionComments {
+    fn mutate(&mut self, event: Self::Event) {
+        match event {
+            CommentEvent::CommentAdded { comment_id, .. } => {
+                self.comments.insert(comment_id);
+            }
+        }
+    }
+}
+
+pub struct AddComment {
+    comment_id: CommentId,
+    transaction_id: TransactionId,
+    journal_id: JournalId,
+    parent_id: Option<CommentId>,
+    body: String,
+    mentions: Vec<UserId>,
+    authority: Authority,
+    timestamp: Timestamp,
+}
+
+impl AddComment {
+    #[expect(clippy::too_many_arguments)]
+    pub fn new(
+        comment_id: CommentId,
+        transaction_id: TransactionId,
+        journal_id: JournalId,
+        parent_id: Option<CommentId>,
+        body: String,
+        mentions: Vec<UserId>,
+        authority: Authority,
+        timestamp: Timestamp,
+    ) -> Self {
+        Self {
+            comment_id,
+            transaction_id,
+            journal_id,
+            parent_id,
+            body,
+            mentions,
+            authority,
+            timestamp,
+        }
+    }
+}
+
+impl Decision for AddComment {
+    type Event = JournalDomainEvent;
+    type StateQuery = (Transaction, TransactionComments, Journal, JournalMember);
+    type Error = JournalError;
+
+    fn state_query(&self) -> Self::StateQuery {
+        (
+            Transaction::new(self.transaction_id),
+            TransactionComments::new(self.transaction_id),
+            Journal::new(self.journal_id),
+            JournalMember::new(
+                self.journal_id,
+                self.authority.user_id().unwrap_or_default(),
+            ),
+        )
+    }
+
+    fn process(
+        &self,
+        (transaction, comments, journal, actor): &Self::StateQuery,
+    ) -> Result<Vec<Self::Event>, Self::Error> {
+        if comments.comments.contains(&self.comment_id) {
+            return Err(JournalError::CommentIdCollision(self.comment_id));
+        }
+
+        if !journal.status.valid() {
+            return Err(JournalError::InvalidJournal(self.journal_id));
+        }
+
+        if !transaction.status.valid() || transaction.journal_id != self.journal_id {
+            return Err(JournalError::InvalidTransaction(self.transaction_id));
+        }
+
+        // anyone who can see a transaction can ask about it
+        check_permissions(actor, &self.authority, journal, Permissions::READ)?;
+
+        if journal.archived {
+            return Err(JournalError::JournalArchived(self.journal_id));
+        }
+
+        if let Some(parent_id) = self.parent_id
+            && !comments.comments.contains(&parent_id)
+        {
+            return Err(JournalError::InvalidComment(parent_id));
+        }
+
+        validate_comment(&self.body)?;
+
+        Ok(vec![JournalDomainEvent::CommentAdded {
+            comment_id: self.comment_id,
+            transaction_id: self.transaction_id,
+            journal_id: self.journal_id,
+            parent_id: self.parent_id,
+            body: self.body.clone(),
+            mentions: self.mentions.clone(),
+            authority: self.authority.clone(),
+            timestamp: self.timestamp,
+        }])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::authority::Actor;
+    use chrono::Utc;
+
+    fn comment(id: &str, parent_id: Option<&str>) -> CommentState {
+        CommentState {
+            id: CommentId::from(id.to_string()),
+            parent_id: parent_id.map(|parent_id| CommentId::from(parent_id.to_string())),
+            body: id.to_string(),
+            authority: Authority::Direct(Actor::System),
+            timestamp: Utc::now(),
+        }
+    }
+
+    #[test]
+    fn replies_follow_their_parents() {
+        let threaded = thread(vec![
+            comment("c000000000000001", None),
+            comment("c000000000000002", None),
+            comment("c000000000000003", Some("c000000000000001")),
+            comment("c000000000000004", Some("c000000000000003")),
+            comment("c000000000000005", Some("c000000000000009")),
+        ]);
+
+        let order: Vec<(usize, &str)> = threaded
+            .iter()
+            .map(|(depth, comment)| (*depth, comment.body.as_str()))
+            .collect();
+
+        assert_eq!(
+            order,
+            vec![
+                (0, "c000000000000001"),
+                (1, "c000000000000003"),
+                (2, "c000000000000004"),
+                (0, "c000000000000002"),
+                (0, "c000000000000005"),
+            ]
+        );
+    }
+}
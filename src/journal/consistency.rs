@@ -0,0 +1,157 @@
+use crate::journal::JournalService;
+use crate::journal::account::AccountId;
+use crate::journal::rebuild::Inconsistency;
+use crate::journal::transaction::BalanceUpdate;
+use crate::shutdown;
+use axum_login::tracing;
+use std::collections::HashMap;
+use std::env;
+use std::time::Duration;
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+pub enum BalanceCheckConfigError {
+    #[error("BALANCE_CHECK_INTERVAL_SECS must be a whole number of seconds, found {0}")]
+    Interval(String),
+
+    #[error("BALANCE_CHECK_REPAIR must be true or false, found {0}")]
+    Repair(String),
+}
+
+/// how often the balance checker runs, and whether it corrects the drift it finds
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct BalanceCheckConfig {
+    pub interval: Duration,
+    pub repair: bool,
+}
+
+impl Default for BalanceCheckConfig {
+    fn default() -> Self {
+        Self {
+            interval: Duration::from_secs(15 * 60),
+            repair: false,
+        }
+    }
+}
+
+impl BalanceCheckConfig {
+    pub fn from_env() -> Result<Self, BalanceCheckConfigError> {
+        let default = Self::default();
+
+        let interval = match env::var("BALANCE_CHECK_INTERVAL_SECS") {
+            Ok(value) => Duration::from_secs(
+                value
+                    .parse::<u64>()
+                    .map_err(|_| BalanceCheckConfigError::Interval(value))?,
+            ),
+            Err(_) => default.interval,
+        };
+
+        let repair = match env::var("BALANCE_CHECK_REPAIR") {
+            Ok(value) => value
+                .parse::<bool>()
+                .map_err(|_| BalanceCheckConfigError::Repair(value))?,
+            Err(_) => default.repair,
+        };
+
+        Ok(Self { interval, repair })
+    }
+}
+
+/// the balance of every account that the transactions touch, where credits are positive
+pub fn recompute_balances<'a>(
+    updates: impl IntoIterator<Item = &'a BalanceUpdate>,
+) -> HashMap<AccountId, i64> {
+    let mut balances = HashMap::new();
+
+    for update in updates {
+        *balances.entry(update.account_id).or_default() += update.signed_amount();
+    }
+
+    balances
+}
+
+/// the accounts whose cached balance differs from the sum of their transactions.
+/// an account without any transactions should have a balance of zero
+pub fn balance_drift(
+    cached: impl IntoIterator<Item = (AccountId, i64)>,
+    recomputed: &HashMap<AccountId, i64>,
+) -> Vec<Inconsistency> {
+    cached
+        .into_iter()
+        .filter_map(|(account_id, cached)| {
+            let rebuilt = recomputed.get(&account_id).copied().unwrap_or_default();
+
+            (cached != rebuilt).then_some(Inconsistency::BalanceMismatch {
+                account_id,
+                cached,
+                rebuilt,
+            })
+        })
+        .collect()
+}
+
+/// periodically recomputes every account balance from the projected transactions so that
+/// bugs in how events update the balances show up in the logs instead of in someone's books
+pub async fn check_balances(journal_service: JournalService, config: BalanceCheckConfig) {
+    let mut shutdown = Box::pin(shutdown());
+
+    loop {
+        tokio::select! {
+            _ = tokio::time::sleep(config.interval) => {},
+            _ = &mut shutdown => return,
+        }
+
+        match journal_service.check_balances(config.repair).await {
+            Ok(drift) => {
+                for inconsistency in drift.iter() {
+                    if config.repair {
+                        tracing::error!("repaired a balance: {inconsistency}");
+                    } else {
+                        tracing::error!("{inconsistency}");
+                    }
+                }
+            }
+            Err(error) => tracing::error!(?error, "failed to check the account balances"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::journal::transaction::EntryType;
+
+    #[test]
+    fn only_drifted_accounts_are_reported() {
+        let cash = AccountId::new();
+        let revenue = AccountId::new();
+        let unused = AccountId::new();
+
+        let updates = [
+            BalanceUpdate {
+                account_id: cash,
+                amount: 500,
+                entry_type: EntryType::Debit,
+                memo: None,
+            },
+            BalanceUpdate {
+                account_id: revenue,
+                amount: 500,
+                entry_type: EntryType::Credit,
+                memo: None,
+            },
+        ];
+
+        let recomputed = recompute_balances(updates.iter());
+
+        assert_eq!(
+            balance_drift([(cash, -500), (revenue, 400), (unused, 0)], &recomputed),
+            [Inconsistency::BalanceMismatch {
+                account_id: revenue,
+                cached: 400,
+                rebuilt: 500,
+            }]
+        );
+    }
+}
@@ -0,0 +1,190 @@
+use crate::BackendType;
+use crate::StateType;
+use crate::authn::get_user;
+use crate::charts::{Chart, Series, bar_chart};
+use crate::journal::appearance::journal_badge;
+use crate::journal::layout::layout;
+use crate::journal::transaction::views::{format_amount, format_balance};
+use crate::journal::transaction::{BalanceUpdate, EntryType};
+use axum::extract::State;
+use axum::response::Redirect;
+use axum_login::AuthSession;
+use maud::Markup;
+use maud::html;
+
+/// how many balances are shown for each journal
+const BALANCES_PER_JOURNAL: i64 = 5;
+
+/// how many of the latest transactions are shown across every journal
+const RECENT_TRANSACTIONS: i64 = 10;
+
+/// the amount a transaction moved, which is the sum of its debits
+fn debits(entries: &[BalanceUpdate]) -> u64 {
+    entries
+        .iter()
+        .filter(|entry| entry.entry_type == EntryType::Debit)
+        .map(|entry| entry.amount)
+        .sum()
+}
+
+/// an overview of every journal the user belongs to
+pub async fn dashboard_page(
+    State(state): State<StateType>,
+    session: AuthSession<BackendType>,
+) -> Result<Markup, Redirect> {
+    let user = get_user(session)?;
+
+    let content = match state
+        .journal_service
+        .dashboard(user.id, BALANCES_PER_JOURNAL, RECENT_TRANSACTIONS)
+        .await
+    {
+        Ok(dashboard) => html! {
+            div class="flex flex-col gap-8 mx-auto w-full max-w-5xl" {
+                @if !dashboard.invites.is_empty() {
+                    section class="space-y-3" {
+                        h2 class="text-base font-semibold text-gray-900 dark:text-white" { "New Journals" }
+                        ul class="divide-y divide-gray-200 dark:divide-gray-700 bg-white dark:bg-gray-800 border border-gray-200 dark:border-gray-700 rounded-xl" {
+                            @for invite in &dashboard.invites {
+                                li class="flex items-center justify-between gap-4 p-4" {
+                                    div {
+                                        p class="text-sm font-medium text-gray-900 dark:text-white" {
+                                            "You were added to " (invite.journal_name)
+                                        }
+                                        p class="text-xs text-gray-500 dark:text-gray-400" {
+                                            (user.timezone().format(invite.created_at))
+                                        }
+                                    }
+                                    a
+                                    href=(format!("/journal/{}", invite.journal_id))
+                                    class="text-sm font-semibold text-indigo-600 hover:text-indigo-500 dark:text-indigo-400" {
+                                        "Open"
+                                    }
+                                }
+                            }
+                        }
+                        a href="/notifications" class="text-xs text-gray-500 hover:text-gray-700 dark:text-gray-400 dark:hover:text-gray-200" {
+                            "Mark these as read in your notifications"
+                        }
+                    }
+                }
+
+                section class="space-y-3" {
+                    div class="flex items-center justify-between" {
+                        h2 class="text-base font-semibold text-gray-900 dark:text-white" { "Balances" }
+                        a href="/journal" class="text-sm text-gray-500 hover:text-gray-700 dark:text-gray-400 dark:hover:text-gray-200" {
+                            "All journals"
+                        }
+                    }
+                    @if dashboard.journals.is_empty() {
+                        p class="text-sm text-gray-500 dark:text-gray-400" {
+                            "You don't belong to any journals yet. "
+                            a href="/journal" class="font-semibold text-indigo-600 hover:text-indigo-500 dark:text-indigo-400" {
+                                "Create one"
+                            }
+                        }
+                    }
+                    div class="grid grid-cols-1 sm:grid-cols-2 lg:grid-cols-3 gap-4" {
+                        @for journal in &dashboard.journals {
+                            a
+                            href=(format!("/journal/{}", journal.id))
+                            class="self-start p-4 bg-white dark:bg-gray-800 border border-gray-200 dark:border-gray-700 rounded-xl hover:bg-gray-50 dark:hover:bg-gray-700 transition-colors" {
+                                h3 class="flex items-center gap-2 text-sm font-semibold text-gray-900 dark:text-white mb-3" {
+                                    (journal_badge(&journal.name, &journal.appearance))
+                                    (journal.name)
+                                }
+                                @if journal.balances.is_empty() {
+                                    p class="text-sm text-gray-500 dark:text-gray-400" { "No balances yet." }
+                                } @else {
+                                    div class="mb-3" {
+                                        (bar_chart(&Chart {
+                                            title: format!("The largest balances in {}", journal.name),
+                                            labels: journal.balances.iter().map(|(_, name, _)| name.to_string()).collect(),
+                                            series: vec![Series {
+                                                name: "Balance".to_string(),
+                                                values: journal.balances.iter().map(|(_, _, balance)| *balance).collect(),
+                                            }],
+                                            format: format_balance,
+                                        }))
+                                    }
+                                    ul class="space-y-1 text-sm" {
+                                        @for (_, name, balance) in &journal.balances {
+                                            li class="flex justify-between gap-2" {
+                                                span class="truncate text-gray-700 dark:text-gray-300" { (name) }
+                                                span class="font-mono text-gray-900 dark:text-white" { (format_balance(*balance)) }
+                                            }
+                                        }
+                                    }
+                                }
+                            }
+                        }
+                    }
+                }
+
+                section class="space-y-3" {
+                    h2 class="text-base font-semibold text-gray-900 dark:text-white" { "Recent Transactions" }
+                    @if dashboard.recent_transactions.is_empty() {
+                        p class="text-sm text-gray-500 dark:text-gray-400" { "No transactions yet." }
+                    } @else {
+                        ul class="divide-y divide-gray-200 dark:divide-gray-700 bg-white dark:bg-gray-800 border border-gray-200 dark:border-gray-700 rounded-xl" {
+                            @for transaction in &dashboard.recent_transactions {
+                                li {
+                                    a
+                                    href=(format!("/journal/{}/transaction/{}", transaction.journal_id, transaction.id))
+                                    class="flex items-center justify-between gap-4 p-4 hover:bg-gray-50 dark:hover:bg-gray-700 transition-colors" {
+                                        div {
+                                            p class="text-sm font-medium text-gray-900 dark:text-white" {
+                                                (transaction.journal_name) " #" (transaction.number)
+                                            }
+                                            p class="text-xs text-gray-500 dark:text-gray-400" {
+                                                (transaction.effective_date)
+                                            }
+                                        }
+                                        span class="font-mono text-sm text-gray-900 dark:text-white" {
+                                            (format_amount(debits(&transaction.entries)))
+                                        }
+                                    }
+                                }
+                            }
+                        }
+                    }
+                }
+            }
+        },
+        Err(e) => html! {
+            div class="flex justify-center items-center h-full" {
+                p class="text-gray-500 dark:text-gray-400" {
+                    (format!("Failed to fetch your dashboard: {:?}", e))
+                }
+            }
+        },
+    };
+
+    Ok(layout(None, false, None, content))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::journal::account::AccountId;
+
+    #[test]
+    fn a_transaction_moves_the_sum_of_its_debits() {
+        let entry = |amount, entry_type| BalanceUpdate {
+            account_id: AccountId::new(),
+            amount,
+            entry_type,
+            memo: None,
+        };
+
+        assert_eq!(
+            debits(&[
+                entry(1500, EntryType::Debit),
+                entry(500, EntryType::Debit),
+                entry(2000, EntryType::Credit),
+            ]),
+            2000
+        );
+        assert_eq!(debits(&[]), 0);
+    }
+}
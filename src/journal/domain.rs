@@ -1,25 +1,77 @@
-use crate::authn::UserId;
+use crate::authn::{AuthnService, UserId};
 use crate::authority::Authority;
-use crate::journal::account::AccountId;
+use crate::journal::account::{AccountId, AccountPlacement};
+use crate::journal::appearance::JournalAppearance;
+use crate::journal::bankfeed::BankFeedId;
+use crate::journal::comment::CommentId;
+use crate::journal::fiscal::FiscalYearStart;
+use crate::journal::notification::notifier::Notifier;
+use crate::journal::notification::outbox::EmailSender;
+use crate::journal::rule::{RuleCondition, RuleId};
 use crate::journal::store::JournalEventStore;
+use crate::journal::template::TemplateId;
 use crate::journal::transaction::{BalanceUpdate, TransactionId};
+use crate::journal::transfer::TransferLink;
+use crate::journal::webhook::WebhookId;
+use crate::journal::webhook::delivery::WebhookDispatcher;
 use crate::journal::{JournalId, JournalService, Permissions};
+use crate::mailer::Mailer;
 use crate::name::Name;
 use crate::shutdown;
 use crate::time_provider::Timestamp;
 use axum_login::tracing;
 use axum_test::expect_json::__private::serde_trampoline::{Deserialize, Serialize};
+use chrono::NaiveDate;
 use disintegrate::Event;
 use disintegrate_postgres::{
     PgEventListener, PgEventListenerConfig, PgEventListenerError, RetryAction,
 };
+use std::sync::Arc;
 use std::time::Duration;
 
 #[derive(Debug, Clone, PartialEq, Event, Serialize, Deserialize)]
-#[stream(JournalEvent, [JournalCreated, JournalDeleted])]
+#[stream(
+    JournalEvent,
+    [
+        JournalCreated,
+        JournalDeleted,
+        JournalSettingsUpdated,
+        JournalArchived,
+        JournalUnarchived
+    ]
+)]
 #[stream(MemberEvent, [MemberAdded, MemberPermissionsUpdated, MemberRemoved])]
 #[stream(AccountEvent, [AccountCreated, AccountRenamed, AccountDeleted])]
-#[stream(TransactionEvent, [TransactionCreated, TransactionDeleted])]
+#[stream(
+    TransactionEvent,
+    [
+        TransactionCreated,
+        TransactionDeleted,
+        TransactionSubmitted,
+        TransactionApproved,
+        TransactionRejected
+    ]
+)]
+#[stream(WebhookEvent, [WebhookAdded, WebhookRemoved])]
+#[stream(ImportRuleEvent, [ImportRuleAdded, ImportRuleRemoved])]
+#[stream(BankFeedEvent, [BankFeedLinked, BankFeedUnlinked])]
+#[stream(TemplateEvent, [TemplateSaved, TemplateDeleted])]
+#[stream(CommentEvent, [CommentAdded])]
+#[stream(
+    TransferJournalEvent,
+    [
+        JournalCreated,
+        JournalDeleted,
+        JournalArchived,
+        JournalUnarchived,
+        MemberAdded,
+        MemberPermissionsUpdated,
+        MemberRemoved,
+        AccountCreated,
+        AccountDeleted,
+        TransactionCreated
+    ]
+)]
 pub enum JournalDomainEvent {
     JournalCreated {
         #[id]
@@ -35,6 +87,49 @@ pub enum JournalDomainEvent {
         authority: Authority,
         timestamp: Timestamp,
     },
+    /// the settings after `timestamp` were added later, so events recorded without them default
+    JournalSettingsUpdated {
+        #[id]
+        journal_id: JournalId,
+        fiscal_year_start: FiscalYearStart,
+        authority: Authority,
+        timestamp: Timestamp,
+        #[serde(default)]
+        default_cash_account: Option<AccountId>,
+        #[serde(default)]
+        default_expense_account: Option<AccountId>,
+        #[serde(default)]
+        appearance: JournalAppearance,
+    },
+    JournalArchived {
+        #[id]
+        journal_id: JournalId,
+        authority: Authority,
+        timestamp: Timestamp,
+    },
+    JournalUnarchived {
+        #[id]
+        journal_id: JournalId,
+        authority: Authority,
+        timestamp: Timestamp,
+    },
+    /// the user keeps the journal at the top of their journal list
+    JournalPinned {
+        #[id]
+        journal_id: JournalId,
+        #[id]
+        user_id: UserId,
+        authority: Authority,
+        timestamp: Timestamp,
+    },
+    JournalUnpinned {
+        #[id]
+        journal_id: JournalId,
+        #[id]
+        user_id: UserId,
+        authority: Authority,
+        timestamp: Timestamp,
+    },
     MemberAdded {
         #[id]
         journal_id: JournalId,
@@ -83,6 +178,17 @@ pub enum JournalDomainEvent {
         authority: Authority,
         timestamp: Timestamp,
     },
+    /// the order the journal's accounts are listed in and the groups they're listed under.
+    /// accounts it leaves out, such as ones created since, are listed after it by name
+    AccountsArranged {
+        #[id]
+        journal_id: JournalId,
+        arrangement: Vec<AccountPlacement>,
+        authority: Authority,
+        timestamp: Timestamp,
+    },
+    /// the fields after `timestamp` were added later, so they have defaults for the events
+    /// recorded without them, and stay after it for the events encoded by position
     TransactionCreated {
         #[id]
         transaction_id: TransactionId,
@@ -91,6 +197,22 @@ pub enum JournalDomainEvent {
         balance_updates: Vec<BalanceUpdate>,
         authority: Authority,
         timestamp: Timestamp,
+        /// the transaction's voucher number, counting up from 1 within the journal. it's 0 on
+        /// transactions recorded before they were numbered, which count as the next number
+        #[serde(default)]
+        number: u64,
+        /// transactions recorded before they had one took effect on the day they were recorded
+        #[serde(default = "undated")]
+        effective_date: NaiveDate,
+        /// the transaction this one reverses, if it was recorded as a reversal
+        #[serde(default)]
+        reverses: Option<TransactionId>,
+        /// the other side, if this was recorded as a transfer between journals
+        #[serde(default)]
+        transfer: Option<TransferLink>,
+        /// who was paid or paid in, the way a bank statement names them
+        #[serde(default)]
+        payee: Option<Name>,
     },
     TransactionDeleted {
         #[id]
@@ -98,9 +220,387 @@ pub enum JournalDomainEvent {
         authority: Authority,
         timestamp: Timestamp,
     },
+    /// a transaction waiting for approval, which doesn't touch any balance until it's approved
+    TransactionSubmitted {
+        #[id]
+        transaction_id: TransactionId,
+        #[id]
+        journal_id: JournalId,
+        balance_updates: Vec<BalanceUpdate>,
+        effective_date: NaiveDate,
+        authority: Authority,
+        timestamp: Timestamp,
+        /// who was paid or paid in. submissions recorded before transactions had payees have none
+        #[serde(default)]
+        payee: Option<Name>,
+    },
+    /// always followed by the `TransactionCreated` that records the approved transaction
+    TransactionApproved {
+        #[id]
+        transaction_id: TransactionId,
+        #[id]
+        journal_id: JournalId,
+        authority: Authority,
+        timestamp: Timestamp,
+    },
+    TransactionRejected {
+        #[id]
+        transaction_id: TransactionId,
+        #[id]
+        journal_id: JournalId,
+        authority: Authority,
+        timestamp: Timestamp,
+    },
+    WebhookAdded {
+        #[id]
+        webhook_id: WebhookId,
+        #[id]
+        journal_id: JournalId,
+        url: String,
+        authority: Authority,
+        timestamp: Timestamp,
+    },
+    WebhookRemoved {
+        #[id]
+        webhook_id: WebhookId,
+        #[id]
+        journal_id: JournalId,
+        authority: Authority,
+        timestamp: Timestamp,
+    },
+    ImportRuleAdded {
+        #[id]
+        rule_id: RuleId,
+        #[id]
+        journal_id: JournalId,
+        condition: RuleCondition,
+        account_id: AccountId,
+        authority: Authority,
+        timestamp: Timestamp,
+    },
+    ImportRuleRemoved {
+        #[id]
+        rule_id: RuleId,
+        #[id]
+        journal_id: JournalId,
+        authority: Authority,
+        timestamp: Timestamp,
+    },
+    BankFeedLinked {
+        #[id]
+        bank_feed_id: BankFeedId,
+        #[id]
+        journal_id: JournalId,
+        provider: String,
+        external_account: String,
+        account_id: AccountId,
+        authority: Authority,
+        timestamp: Timestamp,
+    },
+    BankFeedUnlinked {
+        #[id]
+        bank_feed_id: BankFeedId,
+        #[id]
+        journal_id: JournalId,
+        authority: Authority,
+        timestamp: Timestamp,
+    },
+    TemplateSaved {
+        #[id]
+        template_id: TemplateId,
+        #[id]
+        journal_id: JournalId,
+        name: Name,
+        entries: Vec<BalanceUpdate>,
+        authority: Authority,
+        timestamp: Timestamp,
+    },
+    TemplateDeleted {
+        #[id]
+        template_id: TemplateId,
+        #[id]
+        journal_id: JournalId,
+        authority: Authority,
+        timestamp: Timestamp,
+    },
+    CommentAdded {
+        #[id]
+        comment_id: CommentId,
+        #[id]
+        transaction_id: TransactionId,
+        #[id]
+        journal_id: JournalId,
+        /// the comment this one replies to, if it's a reply
+        parent_id: Option<CommentId>,
+        body: String,
+        /// the members the comment mentions by name or email
+        mentions: Vec<UserId>,
+        authority: Authority,
+        timestamp: Timestamp,
+    },
+}
+
+/// stands in for the effective date of transactions recorded before they had one, until
+/// `upgrade` replaces it. no transaction can be dated this early
+fn undated() -> NaiveDate {
+    NaiveDate::MIN
+}
+
+impl JournalDomainEvent {
+    /// fills in the fields that events recorded before they existed can't default on their own
+    pub fn upgrade(mut self) -> Self {
+        if let Self::TransactionCreated {
+            effective_date,
+            timestamp,
+            ..
+        } = &mut self
+            && *effective_date == undated()
+        {
+            *effective_date = timestamp.date_naive();
+        }
+
+        self
+    }
+
+    /// the journal this event belongs to, if the event records it
+    pub fn journal_id(&self) -> Option<JournalId> {
+        match self {
+            Self::JournalCreated { journal_id, .. }
+            | Self::JournalDeleted { journal_id, .. }
+            | Self::JournalSettingsUpdated { journal_id, .. }
+            | Self::JournalArchived { journal_id, .. }
+            | Self::JournalUnarchived { journal_id, .. }
+            | Self::JournalPinned { journal_id, .. }
+            | Self::JournalUnpinned { journal_id, .. }
+            | Self::MemberAdded { journal_id, .. }
+            | Self::MemberPermissionsUpdated { journal_id, .. }
+            | Self::MemberRemoved { journal_id, .. }
+            | Self::AccountCreated { journal_id, .. }
+            | Self::AccountsArranged { journal_id, .. }
+            | Self::TransactionCreated { journal_id, .. }
+            | Self::TransactionSubmitted { journal_id, .. }
+            | Self::TransactionApproved { journal_id, .. }
+            | Self::TransactionRejected { journal_id, .. }
+            | Self::WebhookAdded { journal_id, .. }
+            | Self::WebhookRemoved { journal_id, .. }
+            | Self::ImportRuleAdded { journal_id, .. }
+            | Self::ImportRuleRemoved { journal_id, .. }
+            | Self::BankFeedLinked { journal_id, .. }
+            | Self::BankFeedUnlinked { journal_id, .. }
+            | Self::TemplateSaved { journal_id, .. }
+            | Self::TemplateDeleted { journal_id, .. }
+            | Self::CommentAdded { journal_id, .. } => Some(*journal_id),
+            Self::AccountRenamed { .. }
+            | Self::AccountDeleted { .. }
+            | Self::TransactionDeleted { .. } => None,
+        }
+    }
+
+    /// whether only the journal's owners may see this event. webhook urls and linked bank
+    /// accounts are often credentials themselves, and a pin is one member's own preference
+    pub fn owners_only(&self) -> bool {
+        matches!(
+            self,
+            Self::WebhookAdded { .. }
+                | Self::WebhookRemoved { .. }
+                | Self::BankFeedLinked { .. }
+                | Self::BankFeedUnlinked { .. }
+                | Self::JournalPinned { .. }
+                | Self::JournalUnpinned { .. }
+        )
+    }
+
+    /// who recorded this event
+    pub fn authority(&self) -> &Authority {
+        match self {
+            Self::JournalCreated { authority, .. }
+            | Self::JournalDeleted { authority, .. }
+            | Self::JournalSettingsUpdated { authority, .. }
+            | Self::JournalArchived { authority, .. }
+            | Self::JournalUnarchived { authority, .. }
+            | Self::JournalPinned { authority, .. }
+            | Self::JournalUnpinned { authority, .. }
+            | Self::MemberAdded { authority, .. }
+            | Self::MemberPermissionsUpdated { authority, .. }
+            | Self::MemberRemoved { authority, .. }
+            | Self::AccountCreated { authority, .. }
+            | Self::AccountsArranged { authority, .. }
+            | Self::AccountRenamed { authority, .. }
+            | Self::AccountDeleted { authority, .. }
+            | Self::TransactionCreated { authority, .. }
+            | Self::TransactionSubmitted { authority, .. }
+            | Self::TransactionApproved { authority, .. }
+            | Self::TransactionRejected { authority, .. }
+            | Self::TransactionDeleted { authority, .. }
+            | Self::WebhookAdded { authority, .. }
+            | Self::WebhookRemoved { authority, .. }
+            | Self::ImportRuleAdded { authority, .. }
+            | Self::ImportRuleRemoved { authority, .. }
+            | Self::BankFeedLinked { authority, .. }
+            | Self::BankFeedUnlinked { authority, .. }
+            | Self::TemplateSaved { authority, .. }
+            | Self::TemplateDeleted { authority, .. }
+            | Self::CommentAdded { authority, .. } => authority,
+        }
+    }
+
+    /// who recorded this event, for rewriting when their account is erased
+    pub fn authority_mut(&mut self) -> &mut Authority {
+        match self {
+            Self::JournalCreated { authority, .. }
+            | Self::JournalDeleted { authority, .. }
+            | Self::JournalSettingsUpdated { authority, .. }
+            | Self::JournalArchived { authority, .. }
+            | Self::JournalUnarchived { authority, .. }
+            | Self::JournalPinned { authority, .. }
+            | Self::JournalUnpinned { authority, .. }
+            | Self::MemberAdded { authority, .. }
+            | Self::MemberPermissionsUpdated { authority, .. }
+            | Self::MemberRemoved { authority, .. }
+            | Self::AccountCreated { authority, .. }
+            | Self::AccountsArranged { authority, .. }
+            | Self::AccountRenamed { authority, .. }
+            | Self::AccountDeleted { authority, .. }
+            | Self::TransactionCreated { authority, .. }
+            | Self::TransactionSubmitted { authority, .. }
+            | Self::TransactionApproved { authority, .. }
+            | Self::TransactionRejected { authority, .. }
+            | Self::TransactionDeleted { authority, .. }
+            | Self::WebhookAdded { authority, .. }
+            | Self::WebhookRemoved { authority, .. }
+            | Self::ImportRuleAdded { authority, .. }
+            | Self::ImportRuleRemoved { authority, .. }
+            | Self::BankFeedLinked { authority, .. }
+            | Self::BankFeedUnlinked { authority, .. }
+            | Self::TemplateSaved { authority, .. }
+            | Self::TemplateDeleted { authority, .. }
+            | Self::CommentAdded { authority, .. } => authority,
+        }
+    }
+
+    /// replaces every mention of the user with the tombstone actor, or the nil id where the event
+    /// names a user rather than an actor, returning whether any changed
+    pub fn forget_user_mut(&mut self, user_id: UserId) -> bool {
+        let mut forgotten = self.authority_mut().forget(user_id);
+
+        let user_ids = match self {
+            Self::JournalCreated { owner, .. } => vec![owner],
+            Self::JournalPinned { user_id, .. }
+            | Self::JournalUnpinned { user_id, .. }
+            | Self::MemberAdded { user_id, .. }
+            | Self::MemberPermissionsUpdated { user_id, .. }
+            | Self::MemberRemoved { user_id, .. } => vec![user_id],
+            Self::CommentAdded { mentions, .. } => mentions.iter_mut().collect(),
+            Self::JournalDeleted { .. }
+            | Self::JournalSettingsUpdated { .. }
+            | Self::JournalArchived { .. }
+            | Self::JournalUnarchived { .. }
+            | Self::AccountCreated { .. }
+            | Self::AccountsArranged { .. }
+            | Self::AccountRenamed { .. }
+            | Self::AccountDeleted { .. }
+            | Self::TransactionCreated { .. }
+            | Self::TransactionSubmitted { .. }
+            | Self::TransactionApproved { .. }
+            | Self::TransactionRejected { .. }
+            | Self::TransactionDeleted { .. }
+            | Self::WebhookAdded { .. }
+            | Self::WebhookRemoved { .. }
+            | Self::ImportRuleAdded { .. }
+            | Self::ImportRuleRemoved { .. }
+            | Self::BankFeedLinked { .. }
+            | Self::BankFeedUnlinked { .. }
+            | Self::TemplateSaved { .. }
+            | Self::TemplateDeleted { .. } => Vec::new(),
+        };
+
+        for id in user_ids {
+            if *id == user_id {
+                *id = UserId::nil();
+                forgotten = true;
+            }
+        }
+        forgotten
+    }
+
+    /// when this event was recorded
+    pub fn timestamp(&self) -> Timestamp {
+        match self {
+            Self::JournalCreated { timestamp, .. }
+            | Self::JournalDeleted { timestamp, .. }
+            | Self::JournalSettingsUpdated { timestamp, .. }
+            | Self::JournalArchived { timestamp, .. }
+            | Self::JournalUnarchived { timestamp, .. }
+            | Self::JournalPinned { timestamp, .. }
+            | Self::JournalUnpinned { timestamp, .. }
+            | Self::MemberAdded { timestamp, .. }
+            | Self::MemberPermissionsUpdated { timestamp, .. }
+            | Self::MemberRemoved { timestamp, .. }
+            | Self::AccountCreated { timestamp, .. }
+            | Self::AccountsArranged { timestamp, .. }
+            | Self::AccountRenamed { timestamp, .. }
+            | Self::AccountDeleted { timestamp, .. }
+            | Self::TransactionCreated { timestamp, .. }
+            | Self::TransactionSubmitted { timestamp, .. }
+            | Self::TransactionApproved { timestamp, .. }
+            | Self::TransactionRejected { timestamp, .. }
+            | Self::TransactionDeleted { timestamp, .. }
+            | Self::WebhookAdded { timestamp, .. }
+            | Self::WebhookRemoved { timestamp, .. }
+            | Self::ImportRuleAdded { timestamp, .. }
+            | Self::ImportRuleRemoved { timestamp, .. }
+            | Self::BankFeedLinked { timestamp, .. }
+            | Self::BankFeedUnlinked { timestamp, .. }
+            | Self::TemplateSaved { timestamp, .. }
+            | Self::TemplateDeleted { timestamp, .. }
+            | Self::CommentAdded { timestamp, .. } => *timestamp,
+        }
+    }
+}
+
+pub(crate) async fn event_listener(
+    event_store: JournalEventStore,
+    service: JournalService,
+    authn_service: AuthnService,
+    mailer: Arc<dyn Mailer>,
+) {
+    let webhook_dispatcher = WebhookDispatcher::new(service.clone());
+    let email_sender = EmailSender::new(service.clone(), mailer);
+    let notifier = Notifier::new(service.clone(), authn_service, email_sender.clone());
+
+    tokio::spawn(webhook_dispatcher.clone().deliver_pending());
+    tokio::spawn(email_sender.send_pending());
+
+    PgEventListener::builder(event_store.event_store)
+        .register_listener(
+            service,
+            PgEventListenerConfig::poller(Duration::from_secs(60))
+                .with_notifier()
+                .fetch_size(100)
+                .with_retry(handle_event_listener_retry),
+        )
+        .register_listener(
+            webhook_dispatcher,
+            PgEventListenerConfig::poller(Duration::from_secs(60))
+                .with_notifier()
+                .fetch_size(100)
+                .with_retry(handle_event_listener_retry),
+        )
+        .register_listener(
+            notifier,
+            PgEventListenerConfig::poller(Duration::from_secs(60))
+                .with_notifier()
+                .fetch_size(100)
+                .with_retry(handle_event_listener_retry),
+        )
+        .start_with_shutdown(shutdown())
+        .await
+        .expect("event listener failed");
 }
 
-pub(crate) async fn event_listener(event_store: JournalEventStore, service: JournalService) {
+/// projects the journal events without delivering webhooks or notifications for them, for
+/// commands that record events outside of a running server
+pub(crate) async fn projection_listener(event_store: JournalEventStore, service: JournalService) {
     PgEventListener::builder(event_store.event_store)
         .register_listener(
             service,
@@ -0,0 +1,165 @@
+use crate::BackendType;
+use crate::StateType;
+use crate::i18n::Locale;
+use crate::journal::{JournalId, JournalResult};
+use crate::negotiate::wants_json;
+use axum::RequestPartsExt;
+use axum::extract::{MatchedPath, Path, Request, State};
+use axum::http::header::{CACHE_CONTROL, ETAG, IF_NONE_MATCH, VARY};
+use axum::http::{HeaderValue, Method, StatusCode};
+use axum::middleware::Next;
+use axum::response::{IntoResponse, Response};
+use axum_login::AuthSession;
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::str::FromStr;
+
+/// pages that only change when their journal does. other journal pages also read the session
+/// or other services, so they're always rendered
+const CACHEABLE_PAGES: [&str; 4] = [
+    "/journal",
+    "/journal/{id}",
+    "/journal/{id}/account",
+    "/journal/{id}/transaction",
+];
+
+/// a weak validator for a version of a page, which differs between query strings
+fn etag(uri: &str, version: &str) -> String {
+    let digest = Sha256::digest(format!("{uri}\n{version}"));
+    format!("W/\"{}\"", hex::encode(&digest[..16]))
+}
+
+/// whether an If-None-Match header names the etag, using the weak comparison GET requires
+fn matches(if_none_match: &str, etag: &str) -> bool {
+    let opaque = |tag: &str| tag.trim().trim_start_matches("W/").to_string();
+
+    if_none_match
+        .split(',')
+        .any(|tag| tag.trim() == "*" || opaque(tag) == opaque(etag))
+}
+
+/// the version of the page for the signed in user in their language, timezone, theme and
+/// shortcuts, or none if it can't be cached
+async fn page_version(
+    state: &StateType,
+    user: &AuthSession<BackendType>,
+    params: &HashMap<String, String>,
+) -> Option<String> {
+    let user = user.user.as_ref()?;
+
+    let version: JournalResult<String> = match params.get("id") {
+        Some(id) => match JournalId::from_str(id) {
+            Ok(journal_id) => state
+                .journal_service
+                .journal_version(journal_id)
+                .await
+                .map(|version| version.to_string()),
+            Err(_) => return None,
+        },
+        None => state
+            .journal_service
+            .journal_list_version(user.id)
+            .await
+            .map(|(count, version)| format!("{count}-{version}")),
+    };
+
+    Some(format!(
+        "{}-{}-{}-{}-{}-{}",
+        user.id,
+        Locale::current(),
+        user.timezone(),
+        user.theme(),
+        serde_json::to_string(&user.shortcuts()).ok()?,
+        version.ok()?
+    ))
+}
+
+/// answers GET requests for journal pages with 304 Not Modified when the journal hasn't changed
+/// since the client's copy, and tags the pages it does render so clients can ask next time
+pub async fn conditional_get(
+    State(state): State<StateType>,
+    session: AuthSession<BackendType>,
+    matched_path: Option<MatchedPath>,
+    request: Request,
+    next: Next,
+) -> Response {
+    let cacheable = request.method() == Method::GET
+        && matched_path.is_some_and(|path| CACHEABLE_PAGES.contains(&path.as_str()));
+
+    if !cacheable {
+        return next.run(request).await;
+    }
+
+    // the journal list has no path parameters, which the extractor rejects
+    let (mut parts, body) = request.into_parts();
+    let params = parts
+        .extract::<Path<HashMap<String, String>>>()
+        .await
+        .map(|Path(params)| params)
+        .unwrap_or_default();
+    let request = Request::from_parts(parts, body);
+
+    let Some(version) = page_version(&state, &session, &params).await else {
+        return next.run(request).await;
+    };
+
+    // the page and its json answer the same url, so they're told apart
+    let representation = if wants_json(request.headers()) {
+        "json"
+    } else {
+        "html"
+    };
+    let tag = etag(
+        &request.uri().to_string(),
+        &format!("{version}-{representation}"),
+    );
+
+    // a tag made of hex digits and quotes is always a valid header value
+    let Ok(tag_header) = HeaderValue::from_str(&tag) else {
+        return next.run(request).await;
+    };
+
+    let not_modified = request
+        .headers()
+        .get(IF_NONE_MATCH)
+        .and_then(|value| value.to_str().ok())
+        .is_some_and(|value| matches(value, &tag));
+
+    let mut response = if not_modified {
+        StatusCode::NOT_MODIFIED.into_response()
+    } else {
+        next.run(request).await
+    };
+
+    if response.status() == StatusCode::OK || response.status() == StatusCode::NOT_MODIFIED {
+        let headers = response.headers_mut();
+        headers.insert(ETAG, tag_header);
+        // pages are per user, and must be revalidated because they can change at any time
+        headers.insert(CACHE_CONTROL, HeaderValue::from_static("private, no-cache"));
+        // the page is rendered in the language the request prefers, or as json if it asks
+        headers.insert(VARY, HeaderValue::from_static("accept, accept-language"));
+    }
+
+    response
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn if_none_match_uses_weak_comparison() {
+        let tag = etag("/journal", "user-1-10");
+
+        assert!(matches(&tag, &tag));
+        assert!(matches(
+            &format!("\"other\", {}", tag.trim_start_matches("W/")),
+            &tag
+        ));
+        assert!(matches("*", &tag));
+        assert!(!matches("W/\"other\"", &tag));
+        assert_ne!(tag, etag("/journal", "user-1-11"));
+        assert_ne!(tag, etag("/journal", "other-1-10"));
+        assert_ne!(tag, etag("/journal?err=x", "user-1-10"));
+    }
+}
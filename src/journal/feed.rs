@@ -0,0 +1,310 @@
+//! the journal's events as json, a page at a time, so sync tools can mirror a journal by asking
+//! for whatever came after the last event they saw instead of exporting it all again
+
+use crate::StateType;
+use crate::authn::user::UserId;
+use crate::journal::Permissions;
+use crate::journal::access::JournalAccess;
+use crate::journal::domain::JournalDomainEvent;
+use crate::monkesto_error::{ErrorBody, MonkestoError};
+use crate::time_provider::{TimeProvider, Timestamp};
+use axum::Json;
+use axum::extract::{Query, State};
+use axum::http::StatusCode;
+use axum::http::header::RETRY_AFTER;
+use axum::response::{IntoResponse, Response};
+use disintegrate_postgres::PgEventId;
+use lru::LruCache;
+use serde::{Deserialize, Serialize};
+use std::num::NonZeroUsize;
+use std::sync::{Arc, Mutex};
+
+/// how many events a page holds when the client doesn't say
+const DEFAULT_LIMIT: i64 = 100;
+/// the most events a page can hold
+const MAX_LIMIT: i64 = 500;
+
+/// how many pages each user can ask for in a window
+const REQUESTS_PER_WINDOW: u32 = 60;
+const WINDOW_SECONDS: i64 = 60;
+/// how many users' windows are kept. the least recent are forgotten first, which only ever
+/// lets a forgotten user ask for more
+const TRACKED_USERS: NonZeroUsize = NonZeroUsize::new(4096).expect("no users are tracked");
+
+#[derive(Deserialize)]
+pub struct FeedQuery {
+    /// the id of the last event the client has, or nothing to start from the beginning
+    after: Option<PgEventId>,
+    limit: Option<i64>,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct FeedEvent {
+    pub id: PgEventId,
+    pub event: JournalDomainEvent,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct FeedPage {
+    pub events: Vec<FeedEvent>,
+    /// what to pass as `after` for the next page. it stays put when there was nothing new
+    pub next: PgEventId,
+    /// whether more events were already waiting after this page
+    pub more: bool,
+}
+
+/// counts each user's requests in fixed windows, kept in memory on each instance
+#[derive(Clone)]
+pub struct FeedLimiter {
+    windows: Arc<Mutex<LruCache<UserId, (i64, u32)>>>,
+}
+
+impl Default for FeedLimiter {
+    fn default() -> Self {
+        Self {
+            windows: Arc::new(Mutex::new(LruCache::new(TRACKED_USERS))),
+        }
+    }
+}
+
+impl FeedLimiter {
+    /// counts a request, or says how many seconds are left until the user can ask again
+    fn check(&self, user_id: UserId, now: Timestamp) -> Result<(), i64> {
+        let window = now.timestamp().div_euclid(WINDOW_SECONDS);
+        // a panic can't leave a count half written, so a poisoned lock is still usable
+        let mut windows = self
+            .windows
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+
+        let (counted, requests) = windows.get_or_insert_mut(user_id, || (window, 0));
+        if *counted != window {
+            *counted = window;
+            *requests = 0;
+        }
+
+        if *requests >= REQUESTS_PER_WINDOW {
+            return Err((window + 1) * WINDOW_SECONDS - now.timestamp());
+        }
+
+        *requests += 1;
+        Ok(())
+    }
+}
+
+fn rate_limited(retry_after: i64) -> Response {
+    (
+        StatusCode::TOO_MANY_REQUESTS,
+        [(RETRY_AFTER, retry_after.to_string())],
+        Json(ErrorBody {
+            code: "rate_limited",
+            message: format!("Too many requests, try again in {retry_after} seconds"),
+            details: None,
+        }),
+    )
+        .into_response()
+}
+
+pub async fn event_feed(
+    State(state): State<StateType>,
+    access: JournalAccess,
+    Query(query): Query<FeedQuery>,
+) -> Result<Response, MonkestoError> {
+    if let Err(retry_after) = state
+        .feed_limiter
+        .check(access.actor, state.clock.get_time())
+    {
+        return Ok(rate_limited(retry_after));
+    }
+
+    let after = query.after.unwrap_or(0).max(0);
+    let limit = query.limit.unwrap_or(DEFAULT_LIMIT).clamp(1, MAX_LIMIT);
+
+    // one extra event says whether there's another page without asking for it
+    let mut page = state
+        .journal_service
+        .journal_events_page(access.journal.id, &access.authority(), after, limit + 1)
+        .await?;
+    let more = page.len() as i64 > limit;
+    page.truncate(limit as usize);
+
+    // the cursor moves past withheld events too, so they don't hold up the next page
+    let next = page.last().map_or(after, |envelope| envelope.event_id);
+    let owner = access.permissions.contains(Permissions::OWNER);
+    let events = page
+        .into_iter()
+        .filter(|envelope| owner || !envelope.event.owners_only())
+        .map(|envelope| FeedEvent {
+            id: envelope.event_id,
+            event: envelope.event,
+        })
+        .collect();
+
+    Ok(Json(FeedPage { events, next, more }).into_response())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::authority::{Actor, Authority};
+    use crate::journal::JournalId;
+    use crate::journal::webhook::WebhookId;
+    use crate::test_app::{TestApp, assert_accepted};
+    use crate::time_provider::DefaultTimeProvider;
+    use axum_test::TestServer;
+    use chrono::Duration;
+    use serde_json::Value;
+
+    #[test]
+    fn each_user_is_limited_until_the_next_window() {
+        let limiter = FeedLimiter::default();
+        let (user, other) = (UserId::new(), UserId::new());
+        let now = DefaultTimeProvider.get_time();
+        let start = now - Duration::seconds(now.timestamp().rem_euclid(WINDOW_SECONDS));
+
+        for _ in 0..REQUESTS_PER_WINDOW {
+            assert_eq!(limiter.check(user, start), Ok(()));
+        }
+        assert_eq!(
+            limiter.check(user, start + Duration::seconds(15)),
+            Err(WINDOW_SECONDS - 15)
+        );
+        assert_eq!(limiter.check(other, start), Ok(()));
+
+        assert_eq!(
+            limiter.check(user, start + Duration::seconds(WINDOW_SECONDS)),
+            Ok(())
+        );
+    }
+
+    #[tokio::test]
+    async fn the_event_feed_pages_through_the_history_in_order() {
+        let Some(app) = TestApp::spawn().await else {
+            return;
+        };
+
+        let user = app.signup("mirror").await;
+        let journal_id = user.create_journal(&app, "Mirrored").await;
+        for name in ["Cash", "Food"] {
+            assert_accepted(&user.create_account(journal_id, name).await);
+        }
+        let cash = app.account_id(journal_id, "Cash").await;
+        let food = app.account_id(journal_id, "Food").await;
+        assert_accepted(&user.post_transaction(journal_id, cash, food, "5.00").await);
+
+        // journal, two accounts and a transaction, read two at a time
+        let mut after = 0;
+        let mut ids = Vec::new();
+        loop {
+            let page = user
+                .client
+                .get(&format!(
+                    "/api/v1/journal/{journal_id}/events?after={after}&limit=2"
+                ))
+                .await
+                .json::<Value>();
+            let events = page["events"].as_array().expect("the events aren't a list");
+            assert!(events.len() <= 2);
+            ids.extend(events.iter().filter_map(|event| event["id"].as_i64()));
+
+            after = page["next"].as_i64().expect("there's no cursor");
+            if page["more"] == false {
+                break;
+            }
+        }
+
+        assert!(ids.len() >= 4);
+        assert!(ids.windows(2).all(|pair| pair[0] < pair[1]));
+        assert_eq!(after, *ids.last().expect("there are no events"));
+
+        // nothing new leaves the cursor where it was
+        let caught_up = user
+            .client
+            .get(&format!(
+                "/api/v1/journal/{journal_id}/events?after={after}"
+            ))
+            .await
+            .json::<Value>();
+        assert!(caught_up["events"].as_array().is_some_and(Vec::is_empty));
+        assert_eq!(caught_up["next"], after);
+
+        let outsider = app.signup("outsider").await;
+        outsider
+            .client
+            .get(&format!("/api/v1/journal/{journal_id}/events"))
+            .await
+            .assert_status(StatusCode::NOT_FOUND);
+    }
+
+    #[tokio::test]
+    async fn readers_dont_see_the_owners_webhooks_in_the_feed() {
+        let Some(app) = TestApp::spawn().await else {
+            return;
+        };
+
+        let owner = app.signup("owner").await;
+        let reader = app.signup("reader").await;
+        let journal_id = owner.create_journal(&app, "Hooked").await;
+        assert_accepted(&owner.invite(journal_id, &reader, Permissions::READ).await);
+
+        let url = "https://8.8.8.8/services/T000/B000/credential";
+        let event_id = app
+            .state
+            .journal_service
+            .add_webhook(
+                WebhookId::new(),
+                journal_id,
+                url.to_string(),
+                "signing secret".to_string(),
+                Authority::Direct(Actor::User(owner.id)),
+                app.state.clock.get_time(),
+            )
+            .await
+            .expect("failed to add the webhook");
+        app.state.journal_service.wait_for(event_id).await;
+        assert_accepted(
+            &reader
+                .client
+                .post(&format!("/journal/{journal_id}/pin"))
+                .await,
+        );
+
+        let kinds = |events: &[Value]| -> Vec<String> {
+            events
+                .iter()
+                .filter_map(|event| event["event"].as_object())
+                .flat_map(|event| event.keys().cloned())
+                .collect()
+        };
+
+        let read = read_feed(&reader.client, journal_id).await;
+        assert!(kinds(&read).contains(&"MemberAdded".to_string()));
+        assert!(!kinds(&read).contains(&"WebhookAdded".to_string()));
+        assert!(!kinds(&read).contains(&"JournalPinned".to_string()));
+        assert!(!Value::from(read).to_string().contains(url));
+
+        let owned = kinds(&read_feed(&owner.client, journal_id).await);
+        assert!(owned.contains(&"WebhookAdded".to_string()));
+        assert!(owned.contains(&"JournalPinned".to_string()));
+    }
+
+    /// every event the client is sent, a page at a time
+    async fn read_feed(client: &TestServer, journal_id: JournalId) -> Vec<Value> {
+        let mut after = 0;
+        let mut events = Vec::new();
+        loop {
+            let page = client
+                .get(&format!(
+                    "/api/v1/journal/{journal_id}/events?after={after}&limit=2"
+                ))
+                .await
+                .json::<Value>();
+            events.extend(page["events"].as_array().cloned().unwrap_or_default());
+
+            after = page["next"].as_i64().expect("there's no cursor");
+            if page["more"] == false {
+                return events;
+            }
+        }
+    }
+}
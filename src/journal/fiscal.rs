@@ -0,0 +1,206 @@
+use crate::journal::JournalError;
+use chrono::{Datelike, Days, Months, NaiveDate};
+use serde::Deserialize;
+use serde::Serialize;
+use sqlx::encode::IsNull;
+use sqlx::error::BoxDynError;
+use sqlx::{Database, Decode, Encode, Postgres, Type};
+use std::fmt::Display;
+use std::fmt::Formatter;
+use std::str::FromStr;
+
+/// the month a journal's fiscal year begins in, from 1 for January to 12 for December
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FiscalYearStart(u32);
+
+impl Default for FiscalYearStart {
+    fn default() -> Self {
+        Self(1)
+    }
+}
+
+impl FiscalYearStart {
+    pub fn try_new(month: u32) -> Option<Self> {
+        (1..=12).contains(&month).then_some(Self(month))
+    }
+
+    pub fn month(&self) -> u32 {
+        self.0
+    }
+
+    pub fn all() -> impl Iterator<Item = Self> {
+        (1..=12).map(Self)
+    }
+}
+
+impl Display for FiscalYearStart {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let month = chrono::Month::try_from(self.0 as u8).map_err(|_| std::fmt::Error)?;
+        write!(f, "{}", month.name())
+    }
+}
+
+impl FromStr for FiscalYearStart {
+    type Err = JournalError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        s.parse()
+            .ok()
+            .and_then(Self::try_new)
+            .ok_or_else(|| JournalError::InvalidFiscalYearStart(s.to_string()))
+    }
+}
+
+impl Type<Postgres> for FiscalYearStart {
+    fn type_info() -> <Postgres as Database>::TypeInfo {
+        <i16 as Type<Postgres>>::type_info()
+    }
+}
+
+impl<'q> Encode<'q, Postgres> for FiscalYearStart {
+    fn encode_by_ref(
+        &self,
+        buf: &mut <Postgres as Database>::ArgumentBuffer<'q>,
+    ) -> Result<IsNull, BoxDynError> {
+        <i16 as Encode<Postgres>>::encode(self.0 as i16, buf)
+    }
+}
+
+impl<'r> Decode<'r, Postgres> for FiscalYearStart {
+    fn decode(value: <Postgres as Database>::ValueRef<'r>) -> Result<Self, BoxDynError> {
+        let month = <i16 as Decode<Postgres>>::decode(value)?;
+        Ok(Self::try_new(month as u32)
+            .ok_or_else(|| JournalError::InvalidFiscalYearStart(month.to_string()))?)
+    }
+}
+
+/// the first day of the accounting period that the date falls in
+pub fn period_start(date: NaiveDate) -> NaiveDate {
+    date - Days::new(date.day0() as u64)
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PeriodKind {
+    /// a calendar month, which is the length of every accounting period
+    Month,
+    FiscalYear,
+}
+
+/// a range of days that reports are totalled over. the arithmetic returns `None` instead
+/// of panicking when a date submitted by the user is near the edge of what chrono supports
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Period {
+    pub kind: PeriodKind,
+    pub first_day: NaiveDate,
+}
+
+impl Period {
+    pub fn containing(
+        kind: PeriodKind,
+        date: NaiveDate,
+        fiscal_year_start: FiscalYearStart,
+    ) -> Option<Self> {
+        let first_of_month = period_start(date);
+
+        let first_day = match kind {
+            PeriodKind::Month => first_of_month,
+            PeriodKind::FiscalYear => {
+                let start = first_of_month.with_month(fiscal_year_start.month())?;
+                if start > date {
+                    start.checked_sub_months(Months::new(12))?
+                } else {
+                    start
+                }
+            }
+        };
+
+        Some(Self { kind, first_day })
+    }
+
+    /// the period that covers exactly the given days, if there is one
+    pub fn matching(
+        from: NaiveDate,
+        to: NaiveDate,
+        fiscal_year_start: FiscalYearStart,
+    ) -> Option<Self> {
+        [PeriodKind::Month, PeriodKind::FiscalYear]
+            .into_iter()
+            .filter_map(|kind| Self::containing(kind, from, fiscal_year_start))
+            .find(|period| period.first_day == from && period.last_day() == Some(to))
+    }
+
+    fn length(&self) -> Months {
+        match self.kind {
+            PeriodKind::Month => Months::new(1),
+            PeriodKind::FiscalYear => Months::new(12),
+        }
+    }
+
+    pub fn last_day(&self) -> Option<NaiveDate> {
+        self.first_day
+            .checked_add_months(self.length())?
+            .checked_sub_days(Days::new(1))
+    }
+
+    pub fn previous(&self) -> Option<Self> {
+        Some(Self {
+            kind: self.kind,
+            first_day: self.first_day.checked_sub_months(self.length())?,
+        })
+    }
+
+    pub fn next(&self) -> Option<Self> {
+        Some(Self {
+            kind: self.kind,
+            first_day: self.first_day.checked_add_months(self.length())?,
+        })
+    }
+
+    /// fiscal years are named after the calendar year they end in
+    pub fn label(&self) -> String {
+        match self.kind {
+            PeriodKind::Month => self.first_day.format("%B %Y").to_string(),
+            PeriodKind::FiscalYear => match self.last_day() {
+                Some(last_day) => format!("FY {}", last_day.year()),
+                None => format!("FY starting {}", self.first_day.format("%B %Y")),
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(year: i32, month: u32, day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(year, month, day).unwrap()
+    }
+
+    #[test]
+    fn fiscal_years_span_the_calendar_year_boundary() {
+        let july = FiscalYearStart::try_new(7).unwrap();
+
+        let before = Period::containing(PeriodKind::FiscalYear, date(2026, 3, 15), july).unwrap();
+        assert_eq!(before.first_day, date(2025, 7, 1));
+        assert_eq!(before.last_day(), Some(date(2026, 6, 30)));
+        assert_eq!(before.label(), "FY 2026");
+
+        let after = Period::containing(PeriodKind::FiscalYear, date(2026, 7, 1), july).unwrap();
+        assert_eq!(Some(after), before.next());
+        assert_eq!(
+            Period::matching(date(2026, 7, 1), date(2027, 6, 30), july),
+            Some(after)
+        );
+
+        let february = Period::containing(PeriodKind::Month, date(2028, 2, 10), july).unwrap();
+        assert_eq!(february.last_day(), Some(date(2028, 2, 29)));
+        assert_eq!(
+            Period::matching(date(2028, 2, 1), date(2028, 2, 29), july),
+            Some(february)
+        );
+        assert_eq!(
+            Period::matching(date(2028, 2, 2), date(2028, 2, 29), july),
+            None
+        );
+    }
+}
@@ -0,0 +1,365 @@
+use crate::BackendType;
+use crate::StateType;
+use crate::authn::get_user;
+use crate::authn::user::UserId;
+use crate::authority::Actor;
+use crate::authority::Authority;
+use crate::email::Email;
+use crate::journal::access::{JournalAccess, check_access};
+use crate::journal::account::AccountId;
+use crate::journal::domain::JournalDomainEvent;
+use crate::journal::layout::journal_layout;
+use crate::journal::revert::last_reversion;
+use crate::journal::service::JournalEventEnvelope;
+use crate::journal::{JournalId, Permissions};
+use crate::monkesto_error::MonkestoResult;
+use crate::name::Name;
+use crate::time_provider::Timestamp;
+use axum::extract::State;
+use axum::response::Redirect;
+use axum_login::AuthSession;
+use disintegrate_postgres::PgEventId;
+use futures::TryStreamExt;
+use maud::Markup;
+use maud::html;
+use std::collections::{HashMap, HashSet};
+
+/// a line of the journal's audit log
+pub struct HistoryEntry {
+    pub event_id: PgEventId,
+    pub actor: Actor,
+    pub description: String,
+    pub timestamp: Timestamp,
+}
+
+fn email_or_id(emails: &HashMap<UserId, Email>, user_id: UserId) -> String {
+    emails
+        .get(&user_id)
+        .map(|email| email.to_string())
+        .unwrap_or_else(|| user_id.to_string())
+}
+
+/// describes each event in order. accounts are only named when they're created or renamed,
+/// so the names are tracked across the history to describe the events that name the account by id
+pub fn describe_history(
+    events: &[JournalEventEnvelope],
+    emails: &HashMap<UserId, Email>,
+) -> Vec<HistoryEntry> {
+    let mut account_names: HashMap<AccountId, Name> = HashMap::new();
+
+    events
+        .iter()
+        .map(|envelope| {
+            let event = &envelope.event;
+
+            let description = match event {
+                JournalDomainEvent::JournalCreated { name, .. } => {
+                    format!("Created the journal {name}")
+                }
+                JournalDomainEvent::JournalDeleted { .. } => "Deleted the journal".to_string(),
+                JournalDomainEvent::JournalSettingsUpdated {
+                    fiscal_year_start,
+                    default_cash_account,
+                    default_expense_account,
+                    appearance,
+                    ..
+                } => {
+                    let default = |account_id: &Option<AccountId>| match account_id {
+                        Some(account_id) => account_names
+                            .get(account_id)
+                            .map_or("an account".to_string(), |name| name.to_string()),
+                        None => "none".to_string(),
+                    };
+                    let icon = match &appearance.icon {
+                        Some(icon) => format!(" with the icon {icon}"),
+                        None => String::new(),
+                    };
+                    format!(
+                        "Set the fiscal year to start in {fiscal_year_start}, the default cash account to {}, the default expense account to {} and the color to {}{icon}",
+                        default(default_cash_account),
+                        default(default_expense_account),
+                        appearance.color
+                    )
+                }
+                JournalDomainEvent::JournalArchived { .. } => "Archived the journal".to_string(),
+                JournalDomainEvent::JournalUnarchived { .. } => {
+                    "Unarchived the journal".to_string()
+                }
+                JournalDomainEvent::JournalPinned { .. } => "Pinned the journal".to_string(),
+                JournalDomainEvent::JournalUnpinned { .. } => "Unpinned the journal".to_string(),
+                JournalDomainEvent::MemberAdded { user_id, .. } => {
+                    format!("Invited {}", email_or_id(emails, *user_id))
+                }
+                JournalDomainEvent::MemberPermissionsUpdated { user_id, .. } => {
+                    format!(
+                        "Changed the permissions of {}",
+                        email_or_id(emails, *user_id)
+                    )
+                }
+                JournalDomainEvent::MemberRemoved { user_id, .. } => {
+                    format!("Removed {}", email_or_id(emails, *user_id))
+                }
+                JournalDomainEvent::AccountCreated {
+                    account_id, name, ..
+                } => {
+                    account_names.insert(*account_id, name.clone());
+                    format!("Created the account {name}")
+                }
+                JournalDomainEvent::AccountRenamed {
+                    account_id,
+                    new_name,
+                    ..
+                } => {
+                    let description = match account_names.get(account_id) {
+                        Some(old_name) => format!("Renamed the account {old_name} to {new_name}"),
+                        None => format!("Renamed an account to {new_name}"),
+                    };
+                    account_names.insert(*account_id, new_name.clone());
+                    description
+                }
+                JournalDomainEvent::AccountDeleted { account_id, .. } => {
+                    match account_names.get(account_id) {
+                        Some(name) => format!("Deleted the account {name}"),
+                        None => "Deleted an account".to_string(),
+                    }
+                }
+                JournalDomainEvent::AccountsArranged { .. } => {
+                    "Rearranged the accounts".to_string()
+                }
+                JournalDomainEvent::TransactionCreated {
+                    effective_date,
+                    reverses: Some(_),
+                    ..
+                } => format!("Reversed a transaction, dated {effective_date}"),
+                JournalDomainEvent::TransactionCreated {
+                    effective_date,
+                    transfer: Some(_),
+                    ..
+                } => format!("Recorded a transfer with another journal, dated {effective_date}"),
+                JournalDomainEvent::TransactionCreated { effective_date, .. } => {
+                    format!("Recorded a transaction dated {effective_date}")
+                }
+                JournalDomainEvent::TransactionDeleted { .. } => {
+                    "Deleted a transaction".to_string()
+                }
+                JournalDomainEvent::TransactionSubmitted { effective_date, .. } => {
+                    format!("Submitted a transaction dated {effective_date} for approval")
+                }
+                JournalDomainEvent::TransactionApproved { .. } => {
+                    "Approved a transaction".to_string()
+                }
+                JournalDomainEvent::TransactionRejected { .. } => {
+                    "Rejected a transaction".to_string()
+                }
+                JournalDomainEvent::WebhookAdded { .. } => "Added a webhook".to_string(),
+                JournalDomainEvent::WebhookRemoved { .. } => "Removed a webhook".to_string(),
+                JournalDomainEvent::ImportRuleAdded { account_id, .. } => {
+                    match account_names.get(account_id) {
+                        Some(name) => format!("Added an import rule for {name}"),
+                        None => "Added an import rule".to_string(),
+                    }
+                }
+                JournalDomainEvent::ImportRuleRemoved { .. } => {
+                    "Removed an import rule".to_string()
+                }
+                JournalDomainEvent::BankFeedLinked { account_id, .. } => {
+                    match account_names.get(account_id) {
+                        Some(name) => format!("Linked a bank feed to {name}"),
+                        None => "Linked a bank feed".to_string(),
+                    }
+                }
+                JournalDomainEvent::BankFeedUnlinked { .. } => "Unlinked a bank feed".to_string(),
+                JournalDomainEvent::TemplateSaved { name, .. } => {
+                    format!("Saved the transaction template {name}")
+                }
+                JournalDomainEvent::TemplateDeleted { .. } => {
+                    "Deleted a transaction template".to_string()
+                }
+                JournalDomainEvent::CommentAdded { parent_id, .. } => match parent_id {
+                    Some(_) => "Replied to a comment on a transaction".to_string(),
+                    None => "Commented on a transaction".to_string(),
+                },
+            };
+
+            HistoryEntry {
+                event_id: envelope.event_id,
+                actor: event.authority().actor().clone(),
+                description,
+                timestamp: event.timestamp(),
+            }
+        })
+        .collect()
+}
+
+/// the journal's history, newest first, with the emails of everyone it mentions and the id of
+/// the change that can be reverted, if any
+async fn journal_history(
+    state: &StateType,
+    journal_id: JournalId,
+    authority: &Authority,
+) -> MonkestoResult<(Vec<HistoryEntry>, HashMap<UserId, Email>, Option<PgEventId>)> {
+    let events: Vec<JournalEventEnvelope> = state
+        .journal_service
+        .stream_journal_events(journal_id, authority)
+        .await?
+        .try_collect()
+        .await?;
+
+    // everyone the history mentions, fetched in one query
+    let user_ids: Vec<UserId> = events
+        .iter()
+        .flat_map(|envelope| {
+            let subject = match &envelope.event {
+                JournalDomainEvent::MemberAdded { user_id, .. }
+                | JournalDomainEvent::MemberPermissionsUpdated { user_id, .. }
+                | JournalDomainEvent::MemberRemoved { user_id, .. } => Some(*user_id),
+                _ => None,
+            };
+            [envelope.event.authority().user_id(), subject]
+        })
+        .flatten()
+        .collect::<HashSet<UserId>>()
+        .into_iter()
+        .collect();
+
+    let emails: HashMap<UserId, Email> = state
+        .authn_service
+        .fetch_users(user_ids.as_slice())
+        .await?
+        .into_iter()
+        .map(|user| (user.id, user.email))
+        .collect();
+
+    let revertible = last_reversion(&events).map(|(event_id, _)| event_id);
+
+    // newest first
+    let mut history = describe_history(&events, &emails);
+    history.reverse();
+
+    Ok((history, emails, revertible))
+}
+
+pub async fn history_page(
+    State(state): State<StateType>,
+    session: AuthSession<BackendType>,
+    access: JournalAccess,
+) -> Result<Markup, Redirect> {
+    let user = get_user(session)?;
+    let history_res = journal_history(&state, access.journal.id, &access.authority()).await;
+
+    // only owners can revert a change, since that can mean changing anything they can
+    let can_revert =
+        check_access(access.journal.id, access.permissions, Permissions::OWNER).is_ok();
+
+    let content = html! {
+        div class="flex flex-col gap-6 mx-auto w-full max-w-4xl" {
+            @match history_res {
+                Ok((history, emails, revertible)) => {
+                    @if history.is_empty() {
+                        p class="text-sm text-gray-500 dark:text-gray-400" {
+                            "Nothing has happened in this journal yet."
+                        }
+                    }
+
+                    ul class="divide-y divide-gray-200 dark:divide-gray-700 bg-white dark:bg-gray-800 border border-gray-200 dark:border-gray-700 rounded-xl" {
+                        @for entry in history {
+                            li id=(format!("event-{}", entry.event_id)) class="p-4 flex justify-between items-start gap-4" {
+                                div class="space-y-1" {
+                                    p class="text-sm text-gray-900 dark:text-white" {
+                                        (entry.description)
+                                    }
+                                    p class="text-xs text-gray-500 dark:text-gray-400" {
+                                        @match entry.actor {
+                                            Actor::User(user_id) => (email_or_id(&emails, user_id)),
+                                            Actor::System => "system",
+                                            Actor::Anonymous => "anonymous",
+                                            Actor::Deleted => "deleted user",
+                                        }
+                                    }
+                                }
+                                div class="flex flex-col items-end gap-2" {
+                                    span class="text-xs text-gray-500 dark:text-gray-400 whitespace-nowrap" {
+                                        (user.timezone().format(entry.timestamp))
+                                    }
+                                    @if can_revert && revertible == Some(entry.event_id) {
+                                        form method="post" action=(format!("/journal/{}/revert/{}", access.journal.id, entry.event_id)) {
+                                            button
+                                            type="submit"
+                                            title="Put back what this change replaced"
+                                            class="px-3 py-1 text-xs font-medium bg-white dark:bg-gray-700 border border-gray-300 dark:border-gray-600 rounded-md text-gray-700 dark:text-gray-200 hover:bg-gray-100 dark:hover:bg-gray-600" {
+                                                "Revert"
+                                            }
+                                        }
+                                    }
+                                }
+                            }
+                        }
+                    }
+                },
+                Err(e) => {
+                    p class="text-gray-500 dark:text-gray-400" {
+                        "failed to read the journal's history: " (e)
+                    }
+                }
+            }
+        }
+    };
+
+    Ok(journal_layout(&access.journal, content))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::time_provider::{DefaultTimeProvider, TimeProvider};
+
+    #[test]
+    fn deleted_accounts_are_described_by_their_latest_name() {
+        let journal_id = JournalId::new();
+        let account_id = AccountId::new();
+        let authority = Authority::Direct(Actor::System);
+        let timestamp = DefaultTimeProvider.get_time();
+
+        let events = [
+            JournalDomainEvent::AccountCreated {
+                account_id,
+                journal_id,
+                name: Name::try_new("Cash".to_string()).unwrap(),
+                authority: authority.clone(),
+                timestamp,
+            },
+            JournalDomainEvent::AccountRenamed {
+                account_id,
+                new_name: Name::try_new("Checking".to_string()).unwrap(),
+                authority: authority.clone(),
+                timestamp,
+            },
+            JournalDomainEvent::AccountDeleted {
+                account_id,
+                authority,
+                timestamp,
+            },
+        ]
+        .into_iter()
+        .enumerate()
+        .map(|(i, event)| JournalEventEnvelope {
+            event_id: i as i64 + 1,
+            event,
+        })
+        .collect::<Vec<_>>();
+
+        let descriptions: Vec<String> = describe_history(&events, &HashMap::new())
+            .into_iter()
+            .map(|entry| entry.description)
+            .collect();
+
+        assert_eq!(
+            descriptions,
+            [
+                "Created the account Cash",
+                "Renamed the account Cash to Checking",
+                "Deleted the account Checking",
+            ]
+        );
+    }
+}
@@ -0,0 +1,137 @@
+use crate::journal::service::{AccountState, JournalState, TransactionState};
+use crate::journal::transaction::format_voucher_number;
+use serde::{Deserialize, Serialize};
+
+/// a journal as the journal page sends it to clients that ask for json, with the same fields
+/// the graphql api has. these are also what the api client reads responses into
+#[derive(Serialize, Deserialize)]
+pub struct JournalJson {
+    pub id: String,
+    pub name: String,
+    /// the month the fiscal year begins in, from 1 for January to 12 for December
+    pub fiscal_year_start: u32,
+    pub archived: bool,
+    pub color: String,
+    pub icon: Option<String>,
+    pub accounts: Vec<AccountJson>,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct AccountJson {
+    pub id: String,
+    pub name: String,
+    /// in cents, where credits are positive
+    pub balance: i64,
+    /// the heading the account is listed under
+    pub group: Option<String>,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct TransactionJson {
+    pub id: String,
+    pub number: String,
+    /// formatted as YYYY-MM-DD
+    pub effective_date: String,
+    pub reverses: Option<String>,
+    pub payee: Option<String>,
+    pub entries: Vec<EntryJson>,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct EntryJson {
+    pub account_id: String,
+    /// in cents
+    pub amount: u64,
+    /// Dr or Cr
+    pub entry_type: String,
+    pub memo: Option<String>,
+}
+
+/// the ids of the transactions a bulk submission recorded, in the order they were submitted
+#[derive(Serialize, Deserialize)]
+pub struct CreatedTransactionsJson {
+    pub transaction_ids: Vec<String>,
+}
+
+impl JournalJson {
+    pub fn new(journal: &JournalState, accounts: Vec<AccountState>) -> Self {
+        Self {
+            id: journal.id.to_string(),
+            name: journal.name.to_string(),
+            fiscal_year_start: journal.fiscal_year_start.month(),
+            archived: journal.archived,
+            color: journal.appearance.color.to_string(),
+            icon: journal.appearance.icon.as_ref().map(ToString::to_string),
+            accounts: accounts.iter().map(AccountJson::from).collect(),
+        }
+    }
+}
+
+impl From<&AccountState> for AccountJson {
+    fn from(account: &AccountState) -> Self {
+        Self {
+            id: account.id.to_string(),
+            name: account.name.to_string(),
+            balance: account.balance,
+            group: account.group.as_ref().map(ToString::to_string),
+        }
+    }
+}
+
+impl From<&TransactionState> for TransactionJson {
+    fn from(transaction: &TransactionState) -> Self {
+        Self {
+            id: transaction.id.to_string(),
+            number: format_voucher_number(transaction.number),
+            effective_date: transaction.effective_date.to_string(),
+            reverses: transaction.reverses.map(|id| id.to_string()),
+            payee: transaction.payee.as_ref().map(ToString::to_string),
+            entries: transaction
+                .entries
+                .iter()
+                .map(|entry| EntryJson {
+                    account_id: entry.account_id.to_string(),
+                    amount: entry.amount,
+                    entry_type: entry.entry_type.to_string(),
+                    memo: entry.memo.clone(),
+                })
+                .collect(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::test_app::{TestApp, assert_accepted};
+    use serde_json::Value;
+
+    #[tokio::test]
+    async fn journal_pages_answer_json_clients_with_their_data() {
+        let Some(app) = TestApp::spawn().await else {
+            return;
+        };
+
+        let user = app.signup("scraper").await;
+        let journal_id = user.create_journal(&app, "Scraped").await;
+        assert_accepted(&user.create_account(journal_id, "Cash").await);
+        assert_accepted(&user.create_account(journal_id, "Food").await);
+        let cash = app.account_id(journal_id, "Cash").await;
+        let food = app.account_id(journal_id, "Food").await;
+        assert_accepted(&user.post_transaction(journal_id, cash, food, "12.50").await);
+
+        let journal = user
+            .client
+            .get(&format!("/journal/{journal_id}"))
+            .await
+            .json::<Value>();
+        assert_eq!(journal["name"], "Scraped");
+        assert_eq!(journal["accounts"].as_array().map(Vec::len), Some(2));
+
+        let transactions = user
+            .client
+            .get(&format!("/journal/{journal_id}/transaction"))
+            .await
+            .json::<Value>();
+        assert_eq!(transactions[0]["entries"][0]["amount"], 1250);
+    }
+}
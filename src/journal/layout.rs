@@ -1,32 +1,63 @@
 use crate::authn::layout as app_layout;
+use crate::i18n::t;
+use crate::journal::JournalId;
+use crate::journal::appearance::journal_badge;
+use crate::journal::service::JournalState;
 use maud::Markup;
 use maud::html;
+use std::str::FromStr;
 
 pub fn layout(
     page_title: Option<&str>,
     show_switch_link: bool,
     journal_id: Option<&str>,
     content: Markup,
+) -> Markup {
+    titled_layout(page_title, None, show_switch_link, journal_id, content)
+}
+
+/// the layout for a page of the journal, headed by its badge and name
+pub fn journal_layout(journal: &JournalState, content: Markup) -> Markup {
+    titled_layout(
+        Some(journal.name.as_ref()),
+        Some(journal_badge(&journal.name, &journal.appearance)),
+        true,
+        Some(&journal.id.to_string()),
+        content,
+    )
+}
+
+fn titled_layout(
+    page_title: Option<&str>,
+    badge: Option<Markup>,
+    show_switch_link: bool,
+    journal_id: Option<&str>,
+    content: Markup,
 ) -> Markup {
     let nav_title = match (page_title, show_switch_link, journal_id) {
         (Some(title), switch_link, journal_id_opt) => Some(html! {
-            div class="flex flex-col items-end justify-center" {
-                @if let Some(id) = journal_id_opt {
-                    a
-                        href=(format!("/journal/{}", id))
-                        class="text-sm font-medium text-gray-700 dark:text-gray-300 hover:text-gray-900 dark:hover:text-white" {
-                        (title)
-                    }
-                } @else {
-                    span class="text-sm font-medium text-gray-700 dark:text-gray-300" {
-                        (title)
-                    }
+            div class="flex items-center gap-3" {
+                @if let Some(badge) = badge {
+                    (badge)
                 }
-                @if switch_link {
-                    a
-                        href="/journal"
-                        class="text-xs text-gray-500 hover:text-gray-700 dark:text-gray-400 dark:hover:text-gray-200" {
-                        "Switch"
+                div class="flex flex-col items-end justify-center" {
+                    @if let Some(id) = journal_id_opt {
+                        a
+                            href=(format!("/journal/{}", id))
+                            class="text-sm font-medium text-gray-700 dark:text-gray-300 hover:text-gray-900 dark:hover:text-white" {
+                            (title)
+                        }
+                    } @else {
+                        span class="text-sm font-medium text-gray-700 dark:text-gray-300" {
+                            (title)
+                        }
+                    }
+                    @if switch_link {
+                        a
+                            href="/journal"
+                            class="text-xs text-gray-500 hover:text-gray-700 dark:text-gray-400 dark:hover:text-gray-200" {
+                            (t("nav.switch"))
+                        }
                     }
                 }
             }
@@ -34,5 +65,14 @@ pub fn layout(
         _ => None,
     };
 
+    // the journal's shortcuts open pages of the journal named here. the id comes from the
+    // url, so it's only written out once it's known to be valid
+    let content = html! {
+        (content)
+        @if let Some(id) = journal_id.and_then(|id| JournalId::from_str(id).ok()) {
+            div hidden data-journal-id=(id) {}
+        }
+    };
+
     app_layout(nav_title, content)
 }
@@ -1,7 +1,7 @@
 use crate::authn::UserId;
 use crate::authority::Authority;
 use crate::journal::domain::{JournalDomainEvent, MemberEvent};
-use crate::journal::{Journal, JournalError, JournalId, Permissions, validate_permissions};
+use crate::journal::{Journal, JournalError, JournalId, Permissions, check_permissions};
 use crate::status::Status;
 use crate::time_provider::Timestamp;
 use axum_test::expect_json::__private::serde_trampoline::{Deserialize, Serialize};
@@ -136,16 +136,12 @@ impl Decision for AddJournalMember {
             return Err(JournalError::UserAlreadyHasAccess(self.user_id));
         }
 
-        if !validate_permissions(
+        check_permissions(
             actor,
             &self.authority,
-            journal.owner,
+            journal,
             Permissions::INVITE.union(self.permissions),
-        ) {
-            return Err(JournalError::Permissions(
-                Permissions::INVITE.union(self.permissions),
-            ));
-        }
+        )?;
 
         Ok(vec![JournalDomainEvent::MemberAdded {
             journal_id: self.journal_id,
@@ -211,16 +207,12 @@ impl Decision for UpdateJournalMember {
             return Err(JournalError::UserDoesntHaveAccess(self.user_id));
         }
 
-        if !validate_permissions(
+        check_permissions(
             actor,
             &self.authority,
-            journal.owner,
+            journal,
             Permissions::OWNER.union(self.permissions),
-        ) {
-            return Err(JournalError::Permissions(
-                Permissions::OWNER.union(self.permissions),
-            ));
-        }
+        )?;
 
         Ok(vec![JournalDomainEvent::MemberPermissionsUpdated {
             journal_id: self.journal_id,
@@ -283,9 +275,7 @@ impl Decision for RemoveJournalMember {
             return Err(JournalError::UserDoesntHaveAccess(self.user_id));
         }
 
-        if !validate_permissions(actor, &self.authority, journal.owner, Permissions::OWNER) {
-            return Err(JournalError::Permissions(Permissions::OWNER));
-        }
+        check_permissions(actor, &self.authority, journal, Permissions::OWNER)?;
 
         Ok(vec![JournalDomainEvent::MemberRemoved {
             journal_id: self.journal_id,
@@ -295,3 +285,120 @@ impl Decision for RemoveJournalMember {
         }])
     }
 }
+
+/// removes the acting user from a journal they were invited to
+pub struct LeaveJournal {
+    journal_id: JournalId,
+    authority: Authority,
+    timestamp: Timestamp,
+}
+
+impl LeaveJournal {
+    pub(crate) fn new(journal_id: JournalId, authority: Authority, timestamp: Timestamp) -> Self {
+        Self {
+            journal_id,
+            authority,
+            timestamp,
+        }
+    }
+}
+
+impl Decision for LeaveJournal {
+    type Event = JournalDomainEvent;
+    type StateQuery = (Journal, JournalMember);
+    type Error = JournalError;
+
+    fn state_query(&self) -> Self::StateQuery {
+        (
+            Journal::new(self.journal_id),
+            JournalMember::new(
+                self.journal_id,
+                self.authority.user_id().unwrap_or_default(),
+            ),
+        )
+    }
+
+    fn process(
+        &self,
+        (journal, member): &Self::StateQuery,
+    ) -> Result<Vec<Self::Event>, Self::Error> {
+        if !journal.status.valid() {
+            return Err(JournalError::InvalidJournal(self.journal_id));
+        }
+
+        let user_id = self.authority.user_id().unwrap_or_default();
+
+        // the journal would be left without anyone who can manage it
+        if journal.owner == user_id {
+            return Err(JournalError::OwnerCannotLeave(self.journal_id));
+        }
+
+        if !member.status.valid() {
+            return Err(JournalError::UserDoesntHaveAccess(user_id));
+        }
+
+        Ok(vec![JournalDomainEvent::MemberRemoved {
+            journal_id: self.journal_id,
+            user_id,
+            authority: self.authority.clone(),
+            timestamp: self.timestamp,
+        }])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::authority::Actor;
+    use chrono::Utc;
+
+    fn journal(owner: UserId) -> Journal {
+        let mut journal = Journal::new(JournalId::new());
+        journal.owner = owner;
+        journal.status = Status::Valid;
+        journal
+    }
+
+    fn member(journal: &Journal, user_id: UserId) -> JournalMember {
+        let mut member = JournalMember::new(journal.journal_id, user_id);
+        member.permissions = Permissions::READ;
+        member.status = Status::Valid;
+        member
+    }
+
+    #[test]
+    fn members_can_leave() {
+        let user_id = UserId::new();
+        let journal = journal(UserId::new());
+        let decision = LeaveJournal::new(
+            journal.journal_id,
+            Authority::Direct(Actor::User(user_id)),
+            Utc::now(),
+        );
+
+        let events = decision
+            .process(&(journal.clone(), member(&journal, user_id)))
+            .expect("the member couldn't leave");
+
+        assert!(matches!(
+            events.as_slice(),
+            [JournalDomainEvent::MemberRemoved { user_id: removed, .. }] if *removed == user_id
+        ));
+    }
+
+    #[test]
+    fn the_owner_cannot_leave() {
+        let owner = UserId::new();
+        let journal = journal(owner);
+        let decision = LeaveJournal::new(
+            journal.journal_id,
+            Authority::Direct(Actor::User(owner)),
+            Utc::now(),
+        );
+
+        assert_eq!(
+            decision.process(&(journal.clone(), member(&journal, owner))),
+            Err(JournalError::OwnerCannotLeave(journal.journal_id))
+        );
+    }
+}
@@ -1,13 +1,47 @@
+pub mod access;
 pub mod account;
+pub mod appearance;
+pub mod backup;
+pub mod bankfeed;
+pub mod cache;
 pub mod commands;
+pub mod comment;
+pub mod consistency;
+pub mod dashboard;
 pub mod domain;
+pub mod etag;
+pub mod feed;
+pub mod fiscal;
+pub mod history;
+pub mod json;
 pub mod layout;
 pub mod member;
+pub mod networth;
+pub mod notification;
+pub mod payee;
+pub mod payload;
 pub mod person;
+pub mod plaintext;
+pub mod print;
+pub mod provenance;
+pub mod quota;
+pub mod rebuild;
+pub mod report;
+pub mod restore;
+pub mod retention;
+pub mod revert;
+pub mod rule;
+pub mod search;
 pub mod service;
+pub mod statement;
+pub mod stats;
 pub mod store;
+pub mod template;
 pub mod transaction;
+pub mod transfer;
+pub mod undo;
 pub mod views;
+pub mod webhook;
 
 use crate::id::Ident;
 pub use service::JournalService;
@@ -16,9 +50,16 @@ use std::cmp::PartialEq;
 use axum::Router;
 use axum::routing::get;
 use axum_login::login_required;
+use sqlx::postgres::PgHasArrayType;
 
 id!(JournalId, Ident::new16());
 
+impl PgHasArrayType for JournalId {
+    fn array_type_info() -> <Postgres as Database>::TypeInfo {
+        <&[&str] as Type<Postgres>>::type_info()
+    }
+}
+
 #[derive(Error, Debug, PartialEq)]
 pub enum JournalError {
     #[error("a journal already exists with the id {0}")]
@@ -62,6 +103,81 @@ pub enum JournalError {
 
     #[error("failed to decode an event: {0}")]
     EventDecode(String),
+
+    #[error("a webhook already exists with the id {0}")]
+    WebhookIdCollision(WebhookId),
+
+    #[error("invalid webhook: {0}")]
+    InvalidWebhook(WebhookId),
+
+    #[error("webhook urls must be valid https urls: {0}")]
+    InvalidWebhookUrl(String),
+
+    #[error("{} rows of the batch are invalid", .0.len())]
+    InvalidBatch(Vec<BatchRowError>),
+
+    #[error("the action can no longer be undone")]
+    UndoExpired,
+
+    #[error("the owner of journal {0} can't leave it")]
+    OwnerCannotLeave(JournalId),
+
+    #[error("you've reached the limit of {0}")]
+    QuotaExceeded(Quota),
+
+    #[error("a fiscal year must start in a month from 1 to 12, found {0}")]
+    InvalidFiscalYearStart(String),
+
+    #[error("an import rule already exists with the id {0}")]
+    RuleIdCollision(RuleId),
+
+    #[error("invalid import rule: {0}")]
+    InvalidRule(RuleId),
+
+    #[error("invalid import rule condition: {0}")]
+    InvalidRuleCondition(String),
+
+    #[error("a bank feed already exists with the id {0}")]
+    BankFeedIdCollision(BankFeedId),
+
+    #[error("invalid bank feed: {0}")]
+    InvalidBankFeed(BankFeedId),
+
+    #[error("failed to read a stored secret: {0}")]
+    Secret(String),
+
+    #[error("unknown bank feed provider: {0}")]
+    UnknownBankFeedProvider(String),
+
+    #[error("failed to sync the bank feed: {0}")]
+    BankFeedSync(String),
+
+    #[error("a transfer needs two different journals, but both sides are {0}")]
+    TransferWithinJournal(JournalId),
+
+    #[error("a transaction template already exists with the id {0}")]
+    TemplateIdCollision(TemplateId),
+
+    #[error("invalid transaction template: {0}")]
+    InvalidTemplate(TemplateId),
+
+    #[error("a comment already exists with the id {0}")]
+    CommentIdCollision(CommentId),
+
+    #[error("invalid comment: {0}")]
+    InvalidComment(CommentId),
+
+    #[error("a comment must be between 1 and {max} characters long, found {0}", max = comment::MAX_COMMENT_LENGTH)]
+    CommentLength(usize),
+
+    #[error("journal {0} is archived, so nothing in it can change until it's unarchived")]
+    JournalArchived(JournalId),
+
+    #[error("not a journal color: {0}")]
+    InvalidJournalColor(String),
+
+    #[error("a journal icon must be a single emoji, found {0}")]
+    InvalidJournalIcon(String),
 }
 
 impl From<sqlx::Error> for JournalError {
@@ -70,6 +186,21 @@ impl From<sqlx::Error> for JournalError {
     }
 }
 
+impl From<SecretError> for JournalError {
+    fn from(value: SecretError) -> Self {
+        match value {
+            SecretError::Sqlx(e) => e.into(),
+            e => Self::Secret(e.to_string()),
+        }
+    }
+}
+
+impl From<PayloadError> for JournalError {
+    fn from(value: PayloadError) -> Self {
+        Self::EventDecode(value.to_string())
+    }
+}
+
 impl From<rmp_serde::decode::Error> for JournalError {
     fn from(value: rmp_serde::decode::Error) -> Self {
         Self::EventDecode(value.to_string())
@@ -80,13 +211,35 @@ pub type JournalResult<T> = Result<T, JournalError>;
 
 pub fn router() -> Router<crate::StateType> {
     Router::new()
+        .route("/dashboard", get(dashboard::dashboard_page))
         .route("/journal", get(views::journal_list))
         .route(
             "/createjournal",
             axum::routing::post(commands::create_journal),
         )
         .route("/journal/{id}", get(views::journal_detail))
+        .route("/journal/{id}/history", get(history::history_page))
+        .route("/api/v1/journal/{id}/events", get(feed::event_feed))
         .route("/journal/{id}/person", get(person::people_list_page))
+        .route("/journal/{id}/search", get(search::search_page))
+        .route(
+            "/journal/{id}/report/net-worth",
+            get(networth::net_worth_page),
+        )
+        .route("/journal/{id}/report/payees", get(payee::payee_report_page))
+        .route(
+            "/journal/{id}/report/trial-balance.pdf",
+            get(report::trial_balance_pdf),
+        )
+        .route(
+            "/journal/{id}/account/export.csv",
+            get(report::chart_of_accounts_csv),
+        )
+        .route(
+            "/journal/{id}/export.beancount",
+            get(plaintext::beancount_export),
+        )
+        .route("/journal/{id}/export.ledger", get(plaintext::ledger_export))
         .route(
             "/journal/{id}/invite",
             axum::routing::post(commands::invite_member),
@@ -103,6 +256,42 @@ pub fn router() -> Router<crate::StateType> {
             "/journal/{id}/person/{person_id}/remove",
             axum::routing::post(commands::remove_member),
         )
+        .route(
+            "/journal/{id}/settings",
+            axum::routing::post(commands::update_settings),
+        )
+        .route(
+            "/journal/{id}/archive",
+            axum::routing::post(commands::archive_journal),
+        )
+        .route(
+            "/journal/{id}/unarchive",
+            axum::routing::post(commands::unarchive_journal),
+        )
+        .route(
+            "/journal/{id}/pin",
+            axum::routing::post(commands::pin_journal),
+        )
+        .route(
+            "/journal/{id}/unpin",
+            axum::routing::post(commands::unpin_journal),
+        )
+        .route(
+            "/journal/{id}/leave",
+            axum::routing::post(commands::leave_journal),
+        )
+        .route(
+            "/journal/{id}/delete",
+            axum::routing::post(commands::delete_journal),
+        )
+        .route(
+            "/journal/{id}/undo/{undo_id}",
+            axum::routing::post(undo::undo),
+        )
+        .route(
+            "/journal/{id}/revert/{event_id}",
+            axum::routing::post(revert::revert_last),
+        )
         .route_layer(login_required!(crate::BackendType, login_url = "/signin"))
 }
 
@@ -112,10 +301,22 @@ use crate::id;
 use crate::id::IdentError;
 use crate::journal::JournalError::InvalidJournal;
 use crate::journal::account::AccountId;
+use crate::journal::appearance::JournalAppearance;
+use crate::journal::bankfeed::BankFeedId;
+use crate::journal::comment::CommentId;
 use crate::journal::domain::JournalDomainEvent;
+use crate::journal::fiscal::FiscalYearStart;
 use crate::journal::member::JournalMember;
-use crate::journal::transaction::{TransactionId, TransactionValidationError};
+use crate::journal::payload::PayloadError;
+use crate::journal::quota::Quota;
+use crate::journal::rule::RuleId;
+use crate::journal::template::TemplateId;
+use crate::journal::transaction::{
+    AllJournalAccounts, BatchRowError, TransactionId, TransactionValidationError,
+};
+use crate::journal::webhook::WebhookId;
 use crate::name::Name;
+use crate::secret::SecretError;
 use crate::status::Status;
 use crate::time_provider::Timestamp;
 use bitflags::bitflags;
@@ -152,6 +353,39 @@ pub fn validate_permissions(
     false
 }
 
+/// like `validate_permissions`, but with the error to return. a journal the actor can't read is
+/// hidden from them, so it's reported missing rather than forbidden
+fn check_member_permissions(
+    member: &JournalMember,
+    authority: &Authority,
+    journal: &Journal,
+    permissions: Permissions,
+) -> Result<(), JournalError> {
+    if validate_permissions(member, authority, journal.owner, permissions) {
+        Ok(())
+    } else if validate_permissions(member, authority, journal.owner, Permissions::READ) {
+        Err(JournalError::Permissions(permissions))
+    } else {
+        Err(JournalError::InvalidJournal(journal.journal_id))
+    }
+}
+
+/// checks that the actor has the permissions, and that an archived journal is only read
+pub fn check_permissions(
+    member: &JournalMember,
+    authority: &Authority,
+    journal: &Journal,
+    permissions: Permissions,
+) -> Result<(), JournalError> {
+    check_member_permissions(member, authority, journal, permissions)?;
+
+    if journal.archived && permissions != Permissions::READ {
+        return Err(JournalError::JournalArchived(journal.journal_id));
+    }
+
+    Ok(())
+}
+
 #[derive(StateQuery, Clone, Default, Serialize, Deserialize)]
 #[state_query(JournalEvent)]
 pub struct Journal {
@@ -160,6 +394,9 @@ pub struct Journal {
     pub owner: UserId,
     pub name: Name,
     pub status: Status,
+    pub fiscal_year_start: FiscalYearStart,
+    /// an archived journal can be read and exported, but not changed
+    pub archived: bool,
 }
 
 impl Journal {
@@ -180,6 +417,11 @@ impl StateMutate for Journal {
                 self.status = Status::Valid;
             }
             JournalEvent::JournalDeleted { .. } => self.status = Status::Deleted,
+            JournalEvent::JournalSettingsUpdated {
+                fiscal_year_start, ..
+            } => self.fiscal_year_start = fiscal_year_start,
+            JournalEvent::JournalArchived { .. } => self.archived = true,
+            JournalEvent::JournalUnarchived { .. } => self.archived = false,
         }
     }
 }
@@ -188,6 +430,7 @@ pub struct CreateJournal {
     journal_id: JournalId,
     owner: UserId,
     name: Name,
+    appearance: JournalAppearance,
     authority: Authority,
     timestamp: Timestamp,
 }
@@ -197,6 +440,7 @@ impl CreateJournal {
         journal_id: JournalId,
         owner: UserId,
         name: Name,
+        appearance: JournalAppearance,
         authority: Authority,
         timestamp: Timestamp,
     ) -> Self {
@@ -204,6 +448,7 @@ impl CreateJournal {
             journal_id,
             owner,
             name,
+            appearance,
             authority,
             timestamp,
         }
@@ -224,13 +469,28 @@ impl Decision for CreateJournal {
             return Err(JournalError::IdCollision(self.journal_id));
         }
 
-        Ok(vec![JournalDomainEvent::JournalCreated {
+        let mut events = vec![JournalDomainEvent::JournalCreated {
             journal_id: self.journal_id,
             owner: self.owner,
             name: self.name.clone(),
             authority: self.authority.clone(),
             timestamp: self.timestamp,
-        }])
+        }];
+
+        // a color or icon chosen up front is recorded like one chosen later in the settings
+        if self.appearance != JournalAppearance::default() {
+            events.push(JournalDomainEvent::JournalSettingsUpdated {
+                journal_id: self.journal_id,
+                fiscal_year_start: FiscalYearStart::default(),
+                default_cash_account: None,
+                default_expense_account: None,
+                appearance: self.appearance.clone(),
+                authority: self.authority.clone(),
+                timestamp: self.timestamp,
+            });
+        }
+
+        Ok(events)
     }
 }
 
@@ -240,7 +500,6 @@ pub struct DeleteJournal {
     timestamp: Timestamp,
 }
 
-#[expect(unused)]
 impl DeleteJournal {
     pub fn new(journal_id: JournalId, authority: Authority, timestamp: Timestamp) -> Self {
         Self {
@@ -253,18 +512,29 @@ impl DeleteJournal {
 
 impl Decision for DeleteJournal {
     type Event = JournalDomainEvent;
-    type StateQuery = Journal;
+    type StateQuery = (Journal, JournalMember);
     type Error = JournalError;
 
     fn state_query(&self) -> Self::StateQuery {
-        Journal::new(self.journal_id)
+        (
+            Journal::new(self.journal_id),
+            JournalMember::new(
+                self.journal_id,
+                self.authority.user_id().unwrap_or_default(),
+            ),
+        )
     }
 
-    fn process(&self, state: &Self::StateQuery) -> Result<Vec<Self::Event>, Self::Error> {
-        if !state.status.valid() {
-            return Err(InvalidJournal(state.journal_id));
+    fn process(
+        &self,
+        (journal, actor): &Self::StateQuery,
+    ) -> Result<Vec<Self::Event>, Self::Error> {
+        if !journal.status.valid() {
+            return Err(InvalidJournal(journal.journal_id));
         }
 
+        check_permissions(actor, &self.authority, journal, Permissions::OWNER)?;
+
         Ok(vec![JournalDomainEvent::JournalDeleted {
             journal_id: self.journal_id,
             authority: self.authority.clone(),
@@ -273,6 +543,258 @@ impl Decision for DeleteJournal {
     }
 }
 
+pub struct UpdateJournalSettings {
+    journal_id: JournalId,
+    fiscal_year_start: FiscalYearStart,
+    default_cash_account: Option<AccountId>,
+    default_expense_account: Option<AccountId>,
+    appearance: JournalAppearance,
+    authority: Authority,
+    timestamp: Timestamp,
+}
+
+impl UpdateJournalSettings {
+    pub fn new(
+        journal_id: JournalId,
+        fiscal_year_start: FiscalYearStart,
+        default_cash_account: Option<AccountId>,
+        default_expense_account: Option<AccountId>,
+        appearance: JournalAppearance,
+        authority: Authority,
+        timestamp: Timestamp,
+    ) -> Self {
+        Self {
+            journal_id,
+            fiscal_year_start,
+            default_cash_account,
+            default_expense_account,
+            appearance,
+            authority,
+            timestamp,
+        }
+    }
+}
+
+impl Decision for UpdateJournalSettings {
+    type Event = JournalDomainEvent;
+    type StateQuery = (AllJournalAccounts, Journal, JournalMember);
+    type Error = JournalError;
+
+    fn state_query(&self) -> Self::StateQuery {
+        (
+            AllJournalAccounts::new(self.journal_id),
+            Journal::new(self.journal_id),
+            JournalMember::new(
+                self.journal_id,
+                self.authority.user_id().unwrap_or_default(),
+            ),
+        )
+    }
+
+    fn process(
+        &self,
+        (accounts, journal, actor): &Self::StateQuery,
+    ) -> Result<Vec<Self::Event>, Self::Error> {
+        if !journal.status.valid() {
+            return Err(InvalidJournal(self.journal_id));
+        }
+
+        check_permissions(actor, &self.authority, journal, Permissions::OWNER)?;
+
+        for account_id in [self.default_cash_account, self.default_expense_account]
+            .iter()
+            .flatten()
+        {
+            if !accounts.contains(account_id) {
+                return Err(JournalError::InvalidAccount(*account_id));
+            }
+        }
+
+        Ok(vec![JournalDomainEvent::JournalSettingsUpdated {
+            journal_id: self.journal_id,
+            fiscal_year_start: self.fiscal_year_start,
+            default_cash_account: self.default_cash_account,
+            default_expense_account: self.default_expense_account,
+            appearance: self.appearance.clone(),
+            authority: self.authority.clone(),
+            timestamp: self.timestamp,
+        }])
+    }
+}
+
+pub struct ArchiveJournal {
+    journal_id: JournalId,
+    authority: Authority,
+    timestamp: Timestamp,
+}
+
+impl ArchiveJournal {
+    pub fn new(journal_id: JournalId, authority: Authority, timestamp: Timestamp) -> Self {
+        Self {
+            journal_id,
+            authority,
+            timestamp,
+        }
+    }
+}
+
+impl Decision for ArchiveJournal {
+    type Event = JournalDomainEvent;
+    type StateQuery = (Journal, JournalMember);
+    type Error = JournalError;
+
+    fn state_query(&self) -> Self::StateQuery {
+        (
+            Journal::new(self.journal_id),
+            JournalMember::new(
+                self.journal_id,
+                self.authority.user_id().unwrap_or_default(),
+            ),
+        )
+    }
+
+    fn process(
+        &self,
+        (journal, actor): &Self::StateQuery,
+    ) -> Result<Vec<Self::Event>, Self::Error> {
+        if !journal.status.valid() {
+            return Err(InvalidJournal(self.journal_id));
+        }
+
+        // archiving an archived journal is rejected like any other change to it
+        check_permissions(actor, &self.authority, journal, Permissions::OWNER)?;
+
+        Ok(vec![JournalDomainEvent::JournalArchived {
+            journal_id: self.journal_id,
+            authority: self.authority.clone(),
+            timestamp: self.timestamp,
+        }])
+    }
+}
+
+pub struct UnarchiveJournal {
+    journal_id: JournalId,
+    authority: Authority,
+    timestamp: Timestamp,
+}
+
+impl UnarchiveJournal {
+    pub fn new(journal_id: JournalId, authority: Authority, timestamp: Timestamp) -> Self {
+        Self {
+            journal_id,
+            authority,
+            timestamp,
+        }
+    }
+}
+
+impl Decision for UnarchiveJournal {
+    type Event = JournalDomainEvent;
+    type StateQuery = (Journal, JournalMember);
+    type Error = JournalError;
+
+    fn state_query(&self) -> Self::StateQuery {
+        (
+            Journal::new(self.journal_id),
+            JournalMember::new(
+                self.journal_id,
+                self.authority.user_id().unwrap_or_default(),
+            ),
+        )
+    }
+
+    fn process(
+        &self,
+        (journal, actor): &Self::StateQuery,
+    ) -> Result<Vec<Self::Event>, Self::Error> {
+        if !journal.status.valid() {
+            return Err(InvalidJournal(self.journal_id));
+        }
+
+        // the only change an archived journal allows
+        check_member_permissions(actor, &self.authority, journal, Permissions::OWNER)?;
+
+        Ok(vec![JournalDomainEvent::JournalUnarchived {
+            journal_id: self.journal_id,
+            authority: self.authority.clone(),
+            timestamp: self.timestamp,
+        }])
+    }
+}
+
+/// pins the journal to the top of the actor's journal list, or unpins it
+pub struct PinJournal {
+    journal_id: JournalId,
+    pinned: bool,
+    authority: Authority,
+    timestamp: Timestamp,
+}
+
+impl PinJournal {
+    pub fn new(
+        journal_id: JournalId,
+        pinned: bool,
+        authority: Authority,
+        timestamp: Timestamp,
+    ) -> Self {
+        Self {
+            journal_id,
+            pinned,
+            authority,
+            timestamp,
+        }
+    }
+}
+
+impl Decision for PinJournal {
+    type Event = JournalDomainEvent;
+    type StateQuery = (Journal, JournalMember);
+    type Error = JournalError;
+
+    fn state_query(&self) -> Self::StateQuery {
+        (
+            Journal::new(self.journal_id),
+            JournalMember::new(
+                self.journal_id,
+                self.authority.user_id().unwrap_or_default(),
+            ),
+        )
+    }
+
+    fn process(
+        &self,
+        (journal, actor): &Self::StateQuery,
+    ) -> Result<Vec<Self::Event>, Self::Error> {
+        if !journal.status.valid() {
+            return Err(InvalidJournal(self.journal_id));
+        }
+
+        // pins belong to a user's own list, so only users have them
+        let Some(user_id) = self.authority.user_id() else {
+            return Err(InvalidJournal(self.journal_id));
+        };
+
+        // pinning changes nothing in the journal, so archived journals can be pinned too
+        check_member_permissions(actor, &self.authority, journal, Permissions::READ)?;
+
+        Ok(vec![if self.pinned {
+            JournalDomainEvent::JournalPinned {
+                journal_id: self.journal_id,
+                user_id,
+                authority: self.authority.clone(),
+                timestamp: self.timestamp,
+            }
+        } else {
+            JournalDomainEvent::JournalUnpinned {
+                journal_id: self.journal_id,
+                user_id,
+                authority: self.authority.clone(),
+                timestamp: self.timestamp,
+            }
+        }])
+    }
+}
+
 bitflags! {
     #[derive(Hash, Default, Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
     pub struct Permissions: i32 {
@@ -281,6 +803,9 @@ bitflags! {
         const APPEND_TRANSACTION = 1 << 2;
         const INVITE = 1 << 3;
         const OWNER = 1 << 4;
+        /// transactions are submitted for approval rather than recorded
+        const SUBMIT_ONLY = 1 << 5;
+        const APPROVE = 1 << 6;
     }
 }
 
@@ -318,3 +843,222 @@ impl Display for PermissionDecodeError {
         )
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::journal::appearance::{JournalColor, JournalIcon};
+    use crate::journal::domain::AccountEvent;
+    use crate::test_app::{TestApp, assert_accepted};
+    use chrono::Utc;
+
+    fn archived_journal(owner: UserId) -> (Journal, JournalMember) {
+        let mut journal = Journal::new(JournalId::new());
+        journal.owner = owner;
+        journal.status = Status::Valid;
+        journal.archived = true;
+        let member = JournalMember::new(journal.journal_id, owner);
+        (journal, member)
+    }
+
+    #[test]
+    fn an_archived_journal_rejects_changes() {
+        let owner = UserId::new();
+        let (journal, member) = archived_journal(owner);
+        let decision = UpdateJournalSettings::new(
+            journal.journal_id,
+            FiscalYearStart::default(),
+            None,
+            None,
+            JournalAppearance::default(),
+            Authority::Direct(Actor::User(owner)),
+            Utc::now(),
+        );
+
+        assert_eq!(
+            decision.process(&(
+                AllJournalAccounts::new(journal.journal_id),
+                journal.clone(),
+                member
+            )),
+            Err(JournalError::JournalArchived(journal.journal_id))
+        );
+    }
+
+    #[test]
+    fn default_accounts_must_be_in_the_journal() {
+        let owner = UserId::new();
+        let (mut journal, member) = archived_journal(owner);
+        journal.archived = false;
+
+        let authority = Authority::Direct(Actor::User(owner));
+        let cash = AccountId::new();
+        let mut accounts = AllJournalAccounts::new(journal.journal_id);
+        accounts.mutate(AccountEvent::AccountCreated {
+            account_id: cash,
+            journal_id: journal.journal_id,
+            name: Name::try_new("Cash".to_string()).expect("invalid account name"),
+            authority: authority.clone(),
+            timestamp: Utc::now(),
+        });
+        let state = (accounts, journal.clone(), member);
+
+        let elsewhere = AccountId::new();
+        let decision = UpdateJournalSettings::new(
+            journal.journal_id,
+            FiscalYearStart::default(),
+            Some(cash),
+            Some(elsewhere),
+            JournalAppearance::default(),
+            authority.clone(),
+            Utc::now(),
+        );
+        assert_eq!(
+            decision.process(&state),
+            Err(JournalError::InvalidAccount(elsewhere))
+        );
+
+        let decision = UpdateJournalSettings::new(
+            journal.journal_id,
+            FiscalYearStart::default(),
+            Some(cash),
+            None,
+            JournalAppearance::default(),
+            authority,
+            Utc::now(),
+        );
+        assert!(matches!(
+            decision.process(&state).as_deref(),
+            Ok([JournalDomainEvent::JournalSettingsUpdated {
+                default_cash_account: Some(account),
+                default_expense_account: None,
+                ..
+            }]) if *account == cash
+        ));
+    }
+
+    #[test]
+    fn an_archived_journal_can_be_unarchived() {
+        let owner = UserId::new();
+        let state = archived_journal(owner);
+        let decision = UnarchiveJournal::new(
+            state.0.journal_id,
+            Authority::Direct(Actor::User(owner)),
+            Utc::now(),
+        );
+
+        assert!(matches!(
+            decision.process(&state).as_deref(),
+            Ok([JournalDomainEvent::JournalUnarchived { .. }])
+        ));
+    }
+
+    #[test]
+    fn an_archived_journal_can_be_pinned() {
+        let owner = UserId::new();
+        let state = archived_journal(owner);
+        let decision = PinJournal::new(
+            state.0.journal_id,
+            true,
+            Authority::Direct(Actor::User(owner)),
+            Utc::now(),
+        );
+
+        assert!(matches!(
+            decision.process(&state).as_deref(),
+            Ok([JournalDomainEvent::JournalPinned { user_id, .. }]) if *user_id == owner
+        ));
+    }
+
+    #[test]
+    fn only_members_can_pin_a_journal() {
+        let (journal, _) = archived_journal(UserId::new());
+        let outsider = UserId::new();
+        let member = JournalMember::new(journal.journal_id, outsider);
+        let decision = PinJournal::new(
+            journal.journal_id,
+            true,
+            Authority::Direct(Actor::User(outsider)),
+            Utc::now(),
+        );
+
+        assert_eq!(
+            decision.process(&(journal.clone(), member)),
+            Err(JournalError::InvalidJournal(journal.journal_id))
+        );
+    }
+
+    #[test]
+    fn an_appearance_chosen_at_creation_is_recorded_as_settings() {
+        let owner = UserId::new();
+        let journal_id = JournalId::new();
+        let name = Name::try_new("Household".to_string()).expect("invalid journal name");
+        let authority = Authority::Direct(Actor::User(owner));
+
+        let plain = CreateJournal::new(
+            journal_id,
+            owner,
+            name.clone(),
+            JournalAppearance::default(),
+            authority.clone(),
+            Utc::now(),
+        );
+        assert!(matches!(
+            plain.process(&Journal::new(journal_id)).as_deref(),
+            Ok([JournalDomainEvent::JournalCreated { .. }])
+        ));
+
+        let appearance = JournalAppearance {
+            color: JournalColor::Teal,
+            icon: Some(JournalIcon::try_new("🏠").expect("invalid icon")),
+        };
+        let decorated = CreateJournal::new(
+            journal_id,
+            owner,
+            name,
+            appearance.clone(),
+            authority,
+            Utc::now(),
+        );
+        assert!(matches!(
+            decorated.process(&Journal::new(journal_id)).as_deref(),
+            Ok([
+                JournalDomainEvent::JournalCreated { .. },
+                JournalDomainEvent::JournalSettingsUpdated { appearance: recorded, .. },
+            ]) if *recorded == appearance
+        ));
+    }
+
+    #[tokio::test]
+    async fn pinned_journals_come_first_then_the_most_recently_active() {
+        let Some(app) = TestApp::spawn().await else {
+            return;
+        };
+
+        let user = app.signup("pinner").await;
+        let garden = user.create_journal(&app, "Garden").await;
+        let garage = user.create_journal(&app, "Garage").await;
+
+        let listed = async || {
+            app.state
+                .journal_service
+                .list_accessible_journals(user.id)
+                .await
+                .expect("failed to list the journals")
+                .into_iter()
+                .map(|(journal, _, _)| journal.id)
+                .collect::<Vec<JournalId>>()
+        };
+
+        assert_eq!(listed().await, vec![garage, garden]);
+
+        assert_accepted(&user.create_account(garden, "Seeds").await);
+        assert_eq!(listed().await, vec![garden, garage]);
+
+        assert_accepted(&user.client.post(&format!("/journal/{garage}/pin")).await);
+        assert_eq!(listed().await, vec![garage, garden]);
+
+        assert_accepted(&user.client.post(&format!("/journal/{garage}/unpin")).await);
+        assert_eq!(listed().await, vec![garden, garage]);
+    }
+}
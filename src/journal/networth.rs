@@ -0,0 +1,241 @@
+use crate::BackendType;
+use crate::StateType;
+use crate::authn::get_user;
+use crate::charts::{Chart, Series, line_chart};
+use crate::journal::access::JournalAccess;
+use crate::journal::account::AccountId;
+use crate::journal::fiscal;
+use crate::journal::layout::journal_layout;
+use crate::journal::print::{PrintHeading, print_button, printable};
+use crate::journal::service::AccountState;
+use crate::journal::transaction::views::format_amount;
+use crate::monkesto_error::MonkestoResult;
+use crate::time_provider::TimeProvider;
+use axum::extract::State;
+use axum::response::Redirect;
+use axum_login::AuthSession;
+use chrono::{Months, NaiveDate};
+use maud::{Markup, html};
+use std::collections::{HashMap, HashSet};
+
+/// whether the account is an asset or a liability, going by its group or, for an account
+/// without one, the start of its name, such as `Assets:Cash`. the rest aren't part of net worth
+fn on_balance_sheet(account: &AccountState) -> bool {
+    let heading = match &account.group {
+        Some(group) => group.as_ref(),
+        None => account.name.as_ref().split(':').next().unwrap_or_default(),
+    };
+
+    matches!(
+        heading.trim().to_lowercase().as_str(),
+        "asset" | "assets" | "liability" | "liabilities"
+    )
+}
+
+/// the net worth at the end of each month from the first one an asset or liability changed in
+/// through the month `today` is in. balances have credits positive, so assets minus liabilities
+/// is the negated sum of both sides' balances
+fn net_worth_by_month(
+    accounts: &[AccountState],
+    changes: &[(AccountId, NaiveDate, i64)],
+    today: NaiveDate,
+) -> Vec<(NaiveDate, i64)> {
+    let counted: HashSet<AccountId> = accounts
+        .iter()
+        .filter(|account| on_balance_sheet(account))
+        .map(|account| account.id)
+        .collect();
+
+    let mut by_month: HashMap<NaiveDate, i64> = HashMap::new();
+    for (account_id, period, change) in changes {
+        if counted.contains(account_id) {
+            *by_month.entry(*period).or_default() -= change;
+        }
+    }
+
+    let Some(first) = by_month.keys().min().copied() else {
+        return Vec::new();
+    };
+    let last = by_month
+        .keys()
+        .max()
+        .copied()
+        .unwrap_or(first)
+        .max(fiscal::period_start(today));
+
+    let mut net_worth = 0;
+    let mut months = Vec::new();
+    let mut month = Some(first);
+    while let Some(current) = month.filter(|month| *month <= last) {
+        net_worth += by_month.get(&current).copied().unwrap_or(0);
+        months.push((current, net_worth));
+        month = current.checked_add_months(Months::new(1));
+    }
+
+    months
+}
+
+fn format_net_worth(amount: i64) -> String {
+    let formatted = format_amount(amount.unsigned_abs());
+    if amount < 0 {
+        format!("-{formatted}")
+    } else {
+        formatted
+    }
+}
+
+pub async fn net_worth_page(
+    State(state): State<StateType>,
+    session: AuthSession<BackendType>,
+    access: JournalAccess,
+) -> Result<Markup, Redirect> {
+    let user = get_user(session)?;
+    let journal_id = access.journal.id;
+    let authority = access.authority();
+    let today = user.timezone().date(state.clock.get_time());
+
+    let months_res: MonkestoResult<Vec<(NaiveDate, i64)>> = async {
+        let accounts: Vec<AccountState> = state
+            .journal_service
+            .list_journal_accounts(journal_id, &authority)
+            .await?
+            .into_iter()
+            .map(|(account, _, _)| account)
+            .collect();
+
+        let changes = state
+            .journal_service
+            .monthly_balance_changes(journal_id, &authority)
+            .await?;
+
+        Ok(net_worth_by_month(&accounts, &changes, today))
+    }
+    .await;
+
+    let content = html! {
+        div class="flex flex-col gap-6 mx-auto w-full max-w-4xl" {
+            div class="flex justify-between items-center" {
+                h2 class="text-xl font-semibold text-gray-900 dark:text-white" { "Net Worth" }
+                (print_button())
+            }
+            p class="text-sm text-gray-500 dark:text-gray-400" {
+                "Assets minus liabilities at the end of each month. Accounts count as assets or liabilities when they're grouped under Assets or Liabilities, or named like Assets:Cash."
+            }
+
+            @match &months_res {
+                Ok(months) if months.is_empty() => {
+                    p class="text-sm text-gray-500 dark:text-gray-400" {
+                        "No assets or liabilities have a balance yet."
+                    }
+                }
+                Ok(months) => {
+                    div class="p-4 bg-white dark:bg-gray-800 border border-gray-200 dark:border-gray-700 rounded-xl" {
+                        (line_chart(&Chart {
+                            title: "Net worth by month".to_string(),
+                            labels: months.iter().map(|(month, _)| month.format("%b %Y").to_string()).collect(),
+                            series: vec![Series {
+                                name: "Net worth".to_string(),
+                                values: months.iter().map(|(_, amount)| *amount).collect(),
+                            }],
+                            format: format_net_worth,
+                        }))
+                    }
+
+                    table class="w-full text-sm" {
+                        thead {
+                            tr class="text-left text-gray-500 dark:text-gray-400" {
+                                th class="py-1 font-medium" { "Month" }
+                                th class="py-1 font-medium text-right" { "Net Worth" }
+                            }
+                        }
+                        tbody class="divide-y divide-gray-200 dark:divide-gray-700" {
+                            @for (month, amount) in months.iter().rev() {
+                                tr {
+                                    td class="py-1 text-gray-700 dark:text-gray-300" { (month.format("%B %Y")) }
+                                    td class="py-1 text-right font-mono text-gray-900 dark:text-white" { (format_net_worth(*amount)) }
+                                }
+                            }
+                        }
+                    }
+                }
+                Err(e) => {
+                    p class="text-gray-500 dark:text-gray-400" {
+                        "failed to compute the journal's net worth: " (e)
+                    }
+                }
+            }
+        }
+    };
+
+    let period = match months_res.as_deref() {
+        Ok([(first, _), .., (last, _)]) => {
+            format!("{} – {}", first.format("%B %Y"), last.format("%B %Y"))
+        }
+        Ok([(only, _)]) => only.format("%B %Y").to_string(),
+        _ => format!("As of {}", today.format("%B %-d, %Y")),
+    };
+    let heading = PrintHeading {
+        journal: access.journal.name.as_ref(),
+        period,
+        timezone: user.timezone(),
+        generated: state.clock.get_time(),
+    };
+
+    Ok(journal_layout(
+        &access.journal,
+        printable(&heading, content),
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::journal::JournalId;
+    use crate::name::Name;
+    use chrono::Days;
+
+    fn account(name: &str, group: Option<&str>) -> AccountState {
+        let named = |name: &str| Name::try_new(name.to_string()).expect("invalid name");
+
+        AccountState {
+            id: AccountId::new(),
+            journal_id: JournalId::new(),
+            name: named(name),
+            balance: 0,
+            group: group.map(named),
+        }
+    }
+
+    #[test]
+    fn net_worth_is_assets_minus_liabilities_at_each_month_end() {
+        let date = |year, month| NaiveDate::from_ymd_opt(year, month, 1).expect("invalid date");
+
+        let accounts = [
+            account("Cash", Some("Assets")),
+            account("Liabilities:Visa", None),
+            account("Rent", Some("Spending")),
+        ];
+        let (cash, card, rent) = (accounts[0].id, accounts[1].id, accounts[2].id);
+
+        let changes = [
+            // paid in 100.00 of cash
+            (cash, date(2026, 1), -10000),
+            // 30.00 of rent on the card
+            (card, date(2026, 3), 3000),
+            (rent, date(2026, 3), -3000),
+        ];
+
+        assert_eq!(
+            net_worth_by_month(&accounts, &changes, date(2026, 4) + Days::new(9)),
+            vec![
+                (date(2026, 1), 10000),
+                (date(2026, 2), 10000),
+                (date(2026, 3), 7000),
+                (date(2026, 4), 7000),
+            ]
+        );
+
+        assert!(!on_balance_sheet(&accounts[2]));
+        assert!(net_worth_by_month(&accounts[2..], &changes, date(2026, 4)).is_empty());
+    }
+}
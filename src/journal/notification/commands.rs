@@ -0,0 +1,41 @@
+use crate::BackendType;
+use crate::StateType;
+use crate::authn::get_user;
+use crate::monkesto_error::{ErrorRedirect, OrRedirect};
+use axum::extract::Path;
+use axum::extract::State;
+use axum::response::Redirect;
+use axum_login::AuthSession;
+
+const CALLBACK_URL: &str = "/notifications";
+
+pub async fn mark_read(
+    State(state): State<StateType>,
+    session: AuthSession<BackendType>,
+    Path(id): Path<i64>,
+) -> Result<Redirect, ErrorRedirect> {
+    let user = get_user(session)?;
+
+    state
+        .journal_service
+        .mark_notification_read(user.id, id)
+        .await
+        .or_redirect(CALLBACK_URL)?;
+
+    Ok(Redirect::to(CALLBACK_URL))
+}
+
+pub async fn mark_all_read(
+    State(state): State<StateType>,
+    session: AuthSession<BackendType>,
+) -> Result<Redirect, ErrorRedirect> {
+    let user = get_user(session)?;
+
+    state
+        .journal_service
+        .mark_all_notifications_read(user.id)
+        .await
+        .or_redirect(CALLBACK_URL)?;
+
+    Ok(Redirect::to(CALLBACK_URL))
+}
@@ -0,0 +1,204 @@
+use crate::authn::AuthnService;
+use crate::authn::UserId;
+use crate::authn::digest::DigestFrequency;
+use crate::authn::user::UserError;
+use crate::authority::{Actor, Authority};
+use crate::email::Email;
+use crate::journal::transaction::views::format_balance;
+use crate::journal::{JournalError, JournalService};
+use crate::shutdown;
+use crate::time_provider::TimeProvider;
+use axum_login::tracing;
+use disintegrate_postgres::PgEventId;
+use std::collections::{BTreeMap, HashMap};
+use std::fmt::Write;
+use std::time::Duration;
+use thiserror::Error;
+
+/// how often the job looks for digests that are due
+const DIGEST_CHECK_INTERVAL: Duration = Duration::from_secs(60 * 60);
+
+#[derive(Debug, Error)]
+enum DigestError {
+    #[error(transparent)]
+    User(#[from] UserError),
+    #[error(transparent)]
+    Journal(#[from] JournalError),
+    #[error(transparent)]
+    Sqlx(#[from] sqlx::Error),
+}
+
+/// what happened in one journal since the user's last digest
+#[derive(Debug, PartialEq)]
+pub struct JournalActivity {
+    pub journal_name: String,
+    pub transactions: usize,
+    /// the net change to each account that moved, by account name, where credits are positive
+    pub balance_changes: Vec<(String, i64)>,
+}
+
+/// the subject and body of a digest, or none if nothing happened
+pub fn compose_digest(
+    frequency: DigestFrequency,
+    activity: &[JournalActivity],
+) -> Option<(String, String)> {
+    if activity.is_empty() {
+        return None;
+    }
+
+    let mut body = String::new();
+
+    for journal in activity {
+        _ = writeln!(
+            body,
+            "{}: {} new transaction{}",
+            journal.journal_name,
+            journal.transactions,
+            if journal.transactions == 1 { "" } else { "s" }
+        );
+
+        for (account, change) in &journal.balance_changes {
+            _ = writeln!(body, "  {account}: {}", format_balance(*change));
+        }
+
+        body.push('\n');
+    }
+
+    Some((format!("Your {frequency} Monkesto digest"), body))
+}
+
+/// gathers the transactions created in each of the user's journals between two events
+async fn collect_activity(
+    journal_service: &JournalService,
+    user_id: UserId,
+    after: PgEventId,
+    up_to: PgEventId,
+) -> Result<Vec<JournalActivity>, DigestError> {
+    let authority = Authority::Direct(Actor::User(user_id));
+    let mut activity = Vec::new();
+
+    for (journal, _, _) in journal_service.list_accessible_journals(user_id).await? {
+        let transactions = journal_service
+            .transactions_created_between(journal.id, after, up_to)
+            .await?;
+
+        if transactions.is_empty() {
+            continue;
+        }
+
+        let account_names: HashMap<_, _> = journal_service
+            .list_journal_accounts(journal.id, &authority)
+            .await?
+            .into_iter()
+            .map(|(account, _, _)| (account.id, account.name.to_string()))
+            .collect();
+
+        let mut changes = BTreeMap::<String, i64>::new();
+        for update in transactions.iter().flat_map(|entries| &entries.0) {
+            let name = account_names
+                .get(&update.account_id)
+                .cloned()
+                .unwrap_or_else(|| update.account_id.to_string());
+            *changes.entry(name).or_default() += update.signed_amount();
+        }
+
+        activity.push(JournalActivity {
+            journal_name: journal.name.to_string(),
+            transactions: transactions.len(),
+            balance_changes: changes
+                .into_iter()
+                .filter(|(_, change)| *change != 0)
+                .collect(),
+        });
+    }
+
+    Ok(activity)
+}
+
+/// queues the user's digest if one is due, and records how far it covered
+async fn send_digest(
+    journal_service: &JournalService,
+    user_id: UserId,
+    email: &Email,
+    frequency: DigestFrequency,
+    up_to: PgEventId,
+) -> Result<(), DigestError> {
+    let Some(period) = frequency.period() else {
+        return Ok(());
+    };
+
+    let now = journal_service.clock().get_time();
+
+    let after = match journal_service.last_digest(user_id).await? {
+        Some((_, sent_at)) if now - sent_at < period => return Ok(()),
+        Some((last_event_id, _)) => last_event_id,
+        // the first digest only covers what happens after the user subscribed
+        None => return Ok(journal_service.record_digest(user_id, up_to, now).await?),
+    };
+
+    let activity = collect_activity(journal_service, user_id, after, up_to).await?;
+
+    match compose_digest(frequency, &activity) {
+        Some((subject, body)) => Ok(journal_service
+            .queue_digest(user_id, email, (&subject, &body), up_to, now)
+            .await?),
+        None => Ok(journal_service.record_digest(user_id, up_to, now).await?),
+    }
+}
+
+/// periodically queues digests for the users who asked for them until shutdown.
+/// a digest that fails is retried on the next check
+pub async fn send_digests(authn_service: AuthnService, journal_service: JournalService) {
+    let mut shutdown = Box::pin(shutdown());
+
+    loop {
+        let up_to = journal_service.current_event_id();
+
+        match authn_service.digest_subscribers().await {
+            Ok(subscribers) => {
+                for (user_id, email, frequency) in subscribers {
+                    if let Err(error) =
+                        send_digest(&journal_service, user_id, &email, frequency, up_to).await
+                    {
+                        tracing::error!(%user_id, ?error, "failed to send a digest");
+                    }
+                }
+            }
+            Err(error) => tracing::error!(?error, "failed to fetch the digest subscribers"),
+        }
+
+        tokio::select! {
+            _ = tokio::time::sleep(DIGEST_CHECK_INTERVAL) => {},
+            _ = &mut shutdown => return,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn digests_summarize_each_journal_and_skip_quiet_periods() {
+        assert_eq!(compose_digest(DigestFrequency::Daily, &[]), None);
+
+        let (subject, body) = compose_digest(
+            DigestFrequency::Weekly,
+            &[JournalActivity {
+                journal_name: "Household".to_string(),
+                transactions: 2,
+                balance_changes: vec![
+                    ("Cash".to_string(), -12_50),
+                    ("Groceries".to_string(), 12_50),
+                ],
+            }],
+        )
+        .expect("a digest with activity wasn't composed");
+
+        assert_eq!(subject, "Your weekly Monkesto digest");
+        assert_eq!(
+            body,
+            "Household: 2 new transactions\n  Cash: $12.50 Dr\n  Groceries: $12.50 Cr\n\n"
+        );
+    }
+}
@@ -0,0 +1,306 @@
+pub mod commands;
+pub mod digest;
+pub mod notifier;
+pub mod outbox;
+pub mod views;
+
+use axum::Router;
+use axum::routing::{get, post};
+use axum_login::login_required;
+
+pub fn router() -> Router<crate::StateType> {
+    Router::new()
+        .route("/notifications", get(views::notification_list_page))
+        .route("/notifications/unread", get(views::unread_count))
+        .route("/notifications/read", post(commands::mark_all_read))
+        .route("/notifications/{id}/read", post(commands::mark_read))
+        .route_layer(login_required!(crate::BackendType, login_url = "/signin"))
+}
+
+use crate::authn::UserId;
+use crate::journal::domain::JournalDomainEvent;
+use crate::journal::transaction::EntryType;
+use crate::journal::transaction::views::format_amount;
+use crate::time_provider::Timestamp;
+
+/// transactions that debit at least this many cents notify everyone in the journal
+pub const LARGE_TRANSACTION_CENTS: u64 = 1_000_000;
+
+#[derive(Debug, PartialEq)]
+pub enum NotificationKind {
+    Invited,
+    /// an owner changed what the recipient can do in the journal
+    PermissionsChanged,
+    Removed,
+    LargeTransaction(u64),
+    Commented,
+    /// a comment that mentions the recipient, with the comment's text
+    Mentioned(String),
+}
+
+impl NotificationKind {
+    pub fn message(&self, journal_name: &str) -> String {
+        match self {
+            Self::Invited => format!("You were added to {journal_name}"),
+            Self::PermissionsChanged => {
+                format!("Your permissions in {journal_name} were changed")
+            }
+            Self::Removed => format!("You were removed from {journal_name}"),
+            Self::LargeTransaction(amount) => format!(
+                "A transaction of {} was posted in {journal_name}",
+                format_amount(*amount)
+            ),
+            Self::Commented => format!("A transaction in {journal_name} has a new comment"),
+            Self::Mentioned(_) => format!("You were mentioned in a comment in {journal_name}"),
+        }
+    }
+
+    /// the subject and body of the email this notification also sends, if it sends one
+    pub fn email(&self, journal_name: &str) -> Option<(String, String)> {
+        match self {
+            Self::Mentioned(body) => Some((
+                self.message(journal_name),
+                format!("{}:\n\n{body}\n", self.message(journal_name)),
+            )),
+            Self::Invited
+            | Self::PermissionsChanged
+            | Self::Removed
+            | Self::LargeTransaction(_)
+            | Self::Commented => None,
+        }
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub enum Recipients {
+    User(UserId),
+    /// these users, as long as they can still see the journal
+    Members(Vec<UserId>),
+    /// everyone who can see the journal, apart from whoever caused the event
+    MembersExcept(Option<UserId>),
+}
+
+#[derive(Debug, PartialEq)]
+pub struct Notification {
+    pub kind: NotificationKind,
+    pub recipients: Recipients,
+    pub timestamp: Timestamp,
+}
+
+/// the notifications that an event sends. a user is only notified once per event, by the first
+/// notification that names them
+pub fn notifications_for(event: &JournalDomainEvent) -> Vec<Notification> {
+    match event {
+        JournalDomainEvent::MemberAdded {
+            user_id,
+            authority,
+            timestamp,
+            ..
+        } if authority.user_id() != Some(*user_id) => vec![Notification {
+            kind: NotificationKind::Invited,
+            recipients: Recipients::User(*user_id),
+            timestamp: *timestamp,
+        }],
+        // the member's next request already sees the change, and this tells them why
+        JournalDomainEvent::MemberPermissionsUpdated {
+            user_id,
+            authority,
+            timestamp,
+            ..
+        } if authority.user_id() != Some(*user_id) => vec![Notification {
+            kind: NotificationKind::PermissionsChanged,
+            recipients: Recipients::User(*user_id),
+            timestamp: *timestamp,
+        }],
+        JournalDomainEvent::MemberRemoved {
+            user_id,
+            authority,
+            timestamp,
+            ..
+        } if authority.user_id() != Some(*user_id) => vec![Notification {
+            kind: NotificationKind::Removed,
+            recipients: Recipients::User(*user_id),
+            timestamp: *timestamp,
+        }],
+        JournalDomainEvent::TransactionCreated {
+            balance_updates,
+            authority,
+            timestamp,
+            ..
+        } => {
+            let debits: u64 = balance_updates
+                .iter()
+                .filter(|update| update.entry_type == EntryType::Debit)
+                .map(|update| update.amount)
+                .sum();
+
+            (debits >= LARGE_TRANSACTION_CENTS)
+                .then(|| Notification {
+                    kind: NotificationKind::LargeTransaction(debits),
+                    recipients: Recipients::MembersExcept(authority.user_id()),
+                    timestamp: *timestamp,
+                })
+                .into_iter()
+                .collect()
+        }
+        JournalDomainEvent::CommentAdded {
+            body,
+            mentions,
+            authority,
+            timestamp,
+            ..
+        } => {
+            let mentioned: Vec<UserId> = mentions
+                .iter()
+                .filter(|user_id| authority.user_id() != Some(**user_id))
+                .copied()
+                .collect();
+
+            // mentioned members hear about the mention instead of the plain comment
+            let mut notifications = Vec::new();
+            if !mentioned.is_empty() {
+                notifications.push(Notification {
+                    kind: NotificationKind::Mentioned(body.clone()),
+                    recipients: Recipients::Members(mentioned),
+                    timestamp: *timestamp,
+                });
+            }
+            notifications.push(Notification {
+                kind: NotificationKind::Commented,
+                recipients: Recipients::MembersExcept(authority.user_id()),
+                timestamp: *timestamp,
+            });
+            notifications
+        }
+        _ => Vec::new(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::authority::{Actor, Authority};
+    use crate::journal::account::AccountId;
+    use crate::journal::comment::CommentId;
+    use crate::journal::transaction::{BalanceUpdate, TransactionId};
+    use crate::journal::{JournalId, Permissions};
+    use chrono::{NaiveDate, Utc};
+
+    fn transaction(amount: u64, actor: UserId) -> JournalDomainEvent {
+        JournalDomainEvent::TransactionCreated {
+            transaction_id: TransactionId::new(),
+            journal_id: JournalId::new(),
+            number: 1,
+            balance_updates: vec![
+                BalanceUpdate {
+                    account_id: AccountId::new(),
+                    amount,
+                    entry_type: EntryType::Debit,
+                    memo: None,
+                },
+                BalanceUpdate {
+                    account_id: AccountId::new(),
+                    amount,
+                    entry_type: EntryType::Credit,
+                    memo: None,
+                },
+            ],
+            effective_date: NaiveDate::default(),
+            reverses: None,
+            transfer: None,
+            payee: None,
+            authority: Authority::Direct(Actor::User(actor)),
+            timestamp: Utc::now(),
+        }
+    }
+
+    #[test]
+    fn only_large_transactions_notify_the_other_members() {
+        let actor = UserId::new();
+
+        assert_eq!(
+            notifications_for(&transaction(LARGE_TRANSACTION_CENTS - 1, actor)),
+            Vec::new()
+        );
+
+        let notification = notifications_for(&transaction(LARGE_TRANSACTION_CENTS, actor))
+            .pop()
+            .expect("a large transaction didn't notify anyone");
+        assert_eq!(
+            notification.kind,
+            NotificationKind::LargeTransaction(LARGE_TRANSACTION_CENTS)
+        );
+        assert_eq!(
+            notification.recipients,
+            Recipients::MembersExcept(Some(actor))
+        );
+    }
+
+    #[test]
+    fn members_hear_when_someone_else_changes_their_access() {
+        let journal_id = JournalId::new();
+        let owner = UserId::new();
+        let member = UserId::new();
+
+        let removed = |by: UserId| {
+            notifications_for(&JournalDomainEvent::MemberRemoved {
+                journal_id,
+                user_id: member,
+                authority: Authority::Direct(Actor::User(by)),
+                timestamp: Utc::now(),
+            })
+        };
+
+        let notification = removed(owner)
+            .pop()
+            .expect("a removed member wasn't notified");
+        assert_eq!(notification.kind, NotificationKind::Removed);
+        assert_eq!(notification.recipients, Recipients::User(member));
+
+        // leaving a journal isn't news to whoever left
+        assert_eq!(removed(member), Vec::new());
+
+        let notification = notifications_for(&JournalDomainEvent::MemberPermissionsUpdated {
+            journal_id,
+            user_id: member,
+            permissions: Permissions::READ,
+            authority: Authority::Direct(Actor::User(owner)),
+            timestamp: Utc::now(),
+        })
+        .pop()
+        .expect("a downgraded member wasn't notified");
+        assert_eq!(notification.kind, NotificationKind::PermissionsChanged);
+        assert_eq!(notification.recipients, Recipients::User(member));
+    }
+
+    #[test]
+    fn mentioned_members_hear_about_the_mention_first() {
+        let author = UserId::new();
+        let mentioned = UserId::new();
+
+        let notifications = notifications_for(&JournalDomainEvent::CommentAdded {
+            comment_id: CommentId::new(),
+            transaction_id: TransactionId::new(),
+            journal_id: JournalId::new(),
+            parent_id: None,
+            body: "what was this charge?".to_string(),
+            mentions: vec![mentioned, author],
+            authority: Authority::Direct(Actor::User(author)),
+            timestamp: Utc::now(),
+        });
+
+        let recipients: Vec<&Recipients> = notifications
+            .iter()
+            .map(|notification| &notification.recipients)
+            .collect();
+        assert_eq!(
+            recipients,
+            vec![
+                &Recipients::Members(vec![mentioned]),
+                &Recipients::MembersExcept(Some(author))
+            ]
+        );
+        assert!(notifications[0].kind.email("Household").is_some());
+        assert!(notifications[1].kind.email("Household").is_none());
+    }
+}
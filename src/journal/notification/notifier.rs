@@ -0,0 +1,143 @@
+use crate::authn::AuthnService;
+use crate::journal::JournalService;
+use crate::journal::domain::JournalDomainEvent;
+use crate::journal::notification::outbox::EmailSender;
+use crate::journal::notification::{Recipients, notifications_for};
+use crate::time_provider::TimeProvider;
+use async_trait::async_trait;
+use axum_login::tracing;
+use chrono::TimeDelta;
+use disintegrate::{EventListener, PersistedEvent, StreamQuery, query};
+use disintegrate_postgres::PgEventId;
+
+/// events older than this don't notify anyone, so that a new listener
+/// doesn't turn the journal's whole history into notifications
+const MAX_EVENT_AGE_DAYS: i64 = 7;
+
+/// writes a notification for each user that a journal event concerns, and queues emails for
+/// the notifications that ask for it
+#[derive(Clone)]
+pub struct Notifier {
+    query: StreamQuery<PgEventId, JournalDomainEvent>,
+    journal_service: JournalService,
+    authn_service: AuthnService,
+    email_sender: EmailSender,
+}
+
+impl Notifier {
+    pub fn new(
+        journal_service: JournalService,
+        authn_service: AuthnService,
+        email_sender: EmailSender,
+    ) -> Self {
+        Self {
+            query: query!(JournalDomainEvent),
+            journal_service,
+            authn_service,
+            email_sender,
+        }
+    }
+}
+
+#[async_trait]
+impl EventListener<PgEventId, JournalDomainEvent> for Notifier {
+    type Error = sqlx::Error;
+
+    fn id(&self) -> &'static str {
+        "notifier"
+    }
+
+    fn query(&self) -> &StreamQuery<PgEventId, JournalDomainEvent> {
+        &self.query
+    }
+
+    async fn handle(
+        &self,
+        event: PersistedEvent<PgEventId, JournalDomainEvent>,
+    ) -> Result<(), Self::Error> {
+        let event_id = event.id();
+        let event = event.into_inner();
+
+        let notifications = notifications_for(&event);
+
+        let (Some(journal_id), Some(first)) = (event.journal_id(), notifications.first()) else {
+            return Ok(());
+        };
+
+        if self.journal_service.clock().get_time() - first.timestamp
+            > TimeDelta::days(MAX_EVENT_AGE_DAYS)
+        {
+            return Ok(());
+        }
+
+        // the journal projection owns the member list, so wait until it has seen this event
+        self.journal_service.wait_for(event_id).await;
+
+        let Some((journal_name, members)) = self
+            .journal_service
+            .notification_recipients(journal_id)
+            .await?
+        else {
+            return Ok(());
+        };
+
+        let mut emails = Vec::new();
+
+        for notification in notifications {
+            let recipients = match notification.recipients {
+                Recipients::User(user_id) => vec![user_id],
+                Recipients::Members(user_ids) => user_ids
+                    .into_iter()
+                    .filter(|user_id| members.contains(user_id))
+                    .collect(),
+                Recipients::MembersExcept(actor) => members
+                    .iter()
+                    .copied()
+                    .filter(|member| Some(*member) != actor)
+                    .collect(),
+            };
+
+            self.journal_service
+                .create_notifications(
+                    event_id,
+                    journal_id,
+                    &recipients,
+                    &notification.kind.message(&journal_name),
+                    notification.timestamp,
+                )
+                .await?;
+
+            if let Some(email) = notification.kind.email(&journal_name) {
+                emails.push((recipients, email));
+            }
+        }
+
+        // emails are queued once every notification is written, keyed like the notifications
+        // so that handling the event again doesn't email anyone twice
+        for (recipients, (subject, body)) in &emails {
+            match self.authn_service.fetch_users(recipients).await {
+                Ok(users) => {
+                    for user in users {
+                        self.journal_service
+                            .queue_email(
+                                &format!("notification/{event_id}/{}", user.id),
+                                &user.email,
+                                subject,
+                                body,
+                            )
+                            .await?;
+                    }
+                }
+                Err(error) => {
+                    tracing::error!(?error, "failed to fetch the users to email a notification")
+                }
+            }
+        }
+
+        if !emails.is_empty() {
+            self.email_sender.queued();
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,151 @@
+use crate::journal::JournalService;
+use crate::journal::service::PendingEmail;
+use crate::mailer::Mailer;
+use crate::shutdown;
+use axum_login::tracing;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::Notify;
+
+const MAX_SEND_ATTEMPTS: i32 = 8;
+const SEND_BATCH_SIZE: i64 = 50;
+const OUTBOX_POLL_INTERVAL: Duration = Duration::from_secs(30);
+/// how long a claimed batch is kept from other instances. it outlasts a batch whose every
+/// attempt times out, and a batch abandoned by a crash is retried once it runs out
+const SEND_LEASE: Duration = Duration::from_secs(600);
+
+/// sends the emails queued in the outbox. emails are queued rather than sent where they're
+/// written, so a mail server that's down delays them instead of losing them
+#[derive(Clone)]
+pub struct EmailSender {
+    journal_service: JournalService,
+    mailer: Arc<dyn Mailer>,
+    queued: Arc<Notify>,
+}
+
+impl EmailSender {
+    pub fn new(journal_service: JournalService, mailer: Arc<dyn Mailer>) -> Self {
+        Self {
+            journal_service,
+            mailer,
+            queued: Arc::new(Notify::new()),
+        }
+    }
+
+    /// wakes the sender once emails were queued, instead of leaving them until its next poll
+    pub fn queued(&self) {
+        self.queued.notify_one();
+    }
+
+    /// sends queued emails until shutdown. an email is only marked sent once the mail server
+    /// accepts it, so it's retried across restarts. every instance runs this, and each claims
+    /// its emails before sending them
+    pub async fn send_pending(self) {
+        let mut shutdown = Box::pin(shutdown());
+
+        loop {
+            match self
+                .journal_service
+                .claim_emails(MAX_SEND_ATTEMPTS, SEND_BATCH_SIZE, SEND_LEASE)
+                .await
+            {
+                Ok(emails) => {
+                    for email in emails {
+                        self.attempt(email).await;
+                    }
+                }
+                Err(error) => tracing::error!(?error, "failed to fetch queued emails"),
+            }
+
+            tokio::select! {
+                _ = self.queued.notified() => {},
+                _ = tokio::time::sleep(OUTBOX_POLL_INTERVAL) => {},
+                _ = &mut shutdown => return,
+            }
+        }
+    }
+
+    async fn attempt(&self, email: PendingEmail) {
+        let recorded = match self
+            .mailer
+            .send(&email.recipient, &email.subject, email.body)
+            .await
+        {
+            Ok(()) => self.journal_service.mark_email_sent(email.id).await,
+            Err(error) => {
+                tracing::warn!(email.id, ?error, email.attempts, "failed to send an email");
+                if email.attempts + 1 >= MAX_SEND_ATTEMPTS {
+                    tracing::error!(email.id, "giving up on sending an email");
+                }
+                self.journal_service.mark_email_failed(email.id).await
+            }
+        };
+
+        if let Err(error) = recorded {
+            tracing::error!(?error, email.id, "failed to record an email attempt");
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::authority::{Actor, Authority};
+    use crate::journal::Permissions;
+    use crate::test_app::{TestApp, assert_accepted};
+    use std::time::Duration;
+
+    #[tokio::test]
+    async fn mentions_are_emailed_through_the_outbox() {
+        let Some(app) = TestApp::spawn().await else {
+            return;
+        };
+
+        let owner = app.signup("owner").await;
+        let member = app.signup("member").await;
+        let journal_id = owner.create_journal(&app, "Bakery").await;
+        assert_accepted(&owner.create_account(journal_id, "Till").await);
+        assert_accepted(&owner.create_account(journal_id, "Flour").await);
+        assert_accepted(&owner.invite(journal_id, &member, Permissions::READ).await);
+
+        let till = app.account_id(journal_id, "Till").await;
+        let flour = app.account_id(journal_id, "Flour").await;
+        assert_accepted(
+            &owner
+                .post_transaction(journal_id, till, flour, "25.00")
+                .await,
+        );
+
+        let (transaction, _, _) = app
+            .state
+            .journal_service
+            .list_journal_transactions(journal_id, &Authority::Direct(Actor::System))
+            .await
+            .expect("failed to list the transactions")
+            .remove(0);
+
+        assert_accepted(
+            &owner
+                .client
+                .post(&format!(
+                    "/journal/{journal_id}/transaction/{}/comment",
+                    transaction.id
+                ))
+                .form(&[("body", format!("@{} is this right?", member.email))])
+                .await,
+        );
+
+        // the notifier queues the email, and the sender picks it up once woken
+        let mut sent = Vec::new();
+        for _ in 0..100 {
+            sent = app.mailer.sent_to(&member.email);
+            if !sent.is_empty() {
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(50)).await;
+        }
+
+        assert_eq!(sent.len(), 1);
+        assert!(sent[0].1.contains("is this right?"));
+        assert!(app.mailer.sent_to(&owner.email).is_empty());
+    }
+}
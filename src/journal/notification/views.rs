@@ -0,0 +1,114 @@
+use crate::BackendType;
+use crate::StateType;
+use crate::authn::get_user;
+use crate::journal::layout::layout;
+use crate::monkesto_error::MonkestoError;
+use crate::monkesto_error::UrlError;
+use crate::monkesto_error::{ErrorRedirect, OrRedirect};
+use axum::Json;
+use axum::extract::Query;
+use axum::extract::State;
+use axum::response::Redirect;
+use axum_login::AuthSession;
+use maud::Markup;
+use maud::html;
+use serde::{Deserialize, Serialize};
+
+const NOTIFICATION_PAGE_SIZE: i64 = 50;
+
+pub async fn notification_list_page(
+    State(state): State<StateType>,
+    session: AuthSession<BackendType>,
+    Query(err): Query<UrlError>,
+) -> Result<Markup, Redirect> {
+    let user = get_user(session)?;
+
+    let content = html! {
+        div class="flex flex-col gap-4 mx-auto w-full max-w-4xl" {
+            div class="flex justify-between items-center" {
+                h2 class="text-lg font-semibold text-gray-900 dark:text-white" { "Notifications" }
+                form method="post" action="/notifications/read" {
+                    button
+                    type="submit"
+                    class="text-sm font-medium text-indigo-600 hover:text-indigo-500 dark:text-indigo-400 dark:hover:text-indigo-300" {
+                        "Mark all as read"
+                    }
+                }
+            }
+
+            @match state.journal_service.list_notifications(user.id, NOTIFICATION_PAGE_SIZE).await {
+                Ok(notifications) if notifications.is_empty() => {
+                    p class="text-center text-sm text-gray-500 dark:text-gray-400" {
+                        "You don't have any notifications"
+                    }
+                }
+                Ok(notifications) => {
+                    @for notification in notifications {
+                        div class={
+                            "flex justify-between items-center gap-4 p-4 border rounded-xl "
+                            @if notification.read {
+                                "bg-white dark:bg-gray-800 border-gray-200 dark:border-gray-700"
+                            } @else {
+                                "bg-indigo-50 dark:bg-indigo-900/30 border-indigo-200 dark:border-indigo-800"
+                            }
+                        } {
+                            div class="min-w-0 space-y-1" {
+                                a
+                                href=(format!("/journal/{}", notification.journal_id))
+                                class="text-sm font-medium text-gray-900 dark:text-white hover:underline" {
+                                    (notification.message)
+                                }
+                                p class="text-xs text-gray-500 dark:text-gray-400" {
+                                    (user.timezone().format(notification.created_at))
+                                }
+                            }
+                            @if !notification.read {
+                                form method="post" action=(format!("/notifications/{}/read", notification.id)) {
+                                    button
+                                    type="submit"
+                                    class="px-3 py-1 text-sm font-medium bg-white dark:bg-gray-700 border border-gray-300 dark:border-gray-600 rounded-md text-gray-700 dark:text-gray-200 hover:bg-gray-50 dark:hover:bg-gray-600" {
+                                        "Mark as read"
+                                    }
+                                }
+                            }
+                        }
+                    }
+                }
+                Err(e) => {
+                    p class="text-gray-500 dark:text-gray-400" {
+                        "failed to fetch your notifications: " (e)
+                    }
+                }
+            }
+
+            @if let Some(e) = err.err {
+                p class="text-center text-sm/6 text-gray-500 dark:text-gray-400" {
+                    (format! ("error: {:?}", MonkestoError::decode(&e)))
+                }
+            }
+        }
+    };
+
+    Ok(layout(None, false, None, content))
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct UnreadCount {
+    pub unread: u64,
+}
+
+/// the number of unread notifications, which the navigation bar fetches for its badge
+pub async fn unread_count(
+    State(state): State<StateType>,
+    session: AuthSession<BackendType>,
+) -> Result<Json<UnreadCount>, ErrorRedirect> {
+    let user = get_user(session)?;
+
+    let unread = state
+        .journal_service
+        .unread_notification_count(user.id)
+        .await
+        .or_redirect("/notifications")?;
+
+    Ok(Json(UnreadCount { unread }))
+}
@@ -0,0 +1,287 @@
+use crate::BackendType;
+use crate::StateType;
+use crate::authn::get_user;
+use crate::i18n::{t, tf};
+use crate::journal::access::JournalAccess;
+use crate::journal::layout::journal_layout;
+use crate::journal::print::{PrintHeading, print_button, printable};
+use crate::journal::transaction::views::format_amount;
+use crate::journal::transaction::{BalanceUpdate, EntryType, parse_effective_date};
+use crate::name::Name;
+use crate::time_provider::TimeProvider;
+use axum::extract::{Query, State};
+use axum::response::Redirect;
+use axum_login::AuthSession;
+use maud::{Markup, html};
+use serde::Deserialize;
+use std::collections::HashMap;
+
+/// the dates the report covers. either can be left out to leave that side open
+#[derive(Deserialize, Default)]
+pub struct PayeeRange {
+    from: Option<String>,
+    to: Option<String>,
+}
+
+/// what the journal's transactions with one payee add up to
+#[derive(Debug, PartialEq)]
+pub struct PayeeTotal {
+    pub payee: Name,
+    pub transactions: u64,
+    /// the sum of the transactions' debits, which is how much each of them moved
+    pub spent: u64,
+}
+
+/// totals each payee's transactions, with the payees that were paid the most first
+fn payee_totals(transactions: Vec<(Name, Vec<BalanceUpdate>)>) -> Vec<PayeeTotal> {
+    let mut by_payee: HashMap<Name, (u64, u64)> = HashMap::new();
+
+    for (payee, entries) in transactions {
+        let debits: u64 = entries
+            .iter()
+            .filter(|entry| entry.entry_type == EntryType::Debit)
+            .map(|entry| entry.amount)
+            .sum();
+
+        let (count, spent) = by_payee.entry(payee).or_default();
+        *count += 1;
+        *spent += debits;
+    }
+
+    let mut totals: Vec<PayeeTotal> = by_payee
+        .into_iter()
+        .map(|(payee, (transactions, spent))| PayeeTotal {
+            payee,
+            transactions,
+            spent,
+        })
+        .collect();
+    totals.sort_by(|a, b| {
+        b.spent
+            .cmp(&a.spent)
+            .then_with(|| a.payee.as_ref().cmp(b.payee.as_ref()))
+    });
+
+    totals
+}
+
+pub async fn payee_report_page(
+    State(state): State<StateType>,
+    session: AuthSession<BackendType>,
+    access: JournalAccess,
+    Query(range): Query<PayeeRange>,
+) -> Result<Markup, Redirect> {
+    let user = get_user(session)?;
+    let journal_id = access.journal.id;
+
+    // a bound that isn't a date is left open, the same as one that's been cleared
+    let from = range
+        .from
+        .as_deref()
+        .and_then(|from| parse_effective_date(from).ok());
+    let to = range
+        .to
+        .as_deref()
+        .and_then(|to| parse_effective_date(to).ok());
+
+    let totals_res = state
+        .journal_service
+        .payee_transactions(journal_id, &access.authority(), from, to)
+        .await
+        .map(payee_totals);
+
+    let content = html! {
+        div class="flex flex-col gap-6 mx-auto w-full max-w-4xl" {
+            div class="flex justify-between items-center" {
+                h2 class="text-xl font-semibold text-gray-900 dark:text-white" { (t("payee.title")) }
+                (print_button())
+            }
+            p class="text-sm text-gray-500 dark:text-gray-400" {
+                (t("payee.description"))
+            }
+
+            form method="get" class="flex flex-wrap items-end gap-3" {
+                div {
+                    label for="from" class="block text-xs font-medium text-gray-500 dark:text-gray-400 mb-1" { (t("payee.from")) }
+                    input type="date" id="from" name="from" value=[from.map(|from| from.format("%Y-%m-%d").to_string())]
+                    class="rounded-md border border-gray-300 dark:border-gray-600 bg-white dark:bg-gray-800 px-2 py-1 text-sm text-gray-900 dark:text-white";
+                }
+                div {
+                    label for="to" class="block text-xs font-medium text-gray-500 dark:text-gray-400 mb-1" { (t("payee.to")) }
+                    input type="date" id="to" name="to" value=[to.map(|to| to.format("%Y-%m-%d").to_string())]
+                    class="rounded-md border border-gray-300 dark:border-gray-600 bg-white dark:bg-gray-800 px-2 py-1 text-sm text-gray-900 dark:text-white";
+                }
+                button type="submit" class="px-3 py-1 text-sm bg-white dark:bg-gray-700 border border-gray-300 dark:border-gray-600 rounded-md text-gray-700 dark:text-gray-200 hover:bg-gray-100 dark:hover:bg-gray-600" {
+                    (t("payee.apply"))
+                }
+            }
+
+            @match &totals_res {
+                Ok(totals) if totals.is_empty() => {
+                    p class="text-sm text-gray-500 dark:text-gray-400" { (t("payee.none")) }
+                }
+                Ok(totals) => {
+                    table class="w-full text-sm" {
+                        thead {
+                            tr class="text-left text-gray-500 dark:text-gray-400" {
+                                th class="py-1 font-medium" { (t("payee.payee")) }
+                                th class="py-1 font-medium text-right" { (t("payee.transactions")) }
+                                th class="py-1 font-medium text-right" { (t("payee.spent")) }
+                            }
+                        }
+                        tbody class="divide-y divide-gray-200 dark:divide-gray-700" {
+                            @for total in totals {
+                                tr {
+                                    td class="py-1 text-gray-700 dark:text-gray-300" { (total.payee) }
+                                    td class="py-1 text-right text-gray-700 dark:text-gray-300" { (total.transactions) }
+                                    td class="py-1 text-right font-mono text-gray-900 dark:text-white" { (format_amount(total.spent)) }
+                                }
+                            }
+                        }
+                        tfoot {
+                            tr class="border-t-2 border-gray-300 dark:border-gray-600 font-semibold text-gray-900 dark:text-white" {
+                                td class="py-1" { (t("payee.total")) }
+                                td class="py-1 text-right" { (totals.iter().map(|total| total.transactions).sum::<u64>()) }
+                                td class="py-1 text-right font-mono" { (format_amount(totals.iter().map(|total| total.spent).sum())) }
+                            }
+                        }
+                    }
+                }
+                Err(e) => {
+                    p class="text-gray-500 dark:text-gray-400" {
+                        (tf("payee.failed", &[("error", e)]))
+                    }
+                }
+            }
+        }
+    };
+
+    let period = match (from, to) {
+        (Some(from), Some(to)) => {
+            format!("{} – {}", from.format("%Y-%m-%d"), to.format("%Y-%m-%d"))
+        }
+        (Some(from), None) => tf("payee.since", &[("date", &from.format("%Y-%m-%d"))]),
+        (None, Some(to)) => tf("payee.through", &[("date", &to.format("%Y-%m-%d"))]),
+        (None, None) => t("payee.all_dates").to_string(),
+    };
+    let heading = PrintHeading {
+        journal: access.journal.name.as_ref(),
+        period,
+        timezone: user.timezone(),
+        generated: state.clock.get_time(),
+    };
+
+    Ok(journal_layout(
+        &access.journal,
+        printable(&heading, content),
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::journal::account::AccountId;
+    use crate::test_app::{TestApp, assert_accepted};
+
+    fn paid(payee: &str, amount: u64) -> (Name, Vec<BalanceUpdate>) {
+        let entry = |entry_type| BalanceUpdate {
+            account_id: AccountId::new(),
+            amount,
+            entry_type,
+            memo: None,
+        };
+
+        (
+            Name::try_new(payee.to_string()).expect("invalid name"),
+            vec![entry(EntryType::Debit), entry(EntryType::Credit)],
+        )
+    }
+
+    #[test]
+    fn payees_are_totalled_with_the_most_paid_first() {
+        let totals = payee_totals(vec![
+            paid("Corner Shop", 450),
+            paid("Landlord", 120000),
+            paid("Corner Shop", 1250),
+        ]);
+
+        let summary: Vec<(&str, u64, u64)> = totals
+            .iter()
+            .map(|total| (total.payee.as_ref(), total.transactions, total.spent))
+            .collect();
+        assert_eq!(
+            summary,
+            vec![("Landlord", 1, 120000), ("Corner Shop", 2, 1700)]
+        );
+    }
+
+    #[tokio::test]
+    async fn payees_show_in_the_ledger_and_the_payee_report() {
+        let Some(app) = TestApp::spawn().await else {
+            return;
+        };
+
+        let user = app.signup("payer").await;
+        let journal_id = user.create_journal(&app, "Groceries").await;
+        assert_accepted(&user.create_account(journal_id, "Cash").await);
+        assert_accepted(&user.create_account(journal_id, "Food").await);
+        let cash = app.account_id(journal_id, "Cash").await.to_string();
+        let food = app.account_id(journal_id, "Food").await.to_string();
+
+        for (amount, date) in [("12.50", "2026-09-30"), ("7.25", "2026-10-17")] {
+            let response = user
+                .client
+                .post(&format!("/journal/{journal_id}/transaction"))
+                .form(&[
+                    ("account", food.as_str()),
+                    ("amount", amount),
+                    ("entry_type", "Dr"),
+                    ("account", cash.as_str()),
+                    ("amount", amount),
+                    ("entry_type", "Cr"),
+                    ("payee", " Corner Shop "),
+                    ("effective_date", date),
+                ])
+                .await;
+            assert_accepted(&response);
+        }
+
+        let ledger = user
+            .client
+            .get(&format!("/journal/{journal_id}/transaction"))
+            .clear_headers()
+            .add_header("accept", "text/html")
+            .await
+            .text();
+        assert!(ledger.contains("<option value=\"Corner Shop\">"));
+
+        let export = user
+            .client
+            .get(&format!("/journal/{journal_id}/export.ledger"))
+            .await
+            .text();
+        assert!(export.contains("2026/10/17 Corner Shop  ; Transaction 2\n"));
+
+        let report = user
+            .client
+            .get(&format!("/journal/{journal_id}/report/payees"))
+            .clear_headers()
+            .add_header("accept", "text/html")
+            .await
+            .text();
+        assert!(report.contains("Corner Shop"));
+        assert!(report.contains("$19.75"));
+
+        let october = user
+            .client
+            .get(&format!(
+                "/journal/{journal_id}/report/payees?from=2026-10-01&to=2026-10-31"
+            ))
+            .clear_headers()
+            .add_header("accept", "text/html")
+            .await
+            .text();
+        assert!(october.contains("$7.25"));
+        assert!(!october.contains("$19.75"));
+    }
+}
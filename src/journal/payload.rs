@@ -0,0 +1,326 @@
+use crate::journal::JournalId;
+use crate::journal::domain::JournalDomainEvent;
+use crate::secret::{DecryptError, SecretKey};
+use disintegrate::serde::{Deserializer, Serializer};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use thiserror::Error;
+
+/// starts every encrypted payload. plain messagepack events start with a map, so payloads
+/// written before encryption was turned on still read as they are
+const ENCRYPTED: &[u8; 4] = b"MKE1";
+
+/// a wrapped data key: its nonce, the 32 byte key and the tag
+const WRAPPED_KEY_LEN: usize = 12 + 32 + 16;
+
+#[derive(Debug, Error)]
+pub enum PayloadError {
+    #[error("an event payload is encrypted, but EVENT_ENCRYPTION_KEY isn't set")]
+    MissingKey,
+    #[error(transparent)]
+    Decrypt(#[from] DecryptError),
+    #[error("failed to decode an event payload: {0}")]
+    Decode(#[from] rmp_serde::decode::Error),
+}
+
+#[derive(Clone)]
+struct DataKey {
+    key: SecretKey,
+    wrapped: Vec<u8>,
+}
+
+/// writes journal events as messagepack, encrypted when a master key is configured. each
+/// journal's events are encrypted with the journal's own data key, which is stored wrapped by
+/// the master key in front of every payload, so the master key can decrypt any of them
+#[derive(Clone, Default)]
+pub struct JournalPayloads {
+    master_key: Option<SecretKey>,
+    data_keys: Arc<Mutex<HashMap<JournalId, DataKey>>>,
+}
+
+impl JournalPayloads {
+    pub fn new(master_key: Option<SecretKey>) -> Self {
+        Self {
+            master_key,
+            data_keys: Arc::default(),
+        }
+    }
+
+    /// whether new payloads are written encrypted, in which case sql can't search them
+    pub fn encrypted(&self) -> bool {
+        self.master_key.is_some()
+    }
+
+    /// the journal's data key, made the first time the journal's events are written or read.
+    /// events outside a journal get a key of their own
+    fn data_key(&self, master_key: &SecretKey, journal_id: Option<JournalId>) -> DataKey {
+        let generate = || {
+            let key = SecretKey::generate();
+            DataKey {
+                wrapped: master_key.wrap(&key),
+                key,
+            }
+        };
+
+        match journal_id {
+            Some(journal_id) => self
+                .data_keys
+                .lock()
+                .expect("the data key lock was poisoned")
+                .entry(journal_id)
+                .or_insert_with(generate)
+                .clone(),
+            None => generate(),
+        }
+    }
+
+    /// fields are written by name, so ones added later can be left out of older events. events
+    /// written before that have their fields in order, which still read the same
+    pub fn encode(&self, event: &JournalDomainEvent) -> Vec<u8> {
+        let plaintext = rmp_serde::to_vec_named(event).expect("MessagePack serialization failed");
+
+        let Some(master_key) = &self.master_key else {
+            return plaintext;
+        };
+
+        let data_key = self.data_key(master_key, event.journal_id());
+
+        [
+            ENCRYPTED.as_slice(),
+            &data_key.wrapped,
+            &data_key.key.encrypt(&plaintext),
+        ]
+        .concat()
+    }
+
+    pub fn decode(&self, payload: &[u8]) -> Result<JournalDomainEvent, PayloadError> {
+        let Some(envelope) = payload.strip_prefix(ENCRYPTED) else {
+            return Ok(rmp_serde::from_slice::<JournalDomainEvent>(payload)?.upgrade());
+        };
+
+        let master_key = self.master_key.as_ref().ok_or(PayloadError::MissingKey)?;
+
+        if envelope.len() < WRAPPED_KEY_LEN {
+            return Err(DecryptError.into());
+        }
+        let (wrapped, ciphertext) = envelope.split_at(WRAPPED_KEY_LEN);
+
+        let key = master_key.unwrap(wrapped)?;
+        let event =
+            rmp_serde::from_slice::<JournalDomainEvent>(&key.decrypt(ciphertext)?)?.upgrade();
+
+        // a journal's events are read before more are written, so they keep using its key
+        if let Some(journal_id) = event.journal_id() {
+            self.data_keys
+                .lock()
+                .expect("the data key lock was poisoned")
+                .entry(journal_id)
+                .or_insert_with(|| DataKey {
+                    key,
+                    wrapped: wrapped.to_vec(),
+                });
+        }
+
+        Ok(event)
+    }
+}
+
+impl Serializer<JournalDomainEvent> for JournalPayloads {
+    fn serialize(&self, event: JournalDomainEvent) -> Vec<u8> {
+        self.encode(&event)
+    }
+}
+
+impl Deserializer<JournalDomainEvent> for JournalPayloads {
+    fn deserialize(&self, data: Vec<u8>) -> Result<JournalDomainEvent, disintegrate::serde::Error> {
+        self.decode(&data)
+            .map_err(|e| disintegrate::serde::Error::Deserialization(Box::new(e)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::authn::user::UserId;
+    use crate::authority::{Actor, Authority};
+    use crate::journal::account::AccountId;
+    use crate::journal::appearance::JournalAppearance;
+    use crate::journal::fiscal::FiscalYearStart;
+    use crate::journal::transaction::{BalanceUpdate, EntryType, TransactionId};
+    use crate::name::Name;
+    use chrono::{NaiveDate, TimeZone, Utc};
+    use serde::Serialize;
+
+    const KEY: &str = "000102030405060708090a0b0c0d0e0f101112131415161718191a1b1c1d1e1f";
+
+    fn journal_created(journal_id: JournalId) -> JournalDomainEvent {
+        let owner = UserId::new();
+
+        JournalDomainEvent::JournalCreated {
+            journal_id,
+            name: Name::try_new("Household".to_string()).expect("invalid name"),
+            owner,
+            authority: Authority::Direct(Actor::User(owner)),
+            timestamp: Utc::now(),
+        }
+    }
+
+    #[test]
+    fn encrypted_payloads_hide_the_event_and_read_back() {
+        let key = SecretKey::from_hex(KEY).expect("failed to read the key");
+        let payloads = JournalPayloads::new(Some(key.clone()));
+        let event = journal_created(JournalId::new());
+
+        let payload = payloads.encode(&event);
+        assert!(!payload.windows(9).any(|w| w == b"Household"));
+
+        // a restarted server only has the master key
+        let restarted = JournalPayloads::new(Some(key));
+        assert_eq!(restarted.decode(&payload).ok(), Some(event));
+    }
+
+    #[test]
+    fn a_journals_events_share_its_data_key() {
+        let key = SecretKey::from_hex(KEY).expect("failed to read the key");
+        let payloads = JournalPayloads::new(Some(key));
+        let journal_id = JournalId::new();
+
+        let first = payloads.encode(&journal_created(journal_id));
+        let second = payloads.encode(&journal_created(journal_id));
+        let other = payloads.encode(&journal_created(JournalId::new()));
+
+        let wrapped = |payload: &[u8]| payload[..ENCRYPTED.len() + WRAPPED_KEY_LEN].to_vec();
+        assert_eq!(wrapped(&first), wrapped(&second));
+        assert_ne!(wrapped(&first), wrapped(&other));
+    }
+
+    #[test]
+    fn plain_payloads_still_read_once_encryption_is_on() {
+        let event = journal_created(JournalId::new());
+        let plain = JournalPayloads::default().encode(&event);
+
+        let key = SecretKey::from_hex(KEY).expect("failed to read the key");
+        assert_eq!(
+            JournalPayloads::new(Some(key)).decode(&plain).ok(),
+            Some(event.clone())
+        );
+
+        let encrypted = JournalPayloads::new(Some(
+            SecretKey::from_hex(KEY).expect("failed to read the key"),
+        ))
+        .encode(&event);
+        assert!(matches!(
+            JournalPayloads::default().decode(&encrypted),
+            Err(PayloadError::MissingKey)
+        ));
+    }
+
+    #[test]
+    fn transactions_recorded_before_their_later_fields_still_read() {
+        // how transactions were written before they had dates, numbers or payees
+        #[derive(Serialize)]
+        struct Entry {
+            account_id: AccountId,
+            amount: u64,
+            entry_type: EntryType,
+        }
+
+        #[derive(Serialize)]
+        enum Event {
+            TransactionCreated {
+                transaction_id: TransactionId,
+                journal_id: JournalId,
+                balance_updates: Vec<Entry>,
+                authority: Authority,
+                timestamp: chrono::DateTime<Utc>,
+            },
+        }
+
+        let (transaction_id, journal_id, account_id) =
+            (TransactionId::new(), JournalId::new(), AccountId::new());
+        let authority = Authority::Direct(Actor::System);
+        let timestamp = Utc.with_ymd_and_hms(2024, 3, 9, 12, 0, 0).unwrap();
+
+        let payload = rmp_serde::to_vec(&Event::TransactionCreated {
+            transaction_id,
+            journal_id,
+            balance_updates: vec![Entry {
+                account_id,
+                amount: 500,
+                entry_type: EntryType::Debit,
+            }],
+            authority: authority.clone(),
+            timestamp,
+        })
+        .expect("failed to encode the event");
+
+        let event = JournalPayloads::default()
+            .decode(&payload)
+            .expect("failed to decode the event");
+
+        assert_eq!(
+            event,
+            JournalDomainEvent::TransactionCreated {
+                transaction_id,
+                journal_id,
+                balance_updates: vec![BalanceUpdate {
+                    account_id,
+                    amount: 500,
+                    entry_type: EntryType::Debit,
+                    memo: None,
+                }],
+                authority,
+                timestamp,
+                number: 0,
+                effective_date: NaiveDate::from_ymd_opt(2024, 3, 9).unwrap(),
+                reverses: None,
+                transfer: None,
+                payee: None,
+            }
+        );
+
+        // events written by name read back the same, whatever order their fields are in
+        let payloads = JournalPayloads::default();
+        assert_eq!(payloads.decode(&payloads.encode(&event)).ok(), Some(event));
+    }
+
+    #[test]
+    fn settings_recorded_before_later_settings_read_with_their_defaults() {
+        // how the settings were written when a journal only had a fiscal year
+        #[derive(Serialize)]
+        enum Event {
+            JournalSettingsUpdated {
+                journal_id: JournalId,
+                fiscal_year_start: FiscalYearStart,
+                authority: Authority,
+                timestamp: chrono::DateTime<Utc>,
+            },
+        }
+
+        let journal_id = JournalId::new();
+        let fiscal_year_start = FiscalYearStart::try_new(4).expect("invalid fiscal year start");
+        let authority = Authority::Direct(Actor::System);
+        let timestamp = Utc::now();
+
+        let payload = rmp_serde::to_vec(&Event::JournalSettingsUpdated {
+            journal_id,
+            fiscal_year_start,
+            authority: authority.clone(),
+            timestamp,
+        })
+        .expect("failed to encode the event");
+
+        assert_eq!(
+            JournalPayloads::default().decode(&payload).ok(),
+            Some(JournalDomainEvent::JournalSettingsUpdated {
+                journal_id,
+                fiscal_year_start,
+                authority,
+                timestamp,
+                default_cash_account: None,
+                default_expense_account: None,
+                appearance: JournalAppearance::default(),
+            })
+        );
+    }
+}
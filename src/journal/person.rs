@@ -1,63 +1,41 @@
 use crate::BackendType;
 use crate::StateType;
-use crate::authn::get_user;
-use crate::authn::user::UserId;
+use crate::authn::profile::avatar;
+use crate::authn::user::{UserId, UserState};
 use crate::authority::Actor;
 use crate::authority::Authority;
-use crate::journal::JournalId;
 use crate::journal::Permissions;
-use crate::journal::layout::layout;
+use crate::journal::access::{JournalAccess, Requirement};
+use crate::journal::layout::journal_layout;
+use crate::journal::undo;
 use crate::monkesto_error::MonkestoError;
 use crate::monkesto_error::UrlError;
+use crate::time_provider::TimeProvider;
+use crate::validation::FormState;
 use axum::extract::Path;
 use axum::extract::Query;
 use axum::extract::State;
-use axum::response::Redirect;
 use axum_login::AuthSession;
 use maud::Markup;
 use maud::html;
+use std::collections::HashMap;
 use std::str::FromStr;
+use tower_sessions::Session;
 
 // TODO: Fix This! Super messy and hard to work with.
 pub async fn person_detail_page(
     State(state): State<StateType>,
-    session: AuthSession<BackendType>,
+    access: JournalAccess,
     Path((id, person_id)): Path<(String, String)>,
     Query(err): Query<UrlError>,
-) -> Result<Markup, Redirect> {
-    let user = get_user(session)?;
-    let authority = Authority::Direct(Actor::User(user.id));
-
-    let journal_id_res = JournalId::from_str(&id);
-    let target_user_id_res = UserId::from_str(&person_id);
-
-    let journal_id = match journal_id_res {
-        Ok(jid) => jid,
-        Err(_) => {
-            return Ok(layout(
-                None,
-                true,
-                None,
-                html! {
-                    div class="max-w-2xl mx-auto py-8 px-4" {
-                        div class="bg-red-50 dark:bg-red-900/30 border-l-4 border-red-400 p-4" {
-                            p class="text-sm text-red-700 dark:text-red-200" {
-                                "Invalid journal ID"
-                            }
-                        }
-                    }
-                },
-            ));
-        }
-    };
+) -> Markup {
+    let journal_id = access.journal.id;
 
-    let target_user_id = match target_user_id_res {
+    let target_user_id = match UserId::from_str(&person_id) {
         Ok(tuid) => tuid,
         Err(_) => {
-            return Ok(layout(
-                None,
-                true,
-                Some(&id),
+            return journal_layout(
+                &access.journal,
                 html! {
                     div class="max-w-2xl mx-auto py-8 px-4" {
                         div class="bg-red-50 dark:bg-red-900/30 border-l-4 border-red-400 p-4" {
@@ -67,32 +45,7 @@ pub async fn person_detail_page(
                         }
                     }
                 },
-            ));
-        }
-    };
-
-    let journal_state_res = state
-        .journal_service
-        .get_journal(journal_id, &authority)
-        .await;
-
-    let (journal_state, _, _) = match journal_state_res {
-        Ok(js) => js,
-        Err(e) => {
-            return Ok(layout(
-                None,
-                true,
-                None,
-                html! {
-                    div class="max-w-2xl mx-auto py-8 px-4" {
-                        div class="bg-red-50 dark:bg-red-900/30 border-l-4 border-red-400 p-4" {
-                            p class="text-sm text-red-700 dark:text-red-200" {
-                                (format!("Error loading journal: {}", e))
-                            }
-                        }
-                    }
-                },
-            ));
+            );
         }
     };
 
@@ -103,10 +56,8 @@ pub async fn person_detail_page(
     {
         Ok(perms) => perms,
         Err(_e) => {
-            return Ok(layout(
-                Some(journal_state.name.as_ref()),
-                true,
-                Some(&id),
+            return journal_layout(
+                &access.journal,
                 html! {
                     div class="max-w-2xl mx-auto py-8 px-4" {
                         div class="bg-red-50 dark:bg-red-900/30 border-l-4 border-red-400 p-4" {
@@ -116,7 +67,7 @@ pub async fn person_detail_page(
                         }
                     }
                 },
-            ));
+            );
         }
     };
 
@@ -129,7 +80,7 @@ pub async fn person_detail_page(
         div class="max-w-2xl mx-auto py-8 px-4" {
             div class="flex justify-between items-center mb-8" {
                 h2 class="text-2xl font-bold text-gray-900 dark:text-white" { (target_email) }
-                @if journal_state.owner_id == target_user_id {
+                @if access.journal.owner_id == target_user_id {
                     span class="inline-flex items-center rounded-md bg-indigo-50 dark:bg-indigo-900/30 px-2 py-1 text-xs font-medium text-indigo-700 dark:text-indigo-300 ring-1 ring-inset ring-indigo-700/10 dark:ring-indigo-400/30" { "Owner" }
                 }
             }
@@ -144,6 +95,8 @@ pub async fn person_detail_page(
                             (permission_checkbox("read", "Read Access", permissions.contains(Permissions::READ)))
                             (permission_checkbox("add_account", "Add Accounts", permissions.contains(Permissions::ADD_ACCOUNT)))
                             (permission_checkbox("append_transaction", "Append Transactions", permissions.contains(Permissions::APPEND_TRANSACTION)))
+                            (permission_checkbox("submit_only", "Submit Transactions for Approval", permissions.contains(Permissions::SUBMIT_ONLY)))
+                            (permission_checkbox("approve", "Approve Transactions", permissions.contains(Permissions::APPROVE)))
                             (permission_checkbox("invite", "Invite Users", permissions.contains(Permissions::INVITE)))
                         }
 
@@ -189,12 +142,7 @@ pub async fn person_detail_page(
         }
     };
 
-    Ok(layout(
-        Some(journal_state.name.as_ref()),
-        true,
-        Some(&id),
-        wrapped_content,
-    ))
+    journal_layout(&access.journal, wrapped_content)
 }
 
 fn permission_checkbox(name: &'static str, label: &'static str, checked: bool) -> Markup {
@@ -219,44 +167,78 @@ fn permission_checkbox(name: &'static str, label: &'static str, checked: bool) -
 pub async fn people_list_page(
     State(state): State<StateType>,
     session: AuthSession<BackendType>,
-    Path(id): Path<String>,
+    access: JournalAccess,
     Query(err): Query<UrlError>,
-) -> Result<Markup, Redirect> {
-    let user = get_user(session)?;
-
-    let user_authority = &Authority::Direct(Actor::User(user.id));
+) -> Markup {
+    people_list(
+        &state,
+        &session.session,
+        &access,
+        err.err,
+        &FormState::default(),
+    )
+    .await
+}
 
-    let journal_id_res = JournalId::from_str(&id);
+/// the people in a journal, with the invite form filled in from a rejected submission
+pub async fn people_list<R: Requirement>(
+    state: &StateType,
+    browser_session: &Session,
+    access: &JournalAccess<R>,
+    err: Option<String>,
+    form: &FormState,
+) -> Markup {
+    let journal_id = access.journal.id;
+    let id = &journal_id.to_string();
+
+    // a rejected invite keeps the permissions that were ticked
+    let submitted = form.value("email").is_some();
+    let checked = |name: &str, default: bool| {
+        if submitted {
+            form.value(name).is_some()
+        } else {
+            default
+        }
+    };
 
     let content = html! {
-        @if let Ok(journal_id) = journal_id_res {
-            @match state.journal_service.list_journal_members(journal_id, &Authority::Direct(Actor::User(user.id))).await {
-                Ok(users) => {
-                    @for user_id in users {
-                        a
-                        href=(format!("/journal/{}/person/{}", id, user_id))
-                        class="block p-4 bg-white dark:bg-gray-800 border border-gray-200 dark:border-gray-700 rounded-xl hover:bg-gray-50 dark:hover:bg-gray-700 transition-colors" {
-                            h3 class="text-lg font-semibold text-gray-900 dark:text-white" {
-                                @match state.authn_service.fetch_user(user_id).await {
-                                    Ok(user) => (user.email),
-                                    Err(e) => (format!("failed to fetch email: {:?}", e)),
+        (undo::banner(browser_session, journal_id, &format!("/journal/{}/person", journal_id), state.clock.get_time()).await)
+
+        @match state.journal_service.list_journal_members(journal_id, &access.authority()).await {
+            Ok(users) => {
+                @match state.authn_service.fetch_users(users.as_slice()).await {
+                    Ok(members) => {
+                        @let members: HashMap<UserId, UserState> = members.into_iter().map(|m| (m.id, m)).collect();
+                        @for user_id in users {
+                            a
+                            href=(format!("/journal/{}/person/{}", id, user_id))
+                            class="block p-4 bg-white dark:bg-gray-800 border border-gray-200 dark:border-gray-700 rounded-xl hover:bg-gray-50 dark:hover:bg-gray-700 transition-colors" {
+                                h3 class="flex items-center gap-2 text-lg font-semibold text-gray-900 dark:text-white" {
+                                    @match members.get(&user_id) {
+                                        Some(member) => {
+                                            (avatar(member))
+                                            (member.display_name())
+                                        },
+                                        None => (format!("failed to fetch email: {}", user_id)),
+                                    }
                                 }
                             }
                         }
-                    }
-                },
-                Err(e) => {
-                    div class="flex justify-center items-center h-full" {
-                        p class="text-gray-500 dark:text-gray-400" {
-                            (format!("An error occurred while fetching users: {}", e))
+                    },
+                    Err(e) => {
+                        div class="flex justify-center items-center h-full" {
+                            p class="text-gray-500 dark:text-gray-400" {
+                                (format!("failed to fetch emails: {:?}", e))
+                            }
                         }
                     }
                 }
-            }
-        } @else {
-            div class="flex justify-center items-center h-full" {
-                p class="text-gray-500 dark:text-gray-400" {
-                    "Invalid journal id"
+            },
+            Err(e) => {
+                div class="flex justify-center items-center h-full" {
+                    p class="text-gray-500 dark:text-gray-400" {
+                        (format!("An error occurred while fetching users: {}", e))
+                    }
                 }
             }
         }
@@ -277,24 +259,32 @@ pub async fn people_list_page(
                         id="email"
                         type="text"
                         name="email"
+                        value=[form.value("email")]
                         required
+                        aria-invalid=[form.invalid("email")]
+                        aria-describedby=[form.described_by("email")]
                         placeholder="Enter email to invite"
                         class="block w-full rounded-md bg-white px-3 py-1.5 text-base text-gray-900 outline-1 -outline-offset-1 outline-gray-300 placeholder:text-gray-400 focus:outline-2 focus:-outline-offset-2 focus:outline-indigo-600 sm:text-sm/6 dark:bg-white/5 dark:text-white dark:outline-white/10 dark:placeholder:text-gray-500 dark:focus:outline-indigo-500"
                         ;
                     }
+                    (form.message("email"))
                 }
 
                 div class="space-y-4" {
                     p class="block text-sm/6 font-medium text-gray-900 dark:text-gray-100" {
                         "Permissions"
                     }
-                    (permission_checkbox("read", "Read Access", true))
-                    (permission_checkbox("addaccount", "Add Accounts", true))
-                    (permission_checkbox("appendtransaction", "Append Transactions", true))
-                    (permission_checkbox("invite", "Invite Users", false))
-                    (permission_checkbox("delete", "Delete Journal", false))
+                    (permission_checkbox("read", "Read Access", checked("read", true)))
+                    (permission_checkbox("add_account", "Add Accounts", checked("add_account", true)))
+                    (permission_checkbox("append_transaction", "Append Transactions", checked("append_transaction", true)))
+                    (permission_checkbox("submit_only", "Submit Transactions for Approval", checked("submit_only", false)))
+                    (permission_checkbox("approve", "Approve Transactions", checked("approve", false)))
+                    (permission_checkbox("invite", "Invite Users", checked("invite", false)))
+                    (permission_checkbox("delete", "Delete Journal", checked("delete", false)))
                 }
 
+                (form.summary())
+
                 div {
                     button
                     type="submit"
@@ -304,7 +294,7 @@ pub async fn people_list_page(
                 }
             }
 
-            @if let Some(e) = err.err {
+            @if let Some(e) = err {
                 p {
                     (format!("An error occurred: {:?}", MonkestoError::decode(&e)))
                 }
@@ -318,21 +308,5 @@ pub async fn people_list_page(
         }
     };
 
-    let journal_name = if let Ok(id) = journal_id_res {
-        state
-            .journal_service
-            .get_journal(id, user_authority)
-            .await
-            .map(|(j, _, _)| j.name.to_string())
-            .unwrap_or_else(|e| format!("failed to fetch the journal name: {e}"))
-    } else {
-        "invalid journal id".to_string()
-    };
-
-    Ok(layout(
-        Some(&journal_name),
-        true,
-        Some(&id),
-        wrapped_content,
-    ))
+    journal_layout(&access.journal, wrapped_content)
 }
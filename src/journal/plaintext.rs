@@ -0,0 +1,916 @@
+use crate::StateType;
+use crate::journal::JournalError;
+use crate::journal::access::{AppendTransaction, JournalAccess};
+use crate::journal::account::AccountId;
+use crate::journal::layout::layout;
+use crate::journal::service::{AccountState, TransactionState};
+use crate::journal::transaction::views::format_amount;
+use crate::journal::transaction::{BatchRowError, EntryType, TransactionValidationError, bulk};
+use crate::monkesto_error::{ErrorRedirect, MonkestoError, MonkestoResult, OrRedirect};
+use crate::parse;
+use axum::Form;
+use axum::extract::State;
+use axum::http::StatusCode;
+use axum::http::header::{CONTENT_DISPOSITION, CONTENT_TYPE};
+use axum::response::{IntoResponse, Response};
+use chrono::NaiveDate;
+use maud::{Markup, html};
+use std::collections::{HashMap, HashSet};
+use std::fmt::Write;
+
+/// the plaintext accounting tools a journal can be exported to
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PlaintextFormat {
+    Beancount,
+    Ledger,
+}
+
+/// beancount only accepts accounts under one of these
+const BEANCOUNT_ROOTS: [&str; 5] = ["Assets", "Liabilities", "Equity", "Income", "Expenses"];
+
+impl PlaintextFormat {
+    fn extension(&self) -> &'static str {
+        match self {
+            Self::Beancount => "beancount",
+            Self::Ledger => "ledger",
+        }
+    }
+
+    /// a single component of an account's path. ledger takes anything without a run of
+    /// spaces, while beancount wants a capitalised word of letters, digits, and dashes
+    fn component(&self, name: &str) -> String {
+        let words = name.split_whitespace();
+
+        match self {
+            Self::Ledger => words.collect::<Vec<_>>().join(" "),
+            Self::Beancount => {
+                let component = words
+                    .map(|word| {
+                        word.chars()
+                            .map(|c| if c.is_alphanumeric() { c } else { '-' })
+                            .collect::<String>()
+                    })
+                    .collect::<Vec<_>>()
+                    .join("-");
+
+                let mut chars = component.chars();
+                match chars.next() {
+                    Some(first) if first.is_alphanumeric() => {
+                        first.to_uppercase().chain(chars).collect()
+                    }
+                    _ => format!("X{component}"),
+                }
+            }
+        }
+    }
+
+    /// the account's path, where a colon in its name starts a sub-account. beancount needs one
+    /// of its root accounts first, so an account named without one is put under assets when
+    /// its balance is a debit, and under liabilities otherwise
+    fn path(&self, name: &str, balance: i64) -> String {
+        let mut components: Vec<String> = name
+            .split(':')
+            .filter(|component| !component.trim().is_empty())
+            .map(|component| self.component(component))
+            .collect();
+
+        if *self == Self::Beancount {
+            match components.first() {
+                Some(first) if BEANCOUNT_ROOTS.contains(&first.as_str()) => {}
+                _ => components.insert(
+                    0,
+                    if balance < 0 { "Assets" } else { "Liabilities" }.to_string(),
+                ),
+            }
+        }
+
+        components.join(":")
+    }
+
+    fn amount(&self, entry_type: EntryType, amount: u64) -> String {
+        // both tools record debits as positive amounts
+        let sign = match entry_type {
+            EntryType::Debit => "",
+            EntryType::Credit => "-",
+        };
+
+        match self {
+            Self::Beancount => format!("{sign}{}.{:02} USD", amount / 100, amount % 100),
+            Self::Ledger => format!("{sign}${}.{:02}", amount / 100, amount % 100),
+        }
+    }
+}
+
+/// the path of every account, and of any deleted account the transactions post to, which is
+/// named by its id. accounts whose names map to the same path are numbered to keep them apart
+fn account_paths(
+    format: PlaintextFormat,
+    accounts: &[AccountState],
+    transactions: &[TransactionState],
+) -> HashMap<AccountId, String> {
+    let mut paths = HashMap::new();
+    let mut taken = HashSet::new();
+
+    let deleted: Vec<(AccountId, String)> = transactions
+        .iter()
+        .flat_map(|transaction| &transaction.entries)
+        .filter(|entry| {
+            !accounts
+                .iter()
+                .any(|account| account.id == entry.account_id)
+        })
+        .map(|entry| (entry.account_id, format!("Deleted {}", entry.account_id)))
+        .collect();
+
+    let named = accounts
+        .iter()
+        .map(|account| {
+            (
+                account.id,
+                format.path(account.name.as_ref(), account.balance),
+            )
+        })
+        .chain(
+            deleted
+                .into_iter()
+                .map(|(id, name)| (id, format.path(&name, 0))),
+        );
+
+    for (account_id, path) in named {
+        if paths.contains_key(&account_id) {
+            continue;
+        }
+
+        let mut unique = path.clone();
+        let mut number = 2;
+        while !taken.insert(unique.clone()) {
+            unique = format!("{path}-{number}");
+            number += 1;
+        }
+
+        paths.insert(account_id, unique);
+    }
+
+    paths
+}
+
+/// renders the transactions, which should be in date order, along with the directives
+/// beancount needs to open each account before it's posted to
+pub fn render(
+    format: PlaintextFormat,
+    accounts: &[AccountState],
+    transactions: &[TransactionState],
+    opened: NaiveDate,
+) -> String {
+    let paths = account_paths(format, accounts, transactions);
+    let mut out = String::new();
+
+    if format == PlaintextFormat::Beancount {
+        let mut sorted: Vec<&String> = paths.values().collect();
+        sorted.sort();
+
+        for path in sorted {
+            writeln!(out, "{} open {path}", opened.format("%Y-%m-%d"))
+                .expect("writing to a string can't fail");
+        }
+    }
+
+    for transaction in transactions {
+        let description = match transaction.reverses {
+            Some(_) => format!("Transaction {} (reversal)", transaction.number),
+            None => format!("Transaction {}", transaction.number),
+        };
+
+        out.push('\n');
+        match (format, &transaction.payee) {
+            (PlaintextFormat::Beancount, Some(payee)) => writeln!(
+                out,
+                "{} * \"{}\" \"{description}\"",
+                transaction.effective_date.format("%Y-%m-%d"),
+                payee.as_ref().replace('\\', "\\\\").replace('"', "\\\"")
+            ),
+            (PlaintextFormat::Beancount, None) => writeln!(
+                out,
+                "{} * \"{description}\"",
+                transaction.effective_date.format("%Y-%m-%d")
+            ),
+            // ledger reads the text after the date as the payee
+            (PlaintextFormat::Ledger, Some(payee)) => writeln!(
+                out,
+                "{} {payee}  ; {description}",
+                transaction.effective_date.format("%Y/%m/%d")
+            ),
+            (PlaintextFormat::Ledger, None) => writeln!(
+                out,
+                "{} {description}",
+                transaction.effective_date.format("%Y/%m/%d")
+            ),
+        }
+        .expect("writing to a string can't fail");
+
+        for entry in &transaction.entries {
+            write!(
+                out,
+                "    {}  {}",
+                paths[&entry.account_id],
+                format.amount(entry.entry_type, entry.amount)
+            )
+            .expect("writing to a string can't fail");
+            // both formats read the rest of a line after a semicolon as a comment
+            if let Some(memo) = &entry.memo {
+                write!(out, "  ; {memo}").expect("writing to a string can't fail");
+            }
+            out.push('\n');
+        }
+    }
+
+    out
+}
+
+/// the beancount directives that share a dated line with transactions, but aren't one
+const BEANCOUNT_DIRECTIVES: [&str; 11] = [
+    "open",
+    "close",
+    "balance",
+    "pad",
+    "note",
+    "document",
+    "price",
+    "event",
+    "commodity",
+    "custom",
+    "query",
+];
+
+/// a transaction read from a ledger or beancount file, with its accounts still named by path
+#[derive(Debug, PartialEq)]
+pub struct PlaintextTransaction {
+    /// the line of the file the transaction started on
+    pub line: u64,
+    pub date: NaiveDate,
+    pub description: String,
+    /// each account and the amount posted to it in cents, where debits are positive
+    pub postings: Vec<(String, i64)>,
+}
+
+fn invalid(reason: impl Into<String>) -> JournalError {
+    JournalError::TransactionValidation(TransactionValidationError::InvalidPlaintext(reason.into()))
+}
+
+fn parse_date(s: &str) -> Option<NaiveDate> {
+    // ledger can follow the date with an auxiliary date after an `=`
+    let s = s.split('=').next().unwrap_or_default();
+
+    NaiveDate::parse_from_str(s, "%Y-%m-%d")
+        .or_else(|_| NaiveDate::parse_from_str(s, "%Y/%m/%d"))
+        .ok()
+}
+
+/// the text after a transaction's date and flag. beancount quotes the payee and the narration,
+/// and the narration is the last quoted string, while ledger writes the payee as is
+fn parse_description(rest: &str) -> String {
+    let rest = match rest.split_once(char::is_whitespace) {
+        Some(("txn", after)) => after.trim_start(),
+        _ if rest == "txn" => "",
+        _ => rest,
+    };
+    let rest = rest.trim_start_matches(['*', '!']).trim_start();
+    let rest = match rest.strip_prefix('(').and_then(|r| r.split_once(')')) {
+        Some((_, after_code)) => after_code.trim(),
+        None => rest.trim(),
+    };
+
+    let quoted: Vec<&str> = rest.split('"').skip(1).step_by(2).collect();
+    match quoted.last() {
+        Some(narration) => narration.to_string(),
+        None => rest.to_string(),
+    }
+}
+
+/// an amount like `12.05 USD`, `$-12.05` or `-1,200 EUR` in cents, ignoring its commodity
+fn parse_posting_amount(s: &str) -> Result<i64, JournalError> {
+    if s.contains(['@', '{']) {
+        return Err(invalid(format!("prices and costs aren't supported: {s}")));
+    }
+
+    let number: String = s
+        .chars()
+        .filter(|c| c.is_ascii_digit() || matches!(c, '.' | ','))
+        .collect();
+
+    if number.is_empty() {
+        return Err(invalid(format!("the posting doesn't have an amount: {s}")));
+    }
+
+    let cents = parse::amount::amount(&number)? as i64;
+
+    Ok(if s.contains('-') { -cents } else { cents })
+}
+
+/// splits a posting into its account and amount. ledger accounts can contain single spaces,
+/// so the amount follows two spaces or a tab, while beancount accounts can't contain spaces
+/// and are followed by a single one
+fn parse_posting(line: &str) -> Result<(String, Option<i64>), JournalError> {
+    let line = line.trim_start_matches(['*', '!']).trim();
+
+    let (account, amount) = match line.find("  ").or_else(|| line.find('\t')) {
+        Some(i) => (&line[..i], Some(line[i..].trim())),
+        None => match line.split_once(' ') {
+            Some((account, amount)) if amount.contains(|c: char| c.is_ascii_digit()) => {
+                (account, Some(amount.trim()))
+            }
+            _ => (line, None),
+        },
+    };
+
+    // virtual postings are read like any other
+    let account = account.trim_matches(['(', ')', '[', ']']).to_string();
+    let amount = amount.map(parse_posting_amount).transpose()?;
+
+    Ok((account, amount))
+}
+
+/// a transaction whose postings are still being read, and whether any of them failed to parse
+struct Pending {
+    line: u64,
+    date: NaiveDate,
+    description: String,
+    postings: Vec<(String, Option<i64>)>,
+    failed: bool,
+}
+
+/// balances a transaction, where at most one posting can leave out its amount
+fn finish(pending: Pending) -> Result<PlaintextTransaction, JournalError> {
+    if pending.postings.is_empty() {
+        return Err(invalid("the transaction doesn't have any postings"));
+    }
+
+    let total: i64 = pending
+        .postings
+        .iter()
+        .filter_map(|(_, amount)| *amount)
+        .sum();
+    let elided = pending
+        .postings
+        .iter()
+        .filter(|(_, amount)| amount.is_none())
+        .count();
+
+    if elided > 1 {
+        return Err(invalid("only one posting can leave out its amount"));
+    }
+
+    Ok(PlaintextTransaction {
+        line: pending.line,
+        date: pending.date,
+        description: pending.description,
+        postings: pending
+            .postings
+            .into_iter()
+            .map(|(account, amount)| (account, amount.unwrap_or(-total)))
+            .collect(),
+    })
+}
+
+/// reads the transactions from a ledger or beancount file, skipping comments, metadata, and
+/// every other kind of directive. every transaction that can't be read is reported by line
+pub fn parse_plaintext(text: &str) -> Result<Vec<PlaintextTransaction>, JournalError> {
+    let mut transactions = Vec::new();
+    let mut errors = Vec::new();
+    let mut current: Option<Pending> = None;
+
+    // a blank last line ends the final transaction like any other unindented line
+    for (row, line) in (1..).zip(text.lines().chain([""])) {
+        let content = line.split(';').next().unwrap_or_default();
+
+        if line.starts_with([' ', '\t']) {
+            let content = content.trim();
+            let Some(pending) = current.as_mut() else {
+                continue;
+            };
+
+            // beancount metadata is a lowercase key followed by a colon and its value
+            let is_metadata = content.split_whitespace().next().is_some_and(|key| {
+                key.ends_with(':') && key.starts_with(|c: char| c.is_ascii_lowercase())
+            });
+
+            if content.is_empty() || content.starts_with('#') || is_metadata {
+                continue;
+            }
+
+            match parse_posting(content) {
+                Ok(posting) => pending.postings.push(posting),
+                Err(error) => {
+                    errors.push(BatchRowError { row, error });
+                    pending.failed = true;
+                }
+            }
+            continue;
+        }
+
+        // a transaction with a posting that couldn't be read has already been reported
+        if let Some(pending) = current.take()
+            && !pending.failed
+        {
+            let row = pending.line;
+            match finish(pending) {
+                Ok(transaction) => transactions.push(transaction),
+                Err(error) => errors.push(BatchRowError { row, error }),
+            }
+        }
+
+        let mut words = content.split_whitespace();
+        let Some(date) = words.next().and_then(parse_date) else {
+            continue;
+        };
+
+        if words
+            .next()
+            .is_some_and(|word| BEANCOUNT_DIRECTIVES.contains(&word))
+        {
+            continue;
+        }
+
+        let rest = content
+            .trim()
+            .split_once(char::is_whitespace)
+            .map_or("", |(_, rest)| rest);
+
+        current = Some(Pending {
+            line: row,
+            date,
+            description: parse_description(rest.trim()),
+            postings: Vec::new(),
+            failed: false,
+        });
+    }
+
+    if !errors.is_empty() {
+        errors.sort_by_key(|error| error.row);
+        return Err(JournalError::InvalidBatch(errors));
+    }
+
+    Ok(transactions)
+}
+
+/// every account the transactions post to, in the order they first appear
+fn plaintext_accounts(transactions: &[PlaintextTransaction]) -> Vec<&str> {
+    let mut seen = HashSet::new();
+
+    transactions
+        .iter()
+        .flat_map(|transaction| &transaction.postings)
+        .map(|(account, _)| account.as_str())
+        .filter(|account| seen.insert(*account))
+        .collect()
+}
+
+/// the journal's account a path most likely means, matching either the whole path or its
+/// last component, ignoring case and the dashes beancount puts in place of spaces
+fn suggest<'a>(path: &str, accounts: &'a [AccountState]) -> Option<&'a AccountState> {
+    let normalise = |s: &str| s.replace('-', " ").to_lowercase();
+    let leaf = path.rsplit(':').next().unwrap_or(path);
+
+    accounts
+        .iter()
+        .find(|account| normalise(account.name.as_ref()) == normalise(path))
+        .or_else(|| {
+            accounts
+                .iter()
+                .find(|account| normalise(account.name.as_ref()) == normalise(leaf))
+        })
+}
+
+/// writes the transactions as bulk entry lines, naming each posting's account by the journal
+/// account it was mapped to. unmapped accounts are left blank, with the transaction's
+/// description in the sixth column so the journal's import rules can choose them
+fn review_csv(transactions: &[PlaintextTransaction], mapping: &HashMap<String, String>) -> String {
+    let mut writer = csv::WriterBuilder::new()
+        .flexible(true)
+        .from_writer(Vec::new());
+
+    for (index, transaction) in transactions.iter().enumerate() {
+        let reference = format!("LEDGER-{}", index + 1);
+
+        for (posting, (account, amount)) in transaction.postings.iter().enumerate() {
+            let date = match posting {
+                0 => transaction.date.to_string(),
+                _ => String::new(),
+            };
+            let entry_type = if *amount < 0 {
+                EntryType::Credit
+            } else {
+                EntryType::Debit
+            };
+
+            writer
+                .write_record([
+                    reference.as_str(),
+                    &date,
+                    mapping.get(account).map(String::as_str).unwrap_or_default(),
+                    &format_amount(amount.unsigned_abs()),
+                    &entry_type.to_string(),
+                    &transaction.description,
+                ])
+                .expect("writing csv to memory can't fail");
+        }
+    }
+
+    String::from_utf8(
+        writer
+            .into_inner()
+            .expect("writing csv to memory can't fail"),
+    )
+    .expect("the csv was written from strings")
+}
+
+async fn plaintext_export(
+    state: StateType,
+    access: JournalAccess,
+    format: PlaintextFormat,
+) -> Result<Response, ErrorRedirect> {
+    let callback_url = &format!("/journal/{}", access.journal.id);
+    let authority = access.authority();
+
+    let accounts: Vec<(AccountState, _, _)> = state
+        .journal_service
+        .list_journal_accounts(access.journal.id, &authority)
+        .await
+        .or_redirect(callback_url)?;
+
+    let transactions: Vec<TransactionState> = state
+        .journal_service
+        .list_journal_transactions(access.journal.id, &authority)
+        .await
+        .or_redirect(callback_url)?
+        .into_iter()
+        .map(|(transaction, _, _)| transaction)
+        .collect();
+
+    // every account is opened on the earliest day anything in the export happened
+    let opened = accounts
+        .iter()
+        .map(|(_, _, created_at)| created_at.date_naive())
+        .chain(transactions.first().map(|t| t.effective_date))
+        .min()
+        .unwrap_or_default();
+
+    let accounts: Vec<AccountState> = accounts
+        .into_iter()
+        .map(|(account, _, _)| account)
+        .collect();
+
+    Ok((
+        [
+            (CONTENT_TYPE, "text/plain; charset=utf-8".to_string()),
+            (
+                CONTENT_DISPOSITION,
+                format!("attachment; filename=\"journal.{}\"", format.extension()),
+            ),
+        ],
+        render(format, &accounts, &transactions, opened),
+    )
+        .into_response())
+}
+
+pub async fn beancount_export(
+    State(state): State<StateType>,
+    access: JournalAccess,
+) -> Result<Response, ErrorRedirect> {
+    plaintext_export(state, access, PlaintextFormat::Beancount).await
+}
+
+pub async fn ledger_export(
+    State(state): State<StateType>,
+    access: JournalAccess,
+) -> Result<Response, ErrorRedirect> {
+    plaintext_export(state, access, PlaintextFormat::Ledger).await
+}
+
+const TEXTAREA_CLASS: &str = "w-full rounded-md border border-gray-300 dark:border-gray-600 bg-white dark:bg-gray-800 px-3 py-2 font-mono text-sm text-gray-900 dark:text-white placeholder:text-gray-400 dark:placeholder:text-gray-500 focus:border-indigo-500 focus:ring-indigo-500 dark:focus:border-indigo-400";
+
+const BUTTON_CLASS: &str = "px-6 py-2 bg-indigo-600 text-white font-medium rounded-md hover:bg-indigo-700 focus:outline-none focus:ring-2 focus:ring-indigo-500 focus:ring-offset-2 dark:bg-indigo-500 dark:hover:bg-indigo-400 dark:focus:ring-indigo-400 dark:ring-offset-gray-800";
+
+fn import_page(journal_name: &str, id: &str, text: &str, error: Option<&MonkestoError>) -> Markup {
+    let content = html! {
+        div class="flex flex-col gap-6 mx-auto w-full max-w-4xl" {
+            div class="bg-white dark:bg-gray-800 border border-gray-200 dark:border-gray-700 rounded-xl p-6" {
+                h3 class="text-lg font-semibold text-gray-900 dark:text-white mb-2" {
+                    "Import from Ledger or Beancount"
+                }
+                p class="text-sm text-gray-500 dark:text-gray-400 mb-6" {
+                    "Paste a ledger-cli or beancount file. Next you'll choose which of the journal's accounts each of its accounts is, "
+                    "then review the transactions before they're created. Directives other than transactions are skipped."
+                }
+
+                form method="post" action=(format!("/journal/{}/transaction/plaintext", id)) class="space-y-6" {
+                    textarea
+                        name="text"
+                        rows="16"
+                        required
+                        placeholder="2026-01-31 * \"Rent\"\n    Expenses:Rent  1200.00 USD\n    Assets:Cash"
+                        class=(TEXTAREA_CLASS) {
+                        (text)
+                    }
+
+                    div class="flex justify-end pt-4 border-t border-gray-200 dark:border-gray-600" {
+                        button class=(BUTTON_CLASS) type="submit" { "Map Accounts" }
+                    }
+                }
+
+                @match error {
+                    Some(MonkestoError::Journal(JournalError::InvalidBatch(rows))) => {
+                        ul class="mt-6 space-y-1 text-sm text-red-600 dark:text-red-400" {
+                            @for row in rows {
+                                li { "Line " (row.row) ": " (row.error) }
+                            }
+                        }
+                    },
+                    Some(e) => {
+                        p class="mt-6 text-sm text-red-600 dark:text-red-400" { (e) }
+                    },
+                    None => {}
+                }
+            }
+        }
+    };
+
+    layout(Some(journal_name), true, Some(id), content)
+}
+
+/// asks which of the journal's accounts each account in the file is, suggesting the ones
+/// with matching names. the file is sent back with the choices and parsed again
+fn mapping_page(
+    journal_name: &str,
+    id: &str,
+    text: &str,
+    paths: &[&str],
+    accounts: &[AccountState],
+) -> Markup {
+    let content = html! {
+        div class="flex flex-col gap-6 mx-auto w-full max-w-4xl" {
+            div class="bg-white dark:bg-gray-800 border border-gray-200 dark:border-gray-700 rounded-xl p-6" {
+                h3 class="text-lg font-semibold text-gray-900 dark:text-white mb-2" {
+                    "Map Accounts"
+                }
+                p class="text-sm text-gray-500 dark:text-gray-400 mb-6" {
+                    "Choose the journal account for each account in the file. "
+                    "Accounts left blank can be chosen by the journal's import rules, or filled in on the next page."
+                }
+
+                form method="post" action=(format!("/journal/{}/transaction/plaintext", id)) class="space-y-4" {
+                    textarea name="text" class="hidden" { (text) }
+                    input type="hidden" name="mapped" value="true";
+
+                    @for path in paths {
+                        @let suggested = suggest(path, accounts).map(|account| account.id);
+                        div class="flex flex-wrap items-center justify-between gap-3" {
+                            label for=(format!("account:{path}")) class="font-mono text-sm text-gray-900 dark:text-white" { (path) }
+                            select
+                            id=(format!("account:{path}"))
+                            name=(format!("account:{path}"))
+                            class="rounded-md border border-gray-300 dark:border-gray-600 bg-white dark:bg-gray-800 px-2 py-1 text-sm text-gray-900 dark:text-white" {
+                                option value="" { "Leave blank" }
+                                @for account in accounts {
+                                    option value=(account.name) selected[suggested == Some(account.id)] { (account.name) }
+                                }
+                            }
+                        }
+                    }
+
+                    div class="flex justify-end pt-4 border-t border-gray-200 dark:border-gray-600" {
+                        button class=(BUTTON_CLASS) type="submit" { "Review Transactions" }
+                    }
+                }
+            }
+        }
+    };
+
+    layout(Some(journal_name), true, Some(id), content)
+}
+
+pub async fn plaintext_import_page(access: JournalAccess) -> Markup {
+    let id = access.journal.id.to_string();
+    import_page(access.journal.name.as_ref(), &id, "", None)
+}
+
+/// reads the pasted file and asks for its accounts to be mapped, then sends the mapped
+/// transactions to the bulk entry review, where they're created like any other import
+pub async fn plaintext_import(
+    State(state): State<StateType>,
+    access: JournalAccess<AppendTransaction>,
+    Form(form): Form<HashMap<String, String>>,
+) -> Response {
+    let journal_id = access.journal.id;
+    let id = journal_id.to_string();
+    let name = access.journal.name.as_ref();
+    let authority = access.authority();
+    let text = form.get("text").map(String::as_str).unwrap_or_default();
+
+    let result: MonkestoResult<Markup> = async {
+        let transactions = parse_plaintext(text)?;
+
+        if form.contains_key("mapped") {
+            let mapping: HashMap<String, String> = form
+                .iter()
+                .filter_map(|(key, account)| {
+                    Some((key.strip_prefix("account:")?.to_string(), account.clone()))
+                })
+                .collect();
+
+            let csv = review_csv(&transactions, &mapping);
+            return bulk::review_page(&state, journal_id, &csv, &authority).await;
+        }
+
+        let accounts: Vec<AccountState> = state
+            .journal_service
+            .list_journal_accounts(journal_id, &authority)
+            .await?
+            .into_iter()
+            .map(|(account, _, _)| account)
+            .collect();
+
+        Ok(mapping_page(
+            name,
+            &id,
+            text,
+            &plaintext_accounts(&transactions),
+            &accounts,
+        ))
+    }
+    .await;
+
+    match result {
+        Ok(page) => page.into_response(),
+        Err(e) => (
+            StatusCode::UNPROCESSABLE_ENTITY,
+            import_page(name, &id, text, Some(&e)),
+        )
+            .into_response(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::journal::JournalId;
+    use crate::journal::transaction::{BalanceUpdate, TransactionId};
+    use crate::name::Name;
+
+    fn account(name: &str, balance: i64) -> AccountState {
+        AccountState {
+            id: AccountId::new(),
+            journal_id: JournalId::new(),
+            name: Name::try_new(name.to_string()).unwrap(),
+            balance,
+            group: None,
+        }
+    }
+
+    #[test]
+    fn beancount_accounts_are_put_under_a_root() {
+        let format = PlaintextFormat::Beancount;
+
+        assert_eq!(
+            format.path("Expenses:eating out", 500),
+            "Expenses:Eating-out"
+        );
+        assert_eq!(format.path("petty cash", -500), "Assets:Petty-cash");
+        assert_eq!(format.path("Visa", 500), "Liabilities:Visa");
+        assert_eq!(
+            PlaintextFormat::Ledger.path("petty  cash", -500),
+            "petty cash"
+        );
+    }
+
+    #[test]
+    fn a_transaction_is_rendered_with_a_posting_per_entry() {
+        let cash = account("Cash", -1205);
+        let sales = account("Income:Sales", 1205);
+        let transaction = TransactionState {
+            id: TransactionId::new(),
+            journal_id: JournalId::new(),
+            number: 1,
+            entries: vec![
+                BalanceUpdate {
+                    account_id: cash.id,
+                    amount: 1205,
+                    entry_type: EntryType::Debit,
+                    memo: Some("table 4".to_string()),
+                },
+                BalanceUpdate {
+                    account_id: sales.id,
+                    amount: 1205,
+                    entry_type: EntryType::Credit,
+                    memo: None,
+                },
+            ],
+            effective_date: NaiveDate::from_ymd_opt(2026, 3, 4).unwrap(),
+            reverses: None,
+            transfer: None,
+            payee: None,
+        };
+        let opened = NaiveDate::from_ymd_opt(2026, 1, 1).unwrap();
+
+        assert_eq!(
+            render(
+                PlaintextFormat::Beancount,
+                &[cash, sales],
+                std::slice::from_ref(&transaction),
+                opened
+            ),
+            "2026-01-01 open Assets:Cash\n\
+            2026-01-01 open Income:Sales\n\
+            \n\
+            2026-03-04 * \"Transaction 1\"\n    \
+            Assets:Cash  12.05 USD  ; table 4\n    \
+            Income:Sales  -12.05 USD\n"
+        );
+    }
+
+    #[test]
+    fn beancount_transactions_are_read_and_directives_skipped() {
+        let text = "option \"title\" \"Household\"\n\
+                    2026-01-01 open Assets:Cash\n\
+                    \n\
+                    2026-01-31 * \"Landlord\" \"January rent\"\n  \
+                    invoice: \"JAN-1\"\n  \
+                    Expenses:Rent  1,200.00 USD ; paid in full\n  \
+                    Assets:Cash\n\
+                    2026-02-01 balance Assets:Cash -1200.00 USD\n";
+
+        assert_eq!(
+            parse_plaintext(text),
+            Ok(vec![PlaintextTransaction {
+                line: 4,
+                date: NaiveDate::from_ymd_opt(2026, 1, 31).unwrap(),
+                description: "January rent".to_string(),
+                postings: vec![
+                    ("Expenses:Rent".to_string(), 120000),
+                    ("Assets:Cash".to_string(), -120000),
+                ],
+            }])
+        );
+    }
+
+    #[test]
+    fn ledger_accounts_can_contain_spaces() {
+        let text = "; groceries\n\
+                    2026/03/04 * (42) Corner Shop\n    \
+                    Expenses:Eating out    $12.05\n    \
+                    Assets:Petty cash  -$12.05\n";
+
+        let transactions = parse_plaintext(text).expect("failed to parse the file");
+
+        assert_eq!(transactions[0].description, "Corner Shop");
+        assert_eq!(
+            transactions[0].postings,
+            vec![
+                ("Expenses:Eating out".to_string(), 1205),
+                ("Assets:Petty cash".to_string(), -1205),
+            ]
+        );
+    }
+
+    #[test]
+    fn every_unreadable_transaction_is_reported() {
+        let text = "2026-01-01 * \"Two blanks\"\n  \
+                    Assets:Cash\n  \
+                    Expenses:Food\n\
+                    2026-01-02 * \"Priced\"\n  \
+                    Assets:Shares  10 ACME @ 5.00 USD\n  \
+                    Assets:Cash\n\
+                    2026-01-03 * \"Fine\"\n  \
+                    Assets:Cash  5 USD\n  \
+                    Income:Gifts\n";
+
+        let Err(JournalError::InvalidBatch(rows)) = parse_plaintext(text) else {
+            panic!("the file should have been rejected");
+        };
+
+        assert_eq!(
+            rows.iter().map(|row| row.row).collect::<Vec<_>>(),
+            vec![1, 5]
+        );
+    }
+
+    #[test]
+    fn mapped_postings_become_bulk_entry_lines() {
+        let transactions = vec![PlaintextTransaction {
+            line: 1,
+            date: NaiveDate::from_ymd_opt(2026, 1, 31).unwrap(),
+            description: "January rent".to_string(),
+            postings: vec![
+                ("Expenses:Rent".to_string(), 120000),
+                ("Assets:Cash".to_string(), -120000),
+            ],
+        }];
+        let mapping = HashMap::from([("Assets:Cash".to_string(), "Cash".to_string())]);
+
+        assert_eq!(
+            review_csv(&transactions, &mapping),
+            "LEDGER-1,2026-01-31,,$1200.00,Dr,January rent\n\
+             LEDGER-1,,Cash,$1200.00,Cr,January rent\n"
+        );
+    }
+}
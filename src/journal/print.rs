@@ -0,0 +1,117 @@
+//! reports and ledgers on paper. the stylesheet hides the app around them when printing, and
+//! these repeat the journal, the period and when it was printed on every page
+
+use crate::authn::timezone::Timezone;
+use crate::i18n::t;
+use crate::time_provider::Timestamp;
+use maud::{Markup, PreEscaped, html};
+
+/// what's printed above and below every page of a report
+pub struct PrintHeading<'a> {
+    pub journal: &'a str,
+    /// the dates the report covers
+    pub period: String,
+    pub timezone: Timezone,
+    pub generated: Timestamp,
+}
+
+/// opens the browser's print dialog. it's left off the printout
+pub fn print_button() -> Markup {
+    html! {
+        button
+        type="button"
+        onclick="window.print()"
+        class="px-3 py-1 text-sm bg-white dark:bg-gray-700 border border-gray-300 dark:border-gray-600 rounded-md text-gray-700 dark:text-gray-200 hover:bg-gray-100 dark:hover:bg-gray-600" {
+            (t("print.action"))
+        }
+    }
+}
+
+/// the content with the heading printed on each page. browsers repeat a table's header and
+/// footer groups on every page they break it across, so the content is laid out as the one
+/// cell of a table that's only a table on paper
+pub fn printable(heading: &PrintHeading, content: Markup) -> Markup {
+    html! {
+        table class="block print:table w-full" {
+            thead class="hidden print:table-header-group" {
+                tr {
+                    td {
+                        div class="flex justify-between pb-3 mb-4 border-b border-gray-300 text-xs text-gray-600" {
+                            span class="font-semibold text-gray-900" { (heading.journal) }
+                            span { (heading.period) }
+                        }
+                    }
+                }
+            }
+            tbody class="block print:table-row-group" {
+                tr class="block print:table-row" {
+                    td class="block print:table-cell" { (content) }
+                }
+            }
+            tfoot class="hidden print:table-footer-group" {
+                tr {
+                    td {
+                        div class="pt-3 mt-4 border-t border-gray-300 text-xs text-gray-600" {
+                            (t("print.generated")) " "
+                            time
+                            data-print-time
+                            data-timezone=(heading.timezone)
+                            datetime=(heading.generated.to_rfc3339()) {
+                                (heading.timezone.format(heading.generated))
+                            }
+                        }
+                    }
+                }
+            }
+        }
+        // a page served from the cache was rendered earlier than it's printed, so the time is
+        // brought up to date as the print dialog opens
+        script {
+            (PreEscaped(r#"
+                window.addEventListener('beforeprint', () => {
+                    for (const time of document.querySelectorAll('[data-print-time]')) {
+                        const now = new Date();
+                        const formatted = now.toLocaleString(document.documentElement.lang, {
+                            timeZone: time.dataset.timezone,
+                            dateStyle: 'medium',
+                            timeStyle: 'short',
+                        });
+                        time.setAttribute('datetime', now.toISOString());
+                        time.textContent = formatted;
+                    }
+                });
+            "#))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::test_app::TestApp;
+
+    #[tokio::test]
+    async fn ledgers_print_with_their_journal_and_period() {
+        let Some(app) = TestApp::spawn().await else {
+            return;
+        };
+
+        let user = app.signup("printer").await;
+        let journal_id = user.create_journal(&app, "Printed Books").await;
+
+        let ledger = user
+            .client
+            .get(&format!(
+                "/journal/{journal_id}/transaction?from=2026-01-01&to=2026-03-31"
+            ))
+            .clear_headers()
+            .add_header("accept", "text/html")
+            .await
+            .text();
+
+        assert!(ledger.contains("window.print()"));
+        assert!(ledger.contains("print:table-header-group"));
+        assert!(ledger.contains("Printed Books"));
+        assert!(ledger.contains("Totals for 2026-01-01 – 2026-03-31"));
+        assert!(ledger.contains("data-print-time"));
+    }
+}
@@ -0,0 +1,219 @@
+//! who last changed an account or a transaction and when, going by the newest event that
+//! changed it. each one links to the change in the journal's history
+
+use crate::StateType;
+use crate::authn::timezone::Timezone;
+use crate::authn::user::UserId;
+use crate::authority::Actor;
+use crate::i18n::{t, tf};
+use crate::journal::JournalId;
+use crate::journal::domain::JournalDomainEvent;
+use crate::journal::service::JournalEventEnvelope;
+use crate::time_provider::Timestamp;
+use disintegrate_postgres::PgEventId;
+use maud::{Markup, html};
+use std::collections::{HashMap, HashSet};
+
+/// the newest change to something, with the name of whoever made it
+pub struct LastChange {
+    pub event_id: PgEventId,
+    /// whether the change is the one that created it
+    pub created: bool,
+    pub actor: String,
+    pub timestamp: Timestamp,
+}
+
+impl LastChange {
+    pub fn new(change: &JournalEventEnvelope, names: &HashMap<UserId, String>) -> Self {
+        let actor = match change.event.authority().actor() {
+            Actor::User(user_id) => names
+                .get(user_id)
+                .cloned()
+                .unwrap_or_else(|| user_id.to_string()),
+            Actor::System => t("actor.system").to_string(),
+            Actor::Anonymous => t("actor.anonymous").to_string(),
+            Actor::Deleted => t("actor.deleted").to_string(),
+        };
+
+        Self {
+            event_id: change.event_id,
+            created: matches!(
+                change.event,
+                JournalDomainEvent::AccountCreated { .. }
+                    | JournalDomainEvent::TransactionCreated { .. }
+                    | JournalDomainEvent::TransactionSubmitted { .. }
+            ),
+            actor,
+            timestamp: change.event.timestamp(),
+        }
+    }
+}
+
+/// the names of everyone who made the changes, fetched in one query. anyone who can't be read
+/// is shown by their id instead
+pub async fn actor_names<'a>(
+    state: &StateType,
+    changes: impl IntoIterator<Item = &'a JournalEventEnvelope>,
+) -> HashMap<UserId, String> {
+    let user_ids: Vec<UserId> = changes
+        .into_iter()
+        .filter_map(|change| change.event.authority().user_id())
+        .collect::<HashSet<UserId>>()
+        .into_iter()
+        .collect();
+
+    state
+        .authn_service
+        .fetch_users(&user_ids)
+        .await
+        .map(|users| {
+            users
+                .into_iter()
+                .map(|user| (user.id, user.display_name()))
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// roughly how long before `now` the time was, in the largest unit that fits
+pub fn time_ago(then: Timestamp, now: Timestamp) -> String {
+    let minutes = (now - then).num_minutes().max(0);
+
+    let (count, one, many) = match minutes {
+        0 => return t("ago.just_now").to_string(),
+        1..60 => (minutes, "ago.minute", "ago.minutes"),
+        60..1440 => (minutes / 60, "ago.hour", "ago.hours"),
+        1440..43200 => (minutes / 1440, "ago.day", "ago.days"),
+        43200..525600 => (minutes / 43200, "ago.month", "ago.months"),
+        _ => (minutes / 525600, "ago.year", "ago.years"),
+    };
+
+    if count == 1 {
+        t(one).to_string()
+    } else {
+        tf(many, &[("count", &count)])
+    }
+}
+
+/// "edited by … 2 days ago", with the exact time shown on hover
+pub fn last_change_link(
+    journal_id: JournalId,
+    change: &LastChange,
+    timezone: Timezone,
+    now: Timestamp,
+) -> Markup {
+    let key = if change.created {
+        "provenance.created"
+    } else {
+        "provenance.edited"
+    };
+
+    html! {
+        a
+        href=(format!("/journal/{}/history#event-{}", journal_id, change.event_id))
+        title=(timezone.format(change.timestamp))
+        class="text-xs text-gray-400 dark:text-gray-500 hover:text-indigo-500 dark:hover:text-indigo-400" {
+            (tf(key, &[("user", &change.actor), ("ago", &time_ago(change.timestamp, now))]))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::authority::Authority;
+    use crate::name::Name;
+    use crate::test_app::{TestApp, assert_accepted};
+    use crate::time_provider::{DefaultTimeProvider, TimeProvider};
+    use chrono::Duration;
+
+    #[test]
+    fn times_are_given_in_the_largest_unit_that_fits() {
+        let now = DefaultTimeProvider.get_time();
+        let ago = |duration: Duration| time_ago(now - duration, now);
+
+        assert_eq!(ago(Duration::seconds(30)), "just now");
+        assert_eq!(ago(Duration::minutes(1)), "1 minute ago");
+        assert_eq!(ago(Duration::minutes(59)), "59 minutes ago");
+        assert_eq!(ago(Duration::hours(5)), "5 hours ago");
+        assert_eq!(ago(Duration::days(2)), "2 days ago");
+        assert_eq!(ago(Duration::days(65)), "2 months ago");
+        assert_eq!(ago(Duration::days(400)), "1 year ago");
+
+        // a clock that's a little behind doesn't put changes in the future
+        assert_eq!(ago(Duration::minutes(-3)), "just now");
+    }
+
+    #[tokio::test]
+    async fn detail_views_link_their_last_change_to_the_history() {
+        let Some(app) = TestApp::spawn().await else {
+            return;
+        };
+
+        let user = app.signup("provenance").await;
+        let journal_id = user.create_journal(&app, "Provenance").await;
+        assert_accepted(&user.create_account(journal_id, "Cash").await);
+        assert_accepted(&user.create_account(journal_id, "Food").await);
+        let cash = app.account_id(journal_id, "Cash").await;
+        let food = app.account_id(journal_id, "Food").await;
+
+        let event_id = app
+            .state
+            .journal_service
+            .rename_account(
+                food,
+                journal_id,
+                Name::try_new("Groceries".to_string()).expect("invalid name"),
+                Authority::Direct(Actor::System),
+                app.state.clock.get_time(),
+            )
+            .await
+            .expect("failed to rename the account");
+        app.state.journal_service.wait_for(event_id).await;
+
+        let accounts = user
+            .client
+            .get(&format!("/journal/{journal_id}/account"))
+            .clear_headers()
+            .add_header("accept", "text/html")
+            .await
+            .text();
+        assert!(accounts.contains(&format!("Created by {} just now", user.email)));
+        assert!(accounts.contains("Edited by System just now"));
+        assert!(accounts.contains(&format!(
+            "href=\"/journal/{journal_id}/history#event-{event_id}\""
+        )));
+
+        let history = user
+            .client
+            .get(&format!("/journal/{journal_id}/history"))
+            .clear_headers()
+            .add_header("accept", "text/html")
+            .await
+            .text();
+        assert!(history.contains(&format!("id=\"event-{event_id}\"")));
+
+        assert_accepted(&user.post_transaction(journal_id, cash, food, "5.00").await);
+        let transaction = app
+            .state
+            .journal_service
+            .list_journal_transactions(journal_id, &Authority::Direct(Actor::System))
+            .await
+            .expect("failed to list the transactions")
+            .remove(0)
+            .0;
+
+        let detail = user
+            .client
+            .get(&format!(
+                "/journal/{journal_id}/transaction/{}",
+                transaction.id
+            ))
+            .clear_headers()
+            .add_header("accept", "text/html")
+            .await
+            .text();
+        assert!(detail.contains(&format!("Created by {} just now", user.email)));
+        assert!(detail.contains(&format!("/journal/{journal_id}/history#event-")));
+    }
+}
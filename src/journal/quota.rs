@@ -0,0 +1,100 @@
+use crate::journal::JournalError;
+use serde::{Deserialize, Serialize};
+use std::env;
+use std::fmt::Display;
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+#[error("{0} must be a whole number, found {1}")]
+pub struct QuotaConfigError(&'static str, String);
+
+/// a per-user limit, along with its configured value
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Quota {
+    Journals(u64),
+    TransactionsPerJournal(u64),
+}
+
+impl Display for Quota {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Journals(limit) => write!(f, "{limit} journals"),
+            Self::TransactionsPerJournal(limit) => write!(f, "{limit} transactions per journal"),
+        }
+    }
+}
+
+/// soft limits on how much each user can create. they're checked against the projections
+/// rather than inside the decisions, so concurrent requests can briefly exceed them.
+/// a missing limit is unlimited
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Quotas {
+    pub max_journals: Option<u64>,
+    pub max_transactions_per_journal: Option<u64>,
+}
+
+fn limit_from_env(key: &'static str) -> Result<Option<u64>, QuotaConfigError> {
+    match env::var(key) {
+        Ok(value) if value.is_empty() => Ok(None),
+        Ok(value) => value
+            .parse::<u64>()
+            .map(Some)
+            .map_err(|_| QuotaConfigError(key, value)),
+        Err(_) => Ok(None),
+    }
+}
+
+impl Quotas {
+    pub fn from_env() -> Result<Self, QuotaConfigError> {
+        Ok(Self {
+            max_journals: limit_from_env("QUOTA_MAX_JOURNALS")?,
+            max_transactions_per_journal: limit_from_env("QUOTA_MAX_TRANSACTIONS_PER_JOURNAL")?,
+        })
+    }
+
+    /// checks whether a user who already owns `owned` journals may create another
+    pub fn check_journals(&self, owned: u64) -> Result<(), JournalError> {
+        match self.max_journals {
+            Some(limit) if owned >= limit => {
+                Err(JournalError::QuotaExceeded(Quota::Journals(limit)))
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// checks whether a journal that already has `count` transactions may have `adding` more
+    pub fn check_transactions(&self, count: u64, adding: u64) -> Result<(), JournalError> {
+        match self.max_transactions_per_journal {
+            Some(limit) if count.saturating_add(adding) > limit => Err(
+                JournalError::QuotaExceeded(Quota::TransactionsPerJournal(limit)),
+            ),
+            _ => Ok(()),
+        }
+    }
+}
+
+/// how much of their quotas a user has used
+pub struct QuotaUsage {
+    pub journals: u64,
+    pub quotas: Quotas,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn limits_are_exclusive() {
+        let quotas = Quotas {
+            max_journals: Some(2),
+            max_transactions_per_journal: None,
+        };
+
+        assert_eq!(quotas.check_journals(1), Ok(()));
+        assert_eq!(
+            quotas.check_journals(2),
+            Err(JournalError::QuotaExceeded(Quota::Journals(2)))
+        );
+        assert_eq!(quotas.check_transactions(u64::MAX, 1), Ok(()));
+    }
+}
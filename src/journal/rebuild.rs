@@ -0,0 +1,310 @@
+use crate::authn::user::UserId;
+use crate::journal::JournalId;
+use crate::journal::account::AccountId;
+use crate::journal::appearance::JournalAppearance;
+use crate::journal::fiscal::FiscalYearStart;
+use crate::journal::transaction::TransactionId;
+use axum_login::tracing;
+use chrono::NaiveDate;
+use std::collections::HashMap;
+use std::fmt::Display;
+use std::hash::Hash;
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+pub enum RebuildError {
+    #[error("sqlx returned an error: {0}")]
+    Sqlx(#[from] sqlx::Error),
+
+    #[error("failed to read the journal events: {0}")]
+    EventStore(#[from] disintegrate_postgres::Error),
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct JournalSnapshot {
+    pub owner_id: UserId,
+    pub name: String,
+    pub fiscal_year_start: FiscalYearStart,
+    pub archived: bool,
+    pub default_cash_account: Option<AccountId>,
+    pub default_expense_account: Option<AccountId>,
+    pub appearance: JournalAppearance,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct AccountSnapshot {
+    pub name: String,
+    pub balance: i64,
+}
+
+/// the rows of the journal projections, captured before and after a rebuild so they can be compared
+#[derive(Debug, Default, PartialEq)]
+pub struct ProjectionSnapshot {
+    pub journals: HashMap<JournalId, JournalSnapshot>,
+    pub members: HashMap<(JournalId, UserId), i32>,
+    pub accounts: HashMap<AccountId, AccountSnapshot>,
+    pub transactions: HashMap<TransactionId, NaiveDate>,
+    /// the debit and credit totals of each account in each period
+    pub period_totals: HashMap<(AccountId, NaiveDate), (i64, i64)>,
+}
+
+/// a difference between the projections before the rebuild and the ones replayed from the events.
+/// stale rows were projected but have no events behind them, missing rows were never projected
+#[derive(Debug, PartialEq)]
+pub enum Inconsistency {
+    StaleJournal(JournalId),
+    MissingJournal(JournalId),
+    JournalMismatch(JournalId),
+    StaleMember(JournalId, UserId),
+    MissingMember(JournalId, UserId),
+    PermissionsMismatch(JournalId, UserId),
+    StaleAccount(AccountId),
+    MissingAccount(AccountId),
+    AccountNameMismatch(AccountId),
+    BalanceMismatch {
+        account_id: AccountId,
+        cached: i64,
+        rebuilt: i64,
+    },
+    StaleTransaction(TransactionId),
+    MissingTransaction(TransactionId),
+    EffectiveDateMismatch(TransactionId),
+    PeriodTotalsMismatch(AccountId, NaiveDate),
+}
+
+impl Display for Inconsistency {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::StaleJournal(id) => write!(f, "journal {id} was projected without any events"),
+            Self::MissingJournal(id) => write!(f, "journal {id} was missing from the projection"),
+            Self::JournalMismatch(id) => write!(f, "journal {id} had an outdated name or owner"),
+            Self::StaleMember(journal_id, user_id) => write!(
+                f,
+                "user {user_id} was projected as a member of journal {journal_id} without any events"
+            ),
+            Self::MissingMember(journal_id, user_id) => write!(
+                f,
+                "user {user_id} was missing from the members of journal {journal_id}"
+            ),
+            Self::PermissionsMismatch(journal_id, user_id) => write!(
+                f,
+                "user {user_id} had outdated permissions in journal {journal_id}"
+            ),
+            Self::StaleAccount(id) => write!(f, "account {id} was projected without any events"),
+            Self::MissingAccount(id) => write!(f, "account {id} was missing from the projection"),
+            Self::AccountNameMismatch(id) => write!(f, "account {id} had an outdated name"),
+            Self::BalanceMismatch {
+                account_id,
+                cached,
+                rebuilt,
+            } => write!(
+                f,
+                "account {account_id} had a balance of {cached} instead of {rebuilt}"
+            ),
+            Self::StaleTransaction(id) => {
+                write!(f, "transaction {id} was projected without any events")
+            }
+            Self::MissingTransaction(id) => {
+                write!(f, "transaction {id} was missing from the projection")
+            }
+            Self::EffectiveDateMismatch(id) => {
+                write!(f, "transaction {id} had an outdated effective date")
+            }
+            Self::PeriodTotalsMismatch(account_id, period) => write!(
+                f,
+                "account {account_id} had outdated totals for the period starting {period}"
+            ),
+        }
+    }
+}
+
+fn diff_rows<K: Copy + Eq + Hash, V>(
+    cached: &HashMap<K, V>,
+    rebuilt: &HashMap<K, V>,
+    stale: impl Fn(K) -> Inconsistency,
+    missing: impl Fn(K) -> Inconsistency,
+    mismatch: impl Fn(K, &V, &V) -> Option<Inconsistency>,
+    inconsistencies: &mut Vec<Inconsistency>,
+) {
+    for (key, cached_value) in cached {
+        match rebuilt.get(key) {
+            Some(rebuilt_value) => {
+                inconsistencies.extend(mismatch(*key, cached_value, rebuilt_value))
+            }
+            None => inconsistencies.push(stale(*key)),
+        }
+    }
+
+    inconsistencies.extend(
+        rebuilt
+            .keys()
+            .filter(|key| !cached.contains_key(key))
+            .map(|key| missing(*key)),
+    );
+}
+
+impl ProjectionSnapshot {
+    /// lists everything that differs between these cached projections and the rebuilt ones
+    pub fn diff(&self, rebuilt: &Self) -> Vec<Inconsistency> {
+        let mut inconsistencies = Vec::new();
+
+        diff_rows(
+            &self.journals,
+            &rebuilt.journals,
+            Inconsistency::StaleJournal,
+            Inconsistency::MissingJournal,
+            |id, cached, rebuilt| (cached != rebuilt).then_some(Inconsistency::JournalMismatch(id)),
+            &mut inconsistencies,
+        );
+
+        diff_rows(
+            &self.members,
+            &rebuilt.members,
+            |(journal_id, user_id)| Inconsistency::StaleMember(journal_id, user_id),
+            |(journal_id, user_id)| Inconsistency::MissingMember(journal_id, user_id),
+            |(journal_id, user_id), cached, rebuilt| {
+                (cached != rebuilt)
+                    .then_some(Inconsistency::PermissionsMismatch(journal_id, user_id))
+            },
+            &mut inconsistencies,
+        );
+
+        diff_rows(
+            &self.accounts,
+            &rebuilt.accounts,
+            Inconsistency::StaleAccount,
+            Inconsistency::MissingAccount,
+            |account_id, cached, rebuilt| {
+                if cached.balance != rebuilt.balance {
+                    Some(Inconsistency::BalanceMismatch {
+                        account_id,
+                        cached: cached.balance,
+                        rebuilt: rebuilt.balance,
+                    })
+                } else {
+                    (cached.name != rebuilt.name)
+                        .then_some(Inconsistency::AccountNameMismatch(account_id))
+                }
+            },
+            &mut inconsistencies,
+        );
+
+        diff_rows(
+            &self.transactions,
+            &rebuilt.transactions,
+            Inconsistency::StaleTransaction,
+            Inconsistency::MissingTransaction,
+            |id, cached, rebuilt| {
+                (cached != rebuilt).then_some(Inconsistency::EffectiveDateMismatch(id))
+            },
+            &mut inconsistencies,
+        );
+
+        // a period without any totals is only a missing row, so every difference is reported the same way
+        diff_rows(
+            &self.period_totals,
+            &rebuilt.period_totals,
+            |(account_id, period)| Inconsistency::PeriodTotalsMismatch(account_id, period),
+            |(account_id, period)| Inconsistency::PeriodTotalsMismatch(account_id, period),
+            |(account_id, period), cached, rebuilt| {
+                (cached != rebuilt)
+                    .then_some(Inconsistency::PeriodTotalsMismatch(account_id, period))
+            },
+            &mut inconsistencies,
+        );
+
+        inconsistencies
+    }
+}
+
+pub struct RebuildReport {
+    pub events_replayed: usize,
+    pub inconsistencies: Vec<Inconsistency>,
+}
+
+impl RebuildReport {
+    pub fn log(&self) {
+        tracing::info!(
+            events_replayed = self.events_replayed,
+            inconsistencies = self.inconsistencies.len(),
+            "rebuilt the journal projections"
+        );
+
+        for inconsistency in self.inconsistencies.iter() {
+            tracing::warn!("{inconsistency}");
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn identical_snapshots_have_no_inconsistencies() {
+        let account_id = AccountId::new();
+        let mut snapshot = ProjectionSnapshot::default();
+        snapshot.accounts.insert(
+            account_id,
+            AccountSnapshot {
+                name: "Cash".to_string(),
+                balance: 500,
+            },
+        );
+
+        let mut rebuilt = ProjectionSnapshot::default();
+        rebuilt.accounts.insert(
+            account_id,
+            AccountSnapshot {
+                name: "Cash".to_string(),
+                balance: 500,
+            },
+        );
+
+        assert_eq!(snapshot.diff(&rebuilt), Vec::new());
+    }
+
+    #[test]
+    fn drifted_balances_and_orphaned_rows_are_reported() {
+        let account_id = AccountId::new();
+        let stale_transaction = TransactionId::new();
+        let missing_transaction = TransactionId::new();
+        let date = NaiveDate::from_ymd_opt(2026, 1, 1).expect("invalid date");
+
+        let mut cached = ProjectionSnapshot::default();
+        cached.accounts.insert(
+            account_id,
+            AccountSnapshot {
+                name: "Cash".to_string(),
+                balance: 700,
+            },
+        );
+        cached.transactions.insert(stale_transaction, date);
+        cached.period_totals.insert((account_id, date), (0, 700));
+
+        let mut rebuilt = ProjectionSnapshot::default();
+        rebuilt.accounts.insert(
+            account_id,
+            AccountSnapshot {
+                name: "Cash".to_string(),
+                balance: 500,
+            },
+        );
+        rebuilt.transactions.insert(missing_transaction, date);
+        rebuilt.period_totals.insert((account_id, date), (0, 500));
+
+        assert_eq!(
+            cached.diff(&rebuilt),
+            vec![
+                Inconsistency::BalanceMismatch {
+                    account_id,
+                    cached: 700,
+                    rebuilt: 500,
+                },
+                Inconsistency::StaleTransaction(stale_transaction),
+                Inconsistency::MissingTransaction(missing_transaction),
+                Inconsistency::PeriodTotalsMismatch(account_id, date),
+            ]
+        );
+    }
+}
@@ -0,0 +1,569 @@
+use crate::BackendType;
+use crate::StateType;
+use crate::authn::get_user;
+use crate::journal::access::JournalAccess;
+use crate::journal::service::AccountState;
+use crate::journal::transaction::views::format_amount;
+use crate::monkesto_error::{ErrorRedirect, OrRedirect};
+use crate::time_provider::TimeProvider;
+use axum::extract::State;
+use axum::http::header::{CONTENT_DISPOSITION, CONTENT_TYPE};
+use axum::response::{IntoResponse, Response};
+use axum_login::AuthSession;
+use chrono::NaiveDate;
+use std::fmt::Write;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Align {
+    Left,
+    Right,
+}
+
+pub struct Column {
+    pub heading: &'static str,
+    pub align: Align,
+}
+
+/// a report laid out as a table, independent of the format it's rendered to
+pub struct Report {
+    pub title: String,
+    pub subtitle: String,
+    pub columns: Vec<Column>,
+    pub rows: Vec<Vec<String>>,
+    /// the row printed below the table, if the report has one
+    pub totals: Option<Vec<String>>,
+}
+
+pub trait ReportRenderer {
+    fn content_type(&self) -> &'static str;
+
+    fn extension(&self) -> &'static str;
+
+    fn render(&self, report: &Report) -> Vec<u8>;
+}
+
+/// the balance of every account, with debit balances and credit balances in separate columns
+pub fn trial_balance(journal_name: &str, as_of: NaiveDate, accounts: &[AccountState]) -> Report {
+    let (mut debits, mut credits) = (0_u64, 0_u64);
+
+    let rows = accounts
+        .iter()
+        .map(|account| {
+            let amount = account.balance.unsigned_abs();
+            let (debit, credit) = if account.balance < 0 {
+                debits += amount;
+                (format_amount(amount), String::new())
+            } else {
+                credits += amount;
+                (String::new(), format_amount(amount))
+            };
+
+            vec![account.name.to_string(), debit, credit]
+        })
+        .collect();
+
+    Report {
+        title: "Trial Balance".to_string(),
+        subtitle: format!("{journal_name}, as of {}", as_of.format("%Y-%m-%d")),
+        columns: vec![
+            Column {
+                heading: "Account",
+                align: Align::Left,
+            },
+            Column {
+                heading: "Debit",
+                align: Align::Right,
+            },
+            Column {
+                heading: "Credit",
+                align: Align::Right,
+            },
+        ],
+        rows,
+        totals: Some(vec![
+            "Total".to_string(),
+            format_amount(debits),
+            format_amount(credits),
+        ]),
+    }
+}
+
+/// every account with its balance, in a layout that can be edited in a spreadsheet and read back
+pub fn chart_of_accounts(accounts: &[AccountState]) -> Report {
+    let rows = accounts
+        .iter()
+        .map(|account| {
+            let amount = account.balance.unsigned_abs();
+            let side = if account.balance < 0 { "Dr" } else { "Cr" };
+
+            vec![
+                account.id.to_string(),
+                account.name.to_string(),
+                format!("{}.{:02}", amount / 100, amount % 100),
+                side.to_string(),
+            ]
+        })
+        .collect();
+
+    Report {
+        title: "Chart of Accounts".to_string(),
+        subtitle: String::new(),
+        columns: vec![
+            Column {
+                heading: "Id",
+                align: Align::Left,
+            },
+            Column {
+                heading: "Name",
+                align: Align::Left,
+            },
+            Column {
+                heading: "Balance",
+                align: Align::Right,
+            },
+            Column {
+                heading: "Dr/Cr",
+                align: Align::Left,
+            },
+        ],
+        rows,
+        totals: None,
+    }
+}
+
+/// writes the table with a header row of the column headings. the title and subtitle are left
+/// out, since a spreadsheet would read them as rows of data
+pub struct CsvRenderer;
+
+impl ReportRenderer for CsvRenderer {
+    fn content_type(&self) -> &'static str {
+        "text/csv; charset=utf-8"
+    }
+
+    fn extension(&self) -> &'static str {
+        "csv"
+    }
+
+    fn render(&self, report: &Report) -> Vec<u8> {
+        let mut writer = csv::Writer::from_writer(Vec::new());
+
+        writer
+            .write_record(report.columns.iter().map(|column| column.heading))
+            .expect("writing csv to memory can't fail");
+
+        for row in report.rows.iter().chain(&report.totals) {
+            writer
+                .write_record(row)
+                .expect("writing csv to memory can't fail");
+        }
+
+        writer
+            .into_inner()
+            .expect("writing csv to memory can't fail")
+    }
+}
+
+/// writes a US letter pdf using the standard Helvetica font, which every pdf reader
+/// provides, so nothing needs to be embedded
+pub struct PdfRenderer;
+
+const PAGE_WIDTH: f32 = 612.0;
+const PAGE_HEIGHT: f32 = 792.0;
+const MARGIN: f32 = 54.0;
+const FONT_SIZE: f32 = 10.0;
+const LINE_HEIGHT: f32 = 16.0;
+/// the width of every column after the first, which takes up the rest of the line
+const COLUMN_WIDTH: f32 = 110.0;
+
+/// the advance widths of the printable ascii characters in Helvetica, in thousandths of an em
+const HELVETICA_WIDTHS: [u16; 95] = [
+    278, 278, 355, 556, 556, 889, 667, 191, 333, 333, 389, 584, 278, 333, 278, 278, 556, 556, 556,
+    556, 556, 556, 556, 556, 556, 556, 278, 278, 584, 584, 584, 556, 1015, 667, 667, 722, 722, 667,
+    611, 778, 722, 278, 500, 667, 556, 833, 722, 778, 667, 778, 722, 667, 611, 722, 667, 944, 667,
+    667, 611, 278, 278, 278, 469, 556, 333, 556, 556, 500, 556, 556, 278, 556, 556, 222, 222, 500,
+    222, 833, 556, 556, 556, 556, 333, 500, 278, 556, 500, 722, 500, 500, 500, 334, 260, 334, 584,
+];
+
+fn text_width(text: &str, size: f32) -> f32 {
+    text.chars()
+        .map(|c| match c {
+            ' '..='~' => HELVETICA_WIDTHS[c as usize - ' ' as usize],
+            _ => 556,
+        } as f32)
+        .sum::<f32>()
+        * size
+        / 1000.0
+}
+
+/// shortens the text with an ellipsis until it fits in the width
+fn fit(text: &str, width: f32) -> String {
+    if text_width(text, FONT_SIZE) <= width {
+        return text.to_string();
+    }
+
+    let mut fitted: String = text.to_string();
+    while !fitted.is_empty() && text_width(&format!("{fitted}..."), FONT_SIZE) > width {
+        fitted.pop();
+    }
+
+    format!("{fitted}...")
+}
+
+/// encodes the text as a pdf string. the standard fonts use WinAnsiEncoding, which matches
+/// latin-1 closely enough for names, so anything outside of it is replaced
+fn pdf_string(text: &str) -> Vec<u8> {
+    let mut bytes = vec![b'('];
+
+    for c in text.chars() {
+        match c {
+            '(' | ')' | '\\' => bytes.extend([b'\\', c as u8]),
+            ' '..='~' | '\u{a0}'..='\u{ff}' => bytes.push(c as u32 as u8),
+            _ => bytes.push(b'?'),
+        }
+    }
+
+    bytes.push(b')');
+    bytes
+}
+
+impl PdfRenderer {
+    /// the left edge of each column, and the right edge for right aligned columns
+    fn column_x(report: &Report, index: usize) -> f32 {
+        let right_columns = report.columns.len().saturating_sub(1 + index) as f32;
+        match report.columns[index].align {
+            Align::Left if index == 0 => MARGIN,
+            Align::Left => PAGE_WIDTH - MARGIN - (right_columns + 1.0) * COLUMN_WIDTH,
+            Align::Right => PAGE_WIDTH - MARGIN - right_columns * COLUMN_WIDTH,
+        }
+    }
+
+    fn column_width(report: &Report, index: usize) -> f32 {
+        if index == 0 {
+            PAGE_WIDTH - 2.0 * MARGIN - (report.columns.len() - 1) as f32 * COLUMN_WIDTH - FONT_SIZE
+        } else {
+            COLUMN_WIDTH - FONT_SIZE
+        }
+    }
+
+    fn row(content: &mut Vec<u8>, report: &Report, cells: &[String], y: f32) {
+        for (index, cell) in cells.iter().enumerate().take(report.columns.len()) {
+            let cell = fit(cell, Self::column_width(report, index));
+            let x = match report.columns[index].align {
+                Align::Left => Self::column_x(report, index),
+                Align::Right => Self::column_x(report, index) - text_width(&cell, FONT_SIZE),
+            };
+
+            _ = write!(Text(content), "BT /F1 {FONT_SIZE} Tf {x:.2} {y:.2} Td ");
+            content.extend(pdf_string(&cell));
+            content.extend(b" Tj ET\n");
+        }
+    }
+
+    fn rule(content: &mut Vec<u8>, y: f32) {
+        _ = writeln!(
+            Text(content),
+            "{MARGIN} {y:.2} m {:.2} {y:.2} l S",
+            PAGE_WIDTH - MARGIN
+        );
+    }
+
+    /// the content stream of each page. the title is only printed on the first page,
+    /// but every page repeats the column headings
+    fn pages(report: &Report) -> Vec<Vec<u8>> {
+        let headings: Vec<String> = report
+            .columns
+            .iter()
+            .map(|column| column.heading.to_string())
+            .collect();
+
+        let mut pages = Vec::new();
+        let mut rows = report.rows.iter().peekable();
+
+        loop {
+            let mut content = Vec::new();
+            let mut y = PAGE_HEIGHT - MARGIN;
+
+            if pages.is_empty() {
+                y -= 16.0;
+                _ = write!(Text(&mut content), "BT /F1 16 Tf {MARGIN} {y:.2} Td ");
+                content.extend(pdf_string(&report.title));
+                content.extend(b" Tj ET\n");
+
+                y -= LINE_HEIGHT;
+                _ = write!(
+                    Text(&mut content),
+                    "BT /F1 {FONT_SIZE} Tf {MARGIN} {y:.2} Td "
+                );
+                content.extend(pdf_string(&report.subtitle));
+                content.extend(b" Tj ET\n");
+
+                y -= LINE_HEIGHT;
+            }
+
+            y -= LINE_HEIGHT;
+            Self::row(&mut content, report, &headings, y);
+            Self::rule(&mut content, y - 4.0);
+
+            while y - LINE_HEIGHT > MARGIN + 2.0 * LINE_HEIGHT
+                && let Some(row) = rows.next()
+            {
+                y -= LINE_HEIGHT;
+                Self::row(&mut content, report, row, y);
+            }
+
+            if rows.peek().is_none() {
+                if let Some(totals) = &report.totals {
+                    Self::rule(&mut content, y - 4.0);
+                    Self::row(&mut content, report, totals, y - LINE_HEIGHT);
+                }
+
+                pages.push(content);
+                return pages;
+            }
+
+            pages.push(content);
+        }
+    }
+}
+
+/// lets `write!` append formatted operators to a content stream
+struct Text<'a>(&'a mut Vec<u8>);
+
+impl std::fmt::Write for Text<'_> {
+    fn write_str(&mut self, s: &str) -> std::fmt::Result {
+        self.0.extend(s.as_bytes());
+        Ok(())
+    }
+}
+
+impl ReportRenderer for PdfRenderer {
+    fn content_type(&self) -> &'static str {
+        "application/pdf"
+    }
+
+    fn extension(&self) -> &'static str {
+        "pdf"
+    }
+
+    fn render(&self, report: &Report) -> Vec<u8> {
+        let pages = Self::pages(report);
+
+        // the catalog, the page tree, and the font come first, then each page and its content
+        let page_ids: Vec<usize> = (0..pages.len()).map(|i| 4 + 2 * i).collect();
+        let mut objects: Vec<Vec<u8>> = vec![
+            b"<< /Type /Catalog /Pages 2 0 R >>".to_vec(),
+            format!(
+                "<< /Type /Pages /Kids [{}] /Count {} >>",
+                page_ids
+                    .iter()
+                    .map(|id| format!("{id} 0 R"))
+                    .collect::<Vec<_>>()
+                    .join(" "),
+                pages.len()
+            )
+            .into_bytes(),
+            b"<< /Type /Font /Subtype /Type1 /BaseFont /Helvetica /Encoding /WinAnsiEncoding >>"
+                .to_vec(),
+        ];
+
+        for (page_id, content) in page_ids.iter().zip(pages) {
+            objects.push(
+                format!(
+                    "<< /Type /Page /Parent 2 0 R /MediaBox [0 0 {PAGE_WIDTH} {PAGE_HEIGHT}] \
+                    /Resources << /Font << /F1 3 0 R >> >> /Contents {} 0 R >>",
+                    page_id + 1
+                )
+                .into_bytes(),
+            );
+
+            let mut stream = format!("<< /Length {} >>\nstream\n", content.len()).into_bytes();
+            stream.extend(content);
+            stream.extend(b"\nendstream");
+            objects.push(stream);
+        }
+
+        let mut pdf = b"%PDF-1.4\n".to_vec();
+        let mut offsets = Vec::with_capacity(objects.len());
+
+        for (index, object) in objects.iter().enumerate() {
+            offsets.push(pdf.len());
+            pdf.extend(format!("{} 0 obj\n", index + 1).into_bytes());
+            pdf.extend(object);
+            pdf.extend(b"\nendobj\n");
+        }
+
+        let xref = pdf.len();
+        pdf.extend(format!("xref\n0 {}\n0000000000 65535 f \n", objects.len() + 1).into_bytes());
+        for offset in offsets {
+            pdf.extend(format!("{offset:010} 00000 n \n").into_bytes());
+        }
+        pdf.extend(
+            format!(
+                "trailer\n<< /Size {} /Root 1 0 R >>\nstartxref\n{xref}\n%%EOF\n",
+                objects.len() + 1
+            )
+            .into_bytes(),
+        );
+
+        pdf
+    }
+}
+
+/// downloads the journal's trial balance as a pdf
+pub async fn trial_balance_pdf(
+    State(state): State<StateType>,
+    session: AuthSession<BackendType>,
+    access: JournalAccess,
+) -> Result<Response, ErrorRedirect> {
+    let callback_url = &format!("/journal/{}/account", access.journal.id);
+
+    let user = get_user(session)?;
+
+    let accounts: Vec<AccountState> = state
+        .journal_service
+        .list_journal_accounts(access.journal.id, &access.authority())
+        .await
+        .or_redirect(callback_url)?
+        .into_iter()
+        .map(|(account, _, _)| account)
+        .collect();
+
+    let today = user.timezone().date(state.clock.get_time());
+
+    let renderer = PdfRenderer;
+    let report = trial_balance(access.journal.name.as_ref(), today, &accounts);
+
+    Ok((
+        [
+            (CONTENT_TYPE, renderer.content_type().to_string()),
+            (
+                CONTENT_DISPOSITION,
+                format!(
+                    "attachment; filename=\"trial-balance-{}.{}\"",
+                    today.format("%Y-%m-%d"),
+                    renderer.extension()
+                ),
+            ),
+        ],
+        renderer.render(&report),
+    )
+        .into_response())
+}
+
+/// downloads every account and its balance as a csv
+pub async fn chart_of_accounts_csv(
+    State(state): State<StateType>,
+    access: JournalAccess,
+) -> Result<Response, ErrorRedirect> {
+    let callback_url = &format!("/journal/{}/account", access.journal.id);
+
+    let accounts: Vec<AccountState> = state
+        .journal_service
+        .list_journal_accounts(access.journal.id, &access.authority())
+        .await
+        .or_redirect(callback_url)?
+        .into_iter()
+        .map(|(account, _, _)| account)
+        .collect();
+
+    let renderer = CsvRenderer;
+    let report = chart_of_accounts(&accounts);
+
+    Ok((
+        [
+            (CONTENT_TYPE, renderer.content_type().to_string()),
+            (
+                CONTENT_DISPOSITION,
+                format!("attachment; filename=\"accounts.{}\"", renderer.extension()),
+            ),
+        ],
+        renderer.render(&report),
+    )
+        .into_response())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::journal::JournalId;
+    use crate::journal::account::AccountId;
+    use crate::name::Name;
+
+    #[test]
+    fn the_cross_reference_table_points_at_each_object() {
+        let report = Report {
+            title: "Trial Balance".to_string(),
+            subtitle: "Café (main)".to_string(),
+            columns: vec![
+                Column {
+                    heading: "Account",
+                    align: Align::Left,
+                },
+                Column {
+                    heading: "Amount",
+                    align: Align::Right,
+                },
+            ],
+            // enough rows to need a second page
+            rows: (0..60)
+                .map(|i| vec![format!("Account {i}"), format_amount(i * 100)])
+                .collect(),
+            totals: None,
+        };
+
+        let pdf = PdfRenderer.render(&report);
+
+        // the trailer and the cross reference table are ascii, even though the subtitle isn't
+        let startxref = pdf
+            .windows(b"startxref\n".len())
+            .rposition(|window| window == b"startxref\n")
+            .unwrap();
+        let trailer = std::str::from_utf8(&pdf[startxref..]).unwrap();
+        let xref: usize = trailer.lines().nth(1).unwrap().parse().unwrap();
+        assert!(pdf[xref..].starts_with(b"xref\n"));
+
+        let offsets: Vec<usize> = std::str::from_utf8(&pdf[xref..startxref])
+            .unwrap()
+            .lines()
+            .skip(3)
+            .take_while(|line| line.ends_with(" n "))
+            .map(|line| line[..10].parse().unwrap())
+            .collect();
+
+        // the catalog, pages, font, and a page and its content for each of two pages
+        assert_eq!(offsets.len(), 7);
+        for (index, offset) in offsets.into_iter().enumerate() {
+            assert!(pdf[offset..].starts_with(format!("{} 0 obj\n", index + 1).as_bytes()));
+        }
+    }
+
+    #[test]
+    fn the_chart_of_accounts_csv_has_a_header_and_a_row_per_account() {
+        let accounts = vec![
+            AccountState {
+                id: AccountId::new(),
+                journal_id: JournalId::new(),
+                name: Name::try_new("Cash, petty".to_string()).unwrap(),
+                balance: -1205,
+                group: None,
+            },
+            AccountState {
+                id: AccountId::new(),
+                journal_id: JournalId::new(),
+                name: Name::try_new("Revenue".to_string()).unwrap(),
+                balance: 1205,
+                group: None,
+            },
+        ];
+
+        let csv = String::from_utf8(CsvRenderer.render(&chart_of_accounts(&accounts))).unwrap();
+
+        assert_eq!(
+            csv,
+            format!(
+                "Id,Name,Balance,Dr/Cr\n{},\"Cash, petty\",12.05,Dr\n{},Revenue,12.05,Cr\n",
+                accounts[0].id, accounts[1].id
+            )
+        );
+    }
+}
@@ -0,0 +1,283 @@
+use crate::id::{IdGenerator, Ident};
+use crate::journal::account::AccountId;
+use crate::journal::domain::JournalDomainEvent;
+use crate::journal::rebuild::Inconsistency;
+use crate::journal::store::PgJournalEventStore;
+use crate::journal::{JournalError, JournalId, JournalService};
+use axum_login::tracing;
+use disintegrate::EventStore;
+use serde_json::Value;
+use std::collections::{HashMap, HashSet};
+use std::time::Duration;
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+pub enum RestoreError {
+    #[error("{0} isn't a journal id")]
+    InvalidJournalId(String),
+
+    #[error("line {0} of the archive isn't a journal event: {1}")]
+    Line(usize, serde_json::Error),
+
+    #[error("the archive has no events for journal {0}")]
+    NotInArchive(JournalId),
+
+    #[error("failed to replace the journal's ids: {0}")]
+    Remap(serde_json::Error),
+
+    #[error("failed to record the restored events: {0}")]
+    EventStore(#[from] disintegrate_postgres::Error),
+
+    #[error(transparent)]
+    Journal(#[from] JournalError),
+}
+
+/// what restoring a journal did
+#[derive(Debug)]
+pub struct RestoreReport {
+    /// the journal's id on this instance, which is new if the archived one was taken
+    pub journal_id: JournalId,
+    pub events: usize,
+    pub remapped: bool,
+    /// the restored accounts whose projected balance doesn't match their transactions
+    pub drift: Vec<Inconsistency>,
+}
+
+impl RestoreReport {
+    pub fn log(&self) {
+        tracing::info!(
+            journal_id = %self.journal_id,
+            events = self.events,
+            remapped = self.remapped,
+            "restored a journal"
+        );
+
+        for inconsistency in &self.drift {
+            tracing::error!("{inconsistency}");
+        }
+    }
+}
+
+/// how often the projection is checked while waiting for it to catch up
+const POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+/// the ids the journal's events create, which restoring it would record again
+fn created_ids(events: &[JournalDomainEvent]) -> HashSet<Ident> {
+    events
+        .iter()
+        .filter_map(|event| match event {
+            JournalDomainEvent::JournalCreated { journal_id, .. } => Some(**journal_id),
+            JournalDomainEvent::AccountCreated { account_id, .. } => Some(**account_id),
+            JournalDomainEvent::TransactionCreated { transaction_id, .. }
+            | JournalDomainEvent::TransactionSubmitted { transaction_id, .. } => {
+                Some(**transaction_id)
+            }
+            JournalDomainEvent::CommentAdded { comment_id, .. } => Some(**comment_id),
+            JournalDomainEvent::TemplateSaved { template_id, .. } => Some(**template_id),
+            JournalDomainEvent::ImportRuleAdded { rule_id, .. } => Some(**rule_id),
+            _ => None,
+        })
+        .collect()
+}
+
+/// the events in the archive that belong to the journal, in the order they were recorded.
+/// webhooks and bank feeds are left out, since a restored journal would deliver and sync
+/// them again, and their secrets aren't in the archive
+fn journal_events(
+    archive: &str,
+    journal_id: JournalId,
+) -> Result<Vec<JournalDomainEvent>, RestoreError> {
+    let mut events = Vec::new();
+    let mut accounts = HashSet::new();
+    let mut transactions = HashSet::new();
+
+    for (number, line) in archive.lines().enumerate() {
+        if line.trim().is_empty() {
+            continue;
+        }
+
+        let mut line: Value =
+            serde_json::from_str(line).map_err(|e| RestoreError::Line(number + 1, e))?;
+        let event: JournalDomainEvent = serde_json::from_value(line["event"].take())
+            .map_err(|e| RestoreError::Line(number + 1, e))?;
+
+        let belongs = match &event {
+            JournalDomainEvent::WebhookAdded { .. }
+            | JournalDomainEvent::WebhookRemoved { .. }
+            | JournalDomainEvent::BankFeedLinked { .. }
+            | JournalDomainEvent::BankFeedUnlinked { .. } => false,
+            JournalDomainEvent::AccountRenamed { account_id, .. }
+            | JournalDomainEvent::AccountDeleted { account_id, .. } => {
+                accounts.contains(account_id)
+            }
+            JournalDomainEvent::TransactionDeleted { transaction_id, .. } => {
+                transactions.contains(transaction_id)
+            }
+            event => event.journal_id() == Some(journal_id),
+        };
+
+        if !belongs {
+            continue;
+        }
+
+        match &event {
+            JournalDomainEvent::AccountCreated { account_id, .. } => {
+                accounts.insert(*account_id);
+            }
+            JournalDomainEvent::TransactionCreated { transaction_id, .. }
+            | JournalDomainEvent::TransactionSubmitted { transaction_id, .. } => {
+                transactions.insert(*transaction_id);
+            }
+            _ => {}
+        }
+
+        events.push(event);
+    }
+
+    if events.is_empty() {
+        return Err(RestoreError::NotInArchive(journal_id));
+    }
+
+    Ok(events)
+}
+
+/// replaces every id in the value that's in the mapping, wherever it's nested
+fn remap_value(value: &mut Value, mapping: &HashMap<Ident, Ident>) {
+    // an id is written as an object with just its kind, like `{"Cuid10": "..."}`
+    if matches!(value, Value::Object(fields) if fields.len() == 1)
+        && let Ok(id) = serde_json::from_value::<Ident>(value.clone())
+        && let Some(new_id) = mapping.get(&id)
+    {
+        *value = serde_json::to_value(new_id).expect("an id always serializes");
+        return;
+    }
+
+    match value {
+        Value::Array(items) => items.iter_mut().for_each(|item| remap_value(item, mapping)),
+        Value::Object(fields) => fields
+            .values_mut()
+            .for_each(|field| remap_value(field, mapping)),
+        _ => {}
+    }
+}
+
+/// the events with each id in the mapping swapped for its new one
+fn remap(
+    events: Vec<JournalDomainEvent>,
+    mapping: &HashMap<Ident, Ident>,
+) -> Result<Vec<JournalDomainEvent>, serde_json::Error> {
+    events
+        .into_iter()
+        .map(|event| {
+            let mut value = serde_json::to_value(event)?;
+            remap_value(&mut value, mapping);
+            serde_json::from_value(value)
+        })
+        .collect()
+}
+
+/// records the journal's events from an archive written by the backup job, then waits for
+/// them to be projected and checks the restored balances against their transactions. when
+/// any of the journal's ids is already taken on this instance, every id the journal created
+/// is replaced, so a journal can be restored next to the one it was backed up from
+pub async fn restore_journal(
+    journal_service: &JournalService,
+    event_store: &PgJournalEventStore,
+    ids: &IdGenerator,
+    archive: &str,
+    journal_id: JournalId,
+) -> Result<RestoreReport, RestoreError> {
+    let events = journal_events(archive, journal_id)?;
+    let created = created_ids(&events);
+
+    let taken: Vec<String> = created.iter().map(|id| id.to_string()).collect();
+    let remapped = journal_service.ids_in_use(&taken).await?;
+
+    let (events, journal_id) = if remapped {
+        let mapping: HashMap<Ident, Ident> =
+            created.into_iter().map(|id| (id, ids.generate())).collect();
+        let new_journal_id = mapping
+            .get(&*journal_id)
+            .map_or(journal_id, |id| JournalId::from(*id));
+
+        (
+            remap(events, &mapping).map_err(RestoreError::Remap)?,
+            new_journal_id,
+        )
+    } else {
+        (events, journal_id)
+    };
+
+    let restored_accounts: HashSet<AccountId> = events
+        .iter()
+        .filter_map(|event| match event {
+            JournalDomainEvent::AccountCreated { account_id, .. } => Some(*account_id),
+            _ => None,
+        })
+        .collect();
+
+    let count = events.len();
+    let persisted = event_store.append_without_validation(events).await?;
+    let last_event_id = persisted.last().map(|event| event.id()).unwrap_or_default();
+
+    while journal_service.projected_through().await? < last_event_id {
+        tokio::time::sleep(POLL_INTERVAL).await;
+    }
+
+    let drift = journal_service
+        .check_balances(false)
+        .await?
+        .into_iter()
+        .filter(|inconsistency| match inconsistency {
+            Inconsistency::BalanceMismatch { account_id, .. } => {
+                restored_accounts.contains(account_id)
+            }
+            _ => false,
+        })
+        .collect();
+
+    Ok(RestoreReport {
+        journal_id,
+        events: count,
+        remapped,
+        drift,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn ids_are_replaced_wherever_theyre_nested() {
+        let account = Ident::new10();
+        let restored = Ident::new10();
+        let other = Ident::new10();
+
+        let mut value = json!({
+            "TransactionCreated": {
+                "entries": [
+                    { "account_id": account, "amount": 500 },
+                    { "account_id": other, "amount": 500 },
+                ],
+                "label": account.to_string(),
+            }
+        });
+        remap_value(&mut value, &HashMap::from([(account, restored)]));
+
+        assert_eq!(
+            value["TransactionCreated"]["entries"][0]["account_id"],
+            json!(restored)
+        );
+        assert_eq!(
+            value["TransactionCreated"]["entries"][1]["account_id"],
+            json!(other)
+        );
+        // only ids are replaced, not text that happens to spell one
+        assert_eq!(
+            value["TransactionCreated"]["label"],
+            json!(account.to_string())
+        );
+    }
+}
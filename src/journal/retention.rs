@@ -0,0 +1,181 @@
+use crate::journal::JournalService;
+use crate::shutdown;
+use crate::time_provider::{TimeProvider, Timestamp};
+use axum_login::tracing;
+use chrono::TimeDelta;
+use std::env;
+use std::time::Duration;
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+pub enum RetentionConfigError {
+    #[error("JOURNAL_RETENTION_DAYS must be a whole number of days, found {0}")]
+    Days(String),
+
+    #[error("JOURNAL_PURGE_INTERVAL_SECS must be a whole number of seconds, found {0}")]
+    Interval(String),
+}
+
+/// how long a deleted journal's events are kept before they're purged, and how often the
+/// purge runs
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct RetentionConfig {
+    pub days: u32,
+    pub interval: Duration,
+}
+
+impl Default for RetentionConfig {
+    fn default() -> Self {
+        Self {
+            days: 30,
+            interval: Duration::from_secs(60 * 60),
+        }
+    }
+}
+
+impl RetentionConfig {
+    pub fn from_env() -> Result<Self, RetentionConfigError> {
+        let default = Self::default();
+
+        let days = match env::var("JOURNAL_RETENTION_DAYS") {
+            Ok(value) => value
+                .parse::<u32>()
+                .map_err(|_| RetentionConfigError::Days(value))?,
+            Err(_) => default.days,
+        };
+
+        let interval = match env::var("JOURNAL_PURGE_INTERVAL_SECS") {
+            Ok(value) => Duration::from_secs(
+                value
+                    .parse::<u64>()
+                    .map_err(|_| RetentionConfigError::Interval(value))?,
+            ),
+            Err(_) => default.interval,
+        };
+
+        Ok(Self { days, interval })
+    }
+
+    /// journals deleted before this are due to be purged
+    pub fn purge_before(&self, now: Timestamp) -> Timestamp {
+        now - TimeDelta::days(i64::from(self.days))
+    }
+}
+
+/// periodically purges the journals whose retention period has passed since they were deleted,
+/// so their events and everything projected from them are gone for good
+pub async fn purge_deleted_journals(journal_service: JournalService, config: RetentionConfig) {
+    let mut shutdown = Box::pin(shutdown());
+
+    loop {
+        tokio::select! {
+            _ = tokio::time::sleep(config.interval) => {},
+            _ = &mut shutdown => return,
+        }
+
+        let before = config.purge_before(journal_service.clock().get_time());
+
+        match journal_service.purge_deleted_journals(before).await {
+            Ok(purged) => {
+                for journal_id in purged {
+                    tracing::info!(%journal_id, "purged a deleted journal");
+                }
+            }
+            Err(error) => tracing::error!(?error, "failed to purge the deleted journals"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::authority::{Actor, Authority};
+    use crate::name::Name;
+    use crate::test_app::{TestApp, assert_accepted};
+    use chrono::{TimeZone, Utc};
+
+    #[test]
+    fn journals_are_purged_once_their_retention_has_passed() {
+        let config = RetentionConfig {
+            days: 30,
+            ..RetentionConfig::default()
+        };
+        let now = Utc
+            .with_ymd_and_hms(2026, 10, 17, 12, 0, 0)
+            .single()
+            .expect("invalid time");
+
+        assert_eq!(
+            config.purge_before(now),
+            Utc.with_ymd_and_hms(2026, 9, 17, 12, 0, 0)
+                .single()
+                .expect("invalid time")
+        );
+    }
+
+    #[tokio::test]
+    async fn purged_journals_leave_no_events_behind() {
+        let Some(app) = TestApp::spawn().await else {
+            return;
+        };
+
+        let owner = app.signup("purged").await;
+        let journal_id = owner.create_journal(&app, "Forgotten").await;
+        assert_accepted(&owner.create_account(journal_id, "Cash").await);
+        assert_accepted(&owner.create_account(journal_id, "Secret").await);
+        let cash = app.account_id(journal_id, "Cash").await;
+        let secret = app.account_id(journal_id, "Secret").await;
+        assert_accepted(
+            &owner
+                .post_transaction(journal_id, cash, secret, "3.00")
+                .await,
+        );
+
+        // a rename is keyed only by its account
+        let event_id = app
+            .state
+            .journal_service
+            .rename_account(
+                secret,
+                journal_id,
+                Name::try_new("Renamed".to_string()).expect("invalid name"),
+                Authority::Direct(Actor::User(owner.id)),
+                app.state.clock.get_time(),
+            )
+            .await
+            .expect("failed to rename the account");
+        app.state.journal_service.wait_for(event_id).await;
+
+        let transaction_id = app
+            .state
+            .journal_service
+            .list_journal_transactions(journal_id, &Authority::Direct(Actor::System))
+            .await
+            .expect("failed to list the transactions")[0]
+            .0
+            .id;
+        let ids = [
+            journal_id.to_string(),
+            cash.to_string(),
+            secret.to_string(),
+            transaction_id.to_string(),
+        ];
+        assert!(app.state.journal_service.ids_in_use(&ids).await.unwrap());
+
+        assert_accepted(
+            &owner
+                .client
+                .post(&format!("/journal/{journal_id}/delete"))
+                .await,
+        );
+        let purged = app
+            .state
+            .journal_service
+            .purge_deleted_journals(app.state.clock.get_time() + chrono::TimeDelta::days(1))
+            .await
+            .expect("failed to purge the journals");
+        assert!(purged.contains(&journal_id));
+
+        assert!(!app.state.journal_service.ids_in_use(&ids).await.unwrap());
+    }
+}
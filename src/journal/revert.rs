@@ -0,0 +1,333 @@
+use crate::StateType;
+use crate::authn::user::UserId;
+use crate::journal::access::{JournalAccess, Owner};
+use crate::journal::account::AccountId;
+use crate::journal::appearance::JournalAppearance;
+use crate::journal::domain::JournalDomainEvent;
+use crate::journal::fiscal::FiscalYearStart;
+use crate::journal::service::JournalEventEnvelope;
+use crate::journal::{JournalError, Permissions};
+use crate::monkesto_error::{ErrorRedirect, OrRedirect};
+use crate::name::Name;
+use crate::time_provider::TimeProvider;
+use axum::extract::{Path, State};
+use axum::response::Redirect;
+use disintegrate_postgres::PgEventId;
+use futures::TryStreamExt;
+use std::collections::HashMap;
+
+/// what reverting a change records to put back what the change replaced. only these changes
+/// can be reverted:
+///
+/// - updating the journal's settings, which restores the settings before it
+/// - changing a member's permissions, which restores the permissions they had before, as long
+///   as they're still a member
+/// - renaming an account, which restores its name before
+///
+/// reverting records a change of the same kind, so reverting again redoes it. transactions are
+/// reversed rather than reverted, and the rest can't be taken back this way
+#[derive(Clone, Debug, PartialEq)]
+pub enum Reversion {
+    Settings {
+        fiscal_year_start: FiscalYearStart,
+        default_cash_account: Option<AccountId>,
+        default_expense_account: Option<AccountId>,
+        appearance: JournalAppearance,
+    },
+    Permissions {
+        user_id: UserId,
+        permissions: Permissions,
+    },
+    AccountName {
+        account_id: AccountId,
+        name: Name,
+    },
+}
+
+/// the most recent change in the history that can be reverted, and what reverting it records
+pub fn last_reversion(events: &[JournalEventEnvelope]) -> Option<(PgEventId, Reversion)> {
+    // a journal starts with the default settings until they're first updated
+    let mut settings = Reversion::Settings {
+        fiscal_year_start: FiscalYearStart::default(),
+        default_cash_account: None,
+        default_expense_account: None,
+        appearance: JournalAppearance::default(),
+    };
+    let mut permissions: HashMap<UserId, Permissions> = HashMap::new();
+    let mut account_names: HashMap<AccountId, Name> = HashMap::new();
+
+    let mut last = None;
+
+    for envelope in events {
+        match &envelope.event {
+            JournalDomainEvent::JournalSettingsUpdated {
+                fiscal_year_start,
+                default_cash_account,
+                default_expense_account,
+                appearance,
+                ..
+            } => {
+                let updated = Reversion::Settings {
+                    fiscal_year_start: *fiscal_year_start,
+                    default_cash_account: *default_cash_account,
+                    default_expense_account: *default_expense_account,
+                    appearance: appearance.clone(),
+                };
+                last = Some((envelope.event_id, std::mem::replace(&mut settings, updated)));
+            }
+            JournalDomainEvent::MemberAdded {
+                user_id,
+                permissions: granted,
+                ..
+            } => {
+                permissions.insert(*user_id, *granted);
+            }
+            JournalDomainEvent::MemberPermissionsUpdated {
+                user_id,
+                permissions: granted,
+                ..
+            } => {
+                if let Some(previous) = permissions.insert(*user_id, *granted) {
+                    last = Some((
+                        envelope.event_id,
+                        Reversion::Permissions {
+                            user_id: *user_id,
+                            permissions: previous,
+                        },
+                    ));
+                }
+            }
+            JournalDomainEvent::MemberRemoved { user_id, .. } => {
+                permissions.remove(user_id);
+                // their permissions can't be put back once they're no longer a member
+                if let Some((
+                    _,
+                    Reversion::Permissions {
+                        user_id: target, ..
+                    },
+                )) = &last
+                    && target == user_id
+                {
+                    last = None;
+                }
+            }
+            JournalDomainEvent::AccountCreated {
+                account_id, name, ..
+            } => {
+                account_names.insert(*account_id, name.clone());
+            }
+            JournalDomainEvent::AccountRenamed {
+                account_id,
+                new_name,
+                ..
+            } => {
+                if let Some(previous) = account_names.insert(*account_id, new_name.clone()) {
+                    last = Some((
+                        envelope.event_id,
+                        Reversion::AccountName {
+                            account_id: *account_id,
+                            name: previous,
+                        },
+                    ));
+                }
+            }
+            _ => {}
+        }
+    }
+
+    last
+}
+
+/// reverts the change the history page offered. the page names the change's event, so if
+/// another change has become the most recent since, nothing is reverted
+pub async fn revert_last(
+    State(state): State<StateType>,
+    access: JournalAccess<Owner>,
+    Path((id, event_id)): Path<(String, PgEventId)>,
+) -> Result<Redirect, ErrorRedirect> {
+    let callback_url = &format!("/journal/{}/history", id);
+
+    let journal_id = access.journal.id;
+    let authority = access.authority();
+
+    let events: Vec<JournalEventEnvelope> = state
+        .journal_service
+        .stream_journal_events(journal_id, &authority)
+        .await
+        .or_redirect(callback_url)?
+        .try_collect()
+        .await
+        .or_redirect(callback_url)?;
+
+    let reversion = last_reversion(&events)
+        .filter(|(last_event_id, _)| *last_event_id == event_id)
+        .map(|(_, reversion)| reversion)
+        .ok_or(JournalError::UndoExpired)
+        .or_redirect(callback_url)?;
+
+    let now = state.clock.get_time();
+
+    let event_id = match reversion {
+        Reversion::Settings {
+            fiscal_year_start,
+            default_cash_account,
+            default_expense_account,
+            appearance,
+        } => {
+            state
+                .journal_service
+                .update_journal_settings(
+                    journal_id,
+                    fiscal_year_start,
+                    default_cash_account,
+                    default_expense_account,
+                    appearance,
+                    authority,
+                    now,
+                )
+                .await
+        }
+        Reversion::Permissions {
+            user_id,
+            permissions,
+        } => {
+            state
+                .journal_service
+                .update_member(journal_id, user_id, permissions, authority, now)
+                .await
+        }
+        Reversion::AccountName { account_id, name } => {
+            state
+                .journal_service
+                .rename_account(account_id, journal_id, name, authority, now)
+                .await
+        }
+    }
+    .or_redirect(callback_url)?;
+
+    state.journal_service.wait_for(event_id).await;
+
+    Ok(Redirect::to(callback_url))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::authority::{Actor, Authority};
+    use crate::journal::JournalId;
+    use crate::journal::transaction::TransactionId;
+    use crate::time_provider::DefaultTimeProvider;
+
+    fn envelopes(events: Vec<JournalDomainEvent>) -> Vec<JournalEventEnvelope> {
+        events
+            .into_iter()
+            .enumerate()
+            .map(|(i, event)| JournalEventEnvelope {
+                event_id: i as i64 + 1,
+                event,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn the_latest_reversible_change_is_reverted_to_what_came_before() {
+        let journal_id = JournalId::new();
+        let account_id = AccountId::new();
+        let member = UserId::new();
+        let authority = Authority::Direct(Actor::System);
+        let timestamp = DefaultTimeProvider.get_time();
+        let name = |name: &str| Name::try_new(name.to_string()).expect("invalid name");
+
+        let mut events = vec![
+            JournalDomainEvent::AccountCreated {
+                account_id,
+                journal_id,
+                name: name("Cash"),
+                authority: authority.clone(),
+                timestamp,
+            },
+            JournalDomainEvent::MemberAdded {
+                journal_id,
+                user_id: member,
+                permissions: Permissions::READ,
+                authority: authority.clone(),
+                timestamp,
+            },
+            JournalDomainEvent::MemberPermissionsUpdated {
+                journal_id,
+                user_id: member,
+                permissions: Permissions::READ | Permissions::INVITE,
+                authority: authority.clone(),
+                timestamp,
+            },
+        ];
+        assert_eq!(
+            last_reversion(&envelopes(events.clone())),
+            Some((
+                3,
+                Reversion::Permissions {
+                    user_id: member,
+                    permissions: Permissions::READ,
+                }
+            ))
+        );
+
+        // the member is gone, so there's nothing to restore their permissions on
+        events.push(JournalDomainEvent::MemberRemoved {
+            journal_id,
+            user_id: member,
+            authority: authority.clone(),
+            timestamp,
+        });
+        assert_eq!(last_reversion(&envelopes(events.clone())), None);
+
+        events.extend([
+            JournalDomainEvent::AccountRenamed {
+                account_id,
+                new_name: name("Checking"),
+                authority: authority.clone(),
+                timestamp,
+            },
+            // transactions can't be reverted, so they don't hide the rename
+            JournalDomainEvent::TransactionDeleted {
+                transaction_id: TransactionId::new(),
+                authority,
+                timestamp,
+            },
+        ]);
+        assert_eq!(
+            last_reversion(&envelopes(events)),
+            Some((
+                5,
+                Reversion::AccountName {
+                    account_id,
+                    name: name("Cash"),
+                }
+            ))
+        );
+    }
+
+    #[test]
+    fn the_first_settings_update_reverts_to_the_defaults() {
+        let events = envelopes(vec![JournalDomainEvent::JournalSettingsUpdated {
+            journal_id: JournalId::new(),
+            fiscal_year_start: FiscalYearStart::default(),
+            default_cash_account: None,
+            default_expense_account: None,
+            appearance: JournalAppearance::default(),
+            authority: Authority::Direct(Actor::System),
+            timestamp: DefaultTimeProvider.get_time(),
+        }]);
+
+        assert!(matches!(
+            last_reversion(&events),
+            Some((
+                1,
+                Reversion::Settings {
+                    default_cash_account: None,
+                    ..
+                }
+            ))
+        ));
+    }
+}
@@ -0,0 +1,86 @@
+use crate::StateType;
+use crate::journal::access::{JournalAccess, Owner};
+use crate::journal::account::AccountId;
+use crate::journal::rule::{RuleCondition, RuleId};
+use crate::monkesto_error::{ErrorRedirect, OrRedirect};
+use crate::parse;
+use crate::time_provider::TimeProvider;
+use axum::extract::Path;
+use axum::extract::State;
+use axum::response::Redirect;
+use axum_extra::extract::Form;
+use serde::Deserialize;
+use std::str::FromStr;
+
+#[derive(Deserialize)]
+pub struct AddRuleForm {
+    pattern: String,
+    min_amount: String,
+    max_amount: String,
+    account_id: String,
+}
+
+pub async fn add_rule(
+    State(state): State<StateType>,
+    access: JournalAccess<Owner>,
+    Form(form): Form<AddRuleForm>,
+) -> Result<Redirect, ErrorRedirect> {
+    let callback_url = &format!("/journal/{}/rule", access.journal.id);
+
+    let journal_id = access.journal.id;
+    let account_id = AccountId::from_str(&form.account_id).or_redirect(callback_url)?;
+
+    let bound = |amount: &str| match amount.trim() {
+        "" => Ok(None),
+        amount => parse::amount::amount(amount).map(Some),
+    };
+
+    let condition = RuleCondition {
+        pattern: form.pattern.trim().to_string(),
+        min_amount: bound(&form.min_amount).or_redirect(callback_url)?,
+        max_amount: bound(&form.max_amount).or_redirect(callback_url)?,
+    };
+
+    let event_id = state
+        .journal_service
+        .add_rule(
+            state.ids.generate(),
+            journal_id,
+            condition,
+            account_id,
+            access.authority(),
+            state.clock.get_time(),
+        )
+        .await
+        .or_redirect(callback_url)?;
+
+    state.journal_service.wait_for(event_id).await;
+
+    Ok(Redirect::to(callback_url))
+}
+
+pub async fn remove_rule(
+    State(state): State<StateType>,
+    access: JournalAccess<Owner>,
+    Path((id, rule_id)): Path<(String, String)>,
+) -> Result<Redirect, ErrorRedirect> {
+    let callback_url = &format!("/journal/{}/rule", id);
+
+    let journal_id = access.journal.id;
+    let rule_id = RuleId::from_str(&rule_id).or_redirect(callback_url)?;
+
+    let event_id = state
+        .journal_service
+        .remove_rule(
+            rule_id,
+            journal_id,
+            access.authority(),
+            state.clock.get_time(),
+        )
+        .await
+        .or_redirect(callback_url)?;
+
+    state.journal_service.wait_for(event_id).await;
+
+    Ok(Redirect::to(callback_url))
+}
@@ -0,0 +1,343 @@
+pub mod commands;
+pub mod views;
+
+use axum::Router;
+use axum::routing::{get, post};
+use axum_login::login_required;
+
+pub fn router() -> Router<crate::StateType> {
+    Router::new()
+        .route("/journal/{id}/rule", get(views::rule_list_page))
+        .route("/journal/{id}/rule", post(commands::add_rule))
+        .route(
+            "/journal/{id}/rule/{rule_id}/remove",
+            post(commands::remove_rule),
+        )
+        .route_layer(login_required!(crate::BackendType, login_url = "/signin"))
+}
+
+use crate::authority::Authority;
+use crate::id;
+use crate::id::Ident;
+use crate::journal::account::AccountId;
+use crate::journal::domain::{ImportRuleEvent, JournalDomainEvent};
+use crate::journal::member::JournalMember;
+use crate::journal::transaction::AllJournalAccounts;
+use crate::journal::transaction::views::format_amount;
+use crate::journal::{Journal, Permissions, check_permissions};
+use crate::journal::{JournalError, JournalId};
+use crate::status::Status;
+use crate::time_provider::Timestamp;
+use disintegrate::{Decision, StateMutate, StateQuery};
+use regex::{Regex, RegexBuilder};
+use serde::Deserialize;
+use serde::Serialize;
+use std::fmt::Display;
+use std::fmt::Formatter;
+
+id!(RuleId, Ident::new16());
+
+/// what an imported line must look like for a rule to choose its account. a line matches
+/// when its description matches the pattern, ignoring case, and its amount is in the range.
+/// an empty pattern matches any description, and a missing bound leaves that end open
+#[derive(Debug, Clone, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub struct RuleCondition {
+    pub pattern: String,
+    pub min_amount: Option<u64>,
+    pub max_amount: Option<u64>,
+}
+
+impl Display for RuleCondition {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let mut parts = Vec::new();
+
+        if !self.pattern.is_empty() {
+            parts.push(format!("description matches {}", self.pattern));
+        }
+
+        match (self.min_amount, self.max_amount) {
+            (Some(min), Some(max)) => parts.push(format!(
+                "amount from {} to {}",
+                format_amount(min),
+                format_amount(max)
+            )),
+            (Some(min), None) => parts.push(format!("amount at least {}", format_amount(min))),
+            (None, Some(max)) => parts.push(format!("amount at most {}", format_amount(max))),
+            (None, None) => {}
+        }
+
+        write!(f, "{}", parts.join(" and "))
+    }
+}
+
+/// a condition with its pattern compiled, so it can be checked against many lines
+pub struct RuleMatcher {
+    regex: Option<Regex>,
+    min_amount: Option<u64>,
+    max_amount: Option<u64>,
+}
+
+impl RuleCondition {
+    pub fn matcher(&self) -> Result<RuleMatcher, JournalError> {
+        if self.pattern.is_empty() && self.min_amount.is_none() && self.max_amount.is_none() {
+            return Err(JournalError::InvalidRuleCondition(
+                "a rule needs a pattern or an amount range".to_string(),
+            ));
+        }
+
+        if let (Some(min), Some(max)) = (self.min_amount, self.max_amount)
+            && min > max
+        {
+            return Err(JournalError::InvalidRuleCondition(
+                "the minimum amount is greater than the maximum".to_string(),
+            ));
+        }
+
+        let regex = match self.pattern.as_str() {
+            "" => None,
+            pattern => Some(
+                RegexBuilder::new(pattern)
+                    .case_insensitive(true)
+                    .size_limit(1 << 16)
+                    .build()
+                    .map_err(|e| JournalError::InvalidRuleCondition(e.to_string()))?,
+            ),
+        };
+
+        Ok(RuleMatcher {
+            regex,
+            min_amount: self.min_amount,
+            max_amount: self.max_amount,
+        })
+    }
+}
+
+impl RuleMatcher {
+    pub fn matches(&self, description: &str, amount: u64) -> bool {
+        self.regex
+            .as_ref()
+            .is_none_or(|regex| regex.is_match(description))
+            && self.min_amount.is_none_or(|min| amount >= min)
+            && self.max_amount.is_none_or(|max| amount <= max)
+    }
+}
+
+#[derive(StateQuery, Clone, Default, Serialize, Deserialize)]
+#[state_query(ImportRuleEvent)]
+pub struct ImportRule {
+    #[id]
+    rule_id: RuleId,
+    journal_id: JournalId,
+    status: Status,
+}
+
+impl ImportRule {
+    fn new(rule_id: RuleId) -> Self {
+        Self {
+            rule_id,
+            ..Default::default()
+        }
+    }
+}
+
+impl StateMutate for ImportRule {
+    fn mutate(&mut self, event: Self::Event) {
+        match event {
+            ImportRuleEvent::ImportRuleAdded { journal_id, .. } => {
+                self.journal_id = journal_id;
+                self.status = Status::Valid;
+            }
+            ImportRuleEvent::ImportRuleRemoved { .. } => self.status = Status::Deleted,
+        }
+    }
+}
+
+pub struct AddRule {
+    rule_id: RuleId,
+    journal_id: JournalId,
+    condition: RuleCondition,
+    account_id: AccountId,
+    authority: Authority,
+    timestamp: Timestamp,
+}
+
+impl AddRule {
+    pub fn new(
+        rule_id: RuleId,
+        journal_id: JournalId,
+        condition: RuleCondition,
+        account_id: AccountId,
+        authority: Authority,
+        timestamp: Timestamp,
+    ) -> Self {
+        Self {
+            rule_id,
+            journal_id,
+            condition,
+            account_id,
+            authority,
+            timestamp,
+        }
+    }
+}
+
+impl Decision for AddRule {
+    type Event = JournalDomainEvent;
+    type StateQuery = (ImportRule, AllJournalAccounts, Journal, JournalMember);
+    type Error = JournalError;
+
+    fn state_query(&self) -> Self::StateQuery {
+        (
+            ImportRule::new(self.rule_id),
+            AllJournalAccounts::new(self.journal_id),
+            Journal::new(self.journal_id),
+            JournalMember::new(
+                self.journal_id,
+                self.authority.user_id().unwrap_or_default(),
+            ),
+        )
+    }
+
+    fn process(
+        &self,
+        (rule, accounts, journal, actor): &Self::StateQuery,
+    ) -> Result<Vec<Self::Event>, Self::Error> {
+        if rule.status.found() {
+            return Err(JournalError::RuleIdCollision(self.rule_id));
+        }
+
+        if !journal.status.valid() {
+            return Err(JournalError::InvalidJournal(self.journal_id));
+        }
+
+        // rules are part of the journal's settings, which only owners change
+        check_permissions(actor, &self.authority, journal, Permissions::OWNER)?;
+
+        if !accounts.contains(&self.account_id) {
+            return Err(JournalError::InvalidAccount(self.account_id));
+        }
+
+        self.condition.matcher()?;
+
+        Ok(vec![JournalDomainEvent::ImportRuleAdded {
+            rule_id: self.rule_id,
+            journal_id: self.journal_id,
+            condition: self.condition.clone(),
+            account_id: self.account_id,
+            authority: self.authority.clone(),
+            timestamp: self.timestamp,
+        }])
+    }
+}
+
+pub struct RemoveRule {
+    rule_id: RuleId,
+    journal_id: JournalId,
+    authority: Authority,
+    timestamp: Timestamp,
+}
+
+impl RemoveRule {
+    pub fn new(
+        rule_id: RuleId,
+        journal_id: JournalId,
+        authority: Authority,
+        timestamp: Timestamp,
+    ) -> Self {
+        Self {
+            rule_id,
+            journal_id,
+            authority,
+            timestamp,
+        }
+    }
+}
+
+impl Decision for RemoveRule {
+    type Event = JournalDomainEvent;
+    type StateQuery = (ImportRule, Journal, JournalMember);
+    type Error = JournalError;
+
+    fn state_query(&self) -> Self::StateQuery {
+        (
+            ImportRule::new(self.rule_id),
+            Journal::new(self.journal_id),
+            JournalMember::new(
+                self.journal_id,
+                self.authority.user_id().unwrap_or_default(),
+            ),
+        )
+    }
+
+    fn process(
+        &self,
+        (rule, journal, actor): &Self::StateQuery,
+    ) -> Result<Vec<Self::Event>, Self::Error> {
+        if !rule.status.valid() || rule.journal_id != self.journal_id {
+            return Err(JournalError::InvalidRule(self.rule_id));
+        }
+
+        if !journal.status.valid() {
+            return Err(JournalError::InvalidJournal(self.journal_id));
+        }
+
+        check_permissions(actor, &self.authority, journal, Permissions::OWNER)?;
+
+        Ok(vec![JournalDomainEvent::ImportRuleRemoved {
+            rule_id: self.rule_id,
+            journal_id: self.journal_id,
+            authority: self.authority.clone(),
+            timestamp: self.timestamp,
+        }])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn conditions_match_descriptions_and_amount_ranges() {
+        let coffee = RuleCondition {
+            pattern: "starbucks|peet'?s".to_string(),
+            min_amount: None,
+            max_amount: Some(2000),
+        }
+        .matcher()
+        .expect("the condition is valid");
+
+        assert!(coffee.matches("STARBUCKS #1234 SEATTLE", 575));
+        assert!(coffee.matches("Peets Coffee", 2000));
+        assert!(!coffee.matches("Starbucks catering", 2001));
+        assert!(!coffee.matches("Blue Bottle", 575));
+
+        let large = RuleCondition {
+            pattern: String::new(),
+            min_amount: Some(100000),
+            max_amount: None,
+        }
+        .matcher()
+        .expect("the condition is valid");
+        assert!(large.matches("anything", 100000));
+        assert!(!large.matches("anything", 99999));
+
+        assert!(RuleCondition::default().matcher().is_err());
+        assert!(
+            RuleCondition {
+                pattern: "(".to_string(),
+                ..Default::default()
+            }
+            .matcher()
+            .is_err()
+        );
+        assert!(
+            RuleCondition {
+                pattern: String::new(),
+                min_amount: Some(2),
+                max_amount: Some(1),
+            }
+            .matcher()
+            .is_err()
+        );
+    }
+}
@@ -0,0 +1,162 @@
+use crate::StateType;
+use crate::journal::access::JournalAccess;
+use crate::journal::layout::journal_layout;
+use crate::monkesto_error::MonkestoError;
+use crate::monkesto_error::UrlError;
+use axum::extract::Query;
+use axum::extract::State;
+use maud::Markup;
+use maud::html;
+use std::collections::HashMap;
+
+pub async fn rule_list_page(
+    State(state): State<StateType>,
+    access: JournalAccess,
+    Query(err): Query<UrlError>,
+) -> Markup {
+    let authority = access.authority();
+    let journal_id = access.journal.id;
+    let id = journal_id.to_string();
+
+    let accounts = state
+        .journal_service
+        .list_journal_accounts(journal_id, &authority)
+        .await
+        .map(|accounts| {
+            accounts
+                .into_iter()
+                .map(|(account, _, _)| (account.id, account.name))
+                .collect::<Vec<_>>()
+        })
+        .unwrap_or_default();
+    let account_names: HashMap<_, _> = accounts.iter().cloned().collect();
+
+    let content = html! {
+        p class="text-sm text-gray-500 dark:text-gray-400" {
+            "When a line of a bulk import leaves its account blank, the first rule that matches "
+            "the line's description and amount chooses the account. "
+            "Patterns are regular expressions and ignore case."
+        }
+
+        @match state.journal_service.list_import_rules(journal_id, &authority).await {
+            Ok(rules) => {
+                @for (position, rule) in rules.iter().enumerate() {
+                    div class="p-4 bg-white dark:bg-gray-800 border border-gray-200 dark:border-gray-700 rounded-xl" {
+                        div class="flex justify-between items-center gap-4" {
+                            div class="min-w-0 space-y-1" {
+                                h3 class="text-base font-semibold text-gray-900 dark:text-white" {
+                                    (position + 1) ". "
+                                    (account_names.get(&rule.account_id).map(|name| name.as_ref()).unwrap_or("Deleted account"))
+                                }
+                                p class="text-xs text-gray-500 dark:text-gray-400 break-all" {
+                                    "When the " (rule.condition)
+                                }
+                            }
+                            form method="post" action=(format!("/journal/{}/rule/{}/remove", id, rule.id)) {
+                                button
+                                type="submit"
+                                class="rounded-md px-3 py-1.5 text-sm font-semibold text-red-600 hover:bg-red-50 dark:text-red-400 dark:hover:bg-red-950" {
+                                    "Remove"
+                                }
+                            }
+                        }
+                    }
+                }
+            },
+            Err(e) => {
+                p {
+                    "failed to get the import rules for " (journal_id) ": " (e)
+                }
+            }
+        }
+
+        hr class="mt-8 mb-6 border-gray-300 dark:border-gray-600";
+
+        div class="mt-10" {
+            form action=(format!("/journal/{}/rule", id)) method="post" class="space-y-4" {
+                h3 class="text-base font-semibold text-gray-900 dark:text-gray-100" { "Add Import Rule" }
+
+                div {
+                    label
+                    for="pattern"
+                    class="block text-sm/6 font-medium text-gray-900 dark:text-gray-100" {
+                        "Description pattern"
+                    }
+                    div class="mt-2" {
+                        input
+                        id="pattern"
+                        type="text"
+                        name="pattern"
+                        placeholder="starbucks|peet's"
+                        class="block w-full rounded-md bg-white px-3 py-1.5 text-base text-gray-900 outline-1 -outline-offset-1 outline-gray-300 placeholder:text-gray-400 focus:outline-2 focus:-outline-offset-2 focus:outline-indigo-600 sm:text-sm/6 dark:bg-white/5 dark:text-white dark:outline-white/10 dark:placeholder:text-gray-500 dark:focus:outline-indigo-500"
+                        ;
+                    }
+                }
+
+                div class="grid grid-cols-2 gap-4" {
+                    @for (name, label) in [("min_amount", "Minimum amount"), ("max_amount", "Maximum amount")] {
+                        div {
+                            label
+                            for=(name)
+                            class="block text-sm/6 font-medium text-gray-900 dark:text-gray-100" {
+                                (label)
+                            }
+                            div class="mt-2" {
+                                input
+                                id=(name)
+                                type="text"
+                                name=(name)
+                                inputmode="decimal"
+                                placeholder="Any"
+                                class="block w-full rounded-md bg-white px-3 py-1.5 text-base text-gray-900 outline-1 -outline-offset-1 outline-gray-300 placeholder:text-gray-400 focus:outline-2 focus:-outline-offset-2 focus:outline-indigo-600 sm:text-sm/6 dark:bg-white/5 dark:text-white dark:outline-white/10 dark:placeholder:text-gray-500 dark:focus:outline-indigo-500"
+                                ;
+                            }
+                        }
+                    }
+                }
+
+                div {
+                    label
+                    for="account_id"
+                    class="block text-sm/6 font-medium text-gray-900 dark:text-gray-100" {
+                        "Account"
+                    }
+                    div class="mt-2" {
+                        select
+                        id="account_id"
+                        name="account_id"
+                        required
+                        class="block w-full rounded-md bg-white px-3 py-1.5 text-base text-gray-900 outline-1 -outline-offset-1 outline-gray-300 focus:outline-2 focus:-outline-offset-2 focus:outline-indigo-600 sm:text-sm/6 dark:bg-white/5 dark:text-white dark:outline-white/10 dark:focus:outline-indigo-500" {
+                            option value="" { "Select account..." }
+                            @for (account_id, name) in &accounts {
+                                option value=(account_id) { (name) }
+                            }
+                        }
+                    }
+                }
+
+                div {
+                    button
+                    type="submit"
+                    class="flex w-full justify-center rounded-md bg-indigo-600 px-3 py-1.5 text-sm/6 font-semibold text-white shadow-xs hover:bg-indigo-500 focus-visible:outline-2 focus-visible:outline-offset-2 focus-visible:outline-indigo-600 dark:bg-indigo-500 dark:shadow-none dark:hover:bg-indigo-400 dark:focus-visible:outline-indigo-500" {
+                        "Add Rule"
+                    }
+                }
+            }
+        }
+
+        @if let Some(e) = err.err {
+            p {
+                (format!("An error occurred: {:?}", MonkestoError::decode(&e)))
+            }
+        }
+    };
+
+    let wrapped_content = html! {
+        div class="flex flex-col gap-6 mx-auto w-full max-w-4xl" {
+            (content)
+        }
+    };
+
+    journal_layout(&access.journal, wrapped_content)
+}
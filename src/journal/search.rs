@@ -0,0 +1,240 @@
+use crate::StateType;
+use crate::authn::user::UserState;
+use crate::authority::Authority;
+use crate::journal::JournalId;
+use crate::journal::access::JournalAccess;
+use crate::journal::account::AccountId;
+use crate::journal::layout::journal_layout;
+use crate::journal::service::{AccountState, TransactionState};
+use crate::journal::transaction::{format_voucher_number, parse_voucher_number};
+use crate::monkesto_error::MonkestoResult;
+use crate::name::Name;
+use axum::extract::Query;
+use axum::extract::State;
+use maud::Markup;
+use maud::html;
+use serde::Deserialize;
+use std::collections::{HashMap, HashSet};
+
+#[derive(Deserialize)]
+pub struct SearchQuery {
+    q: Option<String>,
+}
+
+struct SearchResults {
+    accounts: Vec<AccountState>,
+    transactions: Vec<TransactionState>,
+    people: Vec<UserState>,
+    /// every account in the journal, since a matched transaction can touch accounts that didn't match
+    account_names: HashMap<AccountId, Name>,
+}
+
+async fn search(
+    state: &StateType,
+    journal_id: JournalId,
+    authority: &Authority,
+    search: &str,
+) -> MonkestoResult<SearchResults> {
+    let accounts = state
+        .journal_service
+        .search_accounts(journal_id, authority, search)
+        .await?;
+
+    let account_ids: HashSet<AccountId> = accounts.iter().map(|acc| acc.id).collect();
+
+    let voucher_number = parse_voucher_number(search);
+
+    // apart from their voucher number, transactions don't carry any text of their own,
+    // so they match through their accounts
+    let transactions = state
+        .journal_service
+        .list_journal_transactions(journal_id, authority)
+        .await?
+        .into_iter()
+        .map(|(tx, _, _)| tx)
+        .filter(|tx| {
+            voucher_number == Some(tx.number)
+                || tx
+                    .entries
+                    .iter()
+                    .any(|entry| account_ids.contains(&entry.account_id))
+        })
+        .collect();
+
+    let account_names = state
+        .journal_service
+        .list_journal_accounts(journal_id, authority)
+        .await?
+        .into_iter()
+        .map(|(account, _, _)| (account.id, account.name))
+        .collect();
+
+    // people live in the authn schema, so they're matched with a substring search instead of the index
+    let member_ids = state
+        .journal_service
+        .list_journal_members(journal_id, authority)
+        .await?;
+
+    let lowercase_search = search.to_lowercase();
+
+    let people = state
+        .authn_service
+        .fetch_users(member_ids.as_slice())
+        .await?
+        .into_iter()
+        .filter(|user| {
+            user.email
+                .to_string()
+                .to_lowercase()
+                .contains(&lowercase_search)
+        })
+        .collect();
+
+    Ok(SearchResults {
+        accounts,
+        transactions,
+        people,
+        account_names,
+    })
+}
+
+pub async fn search_page(
+    State(state): State<StateType>,
+    access: JournalAccess,
+    Query(query): Query<SearchQuery>,
+) -> Markup {
+    let id = access.journal.id.to_string();
+
+    let search_text = query.q.unwrap_or_default();
+    let search_text = search_text.trim();
+
+    let results_res: Option<MonkestoResult<SearchResults>> = if search_text.is_empty() {
+        None
+    } else {
+        Some(search(&state, access.journal.id, &access.authority(), search_text).await)
+    };
+
+    let content = html! {
+        div class="flex flex-col gap-6 mx-auto w-full max-w-4xl" {
+            form method="get" action=(format!("/journal/{}/search", id)) class="flex gap-3" {
+                input
+                type="search"
+                name="q"
+                value=(search_text)
+                placeholder="Search accounts, transactions, and people"
+                class="flex-1 rounded-md bg-white px-3 py-1.5 text-base text-gray-900 outline-1 -outline-offset-1 outline-gray-300 placeholder:text-gray-400 focus:outline-2 focus:-outline-offset-2 focus:outline-indigo-600 sm:text-sm/6 dark:bg-white/5 dark:text-white dark:outline-white/10 dark:placeholder:text-gray-500 dark:focus:outline-indigo-500";
+
+                button
+                type="submit"
+                class="rounded-md bg-indigo-600 px-3 py-1.5 text-sm/6 font-semibold text-white shadow-xs hover:bg-indigo-500 focus-visible:outline-2 focus-visible:outline-offset-2 focus-visible:outline-indigo-600 dark:bg-indigo-500 dark:shadow-none dark:hover:bg-indigo-400 dark:focus-visible:outline-indigo-500" {
+                    "Search"
+                }
+            }
+
+            @match &results_res {
+                None => {},
+                Some(Err(e)) => {
+                    p class="text-gray-500 dark:text-gray-400" {
+                        (format!("An error occurred while searching: {}", e))
+                    }
+                },
+                Some(Ok(results)) => {
+                    @if results.accounts.is_empty() && results.transactions.is_empty() && results.people.is_empty() {
+                        p class="text-gray-500 dark:text-gray-400" {
+                            "No results for \"" (search_text) "\""
+                        }
+                    }
+
+                    @if !results.accounts.is_empty() {
+                        h3 class="text-lg font-semibold text-gray-900 dark:text-white" { "Accounts" }
+                        @for acc in results.accounts.iter() {
+                            a
+                            href=(format!("/journal/{}/account", id))
+                            class="block p-4 bg-white dark:bg-gray-800 border border-gray-200 dark:border-gray-700 rounded-xl hover:bg-gray-50 dark:hover:bg-gray-700 transition-colors" {
+                                @let balance = acc.balance.abs();
+                                div class="flex justify-between items-center" {
+                                    span class="text-base font-medium text-gray-900 dark:text-white" { (acc.name) }
+                                    span class="text-base text-gray-700 dark:text-gray-300" {
+                                        (format!("${}.{:02} {}", balance / 100, balance % 100, if acc.balance < 0 { "Dr" } else { "Cr" }))
+                                    }
+                                }
+                            }
+                        }
+                    }
+
+                    @if !results.transactions.is_empty() {
+                        h3 class="text-lg font-semibold text-gray-900 dark:text-white" { "Transactions" }
+                        @for tx in results.transactions.iter() {
+                            a
+                            href=(format!("/journal/{}/transaction/{}", id, tx.id))
+                            class="block p-4 bg-white dark:bg-gray-800 border border-gray-200 dark:border-gray-700 rounded-xl hover:bg-gray-50 dark:hover:bg-gray-700 transition-colors" {
+                                div class="flex justify-between items-center" {
+                                    span class="text-sm font-medium text-gray-500 dark:text-gray-400" {
+                                        (format_voucher_number(tx.number)) " · " (tx.effective_date.format("%Y-%m-%d"))
+                                    }
+                                    span class="text-base text-gray-700 dark:text-gray-300" {
+                                        @for entry in tx.entries.iter() {
+                                            @let name = results.account_names.get(&entry.account_id).map(|name| name.as_ref()).unwrap_or("Deleted account");
+                                            (format!("{} ${}.{:02} {} ", name, entry.amount / 100, entry.amount % 100, entry.entry_type))
+                                        }
+                                    }
+                                }
+                            }
+                        }
+                    }
+
+                    @if !results.people.is_empty() {
+                        h3 class="text-lg font-semibold text-gray-900 dark:text-white" { "People" }
+                        @for person in results.people.iter() {
+                            a
+                            href=(format!("/journal/{}/person/{}", id, person.id))
+                            class="block p-4 bg-white dark:bg-gray-800 border border-gray-200 dark:border-gray-700 rounded-xl hover:bg-gray-50 dark:hover:bg-gray-700 transition-colors" {
+                                span class="text-base font-medium text-gray-900 dark:text-white" { (person.email) }
+                            }
+                        }
+                    }
+                }
+            }
+        }
+    };
+
+    journal_layout(&access.journal, content)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::test_app::{TestApp, assert_accepted};
+
+    #[tokio::test]
+    async fn matched_transactions_name_all_their_accounts() {
+        let Some(app) = TestApp::spawn().await else {
+            return;
+        };
+
+        let user = app.signup("searcher").await;
+        let journal_id = user.create_journal(&app, "Searched").await;
+        assert_accepted(&user.create_account(journal_id, "Cash").await);
+        assert_accepted(&user.create_account(journal_id, "Food").await);
+        let cash = app.account_id(journal_id, "Cash").await;
+        let food = app.account_id(journal_id, "Food").await;
+        assert_accepted(&user.post_transaction(journal_id, cash, food, "5.00").await);
+
+        // only the transaction matches its voucher number
+        let page = user
+            .client
+            .get(&format!("/journal/{journal_id}/search?q=JV-0001"))
+            .await
+            .text();
+        assert!(!page.contains("No results"), "{page}");
+        assert!(page.contains("Food $5.00 Dr"), "{page}");
+        assert!(page.contains("Cash $5.00 Cr"), "{page}");
+
+        // the transaction matches through Food, but still names the account that didn't match
+        let page = user
+            .client
+            .get(&format!("/journal/{journal_id}/search?q=food"))
+            .await
+            .text();
+        assert!(page.contains("Cash $5.00 Cr"), "{page}");
+    }
+}
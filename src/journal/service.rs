@@ -1,52 +1,258 @@
 use crate::authn::AuthConnectError;
 use crate::authn::user::UserId;
 use crate::authority::{Actor, Authority};
+use crate::email::Email;
 use crate::event_id::GetEventId;
 use crate::journal::JournalId;
 use crate::journal::JournalResult;
 use crate::journal::PermissionDecodeError;
 use crate::journal::Permissions;
-use crate::journal::account::{AccountId, CreateAccount};
+use crate::journal::access::check_access;
+use crate::journal::account::{
+    AccountId, AccountPlacement, ArrangeAccounts, CreateAccount, RenameAccount,
+};
+use crate::journal::appearance::{JournalAppearance, JournalColor, JournalIcon};
+use crate::journal::bankfeed::provider::{FeedCredentials, FeedTransaction};
+use crate::journal::bankfeed::{BankFeedId, LinkBankFeed, UnlinkBankFeed, feed_credentials};
+use crate::journal::cache::{self, JournalCache};
+use crate::journal::comment::{AddComment, CommentId};
+use crate::journal::consistency;
 use crate::journal::domain::JournalDomainEvent;
-use crate::journal::member::{AddJournalMember, RemoveJournalMember, UpdateJournalMember};
-use crate::journal::store::JournalEventStore;
+use crate::journal::fiscal::{self, FiscalYearStart};
+use crate::journal::member::{
+    AddJournalMember, LeaveJournal, RemoveJournalMember, UpdateJournalMember,
+};
+use crate::journal::payload::JournalPayloads;
+use crate::journal::quota::{QuotaUsage, Quotas};
+use crate::journal::rebuild::{
+    AccountSnapshot, Inconsistency, JournalSnapshot, ProjectionSnapshot, RebuildError,
+    RebuildReport,
+};
+use crate::journal::retention::RetentionConfig;
+use crate::journal::rule::{AddRule, RemoveRule, RuleCondition, RuleId};
+use crate::journal::store::{JournalEventStore, PgJournalEventStore};
+use crate::journal::template::{DeleteTemplate, SaveTemplate, TemplateId};
+use crate::journal::transaction::approval::{
+    ApproveTransaction, RejectTransaction, SubmitTransaction,
+};
 use crate::journal::transaction::{
-    BalanceUpdate, CreateTransaction, EntryType, TransactionEntries, TransactionId,
+    BalanceUpdate, CreateTransaction, CreateTransactionBatch, EntryType, NewTransaction,
+    ReverseTransaction, TransactionEntries, TransactionId, WithdrawReversal,
+};
+use crate::journal::transfer::{CreateTransfer, TransferLink, TransferSide};
+use crate::journal::webhook::{self, AddWebhook, RemoveWebhook, WebhookId, signing_secret};
+use crate::journal::{
+    ArchiveJournal, CreateJournal, DeleteJournal, JournalError, PinJournal, UnarchiveJournal,
+    UpdateJournalSettings,
 };
-use crate::journal::{CreateJournal, JournalError};
 use crate::name::Name;
-use crate::time_provider::Timestamp;
+use crate::secret::SecretStore;
+use crate::shutdown;
+use crate::time_provider::{Clock, Timestamp};
 use async_trait::async_trait;
-use disintegrate::serde::messagepack::MessagePack;
-use disintegrate::{DecisionError, EventListener, PersistedEvent, StreamQuery, query};
+use chrono::NaiveDate;
+use disintegrate::{
+    DecisionError, Event, EventListener, EventStore, PersistedEvent, StreamItem, StreamQuery, query,
+};
 use disintegrate_postgres::{
     PgDecisionMaker, PgEventId, PgSnapshotter, WithPgSnapshot, decision_maker,
 };
-use sqlx::{FromRow, PgPool};
+use futures::stream::{self, Stream, StreamExt, TryStreamExt};
+use sqlx::postgres::PgListener;
+use sqlx::{FromRow, PgConnection, PgPool};
+use std::collections::{HashMap, HashSet};
+use std::str::FromStr;
+use std::time::Duration;
 use tokio::sync::watch;
 
-type PgJournalDecisionMaker =
-    PgDecisionMaker<JournalDomainEvent, MessagePack<JournalDomainEvent>, WithPgSnapshot>;
+const LISTENER_ID: &str = "journal store";
+
+/// the channel the projection names each journal it changes on, for the other instances' caches
+const JOURNAL_CHANGED_CHANNEL: &str = "journal_changed";
+
+type PgJournalDecisionMaker = PgDecisionMaker<JournalDomainEvent, JournalPayloads, WithPgSnapshot>;
 
+/// how many events `stream_journal_events` reads per query
+const EVENT_PAGE_SIZE: i64 = 500;
+
+/// an event from the journal's history and its position in the event log
+pub struct JournalEventEnvelope {
+    pub event_id: PgEventId,
+    pub event: JournalDomainEvent,
+}
+
+#[derive(Clone)]
 pub struct JournalState {
     pub id: JournalId,
     pub owner_id: UserId,
     pub name: Name,
+    pub fiscal_year_start: FiscalYearStart,
+    pub archived: bool,
+    /// where money comes from when an import or quick entry doesn't say
+    pub default_cash_account: Option<AccountId>,
+    /// where money goes when an import or quick entry doesn't say
+    pub default_expense_account: Option<AccountId>,
+    pub appearance: JournalAppearance,
 }
 
 pub struct AccountState {
     pub id: AccountId,
-    #[expect(unused)]
+    #[cfg_attr(not(feature = "client"), expect(unused))]
     pub journal_id: JournalId,
     pub name: Name,
     pub balance: i64,
+    /// the heading the owner listed the account under, if any
+    pub group: Option<Name>,
 }
 
 pub struct TransactionState {
     pub id: TransactionId,
-    #[expect(unused)]
     pub journal_id: JournalId,
+    pub number: u64,
+    pub entries: Vec<BalanceUpdate>,
+    pub effective_date: NaiveDate,
+    pub reverses: Option<TransactionId>,
+    pub transfer: Option<TransferLink>,
+    pub payee: Option<Name>,
+}
+
+/// an import rule, in the order the rules are checked
+pub struct RuleState {
+    pub id: RuleId,
+    pub condition: RuleCondition,
+    pub account_id: AccountId,
+}
+
+/// a saved set of entries, named after what it's for
+pub struct TemplateState {
+    pub id: TemplateId,
+    pub name: Name,
+    pub entries: Vec<BalanceUpdate>,
+}
+
+pub struct BankFeedState {
+    pub id: BankFeedId,
+    pub provider: String,
+    pub external_account: String,
+    pub account_id: AccountId,
+    pub last_synced_at: Option<Timestamp>,
+    /// lines that have been pulled but not yet sent for review
+    pub pending: i64,
+}
+
+/// a transaction pulled from a bank feed or read from a statement, waiting to be reviewed
+#[derive(Debug, PartialEq)]
+pub struct BankFeedLine {
+    pub external_id: String,
+    pub booked: NaiveDate,
+    /// in cents, where money into the account is positive
+    pub amount: i64,
+    pub description: String,
+}
+
+/// what the sync worker needs to pull a feed's transactions
+pub struct BankFeedSync {
+    pub id: BankFeedId,
+    pub journal_id: JournalId,
+    pub provider: String,
+    pub external_account: String,
+    pub last_booked: Option<NaiveDate>,
+}
+
+pub struct WebhookState {
+    pub id: WebhookId,
+    pub url: String,
+    pub secret: String,
+}
+
+pub struct PendingWebhookDelivery {
+    pub id: i64,
+    pub webhook_id: WebhookId,
+    pub event_id: PgEventId,
+    pub body: Vec<u8>,
+    pub attempts: i32,
+    pub url: String,
+}
+
+pub struct PendingEmail {
+    pub id: i64,
+    pub recipient: Email,
+    pub subject: String,
+    pub body: String,
+    pub attempts: i32,
+}
+
+pub struct NotificationState {
+    pub id: i64,
+    pub journal_id: JournalId,
+    pub message: String,
+    pub created_at: Timestamp,
+    pub read: bool,
+}
+
+pub struct TransactionTotals {
+    pub debits: u64,
+    pub credits: u64,
+}
+
+/// activity figures for the journal page, aggregated in the database
+pub struct JournalStats {
+    /// transactions per month by effective date, for the months that have any
+    pub monthly_transactions: Vec<(NaiveDate, u64)>,
+    /// accounts with the largest balances either way, largest first
+    pub largest_accounts: Vec<(AccountId, Name, i64)>,
+    /// the users who created the most transactions, most first
+    pub top_authors: Vec<(UserId, u64)>,
+}
+
+/// one journal's row on the dashboard
+pub struct JournalOverview {
+    pub id: JournalId,
+    pub name: Name,
+    pub appearance: JournalAppearance,
+    /// accounts with the largest balances either way, largest first
+    pub balances: Vec<(AccountId, Name, i64)>,
+}
+
+/// a transaction on the dashboard, with the journal it was recorded in
+pub struct RecentTransaction {
+    pub id: TransactionId,
+    pub journal_id: JournalId,
+    pub journal_name: Name,
+    pub number: u64,
+    pub entries: Vec<BalanceUpdate>,
+    pub effective_date: NaiveDate,
+}
+
+/// a transaction waiting for approval, which hasn't been numbered yet
+pub struct PendingTransactionState {
+    pub id: TransactionId,
     pub entries: Vec<BalanceUpdate>,
+    pub effective_date: NaiveDate,
+    pub payee: Option<Name>,
+}
+
+/// a comment on a transaction, or a reply to one
+pub struct CommentState {
+    pub id: CommentId,
+    pub parent_id: Option<CommentId>,
+    pub body: String,
+    pub authority: Authority,
+    pub timestamp: Timestamp,
+}
+
+/// a journal the user was added to, whose notification is still unread
+pub struct PendingInvite {
+    pub journal_id: JournalId,
+    pub journal_name: Name,
+    pub created_at: Timestamp,
+}
+
+/// everything the dashboard shows, read across all of a user's journals at once
+pub struct Dashboard {
+    pub journals: Vec<JournalOverview>,
+    pub recent_transactions: Vec<RecentTransaction>,
+    pub invites: Vec<PendingInvite>,
 }
 
 #[derive(FromRow)]
@@ -54,6 +260,12 @@ struct JournalStateWithPayload {
     id: JournalId,
     owner_id: UserId,
     name: Name,
+    fiscal_year_start: FiscalYearStart,
+    archived: bool,
+    default_cash_account: Option<AccountId>,
+    default_expense_account: Option<AccountId>,
+    color: JournalColor,
+    icon: Option<JournalIcon>,
     payload: Vec<u8>,
 }
 
@@ -63,13 +275,17 @@ struct AccountStateWithPayload {
     journal_id: JournalId,
     name: Name,
     balance: i64,
+    group: Option<Name>,
     payload: Vec<u8>,
 }
 #[derive(FromRow)]
 struct TransactionStateWithPayload {
     id: TransactionId,
     journal_id: JournalId,
+    number: i64,
     entries: TransactionEntries,
+    effective_date: NaiveDate,
+    reverses: Option<TransactionId>,
     payload: Vec<u8>,
 }
 
@@ -77,21 +293,40 @@ struct TransactionStateWithPayload {
 pub struct JournalService {
     query: StreamQuery<PgEventId, JournalDomainEvent>,
     projection_pool: PgPool,
+    /// the listings and reports read from here, a replica when one is configured. it can lag
+    /// behind the projection, so access checks, the cache and commands stay on the primary
+    read_pool: PgPool,
     decision_maker: PgJournalDecisionMaker,
+    payloads: JournalPayloads,
     current_event: watch::Sender<PgEventId>,
+    quotas: Quotas,
+    retention: RetentionConfig,
+    secrets: SecretStore,
+    cache: JournalCache,
+    clock: Clock,
 }
 
 impl JournalService {
     pub async fn try_new(
         pool: PgPool,
         event_store: JournalEventStore,
+        quotas: Quotas,
+        retention: RetentionConfig,
+        secrets: SecretStore,
+        clock: Clock,
     ) -> Result<Self, AuthConnectError> {
         sqlx::query!(
             r#"
             CREATE TABLE IF NOT EXISTS journals (
                 id TEXT PRIMARY KEY,
                 owner_id TEXT NOT NULL,
-                name TEXT NOT NULL
+                name TEXT NOT NULL,
+                fiscal_year_start SMALLINT NOT NULL DEFAULT 1,
+                archived BOOLEAN NOT NULL DEFAULT FALSE,
+                default_cash_account TEXT,
+                default_expense_account TEXT,
+                color TEXT NOT NULL DEFAULT 'gray',
+                icon TEXT
             )
         "#
         )
@@ -116,439 +351,3796 @@ impl JournalService {
                 id TEXT PRIMARY KEY,
                 journal_id TEXT NOT NULL,
                 name TEXT NOT NULL,
-                balance BIGINT NOT NULL
+                balance BIGINT NOT NULL,
+                name_search TSVECTOR GENERATED ALWAYS AS (to_tsvector('simple', name)) STORED
             )
         "#
         )
         .execute(&pool)
         .await?;
 
+        // journals projected before they had these settings get their defaults
+        sqlx::query!(
+            r#"
+            ALTER TABLE journals ADD COLUMN IF NOT EXISTS fiscal_year_start SMALLINT NOT NULL DEFAULT 1, ADD COLUMN IF NOT EXISTS archived BOOLEAN NOT NULL DEFAULT FALSE, ADD COLUMN IF NOT EXISTS default_cash_account TEXT, ADD COLUMN IF NOT EXISTS default_expense_account TEXT, ADD COLUMN IF NOT EXISTS color TEXT NOT NULL DEFAULT 'gray', ADD COLUMN IF NOT EXISTS icon TEXT
+        "#
+        )
+        .execute(&pool)
+        .await?;
+
+        // accounts projected before they could be searched or arranged
+        sqlx::query!(
+            r#"
+            ALTER TABLE accounts ADD COLUMN IF NOT EXISTS name_search TSVECTOR GENERATED ALWAYS AS (to_tsvector('simple', name)) STORED, ADD COLUMN IF NOT EXISTS position INTEGER, ADD COLUMN IF NOT EXISTS group_name TEXT
+        "#
+        )
+        .execute(&pool)
+        .await?;
+
+        sqlx::query!(
+            r#"
+            CREATE INDEX IF NOT EXISTS accounts_name_search_idx ON accounts USING GIN (name_search)
+        "#
+        )
+        .execute(&pool)
+        .await?;
+
         sqlx::query!(
             r#"
             CREATE TABLE IF NOT EXISTS transactions (
                 id TEXT PRIMARY KEY,
                 journal_id TEXT NOT NULL,
-                entries BYTEA NOT NULL
+                number BIGINT NOT NULL,
+                entries BYTEA NOT NULL,
+                effective_date DATE NOT NULL,
+                reverses TEXT,
+                author_id TEXT
             )
         "#
         )
         .execute(&pool)
         .await?;
 
-        let snapshotter = PgSnapshotter::try_new(pool.clone(), 10)
-            .await
-            .expect("failed to create a snapshotter for the journal service");
+        // transactions projected before they had these are filled in from their events
+        sqlx::query!(
+            r#"
+            ALTER TABLE transactions ADD COLUMN IF NOT EXISTS effective_date DATE, ADD COLUMN IF NOT EXISTS reverses TEXT, ADD COLUMN IF NOT EXISTS author_id TEXT
+        "#
+        )
+        .execute(&pool)
+        .await?;
 
-        let decision_maker =
-            decision_maker(event_store.event_store, WithPgSnapshot::new(snapshotter));
+        backfill_transaction_dates(&pool, &event_store.payloads).await?;
 
-        let (sender, receiver) = watch::channel(0);
+        sqlx::query!(
+            r#"
+            ALTER TABLE transactions ADD COLUMN IF NOT EXISTS number BIGINT
+        "#
+        )
+        .execute(&pool)
+        .await?;
 
-        Box::leak(Box::new(receiver));
+        backfill_transaction_numbers(&pool).await?;
 
-        Ok(Self {
-            query: query!(JournalDomainEvent),
-            projection_pool: pool,
-            decision_maker,
-            current_event: sender,
-        })
-    }
+        // transactions projected before they could have a payee have none
+        sqlx::query!(
+            r#"
+            ALTER TABLE transactions ADD COLUMN IF NOT EXISTS payee TEXT
+        "#
+        )
+        .execute(&pool)
+        .await?;
 
-    pub async fn create_journal(
-        &self,
-        journal_id: JournalId,
-        owner: UserId,
-        name: Name,
-        authority: Authority,
-        timestamp: Timestamp,
-    ) -> Result<PgEventId, DecisionError<JournalError>> {
-        Ok(self
-            .decision_maker
-            .make(CreateJournal::new(
-                journal_id, owner, name, authority, timestamp,
-            ))
-            .await?
-            .event_id())
-    }
+        // the debits and credits of each account in each period, so reports over whole
+        // periods don't have to read every transaction
+        sqlx::query!(
+            r#"
+            CREATE TABLE IF NOT EXISTS account_period_totals (
+                account_id TEXT NOT NULL,
+                journal_id TEXT NOT NULL,
+                period DATE NOT NULL,
+                debit_total BIGINT NOT NULL,
+                credit_total BIGINT NOT NULL,
+                PRIMARY KEY (account_id, period)
+            )
+        "#
+        )
+        .execute(&pool)
+        .await?;
 
-    pub async fn add_member(
-        &self,
-        journal_id: JournalId,
-        member_id: UserId,
-        permissions: Permissions,
-        authority: Authority,
-        timestamp: Timestamp,
-    ) -> Result<PgEventId, DecisionError<JournalError>> {
-        Ok(self
-            .decision_maker
-            .make(AddJournalMember::new(
-                journal_id,
-                member_id,
-                permissions,
-                authority,
-                timestamp,
-            ))
-            .await?
-            .event_id())
-    }
+        sqlx::query!(
+            r#"
+            CREATE INDEX IF NOT EXISTS account_period_totals_journal_idx ON account_period_totals (journal_id, period)
+        "#
+        )
+        .execute(&pool)
+        .await?;
 
-    pub async fn update_member(
-        &self,
-        journal_id: JournalId,
-        member_id: UserId,
-        permissions: Permissions,
-        authority: Authority,
-        timestamp: Timestamp,
-    ) -> Result<PgEventId, DecisionError<JournalError>> {
-        Ok(self
-            .decision_maker
-            .make(UpdateJournalMember::new(
-                journal_id,
-                member_id,
-                permissions,
-                authority,
-                timestamp,
-            ))
-            .await?
-            .event_id())
-    }
+        sqlx::query!(
+            r#"
+            CREATE TABLE IF NOT EXISTS webhooks (
+                id TEXT PRIMARY KEY,
+                journal_id TEXT NOT NULL,
+                url TEXT NOT NULL
+            )
+        "#
+        )
+        .execute(&pool)
+        .await?;
 
-    pub async fn remove_member(
-        &self,
-        journal_id: JournalId,
-        member_id: UserId,
-        authority: Authority,
-        timestamp: Timestamp,
-    ) -> Result<PgEventId, DecisionError<JournalError>> {
-        Ok(self
-            .decision_maker
-            .make(RemoveJournalMember::new(
-                journal_id, member_id, authority, timestamp,
-            ))
-            .await?
-            .event_id())
-    }
+        sqlx::query!(
+            r#"
+            CREATE TABLE IF NOT EXISTS import_rules (
+                id TEXT PRIMARY KEY,
+                journal_id TEXT NOT NULL,
+                pattern TEXT NOT NULL,
+                min_amount BIGINT,
+                max_amount BIGINT,
+                account_id TEXT NOT NULL,
+                position BIGINT NOT NULL
+            )
+        "#
+        )
+        .execute(&pool)
+        .await?;
 
-    pub async fn create_account(
-        &self,
-        account_id: AccountId,
-        journal_id: JournalId,
-        name: Name,
-        authority: Authority,
-        timestamp: Timestamp,
-    ) -> Result<PgEventId, DecisionError<JournalError>> {
-        Ok(self
-            .decision_maker
-            .make(CreateAccount::new(
-                account_id, journal_id, name, authority, timestamp,
-            ))
-            .await?
-            .event_id())
-    }
+        sqlx::query!(
+            r#"
+            CREATE TABLE IF NOT EXISTS pending_transactions (
+                id TEXT PRIMARY KEY,
+                journal_id TEXT NOT NULL,
+                entries BYTEA NOT NULL,
+                effective_date DATE NOT NULL
+            )
+        "#
+        )
+        .execute(&pool)
+        .await?;
 
-    pub async fn create_transaction(
-        &self,
-        transaction_id: TransactionId,
-        journal_id: JournalId,
-        entries: Vec<BalanceUpdate>,
-        authority: Authority,
-        timestamp: Timestamp,
-    ) -> Result<PgEventId, DecisionError<JournalError>> {
-        Ok(self
-            .decision_maker
-            .make(CreateTransaction::new(
-                transaction_id,
-                journal_id,
-                entries,
-                authority,
-                timestamp,
-            ))
-            .await?
-            .event_id())
-    }
+        sqlx::query!(
+            r#"
+            CREATE TABLE IF NOT EXISTS transaction_comments (
+                id TEXT PRIMARY KEY,
+                journal_id TEXT NOT NULL,
+                transaction_id TEXT NOT NULL,
+                parent_id TEXT,
+                body TEXT NOT NULL
+            )
+        "#
+        )
+        .execute(&pool)
+        .await?;
 
-    pub async fn get_effective_permissions(
-        &self,
-        journal_id: JournalId,
-        authority: &Authority,
-    ) -> JournalResult<Permissions> {
-        match authority.actor() {
-            Actor::System => Ok(Permissions::OWNER),
-            Actor::Anonymous => Ok(Permissions::empty()),
-            Actor::User(user_id) => {
-                let permission_bits = sqlx::query_scalar!(
-                    r#"
-                    SELECT
-                        CASE
-                            WHEN j.owner_id = $1 THEN $2::INTEGER
-                            ELSE COALESCE(
-                                 (SELECT jm.permissions
-                                 FROM journal_members jm
-                                 WHERE jm.journal_id = j.id AND jm.user_id = $1),
-                                 0
-                            )
-                        END as "i32!"
-                    FROM journals j
-                    WHERE j.id = $3
-                "#,
-                    *user_id as UserId,
-                    Permissions::all().bits(),
-                    journal_id as JournalId
-                )
-                .fetch_optional(&self.projection_pool)
-                .await?;
+        sqlx::query!(
+            r#"
+            CREATE TABLE IF NOT EXISTS transaction_templates (
+                id TEXT PRIMARY KEY,
+                journal_id TEXT NOT NULL,
+                name TEXT NOT NULL,
+                entries BYTEA NOT NULL,
+                position BIGINT NOT NULL
+            )
+        "#
+        )
+        .execute(&pool)
+        .await?;
 
-                if let Some(bits) = permission_bits {
-                    Ok(Permissions::from_bits(bits)
-                        .ok_or(JournalError::PermissionDecode(PermissionDecodeError(bits)))?)
-                } else {
-                    Ok(Permissions::empty())
-                }
-            }
-        }
-    }
+        sqlx::query!(
+            r#"
+            CREATE TABLE IF NOT EXISTS bank_feeds (
+                id TEXT PRIMARY KEY,
+                journal_id TEXT NOT NULL,
+                provider TEXT NOT NULL,
+                external_account TEXT NOT NULL,
+                account_id TEXT NOT NULL
+            )
+        "#
+        )
+        .execute(&pool)
+        .await?;
 
-    /// returns the current state, creation authority, and creation timestamp of every accessible journal
-    pub async fn list_accessible_journals(
-        &self,
-        user: UserId,
-    ) -> JournalResult<Vec<(JournalState, Authority, Timestamp)>> {
-        // NOTE(gabriel): a user must not be both a member and the owner, or this query will return duplicate journals
+        // a row is written when a feed is linked, so the sync worker only sees feeds whose
+        // credentials are in the secret store
+        sqlx::query!(
+            r#"
+            CREATE TABLE IF NOT EXISTS bank_feed_syncs (
+                bank_feed_id TEXT PRIMARY KEY,
+                last_synced_at TIMESTAMPTZ
+            )
+        "#
+        )
+        .execute(&pool)
+        .await?;
 
-        let journals = sqlx::query_as!(
-            JournalStateWithPayload,
+        // the primary key keeps a transaction from being pulled twice
+        sqlx::query!(
             r#"
-            SELECT j.id as "id: JournalId", j.owner_id as "owner_id: UserId", j.name as "name: Name", e.payload as "payload!"
-            FROM journals j
-            INNER JOIN event e
-                ON e.journal_id = j.id AND e.event_type = 'JournalCreated'
-            LEFT JOIN journal_members jm ON jm.journal_id = j.id AND (jm.permissions & $1) = $1
-            WHERE j.owner_id = $2 OR jm.user_id = $2
-            "#,
-            Permissions::READ.bits(),
-            user as UserId)
-            .fetch_all(&self.projection_pool)
-            .await?;
+            CREATE TABLE IF NOT EXISTS bank_feed_lines (
+                bank_feed_id TEXT NOT NULL,
+                external_id TEXT NOT NULL,
+                booked DATE NOT NULL,
+                amount BIGINT NOT NULL,
+                description TEXT NOT NULL,
+                reviewed_at TIMESTAMPTZ,
+                PRIMARY KEY (bank_feed_id, external_id)
+            )
+        "#
+        )
+        .execute(&pool)
+        .await?;
 
-        // TODO(gabriel) would .map() be more efficient here?
-        let mut journals_with_meta = Vec::with_capacity(journals.len());
+        // deliveries are written here before they are attempted so that they survive restarts,
+        // and the unique constraint keeps a replayed event from being enqueued twice
+        sqlx::query!(
+            r#"
+            CREATE TABLE IF NOT EXISTS webhook_outbox (
+                id BIGSERIAL PRIMARY KEY,
+                webhook_id TEXT NOT NULL,
+                event_id BIGINT NOT NULL,
+                body BYTEA NOT NULL,
+                attempts INTEGER NOT NULL DEFAULT 0,
+                next_attempt_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+                delivered_at TIMESTAMPTZ,
+                UNIQUE (webhook_id, event_id)
+            )
+        "#
+        )
+        .execute(&pool)
+        .await?;
 
-        for journal in journals {
-            let payload: JournalDomainEvent = rmp_serde::from_slice(journal.payload.as_slice())?;
+        // the unique constraint keeps a replayed event from notifying a user twice
+        sqlx::query!(
+            r#"
+            CREATE TABLE IF NOT EXISTS notifications (
+                id BIGSERIAL PRIMARY KEY,
+                user_id TEXT NOT NULL,
+                journal_id TEXT NOT NULL,
+                event_id BIGINT NOT NULL,
+                message TEXT NOT NULL,
+                created_at TIMESTAMPTZ NOT NULL,
+                read_at TIMESTAMPTZ,
+                UNIQUE (user_id, event_id)
+            )
+        "#
+        )
+        .execute(&pool)
+        .await?;
 
-            match payload {
-                JournalDomainEvent::JournalCreated {
-                    authority,
-                    timestamp,
-                    ..
-                } => {
-                    journals_with_meta.push((
-                        JournalState {
-                            id: journal.id,
-                            owner_id: journal.owner_id,
-                            name: journal.name,
-                        },
-                        authority,
-                        timestamp,
-                    ));
-                }
-                _ => unreachable!("JournalCreated events are filtered by the sql query"),
-            }
-        }
+        // the last event that changed each journal, which versions the journal's pages for caching,
+        // and when the journal's contents last changed, which orders journal lists
+        sqlx::query!(
+            r#"
+            CREATE TABLE IF NOT EXISTS journal_versions (
+                journal_id TEXT PRIMARY KEY,
+                version BIGINT NOT NULL,
+                last_activity TIMESTAMPTZ
+            )
+        "#
+        )
+        .execute(&pool)
+        .await?;
 
-        Ok(journals_with_meta)
-    }
+        // the journals each user keeps at the top of their journal list
+        sqlx::query!(
+            r#"
+            CREATE TABLE IF NOT EXISTS journal_pins (
+                journal_id TEXT NOT NULL,
+                user_id TEXT NOT NULL,
+                PRIMARY KEY (journal_id, user_id)
+            )
+        "#
+        )
+        .execute(&pool)
+        .await?;
 
-    pub async fn get_journal(
-        &self,
-        journal_id: JournalId,
-        authority: &Authority,
-    ) -> JournalResult<(JournalState, Authority, Timestamp)> {
-        if !self
-            .get_effective_permissions(journal_id, authority)
-            .await?
-            .contains(Permissions::READ)
-        {
-            return Err(JournalError::InvalidJournal(journal_id));
-        }
+        // when each deleted journal was deleted, so it can be purged once its retention has passed
+        sqlx::query!(
+            r#"
+            CREATE TABLE IF NOT EXISTS deleted_journals (
+                journal_id TEXT PRIMARY KEY,
+                deleted_at TIMESTAMPTZ NOT NULL
+            )
+        "#
+        )
+        .execute(&pool)
+        .await?;
 
-        let journal = sqlx::query_as!(
-            JournalStateWithPayload,
+        // where each user's last digest email left off, so the next one only covers newer events
+        sqlx::query!(
             r#"
-            SELECT j.id as "id: JournalId", j.owner_id as "owner_id: UserId", j.name as "name: Name", e.payload as "payload!"
-            FROM journals j
-            INNER JOIN event e
-                ON e.journal_id = $1 AND e.event_type = 'JournalCreated'
-            WHERE j.id = $1
+            CREATE TABLE IF NOT EXISTS digest_deliveries (
+                user_id TEXT PRIMARY KEY,
+                last_event_id BIGINT NOT NULL,
+                sent_at TIMESTAMPTZ NOT NULL
+            )
+        "#
+        )
+        .execute(&pool)
+        .await?;
+
+        // emails waiting to be sent. the key is unique so that queuing the same email twice, such
+        // as when an event is handled again, sends it once
+        sqlx::query!(
+            r#"
+            CREATE TABLE IF NOT EXISTS email_outbox (
+                id BIGSERIAL PRIMARY KEY,
+                message_key TEXT NOT NULL UNIQUE,
+                recipient TEXT NOT NULL,
+                subject TEXT NOT NULL,
+                body TEXT NOT NULL,
+                attempts INTEGER NOT NULL DEFAULT 0,
+                next_attempt_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+                sent_at TIMESTAMPTZ
+            )
+        "#
+        )
+        .execute(&pool)
+        .await?;
+
+        // resume from the listener's last position so that waiting on events
+        // that were processed before a restart doesn't block
+        let last_processed_event = sqlx::query_scalar!(
+            r#"
+            SELECT last_processed_event_id FROM event_listener WHERE id = $1
             "#,
-            journal_id as JournalId)
-            .fetch_optional(&self.projection_pool)
-            .await?;
+            LISTENER_ID
+        )
+        .fetch_optional(&pool)
+        .await?
+        .flatten()
+        .unwrap_or_default();
 
-        if let Some(journal) = journal {
-            let payload: JournalDomainEvent = rmp_serde::from_slice(journal.payload.as_slice())?;
+        let snapshotter = PgSnapshotter::try_new(pool.clone(), 10)
+            .await
+            .expect("failed to create a snapshotter for the journal service");
 
-            match payload {
-                JournalDomainEvent::JournalCreated {
-                    authority,
-                    timestamp,
-                    ..
-                } => Ok((
-                    JournalState {
-                        id: journal.id,
-                        owner_id: journal.owner_id,
-                        name: journal.name,
-                    },
-                    authority,
-                    timestamp,
-                )),
-                _ => unreachable!("JournalCreated events are filtered by the sql query"),
-            }
-        } else {
-            Err(JournalError::InvalidJournal(journal_id))
-        }
+        let payloads = event_store.payloads;
+        let decision_maker =
+            decision_maker(event_store.event_store, WithPgSnapshot::new(snapshotter));
+
+        let (sender, receiver) = watch::channel(last_processed_event);
+
+        Box::leak(Box::new(receiver));
+
+        Ok(Self {
+            query: query!(JournalDomainEvent),
+            read_pool: pool.clone(),
+            projection_pool: pool,
+            decision_maker,
+            payloads,
+            current_event: sender,
+            quotas,
+            retention,
+            secrets,
+            cache: JournalCache::new(cache::DEFAULT_CAPACITY),
+            clock,
+        })
     }
 
-    pub async fn list_journal_members(
+    /// sends the listings and reports to a read only replica of the primary
+    pub fn with_replica(mut self, replica_pool: PgPool) -> Self {
+        self.read_pool = replica_pool;
+        self
+    }
+
+    pub fn clock(&self) -> &Clock {
+        &self.clock
+    }
+
+    #[tracing::instrument(skip_all, fields(%journal_id, actor = %authority.actor()))]
+    pub async fn create_journal(
         &self,
         journal_id: JournalId,
-        authority: &Authority,
-    ) -> JournalResult<Vec<UserId>> {
-        if !self
-            .get_effective_permissions(journal_id, authority)
-            .await?
-            .contains(Permissions::READ)
-        {
-            return Err(JournalError::InvalidJournal(journal_id));
-        }
+        owner: UserId,
+        name: Name,
+        appearance: JournalAppearance,
+        authority: Authority,
+        timestamp: Timestamp,
+    ) -> Result<PgEventId, DecisionError<JournalError>> {
+        let owned = self
+            .owned_journal_count(owner)
+            .await
+            .map_err(DecisionError::Domain)?;
 
-        Ok(sqlx::query_scalar!(
-            r#"
-            SELECT user_id as "user_id: UserId" FROM journal_members WHERE journal_id = $1
-            "#,
-            journal_id as JournalId
-        )
-        .fetch_all(&self.projection_pool)
-        .await?)
+        self.quotas
+            .check_journals(owned)
+            .map_err(DecisionError::Domain)?;
+
+        Ok(self
+            .decision_maker
+            .make(CreateJournal::new(
+                journal_id, owner, name, appearance, authority, timestamp,
+            ))
+            .await?
+            .event_id())
     }
 
-    pub async fn list_journal_accounts(
+    #[expect(clippy::too_many_arguments)]
+    #[tracing::instrument(skip_all, fields(%journal_id, actor = %authority.actor()))]
+    pub async fn update_journal_settings(
         &self,
         journal_id: JournalId,
-        authority: &Authority,
-    ) -> JournalResult<Vec<(AccountState, Authority, Timestamp)>> {
-        if !self
+        fiscal_year_start: FiscalYearStart,
+        default_cash_account: Option<AccountId>,
+        default_expense_account: Option<AccountId>,
+        appearance: JournalAppearance,
+        authority: Authority,
+        timestamp: Timestamp,
+    ) -> Result<PgEventId, DecisionError<JournalError>> {
+        Ok(self
+            .decision_maker
+            .make(UpdateJournalSettings::new(
+                journal_id,
+                fiscal_year_start,
+                default_cash_account,
+                default_expense_account,
+                appearance,
+                authority,
+                timestamp,
+            ))
+            .await?
+            .event_id())
+    }
+
+    #[tracing::instrument(skip_all, fields(%journal_id, actor = %authority.actor()))]
+    pub async fn archive_journal(
+        &self,
+        journal_id: JournalId,
+        authority: Authority,
+        timestamp: Timestamp,
+    ) -> Result<PgEventId, DecisionError<JournalError>> {
+        Ok(self
+            .decision_maker
+            .make(ArchiveJournal::new(journal_id, authority, timestamp))
+            .await?
+            .event_id())
+    }
+
+    #[tracing::instrument(skip_all, fields(%journal_id, actor = %authority.actor()))]
+    pub async fn unarchive_journal(
+        &self,
+        journal_id: JournalId,
+        authority: Authority,
+        timestamp: Timestamp,
+    ) -> Result<PgEventId, DecisionError<JournalError>> {
+        Ok(self
+            .decision_maker
+            .make(UnarchiveJournal::new(journal_id, authority, timestamp))
+            .await?
+            .event_id())
+    }
+
+    #[tracing::instrument(skip_all, fields(%journal_id, actor = %authority.actor()))]
+    pub async fn pin_journal(
+        &self,
+        journal_id: JournalId,
+        pinned: bool,
+        authority: Authority,
+        timestamp: Timestamp,
+    ) -> Result<PgEventId, DecisionError<JournalError>> {
+        Ok(self
+            .decision_maker
+            .make(PinJournal::new(journal_id, pinned, authority, timestamp))
+            .await?
+            .event_id())
+    }
+
+    #[tracing::instrument(skip_all, fields(%journal_id, actor = %authority.actor()))]
+    pub async fn delete_journal(
+        &self,
+        journal_id: JournalId,
+        authority: Authority,
+        timestamp: Timestamp,
+    ) -> Result<PgEventId, DecisionError<JournalError>> {
+        Ok(self
+            .decision_maker
+            .make(DeleteJournal::new(journal_id, authority, timestamp))
+            .await?
+            .event_id())
+    }
+
+    /// how many days a deleted journal is kept before it's purged
+    pub fn retention_days(&self) -> u32 {
+        self.retention.days
+    }
+
+    /// permanently removes the events, snapshots, projections and secrets of every journal that
+    /// was deleted before the cutoff, and returns the journals that were purged
+    pub async fn purge_deleted_journals(
+        &self,
+        deleted_before: Timestamp,
+    ) -> JournalResult<Vec<JournalId>> {
+        let journal_ids = sqlx::query_scalar!(
+            r#"
+            SELECT journal_id as "journal_id: JournalId" FROM deleted_journals WHERE deleted_at < $1
+            "#,
+            deleted_before as Timestamp
+        )
+        .fetch_all(&self.projection_pool)
+        .await?;
+
+        for journal_id in journal_ids.iter() {
+            self.purge_journal(*journal_id).await?;
+        }
+
+        Ok(journal_ids)
+    }
+
+    async fn purge_journal(&self, journal_id: JournalId) -> JournalResult<()> {
+        let webhook_ids = sqlx::query_scalar!(
+            r#"
+            SELECT id as "id: WebhookId" FROM webhooks WHERE journal_id = $1
+            "#,
+            journal_id as JournalId
+        )
+        .fetch_all(&self.projection_pool)
+        .await?;
+
+        let bank_feed_ids = sqlx::query_scalar!(
+            r#"
+            SELECT id as "id: BankFeedId" FROM bank_feeds WHERE journal_id = $1
+            "#,
+            journal_id as JournalId
+        )
+        .fetch_all(&self.projection_pool)
+        .await?;
+
+        // removing a secret twice is harmless, so a purge that fails partway can run again
+        for webhook_id in webhook_ids {
+            self.secrets.remove(&signing_secret(webhook_id)).await?;
+        }
+        for bank_feed_id in bank_feed_ids {
+            self.secrets.remove(&feed_credentials(bank_feed_id)).await?;
+        }
+
+        let mut tx = self.projection_pool.begin().await?;
+
+        sqlx::query!(
+            r#"
+            DELETE FROM webhook_outbox
+            WHERE webhook_id IN (SELECT id FROM webhooks WHERE journal_id = $1)
+            "#,
+            journal_id as JournalId
+        )
+        .execute(&mut *tx)
+        .await?;
+
+        sqlx::query!(
+            r#"
+            DELETE FROM bank_feed_syncs
+            WHERE bank_feed_id IN (SELECT id FROM bank_feeds WHERE journal_id = $1)
+            "#,
+            journal_id as JournalId
+        )
+        .execute(&mut *tx)
+        .await?;
+
+        sqlx::query!(
+            r#"
+            DELETE FROM bank_feed_lines
+            WHERE bank_feed_id IN (SELECT id FROM bank_feeds WHERE journal_id = $1)
+            "#,
+            journal_id as JournalId
+        )
+        .execute(&mut *tx)
+        .await?;
+
+        sqlx::query!(
+            r#"
+            DELETE FROM webhooks WHERE journal_id = $1
+            "#,
+            journal_id as JournalId
+        )
+        .execute(&mut *tx)
+        .await?;
+
+        sqlx::query!(
+            r#"
+            DELETE FROM bank_feeds WHERE journal_id = $1
+            "#,
+            journal_id as JournalId
+        )
+        .execute(&mut *tx)
+        .await?;
+
+        sqlx::query!(
+            r#"
+            DELETE FROM import_rules WHERE journal_id = $1
+            "#,
+            journal_id as JournalId
+        )
+        .execute(&mut *tx)
+        .await?;
+
+        sqlx::query!(
+            r#"
+            DELETE FROM transaction_templates WHERE journal_id = $1
+            "#,
+            journal_id as JournalId
+        )
+        .execute(&mut *tx)
+        .await?;
+
+        sqlx::query!(
+            r#"
+            DELETE FROM pending_transactions WHERE journal_id = $1
+            "#,
+            journal_id as JournalId
+        )
+        .execute(&mut *tx)
+        .await?;
+
+        sqlx::query!(
+            r#"
+            DELETE FROM transaction_comments WHERE journal_id = $1
+            "#,
+            journal_id as JournalId
+        )
+        .execute(&mut *tx)
+        .await?;
+
+        sqlx::query!(
+            r#"
+            DELETE FROM account_period_totals WHERE journal_id = $1
+            "#,
+            journal_id as JournalId
+        )
+        .execute(&mut *tx)
+        .await?;
+
+        sqlx::query!(
+            r#"
+            DELETE FROM transactions WHERE journal_id = $1
+            "#,
+            journal_id as JournalId
+        )
+        .execute(&mut *tx)
+        .await?;
+
+        sqlx::query!(
+            r#"
+            DELETE FROM accounts WHERE journal_id = $1
+            "#,
+            journal_id as JournalId
+        )
+        .execute(&mut *tx)
+        .await?;
+
+        sqlx::query!(
+            r#"
+            DELETE FROM journal_members WHERE journal_id = $1
+            "#,
+            journal_id as JournalId
+        )
+        .execute(&mut *tx)
+        .await?;
+
+        sqlx::query!(
+            r#"
+            DELETE FROM notifications WHERE journal_id = $1
+            "#,
+            journal_id as JournalId
+        )
+        .execute(&mut *tx)
+        .await?;
+
+        sqlx::query!(
+            r#"
+            DELETE FROM journal_versions WHERE journal_id = $1
+            "#,
+            journal_id as JournalId
+        )
+        .execute(&mut *tx)
+        .await?;
+
+        sqlx::query!(
+            r#"
+            DELETE FROM journal_pins WHERE journal_id = $1
+            "#,
+            journal_id as JournalId
+        )
+        .execute(&mut *tx)
+        .await?;
+
+        // a snapshot's query names the ids it was built from, as in `account_id=<id>,`
+        sqlx::query!(
+            r#"
+            DELETE FROM snapshot s
+            WHERE EXISTS (
+                SELECT 1 FROM (
+                    SELECT $1::text as id
+                    UNION SELECT account_id FROM event WHERE journal_id = $1 AND account_id IS NOT NULL
+                    UNION SELECT transaction_id FROM event WHERE journal_id = $1 AND transaction_id IS NOT NULL
+                ) ids
+                WHERE strpos(s.query, '=' || ids.id || ',') > 0
+                    OR strpos(s.query, '=' || ids.id || ')') > 0
+            )
+            "#,
+            journal_id as JournalId
+        )
+        .execute(&mut *tx)
+        .await?;
+
+        // the events go last, since without them nothing could rebuild what's left. some are
+        // keyed only by their account or transaction, like a rename or a deletion
+        sqlx::query!(
+            r#"
+            DELETE FROM event
+            WHERE journal_id = $1
+                OR account_id IN (
+                    SELECT account_id FROM event WHERE journal_id = $1 AND account_id IS NOT NULL
+                )
+                OR transaction_id IN (
+                    SELECT transaction_id FROM event WHERE journal_id = $1 AND transaction_id IS NOT NULL
+                )
+            "#,
+            journal_id as JournalId
+        )
+        .execute(&mut *tx)
+        .await?;
+
+        sqlx::query!(
+            r#"
+            DELETE FROM deleted_journals WHERE journal_id = $1
+            "#,
+            journal_id as JournalId
+        )
+        .execute(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+
+        Ok(())
+    }
+
+    /// replaces the user with the tombstone actor in every event that mentions them, and removes
+    /// what was projected only for them. returns how many events were rewritten
+    pub async fn forget_user(&self, user_id: UserId) -> JournalResult<u64> {
+        let mut tx = self.projection_pool.begin().await?;
+        let id = user_id.to_string();
+
+        // ids are stored as strings, so only payloads containing the id can mention the user.
+        // encrypted payloads can't be searched, so then every event is read
+        let rows = sqlx::query!(
+            r#"
+            SELECT event_id as "event_id!", payload as "payload!"
+            FROM event
+            WHERE $2 OR position($1 IN payload) > 0
+            "#,
+            id.as_bytes(),
+            self.payloads.encrypted()
+        )
+        .fetch_all(&mut *tx)
+        .await?;
+
+        let mut rewritten = 0;
+
+        for row in rows {
+            let mut event: JournalDomainEvent = self.payloads.decode(&row.payload)?;
+
+            // members keep their place in the history under an id nothing points to anymore
+            if !event.forget_user_mut(user_id) {
+                continue;
+            }
+
+            sqlx::query!(
+                r#"
+                UPDATE event SET payload = $2 WHERE event_id = $1
+                "#,
+                row.event_id,
+                self.payloads.encode(&event)
+            )
+            .execute(&mut *tx)
+            .await?;
+
+            rewritten += 1;
+        }
+
+        // the events are found by the user's id in their id columns too, so those are rewritten
+        // the same way, and the snapshots decided from the events as they were are dropped
+        sqlx::query!(
+            r#"
+            UPDATE event SET user_id = $2 WHERE user_id = $1
+            "#,
+            user_id as UserId,
+            UserId::nil() as UserId
+        )
+        .execute(&mut *tx)
+        .await?;
+
+        sqlx::query!(
+            r#"
+            DELETE FROM snapshot WHERE position($1 IN query) > 0 OR position($1 IN payload) > 0
+            "#,
+            id
+        )
+        .execute(&mut *tx)
+        .await?;
+
+        sqlx::query!(
+            r#"
+            UPDATE transactions SET author_id = NULL WHERE author_id = $1
+            "#,
+            user_id as UserId
+        )
+        .execute(&mut *tx)
+        .await?;
+
+        sqlx::query!(
+            r#"
+            DELETE FROM notifications WHERE user_id = $1
+            "#,
+            user_id as UserId
+        )
+        .execute(&mut *tx)
+        .await?;
+
+        sqlx::query!(
+            r#"
+            DELETE FROM digest_deliveries WHERE user_id = $1
+            "#,
+            user_id as UserId
+        )
+        .execute(&mut *tx)
+        .await?;
+
+        sqlx::query!(
+            r#"
+            DELETE FROM journal_pins WHERE user_id = $1
+            "#,
+            user_id as UserId
+        )
+        .execute(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+
+        Ok(rewritten)
+    }
+
+    #[tracing::instrument(skip_all, fields(%journal_id, actor = %authority.actor()))]
+    pub async fn add_member(
+        &self,
+        journal_id: JournalId,
+        member_id: UserId,
+        permissions: Permissions,
+        authority: Authority,
+        timestamp: Timestamp,
+    ) -> Result<PgEventId, DecisionError<JournalError>> {
+        Ok(self
+            .decision_maker
+            .make(AddJournalMember::new(
+                journal_id,
+                member_id,
+                permissions,
+                authority,
+                timestamp,
+            ))
+            .await?
+            .event_id())
+    }
+
+    #[tracing::instrument(skip_all, fields(%journal_id, actor = %authority.actor()))]
+    pub async fn update_member(
+        &self,
+        journal_id: JournalId,
+        member_id: UserId,
+        permissions: Permissions,
+        authority: Authority,
+        timestamp: Timestamp,
+    ) -> Result<PgEventId, DecisionError<JournalError>> {
+        Ok(self
+            .decision_maker
+            .make(UpdateJournalMember::new(
+                journal_id,
+                member_id,
+                permissions,
+                authority,
+                timestamp,
+            ))
+            .await?
+            .event_id())
+    }
+
+    #[tracing::instrument(skip_all, fields(%journal_id, actor = %authority.actor()))]
+    pub async fn remove_member(
+        &self,
+        journal_id: JournalId,
+        member_id: UserId,
+        authority: Authority,
+        timestamp: Timestamp,
+    ) -> Result<PgEventId, DecisionError<JournalError>> {
+        Ok(self
+            .decision_maker
+            .make(RemoveJournalMember::new(
+                journal_id, member_id, authority, timestamp,
+            ))
+            .await?
+            .event_id())
+    }
+
+    #[tracing::instrument(skip_all, fields(%journal_id, actor = %authority.actor()))]
+    pub async fn leave_journal(
+        &self,
+        journal_id: JournalId,
+        authority: Authority,
+        timestamp: Timestamp,
+    ) -> Result<PgEventId, DecisionError<JournalError>> {
+        Ok(self
+            .decision_maker
+            .make(LeaveJournal::new(journal_id, authority, timestamp))
+            .await?
+            .event_id())
+    }
+
+    #[tracing::instrument(skip_all, fields(%journal_id, actor = %authority.actor()))]
+    pub async fn create_account(
+        &self,
+        account_id: AccountId,
+        journal_id: JournalId,
+        name: Name,
+        authority: Authority,
+        timestamp: Timestamp,
+    ) -> Result<PgEventId, DecisionError<JournalError>> {
+        Ok(self
+            .decision_maker
+            .make(CreateAccount::new(
+                account_id, journal_id, name, authority, timestamp,
+            ))
+            .await?
+            .event_id())
+    }
+
+    #[tracing::instrument(skip_all, fields(%journal_id, actor = %authority.actor()))]
+    pub async fn rename_account(
+        &self,
+        account_id: AccountId,
+        journal_id: JournalId,
+        name: Name,
+        authority: Authority,
+        timestamp: Timestamp,
+    ) -> Result<PgEventId, DecisionError<JournalError>> {
+        Ok(self
+            .decision_maker
+            .make(RenameAccount::new(
+                account_id, journal_id, name, authority, timestamp,
+            ))
+            .await?
+            .event_id())
+    }
+
+    #[tracing::instrument(skip_all, fields(%journal_id, actor = %authority.actor()))]
+    pub async fn arrange_accounts(
+        &self,
+        journal_id: JournalId,
+        arrangement: Vec<AccountPlacement>,
+        authority: Authority,
+        timestamp: Timestamp,
+    ) -> Result<PgEventId, DecisionError<JournalError>> {
+        Ok(self
+            .decision_maker
+            .make(ArrangeAccounts::new(
+                journal_id,
+                arrangement,
+                authority,
+                timestamp,
+            ))
+            .await?
+            .event_id())
+    }
+
+    #[expect(clippy::too_many_arguments)]
+    #[tracing::instrument(skip_all, fields(%journal_id, actor = %authority.actor()))]
+    pub async fn create_transaction(
+        &self,
+        transaction_id: TransactionId,
+        journal_id: JournalId,
+        entries: Vec<BalanceUpdate>,
+        effective_date: NaiveDate,
+        payee: Option<Name>,
+        authority: Authority,
+        timestamp: Timestamp,
+    ) -> Result<PgEventId, DecisionError<JournalError>> {
+        let transactions = self
+            .transaction_count(journal_id)
+            .await
+            .map_err(DecisionError::Domain)?;
+
+        self.quotas
+            .check_transactions(transactions, 1)
+            .map_err(DecisionError::Domain)?;
+
+        Ok(self
+            .decision_maker
+            .make(CreateTransaction::new(
+                transaction_id,
+                journal_id,
+                entries,
+                effective_date,
+                payee,
+                authority,
+                timestamp,
+            ))
+            .await?
+            .event_id())
+    }
+
+    /// records a transaction for approval. it isn't counted against the quota until it's approved
+    #[expect(clippy::too_many_arguments)]
+    #[tracing::instrument(skip_all, fields(%journal_id, actor = %authority.actor()))]
+    pub async fn submit_transaction(
+        &self,
+        transaction_id: TransactionId,
+        journal_id: JournalId,
+        entries: Vec<BalanceUpdate>,
+        effective_date: NaiveDate,
+        payee: Option<Name>,
+        authority: Authority,
+        timestamp: Timestamp,
+    ) -> Result<PgEventId, DecisionError<JournalError>> {
+        Ok(self
+            .decision_maker
+            .make(SubmitTransaction::new(
+                transaction_id,
+                journal_id,
+                entries,
+                effective_date,
+                payee,
+                authority,
+                timestamp,
+            ))
+            .await?
+            .event_id())
+    }
+
+    #[tracing::instrument(skip_all, fields(%journal_id, actor = %authority.actor()))]
+    pub async fn approve_transaction(
+        &self,
+        transaction_id: TransactionId,
+        journal_id: JournalId,
+        authority: Authority,
+        timestamp: Timestamp,
+    ) -> Result<PgEventId, DecisionError<JournalError>> {
+        let transactions = self
+            .transaction_count(journal_id)
+            .await
+            .map_err(DecisionError::Domain)?;
+
+        self.quotas
+            .check_transactions(transactions, 1)
+            .map_err(DecisionError::Domain)?;
+
+        Ok(self
+            .decision_maker
+            .make(ApproveTransaction::new(
+                transaction_id,
+                journal_id,
+                authority,
+                timestamp,
+            ))
+            .await?
+            .event_id())
+    }
+
+    #[tracing::instrument(skip_all, fields(%journal_id, actor = %authority.actor()))]
+    pub async fn reject_transaction(
+        &self,
+        transaction_id: TransactionId,
+        journal_id: JournalId,
+        authority: Authority,
+        timestamp: Timestamp,
+    ) -> Result<PgEventId, DecisionError<JournalError>> {
+        Ok(self
+            .decision_maker
+            .make(RejectTransaction::new(
+                transaction_id,
+                journal_id,
+                authority,
+                timestamp,
+            ))
+            .await?
+            .event_id())
+    }
+
+    #[expect(clippy::too_many_arguments)]
+    #[tracing::instrument(skip_all, fields(%journal_id, actor = %authority.actor()))]
+    pub async fn add_comment(
+        &self,
+        comment_id: CommentId,
+        transaction_id: TransactionId,
+        journal_id: JournalId,
+        parent_id: Option<CommentId>,
+        body: String,
+        mentions: Vec<UserId>,
+        authority: Authority,
+        timestamp: Timestamp,
+    ) -> Result<PgEventId, DecisionError<JournalError>> {
+        Ok(self
+            .decision_maker
+            .make(AddComment::new(
+                comment_id,
+                transaction_id,
+                journal_id,
+                parent_id,
+                body,
+                mentions,
+                authority,
+                timestamp,
+            ))
+            .await?
+            .event_id())
+    }
+
+    /// records the transfer in both journals at once, each journal's transaction linking to the
+    /// other's. each journal's transaction quota is checked separately
+    #[tracing::instrument(skip_all, fields(from_journal_id = %from.journal_id, to_journal_id = %to.journal_id, actor = %authority.actor()))]
+    pub async fn transfer(
+        &self,
+        from: TransferSide,
+        to: TransferSide,
+        amount: u64,
+        effective_date: NaiveDate,
+        authority: Authority,
+        timestamp: Timestamp,
+    ) -> Result<PgEventId, DecisionError<JournalError>> {
+        for journal_id in [from.journal_id, to.journal_id] {
+            let transactions = self
+                .transaction_count(journal_id)
+                .await
+                .map_err(DecisionError::Domain)?;
+
+            self.quotas
+                .check_transactions(transactions, 1)
+                .map_err(DecisionError::Domain)?;
+        }
+
+        Ok(self
+            .decision_maker
+            .make(CreateTransfer::new(
+                from,
+                to,
+                amount,
+                effective_date,
+                authority,
+                timestamp,
+            ))
+            .await?
+            .event_id())
+    }
+
+    /// records a transaction that swaps every debit and credit of an existing one
+    #[tracing::instrument(skip_all, fields(%journal_id, actor = %authority.actor()))]
+    pub async fn reverse_transaction(
+        &self,
+        reversal_id: TransactionId,
+        transaction_id: TransactionId,
+        journal_id: JournalId,
+        effective_date: NaiveDate,
+        authority: Authority,
+        timestamp: Timestamp,
+    ) -> Result<PgEventId, DecisionError<JournalError>> {
+        let transactions = self
+            .transaction_count(journal_id)
+            .await
+            .map_err(DecisionError::Domain)?;
+
+        self.quotas
+            .check_transactions(transactions, 1)
+            .map_err(DecisionError::Domain)?;
+
+        Ok(self
+            .decision_maker
+            .make(ReverseTransaction::new(
+                reversal_id,
+                transaction_id,
+                journal_id,
+                effective_date,
+                authority,
+                timestamp,
+            ))
+            .await?
+            .event_id())
+    }
+
+    /// deletes a reversal, which undoes it
+    #[tracing::instrument(skip_all, fields(%journal_id, actor = %authority.actor()))]
+    pub async fn withdraw_reversal(
+        &self,
+        reversal_id: TransactionId,
+        journal_id: JournalId,
+        authority: Authority,
+        timestamp: Timestamp,
+    ) -> Result<PgEventId, DecisionError<JournalError>> {
+        Ok(self
+            .decision_maker
+            .make(WithdrawReversal::new(
+                reversal_id,
+                journal_id,
+                authority,
+                timestamp,
+            ))
+            .await?
+            .event_id())
+    }
+
+    /// creates every transaction in the batch or none of them
+    #[tracing::instrument(skip_all, fields(%journal_id, actor = %authority.actor()))]
+    pub async fn create_transactions(
+        &self,
+        journal_id: JournalId,
+        transactions: Vec<NewTransaction>,
+        authority: Authority,
+        timestamp: Timestamp,
+    ) -> Result<PgEventId, DecisionError<JournalError>> {
+        let count = self
+            .transaction_count(journal_id)
+            .await
+            .map_err(DecisionError::Domain)?;
+
+        self.quotas
+            .check_transactions(count, transactions.len() as u64)
+            .map_err(DecisionError::Domain)?;
+
+        Ok(self
+            .decision_maker
+            .make(CreateTransactionBatch::new(
+                journal_id,
+                transactions,
+                authority,
+                timestamp,
+            ))
+            .await?
+            .event_id())
+    }
+
+    /// stores the signing secret before adding the webhook, so its first delivery can be signed.
+    /// a url whose host isn't public is refused before anything is stored
+    #[tracing::instrument(skip_all, fields(%journal_id, actor = %authority.actor()))]
+    pub async fn add_webhook(
+        &self,
+        webhook_id: WebhookId,
+        journal_id: JournalId,
+        url: String,
+        secret: String,
+        authority: Authority,
+        timestamp: Timestamp,
+    ) -> Result<PgEventId, DecisionError<JournalError>> {
+        webhook::ensure_public(&url)
+            .await
+            .map_err(DecisionError::Domain)?;
+
+        self.secrets
+            .set(&signing_secret(webhook_id), &secret)
+            .await
+            .map_err(|e| DecisionError::Domain(e.into()))?;
+
+        let result = self
+            .decision_maker
+            .make(AddWebhook::new(
+                webhook_id, journal_id, url, authority, timestamp,
+            ))
+            .await;
+
+        if result.is_err() {
+            self.secrets
+                .remove(&signing_secret(webhook_id))
+                .await
+                .map_err(|e| DecisionError::Domain(e.into()))?;
+        }
+
+        Ok(result?.event_id())
+    }
+
+    #[tracing::instrument(skip_all, fields(%journal_id, actor = %authority.actor()))]
+    pub async fn remove_webhook(
+        &self,
+        webhook_id: WebhookId,
+        journal_id: JournalId,
+        authority: Authority,
+        timestamp: Timestamp,
+    ) -> Result<PgEventId, DecisionError<JournalError>> {
+        Ok(self
+            .decision_maker
+            .make(RemoveWebhook::new(
+                webhook_id, journal_id, authority, timestamp,
+            ))
+            .await?
+            .event_id())
+    }
+
+    #[tracing::instrument(skip_all, fields(%journal_id, actor = %authority.actor()))]
+    pub async fn add_rule(
+        &self,
+        rule_id: RuleId,
+        journal_id: JournalId,
+        condition: RuleCondition,
+        account_id: AccountId,
+        authority: Authority,
+        timestamp: Timestamp,
+    ) -> Result<PgEventId, DecisionError<JournalError>> {
+        Ok(self
+            .decision_maker
+            .make(AddRule::new(
+                rule_id, journal_id, condition, account_id, authority, timestamp,
+            ))
+            .await?
+            .event_id())
+    }
+
+    #[tracing::instrument(skip_all, fields(%journal_id, actor = %authority.actor()))]
+    pub async fn remove_rule(
+        &self,
+        rule_id: RuleId,
+        journal_id: JournalId,
+        authority: Authority,
+        timestamp: Timestamp,
+    ) -> Result<PgEventId, DecisionError<JournalError>> {
+        Ok(self
+            .decision_maker
+            .make(RemoveRule::new(rule_id, journal_id, authority, timestamp))
+            .await?
+            .event_id())
+    }
+
+    #[tracing::instrument(skip_all, fields(%journal_id, actor = %authority.actor()))]
+    pub async fn save_template(
+        &self,
+        template_id: TemplateId,
+        journal_id: JournalId,
+        name: Name,
+        entries: Vec<BalanceUpdate>,
+        authority: Authority,
+        timestamp: Timestamp,
+    ) -> Result<PgEventId, DecisionError<JournalError>> {
+        Ok(self
+            .decision_maker
+            .make(SaveTemplate::new(
+                template_id,
+                journal_id,
+                name,
+                entries,
+                authority,
+                timestamp,
+            ))
+            .await?
+            .event_id())
+    }
+
+    #[tracing::instrument(skip_all, fields(%journal_id, actor = %authority.actor()))]
+    pub async fn delete_template(
+        &self,
+        template_id: TemplateId,
+        journal_id: JournalId,
+        authority: Authority,
+        timestamp: Timestamp,
+    ) -> Result<PgEventId, DecisionError<JournalError>> {
+        Ok(self
+            .decision_maker
+            .make(DeleteTemplate::new(
+                template_id,
+                journal_id,
+                authority,
+                timestamp,
+            ))
+            .await?
+            .event_id())
+    }
+
+    /// stores the feed's credentials before linking it, so the sync worker never sees a feed
+    /// without them
+    #[expect(clippy::too_many_arguments)]
+    #[tracing::instrument(skip_all, fields(%journal_id, actor = %authority.actor()))]
+    pub async fn link_bank_feed(
+        &self,
+        bank_feed_id: BankFeedId,
+        journal_id: JournalId,
+        provider: String,
+        external_account: String,
+        account_id: AccountId,
+        credentials: FeedCredentials,
+        authority: Authority,
+        timestamp: Timestamp,
+    ) -> Result<PgEventId, DecisionError<JournalError>> {
+        self.secrets
+            .set(&feed_credentials(bank_feed_id), &credentials)
+            .await
+            .map_err(|e| DecisionError::Domain(e.into()))?;
+
+        sqlx::query!(
+            r#"
+            INSERT INTO bank_feed_syncs (bank_feed_id) VALUES ($1)
+            "#,
+            bank_feed_id as BankFeedId
+        )
+        .execute(&self.projection_pool)
+        .await
+        .map_err(|e| DecisionError::Domain(e.into()))?;
+
+        let result = self
+            .decision_maker
+            .make(LinkBankFeed::new(
+                bank_feed_id,
+                journal_id,
+                provider,
+                external_account,
+                account_id,
+                authority,
+                timestamp,
+            ))
+            .await;
+
+        if result.is_err() {
+            sqlx::query!(
+                r#"
+                DELETE FROM bank_feed_syncs WHERE bank_feed_id = $1
+                "#,
+                bank_feed_id as BankFeedId
+            )
+            .execute(&self.projection_pool)
+            .await
+            .map_err(|e| DecisionError::Domain(e.into()))?;
+
+            self.secrets
+                .remove(&feed_credentials(bank_feed_id))
+                .await
+                .map_err(|e| DecisionError::Domain(e.into()))?;
+        }
+
+        Ok(result?.event_id())
+    }
+
+    #[tracing::instrument(skip_all, fields(%journal_id, actor = %authority.actor()))]
+    pub async fn unlink_bank_feed(
+        &self,
+        bank_feed_id: BankFeedId,
+        journal_id: JournalId,
+        authority: Authority,
+        timestamp: Timestamp,
+    ) -> Result<PgEventId, DecisionError<JournalError>> {
+        Ok(self
+            .decision_maker
+            .make(UnlinkBankFeed::new(
+                bank_feed_id,
+                journal_id,
+                authority,
+                timestamp,
+            ))
+            .await?
+            .event_id())
+    }
+
+    pub async fn get_effective_permissions(
+        &self,
+        journal_id: JournalId,
+        authority: &Authority,
+    ) -> JournalResult<Permissions> {
+        match authority.actor() {
+            Actor::System => Ok(Permissions::OWNER),
+            Actor::Anonymous | Actor::Deleted => Ok(Permissions::empty()),
+            Actor::User(user_id) => {
+                if let Some(permissions) = self.cache.permissions(journal_id, *user_id) {
+                    return Ok(permissions);
+                }
+
+                let generation = self.cache.generation();
+
+                let permission_bits = sqlx::query_scalar!(
+                    r#"
+                    SELECT
+                        CASE
+                            WHEN j.owner_id = $1 THEN $2::INTEGER
+                            ELSE COALESCE(
+                                 (SELECT jm.permissions
+                                 FROM journal_members jm
+                                 WHERE jm.journal_id = j.id AND jm.user_id = $1),
+                                 0
+                            )
+                        END as "i32!"
+                    FROM journals j
+                    WHERE j.id = $3
+                "#,
+                    *user_id as UserId,
+                    Permissions::all().bits(),
+                    journal_id as JournalId
+                )
+                .fetch_optional(&self.projection_pool)
+                .await?;
+
+                let permissions = if let Some(bits) = permission_bits {
+                    Permissions::from_bits(bits)
+                        .ok_or(JournalError::PermissionDecode(PermissionDecodeError(bits)))?
+                } else {
+                    Permissions::empty()
+                };
+
+                self.cache
+                    .insert_permissions(generation, journal_id, *user_id, permissions);
+
+                Ok(permissions)
+            }
+        }
+    }
+
+    /// the actor's permissions on a journal, once they're known to cover `required`
+    async fn require(
+        &self,
+        journal_id: JournalId,
+        authority: &Authority,
+        required: Permissions,
+    ) -> JournalResult<Permissions> {
+        let permissions = self
             .get_effective_permissions(journal_id, authority)
+            .await?;
+        check_access(journal_id, permissions, required)?;
+
+        Ok(permissions)
+    }
+
+    /// returns the current state, creation authority, and creation timestamp of every accessible
+    /// journal, with the user's pinned journals first and the rest by their latest activity
+    pub async fn list_accessible_journals(
+        &self,
+        user: UserId,
+    ) -> JournalResult<Vec<(JournalState, Authority, Timestamp)>> {
+        // NOTE(gabriel): a user must not be both a member and the owner, or this query will return duplicate journals
+
+        let journals = sqlx::query_as!(
+            JournalStateWithPayload,
+            r#"
+            SELECT j.id as "id: JournalId", j.owner_id as "owner_id: UserId", j.name as "name: Name", j.fiscal_year_start as "fiscal_year_start: FiscalYearStart", j.archived, j.default_cash_account as "default_cash_account: AccountId", j.default_expense_account as "default_expense_account: AccountId", j.color as "color: JournalColor", j.icon as "icon: JournalIcon", e.payload as "payload!"
+            FROM journals j
+            INNER JOIN event e
+                ON e.journal_id = j.id AND e.event_type = 'JournalCreated'
+            LEFT JOIN journal_members jm ON jm.journal_id = j.id AND (jm.permissions & $1) = $1
+            LEFT JOIN journal_versions v ON v.journal_id = j.id
+            LEFT JOIN journal_pins p ON p.journal_id = j.id AND p.user_id = $2
+            WHERE j.owner_id = $2 OR jm.user_id = $2
+            ORDER BY p.user_id IS NULL, v.last_activity DESC NULLS LAST, j.name
+            "#,
+            Permissions::READ.bits(),
+            user as UserId)
+            .fetch_all(&self.read_pool)
+            .await?;
+
+        // TODO(gabriel) would .map() be more efficient here?
+        let mut journals_with_meta = Vec::with_capacity(journals.len());
+
+        for journal in journals {
+            let payload: JournalDomainEvent = self.payloads.decode(&journal.payload)?;
+
+            match payload {
+                JournalDomainEvent::JournalCreated {
+                    authority,
+                    timestamp,
+                    ..
+                } => {
+                    journals_with_meta.push((
+                        JournalState {
+                            id: journal.id,
+                            owner_id: journal.owner_id,
+                            name: journal.name,
+                            fiscal_year_start: journal.fiscal_year_start,
+                            archived: journal.archived,
+                            default_cash_account: journal.default_cash_account,
+                            default_expense_account: journal.default_expense_account,
+                            appearance: JournalAppearance {
+                                color: journal.color,
+                                icon: journal.icon,
+                            },
+                        },
+                        authority,
+                        timestamp,
+                    ));
+                }
+                _ => unreachable!("JournalCreated events are filtered by the sql query"),
+            }
+        }
+
+        Ok(journals_with_meta)
+    }
+
+    pub async fn get_journal(
+        &self,
+        journal_id: JournalId,
+        authority: &Authority,
+    ) -> JournalResult<(JournalState, Authority, Timestamp)> {
+        self.require(journal_id, authority, Permissions::READ)
+            .await?;
+
+        self.load_journal(journal_id).await
+    }
+
+    /// the journal whoever is asking, for callers that check permissions themselves
+    pub(crate) async fn load_journal(
+        &self,
+        journal_id: JournalId,
+    ) -> JournalResult<(JournalState, Authority, Timestamp)> {
+        if let Some(journal) = self.cache.journal(journal_id) {
+            return Ok(journal);
+        }
+
+        let generation = self.cache.generation();
+
+        let journal = sqlx::query_as!(
+            JournalStateWithPayload,
+            r#"
+            SELECT j.id as "id: JournalId", j.owner_id as "owner_id: UserId", j.name as "name: Name", j.fiscal_year_start as "fiscal_year_start: FiscalYearStart", j.archived, j.default_cash_account as "default_cash_account: AccountId", j.default_expense_account as "default_expense_account: AccountId", j.color as "color: JournalColor", j.icon as "icon: JournalIcon", e.payload as "payload!"
+            FROM journals j
+            INNER JOIN event e
+                ON e.journal_id = $1 AND e.event_type = 'JournalCreated'
+            WHERE j.id = $1
+            "#,
+            journal_id as JournalId)
+            .fetch_optional(&self.projection_pool)
+            .await?;
+
+        if let Some(journal) = journal {
+            let payload: JournalDomainEvent = self.payloads.decode(&journal.payload)?;
+
+            match payload {
+                JournalDomainEvent::JournalCreated {
+                    authority,
+                    timestamp,
+                    ..
+                } => {
+                    let journal = (
+                        JournalState {
+                            id: journal.id,
+                            owner_id: journal.owner_id,
+                            name: journal.name,
+                            fiscal_year_start: journal.fiscal_year_start,
+                            archived: journal.archived,
+                            default_cash_account: journal.default_cash_account,
+                            default_expense_account: journal.default_expense_account,
+                            appearance: JournalAppearance {
+                                color: journal.color,
+                                icon: journal.icon,
+                            },
+                        },
+                        authority,
+                        timestamp,
+                    );
+
+                    self.cache
+                        .insert_journal(generation, journal_id, journal.clone());
+
+                    Ok(journal)
+                }
+                _ => unreachable!("JournalCreated events are filtered by the sql query"),
+            }
+        } else {
+            Err(JournalError::InvalidJournal(journal_id))
+        }
+    }
+
+    pub async fn list_journal_members(
+        &self,
+        journal_id: JournalId,
+        authority: &Authority,
+    ) -> JournalResult<Vec<UserId>> {
+        self.require(journal_id, authority, Permissions::READ)
+            .await?;
+
+        Ok(sqlx::query_scalar!(
+            r#"
+            SELECT user_id as "user_id: UserId" FROM journal_members WHERE journal_id = $1
+            "#,
+            journal_id as JournalId
+        )
+        .fetch_all(&self.read_pool)
+        .await?)
+    }
+
+    /// the owner and members of a journal, who are the users a comment can mention
+    pub async fn list_mentionable_users(
+        &self,
+        journal_id: JournalId,
+        authority: &Authority,
+    ) -> JournalResult<Vec<UserId>> {
+        self.require(journal_id, authority, Permissions::READ)
+            .await?;
+
+        Ok(sqlx::query_scalar!(
+            r#"
+            SELECT owner_id as "user_id!: UserId" FROM journals WHERE id = $1
+            UNION
+            SELECT user_id FROM journal_members WHERE journal_id = $1
+            "#,
+            journal_id as JournalId
+        )
+        .fetch_all(&self.read_pool)
+        .await?)
+    }
+
+    pub async fn list_journal_accounts(
+        &self,
+        journal_id: JournalId,
+        authority: &Authority,
+    ) -> JournalResult<Vec<(AccountState, Authority, Timestamp)>> {
+        self.require(journal_id, authority, Permissions::READ)
+            .await?;
+
+        let accounts = sqlx::query_as!(
+            AccountStateWithPayload,
+            r#"
+            SELECT a.id as "id: AccountId", a.journal_id as "journal_id: JournalId", a.balance, a.name as "name: Name", a.group_name as "group: Name", e.payload as "payload!"
+            FROM accounts a
+            INNER JOIN event e
+                ON e.account_id = a.id AND e.event_type = 'AccountCreated'
+            WHERE a.journal_id = $1
+            ORDER BY a.position NULLS LAST, a.name
+            "#,
+            journal_id as JournalId)
+            .fetch_all(&self.read_pool)
+            .await?;
+
+        let mut transactions_with_meta = Vec::with_capacity(accounts.len());
+
+        for account in accounts {
+            let payload: JournalDomainEvent = self.payloads.decode(&account.payload)?;
+
+            match payload {
+                JournalDomainEvent::AccountCreated {
+                    authority,
+                    timestamp,
+                    ..
+                } => {
+                    transactions_with_meta.push((
+                        AccountState {
+                            id: account.id,
+                            journal_id: account.journal_id,
+                            name: account.name,
+                            balance: account.balance,
+                            group: account.group,
+                        },
+                        authority,
+                        timestamp,
+                    ));
+                }
+                _ => unreachable!("AccountCreated events are filtered by the sql query"),
+            }
+        }
+
+        Ok(transactions_with_meta)
+    }
+
+    /// returns the accounts in the journal whose names match the full-text query,
+    /// falling back to a substring match so partial words still find results
+    pub async fn search_accounts(
+        &self,
+        journal_id: JournalId,
+        authority: &Authority,
+        search: &str,
+    ) -> JournalResult<Vec<AccountState>> {
+        self.require(journal_id, authority, Permissions::READ)
+            .await?;
+
+        Ok(sqlx::query_as!(
+            AccountState,
+            r#"
+            SELECT a.id as "id: AccountId", a.journal_id as "journal_id: JournalId", a.name as "name: Name", a.balance, a.group_name as "group: Name"
+            FROM accounts a
+            WHERE a.journal_id = $1
+                AND (a.name_search @@ websearch_to_tsquery('simple', $2) OR a.name ILIKE '%' || $2 || '%')
+            ORDER BY ts_rank(a.name_search, websearch_to_tsquery('simple', $2)) DESC, a.name
+            "#,
+            journal_id as JournalId,
+            search
+        )
+        .fetch_all(&self.read_pool)
+        .await?)
+    }
+
+    /// returns every transaction in the journal ordered by effective date,
+    /// with transactions sharing a date kept in the order they were entered
+    pub async fn list_journal_transactions(
+        &self,
+        journal_id: JournalId,
+        authority: &Authority,
+    ) -> JournalResult<Vec<(TransactionState, Authority, Timestamp)>> {
+        self.require(journal_id, authority, Permissions::READ)
+            .await?;
+
+        let transactions = sqlx::query_as!(
+            TransactionStateWithPayload,
+            r#"
+            SELECT t.id as "id: TransactionId", t.journal_id as "journal_id: JournalId", t.number, t.entries as "entries: TransactionEntries", t.effective_date as "effective_date: NaiveDate", t.reverses as "reverses: TransactionId", e.payload as "payload!"
+            FROM transactions t
+            INNER JOIN event e
+                ON e.transaction_id = t.id AND e.event_type = 'TransactionCreated'
+            WHERE t.journal_id = $1
+            ORDER BY t.effective_date, e.event_id
+            "#,
+            journal_id as JournalId)
+            .fetch_all(&self.read_pool)
+            .await?;
+
+        let mut transactions_with_meta = Vec::with_capacity(transactions.len());
+
+        for transaction in transactions {
+            let payload: JournalDomainEvent = self.payloads.decode(&transaction.payload)?;
+
+            match payload {
+                JournalDomainEvent::TransactionCreated {
+                    transfer,
+                    payee,
+                    authority,
+                    timestamp,
+                    ..
+                } => {
+                    transactions_with_meta.push((
+                        TransactionState {
+                            id: transaction.id,
+                            journal_id: transaction.journal_id,
+                            number: transaction.number as u64,
+                            entries: transaction.entries.0,
+                            effective_date: transaction.effective_date,
+                            reverses: transaction.reverses,
+                            transfer,
+                            payee,
+                        },
+                        authority,
+                        timestamp,
+                    ));
+                }
+                _ => unreachable!("TransactionCreated events are filtered by the sql query"),
+            }
+        }
+
+        Ok(transactions_with_meta)
+    }
+
+    /// returns the transactions waiting for approval, oldest submission first, with who
+    /// submitted them and when
+    pub async fn list_pending_transactions(
+        &self,
+        journal_id: JournalId,
+        authority: &Authority,
+    ) -> JournalResult<Vec<(PendingTransactionState, Authority, Timestamp)>> {
+        self.require(journal_id, authority, Permissions::READ)
+            .await?;
+
+        let pending = sqlx::query!(
+            r#"
+            SELECT p.id as "id: TransactionId", p.entries as "entries: TransactionEntries", p.effective_date as "effective_date: NaiveDate", e.payload as "payload!"
+            FROM pending_transactions p
+            INNER JOIN event e
+                ON e.transaction_id = p.id AND e.event_type = 'TransactionSubmitted'
+            WHERE p.journal_id = $1
+            ORDER BY e.event_id
+            "#,
+            journal_id as JournalId
+        )
+        .fetch_all(&self.read_pool)
+        .await?;
+
+        let mut pending_with_meta = Vec::with_capacity(pending.len());
+
+        for transaction in pending {
+            let payload: JournalDomainEvent = self.payloads.decode(&transaction.payload)?;
+            let payee = match &payload {
+                JournalDomainEvent::TransactionSubmitted { payee, .. } => payee.clone(),
+                _ => unreachable!("TransactionSubmitted events are filtered by the sql query"),
+            };
+
+            pending_with_meta.push((
+                PendingTransactionState {
+                    id: transaction.id,
+                    entries: transaction.entries.0,
+                    effective_date: transaction.effective_date,
+                    payee,
+                },
+                payload.authority().clone(),
+                payload.timestamp(),
+            ));
+        }
+
+        Ok(pending_with_meta)
+    }
+
+    /// returns the comments on a transaction in the order they were posted
+    pub async fn list_comments(
+        &self,
+        journal_id: JournalId,
+        transaction_id: TransactionId,
+        authority: &Authority,
+    ) -> JournalResult<Vec<CommentState>> {
+        self.require(journal_id, authority, Permissions::READ)
+            .await?;
+
+        let comments = sqlx::query!(
+            r#"
+            SELECT c.id as "id: CommentId", c.parent_id as "parent_id: CommentId", c.body, e.payload as "payload!"
+            FROM transaction_comments c
+            INNER JOIN event e
+                ON e.comment_id = c.id AND e.event_type = 'CommentAdded'
+            WHERE c.journal_id = $1 AND c.transaction_id = $2
+            ORDER BY e.event_id
+            "#,
+            journal_id as JournalId,
+            transaction_id as TransactionId
+        )
+        .fetch_all(&self.read_pool)
+        .await?;
+
+        let mut comments_with_meta = Vec::with_capacity(comments.len());
+
+        for comment in comments {
+            let payload: JournalDomainEvent = self.payloads.decode(&comment.payload)?;
+
+            comments_with_meta.push(CommentState {
+                id: comment.id,
+                parent_id: comment.parent_id,
+                body: comment.body,
+                authority: payload.authority().clone(),
+                timestamp: payload.timestamp(),
+            });
+        }
+
+        Ok(comments_with_meta)
+    }
+
+    /// streams every event in the journal's history in the order it was recorded, including
+    /// events that only name one of the journal's accounts or transactions. the events are
+    /// read a page at a time, so long histories aren't held in memory at once.
+    ///
+    /// every store shares one event table and sequence, so event ids order the whole history.
+    /// ids are handed out before their transactions commit, though, so the stream stops at the
+    /// last id with no uncommitted event before it rather than skipping one that commits late
+    pub async fn stream_journal_events(
+        &self,
+        journal_id: JournalId,
+        authority: &Authority,
+    ) -> JournalResult<impl Stream<Item = JournalResult<JournalEventEnvelope>> + '_> {
+        self.require(journal_id, authority, Permissions::READ)
+            .await?;
+
+        let up_to = self.committed_event_id().await?;
+
+        let pages = stream::try_unfold(Some(0), move |after| async move {
+            let Some(after) = after else {
+                return Ok::<_, JournalError>(None);
+            };
+
+            let page = self
+                .journal_events_between(journal_id, after, up_to, EVENT_PAGE_SIZE)
+                .await?;
+
+            // a short page is the end of the history
+            let next = match page.last() {
+                Some(last) if page.len() as i64 == EVENT_PAGE_SIZE => Some(last.event_id),
+                _ => None,
+            };
+
+            Ok(Some((stream::iter(page.into_iter().map(Ok)), next)))
+        });
+
+        Ok(pages.try_flatten())
+    }
+
+    /// up to `limit` of the journal's events after the `after` event, in the order they were
+    /// recorded. like the stream, it stops short of any event that might still commit before
+    /// one it returns, so a client that resumes from the last id never misses an event
+    pub async fn journal_events_page(
+        &self,
+        journal_id: JournalId,
+        authority: &Authority,
+        after: PgEventId,
+        limit: i64,
+    ) -> JournalResult<Vec<JournalEventEnvelope>> {
+        self.require(journal_id, authority, Permissions::READ)
+            .await?;
+
+        let up_to = self.committed_event_id().await?;
+
+        self.journal_events_between(journal_id, after, up_to, limit)
+            .await
+    }
+
+    /// the newest event id with every earlier event committed
+    async fn committed_event_id(&self) -> JournalResult<PgEventId> {
+        Ok(sqlx::query_scalar!(
+            r#"
+            SELECT event_store_current_epoch() as "epoch!"
+            "#
+        )
+        .fetch_one(&self.projection_pool)
+        .await?)
+    }
+
+    /// up to `limit` of the journal's events after the `after` event, up to and including `up_to`
+    async fn journal_events_between(
+        &self,
+        journal_id: JournalId,
+        after: PgEventId,
+        up_to: PgEventId,
+        limit: i64,
+    ) -> JournalResult<Vec<JournalEventEnvelope>> {
+        let rows = sqlx::query!(
+            r#"
+            SELECT e.event_id as "event_id!", e.payload as "payload!"
+            FROM event e
+            WHERE e.event_id > $2 AND e.event_id <= $3
+                AND (
+                    e.journal_id = $1
+                    OR e.account_id IN (
+                        SELECT account_id FROM event WHERE journal_id = $1 AND account_id IS NOT NULL
+                    )
+                    OR e.transaction_id IN (
+                        SELECT transaction_id FROM event WHERE journal_id = $1 AND transaction_id IS NOT NULL
+                    )
+                )
+            ORDER BY e.event_id
+            LIMIT $4
+            "#,
+            journal_id as JournalId,
+            after,
+            up_to,
+            limit
+        )
+        .fetch_all(&self.projection_pool)
+        .await?;
+
+        let mut events = Vec::with_capacity(rows.len());
+
+        for row in rows {
+            events.push(JournalEventEnvelope {
+                event_id: row.event_id,
+                event: self.payloads.decode(&row.payload)?,
+            });
+        }
+
+        Ok(events)
+    }
+
+    pub async fn get_transaction(
+        &self,
+        journal_id: JournalId,
+        transaction_id: TransactionId,
+        authority: &Authority,
+    ) -> JournalResult<(TransactionState, Authority, Timestamp)> {
+        self.require(journal_id, authority, Permissions::READ)
+            .await?;
+
+        let transaction = sqlx::query_as!(
+            TransactionStateWithPayload,
+            r#"
+            SELECT t.id as "id: TransactionId", t.journal_id as "journal_id: JournalId", t.number, t.entries as "entries: TransactionEntries", t.effective_date as "effective_date: NaiveDate", t.reverses as "reverses: TransactionId", e.payload as "payload!"
+            FROM transactions t
+            INNER JOIN event e
+                ON e.transaction_id = t.id AND e.event_type = 'TransactionCreated'
+            WHERE t.id = $1 AND t.journal_id = $2
+            "#,
+            transaction_id as TransactionId,
+            journal_id as JournalId)
+            .fetch_optional(&self.read_pool)
+            .await?
+            .ok_or(JournalError::InvalidTransaction(transaction_id))?;
+
+        let payload: JournalDomainEvent = self.payloads.decode(&transaction.payload)?;
+
+        match payload {
+            JournalDomainEvent::TransactionCreated {
+                transfer,
+                payee,
+                authority,
+                timestamp,
+                ..
+            } => Ok((
+                TransactionState {
+                    id: transaction.id,
+                    journal_id: transaction.journal_id,
+                    number: transaction.number as u64,
+                    entries: transaction.entries.0,
+                    effective_date: transaction.effective_date,
+                    reverses: transaction.reverses,
+                    transfer,
+                    payee,
+                },
+                authority,
+                timestamp,
+            )),
+            _ => unreachable!("TransactionCreated events are filtered by the sql query"),
+        }
+    }
+
+    /// the newest event that changed the transaction, such as its approval or deletion.
+    /// comments are on the transaction's stream too, but they don't change it
+    pub async fn last_transaction_change(
+        &self,
+        journal_id: JournalId,
+        transaction_id: TransactionId,
+        authority: &Authority,
+    ) -> JournalResult<Option<JournalEventEnvelope>> {
+        self.require(journal_id, authority, Permissions::READ)
+            .await?;
+
+        let Some(row) = sqlx::query!(
+            r#"
+            SELECT e.event_id as "event_id!", e.payload as "payload!"
+            FROM event e
+            WHERE e.transaction_id = $2 AND e.event_type <> 'CommentAdded'
+                AND EXISTS (
+                    SELECT 1 FROM event WHERE journal_id = $1 AND transaction_id = $2
+                )
+            ORDER BY e.event_id DESC
+            LIMIT 1
+            "#,
+            journal_id as JournalId,
+            transaction_id as TransactionId
+        )
+        .fetch_optional(&self.read_pool)
+        .await?
+        else {
+            return Ok(None);
+        };
+
+        Ok(Some(JournalEventEnvelope {
+            event_id: row.event_id,
+            event: self.payloads.decode(&row.payload)?,
+        }))
+    }
+
+    /// the newest event that changed each of the journal's accounts: its creation, its latest
+    /// rename or its deletion. arranging the accounts isn't counted, since it moves all of them
+    pub async fn last_account_changes(
+        &self,
+        journal_id: JournalId,
+        authority: &Authority,
+    ) -> JournalResult<HashMap<AccountId, JournalEventEnvelope>> {
+        self.require(journal_id, authority, Permissions::READ)
+            .await?;
+
+        sqlx::query!(
+            r#"
+            SELECT DISTINCT ON (e.account_id)
+                e.account_id as "account_id!: AccountId", e.event_id as "event_id!", e.payload as "payload!"
+            FROM event e
+            WHERE e.account_id IN (
+                SELECT account_id FROM event WHERE journal_id = $1 AND account_id IS NOT NULL
+            )
+            ORDER BY e.account_id, e.event_id DESC
+            "#,
+            journal_id as JournalId
+        )
+        .fetch_all(&self.read_pool)
+        .await?
+        .into_iter()
+        .map(|row| {
+            Ok((
+                row.account_id,
+                JournalEventEnvelope {
+                    event_id: row.event_id,
+                    event: self.payloads.decode(&row.payload)?,
+                },
+            ))
+        })
+        .collect()
+    }
+
+    /// the transactions recorded as reversals of this one
+    pub async fn list_reversals(
+        &self,
+        journal_id: JournalId,
+        transaction_id: TransactionId,
+        authority: &Authority,
+    ) -> JournalResult<Vec<TransactionId>> {
+        self.require(journal_id, authority, Permissions::READ)
+            .await?;
+
+        Ok(sqlx::query_scalar!(
+            r#"
+            SELECT id as "id: TransactionId" FROM transactions WHERE journal_id = $1 AND reverses = $2
+            "#,
+            journal_id as JournalId,
+            transaction_id as TransactionId
+        )
+        .fetch_all(&self.read_pool)
+        .await?)
+    }
+
+    /// counts transactions by month from `since`, and ranks the journal's accounts and
+    /// authors, keeping the top `limit` of each
+    pub async fn journal_stats(
+        &self,
+        journal_id: JournalId,
+        authority: &Authority,
+        since: NaiveDate,
+        limit: i64,
+    ) -> JournalResult<JournalStats> {
+        self.require(journal_id, authority, Permissions::READ)
+            .await?;
+
+        let monthly_transactions = sqlx::query!(
+            r#"
+            SELECT date_trunc('month', effective_date)::DATE as "month!: NaiveDate", COUNT(*) as "count!"
+            FROM transactions
+            WHERE journal_id = $1 AND effective_date >= $2
+            GROUP BY 1
+            ORDER BY 1
+            "#,
+            journal_id as JournalId,
+            since as NaiveDate
+        )
+        .fetch_all(&self.read_pool)
+        .await?
+        .into_iter()
+        .map(|row| (row.month, row.count as u64))
+        .collect();
+
+        let largest_accounts = sqlx::query!(
+            r#"
+            SELECT id as "id: AccountId", name as "name: Name", balance
+            FROM accounts
+            WHERE journal_id = $1 AND balance <> 0
+            ORDER BY ABS(balance) DESC, name
+            LIMIT $2
+            "#,
+            journal_id as JournalId,
+            limit
+        )
+        .fetch_all(&self.read_pool)
+        .await?
+        .into_iter()
+        .map(|row| (row.id, row.name, row.balance))
+        .collect();
+
+        let top_authors = sqlx::query!(
+            r#"
+            SELECT author_id as "author_id!: UserId", COUNT(*) as "count!"
+            FROM transactions
+            WHERE journal_id = $1 AND author_id IS NOT NULL
+            GROUP BY author_id
+            ORDER BY 2 DESC, author_id
+            LIMIT $2
+            "#,
+            journal_id as JournalId,
+            limit
+        )
+        .fetch_all(&self.read_pool)
+        .await?
+        .into_iter()
+        .map(|row| (row.author_id, row.count as u64))
+        .collect();
+
+        Ok(JournalStats {
+            monthly_transactions,
+            largest_accounts,
+            top_authors,
+        })
+    }
+
+    /// how much each account's balance changed in each month it had entries, oldest first.
+    /// credits are positive, as they are in the balances
+    pub async fn monthly_balance_changes(
+        &self,
+        journal_id: JournalId,
+        authority: &Authority,
+    ) -> JournalResult<Vec<(AccountId, NaiveDate, i64)>> {
+        self.require(journal_id, authority, Permissions::READ)
+            .await?;
+
+        Ok(sqlx::query!(
+            r#"
+            SELECT account_id as "account_id: AccountId", period as "period: NaiveDate", credit_total - debit_total as "change!"
+            FROM account_period_totals
+            WHERE journal_id = $1
+            ORDER BY period, account_id
+            "#,
+            journal_id as JournalId
+        )
+        .fetch_all(&self.read_pool)
+        .await?
+        .into_iter()
+        .map(|row| (row.account_id, row.period, row.change))
+        .collect())
+    }
+
+    /// reads the dashboard for every journal the user can read, keeping each journal's
+    /// `balances` largest balances and the `recent` latest transactions. each section is one
+    /// query across all of the journals, so the number of queries doesn't grow with them
+    pub async fn dashboard(
+        &self,
+        user: UserId,
+        balances: i64,
+        recent: i64,
+    ) -> JournalResult<Dashboard> {
+        let mut journals: Vec<JournalOverview> = sqlx::query!(
+            r#"
+            SELECT j.id as "id: JournalId", j.name as "name: Name", j.color as "color: JournalColor", j.icon as "icon: JournalIcon"
+            FROM journals j
+            LEFT JOIN journal_members jm ON jm.journal_id = j.id AND jm.user_id = $1
+            WHERE j.owner_id = $1 OR (jm.permissions & $2) = $2
+            ORDER BY j.name, j.id
+            "#,
+            user as UserId,
+            Permissions::READ.bits()
+        )
+        .fetch_all(&self.read_pool)
+        .await?
+        .into_iter()
+        .map(|row| JournalOverview {
+            id: row.id,
+            name: row.name,
+            appearance: JournalAppearance {
+                color: row.color,
+                icon: row.icon,
+            },
+            balances: Vec::new(),
+        })
+        .collect();
+
+        let journal_ids: Vec<JournalId> = journals.iter().map(|j| j.id).collect();
+
+        let top_balances = sqlx::query!(
+            r#"
+            SELECT journal_id as "journal_id!: JournalId", id as "id!: AccountId", name as "name!: Name", balance as "balance!"
+            FROM (
+                SELECT journal_id, id, name, balance,
+                    ROW_NUMBER() OVER (PARTITION BY journal_id ORDER BY ABS(balance) DESC, name) as rank
+                FROM accounts
+                WHERE journal_id = ANY($1) AND balance <> 0
+            ) ranked
+            WHERE rank <= $2
+            ORDER BY rank
+            "#,
+            journal_ids.as_slice() as &[JournalId],
+            balances
+        )
+        .fetch_all(&self.read_pool)
+        .await?;
+
+        for row in top_balances {
+            if let Some(journal) = journals.iter_mut().find(|j| j.id == row.journal_id) {
+                journal.balances.push((row.id, row.name, row.balance));
+            }
+        }
+
+        let recent_transactions = sqlx::query!(
+            r#"
+            SELECT t.id as "id: TransactionId", t.journal_id as "journal_id: JournalId", j.name as "journal_name: Name", t.number, t.entries as "entries: TransactionEntries", t.effective_date as "effective_date: NaiveDate"
+            FROM transactions t
+            INNER JOIN journals j ON j.id = t.journal_id
+            WHERE t.journal_id = ANY($1)
+            ORDER BY t.effective_date DESC, t.number DESC
+            LIMIT $2
+            "#,
+            journal_ids.as_slice() as &[JournalId],
+            recent
+        )
+        .fetch_all(&self.read_pool)
+        .await?
+        .into_iter()
+        .map(|row| RecentTransaction {
+            id: row.id,
+            journal_id: row.journal_id,
+            journal_name: row.journal_name,
+            number: row.number as u64,
+            entries: row.entries.0,
+            effective_date: row.effective_date,
+        })
+        .collect();
+
+        let invites = sqlx::query_as!(
+            PendingInvite,
+            r#"
+            SELECT n.journal_id as "journal_id: JournalId", j.name as "journal_name: Name", n.created_at as "created_at: Timestamp"
+            FROM notifications n
+            INNER JOIN event e ON e.event_id = n.event_id AND e.event_type = 'MemberAdded'
+            INNER JOIN journals j ON j.id = n.journal_id
+            WHERE n.user_id = $1 AND n.read_at IS NULL
+            ORDER BY n.id DESC
+            "#,
+            user as UserId
+        )
+        .fetch_all(&self.read_pool)
+        .await?;
+
+        Ok(Dashboard {
+            journals,
+            recent_transactions,
+            invites,
+        })
+    }
+
+    /// sums the debit and credit entries of every transaction whose effective date falls
+    /// within the inclusive range. a missing bound leaves that side of the range open.
+    /// only ranges that start or end partway through a period read the transactions
+    pub async fn transaction_totals_in_range(
+        &self,
+        journal_id: JournalId,
+        authority: &Authority,
+        from: Option<NaiveDate>,
+        to: Option<NaiveDate>,
+    ) -> JournalResult<TransactionTotals> {
+        self.require(journal_id, authority, Permissions::READ)
+            .await?;
+
+        // a range of whole periods can be summed from the period totals
+        let whole_periods = from.is_none_or(|from| fiscal::period_start(from) == from)
+            && to.is_none_or(|to| {
+                to.succ_opt()
+                    .is_none_or(|next| fiscal::period_start(next) == next)
+            });
+
+        if whole_periods {
+            let totals = sqlx::query!(
+                r#"
+                SELECT COALESCE(SUM(debit_total), 0)::BIGINT as "debits!", COALESCE(SUM(credit_total), 0)::BIGINT as "credits!"
+                FROM account_period_totals
+                WHERE journal_id = $1
+                    AND ($2::DATE IS NULL OR period >= $2)
+                    AND ($3::DATE IS NULL OR period <= $3)
+                "#,
+                journal_id as JournalId,
+                from as Option<NaiveDate>,
+                to as Option<NaiveDate>
+            )
+            .fetch_one(&self.read_pool)
+            .await?;
+
+            return Ok(TransactionTotals {
+                debits: totals.debits as u64,
+                credits: totals.credits as u64,
+            });
+        }
+
+        let entries = sqlx::query_scalar!(
+            r#"
+            SELECT entries as "entries: TransactionEntries"
+            FROM transactions
+            WHERE journal_id = $1
+                AND ($2::DATE IS NULL OR effective_date >= $2)
+                AND ($3::DATE IS NULL OR effective_date <= $3)
+            "#,
+            journal_id as JournalId,
+            from as Option<NaiveDate>,
+            to as Option<NaiveDate>
+        )
+        .fetch_all(&self.read_pool)
+        .await?;
+
+        let mut totals = TransactionTotals {
+            debits: 0,
+            credits: 0,
+        };
+
+        for update in entries.into_iter().flat_map(|e| e.0) {
+            match update.entry_type {
+                EntryType::Debit => totals.debits += update.amount,
+                EntryType::Credit => totals.credits += update.amount,
+            }
+        }
+
+        Ok(totals)
+    }
+
+    /// the payees recorded on the journal's transactions, most used first, for suggesting one
+    /// as it's typed
+    pub async fn list_payees(
+        &self,
+        journal_id: JournalId,
+        authority: &Authority,
+    ) -> JournalResult<Vec<Name>> {
+        self.require(journal_id, authority, Permissions::READ)
+            .await?;
+
+        Ok(sqlx::query_scalar!(
+            r#"
+            SELECT payee as "payee!: Name"
+            FROM transactions
+            WHERE journal_id = $1 AND payee IS NOT NULL
+            GROUP BY payee
+            ORDER BY COUNT(*) DESC, payee
+            "#,
+            journal_id as JournalId
+        )
+        .fetch_all(&self.read_pool)
+        .await?)
+    }
+
+    /// the payee and entries of every transaction with a payee whose effective date falls
+    /// within the inclusive range. a missing bound leaves that side of the range open
+    pub async fn payee_transactions(
+        &self,
+        journal_id: JournalId,
+        authority: &Authority,
+        from: Option<NaiveDate>,
+        to: Option<NaiveDate>,
+    ) -> JournalResult<Vec<(Name, Vec<BalanceUpdate>)>> {
+        self.require(journal_id, authority, Permissions::READ)
+            .await?;
+
+        Ok(sqlx::query!(
+            r#"
+            SELECT payee as "payee!: Name", entries as "entries: TransactionEntries"
+            FROM transactions
+            WHERE journal_id = $1
+                AND payee IS NOT NULL
+                AND ($2::DATE IS NULL OR effective_date >= $2)
+                AND ($3::DATE IS NULL OR effective_date <= $3)
+            "#,
+            journal_id as JournalId,
+            from as Option<NaiveDate>,
+            to as Option<NaiveDate>
+        )
+        .fetch_all(&self.read_pool)
+        .await?
+        .into_iter()
+        .map(|row| (row.payee, row.entries.0))
+        .collect())
+    }
+
+    /// returns the journal's import rules, oldest first, which is the order they're checked in
+    pub async fn list_import_rules(
+        &self,
+        journal_id: JournalId,
+        authority: &Authority,
+    ) -> JournalResult<Vec<RuleState>> {
+        self.require(journal_id, authority, Permissions::READ)
+            .await?;
+
+        Ok(sqlx::query!(
+            r#"
+            SELECT id as "id: RuleId", pattern, min_amount, max_amount, account_id as "account_id: AccountId"
+            FROM import_rules
+            WHERE journal_id = $1
+            ORDER BY position
+            "#,
+            journal_id as JournalId
+        )
+        .fetch_all(&self.read_pool)
+        .await?
+        .into_iter()
+        .map(|rule| RuleState {
+            id: rule.id,
+            condition: RuleCondition {
+                pattern: rule.pattern,
+                min_amount: rule.min_amount.map(|amount| amount as u64),
+                max_amount: rule.max_amount.map(|amount| amount as u64),
+            },
+            account_id: rule.account_id,
+        })
+        .collect())
+    }
+
+    /// returns the journal's transaction templates, oldest first
+    pub async fn list_templates(
+        &self,
+        journal_id: JournalId,
+        authority: &Authority,
+    ) -> JournalResult<Vec<TemplateState>> {
+        self.require(journal_id, authority, Permissions::READ)
+            .await?;
+
+        Ok(sqlx::query!(
+            r#"
+            SELECT id as "id: TemplateId", name as "name: Name", entries as "entries: TransactionEntries"
+            FROM transaction_templates
+            WHERE journal_id = $1
+            ORDER BY position
+            "#,
+            journal_id as JournalId
+        )
+        .fetch_all(&self.read_pool)
+        .await?
+        .into_iter()
+        .map(|template| TemplateState {
+            id: template.id,
+            name: template.name,
+            entries: template.entries.0,
+        })
+        .collect())
+    }
+
+    pub async fn get_template(
+        &self,
+        journal_id: JournalId,
+        template_id: TemplateId,
+        authority: &Authority,
+    ) -> JournalResult<TemplateState> {
+        self.require(journal_id, authority, Permissions::READ)
+            .await?;
+
+        let template = sqlx::query!(
+            r#"
+            SELECT id as "id: TemplateId", name as "name: Name", entries as "entries: TransactionEntries"
+            FROM transaction_templates
+            WHERE id = $1 AND journal_id = $2
+            "#,
+            template_id as TemplateId,
+            journal_id as JournalId
+        )
+        .fetch_optional(&self.read_pool)
+        .await?
+        .ok_or(JournalError::InvalidTemplate(template_id))?;
+
+        Ok(TemplateState {
+            id: template.id,
+            name: template.name,
+            entries: template.entries.0,
+        })
+    }
+
+    pub async fn list_journal_webhooks(
+        &self,
+        journal_id: JournalId,
+        authority: &Authority,
+    ) -> JournalResult<Vec<WebhookState>> {
+        self.require(journal_id, authority, Permissions::OWNER)
+            .await?;
+
+        let webhooks = sqlx::query!(
+            r#"
+            SELECT id as "id: WebhookId", url FROM webhooks WHERE journal_id = $1
+            "#,
+            journal_id as JournalId
+        )
+        .fetch_all(&self.read_pool)
+        .await?;
+
+        let mut states = Vec::with_capacity(webhooks.len());
+        for webhook in webhooks {
+            states.push(WebhookState {
+                secret: self
+                    .secrets
+                    .get(&signing_secret(webhook.id))
+                    .await?
+                    .unwrap_or_default(),
+                id: webhook.id,
+                url: webhook.url,
+            });
+        }
+
+        Ok(states)
+    }
+
+    pub fn secrets(&self) -> &SecretStore {
+        &self.secrets
+    }
+
+    pub async fn list_bank_feeds(
+        &self,
+        journal_id: JournalId,
+        authority: &Authority,
+    ) -> JournalResult<Vec<BankFeedState>> {
+        self.require(journal_id, authority, Permissions::READ)
+            .await?;
+
+        Ok(sqlx::query_as!(
+            BankFeedState,
+            r#"
+            SELECT f.id as "id: BankFeedId", f.provider, f.external_account,
+                f.account_id as "account_id: AccountId", s.last_synced_at as "last_synced_at: Timestamp",
+                (SELECT COUNT(*) FROM bank_feed_lines l
+                    WHERE l.bank_feed_id = f.id AND l.reviewed_at IS NULL) as "pending!"
+            FROM bank_feeds f
+            LEFT JOIN bank_feed_syncs s ON s.bank_feed_id = f.id
+            WHERE f.journal_id = $1
+            ORDER BY f.external_account
+            "#,
+            journal_id as JournalId
+        )
+        .fetch_all(&self.read_pool)
+        .await?)
+    }
+
+    pub async fn get_bank_feed(
+        &self,
+        bank_feed_id: BankFeedId,
+        journal_id: JournalId,
+        authority: &Authority,
+    ) -> JournalResult<BankFeedState> {
+        self.list_bank_feeds(journal_id, authority)
             .await?
-            .contains(Permissions::READ)
-        {
-            return Err(JournalError::InvalidJournal(journal_id));
+            .into_iter()
+            .find(|feed| feed.id == bank_feed_id)
+            .ok_or(JournalError::InvalidBankFeed(bank_feed_id))
+    }
+
+    /// returns the lines pulled from a feed that haven't been sent for review, oldest first
+    pub async fn bank_feed_lines(
+        &self,
+        bank_feed_id: BankFeedId,
+        journal_id: JournalId,
+        authority: &Authority,
+    ) -> JournalResult<Vec<BankFeedLine>> {
+        self.get_bank_feed(bank_feed_id, journal_id, authority)
+            .await?;
+
+        Ok(sqlx::query_as!(
+            BankFeedLine,
+            r#"
+            SELECT external_id, booked as "booked: NaiveDate", amount, description
+            FROM bank_feed_lines
+            WHERE bank_feed_id = $1 AND reviewed_at IS NULL
+            ORDER BY booked, external_id
+            "#,
+            bank_feed_id as BankFeedId
+        )
+        .fetch_all(&self.projection_pool)
+        .await?)
+    }
+
+    /// marks a feed's pending lines as sent for review and returns them, oldest first.
+    /// they become transactions through bulk entry, so this needs the same permission
+    pub async fn take_bank_feed_lines(
+        &self,
+        bank_feed_id: BankFeedId,
+        journal_id: JournalId,
+        authority: &Authority,
+    ) -> JournalResult<Vec<BankFeedLine>> {
+        self.require(journal_id, authority, Permissions::APPEND_TRANSACTION)
+            .await?;
+
+        self.get_bank_feed(bank_feed_id, journal_id, authority)
+            .await?;
+
+        let mut lines = sqlx::query_as!(
+            BankFeedLine,
+            r#"
+            UPDATE bank_feed_lines SET reviewed_at = now()
+            WHERE bank_feed_id = $1 AND reviewed_at IS NULL
+            RETURNING external_id, booked as "booked: NaiveDate", amount, description
+            "#,
+            bank_feed_id as BankFeedId
+        )
+        .fetch_all(&self.projection_pool)
+        .await?;
+
+        lines.sort_by(|a, b| (a.booked, &a.external_id).cmp(&(b.booked, &b.external_id)));
+
+        Ok(lines)
+    }
+
+    /// returns a feed so that a member who can record transactions can pull it right away
+    pub(crate) async fn bank_feed_sync(
+        &self,
+        bank_feed_id: BankFeedId,
+        journal_id: JournalId,
+        authority: &Authority,
+    ) -> JournalResult<BankFeedSync> {
+        self.require(journal_id, authority, Permissions::APPEND_TRANSACTION)
+            .await?;
+
+        sqlx::query_as!(
+            BankFeedSync,
+            r#"
+            SELECT f.id as "id: BankFeedId", f.journal_id as "journal_id: JournalId", f.provider,
+                f.external_account,
+                (SELECT MAX(l.booked) FROM bank_feed_lines l WHERE l.bank_feed_id = f.id) as "last_booked: NaiveDate"
+            FROM bank_feeds f
+            INNER JOIN bank_feed_syncs s ON s.bank_feed_id = f.id
+            WHERE f.id = $1 AND f.journal_id = $2
+            "#,
+            bank_feed_id as BankFeedId,
+            journal_id as JournalId
+        )
+        .fetch_optional(&self.projection_pool)
+        .await?
+        .ok_or(JournalError::InvalidBankFeed(bank_feed_id))
+    }
+
+    /// returns the feeds that haven't been pulled since `synced_before`
+    pub(crate) async fn due_bank_feeds(
+        &self,
+        synced_before: Timestamp,
+    ) -> Result<Vec<BankFeedSync>, sqlx::Error> {
+        sqlx::query_as!(
+            BankFeedSync,
+            r#"
+            SELECT f.id as "id: BankFeedId", f.journal_id as "journal_id: JournalId", f.provider,
+                f.external_account,
+                (SELECT MAX(l.booked) FROM bank_feed_lines l WHERE l.bank_feed_id = f.id) as "last_booked: NaiveDate"
+            FROM bank_feeds f
+            INNER JOIN bank_feed_syncs s ON s.bank_feed_id = f.id
+            INNER JOIN journals j ON j.id = f.journal_id
+            WHERE (s.last_synced_at IS NULL OR s.last_synced_at < $1) AND NOT j.archived
+            "#,
+            synced_before as Timestamp
+        )
+        .fetch_all(&self.projection_pool)
+        .await
+    }
+
+    /// stores the lines a feed returned, skipping ones that were pulled before, and returns
+    /// how many were new
+    pub(crate) async fn record_bank_feed_lines(
+        &self,
+        bank_feed_id: BankFeedId,
+        lines: &[FeedTransaction],
+    ) -> Result<u64, sqlx::Error> {
+        let mut tx = self.projection_pool.begin().await?;
+        let mut recorded = 0;
+
+        for line in lines {
+            recorded += sqlx::query!(
+                r#"
+                INSERT INTO bank_feed_lines (bank_feed_id, external_id, booked, amount, description)
+                VALUES ($1, $2, $3, $4, $5)
+                ON CONFLICT (bank_feed_id, external_id) DO NOTHING
+                "#,
+                bank_feed_id as BankFeedId,
+                line.external_id,
+                line.booked as NaiveDate,
+                line.amount,
+                line.description
+            )
+            .execute(&mut *tx)
+            .await?
+            .rows_affected();
+        }
+
+        sqlx::query!(
+            r#"
+            UPDATE bank_feed_syncs SET last_synced_at = now() WHERE bank_feed_id = $1
+            "#,
+            bank_feed_id as BankFeedId
+        )
+        .execute(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+
+        Ok(recorded)
+    }
+
+    /// writes a delivery of the event to every webhook registered on the journal.
+    /// enqueuing the same event twice is a no-op
+    pub(crate) async fn enqueue_webhook_deliveries(
+        &self,
+        event_id: PgEventId,
+        journal_id: JournalId,
+        body: &[u8],
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query!(
+            r#"
+            INSERT INTO webhook_outbox (webhook_id, event_id, body)
+            SELECT id, $1, $2 FROM webhooks WHERE journal_id = $3
+            ON CONFLICT (webhook_id, event_id) DO NOTHING
+            "#,
+            event_id,
+            body,
+            journal_id as JournalId
+        )
+        .execute(&self.projection_pool)
+        .await?;
+
+        Ok(())
+    }
+
+    /// claims undelivered outbox rows that are due for another attempt, oldest first. a claimed
+    /// row isn't due again until the lease runs out, so every instance can drain the outbox
+    /// without sending a row twice. rows locked by another claim are skipped
+    pub(crate) async fn claim_webhook_deliveries(
+        &self,
+        max_attempts: i32,
+        limit: i64,
+        lease: Duration,
+    ) -> Result<Vec<PendingWebhookDelivery>, sqlx::Error> {
+        let mut deliveries = sqlx::query_as!(
+            PendingWebhookDelivery,
+            r#"
+            UPDATE webhook_outbox o
+            SET next_attempt_at = now() + make_interval(secs => $3)
+            FROM webhooks w
+            WHERE w.id = o.webhook_id
+                AND o.id IN (
+                    SELECT id FROM webhook_outbox
+                    WHERE delivered_at IS NULL AND next_attempt_at <= now() AND attempts < $1
+                    ORDER BY id
+                    LIMIT $2
+                    FOR UPDATE SKIP LOCKED
+                )
+            RETURNING o.id, o.webhook_id as "webhook_id: WebhookId", o.event_id, o.body, o.attempts, w.url
+            "#,
+            max_attempts,
+            limit,
+            lease.as_secs_f64()
+        )
+        .fetch_all(&self.projection_pool)
+        .await?;
+
+        deliveries.sort_by_key(|delivery| delivery.id);
+
+        Ok(deliveries)
+    }
+
+    pub(crate) async fn mark_webhook_delivered(&self, delivery_id: i64) -> Result<(), sqlx::Error> {
+        sqlx::query!(
+            r#"
+            UPDATE webhook_outbox SET delivered_at = now() WHERE id = $1
+            "#,
+            delivery_id
+        )
+        .execute(&self.projection_pool)
+        .await?;
+
+        Ok(())
+    }
+
+    /// records a failed attempt and backs the next one off exponentially
+    pub(crate) async fn mark_webhook_failed(&self, delivery_id: i64) -> Result<(), sqlx::Error> {
+        sqlx::query!(
+            r#"
+            UPDATE webhook_outbox
+            SET attempts = attempts + 1,
+                next_attempt_at = now() + make_interval(secs => power(2, attempts + 1))
+            WHERE id = $1
+            "#,
+            delivery_id
+        )
+        .execute(&self.projection_pool)
+        .await?;
+
+        Ok(())
+    }
+
+    /// returns the name of a journal and everyone who can see it without checking permissions.
+    /// this is only meant for sending notifications
+    pub(crate) async fn notification_recipients(
+        &self,
+        journal_id: JournalId,
+    ) -> Result<Option<(String, Vec<UserId>)>, sqlx::Error> {
+        let Some(name) = sqlx::query_scalar!(
+            r#"
+            SELECT name FROM journals WHERE id = $1
+            "#,
+            journal_id as JournalId
+        )
+        .fetch_optional(&self.projection_pool)
+        .await?
+        else {
+            return Ok(None);
+        };
+
+        let recipients = sqlx::query_scalar!(
+            r#"
+            SELECT owner_id as "user_id!: UserId" FROM journals WHERE id = $1
+            UNION
+            SELECT user_id FROM journal_members WHERE journal_id = $1
+            "#,
+            journal_id as JournalId
+        )
+        .fetch_all(&self.projection_pool)
+        .await?;
+
+        Ok(Some((name, recipients)))
+    }
+
+    /// notifies each recipient of an event. notifying a user of the same event twice is a no-op
+    pub(crate) async fn create_notifications(
+        &self,
+        event_id: PgEventId,
+        journal_id: JournalId,
+        recipients: &[UserId],
+        message: &str,
+        created_at: Timestamp,
+    ) -> Result<(), sqlx::Error> {
+        let mut tx = self.projection_pool.begin().await?;
+
+        for recipient in recipients {
+            sqlx::query!(
+                r#"
+                INSERT INTO notifications (user_id, journal_id, event_id, message, created_at)
+                VALUES ($1, $2, $3, $4, $5)
+                ON CONFLICT (user_id, event_id) DO NOTHING
+                "#,
+                recipient as &UserId,
+                journal_id as JournalId,
+                event_id,
+                message,
+                created_at as Timestamp
+            )
+            .execute(&mut *tx)
+            .await?;
         }
 
-        let accounts = sqlx::query_as!(
-            AccountStateWithPayload,
+        tx.commit().await
+    }
+
+    /// returns a user's most recent notifications, newest first
+    pub async fn list_notifications(
+        &self,
+        user_id: UserId,
+        limit: i64,
+    ) -> JournalResult<Vec<NotificationState>> {
+        Ok(sqlx::query_as!(
+            NotificationState,
+            r#"
+            SELECT id, journal_id as "journal_id: JournalId", message, created_at as "created_at: Timestamp", read_at IS NOT NULL as "read!"
+            FROM notifications
+            WHERE user_id = $1
+            ORDER BY id DESC
+            LIMIT $2
+            "#,
+            user_id as UserId,
+            limit
+        )
+        .fetch_all(&self.read_pool)
+        .await?)
+    }
+
+    pub async fn unread_notification_count(&self, user_id: UserId) -> JournalResult<u64> {
+        let count = sqlx::query_scalar!(
+            r#"
+            SELECT COUNT(*) as "count!" FROM notifications WHERE user_id = $1 AND read_at IS NULL
+            "#,
+            user_id as UserId
+        )
+        .fetch_one(&self.read_pool)
+        .await?;
+
+        Ok(count as u64)
+    }
+
+    /// marks one of the user's notifications as read. other users' notifications are ignored
+    pub async fn mark_notification_read(
+        &self,
+        user_id: UserId,
+        notification_id: i64,
+    ) -> JournalResult<()> {
+        sqlx::query!(
+            r#"
+            UPDATE notifications SET read_at = now()
+            WHERE id = $1 AND user_id = $2 AND read_at IS NULL
+            "#,
+            notification_id,
+            user_id as UserId
+        )
+        .execute(&self.projection_pool)
+        .await?;
+
+        Ok(())
+    }
+
+    pub async fn mark_all_notifications_read(&self, user_id: UserId) -> JournalResult<()> {
+        sqlx::query!(
+            r#"
+            UPDATE notifications SET read_at = now() WHERE user_id = $1 AND read_at IS NULL
+            "#,
+            user_id as UserId
+        )
+        .execute(&self.projection_pool)
+        .await?;
+
+        Ok(())
+    }
+
+    /// returns the last event included in the user's previous digest and when it was sent
+    pub(crate) async fn last_digest(
+        &self,
+        user_id: UserId,
+    ) -> Result<Option<(PgEventId, Timestamp)>, sqlx::Error> {
+        Ok(sqlx::query!(
+            r#"
+            SELECT last_event_id, sent_at as "sent_at: Timestamp" FROM digest_deliveries WHERE user_id = $1
+            "#,
+            user_id as UserId
+        )
+        .fetch_optional(&self.projection_pool)
+        .await?
+        .map(|row| (row.last_event_id, row.sent_at)))
+    }
+
+    pub(crate) async fn record_digest(
+        &self,
+        user_id: UserId,
+        last_event_id: PgEventId,
+        sent_at: Timestamp,
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query!(
+            r#"
+            INSERT INTO digest_deliveries (user_id, last_event_id, sent_at) VALUES ($1, $2, $3)
+            ON CONFLICT (user_id) DO UPDATE SET last_event_id = EXCLUDED.last_event_id, sent_at = EXCLUDED.sent_at
+            "#,
+            user_id as UserId,
+            last_event_id,
+            sent_at as Timestamp
+        )
+        .execute(&self.projection_pool)
+        .await?;
+
+        Ok(())
+    }
+
+    /// queues the user's digest and records how far it covered together, so a digest is
+    /// neither lost nor queued again for the same events
+    pub(crate) async fn queue_digest(
+        &self,
+        user_id: UserId,
+        to: &Email,
+        (subject, body): (&str, &str),
+        last_event_id: PgEventId,
+        sent_at: Timestamp,
+    ) -> Result<(), sqlx::Error> {
+        let mut tx = self.projection_pool.begin().await?;
+
+        queue_email(
+            &mut tx,
+            &format!("digest/{user_id}/{last_event_id}"),
+            to,
+            subject,
+            body,
+        )
+        .await?;
+
+        sqlx::query!(
+            r#"
+            INSERT INTO digest_deliveries (user_id, last_event_id, sent_at) VALUES ($1, $2, $3)
+            ON CONFLICT (user_id) DO UPDATE SET last_event_id = EXCLUDED.last_event_id, sent_at = EXCLUDED.sent_at
+            "#,
+            user_id as UserId,
+            last_event_id,
+            sent_at as Timestamp
+        )
+        .execute(&mut *tx)
+        .await?;
+
+        tx.commit().await
+    }
+
+    /// queues an email for the sender to deliver. queuing the same key twice is a no-op
+    pub(crate) async fn queue_email(
+        &self,
+        key: &str,
+        to: &Email,
+        subject: &str,
+        body: &str,
+    ) -> Result<(), sqlx::Error> {
+        let mut conn = self.projection_pool.acquire().await?;
+        queue_email(&mut conn, key, to, subject, body).await
+    }
+
+    /// claims unsent emails that are due for another attempt, oldest first, the way
+    /// [`Self::claim_webhook_deliveries`] claims deliveries
+    pub(crate) async fn claim_emails(
+        &self,
+        max_attempts: i32,
+        limit: i64,
+        lease: Duration,
+    ) -> Result<Vec<PendingEmail>, sqlx::Error> {
+        let mut emails = sqlx::query_as!(
+            PendingEmail,
+            r#"
+            UPDATE email_outbox
+            SET next_attempt_at = now() + make_interval(secs => $3)
+            WHERE id IN (
+                SELECT id FROM email_outbox
+                WHERE sent_at IS NULL AND next_attempt_at <= now() AND attempts < $1
+                ORDER BY id
+                LIMIT $2
+                FOR UPDATE SKIP LOCKED
+            )
+            RETURNING id, recipient as "recipient: Email", subject, body, attempts
+            "#,
+            max_attempts,
+            limit,
+            lease.as_secs_f64()
+        )
+        .fetch_all(&self.projection_pool)
+        .await?;
+
+        emails.sort_by_key(|email| email.id);
+
+        Ok(emails)
+    }
+
+    pub(crate) async fn mark_email_sent(&self, email_id: i64) -> Result<(), sqlx::Error> {
+        sqlx::query!(
+            r#"
+            UPDATE email_outbox SET sent_at = now() WHERE id = $1
+            "#,
+            email_id
+        )
+        .execute(&self.projection_pool)
+        .await?;
+
+        Ok(())
+    }
+
+    /// records a failed attempt and backs the next one off exponentially
+    pub(crate) async fn mark_email_failed(&self, email_id: i64) -> Result<(), sqlx::Error> {
+        sqlx::query!(
             r#"
-            SELECT a.id as "id: AccountId", a.journal_id as "journal_id: JournalId", a.balance, a.name as "name: Name", e.payload as "payload!"
-            FROM accounts a
+            UPDATE email_outbox
+            SET attempts = attempts + 1,
+                next_attempt_at = now() + make_interval(secs => power(2, attempts + 1))
+            WHERE id = $1
+            "#,
+            email_id
+        )
+        .execute(&self.projection_pool)
+        .await?;
+
+        Ok(())
+    }
+
+    /// returns the entries of the journal's transactions that were created after `after`,
+    /// up to and including `up_to`, without checking permissions
+    pub(crate) async fn transactions_created_between(
+        &self,
+        journal_id: JournalId,
+        after: PgEventId,
+        up_to: PgEventId,
+    ) -> Result<Vec<TransactionEntries>, sqlx::Error> {
+        sqlx::query_scalar!(
+            r#"
+            SELECT t.entries as "entries: TransactionEntries"
+            FROM transactions t
             INNER JOIN event e
-                ON e.account_id = a.id AND e.event_type = 'AccountCreated'
-            WHERE a.journal_id = $1
+                ON e.transaction_id = t.id AND e.event_type = 'TransactionCreated'
+            WHERE t.journal_id = $1 AND e.event_id > $2 AND e.event_id <= $3
             "#,
-            journal_id as JournalId)
-            .fetch_all(&self.projection_pool)
-            .await?;
+            journal_id as JournalId,
+            after,
+            up_to
+        )
+        .fetch_all(&self.projection_pool)
+        .await
+    }
 
-        let mut transactions_with_meta = Vec::with_capacity(accounts.len());
+    /// the last event that changed the journal, or zero if none has
+    pub async fn journal_version(&self, journal_id: JournalId) -> JournalResult<PgEventId> {
+        Ok(sqlx::query_scalar!(
+            r#"
+            SELECT version FROM journal_versions WHERE journal_id = $1
+            "#,
+            journal_id as JournalId
+        )
+        .fetch_optional(&self.projection_pool)
+        .await?
+        .unwrap_or_default())
+    }
 
-        for account in accounts {
-            let payload: JournalDomainEvent = rmp_serde::from_slice(account.payload.as_slice())?;
+    /// drops each journal another instance projects a change to, such as a member's removal,
+    /// from this one's cache. notifications sent while the connection was lost can't be known,
+    /// so everything is dropped once it's back
+    pub(crate) async fn follow_journal_changes(self) {
+        let mut shutdown = Box::pin(shutdown());
 
-            match payload {
-                JournalDomainEvent::AccountCreated {
-                    authority,
-                    timestamp,
-                    ..
-                } => {
-                    transactions_with_meta.push((
-                        AccountState {
-                            id: account.id,
-                            journal_id: account.journal_id,
-                            name: account.name,
-                            balance: account.balance,
-                        },
-                        authority,
-                        timestamp,
-                    ));
+        let mut listener = match PgListener::connect_with(&self.projection_pool).await {
+            Ok(listener) => listener,
+            Err(error) => {
+                tracing::error!(?error, "failed to connect the journal cache's listener");
+                return;
+            }
+        };
+        if let Err(error) = listener.listen(JOURNAL_CHANGED_CHANNEL).await {
+            tracing::error!(?error, "failed to listen for journal changes");
+            return;
+        }
+
+        loop {
+            let notification = tokio::select! {
+                notification = listener.try_recv() => notification,
+                _ = &mut shutdown => return,
+            };
+
+            match notification {
+                Ok(Some(notification)) => match JournalId::from_str(notification.payload()) {
+                    Ok(journal_id) => self.cache.invalidate(journal_id),
+                    Err(error) => tracing::error!(?error, "a journal change named no journal"),
+                },
+                Ok(None) => self.cache.clear(),
+                Err(sqlx::Error::PoolClosed) => return,
+                Err(error) => {
+                    tracing::error!(?error, "lost the journal cache's listener");
+                    self.cache.clear();
+                    tokio::time::sleep(Duration::from_secs(1)).await;
                 }
-                _ => unreachable!("AccountCreated events are filtered by the sql query"),
             }
         }
+    }
 
-        Ok(transactions_with_meta)
+    /// the journals the user has pinned to the top of their journal list
+    pub async fn pinned_journals(&self, user: UserId) -> JournalResult<HashSet<JournalId>> {
+        Ok(sqlx::query_scalar!(
+            r#"
+            SELECT journal_id as "journal_id: JournalId" FROM journal_pins WHERE user_id = $1
+            "#,
+            user as UserId
+        )
+        .fetch_all(&self.projection_pool)
+        .await?
+        .into_iter()
+        .collect())
     }
 
-    pub async fn list_journal_transactions(
-        &self,
-        journal_id: JournalId,
-        authority: &Authority,
-    ) -> JournalResult<Vec<(TransactionState, Authority, Timestamp)>> {
-        if !self
-            .get_effective_permissions(journal_id, authority)
-            .await?
-            .contains(Permissions::READ)
-        {
-            return Err(JournalError::Permissions(Permissions::READ));
-        }
+    /// versions the user's list of journals. adding a journal to the list raises the newest
+    /// version and removing one lowers the count, so any change to the list changes the pair
+    pub async fn journal_list_version(&self, user: UserId) -> JournalResult<(i64, PgEventId)> {
+        let row = sqlx::query!(
+            r#"
+            SELECT COUNT(*) as "count!", MAX(v.version) as version
+            FROM journals j
+            LEFT JOIN journal_versions v ON v.journal_id = j.id
+            WHERE j.owner_id = $1
+                OR j.id IN (SELECT journal_id FROM journal_members WHERE user_id = $1)
+            "#,
+            user as UserId
+        )
+        .fetch_one(&self.projection_pool)
+        .await?;
 
-        let transactions = sqlx::query_as!(
-            TransactionStateWithPayload,
+        Ok((row.count, row.version.unwrap_or_default()))
+    }
+
+    /// the last event the projections have caught up to
+    pub fn current_event_id(&self) -> PgEventId {
+        *self.current_event.borrow()
+    }
+
+    async fn owned_journal_count(&self, owner: UserId) -> JournalResult<u64> {
+        let count = sqlx::query_scalar!(
             r#"
-            SELECT t.id as "id: TransactionId", t.journal_id as "journal_id: JournalId", t.entries as "entries: TransactionEntries", e.payload as "payload!"
-            FROM transactions t
-            INNER JOIN event e
-                ON e.transaction_id = t.id AND e.event_type = 'TransactionCreated'
-            WHERE t.journal_id = $1
+            SELECT COUNT(*) as "count!" FROM journals WHERE owner_id = $1
             "#,
-            journal_id as JournalId)
-            .fetch_all(&self.projection_pool)
+            owner as UserId
+        )
+        .fetch_one(&self.projection_pool)
+        .await?;
+
+        Ok(count as u64)
+    }
+
+    async fn transaction_count(&self, journal_id: JournalId) -> JournalResult<u64> {
+        let count = sqlx::query_scalar!(
+            r#"
+            SELECT COUNT(*) as "count!" FROM transactions WHERE journal_id = $1
+            "#,
+            journal_id as JournalId
+        )
+        .fetch_one(&self.projection_pool)
+        .await?;
+
+        Ok(count as u64)
+    }
+
+    pub async fn quota_usage(&self, user_id: UserId) -> JournalResult<QuotaUsage> {
+        Ok(QuotaUsage {
+            journals: self.owned_journal_count(user_id).await?,
+            quotas: self.quotas,
+        })
+    }
+
+    async fn projection_snapshot(&self) -> Result<ProjectionSnapshot, sqlx::Error> {
+        let journals = sqlx::query!(
+            r#"
+            SELECT id as "id: JournalId", owner_id as "owner_id: UserId", name, fiscal_year_start as "fiscal_year_start: FiscalYearStart", archived, default_cash_account as "default_cash_account: AccountId", default_expense_account as "default_expense_account: AccountId", color as "color: JournalColor", icon as "icon: JournalIcon" FROM journals
+            "#
+        )
+        .fetch_all(&self.projection_pool)
+        .await?
+        .into_iter()
+        .map(|row| {
+            (
+                row.id,
+                JournalSnapshot {
+                    owner_id: row.owner_id,
+                    name: row.name,
+                    fiscal_year_start: row.fiscal_year_start,
+                    archived: row.archived,
+                    default_cash_account: row.default_cash_account,
+                    default_expense_account: row.default_expense_account,
+                    appearance: JournalAppearance {
+                        color: row.color,
+                        icon: row.icon,
+                    },
+                },
+            )
+        })
+        .collect();
+
+        let members = sqlx::query!(
+            r#"
+            SELECT journal_id as "journal_id: JournalId", user_id as "user_id: UserId", permissions FROM journal_members
+            "#
+        )
+        .fetch_all(&self.projection_pool)
+        .await?
+        .into_iter()
+        .map(|row| ((row.journal_id, row.user_id), row.permissions))
+        .collect();
+
+        let accounts = sqlx::query!(
+            r#"
+            SELECT id as "id: AccountId", name, balance FROM accounts
+            "#
+        )
+        .fetch_all(&self.projection_pool)
+        .await?
+        .into_iter()
+        .map(|row| {
+            (
+                row.id,
+                AccountSnapshot {
+                    name: row.name,
+                    balance: row.balance,
+                },
+            )
+        })
+        .collect();
+
+        let transactions = sqlx::query!(
+            r#"
+            SELECT id as "id: TransactionId", effective_date as "effective_date: NaiveDate" FROM transactions
+            "#
+        )
+        .fetch_all(&self.projection_pool)
+        .await?
+        .into_iter()
+        .map(|row| (row.id, row.effective_date))
+        .collect();
+
+        let period_totals = sqlx::query!(
+            r#"
+            SELECT account_id as "account_id: AccountId", period as "period: NaiveDate", debit_total, credit_total FROM account_period_totals
+            "#
+        )
+        .fetch_all(&self.projection_pool)
+        .await?
+        .into_iter()
+        .map(|row| {
+            (
+                (row.account_id, row.period),
+                (row.debit_total, row.credit_total),
+            )
+        })
+        .collect();
+
+        Ok(ProjectionSnapshot {
+            journals,
+            members,
+            accounts,
+            transactions,
+            period_totals,
+        })
+    }
+
+    /// compares every account's balance against the sum of its transactions, correcting the
+    /// balance if `repair` is set. the accounts and transactions are read from one snapshot,
+    /// so transactions projected while this runs can't look like drift.
+    ///
+    /// pages cached by their etag show a repaired balance after the journal's next event
+    pub async fn check_balances(&self, repair: bool) -> JournalResult<Vec<Inconsistency>> {
+        let mut tx = self.projection_pool.begin().await?;
+
+        sqlx::query!("SET TRANSACTION ISOLATION LEVEL REPEATABLE READ")
+            .execute(&mut *tx)
             .await?;
 
-        let mut transactions_with_meta = Vec::with_capacity(transactions.len());
+        let balances = sqlx::query!(
+            r#"
+            SELECT id as "id: AccountId", balance FROM accounts
+            "#
+        )
+        .fetch_all(&mut *tx)
+        .await?;
 
-        for transaction in transactions {
-            let payload: JournalDomainEvent =
-                rmp_serde::from_slice(transaction.payload.as_slice())?;
+        let entries = sqlx::query_scalar!(
+            r#"
+            SELECT entries as "entries: TransactionEntries" FROM transactions
+            "#
+        )
+        .fetch_all(&mut *tx)
+        .await?;
 
-            match payload {
-                JournalDomainEvent::TransactionCreated {
-                    authority,
-                    timestamp,
-                    ..
-                } => {
-                    transactions_with_meta.push((
-                        TransactionState {
-                            id: transaction.id,
-                            journal_id: transaction.journal_id,
-                            entries: transaction.entries.0,
-                        },
-                        authority,
-                        timestamp,
-                    ));
+        let recomputed = consistency::recompute_balances(entries.iter().flat_map(|e| e.0.iter()));
+        let drift = consistency::balance_drift(
+            balances.into_iter().map(|row| (row.id, row.balance)),
+            &recomputed,
+        );
+
+        if repair {
+            for inconsistency in drift.iter() {
+                if let Inconsistency::BalanceMismatch {
+                    account_id,
+                    cached,
+                    rebuilt,
+                } = inconsistency
+                {
+                    // a concurrent balance update makes the snapshot stale, so this fails the
+                    // transaction rather than overwriting it and the next check tries again
+                    sqlx::query!(
+                        r#"
+                        UPDATE accounts SET balance = $1 WHERE id = $2 AND balance = $3
+                        "#,
+                        rebuilt,
+                        *account_id as AccountId,
+                        cached
+                    )
+                    .execute(&mut *tx)
+                    .await?;
                 }
-                _ => unreachable!("TransactionCreated events are filtered by the sql query"),
             }
         }
 
-        Ok(transactions_with_meta)
-    }
+        tx.commit().await?;
+
+        Ok(drift)
+    }
+
+    /// wipes the journal projections and replays every journal event into them, reporting
+    /// where the old projections had drifted. the projections are empty while this runs,
+    /// so it should only be run while the server is stopped
+    pub async fn rebuild_projections(
+        &self,
+        event_store: &PgJournalEventStore,
+    ) -> Result<RebuildReport, RebuildError> {
+        let cached = self.projection_snapshot().await?;
+
+        // the webhook outbox, notifications, secrets, and bank feed syncs and lines are delivery
+        // state rather than projections, so they're left alone
+        sqlx::query!(
+            r#"
+            TRUNCATE journals, journal_members, accounts, transactions, pending_transactions, transaction_comments, account_period_totals, webhooks, import_rules, transaction_templates, bank_feeds, journal_versions, journal_pins, deleted_journals
+            "#
+        )
+        .execute(&self.projection_pool)
+        .await?;
+
+        self.cache.clear();
+
+        let mut events_replayed = 0;
+        let mut last_event_id = None;
+
+        let mut events = event_store.stream(&self.query);
+        while let Some(item) = events.next().await {
+            if let StreamItem::Event(event) = item? {
+                last_event_id = Some(event.id());
+                self.handle(event).await?;
+                events_replayed += 1;
+            }
+        }
+
+        // move the listener past the replayed events so that it doesn't handle them again
+        if let Some(last_event_id) = last_event_id {
+            sqlx::query!(
+                r#"
+                INSERT INTO event_listener (id, last_processed_event_id) VALUES($1, $2)
+                ON CONFLICT (id) DO UPDATE SET last_processed_event_id = $2, updated_at = now()
+                "#,
+                LISTENER_ID,
+                last_event_id
+            )
+            .execute(&self.projection_pool)
+            .await?;
+        }
+
+        let rebuilt = self.projection_snapshot().await?;
+
+        Ok(RebuildReport {
+            events_replayed,
+            inconsistencies: cached.diff(&rebuilt),
+        })
+    }
+
+    /// the journal an event changes, which is read from the projections for events that
+    /// only record their account or transaction
+    async fn changed_journal(
+        &self,
+        event: &JournalDomainEvent,
+    ) -> Result<Option<JournalId>, sqlx::Error> {
+        if let Some(journal_id) = event.journal_id() {
+            return Ok(Some(journal_id));
+        }
+
+        match event {
+            JournalDomainEvent::AccountRenamed { account_id, .. }
+            | JournalDomainEvent::AccountDeleted { account_id, .. } => {
+                sqlx::query_scalar!(
+                    r#"
+                    SELECT journal_id as "journal_id: JournalId" FROM accounts WHERE id = $1
+                    "#,
+                    account_id as &AccountId
+                )
+                .fetch_optional(&self.projection_pool)
+                .await
+            }
+            JournalDomainEvent::TransactionDeleted { transaction_id, .. } => {
+                sqlx::query_scalar!(
+                    r#"
+                    SELECT journal_id as "journal_id: JournalId" FROM transactions WHERE id = $1
+                    "#,
+                    transaction_id as &TransactionId
+                )
+                .fetch_optional(&self.projection_pool)
+                .await
+            }
+            _ => Ok(None),
+        }
+    }
+
+    fn processed(&self, event_id: PgEventId) -> Result<(), sqlx::Error> {
+        self.current_event
+            .send(event_id)
+            .expect("journal eventid sender closed");
+
+        Ok(())
+    }
+
+    pub async fn wait_for(&self, event_id: PgEventId) {
+        self.current_event
+            .subscribe()
+            .wait_for(|curr_id| *curr_id >= event_id)
+            .await
+            .expect("journal service eventid sender closed");
+    }
+
+    /// the last event projected by any instance's listener. unlike `wait_for`, this sees
+    /// events projected by another process
+    pub async fn projected_through(&self) -> JournalResult<PgEventId> {
+        Ok(sqlx::query_scalar!(
+            r#"
+            SELECT last_processed_event_id FROM event_listener WHERE id = $1
+            "#,
+            LISTENER_ID
+        )
+        .fetch_optional(&self.projection_pool)
+        .await?
+        .flatten()
+        .unwrap_or_default())
+    }
+
+    /// whether any event recorded on this instance names one of the ids, as a journal,
+    /// account, transaction, comment, template or import rule
+    pub async fn ids_in_use(&self, ids: &[String]) -> JournalResult<bool> {
+        Ok(sqlx::query_scalar!(
+            r#"
+            SELECT EXISTS(
+                SELECT 1 FROM event
+                WHERE journal_id = ANY($1) OR account_id = ANY($1) OR transaction_id = ANY($1)
+                    OR comment_id = ANY($1) OR template_id = ANY($1) OR rule_id = ANY($1)
+            ) as "in_use!"
+            "#,
+            ids
+        )
+        .fetch_one(&self.projection_pool)
+        .await?)
+    }
+}
+
+async fn queue_email(
+    conn: &mut PgConnection,
+    key: &str,
+    to: &Email,
+    subject: &str,
+    body: &str,
+) -> Result<(), sqlx::Error> {
+    sqlx::query!(
+        r#"
+        INSERT INTO email_outbox (message_key, recipient, subject, body) VALUES ($1, $2, $3, $4)
+        ON CONFLICT (message_key) DO NOTHING
+        "#,
+        key,
+        to as &Email,
+        subject,
+        body
+    )
+    .execute(conn)
+    .await?;
+
+    Ok(())
+}
+
+/// dates the transactions that were projected before they had effective dates, and records
+/// who created them, both read from their events
+async fn backfill_transaction_dates(
+    pool: &PgPool,
+    payloads: &JournalPayloads,
+) -> Result<(), sqlx::Error> {
+    let undated = sqlx::query!(
+        r#"
+        SELECT t.id as "id: TransactionId", e.payload as "payload!"
+        FROM transactions t
+        INNER JOIN event e ON e.transaction_id = t.id AND e.event_type = 'TransactionCreated'
+        WHERE t.effective_date IS NULL
+        "#
+    )
+    .fetch_all(pool)
+    .await?;
+
+    for transaction in undated {
+        let event = payloads
+            .decode(&transaction.payload)
+            .map_err(|e| sqlx::Error::Decode(Box::new(e)))?;
+
+        if let JournalDomainEvent::TransactionCreated {
+            effective_date,
+            authority,
+            ..
+        } = event
+        {
+            sqlx::query!(
+                r#"
+                UPDATE transactions SET effective_date = $2, author_id = $3 WHERE id = $1
+                "#,
+                transaction.id as TransactionId,
+                effective_date as NaiveDate,
+                authority.user_id() as Option<UserId>
+            )
+            .execute(pool)
+            .await?;
+        }
+    }
+
+    sqlx::query!(
+        r#"
+        ALTER TABLE transactions ALTER COLUMN effective_date SET NOT NULL
+        "#
+    )
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
+
+/// numbers the transactions that were projected before they had voucher numbers. they count
+/// up in the order they were recorded, with deleted ones taking their numbers along, the same
+/// as in the decisions and the projection
+async fn backfill_transaction_numbers(pool: &PgPool) -> Result<(), sqlx::Error> {
+    sqlx::query!(
+        r#"
+        UPDATE transactions t SET number = numbered.number
+        FROM (
+            SELECT transaction_id, row_number() OVER (PARTITION BY journal_id ORDER BY event_id) as number
+            FROM event
+            WHERE event_type = 'TransactionCreated'
+        ) numbered
+        WHERE t.id = numbered.transaction_id AND t.number IS NULL
+        "#
+    )
+    .execute(pool)
+    .await?;
+
+    sqlx::query!(
+        r#"
+        ALTER TABLE transactions ALTER COLUMN number SET NOT NULL
+        "#
+    )
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
 
-    pub async fn wait_for(&self, event_id: PgEventId) {
-        self.current_event
-            .subscribe()
-            .wait_for(|curr_id| *curr_id >= event_id)
-            .await
-            .expect("journal service eventid sender closed");
+/// adds the balance updates to their accounts' totals for the period of the effective date,
+/// or takes them away again when `sign` is -1
+async fn add_period_totals(
+    conn: &mut PgConnection,
+    journal_id: JournalId,
+    effective_date: NaiveDate,
+    balance_updates: &[BalanceUpdate],
+    sign: i64,
+) -> Result<(), sqlx::Error> {
+    let period = fiscal::period_start(effective_date);
+
+    for update in balance_updates {
+        let (debit, credit) = match update.entry_type {
+            EntryType::Debit => (update.amount as i64 * sign, 0),
+            EntryType::Credit => (0, update.amount as i64 * sign),
+        };
+
+        sqlx::query!(
+            r#"
+            INSERT INTO account_period_totals (account_id, journal_id, period, debit_total, credit_total)
+            VALUES ($1, $2, $3, $4, $5)
+            ON CONFLICT (account_id, period) DO UPDATE SET
+                debit_total = account_period_totals.debit_total + EXCLUDED.debit_total,
+                credit_total = account_period_totals.credit_total + EXCLUDED.credit_total
+            "#,
+            update.account_id as AccountId,
+            journal_id as JournalId,
+            period as NaiveDate,
+            debit,
+            credit
+        )
+        .execute(&mut *conn)
+        .await?;
     }
+
+    Ok(())
 }
 
 #[async_trait]
@@ -556,213 +4148,927 @@ impl EventListener<PgEventId, JournalDomainEvent> for JournalService {
     type Error = sqlx::Error;
 
     fn id(&self) -> &'static str {
-        "journal store"
+        LISTENER_ID
     }
 
     fn query(&self) -> &StreamQuery<PgEventId, JournalDomainEvent> {
         &self.query
     }
 
+    #[tracing::instrument(
+        skip_all,
+        fields(
+            event_id = event.id(),
+            event_type = event.name(),
+            actor = %event.authority().actor(),
+            journal_id = tracing::field::Empty,
+        )
+    )]
     async fn handle(
         &self,
         event: PersistedEvent<PgEventId, JournalDomainEvent>,
     ) -> Result<(), Self::Error> {
         let event_id = event.id();
-        match event.into_inner() {
+        let event = event.into_inner();
+
+        // looked up before the event is applied, since deletes remove the rows it reads
+        let changed_journal = self.changed_journal(&event).await?;
+        if let Some(journal_id) = changed_journal {
+            tracing::Span::current().record("journal_id", tracing::field::display(journal_id));
+        }
+
+        // a pin only changes one user's list, so it isn't activity in the journal
+        let last_activity = (!matches!(
+            event,
+            JournalDomainEvent::JournalPinned { .. } | JournalDomainEvent::JournalUnpinned { .. }
+        ))
+        .then(|| event.timestamp());
+
+        match event {
             JournalDomainEvent::JournalCreated {
                 journal_id,
-                owner,
-                name,
+                owner,
+                name,
+                ..
+            } => {
+                sqlx::query!(
+                    r#"
+                    INSERT INTO journals (id, owner_id, name) VALUES($1, $2, $3) ON CONFLICT DO NOTHING
+                    "#,
+                    journal_id as JournalId,
+                    owner as UserId,
+                    name as Name
+                )
+                .execute(&self.projection_pool)
+                .await?;
+            }
+            JournalDomainEvent::JournalSettingsUpdated {
+                journal_id,
+                fiscal_year_start,
+                default_cash_account,
+                default_expense_account,
+                appearance,
+                ..
+            } => {
+                sqlx::query!(
+                    r#"
+                    UPDATE journals SET fiscal_year_start = $2, default_cash_account = $3, default_expense_account = $4, color = $5, icon = $6 WHERE id = $1
+                    "#,
+                    journal_id as JournalId,
+                    fiscal_year_start as FiscalYearStart,
+                    default_cash_account as Option<AccountId>,
+                    default_expense_account as Option<AccountId>,
+                    appearance.color as JournalColor,
+                    appearance.icon as Option<JournalIcon>
+                )
+                .execute(&self.projection_pool)
+                .await?;
+            }
+            JournalDomainEvent::JournalArchived { journal_id, .. }
+            | JournalDomainEvent::JournalUnarchived { journal_id, .. } => {
+                sqlx::query!(
+                    r#"
+                    UPDATE journals SET archived = $2 WHERE id = $1
+                    "#,
+                    journal_id as JournalId,
+                    matches!(event, JournalDomainEvent::JournalArchived { .. })
+                )
+                .execute(&self.projection_pool)
+                .await?;
+            }
+            JournalDomainEvent::JournalPinned {
+                journal_id,
+                user_id,
+                ..
+            } => {
+                sqlx::query!(
+                    r#"
+                    INSERT INTO journal_pins (journal_id, user_id) VALUES ($1, $2) ON CONFLICT DO NOTHING
+                    "#,
+                    journal_id as JournalId,
+                    user_id as UserId
+                )
+                .execute(&self.projection_pool)
+                .await?;
+            }
+            JournalDomainEvent::JournalUnpinned {
+                journal_id,
+                user_id,
+                ..
+            } => {
+                sqlx::query!(
+                    r#"
+                    DELETE FROM journal_pins WHERE journal_id = $1 AND user_id = $2
+                    "#,
+                    journal_id as JournalId,
+                    user_id as UserId
+                )
+                .execute(&self.projection_pool)
+                .await?;
+            }
+            JournalDomainEvent::JournalDeleted {
+                journal_id,
+                timestamp,
+                ..
+            } => {
+                sqlx::query!(
+                    r#"
+                    DELETE FROM journals where id = $1
+                    "#,
+                    journal_id as JournalId
+                )
+                .execute(&self.projection_pool)
+                .await?;
+
+                sqlx::query!(
+                    r#"
+                    INSERT INTO deleted_journals (journal_id, deleted_at) VALUES ($1, $2)
+                    ON CONFLICT (journal_id) DO NOTHING
+                    "#,
+                    journal_id as JournalId,
+                    timestamp as Timestamp
+                )
+                .execute(&self.projection_pool)
+                .await?;
+            }
+            JournalDomainEvent::MemberAdded {
+                journal_id,
+                user_id,
+                permissions,
+                ..
+            } => {
+                sqlx::query!(
+                    r#"
+                    INSERT INTO journal_members (user_id, journal_id, permissions) VALUES($1, $2, $3) ON CONFLICT DO NOTHING
+                    "#,
+                    user_id as UserId,
+                    journal_id as JournalId,
+                    permissions as Permissions
+                    )
+                    .execute(&self.projection_pool)
+                    .await?;
+            }
+            JournalDomainEvent::MemberPermissionsUpdated {
+                journal_id,
+                user_id,
+                permissions,
+                ..
+            } => {
+                sqlx::query!(
+                    r#"
+                    UPDATE journal_members SET permissions = $1 WHERE user_id = $2 AND journal_id = $3
+                    "#,
+                    permissions as Permissions,
+                    user_id as UserId,
+                    journal_id as JournalId,
+                    )
+                    .execute(&self.projection_pool)
+                    .await?;
+            }
+            JournalDomainEvent::MemberRemoved {
+                journal_id,
+                user_id,
+                ..
+            } => {
+                sqlx::query!(
+                    r#"
+                    DELETE FROM journal_members WHERE user_id = $1 AND journal_id = $2
+                    "#,
+                    user_id as UserId,
+                    journal_id as JournalId,
+                )
+                .execute(&self.projection_pool)
+                .await?;
+
+                sqlx::query!(
+                    r#"
+                    DELETE FROM journal_pins WHERE user_id = $1 AND journal_id = $2
+                    "#,
+                    user_id as UserId,
+                    journal_id as JournalId,
+                )
+                .execute(&self.projection_pool)
+                .await?;
+            }
+            JournalDomainEvent::AccountCreated {
+                account_id,
+                journal_id,
+                name,
+                ..
+            } => {
+                sqlx::query!(
+                    r#"
+                    INSERT INTO accounts (id, journal_id, name, balance) VALUES($1, $2, $3, 0) ON CONFLICT DO NOTHING
+                    "#,
+                    account_id as AccountId,
+                    journal_id as JournalId,
+                    name as Name
+                )
+                .execute(&self.projection_pool)
+                .await?;
+            }
+            JournalDomainEvent::AccountRenamed {
+                account_id,
+                new_name,
+                ..
+            } => {
+                sqlx::query!(
+                    r#"
+                    UPDATE accounts SET name = $1 WHERE id = $2
+                    "#,
+                    new_name as Name,
+                    account_id as AccountId,
+                )
+                .execute(&self.projection_pool)
+                .await?;
+            }
+            JournalDomainEvent::AccountDeleted { account_id, .. } => {
+                sqlx::query!(
+                    r#"
+                    DELETE FROM accounts WHERE id = $1
+                    "#,
+                    account_id as AccountId,
+                )
+                .execute(&self.projection_pool)
+                .await?;
+            }
+            JournalDomainEvent::AccountsArranged {
+                journal_id,
+                arrangement,
+                ..
+            } => {
+                let mut tx = self.projection_pool.begin().await?;
+
+                // accounts the arrangement leaves out go back to being listed by name
+                sqlx::query!(
+                    r#"
+                    UPDATE accounts SET position = NULL, group_name = NULL WHERE journal_id = $1
+                    "#,
+                    journal_id as JournalId,
+                )
+                .execute(&mut *tx)
+                .await?;
+
+                for (position, placement) in arrangement.iter().enumerate() {
+                    sqlx::query!(
+                        r#"
+                        UPDATE accounts SET position = $1, group_name = $2 WHERE id = $3 AND journal_id = $4
+                        "#,
+                        position as i32,
+                        placement.group.clone() as Option<Name>,
+                        placement.account_id as AccountId,
+                        journal_id as JournalId,
+                    )
+                    .execute(&mut *tx)
+                    .await?;
+                }
+
+                tx.commit().await?;
+            }
+            JournalDomainEvent::TransactionCreated {
+                transaction_id,
+                journal_id,
+                number,
+                balance_updates,
+                effective_date,
+                reverses,
+                payee,
+                authority,
+                ..
+            } => {
+                let mut tx = self.projection_pool.begin().await?;
+
+                // an unnumbered transaction counts every transaction the journal recorded up to
+                // it, deleted or not, as it does in the decisions and the backfill
+                let inserted = sqlx::query!(
+                    r#"
+                    INSERT INTO transactions (id, journal_id, number, entries, effective_date, reverses, author_id, payee)
+                    VALUES($1, $2, CASE WHEN $3::BIGINT = 0 THEN (SELECT count(*) FROM event WHERE journal_id = $2 AND event_type = 'TransactionCreated' AND event_id <= $9) ELSE $3 END, $4, $5, $6, $7, $8)
+                    ON CONFLICT DO NOTHING
+                    "#,
+                    transaction_id as TransactionId,
+                    journal_id as JournalId,
+                    number as i64,
+                    TransactionEntries(balance_updates.clone()) as TransactionEntries,
+                    effective_date as NaiveDate,
+                    reverses as Option<TransactionId>,
+                    authority.user_id() as Option<UserId>,
+                    payee as Option<Name>,
+                    event_id
+                )
+                .execute(&mut *tx)
+                .await?
+                .rows_affected()
+                    > 0;
+
+                // a replayed event must not apply its balance updates twice
+                if !inserted {
+                    return self.processed(event_id);
+                }
+
+                // apply the balance updates to each account
+                for update in balance_updates.iter() {
+                    sqlx::query!(
+                        r#"
+                        UPDATE accounts SET balance = balance + $1 WHERE id = $2
+                        "#,
+                        update.signed_amount(),
+                        update.account_id as AccountId
+                    )
+                    .execute(&mut *tx)
+                    .await?;
+                }
+
+                add_period_totals(&mut tx, journal_id, effective_date, &balance_updates, 1).await?;
+
+                tx.commit().await?;
+            }
+            JournalDomainEvent::TransactionSubmitted {
+                transaction_id,
+                journal_id,
+                balance_updates,
+                effective_date,
                 ..
             } => {
                 sqlx::query!(
                     r#"
-                    INSERT INTO journals (id, owner_id, name) VALUES($1, $2, $3) ON CONFLICT DO NOTHING
+                    INSERT INTO pending_transactions (id, journal_id, entries, effective_date)
+                    VALUES ($1, $2, $3, $4) ON CONFLICT DO NOTHING
                     "#,
+                    transaction_id as TransactionId,
                     journal_id as JournalId,
-                    owner as UserId,
-                    name as Name
+                    TransactionEntries(balance_updates) as TransactionEntries,
+                    effective_date as NaiveDate
                 )
                 .execute(&self.projection_pool)
                 .await?;
             }
-            JournalDomainEvent::JournalDeleted { journal_id, .. } => {
+            JournalDomainEvent::CommentAdded {
+                comment_id,
+                transaction_id,
+                journal_id,
+                parent_id,
+                body,
+                ..
+            } => {
                 sqlx::query!(
                     r#"
-                    DELETE FROM journals where id = $1
+                    INSERT INTO transaction_comments (id, journal_id, transaction_id, parent_id, body)
+                    VALUES ($1, $2, $3, $4, $5) ON CONFLICT DO NOTHING
                     "#,
-                    journal_id as JournalId
+                    comment_id as CommentId,
+                    journal_id as JournalId,
+                    transaction_id as TransactionId,
+                    parent_id as Option<CommentId>,
+                    body
                 )
                 .execute(&self.projection_pool)
                 .await?;
             }
-            JournalDomainEvent::MemberAdded {
-                journal_id,
-                user_id,
-                permissions,
-                ..
-            } => {
+            // the approved transaction is projected from the `TransactionCreated` that follows
+            JournalDomainEvent::TransactionApproved { transaction_id, .. }
+            | JournalDomainEvent::TransactionRejected { transaction_id, .. } => {
                 sqlx::query!(
                     r#"
-                    INSERT INTO journal_members (user_id, journal_id, permissions) VALUES($1, $2, $3) ON CONFLICT DO NOTHING
+                    DELETE FROM pending_transactions WHERE id = $1
                     "#,
-                    user_id as UserId,
-                    journal_id as JournalId,
-                    permissions as Permissions
+                    transaction_id as TransactionId
+                )
+                .execute(&self.projection_pool)
+                .await?;
+            }
+            JournalDomainEvent::TransactionDeleted { transaction_id, .. } => {
+                let mut tx = self.projection_pool.begin().await?;
+
+                let deleted = sqlx::query!(
+                    r#"
+                    DELETE FROM transactions WHERE id = $1
+                    RETURNING journal_id as "journal_id: JournalId", entries as "entries: TransactionEntries", effective_date as "effective_date: NaiveDate"
+                    "#,
+                    transaction_id as TransactionId,
                     )
-                    .execute(&self.projection_pool)
+                    .fetch_optional(&mut *tx)
+                    .await?;
+
+                // a replayed event must not revert the balance updates twice
+                let Some(deleted) = deleted else {
+                    return self.processed(event_id);
+                };
+
+                add_period_totals(
+                    &mut tx,
+                    deleted.journal_id,
+                    deleted.effective_date,
+                    &deleted.entries.0,
+                    -1,
+                )
+                .await?;
+
+                // revert the transaction's balance updates
+                for update in deleted.entries.0 {
+                    sqlx::query!(
+                        r#"
+                        UPDATE accounts SET balance = balance - $1 WHERE id = $2
+                        "#,
+                        update.signed_amount(),
+                        update.account_id as AccountId
+                    )
+                    .execute(&mut *tx)
                     .await?;
+                }
+                tx.commit().await?;
             }
-            JournalDomainEvent::MemberPermissionsUpdated {
+            JournalDomainEvent::WebhookAdded {
+                webhook_id,
                 journal_id,
-                user_id,
-                permissions,
+                url,
                 ..
             } => {
                 sqlx::query!(
                     r#"
-                    UPDATE journal_members SET permissions = $1 WHERE user_id = $2 AND journal_id = $3
+                    INSERT INTO webhooks (id, journal_id, url) VALUES($1, $2, $3) ON CONFLICT DO NOTHING
                     "#,
-                    user_id as UserId,
+                    webhook_id as WebhookId,
                     journal_id as JournalId,
-                    permissions as Permissions
-                    )
-                    .execute(&self.projection_pool)
-                    .await?;
+                    url
+                )
+                .execute(&self.projection_pool)
+                .await?;
             }
-            JournalDomainEvent::MemberRemoved {
+            JournalDomainEvent::WebhookRemoved { webhook_id, .. } => {
+                let mut tx = self.projection_pool.begin().await?;
+
+                sqlx::query!(
+                    r#"
+                    DELETE FROM webhooks WHERE id = $1
+                    "#,
+                    webhook_id as WebhookId
+                )
+                .execute(&mut *tx)
+                .await?;
+
+                // pending deliveries to a removed webhook are dropped
+                sqlx::query!(
+                    r#"
+                    DELETE FROM webhook_outbox WHERE webhook_id = $1 AND delivered_at IS NULL
+                    "#,
+                    webhook_id as WebhookId
+                )
+                .execute(&mut *tx)
+                .await?;
+
+                tx.commit().await?;
+
+                self.secrets.remove(&signing_secret(webhook_id)).await?;
+            }
+            JournalDomainEvent::ImportRuleAdded {
+                rule_id,
                 journal_id,
-                user_id,
+                condition,
+                account_id,
                 ..
             } => {
+                // the event id orders the rules, since the first one that matches is applied
                 sqlx::query!(
                     r#"
-                    DELETE FROM journal_members WHERE user_id = $1 AND journal_id = $2
+                    INSERT INTO import_rules (id, journal_id, pattern, min_amount, max_amount, account_id, position)
+                    VALUES ($1, $2, $3, $4, $5, $6, $7) ON CONFLICT DO NOTHING
                     "#,
-                    user_id as UserId,
+                    rule_id as RuleId,
                     journal_id as JournalId,
+                    condition.pattern,
+                    condition.min_amount.map(|amount| amount as i64),
+                    condition.max_amount.map(|amount| amount as i64),
+                    account_id as AccountId,
+                    event_id
                 )
                 .execute(&self.projection_pool)
                 .await?;
             }
-            JournalDomainEvent::AccountCreated {
-                account_id,
-                journal_id,
-                name,
-                ..
-            } => {
+            JournalDomainEvent::ImportRuleRemoved { rule_id, .. } => {
                 sqlx::query!(
                     r#"
-                    INSERT INTO accounts (id, journal_id, name, balance) VALUES($1, $2, $3, 0) ON CONFLICT DO NOTHING
+                    DELETE FROM import_rules WHERE id = $1
                     "#,
-                    account_id as AccountId,
-                    journal_id as JournalId,
-                    name as Name
+                    rule_id as RuleId
                 )
                 .execute(&self.projection_pool)
                 .await?;
             }
-            JournalDomainEvent::AccountRenamed {
-                account_id,
-                new_name,
+            JournalDomainEvent::TemplateSaved {
+                template_id,
+                journal_id,
+                name,
+                entries,
                 ..
             } => {
                 sqlx::query!(
                     r#"
-                    UPDATE accounts SET name = $1 WHERE id = $2
+                    INSERT INTO transaction_templates (id, journal_id, name, entries, position)
+                    VALUES ($1, $2, $3, $4, $5) ON CONFLICT DO NOTHING
                     "#,
-                    new_name as Name,
-                    account_id as AccountId,
+                    template_id as TemplateId,
+                    journal_id as JournalId,
+                    name as Name,
+                    TransactionEntries(entries) as TransactionEntries,
+                    event_id
                 )
                 .execute(&self.projection_pool)
                 .await?;
             }
-            JournalDomainEvent::AccountDeleted { account_id, .. } => {
+            JournalDomainEvent::TemplateDeleted { template_id, .. } => {
                 sqlx::query!(
                     r#"
-                    DELETE FROM accounts WHERE id = $1
+                    DELETE FROM transaction_templates WHERE id = $1
                     "#,
-                    account_id as AccountId,
+                    template_id as TemplateId
                 )
                 .execute(&self.projection_pool)
                 .await?;
             }
-            JournalDomainEvent::TransactionCreated {
-                transaction_id,
+            JournalDomainEvent::BankFeedLinked {
+                bank_feed_id,
                 journal_id,
-                balance_updates,
+                provider,
+                external_account,
+                account_id,
                 ..
             } => {
+                sqlx::query!(
+                    r#"
+                    INSERT INTO bank_feeds (id, journal_id, provider, external_account, account_id)
+                    VALUES ($1, $2, $3, $4, $5) ON CONFLICT DO NOTHING
+                    "#,
+                    bank_feed_id as BankFeedId,
+                    journal_id as JournalId,
+                    provider,
+                    external_account,
+                    account_id as AccountId
+                )
+                .execute(&self.projection_pool)
+                .await?;
+            }
+            JournalDomainEvent::BankFeedUnlinked { bank_feed_id, .. } => {
                 let mut tx = self.projection_pool.begin().await?;
 
                 sqlx::query!(
                     r#"
-                    INSERT INTO transactions (id, journal_id, entries) VALUES($1, $2, $3) ON CONFLICT DO NOTHING
+                    DELETE FROM bank_feeds WHERE id = $1
                     "#,
-                    transaction_id as TransactionId,
-                    journal_id as JournalId,
-                    TransactionEntries(balance_updates.clone()) as TransactionEntries
+                    bank_feed_id as BankFeedId
                 )
                 .execute(&mut *tx)
                 .await?;
 
-                // apply the balance updates to each account
-                for update in balance_updates {
-                    let update_amt = match update.entry_type {
-                        EntryType::Credit => update.amount as i64,
-                        EntryType::Debit => -(update.amount as i64),
-                    };
+                // an unlinked feed's credentials and unreviewed lines go with it
+                sqlx::query!(
+                    r#"
+                    DELETE FROM bank_feed_syncs WHERE bank_feed_id = $1
+                    "#,
+                    bank_feed_id as BankFeedId
+                )
+                .execute(&mut *tx)
+                .await?;
 
-                    sqlx::query!(
-                        r#"
-                        UPDATE accounts SET balance = balance + $1 WHERE id = $2
-                        "#,
-                        update_amt,
-                        update.account_id as AccountId
-                    )
-                    .execute(&mut *tx)
-                    .await?;
-                }
+                sqlx::query!(
+                    r#"
+                    DELETE FROM bank_feed_lines WHERE bank_feed_id = $1
+                    "#,
+                    bank_feed_id as BankFeedId
+                )
+                .execute(&mut *tx)
+                .await?;
 
                 tx.commit().await?;
+
+                self.secrets.remove(&feed_credentials(bank_feed_id)).await?;
             }
-            JournalDomainEvent::TransactionDeleted { transaction_id, .. } => {
-                let mut tx = self.projection_pool.begin().await?;
+        }
 
-                let balance_updates = sqlx::query_scalar!(
-                    r#"
-                    DELETE FROM transactions WHERE id = $1 RETURNING entries as "entries: TransactionEntries"
-                    "#,
-                    transaction_id as TransactionId,
-                    )
-                    .fetch_one(&mut *tx)
-                    .await?;
+        if let Some(journal_id) = changed_journal {
+            sqlx::query!(
+                r#"
+                INSERT INTO journal_versions (journal_id, version, last_activity) VALUES ($1, $2, $3)
+                ON CONFLICT (journal_id) DO UPDATE SET version = GREATEST(journal_versions.version, EXCLUDED.version), last_activity = GREATEST(journal_versions.last_activity, EXCLUDED.last_activity)
+                "#,
+                journal_id as JournalId,
+                event_id,
+                last_activity as Option<Timestamp>
+            )
+            .execute(&self.projection_pool)
+            .await?;
 
-                // revert the transaction's balance updates
-                for update in balance_updates.0 {
-                    let update_amt = match update.entry_type {
-                        EntryType::Credit => update.amount as i64,
-                        EntryType::Debit => -(update.amount as i64),
-                    };
+            // only after the projection is written, so a concurrent read can't cache the old rows
+            self.cache.invalidate(journal_id);
 
-                    sqlx::query!(
-                        r#"
-                        UPDATE accounts SET balance = balance - $1 WHERE id = $2
-                        "#,
-                        update_amt,
-                        update.account_id as AccountId
-                    )
-                    .execute(&mut *tx)
-                    .await?;
-                }
-                tx.commit().await?;
-            }
+            sqlx::query!(
+                r#"
+                SELECT pg_notify($1, $2)
+                "#,
+                JOURNAL_CHANGED_CHANNEL,
+                journal_id.to_string()
+            )
+            .execute(&self.projection_pool)
+            .await?;
         }
 
-        self.current_event
-            .send(event_id)
-            .expect("journal eventid sender closed");
+        tracing::debug!("projected an event");
 
-        Ok(())
+        self.processed(event_id)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_app::{TestApp, assert_accepted};
+    use crate::time_provider::TimeProvider;
+    use axum::http::StatusCode;
+    use disintegrate_postgres::PgEventStore;
+
+    /// the voucher number of each of the journal's transactions
+    async fn numbers(
+        journal_service: &JournalService,
+        journal_id: JournalId,
+    ) -> HashMap<TransactionId, i64> {
+        sqlx::query!(
+            r#"
+            SELECT id as "id: TransactionId", number FROM transactions WHERE journal_id = $1
+            "#,
+            journal_id as JournalId
+        )
+        .fetch_all(&journal_service.projection_pool)
+        .await
+        .expect("failed to read the voucher numbers")
+        .into_iter()
+        .map(|row| (row.id, row.number))
+        .collect()
+    }
+
+    #[tokio::test]
+    async fn unnumbered_transactions_keep_their_numbers_through_a_rebuild() {
+        let Some(app) = TestApp::spawn().await else {
+            return;
+        };
+
+        let owner = app.signup("owner").await;
+        let journal_id = owner.create_journal(&app, "Ledger").await;
+        assert_accepted(&owner.create_account(journal_id, "Cash").await);
+        assert_accepted(&owner.create_account(journal_id, "Food").await);
+        let cash = app.account_id(journal_id, "Cash").await;
+        let food = app.account_id(journal_id, "Food").await;
+
+        let journal_service = &app.state.journal_service;
+        let event_store = PgEventStore::try_new(
+            journal_service.projection_pool.clone(),
+            journal_service.payloads.clone(),
+        )
+        .await
+        .expect("failed to open the event store");
+
+        // recorded the way transactions were before they had numbers
+        let authority = Authority::Direct(Actor::User(owner.id));
+        let timestamp = app.state.clock.get_time();
+        let unnumbered = |transaction_id| JournalDomainEvent::TransactionCreated {
+            transaction_id,
+            journal_id,
+            balance_updates: [(cash, EntryType::Debit), (food, EntryType::Credit)]
+                .map(|(account_id, entry_type)| BalanceUpdate {
+                    account_id,
+                    amount: 100,
+                    entry_type,
+                    memo: None,
+                })
+                .to_vec(),
+            authority: authority.clone(),
+            timestamp,
+            number: 0,
+            effective_date: timestamp.date_naive(),
+            reverses: None,
+            transfer: None,
+            payee: None,
+        };
+        let (deleted, second, third) = (
+            TransactionId::new(),
+            TransactionId::new(),
+            TransactionId::new(),
+        );
+        let recorded = event_store
+            .append_without_validation(vec![
+                unnumbered(deleted),
+                unnumbered(second),
+                JournalDomainEvent::TransactionDeleted {
+                    transaction_id: deleted,
+                    authority: authority.clone(),
+                    timestamp,
+                },
+                unnumbered(third),
+            ])
+            .await
+            .expect("failed to record the events");
+        journal_service
+            .wait_for(recorded.last().expect("nothing was recorded").id())
+            .await;
+
+        // a transaction recorded now is numbered by its decision
+        assert_accepted(&owner.post_transaction(journal_id, cash, food, "1.00").await);
+
+        let projected = numbers(journal_service, journal_id).await;
+        let mut sorted: Vec<i64> = projected.values().copied().collect();
+        sorted.sort();
+        assert_eq!(sorted, vec![2, 3, 4]);
+        assert_eq!(projected[&second], 2);
+        assert_eq!(projected[&third], 3);
+
+        // rows projected before there were numbers are backfilled the same way
+        sqlx::query!(
+            r#"
+            ALTER TABLE transactions ALTER COLUMN number DROP NOT NULL
+            "#
+        )
+        .execute(&journal_service.projection_pool)
+        .await
+        .expect("failed to allow missing numbers");
+        sqlx::query!(
+            r#"
+            UPDATE transactions SET number = NULL WHERE id = ANY($1)
+            "#,
+            &[second.to_string(), third.to_string()]
+        )
+        .execute(&journal_service.projection_pool)
+        .await
+        .expect("failed to forget the numbers");
+        backfill_transaction_numbers(&journal_service.projection_pool)
+            .await
+            .expect("failed to backfill the numbers");
+        assert_eq!(numbers(journal_service, journal_id).await, projected);
+
+        journal_service
+            .rebuild_projections(&event_store)
+            .await
+            .expect("failed to rebuild the projections");
+        assert_eq!(numbers(journal_service, journal_id).await, projected);
+    }
+
+    #[tokio::test]
+    async fn erasing_a_member_forgets_them_wherever_the_events_name_them() {
+        let Some(app) = TestApp::spawn().await else {
+            return;
+        };
+
+        let owner = app.signup("owner").await;
+        let member = app.signup("member").await;
+        let journal_id = owner.create_journal(&app, "Ledger").await;
+        assert_accepted(&owner.invite(journal_id, &member, Permissions::READ).await);
+        assert_accepted(&owner.create_account(journal_id, "Cash").await);
+        assert_accepted(&owner.create_account(journal_id, "Food").await);
+        let cash = app.account_id(journal_id, "Cash").await;
+        let food = app.account_id(journal_id, "Food").await;
+        assert_accepted(&owner.post_transaction(journal_id, cash, food, "1.00").await);
+
+        let journal_service = &app.state.journal_service;
+        let transaction_id = *numbers(journal_service, journal_id)
+            .await
+            .keys()
+            .next()
+            .expect("the transaction wasn't projected");
+        journal_service
+            .pin_journal(
+                journal_id,
+                true,
+                Authority::Direct(Actor::User(member.id)),
+                app.state.clock.get_time(),
+            )
+            .await
+            .expect("failed to pin the journal");
+        let event_id = journal_service
+            .add_comment(
+                CommentId::new(),
+                transaction_id,
+                journal_id,
+                None,
+                "thanks @member".to_string(),
+                vec![member.id],
+                Authority::Direct(Actor::User(owner.id)),
+                app.state.clock.get_time(),
+            )
+            .await
+            .expect("failed to add the comment");
+        journal_service.wait_for(event_id).await;
+
+        member
+            .client
+            .post("/me/delete-account")
+            .form(&[("email", member.email.to_string())])
+            .await
+            .assert_status(StatusCode::SEE_OTHER);
+
+        let rows = sqlx::query!(
+            r#"
+            SELECT payload as "payload!", user_id FROM event
+            "#
+        )
+        .fetch_all(&journal_service.projection_pool)
+        .await
+        .expect("failed to read the events");
+        let id = member.id.to_string();
+
+        assert!(rows.iter().all(|row| row.user_id.as_deref() != Some(&id)));
+
+        let events = rows
+            .iter()
+            .map(|row| journal_service.payloads.decode(&row.payload))
+            .collect::<Result<Vec<JournalDomainEvent>, _>>()
+            .expect("failed to decode the events");
+
+        assert!(events.iter().any(|event| matches!(
+            event,
+            JournalDomainEvent::MemberAdded { user_id, .. } if *user_id == UserId::nil()
+        )));
+        assert!(events.iter().any(|event| matches!(
+            event,
+            JournalDomainEvent::CommentAdded { mentions, .. } if *mentions == [UserId::nil()]
+        )));
+        assert!(
+            !events
+                .iter()
+                .any(|event| format!("{event:?}").contains(&id))
+        );
+    }
+
+    /// tells the instances the journal changed, as another instance's projection does
+    async fn notify_change(journal_service: &JournalService, journal_id: JournalId) {
+        sqlx::query!(
+            r#"
+            SELECT pg_notify($1, $2)
+            "#,
+            JOURNAL_CHANGED_CHANNEL,
+            journal_id.to_string()
+        )
+        .execute(&journal_service.projection_pool)
+        .await
+        .expect("failed to notify the change");
+    }
+
+    /// waits until the user's permissions on the journal are no longer cached
+    async fn wait_until_dropped(
+        journal_service: &JournalService,
+        journal_id: JournalId,
+        user_id: UserId,
+    ) {
+        tokio::time::timeout(Duration::from_secs(5), async {
+            while journal_service
+                .cache
+                .permissions(journal_id, user_id)
+                .is_some()
+            {
+                tokio::time::sleep(Duration::from_millis(10)).await;
+            }
+        })
+        .await
+        .expect("the cached permissions were never dropped");
+    }
+
+    #[tokio::test]
+    async fn cached_permissions_are_dropped_when_another_instance_changes_the_journal() {
+        let Some(app) = TestApp::spawn().await else {
+            return;
+        };
+
+        let owner = app.signup("owner").await;
+        let member = app.signup("member").await;
+        let journal_id = owner.create_journal(&app, "Ledger").await;
+        assert_accepted(&owner.invite(journal_id, &member, Permissions::READ).await);
+
+        let journal_service = &app.state.journal_service;
+        let authority = Authority::Direct(Actor::User(member.id));
+
+        // notifications arrive in order, so once one about a journal nothing else changes has
+        // been handled, so have the ones the invite sent
+        let unchanged = JournalId::new();
+        let permissions = journal_service.get_effective_permissions(unchanged, &authority);
+        assert_eq!(permissions.await.ok(), Some(Permissions::empty()));
+        notify_change(journal_service, unchanged).await;
+        wait_until_dropped(journal_service, unchanged, member.id).await;
+
+        let permissions = || journal_service.get_effective_permissions(journal_id, &authority);
+        assert_eq!(permissions().await.ok(), Some(Permissions::READ));
+
+        // another instance's projection removes the member, which this one doesn't see
+        sqlx::query!(
+            r#"
+            DELETE FROM journal_members WHERE journal_id = $1 AND user_id = $2
+            "#,
+            journal_id as JournalId,
+            member.id as UserId
+        )
+        .execute(&journal_service.projection_pool)
+        .await
+        .expect("failed to remove the member");
+        assert_eq!(permissions().await.ok(), Some(Permissions::READ));
+
+        notify_change(journal_service, journal_id).await;
+        wait_until_dropped(journal_service, journal_id, member.id).await;
+        assert_eq!(permissions().await.ok(), Some(Permissions::empty()));
     }
 }
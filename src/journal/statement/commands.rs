@@ -0,0 +1,67 @@
+use crate::StateType;
+use crate::journal::JournalError;
+use crate::journal::access::{AppendTransaction, JournalAccess};
+use crate::journal::account::AccountId;
+use crate::journal::bankfeed::commands::review_csv;
+use crate::journal::statement::{detect, views};
+use crate::journal::transaction::bulk;
+use crate::monkesto_error::MonkestoResult;
+use axum::Form;
+use axum::extract::State;
+use axum::http::StatusCode;
+use axum::response::{IntoResponse, Response};
+use maud::Markup;
+use serde::Deserialize;
+use std::str::FromStr;
+
+#[derive(Deserialize)]
+pub struct StatementForm {
+    account_id: String,
+    statement: String,
+}
+
+/// reads the statement in whichever format it's in, then sends its lines to bulk entry for
+/// review, the same way a bank feed's pulled lines are
+pub async fn import_statement(
+    State(state): State<StateType>,
+    access: JournalAccess<AppendTransaction>,
+    Form(form): Form<StatementForm>,
+) -> Response {
+    let journal_id = access.journal.id;
+    let authority = access.authority();
+
+    let result: MonkestoResult<Markup> = async {
+        let account_id = AccountId::from_str(&form.account_id)?;
+
+        let account_name = state
+            .journal_service
+            .list_journal_accounts(journal_id, &authority)
+            .await?
+            .into_iter()
+            .find(|(account, _, _)| account.id == account_id)
+            .map(|(account, _, _)| account.name.to_string())
+            .ok_or(JournalError::InvalidAccount(account_id))?;
+
+        let lines = detect(&form.statement)?.parse(&form.statement)?;
+        let csv = review_csv(&lines, &account_name);
+
+        bulk::review_page(&state, journal_id, &csv, &authority).await
+    }
+    .await;
+
+    match result {
+        Ok(page) => page.into_response(),
+        Err(e) => (
+            StatusCode::UNPROCESSABLE_ENTITY,
+            views::statement_page(
+                &state,
+                &access,
+                Some(&form.account_id),
+                &form.statement,
+                Some(&e),
+            )
+            .await,
+        )
+            .into_response(),
+    }
+}
@@ -0,0 +1,77 @@
+pub mod commands;
+pub mod qfx;
+pub mod qif;
+pub mod views;
+
+use crate::journal::JournalError;
+use crate::journal::service::BankFeedLine;
+use crate::journal::transaction::TransactionValidationError;
+use crate::parse;
+use axum::Router;
+use axum::routing::get;
+use axum_login::login_required;
+
+pub fn router() -> Router<crate::StateType> {
+    Router::new()
+        .route(
+            "/journal/{id}/transaction/statement",
+            get(views::statement_import_page).post(commands::import_statement),
+        )
+        .route_layer(login_required!(crate::BackendType, login_url = "/signin"))
+}
+
+/// a file format banks export statements in. the lines it reads go through the same review
+/// as a bank feed's, so the journal's rules can choose the account on the other side
+pub trait StatementParser: Send + Sync {
+    /// the name shown to users
+    fn display_name(&self) -> &'static str;
+
+    /// whether the text looks like a statement in this format
+    fn sniff(&self, text: &str) -> bool;
+
+    fn parse(&self, text: &str) -> Result<Vec<BankFeedLine>, JournalError>;
+}
+
+/// every supported format, in the order they're tried. qfx comes first, since it's the more
+/// particular of the two
+pub const PARSERS: [&dyn StatementParser; 2] = [&qfx::Qfx, &qif::Qif];
+
+/// the format the text is in, judged by its content rather than the name of the file it came from
+pub fn detect(text: &str) -> Result<&'static dyn StatementParser, JournalError> {
+    PARSERS
+        .into_iter()
+        .find(|parser| parser.sniff(text))
+        .ok_or_else(|| invalid("the format wasn't recognised. Expected QIF or QFX"))
+}
+
+fn invalid(reason: impl Into<String>) -> JournalError {
+    JournalError::TransactionValidation(TransactionValidationError::InvalidStatement(reason.into()))
+}
+
+/// a signed amount like `-1,234.56` in cents, where money into the account is positive
+fn signed_cents(s: &str) -> Result<i64, JournalError> {
+    let s = s.trim();
+    let (negative, digits) = match s.strip_prefix('-') {
+        Some(digits) => (true, digits),
+        None => (false, s.strip_prefix('+').unwrap_or(s)),
+    };
+
+    let cents = parse::amount::amount(digits)? as i64;
+
+    Ok(if negative { -cents } else { cents })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn formats_are_detected_by_their_content() {
+        let qif = "!Type:Bank\nD01/31/2026\nT-12.05\nPCorner Shop\n^\n";
+        let qfx = "OFXHEADER:100\nDATA:OFXSGML\n\n<OFX><BANKMSGSRSV1></BANKMSGSRSV1></OFX>";
+
+        assert_eq!(detect(qif).map(|p| p.display_name()), Ok("QIF"));
+        assert_eq!(detect(qfx).map(|p| p.display_name()), Ok("QFX"));
+        assert!(detect("reference,date,account,amount,type").is_err());
+    }
+}
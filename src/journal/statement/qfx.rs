@@ -0,0 +1,121 @@
+use crate::journal::JournalError;
+use crate::journal::service::BankFeedLine;
+use crate::journal::statement::{StatementParser, invalid, signed_cents};
+use chrono::NaiveDate;
+
+/// Quicken's name for the Open Financial Exchange format. the original version is sgml, which
+/// doesn't close the tags around values, and later versions are xml, so values are read up to
+/// the next tag either way
+pub struct Qfx;
+
+/// the value of the first `tag` in the text, with xml's entities decoded. tag names are case
+/// insensitive, so they're found in an uppercased copy of the text, which only changes ascii
+/// letters and keeps every offset the same
+fn value(text: &str, upper: &str, tag: &str) -> Option<String> {
+    let open = format!("<{tag}>");
+    let start = upper.find(&open)? + open.len();
+    let end = text[start..]
+        .find('<')
+        .map_or(text.len(), |end| start + end);
+
+    let value = text[start..end]
+        .trim()
+        .replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&apos;", "'")
+        .replace("&amp;", "&");
+
+    Some(value)
+}
+
+/// the date at the start of an ofx timestamp, like `20260131120000.000[-5:EST]`
+fn parse_date(s: &str) -> Option<NaiveDate> {
+    NaiveDate::parse_from_str(s.get(..8)?, "%Y%m%d").ok()
+}
+
+impl StatementParser for Qfx {
+    fn display_name(&self) -> &'static str {
+        "QFX"
+    }
+
+    fn sniff(&self, text: &str) -> bool {
+        let upper = text.to_ascii_uppercase();
+        upper.contains("OFXHEADER") || upper.contains("<OFX>")
+    }
+
+    fn parse(&self, text: &str) -> Result<Vec<BankFeedLine>, JournalError> {
+        let upper = text.to_ascii_uppercase();
+        let mut lines = Vec::new();
+        let mut rest = 0;
+
+        while let Some(start) = upper[rest..].find("<STMTTRN>").map(|i| rest + i) {
+            let end = upper[start..]
+                .find("</STMTTRN>")
+                .map_or(upper.len(), |end| start + end);
+            rest = end;
+
+            let field = |tag: &str| value(&text[start..end], &upper[start..end], tag);
+
+            let posted = field("DTPOSTED")
+                .ok_or_else(|| invalid("a transaction doesn't have a posted date"))?;
+            let booked = parse_date(&posted)
+                .ok_or_else(|| invalid(format!("a date couldn't be read: {posted}")))?;
+            let amount =
+                field("TRNAMT").ok_or_else(|| invalid("a transaction doesn't have an amount"))?;
+
+            let description = [field("NAME"), field("MEMO")]
+                .into_iter()
+                .flatten()
+                .filter(|part| !part.is_empty())
+                .collect::<Vec<_>>()
+                .join(", ");
+
+            lines.push(BankFeedLine {
+                external_id: field("FITID").unwrap_or_else(|| format!("QFX-{}", lines.len() + 1)),
+                booked,
+                amount: signed_cents(&amount)?,
+                description,
+            });
+        }
+
+        Ok(lines)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sgml_and_xml_transactions_are_both_read() {
+        let text = "OFXHEADER:100\nDATA:OFXSGML\n\n\
+                    <OFX><BANKMSGSRSV1><STMTTRNRS><STMTRS><BANKTRANLIST>\n\
+                    <STMTTRN>\n<TRNTYPE>DEBIT\n<DTPOSTED>20260131120000.000[-5:EST]\n\
+                    <TRNAMT>-45.20\n<FITID>2026013101\n<NAME>Corner Shop\n<MEMO>Card payment\n\
+                    </STMTTRN>\n\
+                    <stmttrn><dtposted>20260201</dtposted><trnamt>1500.00</trnamt>\
+                    <fitid>2026020101</fitid><name>Smith &amp; Sons</name></stmttrn>\n\
+                    </BANKTRANLIST></STMTRS></STMTTRNRS></BANKMSGSRSV1></OFX>\n";
+
+        let lines = Qfx.parse(text).expect("failed to parse the statement");
+
+        assert_eq!(
+            lines,
+            vec![
+                BankFeedLine {
+                    external_id: "2026013101".to_string(),
+                    booked: NaiveDate::from_ymd_opt(2026, 1, 31).unwrap(),
+                    amount: -4520,
+                    description: "Corner Shop, Card payment".to_string(),
+                },
+                BankFeedLine {
+                    external_id: "2026020101".to_string(),
+                    booked: NaiveDate::from_ymd_opt(2026, 2, 1).unwrap(),
+                    amount: 150000,
+                    description: "Smith & Sons".to_string(),
+                },
+            ]
+        );
+    }
+}
@@ -0,0 +1,180 @@
+use crate::journal::JournalError;
+use crate::journal::service::BankFeedLine;
+use crate::journal::statement::{StatementParser, invalid, signed_cents};
+use chrono::NaiveDate;
+
+/// Quicken Interchange Format, a line per field where the first character says which field it
+/// is, and `^` ends each transaction
+pub struct Qif;
+
+/// quicken writes dates month first, with an apostrophe before two digit years after 1999
+fn parse_date(s: &str) -> Option<NaiveDate> {
+    let s = s.trim().replace('\'', "/").replace(' ', "");
+
+    // two digit years are tried first, since `%Y` would read `26` as the year 26
+    ["%m/%d/%y", "%m/%d/%Y", "%Y-%m-%d", "%m-%d-%Y"]
+        .into_iter()
+        .find_map(|format| NaiveDate::parse_from_str(&s, format).ok())
+}
+
+/// the fields of a transaction read so far, along with the line it started on
+#[derive(Default)]
+struct Record {
+    line: u64,
+    date: Option<String>,
+    amount: Option<String>,
+    number: Option<String>,
+    payee: Option<String>,
+    memo: Option<String>,
+}
+
+impl Record {
+    fn finish(self, index: usize) -> Result<BankFeedLine, JournalError> {
+        let line = self.line;
+        let date = self.date.ok_or_else(|| {
+            invalid(format!(
+                "the transaction on line {line} doesn't have a date"
+            ))
+        })?;
+        let booked = parse_date(&date).ok_or_else(|| {
+            invalid(format!(
+                "line {line} has a date that couldn't be read: {date}"
+            ))
+        })?;
+        let amount = self.amount.ok_or_else(|| {
+            invalid(format!(
+                "the transaction on line {line} doesn't have an amount"
+            ))
+        })?;
+
+        let description = [self.payee, self.memo]
+            .into_iter()
+            .flatten()
+            .filter(|part| !part.is_empty())
+            .collect::<Vec<_>>()
+            .join(", ");
+
+        Ok(BankFeedLine {
+            // qif doesn't identify transactions, so the check number is the closest there is
+            external_id: self.number.unwrap_or_else(|| format!("QIF-{}", index + 1)),
+            booked,
+            amount: signed_cents(&amount)?,
+            description,
+        })
+    }
+}
+
+impl StatementParser for Qif {
+    fn display_name(&self) -> &'static str {
+        "QIF"
+    }
+
+    fn sniff(&self, text: &str) -> bool {
+        let start = text.trim_start().to_ascii_lowercase();
+        ["!type:", "!account", "!option"]
+            .into_iter()
+            .any(|header| start.starts_with(header))
+    }
+
+    fn parse(&self, text: &str) -> Result<Vec<BankFeedLine>, JournalError> {
+        let mut lines = Vec::new();
+        let mut record: Option<Record> = None;
+        let mut reading = true;
+
+        for (number, line) in (1..).zip(text.lines()) {
+            let line = line.trim_end();
+            let Some(field) = line.chars().next() else {
+                continue;
+            };
+            let value = line[field.len_utf8()..].trim().to_string();
+
+            // the lists of accounts and categories some exports start with aren't transactions
+            if field == '!' {
+                let header = line.to_ascii_lowercase();
+                reading = header.starts_with("!type:")
+                    && !["!type:cat", "!type:class", "!type:memorized"].contains(&header.as_str());
+                record = None;
+                continue;
+            }
+
+            if !reading {
+                continue;
+            }
+
+            if field == '^' {
+                if let Some(record) = record.take() {
+                    lines.push(record.finish(lines.len())?);
+                }
+                continue;
+            }
+
+            let record = record.get_or_insert_with(|| Record {
+                line: number,
+                ..Record::default()
+            });
+
+            match field {
+                'D' => record.date = Some(value),
+                // `U` is the same amount, written by newer versions of quicken
+                'T' | 'U' => record.amount = Some(value),
+                'N' => record.number = Some(value),
+                'P' => record.payee = Some(value),
+                'M' => record.memo = Some(value),
+                _ => {}
+            }
+        }
+
+        // the last transaction doesn't always end with a `^`
+        if let Some(record) = record
+            && record.date.is_some()
+        {
+            lines.push(record.finish(lines.len())?);
+        }
+
+        Ok(lines)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn transactions_are_read_field_by_field() {
+        let text = "!Account\n\
+                    NChecking\n\
+                    TBank\n\
+                    ^\n\
+                    !Type:Bank\n\
+                    D01/31'26\n\
+                    T-1,200.00\n\
+                    N1042\n\
+                    PLandlord\n\
+                    MJanuary rent\n\
+                    LRent\n\
+                    ^\n\
+                    D2/1/2026\n\
+                    T1500\n\
+                    PEmployer\n";
+
+        let lines = Qif.parse(text).expect("failed to parse the statement");
+
+        assert_eq!(
+            lines,
+            vec![
+                BankFeedLine {
+                    external_id: "1042".to_string(),
+                    booked: NaiveDate::from_ymd_opt(2026, 1, 31).unwrap(),
+                    amount: -120000,
+                    description: "Landlord, January rent".to_string(),
+                },
+                BankFeedLine {
+                    external_id: "QIF-2".to_string(),
+                    booked: NaiveDate::from_ymd_opt(2026, 2, 1).unwrap(),
+                    amount: 150000,
+                    description: "Employer".to_string(),
+                },
+            ]
+        );
+    }
+}
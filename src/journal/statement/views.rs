@@ -0,0 +1,106 @@
+use crate::StateType;
+use crate::journal::JournalError;
+use crate::journal::access::{JournalAccess, Requirement};
+use crate::journal::layout::journal_layout;
+use crate::journal::statement::PARSERS;
+use crate::monkesto_error::MonkestoError;
+use axum::extract::State;
+use maud::{Markup, html};
+
+const INPUT_CLASS: &str = "block w-full rounded-md bg-white px-3 py-1.5 text-base text-gray-900 outline-1 -outline-offset-1 outline-gray-300 placeholder:text-gray-400 focus:outline-2 focus:-outline-offset-2 focus:outline-indigo-600 sm:text-sm/6 dark:bg-white/5 dark:text-white dark:outline-white/10 dark:placeholder:text-gray-500 dark:focus:outline-indigo-500";
+
+pub async fn statement_import_page(
+    State(state): State<StateType>,
+    access: JournalAccess,
+) -> Markup {
+    // a statement is usually for the account money is paid from, so that's chosen to start with
+    let account = access
+        .journal
+        .default_cash_account
+        .map(|account_id| account_id.to_string());
+
+    statement_page(&state, &access, account.as_deref(), "", None).await
+}
+
+/// the statement form, filled back in with a rejected submission and its error
+pub async fn statement_page<R: Requirement>(
+    state: &StateType,
+    access: &JournalAccess<R>,
+    account: Option<&str>,
+    statement: &str,
+    error: Option<&MonkestoError>,
+) -> Markup {
+    let id = access.journal.id.to_string();
+
+    let accounts = state
+        .journal_service
+        .list_journal_accounts(access.journal.id, &access.authority())
+        .await
+        .unwrap_or_default();
+
+    let formats = PARSERS
+        .iter()
+        .map(|parser| parser.display_name())
+        .collect::<Vec<_>>()
+        .join(" or ");
+
+    let content = html! {
+        div class="flex flex-col gap-6 mx-auto w-full max-w-4xl" {
+            div class="bg-white dark:bg-gray-800 border border-gray-200 dark:border-gray-700 rounded-xl p-6" {
+                h3 class="text-lg font-semibold text-gray-900 dark:text-white mb-2" {
+                    "Import a Statement"
+                }
+                p class="text-sm text-gray-500 dark:text-gray-400 mb-6" {
+                    "Paste a " (formats) " statement exported by your bank, and choose the account it's for. "
+                    "The format is worked out from the statement itself. Each line is balanced against the "
+                    a href=(format!("/journal/{}/rule", id)) class="font-medium text-indigo-600 hover:text-indigo-500 dark:text-indigo-400 dark:hover:text-indigo-300" { "import rules" }
+                    "' choice of account, and you'll review the transactions before they're created."
+                }
+
+                form method="post" action=(format!("/journal/{}/transaction/statement", id)) class="space-y-6" {
+                    div {
+                        label for="account_id" class="block text-sm/6 font-medium text-gray-900 dark:text-gray-100" { "Account" }
+                        select id="account_id" name="account_id" required class=(INPUT_CLASS) {
+                            option value="" { "Select account..." }
+                            @for (acc, _, _) in &accounts {
+                                @let value = acc.id.to_string();
+                                option value=(value) selected[account == Some(value.as_str())] { (acc.name) }
+                            }
+                        }
+                    }
+
+                    textarea
+                        name="statement"
+                        rows="16"
+                        required
+                        placeholder="!Type:Bank\nD01/31/2026\nT-45.20\nPCorner Shop\n^"
+                        class="w-full rounded-md border border-gray-300 dark:border-gray-600 bg-white dark:bg-gray-800 px-3 py-2 font-mono text-sm text-gray-900 dark:text-white placeholder:text-gray-400 dark:placeholder:text-gray-500 focus:border-indigo-500 focus:ring-indigo-500 dark:focus:border-indigo-400" {
+                        (statement)
+                    }
+
+                    div class="flex justify-end pt-4 border-t border-gray-200 dark:border-gray-600" {
+                        button class="px-6 py-2 bg-indigo-600 text-white font-medium rounded-md hover:bg-indigo-700 focus:outline-none focus:ring-2 focus:ring-indigo-500 focus:ring-offset-2 dark:bg-indigo-500 dark:hover:bg-indigo-400 dark:focus:ring-indigo-400 dark:ring-offset-gray-800" type="submit" {
+                            "Review Transactions"
+                        }
+                    }
+                }
+
+                @match error {
+                    Some(MonkestoError::Journal(JournalError::InvalidBatch(rows))) => {
+                        ul class="mt-6 space-y-1 text-sm text-red-600 dark:text-red-400" {
+                            @for row in rows {
+                                li { "Line " (row.row) ": " (row.error) }
+                            }
+                        }
+                    },
+                    Some(e) => {
+                        p class="mt-6 text-sm text-red-600 dark:text-red-400" { (e) }
+                    },
+                    None => {}
+                }
+            }
+        }
+    };
+
+    journal_layout(&access.journal, content)
+}
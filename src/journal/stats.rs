@@ -0,0 +1,184 @@
+use crate::StateType;
+use crate::authn::user::UserId;
+use crate::authority::Authority;
+use crate::charts::{Chart, Series, pie_chart};
+use crate::journal::JournalId;
+use crate::journal::fiscal;
+use crate::journal::transaction::views::format_balance;
+use chrono::{Months, NaiveDate};
+use maud::{Markup, html};
+use std::collections::HashMap;
+
+/// how many months the activity heatmap covers, ending with the current one
+const ACTIVITY_MONTHS: u32 = 12;
+
+/// how many accounts and authors are ranked
+const TOP_COUNT: i64 = 5;
+
+/// the first day of each month the heatmap covers, oldest first
+fn activity_months(today: NaiveDate) -> Vec<NaiveDate> {
+    let current = fiscal::period_start(today);
+
+    (0..ACTIVITY_MONTHS)
+        .rev()
+        .filter_map(|ago| current.checked_sub_months(Months::new(ago)))
+        .collect()
+}
+
+/// pairs every month with its transaction count, which is zero for months without any
+fn fill_months(months: &[NaiveDate], counts: &[(NaiveDate, u64)]) -> Vec<(NaiveDate, u64)> {
+    months
+        .iter()
+        .map(|month| {
+            let count = counts
+                .iter()
+                .find(|(counted, _)| counted == month)
+                .map_or(0, |(_, count)| *count);
+            (*month, count)
+        })
+        .collect()
+}
+
+/// shades a month by how busy it was compared to the busiest month shown
+fn heat_class(count: u64, max: u64) -> &'static str {
+    if count == 0 || max == 0 {
+        return "bg-gray-100 dark:bg-gray-700";
+    }
+
+    match count * 4 / max {
+        0 => "bg-indigo-100 dark:bg-indigo-950",
+        1 => "bg-indigo-300 dark:bg-indigo-800",
+        2 => "bg-indigo-500 dark:bg-indigo-600",
+        _ => "bg-indigo-700 dark:bg-indigo-400",
+    }
+}
+
+/// the activity widgets on the journal page
+pub async fn stats_section(
+    state: &StateType,
+    journal_id: JournalId,
+    authority: &Authority,
+    today: NaiveDate,
+) -> Markup {
+    let months = activity_months(today);
+
+    let stats = match state
+        .journal_service
+        .journal_stats(journal_id, authority, months[0], TOP_COUNT)
+        .await
+    {
+        Ok(stats) => stats,
+        Err(e) => {
+            return html! {
+                p class="text-sm text-gray-500 dark:text-gray-400" {
+                    (format!("failed to fetch the journal's activity: {e}"))
+                }
+            };
+        }
+    };
+
+    let author_ids: Vec<UserId> = stats.top_authors.iter().map(|(id, _)| *id).collect();
+    let names: HashMap<UserId, String> = state
+        .authn_service
+        .fetch_users(&author_ids)
+        .await
+        .map(|users| {
+            users
+                .into_iter()
+                .map(|user| (user.id, user.display_name()))
+                .collect()
+        })
+        .unwrap_or_default();
+
+    let monthly = fill_months(&months, &stats.monthly_transactions);
+    let busiest = monthly.iter().map(|(_, count)| *count).max().unwrap_or(0);
+
+    html! {
+        div class="grid grid-cols-1 lg:grid-cols-3 gap-4" {
+            div class="p-4 bg-white dark:bg-gray-800 border border-gray-200 dark:border-gray-700 rounded-xl" {
+                h3 class="text-sm font-semibold text-gray-900 dark:text-white mb-3" { "Transactions by Month" }
+                div class="grid grid-cols-12 gap-1" {
+                    @for (month, count) in &monthly {
+                        div class="flex flex-col items-center gap-1" {
+                            div
+                            class=(format!("w-full aspect-square rounded-sm {}", heat_class(*count, busiest)))
+                            title=(format!("{}: {} transactions", month.format("%B %Y"), count)) {}
+                            span class="text-[10px] text-gray-500 dark:text-gray-400" { (month.format("%b").to_string().chars().next().unwrap_or(' ')) }
+                        }
+                    }
+                }
+                p class="mt-3 text-xs text-gray-500 dark:text-gray-400" {
+                    (monthly.iter().map(|(_, count)| count).sum::<u64>()) " transactions in the last " (ACTIVITY_MONTHS) " months"
+                }
+            }
+
+            div class="p-4 bg-white dark:bg-gray-800 border border-gray-200 dark:border-gray-700 rounded-xl" {
+                h3 class="text-sm font-semibold text-gray-900 dark:text-white mb-3" { "Largest Accounts" }
+                @if stats.largest_accounts.is_empty() {
+                    p class="text-sm text-gray-500 dark:text-gray-400" { "No balances yet." }
+                } @else {
+                    ul class="space-y-1 text-sm" {
+                        @for (_, name, balance) in &stats.largest_accounts {
+                            li class="flex justify-between gap-2" {
+                                span class="truncate text-gray-700 dark:text-gray-300" { (name) }
+                                span class="font-mono text-gray-900 dark:text-white" { (format_balance(*balance)) }
+                            }
+                        }
+                    }
+                }
+            }
+
+            div class="p-4 bg-white dark:bg-gray-800 border border-gray-200 dark:border-gray-700 rounded-xl" {
+                h3 class="text-sm font-semibold text-gray-900 dark:text-white mb-3" { "Most Active" }
+                @if stats.top_authors.is_empty() {
+                    p class="text-sm text-gray-500 dark:text-gray-400" { "No transactions yet." }
+                } @else {
+                    div class="mb-3" {
+                        (pie_chart(&Chart {
+                            title: "Transactions by author".to_string(),
+                            labels: stats.top_authors.iter().map(|(author_id, _)| names.get(author_id).cloned().unwrap_or_else(|| author_id.to_string())).collect(),
+                            series: vec![Series {
+                                name: "Transactions".to_string(),
+                                values: stats.top_authors.iter().map(|(_, count)| *count as i64).collect(),
+                            }],
+                            format: |count| count.to_string(),
+                        }))
+                    }
+                    ul class="space-y-1 text-sm" {
+                        @for (author_id, count) in &stats.top_authors {
+                            li class="flex justify-between gap-2" {
+                                span class="truncate text-gray-700 dark:text-gray-300" {
+                                    (names.get(author_id).cloned().unwrap_or_else(|| author_id.to_string()))
+                                }
+                                span class="text-gray-900 dark:text-white" { (count) }
+                            }
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn every_month_shown_has_a_count() {
+        let date = |year, month| NaiveDate::from_ymd_opt(year, month, 1).expect("invalid date");
+
+        let months = activity_months(NaiveDate::from_ymd_opt(2026, 3, 17).expect("invalid date"));
+        assert_eq!(months.len(), 12);
+        assert_eq!(months.first(), Some(&date(2025, 4)));
+        assert_eq!(months.last(), Some(&date(2026, 3)));
+
+        let monthly = fill_months(&months, &[(date(2025, 12), 3), (date(2026, 3), 1)]);
+        assert_eq!(monthly[8], (date(2025, 12), 3));
+        assert_eq!(monthly[11], (date(2026, 3), 1));
+        assert_eq!(monthly.iter().filter(|(_, count)| *count == 0).count(), 10);
+
+        assert_eq!(heat_class(0, 3), heat_class(0, 0));
+        assert_ne!(heat_class(1, 3), heat_class(3, 3));
+    }
+}
@@ -1,19 +1,30 @@
 use crate::journal::domain::JournalDomainEvent;
-use disintegrate::serde::messagepack::MessagePack;
-use disintegrate_postgres::PgEventStore;
+use crate::journal::payload::JournalPayloads;
+use disintegrate_postgres::{Migrator, PgEventStore};
 use sqlx::PgPool;
 
-pub type PgJournalEventStore = PgEventStore<JournalDomainEvent, MessagePack<JournalDomainEvent>>;
+pub type PgJournalEventStore = PgEventStore<JournalDomainEvent, JournalPayloads>;
 
 #[derive(Clone)]
 pub struct JournalEventStore {
     pub event_store: PgJournalEventStore,
+    pub payloads: JournalPayloads,
 }
 
 impl JournalEventStore {
-    pub async fn try_new(pool: PgPool) -> Result<Self, disintegrate_postgres::Error> {
-        let event_store =
-            PgEventStore::try_new(pool, MessagePack::<JournalDomainEvent>::default()).await?;
-        Ok(Self { event_store })
+    pub async fn try_new(
+        pool: PgPool,
+        payloads: JournalPayloads,
+    ) -> Result<Self, disintegrate_postgres::Error> {
+        let event_store = PgEventStore::try_new(pool, payloads.clone()).await?;
+
+        // the journal service reads its listener position on startup,
+        // so the listener tables must exist before the listener first runs
+        Migrator::new(event_store.clone()).init_listener().await?;
+
+        Ok(Self {
+            event_store,
+            payloads,
+        })
     }
 }
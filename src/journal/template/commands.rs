@@ -0,0 +1,86 @@
+use crate::StateType;
+use crate::journal::access::{AppendTransaction, JournalAccess};
+use crate::journal::template::TemplateId;
+use crate::journal::transaction::TransactionId;
+use crate::monkesto_error::{ErrorRedirect, OrRedirect};
+use crate::name::Name;
+use crate::time_provider::TimeProvider;
+use axum::extract::Path;
+use axum::extract::State;
+use axum::response::Redirect;
+use axum_extra::extract::Form;
+use serde::Deserialize;
+use std::str::FromStr;
+
+#[derive(Deserialize)]
+pub struct SaveTemplateForm {
+    name: String,
+    transaction_id: String,
+}
+
+/// saves a recorded transaction's entries under a name, for filling in the entry form later
+pub async fn save_template(
+    State(state): State<StateType>,
+    access: JournalAccess<AppendTransaction>,
+    Form(form): Form<SaveTemplateForm>,
+) -> Result<Redirect, ErrorRedirect> {
+    let journal_id = access.journal.id;
+    let callback_url = &format!(
+        "/journal/{}/transaction/{}",
+        journal_id, form.transaction_id
+    );
+
+    let transaction_id = TransactionId::from_str(&form.transaction_id).or_redirect(callback_url)?;
+    let name = Name::try_new(form.name).or_redirect(callback_url)?;
+
+    let (transaction, _, _) = state
+        .journal_service
+        .get_transaction(journal_id, transaction_id, &access.authority())
+        .await
+        .or_redirect(callback_url)?;
+
+    let event_id = state
+        .journal_service
+        .save_template(
+            state.ids.generate(),
+            journal_id,
+            name,
+            transaction.entries,
+            access.authority(),
+            state.clock.get_time(),
+        )
+        .await
+        .or_redirect(callback_url)?;
+
+    state.journal_service.wait_for(event_id).await;
+
+    Ok(Redirect::to(&format!(
+        "/journal/{}/transaction",
+        journal_id
+    )))
+}
+
+pub async fn delete_template(
+    State(state): State<StateType>,
+    access: JournalAccess<AppendTransaction>,
+    Path((id, template_id)): Path<(String, String)>,
+) -> Result<Redirect, ErrorRedirect> {
+    let callback_url = &format!("/journal/{}/transaction", id);
+
+    let template_id = TemplateId::from_str(&template_id).or_redirect(callback_url)?;
+
+    let event_id = state
+        .journal_service
+        .delete_template(
+            template_id,
+            access.journal.id,
+            access.authority(),
+            state.clock.get_time(),
+        )
+        .await
+        .or_redirect(callback_url)?;
+
+    state.journal_service.wait_for(event_id).await;
+
+    Ok(Redirect::to(callback_url))
+}
@@ -0,0 +1,276 @@
+pub mod commands;
+
+use axum::Router;
+use axum::routing::post;
+use axum_login::login_required;
+
+pub fn router() -> Router<crate::StateType> {
+    Router::new()
+        .route("/journal/{id}/template", post(commands::save_template))
+        .route(
+            "/journal/{id}/template/{template_id}/delete",
+            post(commands::delete_template),
+        )
+        .route_layer(login_required!(crate::BackendType, login_url = "/signin"))
+}
+
+use crate::authority::Authority;
+use crate::id;
+use crate::id::Ident;
+use crate::journal::domain::{JournalDomainEvent, TemplateEvent};
+use crate::journal::member::JournalMember;
+use crate::journal::transaction::{AllJournalAccounts, BalanceUpdate, TransactionValidationError};
+use crate::journal::{Journal, Permissions, check_permissions};
+use crate::journal::{JournalError, JournalId};
+use crate::name::Name;
+use crate::status::Status;
+use crate::time_provider::Timestamp;
+use crate::validation::FormState;
+use disintegrate::{Decision, StateMutate, StateQuery};
+use serde::Deserialize;
+use serde::Serialize;
+
+id!(TemplateId, Ident::new16());
+
+/// fills the new transaction form in with a template's entries, one row each
+pub fn prefill(entries: &[BalanceUpdate]) -> FormState {
+    let mut form = FormState::default();
+
+    for (i, entry) in entries.iter().enumerate() {
+        form.keep(&format!("account-{i}"), entry.account_id.to_string());
+        form.keep(
+            &format!("amount-{i}"),
+            format!("{}.{:02}", entry.amount / 100, entry.amount % 100),
+        );
+        form.keep(&format!("entry_type-{i}"), entry.entry_type.to_string());
+        if let Some(memo) = &entry.memo {
+            form.keep(&format!("memo-{i}"), memo.as_str());
+        }
+    }
+
+    form
+}
+
+#[derive(StateQuery, Clone, Default, Serialize, Deserialize)]
+#[state_query(TemplateEvent)]
+pub struct Template {
+    #[id]
+    template_id: TemplateId,
+    journal_id: JournalId,
+    status: Status,
+}
+
+impl Template {
+    fn new(template_id: TemplateId) -> Self {
+        Self {
+            template_id,
+            ..Default::default()
+        }
+    }
+}
+
+impl StateMutate for Template {
+    fn mutate(&mut self, event: Self::Event) {
+        match event {
+            TemplateEvent::TemplateSaved { journal_id, .. } => {
+                self.journal_id = journal_id;
+                self.status = Status::Valid;
+            }
+            TemplateEvent::TemplateDeleted { .. } => self.status = Status::Deleted,
+        }
+    }
+}
+
+pub struct SaveTemplate {
+    template_id: TemplateId,
+    journal_id: JournalId,
+    name: Name,
+    entries: Vec<BalanceUpdate>,
+    authority: Authority,
+    timestamp: Timestamp,
+}
+
+impl SaveTemplate {
+    pub fn new(
+        template_id: TemplateId,
+        journal_id: JournalId,
+        name: Name,
+        entries: Vec<BalanceUpdate>,
+        authority: Authority,
+        timestamp: Timestamp,
+    ) -> Self {
+        Self {
+            template_id,
+            journal_id,
+            name,
+            entries,
+            authority,
+            timestamp,
+        }
+    }
+}
+
+impl Decision for SaveTemplate {
+    type Event = JournalDomainEvent;
+    type StateQuery = (Template, AllJournalAccounts, Journal, JournalMember);
+    type Error = JournalError;
+
+    fn state_query(&self) -> Self::StateQuery {
+        (
+            Template::new(self.template_id),
+            AllJournalAccounts::new(self.journal_id),
+            Journal::new(self.journal_id),
+            JournalMember::new(
+                self.journal_id,
+                self.authority.user_id().unwrap_or_default(),
+            ),
+        )
+    }
+
+    fn process(
+        &self,
+        (template, accounts, journal, actor): &Self::StateQuery,
+    ) -> Result<Vec<Self::Event>, Self::Error> {
+        if template.status.found() {
+            return Err(JournalError::TemplateIdCollision(self.template_id));
+        }
+
+        if !journal.status.valid() {
+            return Err(JournalError::InvalidJournal(self.journal_id));
+        }
+
+        // templates only save typing, so anyone who can record the transaction can save one
+        check_permissions(
+            actor,
+            &self.authority,
+            journal,
+            Permissions::APPEND_TRANSACTION,
+        )?;
+
+        if self.entries.is_empty() {
+            return Err(JournalError::TransactionValidation(
+                TransactionValidationError::NoTransactionEntries,
+            ));
+        }
+
+        if let Some(entry) = self
+            .entries
+            .iter()
+            .find(|entry| !accounts.contains(&entry.account_id))
+        {
+            return Err(JournalError::InvalidAccount(entry.account_id));
+        }
+
+        Ok(vec![JournalDomainEvent::TemplateSaved {
+            template_id: self.template_id,
+            journal_id: self.journal_id,
+            name: self.name.clone(),
+            entries: self.entries.clone(),
+            authority: self.authority.clone(),
+            timestamp: self.timestamp,
+        }])
+    }
+}
+
+pub struct DeleteTemplate {
+    template_id: TemplateId,
+    journal_id: JournalId,
+    authority: Authority,
+    timestamp: Timestamp,
+}
+
+impl DeleteTemplate {
+    pub fn new(
+        template_id: TemplateId,
+        journal_id: JournalId,
+        authority: Authority,
+        timestamp: Timestamp,
+    ) -> Self {
+        Self {
+            template_id,
+            journal_id,
+            authority,
+            timestamp,
+        }
+    }
+}
+
+impl Decision for DeleteTemplate {
+    type Event = JournalDomainEvent;
+    type StateQuery = (Template, Journal, JournalMember);
+    type Error = JournalError;
+
+    fn state_query(&self) -> Self::StateQuery {
+        (
+            Template::new(self.template_id),
+            Journal::new(self.journal_id),
+            JournalMember::new(
+                self.journal_id,
+                self.authority.user_id().unwrap_or_default(),
+            ),
+        )
+    }
+
+    fn process(
+        &self,
+        (template, journal, actor): &Self::StateQuery,
+    ) -> Result<Vec<Self::Event>, Self::Error> {
+        if !template.status.valid() || template.journal_id != self.journal_id {
+            return Err(JournalError::InvalidTemplate(self.template_id));
+        }
+
+        if !journal.status.valid() {
+            return Err(JournalError::InvalidJournal(self.journal_id));
+        }
+
+        check_permissions(
+            actor,
+            &self.authority,
+            journal,
+            Permissions::APPEND_TRANSACTION,
+        )?;
+
+        Ok(vec![JournalDomainEvent::TemplateDeleted {
+            template_id: self.template_id,
+            journal_id: self.journal_id,
+            authority: self.authority.clone(),
+            timestamp: self.timestamp,
+        }])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::journal::account::AccountId;
+    use crate::journal::transaction::EntryType;
+    use std::str::FromStr;
+
+    #[test]
+    fn templates_fill_in_one_row_per_entry() {
+        let rent = AccountId::from_str("a000000000000001").unwrap();
+        let checking = AccountId::from_str("a000000000000002").unwrap();
+
+        let form = prefill(&[
+            BalanceUpdate {
+                account_id: rent,
+                amount: 150005,
+                entry_type: EntryType::Debit,
+                memo: None,
+            },
+            BalanceUpdate {
+                account_id: checking,
+                amount: 150005,
+                entry_type: EntryType::Credit,
+                memo: None,
+            },
+        ]);
+
+        assert_eq!(form.value("account-0"), Some(rent.to_string().as_str()));
+        assert_eq!(form.value("amount-0"), Some("1500.05"));
+        assert_eq!(form.value("entry_type-0"), Some("Dr"));
+        assert_eq!(form.value("account-1"), Some(checking.to_string().as_str()));
+        assert_eq!(form.value("entry_type-1"), Some("Cr"));
+        assert_eq!(form.value("account-2"), None);
+    }
+}
@@ -0,0 +1,327 @@
+use crate::authority::Authority;
+use crate::journal::access::satisfies;
+use crate::journal::domain::{JournalDomainEvent, TransactionEvent};
+use crate::journal::member::JournalMember;
+use crate::journal::transaction::{
+    AllJournalAccounts, BalanceUpdate, Transaction, TransactionId, TransactionSequence,
+    validate_entries,
+};
+use crate::journal::{Journal, Permissions, check_permissions};
+use crate::journal::{JournalError, JournalId};
+use crate::name::Name;
+use crate::status::Status;
+use crate::time_provider::Timestamp;
+use chrono::NaiveDate;
+use disintegrate::{Decision, StateMutate, StateQuery};
+use serde::Deserialize;
+use serde::Serialize;
+
+/// whether a member's transactions wait for approval. members who can record transactions
+/// themselves never need it, so submitting only applies to those who can do nothing more
+pub fn needs_approval(permissions: Permissions) -> bool {
+    permissions.contains(Permissions::SUBMIT_ONLY)
+        && !satisfies(permissions, Permissions::APPEND_TRANSACTION)
+}
+
+/// a submitted transaction, valid while it waits and deleted once it's approved or rejected
+#[derive(StateQuery, Clone, Default, Serialize, Deserialize)]
+#[state_query(TransactionEvent)]
+pub struct PendingTransaction {
+    #[id]
+    transaction_id: TransactionId,
+    journal_id: JournalId,
+    updates: Vec<BalanceUpdate>,
+    effective_date: NaiveDate,
+    payee: Option<Name>,
+    submitted_by: Option<Authority>,
+    status: Status,
+}
+
+impl PendingTransaction {
+    fn new(transaction_id: TransactionId) -> Self {
+        Self {
+            transaction_id,
+            ..Default::default()
+        }
+    }
+}
+
+impl StateMutate for PendingTransaction {
+    fn mutate(&mut self, event: Self::Event) {
+        match event {
+            TransactionEvent::TransactionSubmitted {
+                journal_id,
+                balance_updates,
+                effective_date,
+                payee,
+                authority,
+                ..
+            } => {
+                self.journal_id = journal_id;
+                self.updates = balance_updates;
+                self.effective_date = effective_date;
+                self.payee = payee;
+                self.submitted_by = Some(authority);
+                self.status = Status::Valid;
+            }
+            TransactionEvent::TransactionApproved { .. }
+            | TransactionEvent::TransactionRejected { .. } => self.status = Status::Deleted,
+            TransactionEvent::TransactionCreated { .. }
+            | TransactionEvent::TransactionDeleted { .. } => {}
+        }
+    }
+}
+
+pub struct SubmitTransaction {
+    transaction_id: TransactionId,
+    journal_id: JournalId,
+    entries: Vec<BalanceUpdate>,
+    effective_date: NaiveDate,
+    payee: Option<Name>,
+    authority: Authority,
+    timestamp: Timestamp,
+}
+
+impl SubmitTransaction {
+    pub fn new(
+        transaction_id: TransactionId,
+        journal_id: JournalId,
+        entries: Vec<BalanceUpdate>,
+        effective_date: NaiveDate,
+        payee: Option<Name>,
+        authority: Authority,
+        timestamp: Timestamp,
+    ) -> Self {
+        Self {
+            transaction_id,
+            journal_id,
+            entries,
+            effective_date,
+            payee,
+            authority,
+            timestamp,
+        }
+    }
+}
+
+impl Decision for SubmitTransaction {
+    type Event = JournalDomainEvent;
+    type StateQuery = (
+        Transaction,
+        PendingTransaction,
+        AllJournalAccounts,
+        Journal,
+        JournalMember,
+    );
+    type Error = JournalError;
+
+    fn state_query(&self) -> Self::StateQuery {
+        (
+            Transaction::new(self.transaction_id),
+            PendingTransaction::new(self.transaction_id),
+            AllJournalAccounts::new(self.journal_id),
+            Journal::new(self.journal_id),
+            JournalMember::new(
+                self.journal_id,
+                self.authority.user_id().unwrap_or_default(),
+            ),
+        )
+    }
+
+    fn process(
+        &self,
+        (transaction, pending, accounts, journal, actor): &Self::StateQuery,
+    ) -> Result<Vec<Self::Event>, Self::Error> {
+        if transaction.status.found() || pending.status.found() {
+            return Err(JournalError::TransactionIdCollision(self.transaction_id));
+        }
+
+        if !journal.status.valid() {
+            return Err(JournalError::InvalidJournal(self.journal_id));
+        }
+
+        validate_entries(&self.entries, accounts)?;
+
+        check_permissions(actor, &self.authority, journal, Permissions::SUBMIT_ONLY)?;
+
+        Ok(vec![JournalDomainEvent::TransactionSubmitted {
+            transaction_id: self.transaction_id,
+            journal_id: self.journal_id,
+            balance_updates: self.entries.clone(),
+            effective_date: self.effective_date,
+            payee: self.payee.clone(),
+            authority: self.authority.clone(),
+            timestamp: self.timestamp,
+        }])
+    }
+}
+
+pub struct ApproveTransaction {
+    transaction_id: TransactionId,
+    journal_id: JournalId,
+    authority: Authority,
+    timestamp: Timestamp,
+}
+
+impl ApproveTransaction {
+    pub fn new(
+        transaction_id: TransactionId,
+        journal_id: JournalId,
+        authority: Authority,
+        timestamp: Timestamp,
+    ) -> Self {
+        Self {
+            transaction_id,
+            journal_id,
+            authority,
+            timestamp,
+        }
+    }
+}
+
+impl Decision for ApproveTransaction {
+    type Event = JournalDomainEvent;
+    type StateQuery = (
+        PendingTransaction,
+        TransactionSequence,
+        AllJournalAccounts,
+        Journal,
+        JournalMember,
+    );
+    type Error = JournalError;
+
+    fn state_query(&self) -> Self::StateQuery {
+        (
+            PendingTransaction::new(self.transaction_id),
+            TransactionSequence::new(self.journal_id),
+            AllJournalAccounts::new(self.journal_id),
+            Journal::new(self.journal_id),
+            JournalMember::new(
+                self.journal_id,
+                self.authority.user_id().unwrap_or_default(),
+            ),
+        )
+    }
+
+    fn process(
+        &self,
+        (pending, sequence, accounts, journal, actor): &Self::StateQuery,
+    ) -> Result<Vec<Self::Event>, Self::Error> {
+        if !pending.status.valid() || pending.journal_id != self.journal_id {
+            return Err(JournalError::InvalidTransaction(self.transaction_id));
+        }
+
+        if !journal.status.valid() {
+            return Err(JournalError::InvalidJournal(self.journal_id));
+        }
+
+        check_permissions(actor, &self.authority, journal, Permissions::APPROVE)?;
+
+        // an account may have been deleted while the transaction waited
+        validate_entries(&pending.updates, accounts)?;
+
+        // the transaction is still the submitter's, and it's numbered when it's recorded
+        Ok(vec![
+            JournalDomainEvent::TransactionApproved {
+                transaction_id: self.transaction_id,
+                journal_id: self.journal_id,
+                authority: self.authority.clone(),
+                timestamp: self.timestamp,
+            },
+            JournalDomainEvent::TransactionCreated {
+                transaction_id: self.transaction_id,
+                journal_id: self.journal_id,
+                number: sequence.last + 1,
+                balance_updates: pending.updates.clone(),
+                effective_date: pending.effective_date,
+                reverses: None,
+                transfer: None,
+                payee: pending.payee.clone(),
+                authority: pending
+                    .submitted_by
+                    .clone()
+                    .unwrap_or_else(|| self.authority.clone()),
+                timestamp: self.timestamp,
+            },
+        ])
+    }
+}
+
+pub struct RejectTransaction {
+    transaction_id: TransactionId,
+    journal_id: JournalId,
+    authority: Authority,
+    timestamp: Timestamp,
+}
+
+impl RejectTransaction {
+    pub fn new(
+        transaction_id: TransactionId,
+        journal_id: JournalId,
+        authority: Authority,
+        timestamp: Timestamp,
+    ) -> Self {
+        Self {
+            transaction_id,
+            journal_id,
+            authority,
+            timestamp,
+        }
+    }
+}
+
+impl Decision for RejectTransaction {
+    type Event = JournalDomainEvent;
+    type StateQuery = (PendingTransaction, Journal, JournalMember);
+    type Error = JournalError;
+
+    fn state_query(&self) -> Self::StateQuery {
+        (
+            PendingTransaction::new(self.transaction_id),
+            Journal::new(self.journal_id),
+            JournalMember::new(
+                self.journal_id,
+                self.authority.user_id().unwrap_or_default(),
+            ),
+        )
+    }
+
+    fn process(
+        &self,
+        (pending, journal, actor): &Self::StateQuery,
+    ) -> Result<Vec<Self::Event>, Self::Error> {
+        if !pending.status.valid() || pending.journal_id != self.journal_id {
+            return Err(JournalError::InvalidTransaction(self.transaction_id));
+        }
+
+        if !journal.status.valid() {
+            return Err(JournalError::InvalidJournal(self.journal_id));
+        }
+
+        check_permissions(actor, &self.authority, journal, Permissions::APPROVE)?;
+
+        Ok(vec![JournalDomainEvent::TransactionRejected {
+            transaction_id: self.transaction_id,
+            journal_id: self.journal_id,
+            authority: self.authority.clone(),
+            timestamp: self.timestamp,
+        }])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn only_members_who_cant_record_wait_for_approval() {
+        let submitter = Permissions::READ | Permissions::SUBMIT_ONLY;
+
+        assert!(needs_approval(submitter));
+        assert!(!needs_approval(submitter | Permissions::APPEND_TRANSACTION));
+        assert!(!needs_approval(Permissions::all()));
+        assert!(!needs_approval(
+            Permissions::READ | Permissions::APPEND_TRANSACTION
+        ));
+    }
+}
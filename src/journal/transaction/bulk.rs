@@ -0,0 +1,731 @@
+use crate::StateType;
+use crate::authority::Authority;
+use crate::id::IdGenerator;
+use crate::journal::access::{AppendTransaction, JournalAccess};
+use crate::journal::account::AccountId;
+use crate::journal::json::CreatedTransactionsJson;
+use crate::journal::layout;
+use crate::journal::rule::RuleMatcher;
+use crate::journal::service::{AccountState, JournalState};
+use crate::journal::transaction::{
+    BalanceUpdate, BatchRowError, EntryType, NewTransaction, TransactionId,
+    TransactionValidationError, parse_effective_date,
+};
+use crate::journal::{JournalError, JournalId};
+use crate::monkesto_error::{MonkestoError, MonkestoResult};
+use crate::parse;
+use crate::time_provider::{TimeProvider, Timestamp};
+use axum::extract::rejection::JsonRejection;
+use axum::extract::{FromRequest, Request, State};
+use axum::http::StatusCode;
+use axum::http::header::CONTENT_TYPE;
+use axum::response::{IntoResponse, Redirect, Response};
+use axum::{Form, Json};
+use maud::{Markup, html};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::str::FromStr;
+
+/// a transaction submitted to the bulk entry api
+#[derive(Serialize, Deserialize)]
+pub struct BatchTransaction {
+    /// formatted as YYYY-MM-DD
+    pub effective_date: String,
+    pub entries: Vec<BatchEntry>,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct BatchEntry {
+    pub account_id: String,
+    /// in dollars, with at most two decimal places
+    pub amount: String,
+    /// Dr or Cr
+    pub entry_type: String,
+}
+
+#[derive(Deserialize)]
+pub struct BulkForm {
+    csv: String,
+}
+
+fn parse_entry(
+    account_id: AccountId,
+    amount: Option<&str>,
+    entry_type: Option<&str>,
+) -> Result<BalanceUpdate, JournalError> {
+    let amount = amount
+        .filter(|s| !s.is_empty())
+        .ok_or(JournalError::TransactionValidation(
+            TransactionValidationError::MissingEntryAmount,
+        ))?;
+
+    let entry_type =
+        entry_type
+            .filter(|s| !s.is_empty())
+            .ok_or(JournalError::TransactionValidation(
+                TransactionValidationError::MissingEntryType,
+            ))?;
+
+    Ok(BalanceUpdate {
+        account_id,
+        amount: parse::amount::amount(amount)?,
+        entry_type: EntryType::from_str(entry_type)?,
+        memo: None,
+    })
+}
+
+/// parses the transactions submitted to the api, where each transaction is a row
+fn parse_json(
+    transactions: Vec<BatchTransaction>,
+    ids: &IdGenerator,
+) -> Result<Vec<NewTransaction>, JournalError> {
+    let mut parsed = Vec::with_capacity(transactions.len());
+    let mut errors = Vec::new();
+
+    for (row, transaction) in (1..).zip(transactions) {
+        let result = parse_effective_date(&transaction.effective_date).and_then(|effective_date| {
+            let entries = transaction
+                .entries
+                .iter()
+                .map(|entry| {
+                    parse_entry(
+                        AccountId::from_str(&entry.account_id)?,
+                        Some(&entry.amount),
+                        Some(&entry.entry_type),
+                    )
+                })
+                .collect::<Result<_, _>>()?;
+
+            Ok(NewTransaction {
+                transaction_id: ids.generate(),
+                entries,
+                effective_date,
+                payee: None,
+            })
+        });
+
+        match result {
+            Ok(transaction) => parsed.push(transaction),
+            Err(error) => errors.push(BatchRowError { row, error }),
+        }
+    }
+
+    if !errors.is_empty() {
+        return Err(JournalError::InvalidBatch(errors));
+    }
+
+    Ok(parsed)
+}
+
+/// transactions parsed from a csv paste, along with the line each one started on
+#[derive(Debug)]
+struct CsvBatch {
+    transactions: Vec<NewTransaction>,
+    lines: Vec<u64>,
+}
+
+impl CsvBatch {
+    /// renumbers errors from the decision, which counts transactions, to count csv lines instead
+    fn number_by_line(&self, error: JournalError) -> JournalError {
+        match error {
+            JournalError::InvalidBatch(rows) => JournalError::InvalidBatch(
+                rows.into_iter()
+                    .map(|row| BatchRowError {
+                        row: self.lines[row.row as usize - 1],
+                        error: row.error,
+                    })
+                    .collect(),
+            ),
+            error => error,
+        }
+    }
+}
+
+/// a line whose blank account was chosen by an import rule, or by the journal's defaults when
+/// there's no condition
+#[derive(Debug, PartialEq)]
+struct AppliedRule {
+    row: u64,
+    account: String,
+    condition: Option<String>,
+}
+
+/// an import rule ready to check lines against, with the name of the account it chooses
+struct ImportRule {
+    matcher: RuleMatcher,
+    account: String,
+    condition: String,
+}
+
+/// the names of the journal's default accounts, for the blank accounts no rule chose. debits
+/// go to the expense account and credits come from the cash account
+#[derive(Default)]
+struct DefaultAccounts {
+    cash: Option<String>,
+    expense: Option<String>,
+}
+
+impl DefaultAccounts {
+    /// defaults whose account has since been deleted are skipped
+    fn new(journal: &JournalState, accounts: &[(AccountState, Authority, Timestamp)]) -> Self {
+        let name = |default: Option<AccountId>| {
+            accounts
+                .iter()
+                .find(|(account, _, _)| Some(account.id) == default)
+                .map(|(account, _, _)| account.name.to_string())
+        };
+
+        Self {
+            cash: name(journal.default_cash_account),
+            expense: name(journal.default_expense_account),
+        }
+    }
+
+    fn for_entry(&self, entry_type: &str) -> Option<&String> {
+        match EntryType::from_str(entry_type).ok()? {
+            EntryType::Debit => self.expense.as_ref(),
+            EntryType::Credit => self.cash.as_ref(),
+        }
+    }
+}
+
+/// fills in the blank accounts of lines whose description, an optional sixth column, matches
+/// one of the journal's rules, and then the rest from the journal's defaults. the rewritten
+/// csv is returned when any account was chosen, so it can be reviewed before the batch is created
+fn apply_rules(
+    csv: &str,
+    rules: &[ImportRule],
+    defaults: &DefaultAccounts,
+) -> Option<(String, Vec<AppliedRule>)> {
+    let mut reader = csv::ReaderBuilder::new()
+        .has_headers(false)
+        .flexible(true)
+        .trim(csv::Trim::All)
+        .from_reader(csv.as_bytes());
+    let mut writer = csv::WriterBuilder::new()
+        .flexible(true)
+        .from_writer(Vec::new());
+
+    let mut applied = Vec::new();
+
+    for (index, record) in reader.records().flatten().enumerate() {
+        let row = record
+            .position()
+            .map_or(index as u64 + 1, |position| position.line());
+        let mut fields: Vec<String> = record.iter().map(ToString::to_string).collect();
+
+        let field = |i: usize| record.get(i).unwrap_or_default();
+        if field(2).is_empty()
+            && !field(5).is_empty()
+            && let Ok(amount) = parse::amount::amount(field(3))
+            && let Some(rule) = rules
+                .iter()
+                .find(|rule| rule.matcher.matches(field(5), amount))
+        {
+            fields[2] = rule.account.clone();
+            applied.push(AppliedRule {
+                row,
+                account: rule.account.clone(),
+                condition: Some(rule.condition.clone()),
+            });
+        } else if field(2).is_empty()
+            && !field(3).is_empty()
+            && let Some(account) = defaults.for_entry(field(4))
+        {
+            fields[2] = account.clone();
+            applied.push(AppliedRule {
+                row,
+                account: account.clone(),
+                condition: None,
+            });
+        }
+
+        writer.write_record(&fields).ok()?;
+    }
+
+    if applied.is_empty() {
+        return None;
+    }
+
+    let csv = String::from_utf8(writer.into_inner().ok()?).ok()?;
+    Some((csv, applied))
+}
+
+/// parses `reference,date,account,amount,type` lines, where the lines that share a reference
+/// are the entries of one transaction. accounts are matched by name, ignoring case.
+/// the first line of each transaction sets its date, so later lines can leave it blank
+fn parse_csv(
+    csv: &str,
+    accounts: &HashMap<String, AccountId>,
+    ids: &IdGenerator,
+) -> Result<CsvBatch, JournalError> {
+    let mut reader = csv::ReaderBuilder::new()
+        .has_headers(false)
+        .flexible(true)
+        .trim(csv::Trim::All)
+        .from_reader(csv.as_bytes());
+
+    let mut references: HashMap<String, usize> = HashMap::new();
+    let mut batch = CsvBatch {
+        transactions: Vec::new(),
+        lines: Vec::new(),
+    };
+    let mut errors = Vec::new();
+
+    // reading from a str can't fail, since it's already utf-8 and rows may have any length
+    for (index, record) in reader.records().flatten().enumerate() {
+        let row = record
+            .position()
+            .map_or(index as u64 + 1, |position| position.line());
+
+        let field = |i: usize| record.get(i).unwrap_or_default();
+
+        // a header is allowed, but not required
+        if row == 1 && field(1).eq_ignore_ascii_case("date") {
+            continue;
+        }
+
+        if record.iter().all(str::is_empty) {
+            continue;
+        }
+
+        let entry = accounts
+            .get(&field(2).to_lowercase())
+            .ok_or_else(|| {
+                JournalError::TransactionValidation(TransactionValidationError::UnknownAccount(
+                    field(2).to_string(),
+                ))
+            })
+            .and_then(|account_id| parse_entry(*account_id, record.get(3), record.get(4)));
+
+        let result = match references.get(field(0)) {
+            Some(index) => entry.map(|entry| batch.transactions[*index].entries.push(entry)),
+            None => parse_effective_date(field(1)).and_then(|effective_date| {
+                let entry = entry?;
+                references.insert(field(0).to_string(), batch.transactions.len());
+                batch.transactions.push(NewTransaction {
+                    transaction_id: ids.generate(),
+                    entries: vec![entry],
+                    effective_date,
+                    payee: None,
+                });
+                batch.lines.push(row);
+                Ok(())
+            }),
+        };
+
+        if let Err(error) = result {
+            errors.push(BatchRowError { row, error });
+        }
+    }
+
+    if !errors.is_empty() {
+        return Err(JournalError::InvalidBatch(errors));
+    }
+
+    Ok(batch)
+}
+
+async fn create_transactions(
+    state: &StateType,
+    journal_id: JournalId,
+    transactions: Vec<NewTransaction>,
+    authority: Authority,
+) -> Result<Vec<TransactionId>, MonkestoError> {
+    let transaction_ids = transactions.iter().map(|t| t.transaction_id).collect();
+
+    let event_id = state
+        .journal_service
+        .create_transactions(journal_id, transactions, authority, state.clock.get_time())
+        .await?;
+
+    state.journal_service.wait_for(event_id).await;
+
+    Ok(transaction_ids)
+}
+
+fn bulk_page(
+    journal: &JournalState,
+    csv: &str,
+    error: Option<&MonkestoError>,
+    applied: &[AppliedRule],
+) -> Markup {
+    let id = journal.id;
+
+    let content = html! {
+        div class="flex flex-col gap-6 mx-auto w-full max-w-4xl" {
+            div class="bg-white dark:bg-gray-800 border border-gray-200 dark:border-gray-700 rounded-xl p-6" {
+                h3 class="text-lg font-semibold text-gray-900 dark:text-white mb-2" {
+                    "Bulk Entry"
+                }
+                p class="text-sm text-gray-500 dark:text-gray-400 mb-6" {
+                    "Paste one entry per line as "
+                    code { "reference,date,account,amount,type" }
+                    ". Lines with the same reference are entries of the same transaction, and the type is Dr or Cr. "
+                    "Every transaction is created, or none are. "
+                    "A line can leave its account blank and add a description as a sixth column to let the journal's "
+                    a href=(format!("/journal/{}/rule", id)) class="font-medium text-indigo-600 hover:text-indigo-500 dark:text-indigo-400 dark:hover:text-indigo-300" { "import rules" }
+                    " choose the account. "
+                    "Moving from ledger-cli or beancount? "
+                    a href=(format!("/journal/{}/transaction/plaintext", id)) class="font-medium text-indigo-600 hover:text-indigo-500 dark:text-indigo-400 dark:hover:text-indigo-300" { "Import the file" }
+                    " instead. Have a QIF or QFX statement from your bank? "
+                    a href=(format!("/journal/{}/transaction/statement", id)) class="font-medium text-indigo-600 hover:text-indigo-500 dark:text-indigo-400 dark:hover:text-indigo-300" { "Import the statement" }
+                    "."
+                }
+
+                @if !applied.is_empty() {
+                    div class="mb-6 p-4 bg-indigo-50 dark:bg-indigo-950 rounded-lg text-sm text-indigo-900 dark:text-indigo-200" {
+                        p class="font-medium mb-2" {
+                            "Rules and the journal's default accounts chose the accounts below. Check them, then create the transactions."
+                        }
+                        ul class="space-y-1" {
+                            @for rule in applied {
+                                li {
+                                    "Line " (rule.row) ": "
+                                    @match &rule.condition {
+                                        Some(condition) => {
+                                            span class="px-1.5 py-0.5 text-xs font-semibold rounded bg-indigo-100 text-indigo-700 dark:bg-indigo-900 dark:text-indigo-300" { "rule applied" }
+                                            " " (rule.account) ", because the " (condition)
+                                        },
+                                        None => {
+                                            span class="px-1.5 py-0.5 text-xs font-semibold rounded bg-gray-100 text-gray-700 dark:bg-gray-700 dark:text-gray-300" { "default" }
+                                            " " (rule.account) ", the journal's default for lines no rule matched"
+                                        },
+                                    }
+                                }
+                            }
+                        }
+                    }
+                }
+
+                form method="post" action=(format!("/journal/{}/transaction/bulk", id)) class="space-y-6" {
+                    textarea
+                        name="csv"
+                        rows="16"
+                        required
+                        placeholder="JE-1,2026-01-31,Rent Expense,1200.00,Dr\nJE-1,,Cash,1200.00,Cr"
+                        class="w-full rounded-md border border-gray-300 dark:border-gray-600 bg-white dark:bg-gray-800 px-3 py-2 font-mono text-sm text-gray-900 dark:text-white placeholder:text-gray-400 dark:placeholder:text-gray-500 focus:border-indigo-500 focus:ring-indigo-500 dark:focus:border-indigo-400" {
+                        (csv)
+                    }
+
+                    div class="flex justify-end pt-4 border-t border-gray-200 dark:border-gray-600" {
+                        button class="px-6 py-2 bg-indigo-600 text-white font-medium rounded-md hover:bg-indigo-700 focus:outline-none focus:ring-2 focus:ring-indigo-500 focus:ring-offset-2 dark:bg-indigo-500 dark:hover:bg-indigo-400 dark:focus:ring-indigo-400 dark:ring-offset-gray-800" type="submit" {
+                            "Create Transactions"
+                        }
+                    }
+                }
+
+                @match error {
+                    Some(MonkestoError::Journal(JournalError::InvalidBatch(rows))) => {
+                        ul class="mt-6 space-y-1 text-sm text-red-600 dark:text-red-400" {
+                            @for row in rows {
+                                li { "Line " (row.row) ": " (row.error) }
+                            }
+                        }
+                    },
+                    Some(e) => {
+                        p class="mt-6 text-sm text-red-600 dark:text-red-400" { (e) }
+                    },
+                    None => {}
+                }
+            }
+        }
+    };
+
+    layout::journal_layout(journal, content)
+}
+
+/// loads the journal's rules, naming the accounts they choose. rules whose account has since
+/// been deleted are skipped
+async fn import_rules(
+    state: &StateType,
+    journal_id: JournalId,
+    authority: &Authority,
+    accounts: &[(AccountState, Authority, Timestamp)],
+) -> MonkestoResult<Vec<ImportRule>> {
+    Ok(state
+        .journal_service
+        .list_import_rules(journal_id, authority)
+        .await?
+        .into_iter()
+        .filter_map(|rule| {
+            let (account, _, _) = accounts
+                .iter()
+                .find(|(account, _, _)| account.id == rule.account_id)?;
+            Some(ImportRule {
+                matcher: rule.condition.matcher().ok()?,
+                account: account.name.to_string(),
+                condition: rule.condition.to_string(),
+            })
+        })
+        .collect())
+}
+
+/// renders the bulk entry page filled in with lines from somewhere other than a paste, with the
+/// journal's rules already applied, so they go through the same review before being created
+pub(crate) async fn review_page(
+    state: &StateType,
+    journal_id: JournalId,
+    csv: &str,
+    authority: &Authority,
+) -> MonkestoResult<Markup> {
+    let (journal, _, _) = state
+        .journal_service
+        .get_journal(journal_id, authority)
+        .await?;
+    let accounts = state
+        .journal_service
+        .list_journal_accounts(journal_id, authority)
+        .await?;
+    let rules = import_rules(state, journal_id, authority, &accounts).await?;
+    let defaults = DefaultAccounts::new(&journal, &accounts);
+
+    Ok(match apply_rules(csv, &rules, &defaults) {
+        Some((csv, applied)) => bulk_page(&journal, &csv, None, &applied),
+        None => bulk_page(&journal, csv, None, &[]),
+    })
+}
+
+pub async fn bulk_entry_page(access: JournalAccess) -> Markup {
+    bulk_page(&access.journal, "", None, &[])
+}
+
+/// accepts either a csv paste from the bulk entry form or a json array of transactions.
+/// the form gets its page back with the errors for each line, while api clients get json
+pub async fn bulk_transact(
+    State(state): State<StateType>,
+    access: JournalAccess<AppendTransaction>,
+    request: Request,
+) -> Result<Response, Redirect> {
+    let authority = access.authority();
+    let journal_id = access.journal.id;
+
+    let is_json = request
+        .headers()
+        .get(CONTENT_TYPE)
+        .and_then(|content_type| content_type.to_str().ok())
+        .is_some_and(|content_type| content_type.starts_with("application/json"));
+
+    if is_json {
+        let result: MonkestoResult<Result<Vec<TransactionId>, JsonRejection>> = async {
+            let transactions =
+                match Json::<Vec<BatchTransaction>>::from_request(request, &state).await {
+                    Ok(Json(transactions)) => transactions,
+                    Err(rejection) => return Ok(Err(rejection)),
+                };
+
+            Ok(Ok(create_transactions(
+                &state,
+                journal_id,
+                parse_json(transactions, &state.ids)?,
+                authority,
+            )
+            .await?))
+        }
+        .await;
+
+        return Ok(match result {
+            // the body couldn't be deserialized at all, which axum already explains
+            Ok(Err(rejection)) => rejection.into_response(),
+            Ok(Ok(transaction_ids)) => (
+                StatusCode::CREATED,
+                Json(CreatedTransactionsJson {
+                    transaction_ids: transaction_ids.iter().map(ToString::to_string).collect(),
+                }),
+            )
+                .into_response(),
+            Err(e) => e.into_response(),
+        });
+    }
+
+    let Ok(Form(form)) = Form::<BulkForm>::from_request(request, &state).await else {
+        return Ok(StatusCode::BAD_REQUEST.into_response());
+    };
+
+    let result: MonkestoResult<Option<(String, Vec<AppliedRule>)>> = async {
+        let accounts = state
+            .journal_service
+            .list_journal_accounts(journal_id, &authority)
+            .await?;
+
+        let rules = import_rules(&state, journal_id, &authority, &accounts).await?;
+        let defaults = DefaultAccounts::new(&access.journal, &accounts);
+
+        if let Some(review) = apply_rules(&form.csv, &rules, &defaults) {
+            return Ok(Some(review));
+        }
+
+        let accounts = accounts
+            .into_iter()
+            .map(|(account, _, _)| (account.name.as_ref().to_lowercase(), account.id))
+            .collect();
+
+        let batch = parse_csv(&form.csv, &accounts, &state.ids)?;
+
+        create_transactions(
+            &state,
+            journal_id,
+            batch.transactions.clone(),
+            authority.clone(),
+        )
+        .await
+        .map_err(|e| match e {
+            MonkestoError::Journal(e) => MonkestoError::Journal(batch.number_by_line(e)),
+            e => e,
+        })?;
+
+        Ok(None)
+    }
+    .await;
+
+    match result {
+        Ok(None) => {
+            Ok(Redirect::to(&format!("/journal/{}/transaction", journal_id)).into_response())
+        }
+        Ok(Some((csv, applied))) => {
+            Ok(bulk_page(&access.journal, &csv, None, &applied).into_response())
+        }
+        Err(e) => {
+            let page = bulk_page(&access.journal, &form.csv, Some(&e), &[]);
+            Ok((StatusCode::UNPROCESSABLE_ENTITY, page).into_response())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::journal::rule::RuleCondition;
+
+    fn accounts() -> (AccountId, AccountId, HashMap<String, AccountId>) {
+        let cash = AccountId::new();
+        let rent = AccountId::new();
+        let accounts = HashMap::from([("cash".to_string(), cash), ("rent".to_string(), rent)]);
+        (cash, rent, accounts)
+    }
+
+    #[test]
+    fn lines_are_grouped_into_transactions_by_reference() {
+        let (cash, rent, accounts) = accounts();
+        let csv = "reference,date,account,amount,type\n\
+                   JE-1,2026-01-31,Rent,1200.00,Dr\n\
+                   JE-2,2026-02-28,Rent,1200,Dr\n\
+                   JE-1,,Cash,1200.00,Cr\n\
+                   JE-2,,CASH,1200,Cr\n";
+
+        let batch =
+            parse_csv(csv, &accounts, &IdGenerator::default()).expect("failed to parse the batch");
+
+        assert_eq!(batch.lines, vec![2, 3]);
+        assert_eq!(
+            batch.transactions[0].entries,
+            vec![
+                BalanceUpdate {
+                    account_id: rent,
+                    amount: 120000,
+                    entry_type: EntryType::Debit,
+                    memo: None,
+                },
+                BalanceUpdate {
+                    account_id: cash,
+                    amount: 120000,
+                    entry_type: EntryType::Credit,
+                    memo: None,
+                },
+            ]
+        );
+        assert_eq!(batch.transactions[1].entries.len(), 2);
+    }
+
+    #[test]
+    fn every_invalid_line_is_reported() {
+        let (_, _, accounts) = accounts();
+        let csv = "JE-1,2026-01-31,Rent,12.00,Dr\n\
+                   JE-1,,Savings,12.00,Cr\n\
+                   JE-2,2026-13-01,Rent,5,Dr\n\
+                   JE-3,2026-01-31,Cash,-5,Cr\n";
+
+        let Err(JournalError::InvalidBatch(rows)) =
+            parse_csv(csv, &accounts, &IdGenerator::default())
+        else {
+            panic!("the batch should have been rejected");
+        };
+
+        assert_eq!(
+            rows.iter().map(|row| row.row).collect::<Vec<_>>(),
+            vec![2, 3, 4]
+        );
+        assert_eq!(
+            rows[0].error,
+            JournalError::TransactionValidation(TransactionValidationError::UnknownAccount(
+                "Savings".to_string()
+            ))
+        );
+    }
+
+    #[test]
+    fn rules_fill_in_blank_accounts_by_description() {
+        let rules = vec![ImportRule {
+            matcher: RuleCondition {
+                pattern: "landlord".to_string(),
+                min_amount: None,
+                max_amount: Some(200000),
+            }
+            .matcher()
+            .expect("failed to build the matcher"),
+            account: "Rent".to_string(),
+            condition: "pattern".to_string(),
+        }];
+        let defaults = DefaultAccounts::default();
+        let csv = "JE-1,2026-01-31,,1200.00,Dr,Paid the LANDLORD\n\
+                   JE-1,,Cash,1200.00,Cr\n\
+                   JE-2,2026-01-31,,3000.00,Dr,Paid the landlord\n";
+
+        let (csv, applied) =
+            apply_rules(csv, &rules, &defaults).expect("a rule should have applied");
+
+        assert_eq!(
+            applied,
+            vec![AppliedRule {
+                row: 1,
+                account: "Rent".to_string(),
+                condition: Some("pattern".to_string()),
+            }]
+        );
+        assert!(csv.starts_with("JE-1,2026-01-31,Rent,1200.00,Dr,Paid the LANDLORD\n"));
+        assert!(csv.contains("JE-2,2026-01-31,,3000.00,Dr,Paid the landlord\n"));
+        assert!(
+            apply_rules(
+                "JE-1,2026-01-31,Rent,12.00,Dr,landlord\n",
+                &rules,
+                &defaults
+            )
+            .is_none()
+        );
+    }
+
+    #[test]
+    fn blank_accounts_no_rule_chose_are_the_journal_defaults() {
+        let defaults = DefaultAccounts {
+            cash: Some("Clearing".to_string()),
+            expense: Some("Sundry".to_string()),
+        };
+        let csv = "FEED-1,2026-01-31,Checking,45.20,Cr\n\
+                   FEED-1,,,45.20,Dr,Corner shop\n\
+                   FEED-2,2026-02-01,Checking,100.00,Dr\n\
+                   FEED-2,,,100.00,Cr,Refund\n";
+
+        let (csv, applied) =
+            apply_rules(csv, &[], &defaults).expect("the defaults should have applied");
+
+        assert_eq!(
+            applied
+                .iter()
+                .map(|rule| (rule.row, rule.account.as_str(), rule.condition.as_deref()))
+                .collect::<Vec<_>>(),
+            vec![(2, "Sundry", None), (4, "Clearing", None)]
+        );
+        assert!(csv.contains("FEED-1,,Sundry,45.20,Dr,Corner shop\n"));
+        assert!(apply_rules(csv.as_str(), &[], &DefaultAccounts::default()).is_none());
+    }
+}
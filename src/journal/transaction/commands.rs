@@ -1,21 +1,29 @@
 use crate::BackendType;
 use crate::StateType;
 use crate::authn::get_user;
-use crate::authority::Actor;
-use crate::authority::Authority;
+use crate::journal::JournalError;
+use crate::journal::access::{AppendTransaction, JournalAccess, Submit};
 use crate::journal::account::AccountId;
-use crate::journal::transaction::{BalanceUpdate, TransactionId};
+use crate::journal::transaction::approval::needs_approval;
+use crate::journal::transaction::views::{
+    TotalsRange, journal_accounts, transaction_cards, transaction_list,
+};
+use crate::journal::transaction::{
+    BalanceUpdate, TransactionId, format_voucher_number, parse_amount, parse_effective_date,
+    parse_memo,
+};
 use crate::journal::transaction::{EntryType, TransactionValidationError};
-use crate::journal::{JournalError, JournalId};
-use crate::monkesto_error::OrRedirect;
-use crate::time_provider::{DefaultTimeProvider, TimeProvider};
+use crate::journal::undo::{self, Compensation};
+use crate::monkesto_error::{ErrorRedirect, FormRejection, OrRedirect};
+use crate::name::Name;
+use crate::negotiate::WantsPartial;
+use crate::time_provider::TimeProvider;
+use crate::validation::FormState;
 use axum::extract::Path;
 use axum::extract::State;
-use axum::response::Redirect;
+use axum::response::{IntoResponse, Redirect, Response};
 use axum_extra::extract::Form;
 use axum_login::AuthSession;
-use rust_decimal::dec;
-use rust_decimal::prelude::*;
 use serde::Deserialize;
 use std::str::FromStr;
 
@@ -24,108 +32,227 @@ pub struct TransactForm {
     account: Vec<String>,
     amount: Vec<String>,
     entry_type: Vec<String>,
+    /// forms rendered before entries had memos don't send any
+    #[serde(default)]
+    memo: Vec<String>,
+    effective_date: String,
+    #[serde(default)]
+    payee: String,
 }
 
 pub async fn transact(
     State(state): State<StateType>,
     session: AuthSession<BackendType>,
-    Path(id): Path<String>,
+    access: JournalAccess<Submit>,
+    WantsPartial(partial): WantsPartial,
     Form(form): Form<TransactForm>,
-) -> Result<Redirect, Redirect> {
-    let callback_url = &format!("/journal/{}/transaction", id);
-
-    let journal_id = JournalId::from_str(&id).or_redirect(callback_url)?;
+) -> Result<Response, FormRejection> {
+    let callback_url = &format!("/journal/{}/transaction", access.journal.id);
 
     let user = get_user(session)?;
-    let user_authority = Authority::Direct(Actor::User(user.id));
+
+    let mut form_state = FormState::default();
+    form_state.keep("effective_date", form.effective_date.as_str());
+
+    let effective_date =
+        form_state.check("effective_date", parse_effective_date(&form.effective_date));
+
+    form_state.keep("payee", form.payee.as_str());
+    let payee = form_state.check(
+        "payee",
+        Some(form.payee.trim())
+            .filter(|payee| !payee.is_empty())
+            .map(|payee| Name::try_new(payee.to_string()))
+            .transpose(),
+    );
 
     let mut updates = Vec::new();
 
     if form.account.is_empty() {
-        return Err(JournalError::TransactionValidation(
+        form_state.fail(JournalError::TransactionValidation(
             TransactionValidationError::NoTransactionEntries,
-        ))
-        .or_redirect(callback_url);
+        ));
     }
 
     for (idx, acc_id_str) in form.account.iter().enumerate() {
+        let amount_field = format!("amount-{idx}");
+        let entry_type_field = format!("entry_type-{idx}");
+        let memo_field = format!("memo-{idx}");
+
+        form_state.keep(&format!("account-{idx}"), acc_id_str.as_str());
+        if let Some(amount) = form.amount.get(idx) {
+            form_state.keep(&amount_field, amount.as_str());
+        }
+        if let Some(entry_type) = form.entry_type.get(idx) {
+            form_state.keep(&entry_type_field, entry_type.as_str());
+        }
+        if let Some(memo) = form.memo.get(idx) {
+            form_state.keep(&memo_field, memo.as_str());
+        }
+
         // if the id isn't valid, assume that the user just didn't select an account
         if let Ok(acc_id) = AccountId::from_str(acc_id_str) {
-            let str_decimal_amt = form
-                .amount
-                .get(idx)
-                .ok_or(JournalError::TransactionValidation(
-                    TransactionValidationError::MissingEntryAmount,
-                ))
-                .or_redirect(callback_url)?;
-
-            let dec_amt = Decimal::from_str(str_decimal_amt)
-                .map_err(|_| {
-                    JournalError::TransactionValidation(TransactionValidationError::ParseDecimal(
-                        str_decimal_amt.to_string(),
+            let amount = form_state.check(
+                &amount_field,
+                form.amount
+                    .get(idx)
+                    .ok_or(JournalError::TransactionValidation(
+                        TransactionValidationError::MissingEntryAmount,
                     ))
-                })
-                .or_redirect(callback_url)?
-                * dec!(100);
-
-            // this will reject inputs with partial cent values
-            // this should not be possible unless a user uses the
-            //  inspector tool to change their HTML
-            if !dec_amt.is_integer() {
-                return Err(JournalError::TransactionValidation(
-                    TransactionValidationError::PartialCentValue(str_decimal_amt.to_string()),
-                ))
-                .or_redirect(callback_url);
-            } else {
-                let amt = dec_amt
-                    .to_i64()
-                    .ok_or_else(|| {
-                        JournalError::TransactionValidation(TransactionValidationError::OutOfRange(
-                            str_decimal_amt.to_string(),
-                        ))
-                    })
-                    .or_redirect(callback_url)?;
-
-                // error when the amount is below zero to prevent confusion with the credit/debit selector
-                if amt <= 0 {
-                    return Err(JournalError::TransactionValidation(
-                        TransactionValidationError::NegativeEntryAmount(dec_amt.to_string()),
+                    .and_then(|amount| parse_amount(amount)),
+            );
+
+            let entry_type = form_state.check(
+                &entry_type_field,
+                form.entry_type
+                    .get(idx)
+                    .ok_or(JournalError::TransactionValidation(
+                        TransactionValidationError::MissingEntryType,
                     ))
-                    .or_redirect(callback_url);
-                }
+                    .and_then(|entry_type| EntryType::from_str(entry_type)),
+            );
 
-                let entry_type = EntryType::from_str(
-                    form.entry_type
-                        .get(idx)
-                        .ok_or(JournalError::TransactionValidation(
-                            TransactionValidationError::MissingEntryType,
-                        ))
-                        .or_redirect(callback_url)?,
-                )
-                .or_redirect(callback_url)?;
+            let memo = form_state.check(
+                &memo_field,
+                parse_memo(form.memo.get(idx).map_or("", String::as_str)),
+            );
 
+            if let (Some(amount), Some(entry_type), Some(memo)) = (amount, entry_type, memo) {
                 updates.push(BalanceUpdate {
                     account_id: acc_id,
-                    amount: amt as u64,
+                    amount,
                     entry_type,
+                    memo,
                 });
             }
         }
     }
 
+    if let (Some(effective_date), Some(payee)) = (effective_date, payee)
+        && form_state.is_valid()
+    {
+        // members who can only submit leave their transactions in the approval queue
+        let approval = needs_approval(access.permissions);
+
+        let (result, next_url) = if approval {
+            (
+                state
+                    .journal_service
+                    .submit_transaction(
+                        state.ids.generate(),
+                        access.journal.id,
+                        updates,
+                        effective_date,
+                        payee,
+                        access.authority(),
+                        state.clock.get_time(),
+                    )
+                    .await,
+                format!("/journal/{}/pending", access.journal.id),
+            )
+        } else {
+            (
+                state
+                    .journal_service
+                    .create_transaction(
+                        state.ids.generate(),
+                        access.journal.id,
+                        updates,
+                        effective_date,
+                        payee,
+                        access.authority(),
+                        state.clock.get_time(),
+                    )
+                    .await,
+                callback_url.to_string(),
+            )
+        };
+
+        match result {
+            Ok(event_id) => {
+                state.journal_service.wait_for(event_id).await;
+
+                // the list only changes when the transaction was posted straight to it
+                if partial && !approval {
+                    let accounts_res =
+                        journal_accounts(&state, access.journal.id, &access.authority()).await;
+                    return Ok(transaction_cards(&state, &access, &accounts_res)
+                        .await
+                        .into_response());
+                }
+
+                return Ok(Redirect::to(&next_url).into_response());
+            }
+            Err(e) => form_state.fail(e),
+        }
+    }
+
+    let page = transaction_list(
+        &state,
+        &user,
+        &access,
+        None,
+        TotalsRange::default(),
+        &form_state,
+    )
+    .await;
+
+    Err(form_state.rejected(page).into())
+}
+
+pub async fn reverse(
+    State(state): State<StateType>,
+    session: AuthSession<BackendType>,
+    access: JournalAccess<AppendTransaction>,
+    Path((id, transaction_id)): Path<(String, String)>,
+) -> Result<Redirect, ErrorRedirect> {
+    let callback_url = &format!("/journal/{}/transaction/{}", id, transaction_id);
+
+    let transaction_id = TransactionId::from_str(&transaction_id).or_redirect(callback_url)?;
+
+    let browser_session = session.session.clone();
+    let user = get_user(session)?;
+
+    let now = state.clock.get_time();
+    let reversal_id: TransactionId = state.ids.generate();
+
     let event_id = state
         .journal_service
-        .create_transaction(
-            TransactionId::new(),
-            journal_id,
-            updates,
-            user_authority,
-            DefaultTimeProvider.get_time(),
+        .reverse_transaction(
+            reversal_id,
+            transaction_id,
+            access.journal.id,
+            user.timezone().date(now),
+            access.authority(),
+            now,
         )
         .await
         .or_redirect(callback_url)?;
 
     state.journal_service.wait_for(event_id).await;
 
-    Ok(Redirect::to(callback_url))
+    let reversed = state
+        .journal_service
+        .get_transaction(access.journal.id, transaction_id, &access.authority())
+        .await
+        .map(|(transaction, _, _)| format_voucher_number(transaction.number))
+        .unwrap_or_else(|_| transaction_id.to_string());
+
+    undo::record(
+        &browser_session,
+        event_id,
+        access.journal.id,
+        format!("Reversed {reversed}"),
+        Compensation::WithdrawReversal {
+            reversal_id,
+            transaction_id,
+        },
+        now,
+    )
+    .await;
+
+    Ok(Redirect::to(&format!(
+        "/journal/{}/transaction/{}",
+        id, reversal_id
+    )))
 }
@@ -0,0 +1,578 @@
+use super::*;
+use crate::authn::UserId;
+use crate::authority::Actor;
+use crate::id::{IdGenerator, SequentialIdProvider};
+use chrono::Utc;
+use proptest::prelude::*;
+use std::collections::HashMap;
+
+/// an in-memory copy of the balance projection in `JournalService`,
+/// including its handling of events that are delivered more than once
+#[derive(Clone, Debug, Default, PartialEq)]
+struct LedgerChecker {
+    transactions: HashMap<TransactionId, Vec<BalanceUpdate>>,
+    balances: HashMap<AccountId, i64>,
+}
+
+impl LedgerChecker {
+    fn replay<'a>(events: impl IntoIterator<Item = &'a JournalDomainEvent>) -> Self {
+        let mut checker = Self::default();
+        events.into_iter().for_each(|event| checker.apply(event));
+        checker
+    }
+
+    fn apply(&mut self, event: &JournalDomainEvent) {
+        match event {
+            JournalDomainEvent::TransactionCreated {
+                transaction_id,
+                balance_updates,
+                ..
+            } => {
+                if self.transactions.contains_key(transaction_id) {
+                    return;
+                }
+
+                for update in balance_updates {
+                    *self.balances.entry(update.account_id).or_default() += update.signed_amount();
+                }
+
+                self.transactions
+                    .insert(*transaction_id, balance_updates.clone());
+            }
+            JournalDomainEvent::TransactionDeleted { transaction_id, .. } => {
+                let Some(balance_updates) = self.transactions.remove(transaction_id) else {
+                    return;
+                };
+
+                for update in balance_updates {
+                    *self.balances.entry(update.account_id).or_default() -= update.signed_amount();
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn total(&self) -> i64 {
+        self.balances.values().sum()
+    }
+}
+
+#[derive(Clone, Debug)]
+enum Op {
+    Create(Vec<(usize, u64, EntryType)>),
+    Void(usize),
+    Reverse(usize),
+    Redeliver(usize),
+}
+
+const ACCOUNTS: usize = 4;
+
+fn op() -> impl Strategy<Value = Op> {
+    let entry = (
+        0..ACCOUNTS,
+        1..1_000_000_u64,
+        prop_oneof![Just(EntryType::Debit), Just(EntryType::Credit)],
+    );
+
+    prop_oneof![
+        3 => prop::collection::vec(entry, 1..6).prop_map(Op::Create),
+        1 => any::<usize>().prop_map(Op::Void),
+        1 => any::<usize>().prop_map(Op::Reverse),
+        1 => any::<usize>().prop_map(Op::Redeliver),
+    ]
+}
+
+/// the journal that every generated transaction is appended to
+struct Fixture {
+    journal: Journal,
+    accounts: Vec<AccountId>,
+    authority: Authority,
+    transactions: HashMap<TransactionId, Transaction>,
+    sequence: TransactionSequence,
+}
+
+impl Fixture {
+    fn new() -> Self {
+        let owner = UserId::new();
+        let mut journal = Journal::new(JournalId::new());
+        journal.owner = owner;
+        journal.status = Status::Valid;
+
+        Self {
+            sequence: TransactionSequence::new(journal.journal_id),
+            journal,
+            accounts: (0..ACCOUNTS).map(|_| AccountId::new()).collect(),
+            authority: Authority::Direct(Actor::User(owner)),
+            transactions: HashMap::new(),
+        }
+    }
+
+    fn member(&self) -> JournalMember {
+        JournalMember::new(
+            self.journal.journal_id,
+            self.authority.user_id().unwrap_or_default(),
+        )
+    }
+
+    /// runs the event through the transaction state, the same way disintegrate would
+    fn record(&mut self, event: &JournalDomainEvent) {
+        let (transaction_id, event) = match event.clone() {
+            JournalDomainEvent::TransactionCreated {
+                transaction_id,
+                journal_id,
+                number,
+                balance_updates,
+                effective_date,
+                reverses,
+                transfer,
+                payee,
+                authority,
+                timestamp,
+            } => (
+                transaction_id,
+                TransactionEvent::TransactionCreated {
+                    transaction_id,
+                    journal_id,
+                    number,
+                    balance_updates,
+                    effective_date,
+                    reverses,
+                    transfer,
+                    payee,
+                    authority,
+                    timestamp,
+                },
+            ),
+            JournalDomainEvent::TransactionDeleted {
+                transaction_id,
+                authority,
+                timestamp,
+            } => (
+                transaction_id,
+                TransactionEvent::TransactionDeleted {
+                    transaction_id,
+                    authority,
+                    timestamp,
+                },
+            ),
+            _ => return,
+        };
+
+        self.sequence.mutate(event.clone());
+
+        self.transactions
+            .entry(transaction_id)
+            .or_insert_with(|| Transaction::new(transaction_id))
+            .mutate(event);
+    }
+
+    fn create(
+        &mut self,
+        entries: &[(usize, u64, EntryType)],
+    ) -> Result<Vec<JournalDomainEvent>, JournalError> {
+        let entries = entries
+            .iter()
+            .map(|(account, amount, entry_type)| BalanceUpdate {
+                account_id: self.accounts[*account],
+                amount: *amount,
+                entry_type: *entry_type,
+                memo: None,
+            })
+            .collect();
+
+        let decision = CreateTransaction::new(
+            TransactionId::new(),
+            self.journal.journal_id,
+            entries,
+            Utc::now().date_naive(),
+            None,
+            self.authority.clone(),
+            Utc::now(),
+        );
+
+        let mut accounts = AllJournalAccounts::new(self.journal.journal_id);
+        accounts.accounts = self.accounts.iter().copied().collect();
+
+        decision.process(&(
+            Transaction::new(decision.transaction_id),
+            self.sequence.clone(),
+            accounts,
+            self.journal.clone(),
+            self.member(),
+        ))
+    }
+
+    fn void(&self, transaction_id: TransactionId) -> Result<Vec<JournalDomainEvent>, JournalError> {
+        let decision = DeleteTransaction::new(
+            transaction_id,
+            self.journal.journal_id,
+            self.authority.clone(),
+            Utc::now(),
+        );
+
+        let transaction = self
+            .transactions
+            .get(&transaction_id)
+            .cloned()
+            .unwrap_or_else(|| Transaction::new(transaction_id));
+
+        decision.process(&(transaction, self.journal.clone(), self.member()))
+    }
+
+    fn reverse(
+        &self,
+        reversal_id: TransactionId,
+        transaction_id: TransactionId,
+    ) -> Result<Vec<JournalDomainEvent>, JournalError> {
+        let decision = ReverseTransaction::new(
+            reversal_id,
+            transaction_id,
+            self.journal.journal_id,
+            Utc::now().date_naive(),
+            self.authority.clone(),
+            Utc::now(),
+        );
+
+        let mut accounts = AllJournalAccounts::new(self.journal.journal_id);
+        accounts.accounts = self.accounts.iter().copied().collect();
+
+        decision.process(&(
+            self.existing([reversal_id, transaction_id]),
+            self.sequence.clone(),
+            accounts,
+            self.journal.clone(),
+            self.member(),
+        ))
+    }
+
+    fn withdraw(
+        &self,
+        reversal_id: TransactionId,
+    ) -> Result<Vec<JournalDomainEvent>, JournalError> {
+        let decision = WithdrawReversal::new(
+            reversal_id,
+            self.journal.journal_id,
+            self.authority.clone(),
+            Utc::now(),
+        );
+
+        let reversal = self
+            .transactions
+            .get(&reversal_id)
+            .cloned()
+            .unwrap_or_else(|| Transaction::new(reversal_id));
+
+        decision.process(&(reversal, self.journal.clone(), self.member()))
+    }
+
+    fn batch(
+        &self,
+        transaction_ids: &[TransactionId],
+    ) -> Result<Vec<JournalDomainEvent>, JournalError> {
+        let transactions: Vec<NewTransaction> = transaction_ids
+            .iter()
+            .map(|transaction_id| NewTransaction {
+                transaction_id: *transaction_id,
+                entries: vec![
+                    BalanceUpdate {
+                        account_id: self.accounts[0],
+                        amount: 100,
+                        entry_type: EntryType::Debit,
+                        memo: None,
+                    },
+                    BalanceUpdate {
+                        account_id: self.accounts[1],
+                        amount: 100,
+                        entry_type: EntryType::Credit,
+                        memo: None,
+                    },
+                ],
+                effective_date: Utc::now().date_naive(),
+                payee: None,
+            })
+            .collect();
+
+        let decision = CreateTransactionBatch::new(
+            self.journal.journal_id,
+            transactions,
+            self.authority.clone(),
+            Utc::now(),
+        );
+
+        let mut accounts = AllJournalAccounts::new(self.journal.journal_id);
+        accounts.accounts = self.accounts.iter().copied().collect();
+
+        decision.process(&(
+            self.existing(transaction_ids.iter().copied()),
+            self.sequence.clone(),
+            accounts,
+            self.journal.clone(),
+            self.member(),
+        ))
+    }
+
+    /// the recorded state of each id, as the `Transactions` query would load it
+    fn existing(&self, transaction_ids: impl IntoIterator<Item = TransactionId>) -> Transactions {
+        Transactions(
+            transaction_ids
+                .into_iter()
+                .map(|transaction_id| {
+                    self.transactions
+                        .get(&transaction_id)
+                        .cloned()
+                        .unwrap_or_else(|| Transaction::new(transaction_id))
+                })
+                .collect(),
+        )
+    }
+}
+
+/// ids from a generator that started over, as a misconfigured `IdGenerator` would hand out
+fn repeated_ids(count: usize) -> (Vec<TransactionId>, Vec<TransactionId>) {
+    let ids = || {
+        let generator = IdGenerator::from(SequentialIdProvider::default());
+        (0..count).map(|_| generator.generate()).collect()
+    };
+
+    (ids(), ids())
+}
+
+#[test]
+fn batches_reject_ids_that_are_already_taken() {
+    let mut fixture = Fixture::new();
+    let (first, repeated) = repeated_ids(2);
+
+    for event in fixture.batch(&first).expect("failed to create the batch") {
+        fixture.record(&event);
+    }
+
+    let fresh = TransactionId::new();
+    assert_eq!(
+        fixture.batch(&[fresh, repeated[1]]),
+        Err(JournalError::InvalidBatch(vec![BatchRowError {
+            row: 2,
+            error: JournalError::TransactionIdCollision(repeated[1]),
+        }]))
+    );
+    assert!(fixture.batch(&[fresh]).is_ok());
+}
+
+#[test]
+fn reversals_reject_ids_that_are_already_taken() {
+    let mut fixture = Fixture::new();
+    let (first, repeated) = repeated_ids(2);
+
+    for event in fixture.batch(&first).expect("failed to create the batch") {
+        fixture.record(&event);
+    }
+
+    assert_eq!(
+        fixture.reverse(repeated[1], first[0]),
+        Err(JournalError::TransactionIdCollision(repeated[1]))
+    );
+    assert!(fixture.reverse(TransactionId::new(), first[0]).is_ok());
+}
+
+#[test]
+fn withdrawing_a_reversal_restores_the_balances_it_reversed() {
+    let mut fixture = Fixture::new();
+    let transaction_id = TransactionId::new();
+    let reversal_id = TransactionId::new();
+    let mut checker = LedgerChecker::default();
+
+    for event in fixture
+        .batch(&[transaction_id])
+        .expect("failed to create the batch")
+    {
+        fixture.record(&event);
+        checker.apply(&event);
+    }
+    let balances = checker.balances.clone();
+
+    // a transaction that isn't a reversal can't be withdrawn
+    assert_eq!(
+        fixture.withdraw(transaction_id),
+        Err(JournalError::InvalidTransaction(transaction_id))
+    );
+
+    for event in fixture
+        .reverse(reversal_id, transaction_id)
+        .expect("failed to reverse the transaction")
+    {
+        fixture.record(&event);
+        checker.apply(&event);
+    }
+    assert_ne!(checker.balances, balances);
+
+    for event in fixture
+        .withdraw(reversal_id)
+        .expect("failed to withdraw the reversal")
+    {
+        fixture.record(&event);
+        checker.apply(&event);
+    }
+    assert_eq!(checker.balances, balances);
+
+    assert_eq!(
+        fixture.withdraw(reversal_id),
+        Err(JournalError::InvalidTransaction(reversal_id))
+    );
+}
+
+proptest! {
+    #[test]
+    fn balances_hold_their_invariants(ops in prop::collection::vec(op(), 1..60)) {
+        let mut fixture = Fixture::new();
+        let mut checker = LedgerChecker::default();
+        // the events the store accepted, in order
+        let mut log: Vec<JournalDomainEvent> = Vec::new();
+        // every delivery the projection saw, including duplicates
+        let mut deliveries: Vec<JournalDomainEvent> = Vec::new();
+
+        for op in ops {
+            let events = match op {
+                Op::Create(entries) => fixture.create(&entries).unwrap_or_default(),
+                Op::Void(index) => {
+                    let ids: Vec<TransactionId> = checker.transactions.keys().copied().collect();
+                    match ids.get(index % ids.len().max(1)) {
+                        Some(id) => fixture.void(*id).expect("voiding a live transaction failed"),
+                        None => Vec::new(),
+                    }
+                }
+                Op::Reverse(index) => {
+                    let ids: Vec<TransactionId> = checker.transactions.keys().copied().collect();
+                    match ids.get(index % ids.len().max(1)) {
+                        Some(id) => fixture.reverse(TransactionId::new(), *id).expect("reversing a live transaction failed"),
+                        None => Vec::new(),
+                    }
+                }
+                Op::Redeliver(index) => {
+                    if let Some(event) = log.get(index % log.len().max(1)) {
+                        checker.apply(event);
+                        deliveries.push(event.clone());
+                    }
+                    Vec::new()
+                }
+            };
+
+            for event in events {
+                fixture.record(&event);
+                checker.apply(&event);
+                deliveries.push(event.clone());
+                log.push(event);
+            }
+
+            // the decision rejects imbalanced transactions, so debits always equal credits
+            prop_assert_eq!(checker.total(), 0);
+        }
+
+        // every transaction, including reversals, takes the next voucher number
+        let numbers: Vec<u64> = log
+            .iter()
+            .filter_map(|event| match event {
+                JournalDomainEvent::TransactionCreated { number, .. } => Some(*number),
+                _ => None,
+            })
+            .collect();
+        let expected: Vec<u64> = (1..=numbers.len() as u64).collect();
+        prop_assert_eq!(numbers, expected);
+
+        // rebuilding the projection from the event log gives the same balances,
+        // whether or not events were delivered more than once
+        prop_assert_eq!(&LedgerChecker::replay(&log), &checker);
+        prop_assert_eq!(&LedgerChecker::replay(&deliveries), &checker);
+
+        // voiding every remaining transaction reverses each balance update exactly
+        let live: Vec<TransactionId> = checker.transactions.keys().copied().collect();
+        for transaction_id in live {
+            for event in fixture.void(transaction_id).expect("voiding a live transaction failed") {
+                checker.apply(&event);
+            }
+        }
+
+        prop_assert!(checker.balances.values().all(|balance| *balance == 0));
+    }
+
+    #[test]
+    fn reversals_cancel_the_transaction(
+        entries in prop::collection::vec(
+            (0..ACCOUNTS, 1..1_000_000_u64, prop_oneof![Just(EntryType::Debit), Just(EntryType::Credit)]),
+            1..6,
+        )
+    ) {
+        let mut fixture = Fixture::new();
+        let mut checker = LedgerChecker::default();
+
+        // only balanced transactions are accepted, so there may be nothing to reverse
+        let Ok(created) = fixture.create(&entries) else {
+            return Ok(());
+        };
+
+        for event in created.iter() {
+            fixture.record(event);
+            checker.apply(event);
+        }
+
+        let JournalDomainEvent::TransactionCreated { transaction_id, .. } = created[0] else {
+            unreachable!("creating a transaction records it");
+        };
+
+        for event in fixture.reverse(TransactionId::new(), transaction_id).expect("reversing a live transaction failed") {
+            let reverses = match &event {
+                JournalDomainEvent::TransactionCreated { reverses, .. } => *reverses,
+                _ => None,
+            };
+            prop_assert_eq!(reverses, Some(transaction_id));
+            checker.apply(&event);
+        }
+
+        prop_assert!(checker.balances.values().all(|balance| *balance == 0));
+    }
+
+    #[test]
+    fn voucher_numbers_round_trip(number in 1..u64::MAX) {
+        prop_assert_eq!(parse_voucher_number(&format_voucher_number(number)), Some(number));
+        prop_assert_eq!(parse_voucher_number(&number.to_string()), Some(number));
+    }
+
+    #[test]
+    fn unnumbered_transactions_count_towards_the_next_number(unnumbered in 1..20_u64) {
+        let mut fixture = Fixture::new();
+        let entries = [(0, 100, EntryType::Debit), (1, 100, EntryType::Credit)];
+
+        // transactions recorded before they were numbered
+        for _ in 0..unnumbered {
+            for mut event in fixture.create(&entries).expect("failed to create a transaction") {
+                if let JournalDomainEvent::TransactionCreated { number, .. } = &mut event {
+                    *number = 0;
+                }
+                fixture.record(&event);
+            }
+        }
+
+        let created = fixture.create(&entries).expect("failed to create a transaction");
+        let JournalDomainEvent::TransactionCreated { number, .. } = created[0] else {
+            panic!("expected a TransactionCreated event");
+        };
+        prop_assert_eq!(number, unnumbered + 1);
+    }
+
+    #[test]
+    fn imbalanced_transactions_are_rejected(
+        entries in prop::collection::vec(
+            (0..ACCOUNTS, 1..1_000_000_u64, prop_oneof![Just(EntryType::Debit), Just(EntryType::Credit)]),
+            1..6,
+        )
+    ) {
+        let mut fixture = Fixture::new();
+        let total: i64 = entries
+            .iter()
+            .map(|(_, amount, entry_type)| match entry_type {
+                EntryType::Credit => *amount as i64,
+                EntryType::Debit => -(*amount as i64),
+            })
+            .sum();
+
+        prop_assert_eq!(fixture.create(&entries).is_ok(), total == 0);
+    }
+}
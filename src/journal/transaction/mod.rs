@@ -1,8 +1,16 @@
+pub mod approval;
+pub mod bulk;
 pub mod commands;
+#[cfg(test)]
+mod ledger_checker;
+pub mod pending;
+pub mod quick;
+pub mod split;
 pub mod views;
 
 use crate::id::Ident;
 use crate::journal::domain::{AccountEvent, JournalDomainEvent, TransactionEvent};
+use crate::journal::plaintext;
 use axum::Router;
 use axum::routing::{get, post};
 use axum_login::login_required;
@@ -17,6 +25,49 @@ pub fn router() -> Router<crate::StateType> {
             get(views::transaction_list_page),
         )
         .route("/journal/{id}/transaction", post(commands::transact))
+        .route(
+            "/journal/{id}/transaction/_list",
+            get(views::transaction_list_fragment),
+        )
+        .route(
+            "/journal/{id}/quick",
+            get(quick::quick_entry_page).post(quick::quick_transact),
+        )
+        .route("/journal/{id}/pending", get(pending::pending_list_page))
+        .route(
+            "/journal/{id}/pending/{transaction_id}/approve",
+            post(pending::approve),
+        )
+        .route(
+            "/journal/{id}/pending/{transaction_id}/reject",
+            post(pending::reject),
+        )
+        .route(
+            "/journal/{id}/transaction/split",
+            get(split::split_entry_page).post(split::split_transact),
+        )
+        .route(
+            "/journal/{id}/transaction/{transaction_id}",
+            get(views::transaction_detail_page),
+        )
+        .route(
+            "/journal/{id}/transaction/{transaction_id}/reverse",
+            post(commands::reverse),
+        )
+        .route_layer(login_required!(crate::BackendType, login_url = "/signin"))
+}
+
+/// the routes that take whole files, kept apart so they can accept larger bodies
+pub fn import_router() -> Router<crate::StateType> {
+    Router::new()
+        .route(
+            "/journal/{id}/transaction/bulk",
+            get(bulk::bulk_entry_page).post(bulk::bulk_transact),
+        )
+        .route(
+            "/journal/{id}/transaction/plaintext",
+            get(plaintext::plaintext_import_page).post(plaintext::plaintext_import),
+        )
         .route_layer(login_required!(crate::BackendType, login_url = "/signin"))
 }
 
@@ -24,13 +75,17 @@ use crate::authority::Authority;
 use crate::id;
 use crate::journal::account::AccountId;
 use crate::journal::member::JournalMember;
-use crate::journal::{Journal, Permissions, validate_permissions};
+use crate::journal::{Journal, Permissions, check_permissions};
 use crate::journal::{JournalError, JournalId};
+use crate::name::Name;
 use crate::proto::error::RepeatedBalanceUpdates;
 use crate::status::Status;
 use crate::time_provider::Timestamp;
-use disintegrate::{Decision, StateMutate, StateQuery};
+use chrono::NaiveDate;
+use disintegrate::{Decision, EventId, StateMutate, StateQuery, StreamQuery};
 use prost::Message;
+use rust_decimal::dec;
+use rust_decimal::prelude::*;
 use serde::Deserialize;
 use serde::Serialize;
 use sqlx::encode::IsNull;
@@ -63,6 +118,75 @@ pub enum TransactionValidationError {
     NegativeEntryAmount(String),
     #[error("Imbalanced transaction: {:?}", 0)]
     ImbalancedTransaction(TransactionEntries),
+    #[error("Invalid effective date: {0}. Expected a date in the format YYYY-MM-DD")]
+    InvalidEffectiveDate(String),
+    #[error("The journal doesn't have an account named {0}")]
+    UnknownAccount(String),
+    #[error("Couldn't read the quick entry: {0}")]
+    InvalidQuickEntry(String),
+    #[error("Couldn't split the amount: {0}")]
+    InvalidSplit(String),
+    #[error("Couldn't read the ledger file: {0}")]
+    InvalidPlaintext(String),
+    #[error("Couldn't read the statement: {0}")]
+    InvalidStatement(String),
+    #[error("A memo can be at most {max} characters long, found {0}", max = MAX_MEMO_LENGTH)]
+    MemoTooLong(usize),
+}
+
+/// parses a date submitted by an `input type="date"` element
+pub fn parse_effective_date(s: &str) -> Result<NaiveDate, JournalError> {
+    NaiveDate::parse_from_str(s, "%Y-%m-%d").map_err(|_| {
+        JournalError::TransactionValidation(TransactionValidationError::InvalidEffectiveDate(
+            s.to_string(),
+        ))
+    })
+}
+
+/// parses a positive dollar amount into cents
+pub fn parse_amount(s: &str) -> Result<u64, JournalError> {
+    let cents = Decimal::from_str(s).map_err(|_| {
+        JournalError::TransactionValidation(TransactionValidationError::ParseDecimal(s.to_string()))
+    })? * dec!(100);
+
+    // this will reject inputs with partial cent values
+    // this should not be possible unless a user uses the
+    //  inspector tool to change their HTML
+    if !cents.is_integer() {
+        return Err(JournalError::TransactionValidation(
+            TransactionValidationError::PartialCentValue(s.to_string()),
+        ));
+    }
+
+    let amount = cents.to_i64().ok_or_else(|| {
+        JournalError::TransactionValidation(TransactionValidationError::OutOfRange(s.to_string()))
+    })?;
+
+    // error when the amount is below zero to prevent confusion with the credit/debit selector
+    if amount <= 0 {
+        return Err(JournalError::TransactionValidation(
+            TransactionValidationError::NegativeEntryAmount(cents.to_string()),
+        ));
+    }
+
+    Ok(amount as u64)
+}
+
+/// the longest memo an entry can have, in characters
+pub const MAX_MEMO_LENGTH: usize = 200;
+
+/// reads an entry's memo, where a blank one means the entry doesn't have one. runs of
+/// whitespace are collapsed so a memo always fits on one line of an export
+pub fn parse_memo(s: &str) -> Result<Option<String>, JournalError> {
+    let memo = s.split_whitespace().collect::<Vec<_>>().join(" ");
+
+    match memo.chars().count() {
+        0 => Ok(None),
+        length if length > MAX_MEMO_LENGTH => Err(JournalError::TransactionValidation(
+            TransactionValidationError::MemoTooLong(length),
+        )),
+        _ => Ok(Some(memo)),
+    }
 }
 
 // TODO(gabriel) there's probably a more efficient way to validate that the applicable accounts exist
@@ -81,6 +205,10 @@ impl AllJournalAccounts {
             ..Default::default()
         }
     }
+
+    pub fn contains(&self, account_id: &AccountId) -> bool {
+        self.accounts.contains(account_id)
+    }
 }
 
 impl StateMutate for AllJournalAccounts {
@@ -95,18 +223,81 @@ impl StateMutate for AllJournalAccounts {
     }
 }
 
+/// the last voucher number given to a transaction in the journal. every decision that creates
+/// a transaction reads this, so concurrent transactions conflict instead of sharing a number.
+/// deleted transactions keep their number, which leaves a gap
+#[derive(StateQuery, Clone, Default, Serialize, Deserialize)]
+#[state_query(TransactionEvent)]
+pub struct TransactionSequence {
+    #[id]
+    journal_id: JournalId,
+    last: u64,
+}
+
+impl TransactionSequence {
+    pub fn new(journal_id: JournalId) -> Self {
+        Self {
+            journal_id,
+            ..Default::default()
+        }
+    }
+}
+
+impl StateMutate for TransactionSequence {
+    fn mutate(&mut self, event: Self::Event) {
+        match event {
+            TransactionEvent::TransactionCreated { number, .. } => {
+                self.last = recorded_number(self.last, number)
+            }
+            TransactionEvent::TransactionDeleted { .. }
+            | TransactionEvent::TransactionSubmitted { .. }
+            | TransactionEvent::TransactionApproved { .. }
+            | TransactionEvent::TransactionRejected { .. } => {}
+        }
+    }
+}
+
+/// the journal's last voucher number once a transaction with `number` is recorded after `last`.
+/// transactions recorded before they were numbered have 0, and take the next number in order
+pub(crate) fn recorded_number(last: u64, number: u64) -> u64 {
+    match number {
+        0 => last + 1,
+        number => last.max(number),
+    }
+}
+
+/// how a voucher number is shown, e.g. JV-0042
+pub fn format_voucher_number(number: u64) -> String {
+    format!("JV-{number:04}")
+}
+
+/// reads a voucher number written with or without its prefix, e.g. JV-0042 or 42
+pub fn parse_voucher_number(s: &str) -> Option<u64> {
+    let s = s.trim();
+    let digits = match s.get(..3) {
+        Some(prefix) if prefix.eq_ignore_ascii_case("JV-") => &s[3..],
+        _ => s,
+    };
+
+    digits.parse().ok().filter(|number| *number > 0)
+}
+
 #[derive(StateQuery, Clone, Default, Serialize, Deserialize)]
 #[state_query(TransactionEvent)]
 pub struct Transaction {
     #[id]
     transaction_id: TransactionId,
-    journal_id: JournalId,
+    pub(crate) journal_id: JournalId,
     updates: Vec<BalanceUpdate>,
-    status: Status,
+    payee: Option<Name>,
+    /// the transaction this one reverses. snapshots taken before it was kept don't have it
+    #[serde(default)]
+    reverses: Option<TransactionId>,
+    pub(crate) status: Status,
 }
 
 impl Transaction {
-    fn new(transaction_id: TransactionId) -> Self {
+    pub(crate) fn new(transaction_id: TransactionId) -> Self {
         Self {
             transaction_id,
             ..Default::default()
@@ -120,13 +311,71 @@ impl StateMutate for Transaction {
             TransactionEvent::TransactionCreated {
                 balance_updates,
                 journal_id,
+                payee,
+                reverses,
                 ..
             } => {
                 self.journal_id = journal_id;
                 self.updates = balance_updates;
+                self.payee = payee;
+                self.reverses = reverses;
                 self.status = Status::Valid;
             }
             TransactionEvent::TransactionDeleted { .. } => self.status = Status::Deleted,
+            // a pending transaction only exists once it's approved
+            TransactionEvent::TransactionSubmitted { .. }
+            | TransactionEvent::TransactionApproved { .. }
+            | TransactionEvent::TransactionRejected { .. } => {}
+        }
+    }
+}
+
+/// the transactions with each of the ids, for decisions that read or create several at once.
+/// only the events of those ids are read, rather than every transaction in the journal
+#[derive(Clone, Default, Serialize, Deserialize)]
+pub struct Transactions(Vec<Transaction>);
+
+impl Transactions {
+    pub(crate) fn new(transaction_ids: impl IntoIterator<Item = TransactionId>) -> Self {
+        Self(transaction_ids.into_iter().map(Transaction::new).collect())
+    }
+
+    /// the transaction with the id, which must be one of the ids the state was made with
+    pub(crate) fn get(&self, transaction_id: TransactionId) -> &Transaction {
+        self.0
+            .iter()
+            .find(|transaction| transaction.transaction_id == transaction_id)
+            .expect("the transaction wasn't one of the queried ids")
+    }
+}
+
+impl StateQuery for Transactions {
+    const NAME: &'static str = "Transactions";
+    type Event = TransactionEvent;
+
+    fn query<ID: EventId>(&self) -> StreamQuery<ID, Self::Event> {
+        self.0
+            .iter()
+            .map(StateQuery::query)
+            .reduce(|all, query| all.union(&query))
+            .unwrap_or_else(|| Transaction::default().query())
+    }
+}
+
+impl StateMutate for Transactions {
+    fn mutate(&mut self, event: Self::Event) {
+        let (TransactionEvent::TransactionCreated { transaction_id, .. }
+        | TransactionEvent::TransactionDeleted { transaction_id, .. }
+        | TransactionEvent::TransactionSubmitted { transaction_id, .. }
+        | TransactionEvent::TransactionApproved { transaction_id, .. }
+        | TransactionEvent::TransactionRejected { transaction_id, .. }) = event;
+
+        for transaction in self
+            .0
+            .iter_mut()
+            .filter(|transaction| transaction.transaction_id == transaction_id)
+        {
+            transaction.mutate(event.clone());
         }
     }
 }
@@ -135,6 +384,8 @@ pub struct CreateTransaction {
     transaction_id: TransactionId,
     journal_id: JournalId,
     entries: Vec<BalanceUpdate>,
+    effective_date: NaiveDate,
+    payee: Option<Name>,
     authority: Authority,
     timestamp: Timestamp,
 }
@@ -144,6 +395,8 @@ impl CreateTransaction {
         transaction_id: TransactionId,
         journal_id: JournalId,
         entries: Vec<BalanceUpdate>,
+        effective_date: NaiveDate,
+        payee: Option<Name>,
         authority: Authority,
         timestamp: Timestamp,
     ) -> Self {
@@ -151,6 +404,8 @@ impl CreateTransaction {
             transaction_id,
             journal_id,
             entries,
+            effective_date,
+            payee,
             authority,
             timestamp,
         }
@@ -159,12 +414,19 @@ impl CreateTransaction {
 
 impl Decision for CreateTransaction {
     type Event = JournalDomainEvent;
-    type StateQuery = (Transaction, AllJournalAccounts, Journal, JournalMember);
+    type StateQuery = (
+        Transaction,
+        TransactionSequence,
+        AllJournalAccounts,
+        Journal,
+        JournalMember,
+    );
     type Error = JournalError;
 
     fn state_query(&self) -> Self::StateQuery {
         (
             Transaction::new(self.transaction_id),
+            TransactionSequence::new(self.journal_id),
             AllJournalAccounts::new(self.journal_id),
             Journal::new(self.journal_id),
             JournalMember::new(
@@ -176,7 +438,7 @@ impl Decision for CreateTransaction {
 
     fn process(
         &self,
-        (transaction, accounts, journal, actor): &Self::StateQuery,
+        (transaction, sequence, accounts, journal, actor): &Self::StateQuery,
     ) -> Result<Vec<Self::Event>, Self::Error> {
         if transaction.status.found() {
             return Err(JournalError::TransactionIdCollision(self.transaction_id));
@@ -186,40 +448,369 @@ impl Decision for CreateTransaction {
             return Err(JournalError::InvalidJournal(self.journal_id));
         }
 
-        let mut balance = 0;
+        validate_entries(&self.entries, accounts)?;
 
-        for update in self.entries.iter() {
-            if !accounts.accounts.contains(&update.account_id) {
-                return Err(JournalError::InvalidAccount(update.account_id));
-            }
+        check_permissions(
+            actor,
+            &self.authority,
+            journal,
+            Permissions::APPEND_TRANSACTION,
+        )?;
 
-            match update.entry_type {
-                EntryType::Credit => balance += update.amount as i64,
-                EntryType::Debit => balance -= update.amount as i64,
-            }
+        Ok(vec![JournalDomainEvent::TransactionCreated {
+            transaction_id: self.transaction_id,
+            journal_id: self.journal_id,
+            number: sequence.last + 1,
+            balance_updates: self.entries.clone(),
+            effective_date: self.effective_date,
+            reverses: None,
+            transfer: None,
+            payee: self.payee.clone(),
+            authority: self.authority.clone(),
+            timestamp: self.timestamp,
+        }])
+    }
+}
+
+/// checks that every entry belongs to the journal and that debits equal credits
+fn validate_entries(
+    entries: &[BalanceUpdate],
+    accounts: &AllJournalAccounts,
+) -> Result<(), JournalError> {
+    let mut balance = 0;
+
+    for update in entries.iter() {
+        if !accounts.contains(&update.account_id) {
+            return Err(JournalError::InvalidAccount(update.account_id));
         }
 
-        if balance != 0 {
+        if let Some(memo) = &update.memo
+            && memo.chars().count() > MAX_MEMO_LENGTH
+        {
             return Err(JournalError::TransactionValidation(
-                TransactionValidationError::ImbalancedTransaction(TransactionEntries(
-                    self.entries.clone(),
-                )),
+                TransactionValidationError::MemoTooLong(memo.chars().count()),
             ));
         }
 
-        if !validate_permissions(
+        balance += update.signed_amount();
+    }
+
+    if balance != 0 {
+        return Err(JournalError::TransactionValidation(
+            TransactionValidationError::ImbalancedTransaction(TransactionEntries(entries.to_vec())),
+        ));
+    }
+
+    Ok(())
+}
+
+/// an error in one row of a batch, numbered from 1 in the order the rows were submitted
+#[derive(Debug, PartialEq)]
+pub struct BatchRowError {
+    pub row: u64,
+    pub error: JournalError,
+}
+
+#[derive(Clone, Debug)]
+pub struct NewTransaction {
+    pub transaction_id: TransactionId,
+    pub entries: Vec<BalanceUpdate>,
+    pub effective_date: NaiveDate,
+    pub payee: Option<Name>,
+}
+
+/// creates several transactions in a single append, so either all of them are recorded or none are
+pub struct CreateTransactionBatch {
+    journal_id: JournalId,
+    transactions: Vec<NewTransaction>,
+    authority: Authority,
+    timestamp: Timestamp,
+}
+
+impl CreateTransactionBatch {
+    pub fn new(
+        journal_id: JournalId,
+        transactions: Vec<NewTransaction>,
+        authority: Authority,
+        timestamp: Timestamp,
+    ) -> Self {
+        Self {
+            journal_id,
+            transactions,
+            authority,
+            timestamp,
+        }
+    }
+}
+
+impl Decision for CreateTransactionBatch {
+    type Event = JournalDomainEvent;
+    type StateQuery = (
+        Transactions,
+        TransactionSequence,
+        AllJournalAccounts,
+        Journal,
+        JournalMember,
+    );
+    type Error = JournalError;
+
+    fn state_query(&self) -> Self::StateQuery {
+        (
+            Transactions::new(
+                self.transactions
+                    .iter()
+                    .map(|transaction| transaction.transaction_id),
+            ),
+            TransactionSequence::new(self.journal_id),
+            AllJournalAccounts::new(self.journal_id),
+            Journal::new(self.journal_id),
+            JournalMember::new(
+                self.journal_id,
+                self.authority.user_id().unwrap_or_default(),
+            ),
+        )
+    }
+
+    fn process(
+        &self,
+        (existing, sequence, accounts, journal, actor): &Self::StateQuery,
+    ) -> Result<Vec<Self::Event>, Self::Error> {
+        if !journal.status.valid() {
+            return Err(JournalError::InvalidJournal(self.journal_id));
+        }
+
+        check_permissions(
             actor,
             &self.authority,
-            journal.owner,
+            journal,
             Permissions::APPEND_TRANSACTION,
-        ) {
-            return Err(JournalError::Permissions(Permissions::APPEND_TRANSACTION));
+        )?;
+
+        if self.transactions.is_empty() {
+            return Err(JournalError::TransactionValidation(
+                TransactionValidationError::NoTransactionEntries,
+            ));
         }
 
+        // an id can collide with a transaction that's already recorded or with another row
+        let mut transaction_ids = HashSet::new();
+        let mut errors = Vec::new();
+
+        for (row, transaction) in (1..).zip(self.transactions.iter()) {
+            let result = if existing.get(transaction.transaction_id).status.found()
+                || !transaction_ids.insert(transaction.transaction_id)
+            {
+                Err(JournalError::TransactionIdCollision(
+                    transaction.transaction_id,
+                ))
+            } else if transaction.entries.is_empty() {
+                Err(JournalError::TransactionValidation(
+                    TransactionValidationError::NoTransactionEntries,
+                ))
+            } else {
+                validate_entries(&transaction.entries, accounts)
+            };
+
+            if let Err(error) = result {
+                errors.push(BatchRowError { row, error });
+            }
+        }
+
+        if !errors.is_empty() {
+            return Err(JournalError::InvalidBatch(errors));
+        }
+
+        // the batch is numbered in the order the rows were submitted
+        Ok((sequence.last + 1..)
+            .zip(self.transactions.iter())
+            .map(
+                |(number, transaction)| JournalDomainEvent::TransactionCreated {
+                    transaction_id: transaction.transaction_id,
+                    journal_id: self.journal_id,
+                    number,
+                    balance_updates: transaction.entries.clone(),
+                    effective_date: transaction.effective_date,
+                    reverses: None,
+                    transfer: None,
+                    payee: transaction.payee.clone(),
+                    authority: self.authority.clone(),
+                    timestamp: self.timestamp,
+                },
+            )
+            .collect())
+    }
+}
+
+/// records a new transaction that swaps every debit and credit of an existing one,
+/// which undoes its effect on the balances while leaving it in the history
+pub struct ReverseTransaction {
+    reversal_id: TransactionId,
+    transaction_id: TransactionId,
+    journal_id: JournalId,
+    effective_date: NaiveDate,
+    authority: Authority,
+    timestamp: Timestamp,
+}
+
+impl ReverseTransaction {
+    pub fn new(
+        reversal_id: TransactionId,
+        transaction_id: TransactionId,
+        journal_id: JournalId,
+        effective_date: NaiveDate,
+        authority: Authority,
+        timestamp: Timestamp,
+    ) -> Self {
+        Self {
+            reversal_id,
+            transaction_id,
+            journal_id,
+            effective_date,
+            authority,
+            timestamp,
+        }
+    }
+}
+
+impl Decision for ReverseTransaction {
+    type Event = JournalDomainEvent;
+    // the transaction and its reversal share a state, since disintegrate can't query more
+    // than five states at once
+    type StateQuery = (
+        Transactions,
+        TransactionSequence,
+        AllJournalAccounts,
+        Journal,
+        JournalMember,
+    );
+    type Error = JournalError;
+
+    fn state_query(&self) -> Self::StateQuery {
+        (
+            Transactions::new([self.transaction_id, self.reversal_id]),
+            TransactionSequence::new(self.journal_id),
+            AllJournalAccounts::new(self.journal_id),
+            Journal::new(self.journal_id),
+            JournalMember::new(
+                self.journal_id,
+                self.authority.user_id().unwrap_or_default(),
+            ),
+        )
+    }
+
+    fn process(
+        &self,
+        (transactions, sequence, accounts, journal, actor): &Self::StateQuery,
+    ) -> Result<Vec<Self::Event>, Self::Error> {
+        if transactions.get(self.reversal_id).status.found() {
+            return Err(JournalError::TransactionIdCollision(self.reversal_id));
+        }
+
+        let transaction = transactions.get(self.transaction_id);
+        if !transaction.status.valid() || transaction.journal_id != self.journal_id {
+            return Err(JournalError::InvalidTransaction(self.transaction_id));
+        }
+
+        if !journal.status.valid() {
+            return Err(JournalError::InvalidJournal(self.journal_id));
+        }
+
+        let entries: Vec<BalanceUpdate> = transaction
+            .updates
+            .iter()
+            .map(BalanceUpdate::reversed)
+            .collect();
+
+        // an account the transaction used may have been deleted since
+        validate_entries(&entries, accounts)?;
+
+        check_permissions(
+            actor,
+            &self.authority,
+            journal,
+            Permissions::APPEND_TRANSACTION,
+        )?;
+
         Ok(vec![JournalDomainEvent::TransactionCreated {
-            transaction_id: self.transaction_id,
+            transaction_id: self.reversal_id,
             journal_id: self.journal_id,
-            balance_updates: self.entries.clone(),
+            number: sequence.last + 1,
+            balance_updates: entries,
+            effective_date: self.effective_date,
+            reverses: Some(self.transaction_id),
+            transfer: None,
+            payee: transaction.payee.clone(),
+            authority: self.authority.clone(),
+            timestamp: self.timestamp,
+        }])
+    }
+}
+
+/// deletes a reversal, which puts the transaction it reversed back into the balances. this is
+/// how a reversal is undone, so it only takes the permission that made the reversal
+pub struct WithdrawReversal {
+    reversal_id: TransactionId,
+    journal_id: JournalId,
+    authority: Authority,
+    timestamp: Timestamp,
+}
+
+impl WithdrawReversal {
+    pub fn new(
+        reversal_id: TransactionId,
+        journal_id: JournalId,
+        authority: Authority,
+        timestamp: Timestamp,
+    ) -> Self {
+        Self {
+            reversal_id,
+            journal_id,
+            authority,
+            timestamp,
+        }
+    }
+}
+
+impl Decision for WithdrawReversal {
+    type Event = JournalDomainEvent;
+    type StateQuery = (Transaction, Journal, JournalMember);
+    type Error = JournalError;
+
+    fn state_query(&self) -> Self::StateQuery {
+        (
+            Transaction::new(self.reversal_id),
+            Journal::new(self.journal_id),
+            JournalMember::new(
+                self.journal_id,
+                self.authority.user_id().unwrap_or_default(),
+            ),
+        )
+    }
+
+    fn process(
+        &self,
+        (reversal, journal, actor): &Self::StateQuery,
+    ) -> Result<Vec<Self::Event>, Self::Error> {
+        if !reversal.status.valid()
+            || reversal.journal_id != self.journal_id
+            || reversal.reverses.is_none()
+        {
+            return Err(JournalError::InvalidTransaction(self.reversal_id));
+        }
+
+        if !journal.status.valid() {
+            return Err(JournalError::InvalidJournal(self.journal_id));
+        }
+
+        check_permissions(
+            actor,
+            &self.authority,
+            journal,
+            Permissions::APPEND_TRANSACTION,
+        )?;
+
+        Ok(vec![JournalDomainEvent::TransactionDeleted {
+            transaction_id: self.reversal_id,
             authority: self.authority.clone(),
             timestamp: self.timestamp,
         }])
@@ -233,7 +824,7 @@ pub struct DeleteTransaction {
     timestamp: Timestamp,
 }
 
-#[expect(unused)]
+#[cfg_attr(not(test), expect(unused))]
 impl DeleteTransaction {
     pub fn new(
         transaction_id: TransactionId,
@@ -278,9 +869,7 @@ impl Decision for DeleteTransaction {
             return Err(JournalError::InvalidJournal(self.journal_id));
         }
 
-        if !validate_permissions(actor, &self.authority, journal.owner, Permissions::OWNER) {
-            return Err(JournalError::Permissions(Permissions::OWNER));
-        }
+        check_permissions(actor, &self.authority, journal, Permissions::OWNER)?;
 
         Ok(vec![JournalDomainEvent::TransactionDeleted {
             transaction_id: self.transaction_id,
@@ -318,11 +907,36 @@ impl FromStr for EntryType {
     }
 }
 
-#[derive(Serialize, Deserialize, Copy, Clone, Debug, PartialEq, Eq)]
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
 pub struct BalanceUpdate {
     pub account_id: AccountId,
     pub amount: u64,
     pub entry_type: EntryType,
+    /// a note on just this entry, e.g. "half of dinner". events recorded before entries had
+    /// memos don't have one
+    #[serde(default)]
+    pub memo: Option<String>,
+}
+
+impl BalanceUpdate {
+    /// the change this update makes to its account's balance, where credits are positive
+    pub fn signed_amount(&self) -> i64 {
+        match self.entry_type {
+            EntryType::Credit => self.amount as i64,
+            EntryType::Debit => -(self.amount as i64),
+        }
+    }
+
+    /// the same amount on the other side of the account
+    pub fn reversed(&self) -> Self {
+        Self {
+            entry_type: match self.entry_type {
+                EntryType::Debit => EntryType::Credit,
+                EntryType::Credit => EntryType::Debit,
+            },
+            ..self.clone()
+        }
+    }
 }
 
 #[derive(Debug, PartialEq)]
@@ -352,3 +966,76 @@ impl<'r> Decode<'r, Postgres> for TransactionEntries {
         Ok(prost_entries.try_into()?)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_app::{TestApp, assert_accepted};
+
+    #[tokio::test]
+    async fn entry_memos_show_in_the_ledger_and_exports() {
+        let Some(app) = TestApp::spawn().await else {
+            return;
+        };
+
+        let user = app.signup("memoist").await;
+        let journal_id = user.create_journal(&app, "Dinners").await;
+        assert_accepted(&user.create_account(journal_id, "Cash").await);
+        assert_accepted(&user.create_account(journal_id, "Dining").await);
+        let cash = app.account_id(journal_id, "Cash").await.to_string();
+        let dining = app.account_id(journal_id, "Dining").await.to_string();
+
+        let response = user
+            .client
+            .post(&format!("/journal/{journal_id}/transaction"))
+            .form(&[
+                ("account", dining.as_str()),
+                ("amount", "42.00"),
+                ("entry_type", "Dr"),
+                ("memo", "  half of\ndinner "),
+                ("account", cash.as_str()),
+                ("amount", "42.00"),
+                ("entry_type", "Cr"),
+                ("memo", ""),
+                ("effective_date", "2026-10-17"),
+            ])
+            .await;
+        assert_accepted(&response);
+
+        let ledger = user
+            .client
+            .get(&format!("/journal/{journal_id}/transaction"))
+            .clear_headers()
+            .add_header("accept", "text/html")
+            .await
+            .text();
+        assert!(ledger.contains("half of dinner"));
+
+        let export = user
+            .client
+            .get(&format!("/journal/{journal_id}/export.ledger"))
+            .await
+            .text();
+        assert!(export.contains("$42.00  ; half of dinner\n"));
+        assert_eq!(export.matches(';').count(), 1);
+
+        let memo = "x".repeat(MAX_MEMO_LENGTH + 1);
+        let response = user
+            .client
+            .post(&format!("/journal/{journal_id}/transaction"))
+            .clear_headers()
+            .add_header("accept", "text/html")
+            .form(&[
+                ("account", dining.as_str()),
+                ("amount", "1.00"),
+                ("entry_type", "Dr"),
+                ("memo", memo.as_str()),
+                ("account", cash.as_str()),
+                ("amount", "1.00"),
+                ("entry_type", "Cr"),
+                ("effective_date", "2026-10-17"),
+            ])
+            .await;
+        assert!(response.text().contains("A memo can be at most"));
+    }
+}
@@ -0,0 +1,198 @@
+use crate::BackendType;
+use crate::StateType;
+use crate::authn::get_user;
+use crate::authority::Actor;
+use crate::i18n::{t, tf};
+use crate::journal::Permissions;
+use crate::journal::access::{Approve, JournalAccess, check_access};
+use crate::journal::account::AccountId;
+use crate::journal::layout;
+use crate::journal::transaction::TransactionId;
+use crate::journal::transaction::views::format_amount;
+use crate::monkesto_error::{ErrorRedirect, MonkestoError, OrRedirect, UrlError};
+use crate::name::Name;
+use crate::time_provider::TimeProvider;
+use axum::extract::{Path, Query, State};
+use axum::response::Redirect;
+use axum_login::AuthSession;
+use maud::{Markup, html};
+use std::collections::HashMap;
+use std::str::FromStr;
+
+/// the transactions waiting for approval. everyone who can read the journal sees the queue,
+/// but only approvers get the buttons
+pub async fn pending_list_page(
+    State(state): State<StateType>,
+    session: AuthSession<BackendType>,
+    access: JournalAccess,
+    Query(err): Query<UrlError>,
+) -> Result<Markup, Redirect> {
+    let user = get_user(session)?;
+    let authority = access.authority();
+    let journal_id = access.journal.id;
+    let id = &journal_id.to_string();
+
+    let can_approve = check_access(journal_id, access.permissions, Permissions::APPROVE).is_ok();
+
+    let pending_res = state
+        .journal_service
+        .list_pending_transactions(journal_id, &authority)
+        .await;
+
+    let accounts: HashMap<AccountId, Name> = state
+        .journal_service
+        .list_journal_accounts(journal_id, &authority)
+        .await
+        .map(|accounts| {
+            accounts
+                .into_iter()
+                .map(|(account, _, _)| (account.id, account.name))
+                .collect()
+        })
+        .unwrap_or_default();
+
+    let mut submitters = HashMap::new();
+    if let Ok(pending) = &pending_res {
+        for (_, submitted_by, _) in pending {
+            if let Actor::User(user_id) = submitted_by.actor()
+                && !submitters.contains_key(user_id)
+            {
+                let name = match state.authn_service.get_display_name(*user_id).await {
+                    Ok(name) => name,
+                    Err(e) => tf("transaction.user_failed", &[("error", &e)]),
+                };
+                submitters.insert(*user_id, name);
+            }
+        }
+    }
+
+    let content = html! {
+        div class="flex flex-col gap-6 mx-auto w-full max-w-4xl" {
+            h2 class="text-2xl font-bold text-gray-900 dark:text-white" { (t("pending.heading")) }
+
+            @match &pending_res {
+                Ok(pending) if pending.is_empty() => {
+                    p class="text-gray-500 dark:text-gray-400" { (t("pending.empty")) }
+                },
+                Ok(pending) => {
+                    @for (transaction, submitted_by, submitted_at) in pending {
+                        div class="p-4 bg-white dark:bg-gray-800 border border-gray-200 dark:border-gray-700 rounded-xl space-y-3" {
+                            div class="text-sm font-medium text-gray-500 dark:text-gray-400" {
+                                (transaction.effective_date.format("%Y-%m-%d"))
+                                @if let Some(payee) = &transaction.payee {
+                                    " · " (payee)
+                                }
+                            }
+
+                            @for entry in &transaction.entries {
+                                div class="flex justify-between items-center" {
+                                    div {
+                                        span class="text-base font-medium text-gray-900 dark:text-white" {
+                                            (accounts.get(&entry.account_id).map(|name| name.as_ref()).unwrap_or(t("transaction.unknown_account")))
+                                        }
+                                        @if let Some(memo) = &entry.memo {
+                                            p class="text-xs text-gray-500 dark:text-gray-400" { (memo) }
+                                        }
+                                    }
+                                    span class="text-base text-gray-700 dark:text-gray-300" {
+                                        (format_amount(entry.amount)) " " (entry.entry_type)
+                                    }
+                                }
+                            }
+
+                            div class="text-xs text-gray-400 dark:text-gray-500" {
+                                (tf("pending.submitted_by", &[
+                                    ("user", &match submitted_by.actor() {
+                                        Actor::User(user_id) => submitters.get(user_id).cloned().unwrap_or_default(),
+                                        Actor::System => t("actor.system").to_string(),
+                                        Actor::Anonymous => t("actor.anonymous").to_string(),
+                                        Actor::Deleted => t("actor.deleted").to_string(),
+                                    }),
+                                    ("time", &user.timezone().format(*submitted_at)),
+                                ]))
+                            }
+
+                            @if can_approve {
+                                div class="flex gap-3" {
+                                    form method="post" action=(format!("/journal/{}/pending/{}/approve", id, transaction.id)) {
+                                        button type="submit" class="px-4 py-2 text-sm font-medium bg-indigo-600 text-white rounded-md hover:bg-indigo-700 dark:bg-indigo-500 dark:hover:bg-indigo-400" {
+                                            (t("pending.approve"))
+                                        }
+                                    }
+                                    form method="post" action=(format!("/journal/{}/pending/{}/reject", id, transaction.id)) {
+                                        button type="submit" class="px-4 py-2 text-sm font-medium bg-white dark:bg-gray-700 border border-gray-300 dark:border-gray-600 rounded-md text-gray-700 dark:text-gray-200 hover:bg-gray-100 dark:hover:bg-gray-600" {
+                                            (t("pending.reject"))
+                                        }
+                                    }
+                                }
+                            }
+                        }
+                    }
+                },
+                Err(e) => {
+                    p class="text-gray-500 dark:text-gray-400" {
+                        (tf("pending.fetch_failed", &[("error", e)]))
+                    }
+                }
+            }
+
+            @if let Some(e) = err.err {
+                p {
+                    (tf("error.occurred", &[("error", &format!("{:?}", MonkestoError::decode(&e)))]))
+                }
+            }
+        }
+    };
+
+    Ok(layout::journal_layout(&access.journal, content))
+}
+
+pub async fn approve(
+    State(state): State<StateType>,
+    access: JournalAccess<Approve>,
+    Path((id, transaction_id)): Path<(String, String)>,
+) -> Result<Redirect, ErrorRedirect> {
+    let callback_url = &format!("/journal/{}/pending", id);
+
+    let transaction_id = TransactionId::from_str(&transaction_id).or_redirect(callback_url)?;
+
+    let event_id = state
+        .journal_service
+        .approve_transaction(
+            transaction_id,
+            access.journal.id,
+            access.authority(),
+            state.clock.get_time(),
+        )
+        .await
+        .or_redirect(callback_url)?;
+
+    state.journal_service.wait_for(event_id).await;
+
+    Ok(Redirect::to(callback_url))
+}
+
+pub async fn reject(
+    State(state): State<StateType>,
+    access: JournalAccess<Approve>,
+    Path((id, transaction_id)): Path<(String, String)>,
+) -> Result<Redirect, ErrorRedirect> {
+    let callback_url = &format!("/journal/{}/pending", id);
+
+    let transaction_id = TransactionId::from_str(&transaction_id).or_redirect(callback_url)?;
+
+    let event_id = state
+        .journal_service
+        .reject_transaction(
+            transaction_id,
+            access.journal.id,
+            access.authority(),
+            state.clock.get_time(),
+        )
+        .await
+        .or_redirect(callback_url)?;
+
+    state.journal_service.wait_for(event_id).await;
+
+    Ok(Redirect::to(callback_url))
+}
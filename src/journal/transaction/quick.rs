@@ -0,0 +1,531 @@
+use crate::BackendType;
+use crate::StateType;
+use crate::authn::get_user;
+use crate::authority::Authority;
+use crate::journal::JournalError;
+use crate::journal::access::{AppendTransaction, JournalAccess};
+use crate::journal::account::AccountId;
+use crate::journal::layout;
+use crate::journal::service::JournalState;
+use crate::journal::transaction::views::format_amount;
+use crate::journal::transaction::{
+    BalanceUpdate, EntryType, TransactionId, TransactionValidationError,
+};
+use crate::monkesto_error::MonkestoResult;
+use crate::name::Name;
+use crate::parse;
+use crate::time_provider::TimeProvider;
+use axum::extract::{Query, State};
+use axum::http::StatusCode;
+use axum::response::{IntoResponse, Redirect, Response};
+use axum_extra::extract::Form;
+use axum_login::AuthSession;
+use chrono::NaiveDate;
+use maud::{Markup, html};
+use serde::Deserialize;
+
+/// a one line transaction, written as `[date] [note] amount from>to`. the amount moves
+/// from the first account to the second, so the first is credited and the second debited.
+/// either account can be left out for the journal's default to be used instead
+#[derive(Debug, PartialEq)]
+pub struct QuickEntry {
+    pub effective_date: NaiveDate,
+    pub note: String,
+    pub amount: u64,
+    pub from: Option<String>,
+    pub to: Option<String>,
+}
+
+fn invalid(reason: &str) -> JournalError {
+    JournalError::TransactionValidation(TransactionValidationError::InvalidQuickEntry(
+        reason.to_string(),
+    ))
+}
+
+/// account names may contain spaces, so the amount is the last number before the `>`
+/// that still leaves words for the first account's name, or the last word when no number
+/// does. without a `>`, both accounts are left out and the entry ends with the amount.
+/// the date may be one or two words, and is today when it's left out
+pub fn parse_quick_entry(input: &str, today: NaiveDate) -> Result<QuickEntry, JournalError> {
+    let (left, to, arrow) = match input.split_once('>') {
+        Some((left, to)) => (left, Some(to.trim()).filter(|to| !to.is_empty()), true),
+        None => (input, None, false),
+    };
+
+    let words: Vec<&str> = left.split_whitespace().collect();
+    let amount_at = |i: usize| Some((i, parse::amount::amount(words[i]).ok()?));
+    let last = words.len().checked_sub(1);
+
+    let (position, amount) = (0..last.unwrap_or_default())
+        .rev()
+        .filter(|_| arrow)
+        .find_map(amount_at)
+        .or_else(|| amount_at(last?))
+        .ok_or_else(|| {
+            invalid("expected an amount, followed by the accounts written as from>to")
+        })?;
+
+    let prefix = &words[..position];
+    let (effective_date, note) = [2, 1]
+        .into_iter()
+        .filter(|&length| length <= prefix.len())
+        .find_map(|length| {
+            let date = parse::date::date(&prefix[..length].join(" "), today).ok()?;
+            Some((date, &prefix[length..]))
+        })
+        .unwrap_or((today, prefix));
+
+    Ok(QuickEntry {
+        effective_date,
+        note: note.join(" "),
+        amount,
+        from: Some(words[position + 1..].join(" ")).filter(|from| !from.is_empty()),
+        to: to.map(str::to_string),
+    })
+}
+
+/// matches the name exactly if it can, ignoring case, and otherwise by the start of the name
+fn find_account<'a>(
+    accounts: &'a [(AccountId, Name)],
+    name: &str,
+) -> Result<&'a (AccountId, Name), JournalError> {
+    let name = name.to_lowercase();
+
+    if let Some(account) = accounts
+        .iter()
+        .find(|(_, account)| account.as_ref().to_lowercase() == name)
+    {
+        return Ok(account);
+    }
+
+    let mut matches = accounts
+        .iter()
+        .filter(|(_, account)| account.as_ref().to_lowercase().starts_with(&name));
+
+    match (matches.next(), matches.next()) {
+        (Some(account), None) => Ok(account),
+        (Some(_), Some(_)) => Err(invalid(&format!(
+            "more than one account starts with {name}"
+        ))),
+        (None, _) => Err(JournalError::TransactionValidation(
+            TransactionValidationError::UnknownAccount(name),
+        )),
+    }
+}
+
+/// a quick entry with its accounts found, ready to preview or record
+pub struct QuickTransaction {
+    pub effective_date: NaiveDate,
+    pub note: String,
+    pub amount: u64,
+    pub from: (AccountId, Name),
+    pub to: (AccountId, Name),
+}
+
+impl QuickTransaction {
+    /// an account left out of the entry is the journal's default cash account when it's the
+    /// one the money comes from, and its default expense account when it's the one it goes to
+    pub fn new(
+        input: &str,
+        accounts: &[(AccountId, Name)],
+        default_cash_account: Option<AccountId>,
+        default_expense_account: Option<AccountId>,
+        today: NaiveDate,
+    ) -> Result<Self, JournalError> {
+        let entry = parse_quick_entry(input, today)?;
+
+        let account = |name: Option<String>, default: Option<AccountId>, missing: &str| match name {
+            Some(name) => Ok(find_account(accounts, &name)?.clone()),
+            None => default
+                .and_then(|default| accounts.iter().find(|(id, _)| *id == default))
+                .cloned()
+                .ok_or_else(|| invalid(missing)),
+        };
+
+        Ok(Self {
+            effective_date: entry.effective_date,
+            note: entry.note,
+            amount: entry.amount,
+            from: account(
+                entry.from,
+                default_cash_account,
+                "expected an account before >, since the journal has no default cash account",
+            )?,
+            to: account(
+                entry.to,
+                default_expense_account,
+                "expected an account after >, since the journal has no default expense account",
+            )?,
+        })
+    }
+
+    pub fn entries(&self) -> Vec<BalanceUpdate> {
+        vec![
+            BalanceUpdate {
+                account_id: self.to.0,
+                amount: self.amount,
+                entry_type: EntryType::Debit,
+                memo: None,
+            },
+            BalanceUpdate {
+                account_id: self.from.0,
+                amount: self.amount,
+                entry_type: EntryType::Credit,
+                memo: None,
+            },
+        ]
+    }
+}
+
+#[derive(Deserialize)]
+pub struct QuickQuery {
+    entry: Option<String>,
+    recorded: Option<String>,
+}
+
+#[derive(Deserialize)]
+pub struct QuickForm {
+    entry: String,
+}
+
+async fn prepare(
+    state: &StateType,
+    journal: &JournalState,
+    authority: &Authority,
+    input: &str,
+    today: NaiveDate,
+) -> MonkestoResult<QuickTransaction> {
+    let accounts: Vec<(AccountId, Name)> = state
+        .journal_service
+        .list_journal_accounts(journal.id, authority)
+        .await?
+        .into_iter()
+        .map(|(account, _, _)| (account.id, account.name))
+        .collect();
+
+    Ok(QuickTransaction::new(
+        input,
+        &accounts,
+        journal.default_cash_account,
+        journal.default_expense_account,
+        today,
+    )?)
+}
+
+pub async fn quick_entry_page(
+    State(state): State<StateType>,
+    session: AuthSession<BackendType>,
+    access: JournalAccess,
+    Query(query): Query<QuickQuery>,
+) -> Result<Markup, Redirect> {
+    let user = get_user(session)?;
+
+    let preview = match query.entry.as_deref().filter(|entry| !entry.is_empty()) {
+        Some(entry) => Some(
+            prepare(
+                &state,
+                &access.journal,
+                &access.authority(),
+                entry,
+                user.timezone().date(state.clock.get_time()),
+            )
+            .await,
+        ),
+        None => None,
+    };
+
+    Ok(quick_page(
+        &access.journal,
+        query.entry.as_deref().unwrap_or_default(),
+        preview,
+        query.recorded.as_deref(),
+    ))
+}
+
+/// records the entry that was previewed. it's parsed again, so the transaction matches the
+/// accounts as they are now rather than when the preview was shown
+pub async fn quick_transact(
+    State(state): State<StateType>,
+    session: AuthSession<BackendType>,
+    access: JournalAccess<AppendTransaction>,
+    Form(form): Form<QuickForm>,
+) -> Result<Response, Redirect> {
+    let user = get_user(session)?;
+    let authority = access.authority();
+    let journal_id = access.journal.id;
+
+    let result: MonkestoResult<TransactionId> = async {
+        let transaction = prepare(
+            &state,
+            &access.journal,
+            &authority,
+            &form.entry,
+            user.timezone().date(state.clock.get_time()),
+        )
+        .await?;
+        let transaction_id: TransactionId = state.ids.generate();
+
+        let event_id = state
+            .journal_service
+            .create_transaction(
+                transaction_id,
+                journal_id,
+                transaction.entries(),
+                transaction.effective_date,
+                None,
+                authority.clone(),
+                state.clock.get_time(),
+            )
+            .await?;
+
+        state.journal_service.wait_for(event_id).await;
+
+        Ok(transaction_id)
+    }
+    .await;
+
+    match result {
+        Ok(transaction_id) => Ok(Redirect::to(&format!(
+            "/journal/{}/quick?recorded={}",
+            journal_id, transaction_id
+        ))
+        .into_response()),
+        Err(e) => {
+            let page = quick_page(&access.journal, &form.entry, Some(Err(e)), None);
+            Ok((StatusCode::UNPROCESSABLE_ENTITY, page).into_response())
+        }
+    }
+}
+
+fn quick_page(
+    journal: &JournalState,
+    entry: &str,
+    preview: Option<MonkestoResult<QuickTransaction>>,
+    recorded: Option<&str>,
+) -> Markup {
+    // once there's a preview, enter records it
+    let previewing = matches!(preview, Some(Ok(_)));
+
+    let id = journal.id;
+
+    let content = html! {
+        div class="flex flex-col gap-6 mx-auto w-full max-w-4xl" {
+            div class="bg-white dark:bg-gray-800 border border-gray-200 dark:border-gray-700 rounded-xl p-6" {
+                h3 class="text-lg font-semibold text-gray-900 dark:text-white mb-2" {
+                    "Quick Entry"
+                }
+                p class="text-sm text-gray-500 dark:text-gray-400 mb-6" {
+                    "Write a transaction as "
+                    code { "[date] [note] amount from>to" }
+                    ", such as "
+                    code { "coffee 4.25 cash>dining" }
+                    ". The amount is credited to the first account and debited to the second. "
+                    "Accounts can be shortened to the start of their name, or left out, as in "
+                    code { "coffee 4.25" }
+                    ", to use the journal's default cash and expense accounts. "
+                    "The date defaults to today and can be written like "
+                    code { "yesterday" } " or " code { "mar 3" }
+                    ", and amounts like " code { "1,200" } " or " code { "1.2k" } "."
+                }
+
+                @if let Some(transaction_id) = recorded {
+                    p class="mb-4 text-sm text-green-700 dark:text-green-400" {
+                        "Recorded. "
+                        a
+                        href=(format!("/journal/{}/transaction/{}", id, transaction_id))
+                        class="font-medium underline" {
+                            "View the transaction"
+                        }
+                    }
+                }
+
+                form method="get" action=(format!("/journal/{}/quick", id)) class="flex gap-3" {
+                    input
+                        type="text"
+                        name="entry"
+                        value=(entry)
+                        autofocus[!previewing]
+                        autocomplete="off"
+                        required
+                        placeholder="coffee 4.25 cash>dining"
+                        class="flex-1 rounded-md border border-gray-300 dark:border-gray-600 bg-white dark:bg-gray-800 px-3 py-2 font-mono text-sm text-gray-900 dark:text-white placeholder:text-gray-400 dark:placeholder:text-gray-500 focus:border-indigo-500 focus:ring-indigo-500 dark:focus:border-indigo-400";
+                    button class="px-4 py-2 bg-gray-100 text-gray-900 font-medium rounded-md hover:bg-gray-200 dark:bg-gray-700 dark:text-white dark:hover:bg-gray-600" type="submit" {
+                        "Preview"
+                    }
+                }
+
+                @match preview {
+                    Some(Ok(transaction)) => {
+                        div class="mt-6 p-4 bg-gray-50 dark:bg-gray-700 rounded-lg space-y-2 text-sm text-gray-900 dark:text-white" {
+                            p { (transaction.effective_date.format("%Y-%m-%d")) }
+                            @if !transaction.note.is_empty() {
+                                p class="text-gray-500 dark:text-gray-400" {
+                                    (transaction.note) " (transactions don't have notes yet, so this won't be saved)"
+                                }
+                            }
+                            div class="flex justify-between" {
+                                span { (transaction.to.1) }
+                                span { (format_amount(transaction.amount)) " Dr" }
+                            }
+                            div class="flex justify-between" {
+                                span { (transaction.from.1) }
+                                span { (format_amount(transaction.amount)) " Cr" }
+                            }
+                        }
+                        form method="post" action=(format!("/journal/{}/quick", id)) class="mt-4 flex justify-end" {
+                            input type="hidden" name="entry" value=(entry);
+                            button class="px-6 py-2 bg-indigo-600 text-white font-medium rounded-md hover:bg-indigo-700 focus:outline-none focus:ring-2 focus:ring-indigo-500 focus:ring-offset-2 dark:bg-indigo-500 dark:hover:bg-indigo-400 dark:focus:ring-indigo-400 dark:ring-offset-gray-800" type="submit" autofocus {
+                                "Record Transaction"
+                            }
+                        }
+                    },
+                    Some(Err(e)) => {
+                        p class="mt-6 text-sm text-red-600 dark:text-red-400" { (e) }
+                    },
+                    None => {}
+                }
+            }
+        }
+    };
+
+    layout::journal_layout(journal, content)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn name(name: &str) -> Name {
+        Name::try_new(name.to_string()).expect("invalid account name")
+    }
+
+    fn today() -> NaiveDate {
+        NaiveDate::from_ymd_opt(2026, 3, 2).expect("invalid date")
+    }
+
+    #[test]
+    fn quick_entries_are_split_around_the_amount() {
+        assert_eq!(
+            parse_quick_entry(
+                "2026-03-02 team coffee $4.25 petty cash > dining out",
+                today()
+            ),
+            Ok(QuickEntry {
+                effective_date: today(),
+                note: "team coffee".to_string(),
+                amount: 425,
+                from: Some("petty cash".to_string()),
+                to: Some("dining out".to_string()),
+            })
+        );
+
+        // a number at the end of the account's name isn't mistaken for the amount
+        let entry =
+            parse_quick_entry("12 visa 1234>fuel", today()).expect("failed to parse the entry");
+        assert_eq!(
+            (entry.amount, entry.from.as_deref()),
+            (1200, Some("visa 1234"))
+        );
+
+        let entry = parse_quick_entry("feb 27 rent 1.2k checking>rent", today())
+            .expect("failed to parse the entry");
+        assert_eq!(
+            entry.effective_date,
+            NaiveDate::from_ymd_opt(2026, 2, 27).expect("invalid date")
+        );
+        assert_eq!((entry.note.as_str(), entry.amount), ("rent", 120000));
+
+        let entry = parse_quick_entry("yesterday $1,050 cash>savings", today())
+            .expect("failed to parse the entry");
+        assert_eq!(
+            entry.effective_date,
+            NaiveDate::from_ymd_opt(2026, 3, 1).expect("invalid date")
+        );
+        assert_eq!((entry.note.as_str(), entry.amount), ("", 105000));
+
+        // either account can be left out
+        let entry = parse_quick_entry("coffee 4.25", today()).expect("failed to parse the entry");
+        assert_eq!(
+            (entry.note.as_str(), entry.amount, entry.from, entry.to),
+            ("coffee", 425, None, None)
+        );
+        let entry =
+            parse_quick_entry("coffee 4.25>dining", today()).expect("failed to parse the entry");
+        assert_eq!((entry.from, entry.to.as_deref()), (None, Some("dining")));
+        let entry = parse_quick_entry("4.25 cash>", today()).expect("failed to parse the entry");
+        assert_eq!((entry.from.as_deref(), entry.to), (Some("cash"), None));
+
+        assert!(parse_quick_entry("coffee 4.25 cash", today()).is_err());
+        assert!(parse_quick_entry("coffee cash>dining", today()).is_err());
+        assert!(parse_quick_entry("", today()).is_err());
+    }
+
+    #[test]
+    fn accounts_are_found_by_the_start_of_their_name() {
+        let cash = AccountId::new();
+        let dining = AccountId::new();
+        let accounts = [
+            (cash, name("Cash")),
+            (AccountId::new(), name("Cash Reserve")),
+            (dining, name("Dining")),
+        ];
+        let today = today();
+
+        let transaction =
+            QuickTransaction::new("coffee 4.25 cash>din", &accounts, None, None, today)
+                .expect("no match");
+        assert_eq!(transaction.effective_date, today);
+        assert_eq!(
+            transaction.entries(),
+            vec![
+                BalanceUpdate {
+                    account_id: dining,
+                    amount: 425,
+                    entry_type: EntryType::Debit,
+                    memo: None,
+                },
+                BalanceUpdate {
+                    account_id: cash,
+                    amount: 425,
+                    entry_type: EntryType::Credit,
+                    memo: None,
+                },
+            ]
+        );
+
+        assert!(QuickTransaction::new("4.25 ca>dining", &accounts, None, None, today).is_err());
+        assert!(QuickTransaction::new("4.25 cash>rent", &accounts, None, None, today).is_err());
+    }
+
+    #[test]
+    fn left_out_accounts_are_the_journal_defaults() {
+        let cash = AccountId::new();
+        let dining = AccountId::new();
+        let accounts = [(cash, name("Cash")), (dining, name("Dining"))];
+        let today = today();
+
+        let transaction =
+            QuickTransaction::new("coffee 4.25", &accounts, Some(cash), Some(dining), today)
+                .expect("the defaults weren't used");
+        assert_eq!((transaction.from.0, transaction.to.0), (cash, dining));
+
+        let transaction =
+            QuickTransaction::new("4.25 dining>cash", &accounts, Some(cash), None, today)
+                .expect("no match");
+        assert_eq!((transaction.from.0, transaction.to.0), (dining, cash));
+
+        assert!(QuickTransaction::new("coffee 4.25", &accounts, Some(cash), None, today).is_err());
+
+        // a default whose account was deleted is the same as no default
+        assert!(
+            QuickTransaction::new(
+                "coffee 4.25",
+                &accounts,
+                Some(AccountId::new()),
+                Some(dining),
+                today
+            )
+            .is_err()
+        );
+    }
+}
@@ -0,0 +1,428 @@
+use crate::BackendType;
+use crate::StateType;
+use crate::authn::get_user;
+use crate::journal::JournalError;
+use crate::journal::access::{AppendTransaction, JournalAccess, Requirement};
+use crate::journal::account::AccountId;
+use crate::journal::account::views::account_options;
+use crate::journal::layout;
+use crate::journal::transaction::views::journal_accounts;
+use crate::journal::transaction::{
+    BalanceUpdate, EntryType, TransactionValidationError, parse_amount, parse_effective_date,
+};
+use crate::monkesto_error::{FormRejection, MonkestoResult};
+use crate::time_provider::TimeProvider;
+use crate::validation::FormState;
+use axum::extract::State;
+use axum::response::Redirect;
+use axum_extra::extract::Form;
+use axum_login::AuthSession;
+use maud::{Markup, html};
+use rust_decimal::Decimal;
+use serde::Deserialize;
+use std::str::FromStr;
+
+/// how the shares of a split are read. percentages have to add up to 100, while shares are
+/// only compared with each other, so 1, 1 and 2 give the last account half
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SplitBasis {
+    Percent,
+    Shares,
+}
+
+impl FromStr for SplitBasis {
+    type Err = JournalError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "percent" => Ok(Self::Percent),
+            "shares" => Ok(Self::Shares),
+            _ => Err(invalid(&format!("unknown split basis {s}"))),
+        }
+    }
+}
+
+fn invalid(reason: &str) -> JournalError {
+    JournalError::TransactionValidation(TransactionValidationError::InvalidSplit(
+        reason.to_string(),
+    ))
+}
+
+/// divides an amount in cents by weight. every part is rounded down, and the cents left over
+/// go one each to the parts that lost the most to rounding, earlier parts first on a tie, so
+/// the same split always comes out the same and the parts add up to the amount
+pub fn split_amount(
+    amount: u64,
+    weights: &[Decimal],
+    basis: SplitBasis,
+) -> Result<Vec<u64>, JournalError> {
+    if weights.iter().any(Decimal::is_sign_negative) {
+        return Err(invalid("shares can't be negative"));
+    }
+
+    let total: Decimal = weights.iter().sum();
+
+    if total.is_zero() {
+        return Err(invalid("expected at least one share"));
+    }
+
+    if basis == SplitBasis::Percent && total != Decimal::ONE_HUNDRED {
+        return Err(invalid(&format!(
+            "the percentages add up to {total} rather than 100"
+        )));
+    }
+
+    let exact = weights
+        .iter()
+        .map(|weight| {
+            Decimal::from(amount)
+                .checked_mul(*weight)
+                .and_then(|scaled| scaled.checked_div(total))
+                .ok_or_else(|| invalid("the amount is too large to split"))
+        })
+        .collect::<Result<Vec<Decimal>, JournalError>>()?;
+
+    let mut parts: Vec<u64> = exact
+        .iter()
+        .map(|part| part.floor().try_into().unwrap_or_default())
+        .collect();
+
+    let mut by_remainder: Vec<usize> = (0..parts.len()).collect();
+    by_remainder.sort_by(|&a, &b| {
+        (exact[b] - exact[b].floor())
+            .cmp(&(exact[a] - exact[a].floor()))
+            .then(a.cmp(&b))
+    });
+
+    let residual = amount - parts.iter().sum::<u64>();
+    for &i in by_remainder.iter().take(residual as usize) {
+        parts[i] += 1;
+    }
+
+    Ok(parts)
+}
+
+/// the balanced transaction for an expense paid from one account and shared by the others.
+/// the payer is credited the whole amount, and parts that round to nothing are left out
+pub fn split_entries(
+    paid_from: AccountId,
+    amount: u64,
+    shares: &[(AccountId, Decimal)],
+    basis: SplitBasis,
+) -> Result<Vec<BalanceUpdate>, JournalError> {
+    let weights: Vec<Decimal> = shares.iter().map(|(_, weight)| *weight).collect();
+    let parts = split_amount(amount, &weights, basis)?;
+
+    let mut entries = vec![BalanceUpdate {
+        account_id: paid_from,
+        amount,
+        entry_type: EntryType::Credit,
+        memo: None,
+    }];
+
+    entries.extend(shares.iter().zip(parts).filter(|(_, part)| *part > 0).map(
+        |((account_id, _), part)| BalanceUpdate {
+            account_id: *account_id,
+            amount: part,
+            entry_type: EntryType::Debit,
+            memo: None,
+        },
+    ));
+
+    Ok(entries)
+}
+
+#[derive(Deserialize)]
+pub struct SplitForm {
+    effective_date: String,
+    paid_from: String,
+    amount: String,
+    basis: String,
+    account: Vec<String>,
+    share: Vec<String>,
+}
+
+pub async fn split_entry_page(
+    State(state): State<StateType>,
+    session: AuthSession<BackendType>,
+    access: JournalAccess,
+) -> Result<Markup, Redirect> {
+    let user = get_user(session)?;
+
+    let mut form = FormState::default();
+    form.keep(
+        "effective_date",
+        user.timezone()
+            .date(state.clock.get_time())
+            .format("%Y-%m-%d")
+            .to_string(),
+    );
+
+    Ok(split_page(&state, &access, &form).await)
+}
+
+pub async fn split_transact(
+    State(state): State<StateType>,
+    access: JournalAccess<AppendTransaction>,
+    Form(form): Form<SplitForm>,
+) -> Result<Redirect, FormRejection> {
+    let mut form_state = FormState::default();
+    form_state.keep("effective_date", form.effective_date.as_str());
+    form_state.keep("paid_from", form.paid_from.as_str());
+    form_state.keep("amount", form.amount.as_str());
+    form_state.keep("basis", form.basis.as_str());
+
+    let effective_date =
+        form_state.check("effective_date", parse_effective_date(&form.effective_date));
+    let paid_from = form_state.check("paid_from", AccountId::from_str(&form.paid_from));
+    let amount = form_state.check("amount", parse_amount(&form.amount));
+    let basis = form_state.check("basis", SplitBasis::from_str(&form.basis));
+
+    let mut shares = Vec::new();
+
+    for (idx, account) in form.account.iter().enumerate() {
+        let share_field = format!("share-{idx}");
+        let share = form.share.get(idx).map(String::as_str).unwrap_or_default();
+
+        form_state.keep(&format!("account-{idx}"), account.as_str());
+        form_state.keep(&share_field, share);
+
+        // rows without an account are the spare ones the form always shows
+        if let Ok(account_id) = AccountId::from_str(account)
+            && let Some(weight) = form_state.check(
+                &share_field,
+                Decimal::from_str(share.trim()).map_err(|_| invalid("expected a number")),
+            )
+        {
+            shares.push((account_id, weight));
+        }
+    }
+
+    if let (Some(effective_date), Some(paid_from), Some(amount), Some(basis)) =
+        (effective_date, paid_from, amount, basis)
+        && form_state.is_valid()
+    {
+        let result: MonkestoResult<_> = async {
+            let transaction_id = state.ids.generate();
+            let event_id = state
+                .journal_service
+                .create_transaction(
+                    transaction_id,
+                    access.journal.id,
+                    split_entries(paid_from, amount, &shares, basis)?,
+                    effective_date,
+                    None,
+                    access.authority(),
+                    state.clock.get_time(),
+                )
+                .await?;
+
+            state.journal_service.wait_for(event_id).await;
+
+            Ok(transaction_id)
+        }
+        .await;
+
+        match result {
+            Ok(transaction_id) => {
+                return Ok(Redirect::to(&format!(
+                    "/journal/{}/transaction/{}",
+                    access.journal.id, transaction_id
+                )));
+            }
+            Err(e) => form_state.fail(e),
+        }
+    }
+
+    let page = split_page(&state, &access, &form_state).await;
+
+    Err(form_state.rejected(page).into())
+}
+
+async fn split_page<R: Requirement>(
+    state: &StateType,
+    access: &JournalAccess<R>,
+    form: &FormState,
+) -> Markup {
+    let id = &access.journal.id.to_string();
+
+    let accounts_res = journal_accounts(state, access.journal.id, &access.authority()).await;
+
+    let rows = (0..)
+        .take_while(|i| form.value(&format!("account-{i}")).is_some())
+        .count()
+        .max(4);
+
+    let input_class = "w-full rounded-md border border-gray-300 dark:border-gray-600 bg-white dark:bg-gray-800 px-3 py-2 text-gray-900 dark:text-white focus:border-indigo-500 focus:ring-indigo-500 dark:focus:border-indigo-400";
+    let label_class = "block text-sm font-medium text-gray-700 dark:text-gray-300 mb-2";
+
+    let account_select = |field: &str, name: &str, placeholder: &str| {
+        html! {
+            select class=(input_class) id=(field) name=(name)
+            aria-invalid=[form.invalid(field)]
+            aria-describedby=[form.described_by(field)] {
+                option value="" { (placeholder) }
+                @match &accounts_res {
+                    Ok(accounts) => { (account_options(accounts, form.value(field))) },
+                    Err(e) => {
+                        option value="" { "failed to fetch the accounts: " (e) }
+                    }
+                }
+            }
+            (form.message(field))
+        }
+    };
+
+    let content = html! {
+        div class="flex flex-col gap-6 mx-auto w-full max-w-4xl" {
+            div class="bg-white dark:bg-gray-800 border border-gray-200 dark:border-gray-700 rounded-xl p-6" {
+                h3 class="text-lg font-semibold text-gray-900 dark:text-white mb-2" {
+                    "Split Entry"
+                }
+                p class="text-sm text-gray-500 dark:text-gray-400 mb-6" {
+                    "Share one expense between several accounts. The whole amount is credited to the account it was paid from, "
+                    "and each account below is debited its part. Percentages have to add up to 100, "
+                    "while shares are weighed against each other. Leftover cents go to the parts that were rounded down the most."
+                }
+
+                form method="post" action=(format!("/journal/{}/transaction/split", id)) class="space-y-6" {
+                    div class="grid gap-4 md:grid-cols-3" {
+                        div {
+                            label for="effective_date" class=(label_class) { "Date" }
+                            input class=(input_class) type="date" id="effective_date" name="effective_date" required
+                            value=[form.value("effective_date")]
+                            aria-invalid=[form.invalid("effective_date")]
+                            aria-describedby=[form.described_by("effective_date")];
+                            (form.message("effective_date"))
+                        }
+                        div {
+                            label for="amount" class=(label_class) { "Amount" }
+                            input class=(input_class) type="number" step="0.01" min="0" placeholder="0.00" id="amount" name="amount" required
+                            value=[form.value("amount")]
+                            aria-invalid=[form.invalid("amount")]
+                            aria-describedby=[form.described_by("amount")];
+                            (form.message("amount"))
+                        }
+                        div {
+                            label for="paid_from" class=(label_class) { "Paid from" }
+                            (account_select("paid_from", "paid_from", "Select an account"))
+                        }
+                    }
+
+                    div {
+                        label for="basis" class=(label_class) { "Split by" }
+                        select class=(input_class) id="basis" name="basis" {
+                            option value="percent" selected[form.value("basis") == Some("percent")] { "Percentage" }
+                            option value="shares" selected[form.value("basis") == Some("shares")] { "Shares" }
+                        }
+                        (form.message("basis"))
+                    }
+
+                    div class="space-y-3" {
+                        @for i in 0..rows {
+                            @let account_field = format!("account-{i}");
+                            @let share_field = format!("share-{i}");
+                            div class="grid grid-cols-3 gap-3 p-4 bg-gray-50 dark:bg-gray-700 rounded-lg" {
+                                div class="col-span-2" {
+                                    label for=(account_field) class=(label_class) { "Account" }
+                                    (account_select(&account_field, "account", "Select an account"))
+                                }
+                                div {
+                                    label for=(share_field) class=(label_class) { "Share" }
+                                    input class=(input_class) type="number" step="any" min="0" id=(share_field) name="share"
+                                    value=[form.value(&share_field)]
+                                    aria-invalid=[form.invalid(&share_field)]
+                                    aria-describedby=[form.described_by(&share_field)];
+                                    (form.message(&share_field))
+                                }
+                            }
+                        }
+                    }
+
+                    (form.summary())
+
+                    div class="flex justify-end pt-4 border-t border-gray-200 dark:border-gray-600" {
+                        button class="px-6 py-2 bg-indigo-600 text-white font-medium rounded-md hover:bg-indigo-700 focus:outline-none focus:ring-2 focus:ring-indigo-500 focus:ring-offset-2 dark:bg-indigo-500 dark:hover:bg-indigo-400 dark:focus:ring-indigo-400 dark:ring-offset-gray-800" type="submit" {
+                            "Record Transaction"
+                        }
+                    }
+                }
+            }
+        }
+    };
+
+    layout::journal_layout(&access.journal, content)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rust_decimal::dec;
+
+    #[test]
+    fn leftover_cents_go_to_the_largest_remainders() {
+        // 100.00 three ways leaves a cent, which goes to the first of the tied parts
+        assert_eq!(
+            split_amount(10000, &[dec!(1), dec!(1), dec!(1)], SplitBasis::Shares),
+            Ok(vec![3334, 3333, 3333])
+        );
+
+        // 10.01 at 50/25/25 is 500.5, 250.25 and 250.25, so the half cent wins
+        assert_eq!(
+            split_amount(1001, &[dec!(50), dec!(25), dec!(25)], SplitBasis::Percent),
+            Ok(vec![501, 250, 250])
+        );
+
+        assert_eq!(
+            split_amount(
+                100,
+                &[dec!(33.33), dec!(33.33), dec!(33.34)],
+                SplitBasis::Percent
+            ),
+            Ok(vec![33, 33, 34])
+        );
+
+        assert!(split_amount(100, &[dec!(60), dec!(30)], SplitBasis::Percent).is_err());
+        assert!(split_amount(100, &[dec!(0), dec!(0)], SplitBasis::Shares).is_err());
+        assert!(split_amount(100, &[dec!(2), dec!(-1)], SplitBasis::Shares).is_err());
+    }
+
+    #[test]
+    fn splits_balance_against_the_payer() {
+        let card = AccountId::new();
+        let alex = AccountId::new();
+        let sam = AccountId::new();
+
+        let entries = split_entries(
+            card,
+            2,
+            &[(alex, dec!(1)), (sam, dec!(2)), (card, dec!(0))],
+            SplitBasis::Shares,
+        )
+        .expect("the split is valid");
+
+        assert_eq!(
+            entries,
+            vec![
+                BalanceUpdate {
+                    account_id: card,
+                    amount: 2,
+                    entry_type: EntryType::Credit,
+                    memo: None,
+                },
+                BalanceUpdate {
+                    account_id: alex,
+                    amount: 1,
+                    entry_type: EntryType::Debit,
+                    memo: None,
+                },
+                BalanceUpdate {
+                    account_id: sam,
+                    amount: 1,
+                    entry_type: EntryType::Debit,
+                    memo: None,
+                },
+            ]
+        );
+    }
+}
@@ -1,214 +1,981 @@
 use crate::BackendType;
 use crate::StateType;
+use crate::authn::profile::avatar;
+use crate::authn::shortcuts::ShortcutAction;
 use crate::authn::user::UserState;
 use crate::authn::{UserId, get_user};
 use crate::authority::Actor;
 use crate::authority::Authority;
-use crate::email::Email;
-use crate::journal::JournalId;
+use crate::i18n::{t, tf};
+use crate::journal::access::{JournalAccess, Requirement, check_access};
 use crate::journal::account::AccountId;
+use crate::journal::account::views::account_options;
+use crate::journal::comment::{self, CommentId};
+use crate::journal::fiscal::{Period, PeriodKind};
+use crate::journal::json::TransactionJson;
 use crate::journal::layout;
-use crate::journal::service::{AccountState, TransactionState};
-use crate::journal::transaction::EntryType;
+use crate::journal::print::{PrintHeading, print_button, printable};
+use crate::journal::provenance::{self, LastChange};
+use crate::journal::service::{
+    AccountState, CommentState, TemplateState, TransactionState, TransactionTotals,
+};
+use crate::journal::template::{self, TemplateId};
+use crate::journal::transaction::{
+    EntryType, MAX_MEMO_LENGTH, TransactionId, format_voucher_number, parse_effective_date,
+};
+use crate::journal::undo;
+use crate::journal::{JournalId, Permissions};
 use crate::monkesto_error::UrlError;
+use crate::monkesto_error::{ErrorRedirect, OrRedirect};
 use crate::monkesto_error::{MonkestoError, MonkestoResult};
-use crate::time_provider::Timestamp;
+use crate::negotiate::WantsJson;
+use crate::time_provider::{TimeProvider, Timestamp};
+use crate::validation::FormState;
+use axum::Json;
 use axum::extract::Path;
 use axum::extract::Query;
 use axum::extract::State;
-use axum::response::Redirect;
+use axum::response::{IntoResponse, Redirect, Response};
 use axum_login::AuthSession;
 use maud::Markup;
+use maud::PreEscaped;
 use maud::html;
-use std::collections::HashMap;
+use serde::Deserialize;
+use std::collections::{HashMap, HashSet};
 use std::str::FromStr;
 
+/// the template to fill the new transaction form in from
+#[derive(Deserialize)]
+pub struct TemplateQuery {
+    template: Option<String>,
+}
+
+#[derive(Default, Deserialize)]
+pub struct TotalsRange {
+    from: Option<String>,
+    to: Option<String>,
+}
+
+pub(crate) fn format_balance(balance: i64) -> String {
+    let abs = balance.abs();
+    format!(
+        "${}.{:02} {}",
+        abs / 100,
+        abs % 100,
+        if balance < 0 { "Dr" } else { "Cr" }
+    )
+}
+
+pub(crate) fn format_amount(amount: u64) -> String {
+    format!("${}.{:02}", amount / 100, amount % 100)
+}
+
+/// checks the entries as they're typed. the form still submits without it, and the server
+/// validates the transaction either way
+const TRANSACTION_FORM_SCRIPT: &str = r#"
+    (() => {
+        const form = document.getElementById('transaction-form');
+        const rows = document.getElementById('entry-rows');
+        const status = document.getElementById('entry-balance');
+        if (!form || !rows || !status) return;
+
+        const cents = (input) => {
+            const value = Number.parseFloat(input.value);
+            return Number.isFinite(value) && value > 0 ? Math.round(value * 100) : 0;
+        };
+        const dollars = (amount) => '$' + (amount / 100).toFixed(2);
+
+        const update = () => {
+            let debits = 0;
+            let credits = 0;
+            rows.querySelectorAll('[data-entry-row]').forEach((row) => {
+                const amount = cents(row.querySelector('[name=amount]'));
+                if (row.querySelector('[name=entry_type]').value === 'Cr') {
+                    credits += amount;
+                } else {
+                    debits += amount;
+                }
+            });
+
+            const balanced = debits === credits && debits > 0;
+            const labels = status.dataset;
+            status.textContent = labels.debits + ' ' + dollars(debits) + ' · ' + labels.credits + ' ' + dollars(credits) +
+                (balanced ? ' · ' + labels.balanced : ' · ' + labels.outOfBalance + ' ' + dollars(Math.abs(debits - credits)));
+            status.classList.toggle('text-red-600', !balanced && debits + credits > 0);
+            return balanced;
+        };
+
+        const showControls = () => {
+            const all = rows.querySelectorAll('[data-entry-row]');
+            all.forEach((row) => {
+                const controls = row.querySelector('[data-entry-controls]');
+                controls.classList.toggle('hidden', all.length <= 2);
+                controls.classList.toggle('flex', all.length > 2);
+            });
+        };
+
+        rows.addEventListener('input', update);
+        rows.addEventListener('change', update);
+        rows.addEventListener('focusout', (event) => {
+            if (event.target.name === 'amount' && event.target.value !== '') {
+                event.target.value = (cents(event.target) / 100).toFixed(2);
+                update();
+            }
+        });
+        rows.addEventListener('click', (event) => {
+            const remove = event.target.closest('[data-remove-entry]');
+            if (remove && rows.querySelectorAll('[data-entry-row]').length > 2) {
+                remove.closest('[data-entry-row]').remove();
+                showControls();
+                update();
+            }
+        });
+
+        // rows keep unique ids so their labels and error messages stay attached
+        let nextRow = rows.querySelectorAll('[data-entry-row]').length;
+        const add = document.getElementById('add-entry');
+        add.classList.remove('hidden');
+        add.addEventListener('click', () => {
+            const all = rows.querySelectorAll('[data-entry-row]');
+            const row = all[all.length - 1].cloneNode(true);
+            row.querySelectorAll('[id$="-error"]').forEach((message) => message.remove());
+            row.querySelectorAll('[id]').forEach((field) => {
+                field.id = field.id.replace(/\d+$/, nextRow);
+                field.removeAttribute('aria-invalid');
+                field.removeAttribute('aria-describedby');
+            });
+            row.querySelectorAll('label[for]').forEach((label) => {
+                label.htmlFor = label.htmlFor.replace(/\d+$/, nextRow);
+            });
+            nextRow += 1;
+            row.querySelectorAll('input').forEach((input) => {
+                input.value = '';
+                input.required = false;
+            });
+            row.querySelectorAll('select').forEach((select) => { select.selectedIndex = 0; });
+            rows.appendChild(row);
+            showControls();
+        });
+
+        // the new transaction is swapped into the list without a reload. anything other than
+        // the list coming back, such as a rejected form, is submitted again the ordinary way
+        const list = document.getElementById('transaction-list');
+        const swap = async () => {
+            const response = await fetch(form.action, {
+                method: 'POST',
+                body: new URLSearchParams(new FormData(form)),
+                headers: { 'X-Partial': 'true' },
+                redirect: 'manual',
+            });
+            if (!response.ok) throw new Error(response.statusText);
+            document.getElementById('transaction-list').outerHTML = await response.text();
+            form.querySelectorAll('[name=amount], [name=memo], [name=payee]').forEach((input) => { input.value = ''; });
+            update();
+        };
+
+        form.addEventListener('submit', (event) => {
+            if (!update()) {
+                event.preventDefault();
+                status.classList.add('text-red-600');
+            } else if (list && form.hasAttribute('data-partial') && window.fetch) {
+                event.preventDefault();
+                swap().catch(() => form.submit());
+            }
+        });
+
+        showControls();
+        update();
+    })();
+"#;
+
+/// turns each account select in the transaction form into a search box whose matches come from
+/// the account search endpoint. the arrow keys move through the matches, enter picks one and
+/// escape closes them. picking a match selects its account in the hidden select, so the form
+/// submits the same either way
+const ACCOUNT_PICKER_SCRIPT: &str = r#"
+    (() => {
+        const rows = document.getElementById('entry-rows');
+        if (!rows || !window.fetch) return;
+
+        rows.querySelectorAll('[data-account-picker]').forEach((picker) => {
+            const query = picker.querySelector('[data-account-query]');
+            const select = picker.querySelector('select');
+            query.classList.remove('hidden');
+            select.classList.add('hidden');
+            select.tabIndex = -1;
+            const label = picker.parentElement.querySelector('label');
+            if (label) label.htmlFor = query.id;
+        });
+
+        const parts = (query) => {
+            const picker = query.closest('[data-account-picker]');
+            return {
+                picker,
+                select: picker.querySelector('select'),
+                list: picker.querySelector('[data-account-results]'),
+            };
+        };
+
+        const options = (list) => Array.from(list.querySelectorAll('[role=option]'));
+
+        const close = (query) => {
+            const { list } = parts(query);
+            list.classList.add('hidden');
+            list.replaceChildren();
+            query.setAttribute('aria-expanded', 'false');
+            query.removeAttribute('aria-activedescendant');
+        };
+
+        const activate = (query, index) => {
+            const found = options(parts(query).list);
+            if (found.length === 0) return;
+            const active = (index + found.length) % found.length;
+            found.forEach((option, i) => option.setAttribute('aria-selected', String(i === active)));
+            query.setAttribute('aria-activedescendant', found[active].id);
+            found[active].scrollIntoView({ block: 'nearest' });
+        };
+
+        const choose = (query, option) => {
+            const { select } = parts(query);
+            query.value = option.dataset.accountName;
+            select.value = option.dataset.accountId;
+            select.dispatchEvent(new Event('change', { bubbles: true }));
+            close(query);
+        };
+
+        // only the latest search's matches are shown, however the responses arrive
+        let latest = 0;
+        const search = async (query) => {
+            const { picker, list } = parts(query);
+            const request = ++latest;
+            const response = await fetch(picker.dataset.search + '?q=' + encodeURIComponent(query.value), {
+                headers: { Accept: 'text/html' },
+            });
+            if (!response.ok || request !== latest || document.activeElement !== query) return;
+
+            list.innerHTML = await response.text();
+            options(list).forEach((option, i) => { option.id = list.id + '-' + i; });
+            query.setAttribute('aria-controls', list.id);
+            query.removeAttribute('aria-activedescendant');
+            const open = options(list).length > 0;
+            list.classList.toggle('hidden', !open);
+            query.setAttribute('aria-expanded', String(open));
+        };
+
+        let timer;
+        rows.addEventListener('input', (event) => {
+            const query = event.target.closest('[data-account-query]');
+            if (!query) return;
+            // the typed text isn't an account until one of the matches is picked
+            parts(query).select.value = '';
+            clearTimeout(timer);
+            timer = setTimeout(() => search(query).catch(() => close(query)), 150);
+        });
+
+        rows.addEventListener('keydown', (event) => {
+            const query = event.target.closest('[data-account-query]');
+            if (!query) return;
+            const found = options(parts(query).list);
+            const current = found.findIndex((option) => option.getAttribute('aria-selected') === 'true');
+
+            if (event.key === 'ArrowDown' || event.key === 'ArrowUp') {
+                event.preventDefault();
+                if (found.length === 0) {
+                    search(query).catch(() => close(query));
+                } else {
+                    activate(query, event.key === 'ArrowDown' ? current + 1 : (current < 0 ? -1 : current - 1));
+                }
+            } else if (event.key === 'Enter' && current >= 0) {
+                event.preventDefault();
+                choose(query, found[current]);
+            } else if (event.key === 'Escape' && found.length > 0) {
+                event.preventDefault();
+                close(query);
+            }
+        });
+
+        // mousedown comes before the search box loses focus, which would close the matches
+        rows.addEventListener('mousedown', (event) => {
+            const option = event.target.closest('[data-account-results] [role=option]');
+            if (!option) return;
+            event.preventDefault();
+            choose(option.closest('[data-account-picker]').querySelector('[data-account-query]'), option);
+        });
+
+        rows.addEventListener('focusout', (event) => {
+            if (event.target.matches('[data-account-query]')) close(event.target);
+        });
+    })();
+"#;
+
 pub async fn transaction_list_page(
     State(state): State<StateType>,
     session: AuthSession<BackendType>,
-    Path(id): Path<String>,
+    access: JournalAccess,
     Query(err): Query<UrlError>,
-) -> Result<Markup, Redirect> {
+    Query(range): Query<TotalsRange>,
+    Query(prefill): Query<TemplateQuery>,
+    WantsJson(json): WantsJson,
+) -> Result<Response, Redirect> {
     let user = get_user(session)?;
-    let user_authority = Authority::Direct(Actor::User(user.id));
 
-    let journal_id_res = JournalId::from_str(&id);
+    if json {
+        return Ok(transactions_json(&state, &access).await.into_response());
+    }
 
-    let transactions_res: MonkestoResult<Vec<(TransactionState, Authority, Timestamp)>> =
-        match &journal_id_res {
-            Ok(id) => state
-                .journal_service
-                .list_journal_transactions(*id, &user_authority)
-                .await
-                .map_err(|e| e.into()),
-            Err(e) => Err(e.clone().into()),
-        };
+    let form = match prefill.template {
+        Some(template_id) => {
+            let template = match TemplateId::from_str(&template_id) {
+                Ok(template_id) => state
+                    .journal_service
+                    .get_template(access.journal.id, template_id, &access.authority())
+                    .await
+                    .map_err(MonkestoError::from),
+                Err(e) => Err(e.into()),
+            };
 
-    let accounts_res: MonkestoResult<HashMap<AccountId, AccountState>> = match &journal_id_res {
-        Ok(id) => match state
-            .journal_service
-            .list_journal_accounts(*id, &user_authority)
-            .await
-        {
-            Ok(accounts) => Ok(accounts
-                .into_iter()
-                .map(|(state, _, _)| (state.id, state))
-                .collect::<HashMap<AccountId, AccountState>>()),
-            Err(e) => Err(e.into()),
-        },
-        Err(e) => Err(e.clone().into()),
+            match template {
+                Ok(template) => template::prefill(&template.entries),
+                Err(e) => {
+                    let mut form = FormState::default();
+                    form.fail(e);
+                    form
+                }
+            }
+        }
+        None => FormState::default(),
     };
 
-    let members_res: MonkestoResult<HashMap<UserId, UserState>> = match &journal_id_res {
-        Ok(id) => match state
-            .journal_service
-            .list_journal_members(*id, &user_authority)
+    Ok(
+        transaction_list(&state, &user, &access, err.err, range, &form)
             .await
-        {
-            Ok(ids) => match state.authn_service.fetch_users(ids.as_slice()).await {
-                Ok(members) => Ok(members
+            .into_response(),
+    )
+}
+
+/// the journal's transactions in the order the page lists them, for scripts reading it as json
+async fn transactions_json(
+    state: &StateType,
+    access: &JournalAccess,
+) -> Result<Json<Vec<TransactionJson>>, ErrorRedirect> {
+    let journal_id = access.journal.id;
+
+    let transactions = state
+        .journal_service
+        .list_journal_transactions(journal_id, &access.authority())
+        .await
+        .or_redirect(&format!("/journal/{journal_id}"))?;
+
+    Ok(Json(
+        transactions
+            .iter()
+            .map(|(transaction, _, _)| TransactionJson::from(transaction))
+            .collect(),
+    ))
+}
+
+/// the journal's accounts in the order the owner arranged them, for naming the accounts
+/// entries post to and for picking them in the form
+pub(crate) async fn journal_accounts(
+    state: &StateType,
+    journal_id: JournalId,
+    user_authority: &Authority,
+) -> MonkestoResult<Vec<AccountState>> {
+    state
+        .journal_service
+        .list_journal_accounts(journal_id, user_authority)
+        .await
+        .map(|accounts| accounts.into_iter().map(|(state, _, _)| state).collect())
+        .map_err(|e| e.into())
+}
+
+/// the journal's transactions as cards. the page lists them and the list fragment swaps them
+/// in on their own, so both render them here
+pub async fn transaction_cards<R: Requirement>(
+    state: &StateType,
+    access: &JournalAccess<R>,
+    accounts_res: &MonkestoResult<Vec<AccountState>>,
+) -> Markup {
+    let user_authority = access.authority();
+
+    let journal_id = access.journal.id;
+    let id = &journal_id.to_string();
+
+    let transactions_res: MonkestoResult<Vec<(TransactionState, Authority, Timestamp)>> = state
+        .journal_service
+        .list_journal_transactions(journal_id, &user_authority)
+        .await
+        .map_err(|e| e.into());
+
+    // creators may be the owner or somebody who has since left the journal,
+    // so they're fetched by id in one query rather than from the member list
+    let creators_res: MonkestoResult<HashMap<UserId, UserState>> = match &transactions_res {
+        Ok(transactions) => {
+            let creator_ids: Vec<UserId> = transactions
+                .iter()
+                .filter_map(|(_, authority, _)| match authority.actor() {
+                    Actor::User(id) => Some(*id),
+                    Actor::System | Actor::Anonymous | Actor::Deleted => None,
+                })
+                .collect::<HashSet<UserId>>()
+                .into_iter()
+                .collect();
+
+            match state
+                .authn_service
+                .fetch_users(creator_ids.as_slice())
+                .await
+            {
+                Ok(creators) => Ok(creators
                     .into_iter()
-                    .map(|m| (m.id, m))
+                    .map(|c| (c.id, c))
                     .collect::<HashMap<UserId, UserState>>()),
                 Err(e) => Err(e.into()),
-            },
-            Err(e) => Err(e.into()),
-        },
-        Err(e) => Err(e.clone().into()),
+            }
+        }
+        // the transaction list shows its own error
+        Err(_) => Ok(HashMap::new()),
     };
 
-    let mut nonmember_cache: HashMap<UserId, Email> = HashMap::new();
+    // the balance of each entry's account after its transaction was applied
+    let running_balances: Vec<Vec<i64>> = match &transactions_res {
+        Ok(transactions) => {
+            let mut balances: HashMap<AccountId, i64> = HashMap::new();
 
-    let content = html! {
-        @if let Ok(ref transactions) = transactions_res {
-            @for (tx, tx_authority, _) in transactions {
-                a
-                href=(format!("/journal/{}/transaction/{}", id, tx.id))
-                class="block p-4 bg-white dark:bg-gray-800 border border-gray-200 dark:border-gray-700 rounded-xl hover:bg-gray-50 dark:hover:bg-gray-700 transition-colors"{
-                    div class="space-y-3" {
-                        div class="space-y-2" {
-                            @for entry in tx.entries.iter() {
-                                @let entry_amount = format!("${}.{:02}", entry.amount / 100, entry.amount % 100);
-
-                                div class="flex justify-between items-center" {
-                                    span class="text-base font-medium text-gray-900 dark:text-white" {
-                                        @match &accounts_res {
-                                            Ok(accounts) => (accounts.get(&entry.account_id).map(|acct| acct.name.as_ref()).unwrap_or("Unknown Account")),
-                                            Err(e) => {"encountered an error while fetching accounts: " (e)}
+            transactions
+                .iter()
+                .map(|(tx, _, _)| {
+                    for entry in tx.entries.iter() {
+                        *balances.entry(entry.account_id).or_default() += match entry.entry_type {
+                            EntryType::Credit => entry.amount as i64,
+                            EntryType::Debit => -(entry.amount as i64),
+                        };
+                    }
+
+                    tx.entries
+                        .iter()
+                        .map(|entry| balances.get(&entry.account_id).copied().unwrap_or_default())
+                        .collect()
+                })
+                .collect()
+        }
+        Err(_) => Vec::new(),
+    };
+
+    html! {
+        div id="transaction-list" class="flex flex-col gap-6" {
+            @if let Ok(transactions) = &transactions_res {
+                @for ((tx, tx_authority, _), balances) in transactions.iter().zip(running_balances.iter()) {
+                    a
+                    href=(format!("/journal/{}/transaction/{}", id, tx.id))
+                    class="block p-4 bg-white dark:bg-gray-800 border border-gray-200 dark:border-gray-700 rounded-xl hover:bg-gray-50 dark:hover:bg-gray-700 transition-colors"{
+                        div class="space-y-3" {
+                            div class="text-sm font-medium text-gray-500 dark:text-gray-400" {
+                                (format_voucher_number(tx.number)) " · " (tx.effective_date.format("%Y-%m-%d"))
+                                @if let Some(payee) = &tx.payee {
+                                    " · " (payee)
+                                }
+                                @if tx.reverses.is_some() {
+                                    " · " (t("transaction.reversal"))
+                                }
+                                @if tx.transfer.is_some() {
+                                    " · " (t("transaction.transfer"))
+                                }
+                            }
+                            div class="space-y-2" {
+                                @for (entry, balance) in tx.entries.iter().zip(balances.iter()) {
+                                    @let entry_amount = format_amount(entry.amount);
+
+                                    div class="flex justify-between items-center" {
+                                        div {
+                                            span class="text-base font-medium text-gray-900 dark:text-white" {
+                                                @match accounts_res {
+                                                    Ok(accounts) => (accounts.iter().find(|acct| acct.id == entry.account_id).map(|acct| acct.name.as_ref()).unwrap_or(t("transaction.unknown_account"))),
+                                                    Err(e) => {(tf("transaction.accounts_failed", &[("error", e)]))}
+                                                }
+                                            }
+                                            @if let Some(memo) = &entry.memo {
+                                                p class="text-xs text-gray-500 dark:text-gray-400" { (memo) }
+                                            }
+                                        }
+
+                                        div class="text-right" {
+                                            span class="text-base text-gray-700 dark:text-gray-300" {
+                                                (entry_amount) " " (entry.entry_type)
+                                            }
+                                            div class="text-xs text-gray-500 dark:text-gray-400" {
+                                                (tf("transaction.balance", &[("balance", &format_balance(*balance))]))
+                                            }
                                         }
                                     }
+                                }
 
-                                    span class="text-base text-gray-700 dark:text-gray-300" {
-                                        (entry_amount) " " (entry.entry_type)
+                                div class="text-xs text-gray-400 dark:text-gray-500" {
+                                    @match tx_authority.actor() {
+                                        Actor::User(id) => {
+                                            @match &creators_res {
+                                                Ok(creators) => {
+                                                    @if let Some(creator) = creators.get(id) {
+                                                        span class="inline-flex items-center gap-1.5" {
+                                                            (avatar(creator))
+                                                            (creator.display_name())
+                                                        }
+                                                    } @else {
+                                                        (tf("transaction.user_failed", &[("error", id)]))
+                                                    }
+                                                },
+                                                Err(e) => {(tf("transaction.users_failed", &[("error", e)]))}
+                                            }
+                                        },
+                                        Actor::System => {(t("actor.system"))},
+                                        Actor::Anonymous => {(t("actor.anonymous"))},
+                                        Actor::Deleted => {(t("actor.deleted"))}
                                     }
                                 }
                             }
+                        }
+                    }
+                }
+            } @else if let Err(e) = &transactions_res {
+                p class="text-sm text-red-600 dark:text-red-400" {
+                    (tf("transaction.list_failed", &[("error", e)]))
+                }
+            }
+        }
+    }
+}
 
-                            div class="text-xs text-gray-400 dark:text-gray-500" {
-                                @match tx_authority.actor() {
-                                    Actor::User(id) => {
-                                        @match &members_res {
-                                            Ok(members) => {
-                                                @if let Some(email) = members.get(id).map(|m| m.email.clone()) {
-                                                    (email.to_string())
-                                                } @else if let Some(email) = nonmember_cache.get(id)  {
-                                                    (email.to_string())
-                                                } @else {
-                                                    // the user may be the owner or somebody who left the journal after creating the transaction
-                                                    @match state.authn_service.fetch_user(*id).await {
-                                                        Ok(user) => {
-                                                            // maud assumes that you never want to call functions for
-                                                            // side effects and makes you assign a value to the result
-                                                            @let _ = nonmember_cache.insert(user.id, user.email.clone());
-                                                            (user.email.to_string())
-                                                        },
-                                                        Err(e) => {"failed to fetch user: " (e)}
-                                                    }
+/// just the transaction cards, for scripts that refresh the list without reloading the page
+pub async fn transaction_list_fragment(
+    State(state): State<StateType>,
+    access: JournalAccess,
+) -> Markup {
+    let accounts_res = journal_accounts(&state, access.journal.id, &access.authority()).await;
+
+    transaction_cards(&state, &access, &accounts_res).await
+}
+
+/// the transaction list, with the new transaction form filled in from a rejected submission
+pub async fn transaction_list<R: Requirement>(
+    state: &StateType,
+    user: &UserState,
+    access: &JournalAccess<R>,
+    err: Option<String>,
+    range: TotalsRange,
+    form: &FormState,
+) -> Markup {
+    let user_authority = access.authority();
+
+    let journal_id = access.journal.id;
+    let id = &journal_id.to_string();
+
+    let accounts_res = journal_accounts(state, journal_id, &user_authority).await;
+
+    let cards = transaction_cards(state, access, &accounts_res).await;
+
+    // payees used before are suggested as one is typed, and any other is still accepted
+    let payees = state
+        .journal_service
+        .list_payees(journal_id, &user_authority)
+        .await
+        .unwrap_or_default();
+
+    let today = user.timezone().date(state.clock.get_time());
+
+    let shortcuts = user.shortcuts();
+    let quick_keys = shortcuts.keys(ShortcutAction::QuickEntry);
+
+    let fiscal_year_start = access.journal.fiscal_year_start;
+
+    let current_period = |kind| Period::containing(kind, today, fiscal_year_start);
+
+    // without a range the totals cover the current fiscal year. a range that's been
+    // cleared is submitted as empty strings, which leaves the totals open ended
+    let (from, to) = if range.from.is_none() && range.to.is_none() {
+        match current_period(PeriodKind::FiscalYear) {
+            Some(year) => (
+                Some(year.first_day.format("%Y-%m-%d").to_string()),
+                year.last_day()
+                    .map(|day| day.format("%Y-%m-%d").to_string()),
+            ),
+            None => (None, None),
+        }
+    } else {
+        (
+            range.from.filter(|s| !s.is_empty()),
+            range.to.filter(|s| !s.is_empty()),
+        )
+    };
+
+    // the range is navigable when it covers exactly one month or fiscal year
+    let period = match (&from, &to) {
+        (Some(from), Some(to)) => match (parse_effective_date(from), parse_effective_date(to)) {
+            (Ok(from), Ok(to)) => Period::matching(from, to, fiscal_year_start),
+            _ => None,
+        },
+        _ => None,
+    };
+
+    let period_url = |period: Option<Period>| {
+        period.and_then(|period| {
+            Some(format!(
+                "/journal/{}/transaction?from={}&to={}",
+                id,
+                period.first_day.format("%Y-%m-%d"),
+                period.last_day()?.format("%Y-%m-%d")
+            ))
+        })
+    };
+
+    let parse = |date: &Option<String>| date.as_deref().map(parse_effective_date).transpose();
+
+    let templates_res: MonkestoResult<Vec<TemplateState>> = state
+        .journal_service
+        .list_templates(journal_id, &user_authority)
+        .await
+        .map_err(|e| e.into());
+
+    let can_append = check_access(
+        journal_id,
+        access.permissions,
+        Permissions::APPEND_TRANSACTION,
+    )
+    .is_ok();
+
+    let totals_res: MonkestoResult<TransactionTotals> = match (parse(&from), parse(&to)) {
+        (Ok(from), Ok(to)) => state
+            .journal_service
+            .transaction_totals_in_range(journal_id, &user_authority, from, to)
+            .await
+            .map_err(|e| e.into()),
+        (Err(e), _) | (_, Err(e)) => Err(e.into()),
+    };
+
+    let content = html! {
+        (cards)
+
+        div class="p-4 bg-gray-50 dark:bg-gray-800 border border-gray-200 dark:border-gray-700 rounded-xl space-y-3" {
+            form method="get" action=(format!("/journal/{}/transaction", id)) class="flex flex-wrap items-end gap-3" {
+                div {
+                    label for="from" class="block text-xs font-medium text-gray-700 dark:text-gray-300 mb-1" { (t("transaction.from")) }
+                    input class="rounded-md border border-gray-300 dark:border-gray-600 bg-white dark:bg-gray-800 px-2 py-1 text-sm text-gray-900 dark:text-white"
+                    type="date" id="from" name="from" value=[from.as_deref()];
+                }
+                div {
+                    label for="to" class="block text-xs font-medium text-gray-700 dark:text-gray-300 mb-1" { (t("transaction.to")) }
+                    input class="rounded-md border border-gray-300 dark:border-gray-600 bg-white dark:bg-gray-800 px-2 py-1 text-sm text-gray-900 dark:text-white"
+                    type="date" id="to" name="to" value=[to.as_deref()];
+                }
+                button class="px-3 py-1 text-sm bg-white dark:bg-gray-700 border border-gray-300 dark:border-gray-600 rounded-md text-gray-700 dark:text-gray-200 hover:bg-gray-100 dark:hover:bg-gray-600" type="submit" {
+                    (t("transaction.update_totals"))
+                }
+            }
+            div class="flex flex-wrap items-center gap-3 text-sm print:hidden" {
+                @if let Some(period) = period {
+                    @if let Some(url) = period_url(period.previous()) {
+                        a href=(url) class="font-medium text-indigo-600 hover:text-indigo-500 dark:text-indigo-400 dark:hover:text-indigo-300" { "← " (t("transaction.previous")) }
+                    }
+                    span class="font-medium text-gray-900 dark:text-white" { (period.label()) }
+                    @if let Some(url) = period_url(period.next()) {
+                        a href=(url) class="font-medium text-indigo-600 hover:text-indigo-500 dark:text-indigo-400 dark:hover:text-indigo-300" { (t("transaction.next")) " →" }
+                    }
+                }
+                @if let Some(url) = period_url(current_period(PeriodKind::Month)) {
+                    a href=(url) class="text-gray-600 hover:text-gray-900 dark:text-gray-400 dark:hover:text-white" { (t("transaction.this_period")) }
+                }
+                @if let Some(url) = period_url(current_period(PeriodKind::FiscalYear)) {
+                    a href=(url) class="text-gray-600 hover:text-gray-900 dark:text-gray-400 dark:hover:text-white" { (t("transaction.this_fiscal_year")) }
+                }
+                a href=(format!("/journal/{}/transaction?from=&to=", id)) class="text-gray-600 hover:text-gray-900 dark:text-gray-400 dark:hover:text-white" { (t("transaction.all_time")) }
+                span class="ml-auto" { (print_button()) }
+            }
+            @match &totals_res {
+                Ok(totals) => {
+                    div class="flex justify-between text-sm font-medium text-gray-900 dark:text-white" {
+                        span { (tf("transaction.total_debits", &[("amount", &format_amount(totals.debits))])) }
+                        span { (tf("transaction.total_credits", &[("amount", &format_amount(totals.credits))])) }
+                    }
+                },
+                Err(e) => {
+                    p class="text-sm text-red-600 dark:text-red-400" { (tf("transaction.totals_failed", &[("error", e)])) }
+                }
+            }
+        }
+
+        hr class="mt-8 mb-6 border-gray-300 dark:border-gray-600 print:hidden";
+
+        div class="mt-10 print:hidden" {
+            div class="bg-white dark:bg-gray-800 border border-gray-200 dark:border-gray-700 rounded-xl p-6" {
+                div class="flex justify-between items-center mb-6" {
+                    h3 class="text-lg font-semibold text-gray-900 dark:text-white" {
+                        (t("transaction.create_heading"))
+                    }
+                    div class="flex gap-4" {
+                        a
+                        href=(format!("/journal/{}/quick", id))
+                        title=[quick_keys.map(|keys| tf("transaction.quick_hint", &[("keys", &keys)]))]
+                        class="text-sm font-medium text-indigo-600 hover:text-indigo-500 dark:text-indigo-400 dark:hover:text-indigo-300" {
+                            (t("transaction.quick_entry"))
+                        }
+                        a
+                        href=(format!("/journal/{}/transaction/split", id))
+                        class="text-sm font-medium text-indigo-600 hover:text-indigo-500 dark:text-indigo-400 dark:hover:text-indigo-300" {
+                            (t("transaction.split_entry"))
+                        }
+                        a
+                        href=(format!("/journal/{}/transaction/bulk", id))
+                        class="text-sm font-medium text-indigo-600 hover:text-indigo-500 dark:text-indigo-400 dark:hover:text-indigo-300" {
+                            (t("transaction.bulk_entry"))
+                        }
+                    }
+                }
+
+                @match &templates_res {
+                    Ok(templates) if !templates.is_empty() => {
+                        div class="mb-6" {
+                            h4 class="text-sm font-medium text-gray-700 dark:text-gray-300 mb-2" {
+                                (t("transaction.templates"))
+                            }
+                            div class="flex flex-wrap gap-2" {
+                                @for template in templates {
+                                    div class="flex items-center rounded-full border border-gray-300 dark:border-gray-600 bg-gray-50 dark:bg-gray-700" {
+                                        a
+                                        href=(format!("/journal/{}/transaction?template={}", id, template.id))
+                                        class="pl-3 pr-2 py-1 text-sm font-medium text-gray-700 dark:text-gray-200 hover:text-indigo-600 dark:hover:text-indigo-400" {
+                                            (template.name)
+                                        }
+                                        @if can_append {
+                                            form method="post" action=(format!("/journal/{}/template/{}/delete", id, template.id)) {
+                                                button
+                                                type="submit"
+                                                title=(t("transaction.delete_template"))
+                                                aria-label=(t("transaction.delete_template"))
+                                                class="pr-3 py-1 text-sm text-gray-400 hover:text-red-600 dark:hover:text-red-400" {
+                                                    "×"
                                                 }
-                                            },
-                                            Err(e) => {"failed to fetch users: " (e)}
+                                            }
                                         }
-                                    },
-                                    Actor::System => {"system"},
-                                    Actor::Anonymous => {"anonymous"}
+                                    }
                                 }
                             }
                         }
+                    },
+                    Ok(_) => {},
+                    Err(e) => {
+                        p class="mb-6 text-sm text-red-600 dark:text-red-400" {
+                            (tf("transaction.templates_failed", &[("error", e)]))
+                        }
                     }
                 }
-            }
-            hr class="mt-8 mb-6 border-gray-300 dark:border-gray-600";
 
-            div class="mt-10" {
-                div class="bg-white dark:bg-gray-800 border border-gray-200 dark:border-gray-700 rounded-xl p-6" {
-                    h3 class="text-lg font-semibold text-gray-900 dark:text-white mb-6" {
-                        "Create New Transaction"
+                // members whose transactions need approval are sent on to the queue instead
+                form id="transaction-form" method="post" action=(format!("/journal/{}/transaction", id)) class="space-y-6" data-partial[can_append] {
+                    div {
+                        label for="effective_date" class="block text-sm font-medium text-gray-700 dark:text-gray-300 mb-2" {
+                            (t("transaction.date"))
+                        }
+                        input class="rounded-md border border-gray-300 dark:border-gray-600 bg-white dark:bg-gray-800 px-3 py-2 text-gray-900 dark:text-white focus:border-indigo-500 focus:ring-indigo-500 dark:focus:border-indigo-400"
+                        type="date"
+                        id="effective_date"
+                        name="effective_date"
+                        value=(form.value("effective_date").map_or_else(|| today.format("%Y-%m-%d").to_string(), str::to_string))
+                        required
+                        aria-invalid=[form.invalid("effective_date")]
+                        aria-describedby=[form.described_by("effective_date")];
+                        (form.message("effective_date"))
                     }
 
-                    form method="post" action=(format!("/journal/{}/transaction", id)) class="space-y-6" {
-                        @for i in 0..4 {
-                            div class="p-4 bg-gray-50 dark:bg-gray-700 rounded-lg" {
+                    div {
+                        label for="payee" class="block text-sm font-medium text-gray-700 dark:text-gray-300 mb-2" {
+                            (t("transaction.payee"))
+                        }
+                        input class="w-full md:w-1/2 rounded-md border border-gray-300 dark:border-gray-600 bg-white dark:bg-gray-800 px-3 py-2 text-gray-900 dark:text-white placeholder:text-gray-400 dark:placeholder:text-gray-500 focus:border-indigo-500 focus:ring-indigo-500 dark:focus:border-indigo-400"
+                        type="text"
+                        id="payee"
+                        name="payee"
+                        list="payees"
+                        maxlength="64"
+                        autocomplete="off"
+                        placeholder=(t("transaction.payee_placeholder"))
+                        value=[form.value("payee")]
+                        aria-invalid=[form.invalid("payee")]
+                        aria-describedby=[form.described_by("payee")];
+                        datalist id="payees" {
+                            @for payee in &payees {
+                                option value=(payee) {}
+                            }
+                        }
+                        (form.message("payee"))
+                    }
+
+                    // a rejected submission keeps every row it had, even ones added by the script
+                    @let rows = (0..)
+                        .take_while(|i| form.value(&format!("account-{i}")).is_some())
+                        .count()
+                        .max(4);
+
+                    div id="entry-rows" class="space-y-6" {
+                        @for i in 0..rows {
+                            @let account_field = format!("account-{i}");
+                            @let amount_field = format!("amount-{i}");
+                            @let entry_type_field = format!("entry_type-{i}");
+                            @let memo_field = format!("memo-{i}");
+                            div class="p-4 bg-gray-50 dark:bg-gray-700 rounded-lg" data-entry-row {
                                 div class="space-y-3 md:space-y-0 md:grid md:grid-cols-12 md:gap-3" {
                                     div class="md:col-span-6" {
-                                        label class="block text-sm font-medium text-gray-700 dark:text-gray-300 mb-2" {
-                                            (if i < 2 {"Account"} else {"Account (Optional)"})
+                                        label for=(account_field) class="block text-sm font-medium text-gray-700 dark:text-gray-300 mb-2" {
+                                            (if i < 2 { t("transaction.account") } else { t("transaction.account_optional") })
                                         }
-                                        select class="w-full rounded-md border border-gray-300 dark:border-gray-600 bg-white dark:bg-gray-800 px-3 py-2 text-gray-900 dark:text-white focus:border-indigo-500 focus:ring-indigo-500 dark:focus:border-indigo-400"
-                                        name="account" {
-                                            option value="" { "Select account..." }
-                                            @if let Ok(accounts) = &accounts_res {
-                                                @for (acc_id, acc_state) in accounts {
-                                                    option value=(acc_id) { (acc_state.name)}
+                                        // the script swaps the select for a search box, which picks the select's option
+                                        div class="relative" data-account-picker data-search=(format!("/journal/{}/account/search", id)) {
+                                            input class="hidden w-full rounded-md border border-gray-300 dark:border-gray-600 bg-white dark:bg-gray-800 px-3 py-2 text-gray-900 dark:text-white focus:border-indigo-500 focus:ring-indigo-500 dark:focus:border-indigo-400"
+                                            type="text"
+                                            id=(format!("account-query-{i}"))
+                                            role="combobox"
+                                            aria-autocomplete="list"
+                                            aria-expanded="false"
+                                            autocomplete="off"
+                                            placeholder=(t("transaction.search_account"))
+                                            value=[accounts_res.as_ref().ok().and_then(|accounts| {
+                                                accounts
+                                                    .iter()
+                                                    .find(|a| form.value(&account_field) == Some(a.id.to_string().as_str()))
+                                                    .map(|a| a.name.to_string())
+                                            })]
+                                            aria-invalid=[form.invalid(&account_field)]
+                                            aria-describedby=[form.described_by(&account_field)]
+                                            data-account-query;
+                                            ul
+                                            id=(format!("account-results-{i}"))
+                                            role="listbox"
+                                            class="hidden absolute z-10 mt-1 max-h-60 w-full overflow-auto rounded-md border border-gray-200 dark:border-gray-600 bg-white dark:bg-gray-800 py-1 shadow-lg"
+                                            data-account-results {}
+                                            select class="w-full rounded-md border border-gray-300 dark:border-gray-600 bg-white dark:bg-gray-800 px-3 py-2 text-gray-900 dark:text-white focus:border-indigo-500 focus:ring-indigo-500 dark:focus:border-indigo-400"
+                                            id=(account_field)
+                                            name="account"
+                                            aria-invalid=[form.invalid(&account_field)]
+                                            aria-describedby=[form.described_by(&account_field)] {
+                                                option value="" { (t("transaction.select_account")) }
+                                                @if let Ok(accounts) = &accounts_res {
+                                                    (account_options(accounts, form.value(&account_field)))
+                                                } @else {
+                                                    option value=("invalid account") { (t("transaction.accounts_unavailable")) }
                                                 }
-                                            } @else {
-                                                option value=("invalid account") { "failed to fetch accounts" }
                                             }
                                         }
+                                        (form.message(&account_field))
                                     }
                                     div class="grid grid-cols-4 gap-3 md:col-span-6 md:grid-cols-6" {
                                         div class="col-span-3 md:col-span-4" {
-                                            label class="block text-sm font-medium text-gray-700 dark:text-gray-300 mb-2" {
-                                                "Amount"
+                                            label for=(amount_field) class="block text-sm font-medium text-gray-700 dark:text-gray-300 mb-2" {
+                                                (t("transaction.amount"))
                                             }
                                             input class="w-full rounded-md border border-gray-300 dark:border-gray-600 bg-white dark:bg-gray-800 px-3 py-2 text-gray-900 dark:text-white placeholder:text-gray-400 dark:placeholder:text-gray-500 focus:border-indigo-500 focus:ring-indigo-500 dark:focus:border-indigo-400 text-right [&::-webkit-outer-spin-button]:appearance-none [&::-webkit-inner-spin-button]:appearance-none [-moz-appearance:textfield]"
                                             type="number"
                                             step="0.01" min="0"
                                             placeholder="0.00"
                                             required[i < 2]
-                                            name="amount";
+                                            id=(amount_field)
+                                            name="amount"
+                                            value=[form.value(&amount_field)]
+                                            aria-invalid=[form.invalid(&amount_field)]
+                                            aria-describedby=[form.described_by(&amount_field)];
+                                            (form.message(&amount_field))
                                         }
                                         div class="col-span-1 md:col-span-2" {
-                                            label class="block text-sm font-medium text-gray-700 dark:text-gray-300 mb-2" {
-                                                "Type"
+                                            label for=(entry_type_field) class="block text-sm font-medium text-gray-700 dark:text-gray-300 mb-2" {
+                                                (t("transaction.type"))
                                             }
                                             select class="w-full rounded-md border border-gray-300 dark:border-gray-600 bg-white dark:bg-gray-800 px-3 py-2 text-gray-900 dark:text-white focus:border-indigo-500 focus:ring-indigo-500 dark:focus:border-indigo-400"
-                                            name="entry_type" {
-                                                option value=(EntryType::Debit) { "Dr" }
-                                                option value=(EntryType::Credit) { "Cr" }
+                                            id=(entry_type_field)
+                                            name="entry_type"
+                                            aria-invalid=[form.invalid(&entry_type_field)]
+                                            aria-describedby=[form.described_by(&entry_type_field)] {
+                                                @for entry_type in [EntryType::Debit, EntryType::Credit] {
+                                                    option value=(entry_type) selected[form.value(&entry_type_field) == Some(entry_type.to_string().as_str())] {
+                                                        (match entry_type {
+                                                            EntryType::Debit => "Dr",
+                                                            EntryType::Credit => "Cr",
+                                                        })
+                                                    }
+                                                }
                                             }
+                                            (form.message(&entry_type_field))
                                         }
                                     }
                                 }
+                                div class="mt-3" {
+                                    label for=(memo_field) class="block text-sm font-medium text-gray-700 dark:text-gray-300 mb-2" {
+                                        (t("transaction.memo"))
+                                    }
+                                    input class="w-full rounded-md border border-gray-300 dark:border-gray-600 bg-white dark:bg-gray-800 px-3 py-2 text-gray-900 dark:text-white placeholder:text-gray-400 dark:placeholder:text-gray-500 focus:border-indigo-500 focus:ring-indigo-500 dark:focus:border-indigo-400"
+                                    type="text"
+                                    id=(memo_field)
+                                    name="memo"
+                                    maxlength=(MAX_MEMO_LENGTH)
+                                    placeholder=(t("transaction.memo_placeholder"))
+                                    value=[form.value(&memo_field)]
+                                    aria-invalid=[form.invalid(&memo_field)]
+                                    aria-describedby=[form.described_by(&memo_field)];
+                                    (form.message(&memo_field))
+                                }
+                                div class="hidden justify-end mt-2" data-entry-controls {
+                                    button type="button" class="text-xs text-gray-500 hover:text-red-600 dark:text-gray-400 dark:hover:text-red-400" data-remove-entry {
+                                        (t("transaction.remove_entry"))
+                                    }
+                                }
                             }
                         }
+                    }
 
-                        div class="flex justify-between items-center pt-4 border-t border-gray-200 dark:border-gray-600" {
-                            div class="text-sm text-gray-500 dark:text-gray-400" {
-                                "Debits must equal credits"
-                            }
-                            button class="px-6 py-2 bg-indigo-600 text-white font-medium rounded-md hover:bg-indigo-700 focus:outline-none focus:ring-2 focus:ring-indigo-500 focus:ring-offset-2 dark:bg-indigo-500 dark:hover:bg-indigo-400 dark:focus:ring-indigo-400 dark:ring-offset-gray-800" type="submit" {
-                                "Create Transaction"
-                            }
+                    (form.summary())
+
+                    button type="button" id="add-entry" class="hidden text-sm font-medium text-indigo-600 hover:text-indigo-500 dark:text-indigo-400 dark:hover:text-indigo-300" {
+                        "+ " (t("transaction.add_entry"))
+                    }
+
+                    div class="flex justify-between items-center pt-4 border-t border-gray-200 dark:border-gray-600" {
+                        div
+                        id="entry-balance"
+                        data-debits=(t("transaction.debits"))
+                        data-credits=(t("transaction.credits"))
+                        data-balanced=(t("transaction.balanced"))
+                        data-out-of-balance=(t("transaction.out_of_balance"))
+                        class="text-sm text-gray-500 dark:text-gray-400" {
+                            (t("transaction.must_balance"))
+                        }
+                        button class="px-6 py-2 bg-indigo-600 text-white font-medium rounded-md hover:bg-indigo-700 focus:outline-none focus:ring-2 focus:ring-indigo-500 focus:ring-offset-2 dark:bg-indigo-500 dark:hover:bg-indigo-400 dark:focus:ring-indigo-400 dark:ring-offset-gray-800" type="submit" {
+                            (t("transaction.create"))
                         }
                     }
                 }
-                @if let Some(e) = err.err {
-                    p {
-                        (format!("An error occurred: {:?}", MonkestoError::decode(&e)))
-                    }
+            }
+            @if let Some(e) = err {
+                p {
+                    (tf("error.occurred", &[("error", &format!("{:?}", MonkestoError::decode(&e)))]))
                 }
             }
         }
+
+        script { (PreEscaped(TRANSACTION_FORM_SCRIPT)) }
+        script { (PreEscaped(ACCOUNT_PICKER_SCRIPT)) }
+    };
+
+    // the totals' range heads the printout, since that's what they add up
+    let range_label = match (period, &from, &to) {
+        (Some(period), _, _) => period.label(),
+        (None, None, None) => t("transaction.all_time").to_string(),
+        (None, from, to) => format!(
+            "{} – {}",
+            from.as_deref().unwrap_or("…"),
+            to.as_deref().unwrap_or("…")
+        ),
+    };
+    let heading = PrintHeading {
+        journal: access.journal.name.as_ref(),
+        period: tf("print.totals_for", &[("period", &range_label)]),
+        timezone: user.timezone(),
+        generated: state.clock.get_time(),
     };
 
     let wrapped_content = html! {
@@ -217,24 +984,340 @@ pub async fn transaction_list_page(
         }
     };
 
-    let journal_name = match &journal_id_res {
-        Ok(id) => {
+    layout::journal_layout(&access.journal, printable(&heading, wrapped_content))
+}
+
+/// replies nested deeper than this are shown at this depth, so long threads stay readable
+const MAX_COMMENT_INDENT: usize = 4;
+
+fn comment_form(
+    journal_id: &str,
+    transaction_id: TransactionId,
+    parent_id: Option<CommentId>,
+) -> Markup {
+    html! {
+        form method="post" action=(format!("/journal/{}/transaction/{}/comment", journal_id, transaction_id)) class="flex flex-col gap-2 mt-2" {
+            @if let Some(parent_id) = parent_id {
+                input type="hidden" name="parent_id" value=(parent_id);
+            }
+            textarea class="rounded-md border border-gray-300 dark:border-gray-600 bg-white dark:bg-gray-800 px-3 py-2 text-sm text-gray-900 dark:text-white"
+            name="body"
+            rows="2"
+            maxlength=(comment::MAX_COMMENT_LENGTH)
+            placeholder=(t("transaction.comment_placeholder"))
+            required {}
+            button
+            type="submit"
+            class="self-start px-4 py-2 text-sm font-medium bg-white dark:bg-gray-700 border border-gray-300 dark:border-gray-600 rounded-md text-gray-700 dark:text-gray-200 hover:bg-gray-100 dark:hover:bg-gray-600" {
+                (t("transaction.add_comment"))
+            }
+        }
+    }
+}
+
+pub async fn transaction_detail_page(
+    State(state): State<StateType>,
+    session: AuthSession<BackendType>,
+    access: JournalAccess,
+    Path((id, transaction_id)): Path<(String, String)>,
+    Query(err): Query<UrlError>,
+) -> Result<Markup, Redirect> {
+    let browser_session = session.session.clone();
+    let user = get_user(session)?;
+    let user_authority = access.authority();
+    let journal_id = access.journal.id;
+
+    let transaction_res: MonkestoResult<(TransactionState, Authority, Timestamp)> =
+        match TransactionId::from_str(&transaction_id) {
+            Ok(transaction_id) => state
+                .journal_service
+                .get_transaction(journal_id, transaction_id, &user_authority)
+                .await
+                .map_err(|e| e.into()),
+            Err(e) => Err(e.into()),
+        };
+
+    let accounts: HashMap<AccountId, AccountState> = state
+        .journal_service
+        .list_journal_accounts(journal_id, &user_authority)
+        .await
+        .map(|accounts| {
+            accounts
+                .into_iter()
+                .map(|(state, _, _)| (state.id, state))
+                .collect()
+        })
+        .unwrap_or_default();
+
+    let reversals: Vec<TransactionId> = match &transaction_res {
+        Ok((transaction, _, _)) => state
+            .journal_service
+            .list_reversals(journal_id, transaction.id, &user_authority)
+            .await
+            .unwrap_or_default(),
+        Err(_) => Vec::new(),
+    };
+
+    let comments: MonkestoResult<Vec<(usize, CommentState)>> = match &transaction_res {
+        Ok((transaction, _, _)) => state
+            .journal_service
+            .list_comments(journal_id, transaction.id, &user_authority)
+            .await
+            .map(comment::thread)
+            .map_err(|e| e.into()),
+        Err(_) => Ok(Vec::new()),
+    };
+
+    let mut commenters: HashMap<UserId, String> = HashMap::new();
+    if let Ok(comments) = &comments {
+        for (_, comment) in comments {
+            if let Actor::User(user_id) = comment.authority.actor()
+                && !commenters.contains_key(user_id)
+            {
+                let name = match state.authn_service.get_display_name(*user_id).await {
+                    Ok(name) => name,
+                    Err(e) => tf("transaction.user_failed", &[("error", &e)]),
+                };
+                commenters.insert(*user_id, name);
+            }
+        }
+    }
+
+    let last_change: Option<LastChange> = match &transaction_res {
+        Ok((transaction, _, _)) => {
             match state
                 .journal_service
-                .get_journal(*id, &user_authority)
+                .last_transaction_change(journal_id, transaction.id, &user_authority)
                 .await
             {
-                Ok((journal, _, _)) => journal.name.to_string(),
-                Err(e) => format!("failed to fetch the journal: {e}"),
+                Ok(Some(change)) => {
+                    let names = provenance::actor_names(&state, [&change]).await;
+                    Some(LastChange::new(&change, &names))
+                }
+                _ => None,
             }
         }
-        Err(e) => format!("invalid journal id: {e}"),
+        Err(_) => None,
     };
 
-    Ok(layout::layout(
-        Some(&journal_name),
-        true,
-        Some(&id),
-        wrapped_content,
-    ))
+    let creator_name = match &transaction_res {
+        Ok((_, authority, _)) => match authority.actor() {
+            Actor::User(creator_id) => {
+                match state.authn_service.get_display_name(*creator_id).await {
+                    Ok(name) => name,
+                    Err(e) => tf("transaction.user_failed", &[("error", &e)]),
+                }
+            }
+            Actor::System => t("actor.system").to_string(),
+            Actor::Anonymous => t("actor.anonymous").to_string(),
+            Actor::Deleted => t("actor.deleted").to_string(),
+        },
+        Err(_) => String::new(),
+    };
+
+    let content = html! {
+        div class="flex flex-col gap-6 mx-auto w-full max-w-4xl" {
+            (undo::banner(&browser_session, journal_id, &format!("/journal/{}/transaction/{}", id, transaction_id), state.clock.get_time()).await)
+
+            @match &transaction_res {
+                Ok((transaction, _, created_at)) => {
+                    div class="p-4 bg-white dark:bg-gray-800 border border-gray-200 dark:border-gray-700 rounded-xl space-y-3" {
+                        div class="text-sm font-medium text-gray-500 dark:text-gray-400" {
+                            (format_voucher_number(transaction.number)) " · " (transaction.effective_date.format("%Y-%m-%d"))
+                            @if let Some(payee) = &transaction.payee {
+                                " · " (payee)
+                            }
+                        }
+
+                        @for entry in transaction.entries.iter() {
+                            div class="flex justify-between items-center" {
+                                div {
+                                    span class="text-base font-medium text-gray-900 dark:text-white" {
+                                        (accounts.get(&entry.account_id).map(|acct| acct.name.as_ref()).unwrap_or(t("transaction.unknown_account")))
+                                    }
+                                    @if let Some(memo) = &entry.memo {
+                                        p class="text-sm text-gray-500 dark:text-gray-400" { (memo) }
+                                    }
+                                }
+                                span class="text-base text-gray-700 dark:text-gray-300" {
+                                    (format_amount(entry.amount)) " " (entry.entry_type)
+                                }
+                            }
+                        }
+
+                        @if let Some(change) = &last_change {
+                            div { (provenance::last_change_link(transaction.journal_id, change, user.timezone(), state.clock.get_time())) }
+                        } @else {
+                            div class="text-xs text-gray-400 dark:text-gray-500" {
+                                (tf("transaction.recorded_by", &[
+                                    ("user", &creator_name),
+                                    ("time", &user.timezone().format(*created_at)),
+                                ]))
+                            }
+                        }
+
+                        @if let Some(original) = transaction.reverses {
+                            p class="text-sm text-gray-600 dark:text-gray-400" {
+                                (t("transaction.reverses")) " "
+                                a
+                                href=(format!("/journal/{}/transaction/{}", id, original))
+                                class="font-medium text-indigo-600 hover:text-indigo-500 dark:text-indigo-400 dark:hover:text-indigo-300" {
+                                    (original)
+                                }
+                            }
+                        }
+
+                        @if let Some(link) = transaction.transfer {
+                            p class="text-sm text-gray-600 dark:text-gray-400" {
+                                (t("transaction.part_of_transfer")) " "
+                                a
+                                href=(format!("/journal/{}/transaction/{}", link.journal_id, link.transaction_id))
+                                class="font-medium text-indigo-600 hover:text-indigo-500 dark:text-indigo-400 dark:hover:text-indigo-300" {
+                                    (t("transaction.other_side"))
+                                }
+                            }
+                        }
+
+                        @for reversal in reversals.iter() {
+                            p class="text-sm text-gray-600 dark:text-gray-400" {
+                                (t("transaction.reversed_by")) " "
+                                a
+                                href=(format!("/journal/{}/transaction/{}", id, reversal))
+                                class="font-medium text-indigo-600 hover:text-indigo-500 dark:text-indigo-400 dark:hover:text-indigo-300" {
+                                    (reversal)
+                                }
+                            }
+                        }
+                    }
+
+                    form method="post" action=(format!("/journal/{}/transaction/{}/reverse", id, transaction.id)) {
+                        button
+                        type="submit"
+                        onclick=[(!reversals.is_empty()).then(|| format!("return confirm('{}')", t("transaction.reverse_again_confirm")))]
+                        class="px-4 py-2 text-sm font-medium bg-white dark:bg-gray-700 border border-gray-300 dark:border-gray-600 rounded-md text-gray-700 dark:text-gray-200 hover:bg-gray-100 dark:hover:bg-gray-600" {
+                            (t("transaction.reverse"))
+                        }
+                    }
+
+                    form method="post" action=(format!("/journal/{}/template", id)) class="flex flex-wrap items-end gap-3" {
+                        input type="hidden" name="transaction_id" value=(transaction.id);
+                        div {
+                            label for="template_name" class="block text-xs font-medium text-gray-700 dark:text-gray-300 mb-1" {
+                                (t("transaction.template_name"))
+                            }
+                            input class="rounded-md border border-gray-300 dark:border-gray-600 bg-white dark:bg-gray-800 px-3 py-2 text-sm text-gray-900 dark:text-white"
+                            type="text"
+                            id="template_name"
+                            name="name"
+                            placeholder=(t("transaction.template_placeholder"))
+                            required;
+                        }
+                        button
+                        type="submit"
+                        class="px-4 py-2 text-sm font-medium bg-white dark:bg-gray-700 border border-gray-300 dark:border-gray-600 rounded-md text-gray-700 dark:text-gray-200 hover:bg-gray-100 dark:hover:bg-gray-600" {
+                            (t("transaction.save_template"))
+                        }
+                    }
+
+                    div class="flex flex-col gap-3" {
+                        h3 class="text-lg font-semibold text-gray-900 dark:text-white" { (t("transaction.comments")) }
+
+                        @match &comments {
+                            Ok(comments) => {
+                                @for (depth, comment) in comments {
+                                    div
+                                    id=(format!("comment-{}", comment.id))
+                                    class="p-3 bg-white dark:bg-gray-800 border border-gray-200 dark:border-gray-700 rounded-lg space-y-2"
+                                    style=(format!("margin-left: {}rem", (*depth).min(MAX_COMMENT_INDENT) * 2)) {
+                                        div class="text-xs text-gray-400 dark:text-gray-500" {
+                                            (match comment.authority.actor() {
+                                                Actor::User(user_id) => commenters.get(user_id).cloned().unwrap_or_default(),
+                                                Actor::System => t("actor.system").to_string(),
+                                                Actor::Anonymous => t("actor.anonymous").to_string(),
+                                                Actor::Deleted => t("actor.deleted").to_string(),
+                                            })
+                                            " · "
+                                            (user.timezone().format(comment.timestamp))
+                                        }
+                                        p class="text-sm text-gray-900 dark:text-white whitespace-pre-wrap" { (comment.body) }
+                                        details {
+                                            summary class="text-xs font-medium text-indigo-600 hover:text-indigo-500 dark:text-indigo-400 dark:hover:text-indigo-300 cursor-pointer" {
+                                                (t("transaction.reply"))
+                                            }
+                                            (comment_form(&id, transaction.id, Some(comment.id)))
+                                        }
+                                    }
+                                }
+                            },
+                            Err(e) => {
+                                p class="text-gray-500 dark:text-gray-400" {
+                                    (tf("transaction.comments_failed", &[("error", e)]))
+                                }
+                            }
+                        }
+
+                        (comment_form(&id, transaction.id, None))
+                    }
+                },
+                Err(e) => {
+                    p class="text-gray-500 dark:text-gray-400" {
+                        (tf("transaction.fetch_failed", &[("error", e)]))
+                    }
+                }
+            }
+
+            @if let Some(e) = err.err {
+                p {
+                    (tf("error.occurred", &[("error", &format!("{:?}", MonkestoError::decode(&e)))]))
+                }
+            }
+        }
+    };
+
+    Ok(layout::journal_layout(&access.journal, content))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::test_app::{TestApp, assert_accepted};
+
+    #[tokio::test]
+    async fn partial_requests_get_just_the_transaction_list() {
+        let Some(app) = TestApp::spawn().await else {
+            return;
+        };
+
+        let user = app.signup("swapper").await;
+        let journal_id = user.create_journal(&app, "Swapped").await;
+        assert_accepted(&user.create_account(journal_id, "Cash").await);
+        assert_accepted(&user.create_account(journal_id, "Rent").await);
+        let cash = app.account_id(journal_id, "Cash").await;
+        let rent = app.account_id(journal_id, "Rent").await;
+
+        let (cash, rent) = (cash.to_string(), rent.to_string());
+        let response = user
+            .client
+            .post(&format!("/journal/{journal_id}/transaction"))
+            .add_header("x-partial", "true")
+            .form(&[
+                ("account", rent.as_str()),
+                ("amount", "800.00"),
+                ("entry_type", "Dr"),
+                ("account", cash.as_str()),
+                ("amount", "800.00"),
+                ("entry_type", "Cr"),
+                ("effective_date", "2026-10-17"),
+            ])
+            .await;
+        response.assert_status_ok();
+        assert!(response.text().starts_with(r#"<div id="transaction-list""#));
+        assert!(response.text().contains("$800.00"));
+
+        let fragment = user
+            .client
+            .get(&format!("/journal/{journal_id}/transaction/_list"))
+            .await
+            .text();
+        assert_eq!(fragment, response.text());
+    }
 }
@@ -0,0 +1,72 @@
+use crate::StateType;
+use crate::journal::JournalId;
+use crate::journal::access::{AppendTransaction, JournalAccess};
+use crate::journal::account::AccountId;
+use crate::journal::transaction::{parse_amount, parse_effective_date};
+use crate::journal::transfer::TransferSide;
+use crate::monkesto_error::{ErrorRedirect, OrRedirect};
+use crate::time_provider::TimeProvider;
+use axum::extract::Path;
+use axum::extract::State;
+use axum::response::Redirect;
+use axum_extra::extract::Form;
+use serde::Deserialize;
+use std::str::FromStr;
+
+#[derive(Deserialize)]
+pub struct TransferForm {
+    account_id: String,
+    offset_account_id: String,
+    to_account_id: String,
+    to_offset_account_id: String,
+    amount: String,
+    effective_date: String,
+}
+
+pub async fn transfer(
+    State(state): State<StateType>,
+    access: JournalAccess<AppendTransaction>,
+    Path((id, to_id)): Path<(String, String)>,
+    Form(form): Form<TransferForm>,
+) -> Result<Redirect, ErrorRedirect> {
+    let callback_url = &format!("/journal/{}/transfer/{}", id, to_id);
+
+    let from = TransferSide {
+        journal_id: access.journal.id,
+        transaction_id: state.ids.generate(),
+        account_id: AccountId::from_str(&form.account_id).or_redirect(callback_url)?,
+        offset_account_id: AccountId::from_str(&form.offset_account_id)
+            .or_redirect(callback_url)?,
+    };
+
+    let to = TransferSide {
+        journal_id: JournalId::from_str(&to_id).or_redirect(callback_url)?,
+        transaction_id: state.ids.generate(),
+        account_id: AccountId::from_str(&form.to_account_id).or_redirect(callback_url)?,
+        offset_account_id: AccountId::from_str(&form.to_offset_account_id)
+            .or_redirect(callback_url)?,
+    };
+
+    let amount = parse_amount(form.amount.trim()).or_redirect(callback_url)?;
+    let effective_date = parse_effective_date(&form.effective_date).or_redirect(callback_url)?;
+
+    let event_id = state
+        .journal_service
+        .transfer(
+            from,
+            to,
+            amount,
+            effective_date,
+            access.authority(),
+            state.clock.get_time(),
+        )
+        .await
+        .or_redirect(callback_url)?;
+
+    state.journal_service.wait_for(event_id).await;
+
+    Ok(Redirect::to(&format!(
+        "/journal/{}/transaction/{}",
+        id, from.transaction_id
+    )))
+}
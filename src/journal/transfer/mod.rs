@@ -0,0 +1,449 @@
+pub mod commands;
+pub mod views;
+
+use axum::Router;
+use axum::routing::get;
+use axum_login::login_required;
+
+pub fn router() -> Router<crate::StateType> {
+    Router::new()
+        .route("/journal/{id}/transfer", get(views::transfer_list_page))
+        .route(
+            "/journal/{id}/transfer/{to_id}",
+            get(views::transfer_page).post(commands::transfer),
+        )
+        .route_layer(login_required!(crate::BackendType, login_url = "/signin"))
+}
+
+use crate::authn::UserId;
+use crate::authority::Authority;
+use crate::journal::account::AccountId;
+use crate::journal::domain::{JournalDomainEvent, TransferJournalEvent};
+use crate::journal::member::JournalMember;
+use crate::journal::transaction::{
+    BalanceUpdate, EntryType, Transaction, TransactionId, recorded_number,
+};
+use crate::journal::{JournalError, JournalId};
+use crate::journal::{Permissions, validate_permissions};
+use crate::status::Status;
+use crate::time_provider::Timestamp;
+use chrono::NaiveDate;
+use disintegrate::{Decision, StateMutate, StateQuery};
+use serde::Deserialize;
+use serde::Serialize;
+use std::collections::{HashMap, HashSet};
+
+/// the transaction on the other side of a transfer between journals
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TransferLink {
+    pub journal_id: JournalId,
+    pub transaction_id: TransactionId,
+}
+
+/// what a transfer checks about one of its journals. a decision can only read five states,
+/// so each journal's status, members, accounts and voucher numbers are read as one
+#[derive(StateQuery, Clone, Default, Serialize, Deserialize)]
+#[state_query(TransferJournalEvent)]
+pub struct TransferJournal {
+    #[id]
+    journal_id: JournalId,
+    owner: UserId,
+    status: Status,
+    archived: bool,
+    members: HashMap<UserId, Permissions>,
+    accounts: HashSet<AccountId>,
+    last_number: u64,
+}
+
+impl TransferJournal {
+    pub fn new(journal_id: JournalId) -> Self {
+        Self {
+            journal_id,
+            ..Default::default()
+        }
+    }
+
+    fn permits(&self, authority: &Authority, permissions: Permissions) -> bool {
+        let user_id = authority.user_id().unwrap_or_default();
+        let mut member = JournalMember::new(self.journal_id, user_id);
+
+        if let Some(granted) = self.members.get(&user_id) {
+            member.permissions = *granted;
+            member.status = Status::Valid;
+        }
+
+        validate_permissions(&member, authority, self.owner, permissions)
+    }
+}
+
+impl StateMutate for TransferJournal {
+    fn mutate(&mut self, event: Self::Event) {
+        match event {
+            TransferJournalEvent::JournalCreated { owner, .. } => {
+                self.owner = owner;
+                self.status = Status::Valid;
+            }
+            TransferJournalEvent::JournalDeleted { .. } => self.status = Status::Deleted,
+            TransferJournalEvent::JournalArchived { .. } => self.archived = true,
+            TransferJournalEvent::JournalUnarchived { .. } => self.archived = false,
+            TransferJournalEvent::MemberAdded {
+                user_id,
+                permissions,
+                ..
+            }
+            | TransferJournalEvent::MemberPermissionsUpdated {
+                user_id,
+                permissions,
+                ..
+            } => _ = self.members.insert(user_id, permissions),
+            TransferJournalEvent::MemberRemoved { user_id, .. } => {
+                _ = self.members.remove(&user_id)
+            }
+            TransferJournalEvent::AccountCreated { account_id, .. } => {
+                _ = self.accounts.insert(account_id)
+            }
+            TransferJournalEvent::AccountDeleted { account_id, .. } => {
+                _ = self.accounts.remove(&account_id)
+            }
+            TransferJournalEvent::TransactionCreated { number, .. } => {
+                self.last_number = recorded_number(self.last_number, number)
+            }
+        }
+    }
+}
+
+/// one journal's side of a transfer. the money leaves `account_id` in the source journal and
+/// arrives in `account_id` in the destination, and `offset_account_id` balances each side
+#[derive(Debug, Clone, Copy)]
+pub struct TransferSide {
+    pub journal_id: JournalId,
+    pub transaction_id: TransactionId,
+    pub account_id: AccountId,
+    pub offset_account_id: AccountId,
+}
+
+/// records a transfer as a transaction in each journal, in a single append, each linked to
+/// the other
+pub struct CreateTransfer {
+    from: TransferSide,
+    to: TransferSide,
+    amount: u64,
+    effective_date: NaiveDate,
+    authority: Authority,
+    timestamp: Timestamp,
+}
+
+impl CreateTransfer {
+    pub fn new(
+        from: TransferSide,
+        to: TransferSide,
+        amount: u64,
+        effective_date: NaiveDate,
+        authority: Authority,
+        timestamp: Timestamp,
+    ) -> Self {
+        Self {
+            from,
+            to,
+            amount,
+            effective_date,
+            authority,
+            timestamp,
+        }
+    }
+
+    fn transaction(
+        &self,
+        side: &TransferSide,
+        other: &TransferSide,
+        journal: &TransferJournal,
+        account_entry: EntryType,
+        offset_entry: EntryType,
+    ) -> JournalDomainEvent {
+        JournalDomainEvent::TransactionCreated {
+            transaction_id: side.transaction_id,
+            journal_id: side.journal_id,
+            number: journal.last_number + 1,
+            balance_updates: vec![
+                BalanceUpdate {
+                    account_id: side.account_id,
+                    amount: self.amount,
+                    entry_type: account_entry,
+                    memo: None,
+                },
+                BalanceUpdate {
+                    account_id: side.offset_account_id,
+                    amount: self.amount,
+                    entry_type: offset_entry,
+                    memo: None,
+                },
+            ],
+            effective_date: self.effective_date,
+            reverses: None,
+            transfer: Some(TransferLink {
+                journal_id: other.journal_id,
+                transaction_id: other.transaction_id,
+            }),
+            payee: None,
+            authority: self.authority.clone(),
+            timestamp: self.timestamp,
+        }
+    }
+}
+
+impl Decision for CreateTransfer {
+    type Event = JournalDomainEvent;
+    type StateQuery = (TransferJournal, TransferJournal, Transaction, Transaction);
+    type Error = JournalError;
+
+    fn state_query(&self) -> Self::StateQuery {
+        (
+            TransferJournal::new(self.from.journal_id),
+            TransferJournal::new(self.to.journal_id),
+            Transaction::new(self.from.transaction_id),
+            Transaction::new(self.to.transaction_id),
+        )
+    }
+
+    fn process(
+        &self,
+        (from_journal, to_journal, from_transaction, to_transaction): &Self::StateQuery,
+    ) -> Result<Vec<Self::Event>, Self::Error> {
+        if self.from.journal_id == self.to.journal_id {
+            return Err(JournalError::TransferWithinJournal(self.from.journal_id));
+        }
+
+        for (side, transaction) in [(&self.from, from_transaction), (&self.to, to_transaction)] {
+            if transaction.status.found() {
+                return Err(JournalError::TransactionIdCollision(side.transaction_id));
+            }
+        }
+
+        for (side, journal) in [(&self.from, from_journal), (&self.to, to_journal)] {
+            // a journal the user can't read is turned away like a missing one, before its
+            // accounts can give away anything about it
+            if !journal.status.valid() || !journal.permits(&self.authority, Permissions::READ) {
+                return Err(JournalError::InvalidJournal(side.journal_id));
+            }
+
+            if !journal.permits(&self.authority, Permissions::APPEND_TRANSACTION) {
+                return Err(JournalError::Permissions(Permissions::APPEND_TRANSACTION));
+            }
+
+            if journal.archived {
+                return Err(JournalError::JournalArchived(side.journal_id));
+            }
+
+            for account_id in [side.account_id, side.offset_account_id] {
+                if !journal.accounts.contains(&account_id) {
+                    return Err(JournalError::InvalidAccount(account_id));
+                }
+            }
+        }
+
+        Ok(vec![
+            self.transaction(
+                &self.from,
+                &self.to,
+                from_journal,
+                EntryType::Credit,
+                EntryType::Debit,
+            ),
+            self.transaction(
+                &self.to,
+                &self.from,
+                to_journal,
+                EntryType::Debit,
+                EntryType::Credit,
+            ),
+        ])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::authority::Actor;
+    use chrono::Utc;
+
+    fn journal(journal_id: JournalId, owner: UserId, accounts: &[AccountId]) -> TransferJournal {
+        let mut journal = TransferJournal::new(journal_id);
+        journal.owner = owner;
+        journal.status = Status::Valid;
+        journal.accounts = accounts.iter().copied().collect();
+        journal.last_number = 7;
+        journal
+    }
+
+    fn side(journal_id: JournalId) -> TransferSide {
+        TransferSide {
+            journal_id,
+            transaction_id: TransactionId::new(),
+            account_id: AccountId::new(),
+            offset_account_id: AccountId::new(),
+        }
+    }
+
+    #[test]
+    fn transfers_record_a_linked_transaction_in_each_journal() {
+        let owner = UserId::new();
+        let member = UserId::new();
+        let from = side(JournalId::new());
+        let to = side(JournalId::new());
+        let date = NaiveDate::from_ymd_opt(2026, 10, 17).expect("invalid date");
+
+        let decision = CreateTransfer::new(
+            from,
+            to,
+            2500,
+            date,
+            Authority::Direct(Actor::User(member)),
+            Utc::now(),
+        );
+        let (_, _, from_transaction, to_transaction) = decision.state_query();
+
+        let from_journal = journal(
+            from.journal_id,
+            member,
+            &[from.account_id, from.offset_account_id],
+        );
+        let mut to_journal = journal(to.journal_id, owner, &[to.account_id, to.offset_account_id]);
+
+        // the member can't read the destination, so it's hidden rather than refused
+        assert_eq!(
+            decision.process(&(
+                from_journal.clone(),
+                to_journal.clone(),
+                from_transaction.clone(),
+                to_transaction.clone()
+            )),
+            Err(JournalError::InvalidJournal(to.journal_id))
+        );
+
+        to_journal.members.insert(member, Permissions::READ);
+
+        assert_eq!(
+            decision.process(&(
+                from_journal.clone(),
+                to_journal.clone(),
+                from_transaction.clone(),
+                to_transaction.clone()
+            )),
+            Err(JournalError::Permissions(Permissions::APPEND_TRANSACTION))
+        );
+
+        to_journal
+            .members
+            .insert(member, Permissions::READ | Permissions::APPEND_TRANSACTION);
+
+        let events = decision
+            .process(&(from_journal, to_journal, from_transaction, to_transaction))
+            .expect("the transfer is valid");
+
+        let JournalDomainEvent::TransactionCreated {
+            number,
+            balance_updates,
+            transfer,
+            ..
+        } = &events[0]
+        else {
+            panic!("expected a transaction");
+        };
+        assert_eq!(*number, 8);
+        assert_eq!(balance_updates[0].account_id, from.account_id);
+        assert_eq!(balance_updates[0].entry_type, EntryType::Credit);
+        assert_eq!(
+            *transfer,
+            Some(TransferLink {
+                journal_id: to.journal_id,
+                transaction_id: to.transaction_id,
+            })
+        );
+
+        let JournalDomainEvent::TransactionCreated {
+            journal_id,
+            balance_updates,
+            transfer,
+            ..
+        } = &events[1]
+        else {
+            panic!("expected a transaction");
+        };
+        assert_eq!(*journal_id, to.journal_id);
+        assert_eq!(balance_updates[0].account_id, to.account_id);
+        assert_eq!(balance_updates[0].entry_type, EntryType::Debit);
+        assert_eq!(
+            transfer.map(|link| link.transaction_id),
+            Some(from.transaction_id)
+        );
+    }
+
+    #[test]
+    fn unreadable_destinations_dont_reveal_their_accounts() {
+        let owner = UserId::new();
+        let stranger = UserId::new();
+        let from = side(JournalId::new());
+        let to = side(JournalId::new());
+
+        let decision = CreateTransfer::new(
+            from,
+            to,
+            2500,
+            NaiveDate::from_ymd_opt(2026, 10, 17).expect("invalid date"),
+            Authority::Direct(Actor::User(stranger)),
+            Utc::now(),
+        );
+        let (_, _, from_transaction, to_transaction) = decision.state_query();
+
+        // whether or not the destination has the account, the stranger learns only that they
+        // can't reach the journal
+        for accounts in [&[][..], &[to.account_id, to.offset_account_id][..]] {
+            assert_eq!(
+                decision.process(&(
+                    journal(
+                        from.journal_id,
+                        stranger,
+                        &[from.account_id, from.offset_account_id]
+                    ),
+                    journal(to.journal_id, owner, accounts),
+                    from_transaction.clone(),
+                    to_transaction.clone()
+                )),
+                Err(JournalError::InvalidJournal(to.journal_id))
+            );
+        }
+    }
+
+    #[test]
+    fn transfers_need_two_journals() {
+        let owner = UserId::new();
+        let from = side(JournalId::new());
+        let to = side(from.journal_id);
+
+        let decision = CreateTransfer::new(
+            from,
+            to,
+            2500,
+            NaiveDate::from_ymd_opt(2026, 10, 17).expect("invalid date"),
+            Authority::Direct(Actor::User(owner)),
+            Utc::now(),
+        );
+        let (_, _, from_transaction, to_transaction) = decision.state_query();
+        let accounts = [
+            from.account_id,
+            from.offset_account_id,
+            to.account_id,
+            to.offset_account_id,
+        ];
+
+        assert_eq!(
+            decision.process(&(
+                journal(from.journal_id, owner, &accounts),
+                journal(to.journal_id, owner, &accounts),
+                from_transaction,
+                to_transaction
+            )),
+            Err(JournalError::TransferWithinJournal(from.journal_id))
+        );
+    }
+}
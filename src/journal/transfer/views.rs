@@ -0,0 +1,229 @@
+use crate::BackendType;
+use crate::StateType;
+use crate::authn::get_user;
+use crate::authority::Authority;
+use crate::journal::JournalId;
+use crate::journal::access::JournalAccess;
+use crate::journal::account::AccountId;
+use crate::journal::layout::journal_layout;
+use crate::monkesto_error::MonkestoError;
+use crate::monkesto_error::UrlError;
+use crate::name::Name;
+use crate::time_provider::TimeProvider;
+use axum::extract::Path;
+use axum::extract::Query;
+use axum::extract::State;
+use axum::response::Redirect;
+use axum_login::AuthSession;
+use maud::Markup;
+use maud::html;
+use std::str::FromStr;
+
+async fn journal_name(state: &StateType, journal_id: JournalId, authority: &Authority) -> String {
+    state
+        .journal_service
+        .get_journal(journal_id, authority)
+        .await
+        .map(|(journal, _, _)| journal.name.to_string())
+        .unwrap_or_else(|e| format!("failed to fetch the journal name: {e}"))
+}
+
+async fn accounts(
+    state: &StateType,
+    journal_id: JournalId,
+    authority: &Authority,
+) -> Vec<(AccountId, Name)> {
+    state
+        .journal_service
+        .list_journal_accounts(journal_id, authority)
+        .await
+        .map(|accounts| {
+            accounts
+                .into_iter()
+                .map(|(account, _, _)| (account.id, account.name))
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+fn account_select(name: &str, label: &str, accounts: &[(AccountId, Name)]) -> Markup {
+    html! {
+        div {
+            label
+            for=(name)
+            class="block text-sm/6 font-medium text-gray-900 dark:text-gray-100" {
+                (label)
+            }
+            div class="mt-2" {
+                select
+                id=(name)
+                name=(name)
+                required
+                class="block w-full rounded-md bg-white px-3 py-1.5 text-base text-gray-900 outline-1 -outline-offset-1 outline-gray-300 focus:outline-2 focus:-outline-offset-2 focus:outline-indigo-600 sm:text-sm/6 dark:bg-white/5 dark:text-white dark:outline-white/10 dark:focus:outline-indigo-500" {
+                    option value="" { "Select account..." }
+                    @for (account_id, name) in accounts {
+                        option value=(account_id) { (name) }
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// lists the other journals the user can transfer to
+pub async fn transfer_list_page(State(state): State<StateType>, access: JournalAccess) -> Markup {
+    let journal_id = access.journal.id;
+
+    let content = html! {
+        p class="text-sm text-gray-500 dark:text-gray-400" {
+            "A transfer records money leaving an account in this journal and arriving in an "
+            "account in another, as a linked transaction in each."
+        }
+
+        @match state.journal_service.list_accessible_journals(access.actor).await {
+            Ok(journals) => {
+                @let others: Vec<_> = journals.into_iter().filter(|(journal, _, _)| journal.id != journal_id).collect();
+                @if others.is_empty() {
+                    p class="text-sm text-gray-500 dark:text-gray-400" {
+                        "You don't have any other journals to transfer to."
+                    }
+                }
+                div class="grid grid-cols-1 sm:grid-cols-2 gap-4" {
+                    @for (journal, _, _) in &others {
+                        a
+                        href=(format!("/journal/{}/transfer/{}", journal_id, journal.id))
+                        class="p-4 bg-white dark:bg-gray-800 border border-gray-200 dark:border-gray-700 rounded-xl hover:bg-gray-50 dark:hover:bg-gray-700 transition-colors" {
+                            h3 class="text-base font-semibold text-gray-900 dark:text-white" {
+                                "Transfer to " (journal.name)
+                            }
+                        }
+                    }
+                }
+            }
+            Err(e) => {
+                p { "failed to get your journals: " (e) }
+            }
+        }
+    };
+
+    let wrapped_content = html! {
+        div class="flex flex-col gap-6 mx-auto w-full max-w-4xl" {
+            (content)
+        }
+    };
+
+    journal_layout(&access.journal, wrapped_content)
+}
+
+pub async fn transfer_page(
+    State(state): State<StateType>,
+    session: AuthSession<BackendType>,
+    access: JournalAccess,
+    Path((id, to_id)): Path<(String, String)>,
+    Query(err): Query<UrlError>,
+) -> Result<Markup, Redirect> {
+    let user = get_user(session)?;
+    let authority = access.authority();
+    let journal_id = access.journal.id;
+
+    let Ok(to_journal_id) = JournalId::from_str(&to_id) else {
+        return Ok(journal_layout(
+            &access.journal,
+            html! {
+                div class="flex justify-center items-center h-full" {
+                    p class="text-gray-500 dark:text-gray-400" {
+                        "Invalid journal Id"
+                    }
+                }
+            },
+        ));
+    };
+
+    let to_name = journal_name(&state, to_journal_id, &authority).await;
+    let from_accounts = accounts(&state, journal_id, &authority).await;
+    let to_accounts = accounts(&state, to_journal_id, &authority).await;
+    let today = user.timezone().date(state.clock.get_time());
+
+    let content = html! {
+        div class="flex flex-col gap-6 mx-auto w-full max-w-4xl" {
+            form action=(format!("/journal/{}/transfer/{}", id, to_id)) method="post" class="space-y-6" {
+                h3 class="text-base font-semibold text-gray-900 dark:text-gray-100" {
+                    "Transfer to " (to_name)
+                }
+
+                div class="grid grid-cols-1 sm:grid-cols-2 gap-4" {
+                    div {
+                        label
+                        for="amount"
+                        class="block text-sm/6 font-medium text-gray-900 dark:text-gray-100" {
+                            "Amount"
+                        }
+                        div class="mt-2" {
+                            input
+                            id="amount"
+                            type="text"
+                            name="amount"
+                            inputmode="decimal"
+                            required
+                            placeholder="0.00"
+                            class="block w-full rounded-md bg-white px-3 py-1.5 text-base text-gray-900 outline-1 -outline-offset-1 outline-gray-300 placeholder:text-gray-400 focus:outline-2 focus:-outline-offset-2 focus:outline-indigo-600 sm:text-sm/6 dark:bg-white/5 dark:text-white dark:outline-white/10 dark:placeholder:text-gray-500 dark:focus:outline-indigo-500"
+                            ;
+                        }
+                    }
+
+                    div {
+                        label
+                        for="effective_date"
+                        class="block text-sm/6 font-medium text-gray-900 dark:text-gray-100" {
+                            "Date"
+                        }
+                        div class="mt-2" {
+                            input
+                            id="effective_date"
+                            type="date"
+                            name="effective_date"
+                            required
+                            value=(today)
+                            class="block w-full rounded-md bg-white px-3 py-1.5 text-base text-gray-900 outline-1 -outline-offset-1 outline-gray-300 focus:outline-2 focus:-outline-offset-2 focus:outline-indigo-600 sm:text-sm/6 dark:bg-white/5 dark:text-white dark:outline-white/10 dark:focus:outline-indigo-500"
+                            ;
+                        }
+                    }
+                }
+
+                div class="grid grid-cols-1 sm:grid-cols-2 gap-6" {
+                    div class="space-y-4" {
+                        h4 class="text-sm font-semibold text-gray-900 dark:text-gray-100" { "In " (access.journal.name) }
+                        (account_select("account_id", "Paid from", &from_accounts))
+                        (account_select("offset_account_id", "Recorded against", &from_accounts))
+                    }
+                    div class="space-y-4" {
+                        h4 class="text-sm font-semibold text-gray-900 dark:text-gray-100" { "In " (to_name) }
+                        (account_select("to_account_id", "Paid into", &to_accounts))
+                        (account_select("to_offset_account_id", "Recorded against", &to_accounts))
+                    }
+                }
+
+                p class="text-xs text-gray-500 dark:text-gray-400" {
+                    "Each journal's side is balanced by the account it's recorded against, "
+                    "such as owner's draws in one and owner's contributions in the other."
+                }
+
+                div {
+                    button
+                    type="submit"
+                    class="flex w-full justify-center rounded-md bg-indigo-600 px-3 py-1.5 text-sm/6 font-semibold text-white shadow-xs hover:bg-indigo-500 focus-visible:outline-2 focus-visible:outline-offset-2 focus-visible:outline-indigo-600 dark:bg-indigo-500 dark:shadow-none dark:hover:bg-indigo-400 dark:focus-visible:outline-indigo-500" {
+                        "Record Transfer"
+                    }
+                }
+            }
+
+            @if let Some(e) = err.err {
+                p {
+                    (format!("An error occurred: {:?}", MonkestoError::decode(&e)))
+                }
+            }
+        }
+    };
+
+    Ok(journal_layout(&access.journal, content))
+}
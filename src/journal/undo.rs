@@ -0,0 +1,301 @@
+use crate::BackendType;
+use crate::StateType;
+use crate::authn::get_user;
+use crate::authn::user::UserId;
+use crate::authority::{Actor, Authority};
+use crate::journal::transaction::TransactionId;
+use crate::journal::{JournalError, JournalId, Permissions};
+use crate::monkesto_error::{ErrorRedirect, OrRedirect};
+use crate::time_provider::{TimeProvider, Timestamp};
+use axum::extract::{Path, State};
+use axum::response::Redirect;
+use axum_extra::extract::Form;
+use axum_login::AuthSession;
+use chrono::TimeDelta;
+use maud::{Markup, html};
+use serde::{Deserialize, Serialize};
+use std::str::FromStr;
+use tower_sessions::Session;
+
+const UNDO_KEY: &str = "undo_actions";
+
+/// how long a destructive action can be undone after it was made
+const UNDO_WINDOW_MINUTES: i64 = 5;
+
+/// the event that reverses a destructive action. accounts can't be deleted in the app, so
+/// there's nothing to restore them from
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub enum Compensation {
+    RestoreMember {
+        user_id: UserId,
+        permissions: Permissions,
+    },
+    /// reversing is how a transaction is voided, so withdrawing the reversal un-voids it
+    WithdrawReversal {
+        reversal_id: TransactionId,
+        transaction_id: TransactionId,
+    },
+}
+
+/// a destructive action that the user who made it can still undo, kept in their session
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct UndoableAction {
+    /// the id of the event that made the action, which is unique across every journal
+    id: String,
+    journal_id: JournalId,
+    description: String,
+    compensation: Compensation,
+    expires_at: Timestamp,
+}
+
+fn unexpired(actions: Vec<UndoableAction>, now: Timestamp) -> Vec<UndoableAction> {
+    actions
+        .into_iter()
+        .filter(|action| action.expires_at > now)
+        .collect()
+}
+
+async fn load(session: &Session, now: Timestamp) -> Vec<UndoableAction> {
+    let actions = session
+        .get::<Vec<UndoableAction>>(UNDO_KEY)
+        .await
+        .ok()
+        .flatten()
+        .unwrap_or_default();
+
+    unexpired(actions, now)
+}
+
+/// offers to undo an action for the next few minutes.
+/// failing to save it only loses the undo, so the action itself still succeeds
+pub async fn record(
+    session: &Session,
+    id: impl ToString,
+    journal_id: JournalId,
+    description: String,
+    compensation: Compensation,
+    now: Timestamp,
+) {
+    let mut actions = load(session, now).await;
+
+    actions.push(UndoableAction {
+        id: id.to_string(),
+        journal_id,
+        description,
+        compensation,
+        expires_at: now + TimeDelta::minutes(UNDO_WINDOW_MINUTES),
+    });
+
+    _ = session.insert(UNDO_KEY, actions).await;
+}
+
+/// a banner for each action in the journal that can still be undone, on the page at `page`
+pub async fn banner(
+    session: &Session,
+    journal_id: JournalId,
+    page: &str,
+    now: Timestamp,
+) -> Markup {
+    let actions = load(session, now).await;
+
+    html! {
+        @for action in actions.iter().filter(|action| action.journal_id == journal_id) {
+            div class="flex justify-between items-center p-4 bg-yellow-50 dark:bg-yellow-900/30 border border-yellow-200 dark:border-yellow-800 rounded-xl" {
+                p class="text-sm text-yellow-800 dark:text-yellow-200" {
+                    (action.description)
+                }
+                form method="post" action=(format!("/journal/{}/undo/{}", journal_id, action.id)) {
+                    input type="hidden" name="page" value=(page);
+                    button
+                    type="submit"
+                    class="px-3 py-1 text-sm font-medium bg-white dark:bg-gray-700 border border-yellow-300 dark:border-yellow-700 rounded-md text-yellow-800 dark:text-yellow-200 hover:bg-yellow-100 dark:hover:bg-gray-600" {
+                        "Undo"
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[derive(Deserialize)]
+pub struct UndoForm {
+    /// the page the banner was on, which is where errors are shown
+    page: Option<String>,
+}
+
+pub async fn undo(
+    State(state): State<StateType>,
+    session: AuthSession<BackendType>,
+    Path((id, undo_id)): Path<(String, String)>,
+    Form(form): Form<UndoForm>,
+) -> Result<Redirect, ErrorRedirect> {
+    // only pages in the journal are returned to, so the form can't redirect anywhere else
+    let callback_url = &form
+        .page
+        .filter(|page| page.starts_with(&format!("/journal/{}/", id)))
+        .unwrap_or_else(|| format!("/journal/{}/person", id));
+
+    let journal_id = JournalId::from_str(&id).or_redirect(callback_url)?;
+
+    let browser_session = session.session.clone();
+    let user = get_user(session)?;
+
+    let mut actions = load(&browser_session, state.clock.get_time()).await;
+
+    // the action is taken out of the session first, so it can't be undone twice
+    let action = actions
+        .iter()
+        .position(|action| action.id == undo_id && action.journal_id == journal_id)
+        .map(|index| actions.remove(index))
+        .ok_or(JournalError::UndoExpired)
+        .or_redirect(callback_url)?;
+
+    _ = browser_session.insert(UNDO_KEY, actions).await;
+
+    let authority = Authority::Direct(Actor::User(user.id));
+
+    let (event_id, return_url) = match action.compensation {
+        Compensation::RestoreMember {
+            user_id,
+            permissions,
+        } => (
+            state
+                .journal_service
+                .add_member(
+                    journal_id,
+                    user_id,
+                    permissions,
+                    authority,
+                    state.clock.get_time(),
+                )
+                .await
+                .or_redirect(callback_url)?,
+            callback_url.clone(),
+        ),
+        // the reversal's page is gone once it's withdrawn, so the reversed transaction is shown
+        Compensation::WithdrawReversal {
+            reversal_id,
+            transaction_id,
+        } => (
+            state
+                .journal_service
+                .withdraw_reversal(reversal_id, journal_id, authority, state.clock.get_time())
+                .await
+                .or_redirect(callback_url)?,
+            format!("/journal/{}/transaction/{}", id, transaction_id),
+        ),
+    };
+
+    state.journal_service.wait_for(event_id).await;
+
+    Ok(Redirect::to(&return_url))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_app::{TestApp, assert_accepted};
+    use crate::time_provider::DefaultTimeProvider;
+
+    #[test]
+    fn expired_actions_are_dropped() {
+        let now = DefaultTimeProvider.get_time();
+        let action = |id: &str, expires_at| UndoableAction {
+            id: id.to_string(),
+            journal_id: JournalId::new(),
+            description: "removed a member".to_string(),
+            compensation: Compensation::RestoreMember {
+                user_id: UserId::new(),
+                permissions: Permissions::READ,
+            },
+            expires_at,
+        };
+
+        let live = action("live", now + TimeDelta::minutes(1));
+        let expired = action("expired", now - TimeDelta::seconds(1));
+
+        assert_eq!(unexpired(vec![expired, live.clone()], now), vec![live]);
+    }
+
+    #[tokio::test]
+    async fn reversals_are_undone_from_their_banner() {
+        let Some(app) = TestApp::spawn().await else {
+            return;
+        };
+
+        let owner = app.signup("owner").await;
+        let member = app.signup("member").await;
+        let journal_id = owner.create_journal(&app, "Bakery").await;
+        assert_accepted(&owner.create_account(journal_id, "Till").await);
+        assert_accepted(&owner.create_account(journal_id, "Flour").await);
+        assert_accepted(
+            &owner
+                .invite(
+                    journal_id,
+                    &member,
+                    Permissions::READ | Permissions::APPEND_TRANSACTION,
+                )
+                .await,
+        );
+
+        let till = app.account_id(journal_id, "Till").await;
+        let flour = app.account_id(journal_id, "Flour").await;
+        assert_accepted(
+            &member
+                .post_transaction(journal_id, till, flour, "25.00")
+                .await,
+        );
+        let transaction_id = app
+            .state
+            .journal_service
+            .list_journal_transactions(journal_id, &Authority::Direct(Actor::System))
+            .await
+            .expect("failed to list the transactions")[0]
+            .0
+            .id;
+
+        // a member who can append transactions can reverse one, and undo that
+        let reversed = member
+            .client
+            .post(&format!(
+                "/journal/{journal_id}/transaction/{transaction_id}/reverse"
+            ))
+            .await;
+        assert_accepted(&reversed);
+        assert_eq!(app.transaction_count(journal_id).await, 2);
+
+        let reversal_url = reversed.header("location").to_str().unwrap().to_string();
+        let reversal_page = member
+            .client
+            .get(&reversal_url)
+            .clear_headers()
+            .add_header("accept", "text/html")
+            .await
+            .text();
+        let undo_url = reversal_page
+            .split('"')
+            .find(|part| part.starts_with(&format!("/journal/{journal_id}/undo/")))
+            .expect("the reversal has no undo banner")
+            .to_string();
+
+        let undone = member
+            .client
+            .post(&undo_url)
+            .form(&[("page", reversal_url.as_str())])
+            .await;
+        assert_accepted(&undone);
+        assert_eq!(
+            undone.header("location"),
+            format!("/journal/{journal_id}/transaction/{transaction_id}").as_str()
+        );
+        assert_eq!(app.transaction_count(journal_id).await, 1);
+
+        // the undo was used up
+        member
+            .client
+            .post(&undo_url)
+            .form(&[("page", reversal_url.as_str())])
+            .await;
+        assert_eq!(app.transaction_count(journal_id).await, 1);
+    }
+}
@@ -1,21 +1,31 @@
 use crate::BackendType;
 use crate::StateType;
 use crate::authn::get_user;
+use crate::authn::user::UserId;
 use crate::authority::Actor;
-use crate::authority::Authority;
+use crate::i18n::{t, tf};
 use crate::id::Ident;
-use crate::journal::JournalId;
-use crate::journal::layout::layout;
+use crate::journal::Permissions;
+use crate::journal::access::JournalAccess;
+use crate::journal::appearance::{JournalAppearance, JournalColor, journal_badge};
+use crate::journal::fiscal::FiscalYearStart;
+use crate::journal::json::JournalJson;
+use crate::journal::layout::{journal_layout, layout};
+use crate::journal::stats;
 use crate::monkesto_error::MonkestoError;
+use crate::monkesto_error::MonkestoResult;
 use crate::monkesto_error::UrlError;
-use axum::extract::Path;
+use crate::monkesto_error::{ErrorRedirect, OrRedirect};
+use crate::negotiate::WantsJson;
+use crate::time_provider::TimeProvider;
+use axum::Json;
 use axum::extract::Query;
 use axum::extract::State;
-use axum::response::Redirect;
+use axum::response::{IntoResponse, Redirect, Response};
 use axum_login::AuthSession;
 use maud::Markup;
 use maud::html;
-use std::str::FromStr;
+use std::collections::{HashMap, HashSet};
 
 #[expect(dead_code)]
 pub struct Journal {
@@ -32,37 +42,101 @@ pub async fn journal_list(
 ) -> Result<Markup, Redirect> {
     let user = get_user(session)?;
 
+    let journals_res = state
+        .journal_service
+        .list_accessible_journals(user.id)
+        .await;
+
+    // a journal only shows as unpinned if the pins can't be read
+    let pinned = state
+        .journal_service
+        .pinned_journals(user.id)
+        .await
+        .unwrap_or_default();
+
+    // every creator's email in one query rather than one per journal
+    let creators_res: MonkestoResult<HashMap<UserId, String>> = match &journals_res {
+        Ok(journals) => {
+            let creator_ids: Vec<UserId> = journals
+                .iter()
+                .filter_map(|(_, authority, _)| match authority.actor() {
+                    Actor::User(id) => Some(*id),
+                    Actor::System | Actor::Anonymous | Actor::Deleted => None,
+                })
+                .collect::<HashSet<UserId>>()
+                .into_iter()
+                .collect();
+
+            state
+                .authn_service
+                .fetch_users(creator_ids.as_slice())
+                .await
+                .map(|creators| {
+                    creators
+                        .into_iter()
+                        .map(|c| (c.id, c.display_name()))
+                        .collect()
+                })
+                .map_err(|e| e.into())
+        }
+        Err(_) => Ok(HashMap::new()),
+    };
+
     let content = html! {
         div class="grid grid-cols-1 sm:grid-cols-2 lg:grid-cols-3 gap-4" {
-            @match state.journal_service.list_accessible_journals(user.id).await {
+            @match journals_res {
                 Ok(journals) => {
                     @for (journal, journal_creator, journal_creation_timestamp) in journals {
-                        a
-                        href=(format! ("/journal/{}", journal.id))
-                        class="self-start p-4 bg-white dark:bg-gray-800 border border-gray-200 dark:border-gray-700 rounded-xl hover:bg-gray-50 dark:hover:bg-gray-700 transition-colors" {
-                            h3 class="text-lg font-semibold text-gray-900 dark:text-white" {
-                                (journal.name)
-                            }
+                        @let is_pinned = pinned.contains(&journal.id);
+                        div class="relative self-start" {
+                            a
+                            href=(format! ("/journal/{}", journal.id))
+                            class="block p-4 pr-20 bg-white dark:bg-gray-800 border border-gray-200 dark:border-gray-700 rounded-xl hover:bg-gray-50 dark:hover:bg-gray-700 transition-colors" {
+                                h3 class="flex items-center gap-2 text-lg font-semibold text-gray-900 dark:text-white" {
+                                    (journal_badge(&journal.name, &journal.appearance))
+                                    (journal.name)
 
-                            div class="mt-2 text-sm text-gray-600 dark:text-gray-400" {
-                                "Created by "
+                                    @if journal.archived {
+                                        (archived_badge())
+                                    }
+                                }
 
-                                @match journal_creator.actor() {
-                                    Actor::System => {"System"},
-                                    Actor::Anonymous => {"Anonymous"},
-                                    Actor::User(creator_id) => {
-                                         @match state.authn_service.fetch_user(*creator_id).await {
-                                            Ok(user) => (user.email.to_string()),
+                                div class="mt-2 text-sm text-gray-600 dark:text-gray-400" {
+                                    (t("journal.created_by")) " "
 
-                                            Err(e) => (format!("failed to fetch creator email: {:?}", e)),
+                                    @match journal_creator.actor() {
+                                        Actor::System => {(t("actor.system"))},
+                                        Actor::Anonymous => {(t("actor.anonymous"))},
+                                        Actor::Deleted => {(t("actor.deleted"))},
+                                        Actor::User(creator_id) => {
+                                             @match creators_res.as_ref().map(|creators| creators.get(creator_id)) {
+                                                Ok(Some(name)) => (name),
+                                                Ok(None) => (tf("journal.creator_failed", &[("error", creator_id)])),
+                                                Err(e) => (tf("journal.creator_failed", &[("error", &format!("{:?}", e))])),
+                                            }
                                         }
                                     }
-                                }
 
-                                " on "
+                                    " " (t("journal.created_on")) " "
+
+                                    (user.timezone().format(journal_creation_timestamp))
 
-                                (journal_creation_timestamp.with_timezone(&chrono_tz::America::Chicago).format("%Y-%m-%d %H:%M:%S %Z"))
+                                }
+                            }
 
+                            form
+                            action=(format!("/journal/{}/{}", journal.id, if is_pinned { "unpin" } else { "pin" }))
+                            method="post"
+                            class="absolute top-4 right-4" {
+                                button
+                                type="submit"
+                                class="text-sm font-medium text-indigo-600 hover:text-indigo-500 dark:text-indigo-400 dark:hover:text-indigo-300" {
+                                    @if is_pinned {
+                                        (t("journal.unpin"))
+                                    } @else {
+                                        (t("journal.pin"))
+                                    }
+                                }
                             }
                         }
                     }
@@ -71,7 +145,7 @@ pub async fn journal_list(
                 Err(e) => {
                     div class="flex justify-center items-center h-full" {
                         p class="text-gray-500 dark:text-gray-400" {
-                            (format!("Failed to fetch journals: {:?}", e))
+                            (tf("journal.list_failed", &[("error", &format!("{:?}", e))]))
                         }
                     }
                 }
@@ -79,7 +153,7 @@ pub async fn journal_list(
 
             form action="/createjournal" method="post" class="self-start rounded-xl transition-colors space-y-4" {
                 h3 class="text-lg font-semibold text-gray-900 dark:text-white" {
-                    "Create New Journal"
+                    (t("journal.create_heading"))
                 }
 
                 div {
@@ -87,23 +161,27 @@ pub async fn journal_list(
                     id="journal_name"
                     type="text"
                     name="journal_name"
-                    placeholder="Journal name"
+                    placeholder=(t("journal.name_placeholder"))
                     required
                     class="block w-full rounded-md bg-white px-3 py-1.5 text-base text-gray-900 outline-1 -outline-offset-1 outline-gray-300 placeholder:text-gray-400 focus:outline-2 focus:-outline-offset-2 focus:outline-indigo-600 sm:text-sm/6 dark:bg-white/5 dark:text-white dark:outline-white/10 dark:placeholder:text-gray-500 dark:focus:outline-indigo-500"
                     ;
                 }
 
+                div class="flex flex-wrap items-end gap-3" {
+                    (appearance_fields(&JournalAppearance::default()))
+                }
+
                 button
                 type="submit"
                 class="w-full rounded-md bg-indigo-600 px-3 py-1.5 text-sm/6 font-semibold text-white shadow-xs hover:bg-indigo-500 focus-visible:outline-2 focus-visible:outline-offset-2 focus-visible:outline-indigo-600 dark:bg-indigo-500 dark:shadow-none dark:hover:bg-indigo-400 dark:focus-visible:outline-indigo-500"{
-                    "Create"
+                    (t("journal.create"))
                 }
             }
         }
 
         @if let Some(e) = err.err {
             p class="mt-6 text-center text-sm/6 text-gray-500 dark:text-gray-400" {
-                (format! ("error: {:?}", MonkestoError::decode(&e)))
+                (tf("error.generic", &[("error", &format!("{:?}", MonkestoError::decode(&e)))]))
             }
         }
     };
@@ -114,106 +192,302 @@ pub async fn journal_list(
 pub async fn journal_detail(
     State(state): State<StateType>,
     session: AuthSession<BackendType>,
-    Path(id): Path<String>,
-) -> Result<Markup, Redirect> {
+    access: JournalAccess,
+    WantsJson(json): WantsJson,
+    Query(err): Query<UrlError>,
+) -> Result<Response, Redirect> {
     let user = get_user(session)?;
 
-    let journal_state_res = match JournalId::from_str(&id) {
-        Ok(s) => {
-            state
-                .journal_service
-                .get_journal(s, &Authority::Direct(Actor::User(user.id)))
-                .await
-        }
-        Err(e) => Err(e.into()),
-    };
+    if json {
+        return Ok(journal_json(&state, &access).await.into_response());
+    }
 
-    let content = if let Ok(journal_id) = JournalId::from_str(&id) {
-        let journal_state_res = state
-            .journal_service
-            .get_journal(journal_id, &Authority::Direct(Actor::User(user.id)))
-            .await;
-        html! {
-            div class="flex flex-col gap-6" {
-                @match &journal_state_res {
-                    Ok((_journal, journal_creator, journal_creation_timestamp)) => {
-                        div class="grid grid-cols-1 sm:grid-cols-2 lg:grid-cols-3 gap-4" {
-                            a
-                            href=(format!("/journal/{}/transaction", &id))
-                            class="self-start p-4 bg-white dark:bg-gray-800 border border-gray-200 dark:border-gray-700 rounded-xl hover:bg-gray-50 dark:hover:bg-gray-700 transition-colors"{
-                                h3 class="text-lg font-semibold text-gray-900 dark:text-white" {
-                                    "Transactions"
-                                }
-                            }
+    let journal = &access.journal;
+    let journal_id = journal.id;
+    let id = journal_id.to_string();
 
-                            a
-                            href=(format!("/journal/{}/account", &id))
-                            class="self-start p-4 bg-white dark:bg-gray-800 border border-gray-200 dark:border-gray-700 rounded-xl hover:bg-gray-50 dark:hover:bg-gray-700 transition-colors"{
-                                h3 class="text-lg font-semibold text-gray-900 dark:text-white" {
-                                    "Accounts"
-                                }
-                            }
+    let content = html! {
+        div class="flex flex-col gap-6" {
+            @if journal.archived {
+                div class="flex items-center gap-2 p-4 bg-gray-50 dark:bg-gray-800 rounded-lg text-sm text-gray-600 dark:text-gray-400" {
+                    (archived_badge())
+                    (t("journal.archived_notice"))
+                }
+            }
 
-                            a
-                            href=(format!("/journal/{}/person", &id))
-                            class="self-start p-4 bg-white dark:bg-gray-800 border border-gray-200 dark:border-gray-700 rounded-xl hover:bg-gray-50 dark:hover:bg-gray-700 transition-colors"{
-                                h3 class="text-lg font-semibold text-gray-900 dark:text-white" {
-                                    "People"
+            form method="get" action=(format!("/journal/{}/search", &id)) {
+                input
+                type="search"
+                name="q"
+                placeholder=(t("journal.search_placeholder"))
+                class="block w-full rounded-md bg-white px-3 py-1.5 text-base text-gray-900 outline-1 -outline-offset-1 outline-gray-300 placeholder:text-gray-400 focus:outline-2 focus:-outline-offset-2 focus:outline-indigo-600 sm:text-sm/6 dark:bg-white/5 dark:text-white dark:outline-white/10 dark:placeholder:text-gray-500 dark:focus:outline-indigo-500";
+            }
+
+            div class="grid grid-cols-1 sm:grid-cols-2 lg:grid-cols-3 gap-4" {
+                a
+                href=(format!("/journal/{}/transaction", &id))
+                class="self-start p-4 bg-white dark:bg-gray-800 border border-gray-200 dark:border-gray-700 rounded-xl hover:bg-gray-50 dark:hover:bg-gray-700 transition-colors"{
+                    h3 class="text-lg font-semibold text-gray-900 dark:text-white" {
+                        (t("journal.transactions"))
+                    }
+                }
+
+                a
+                href=(format!("/journal/{}/pending", &id))
+                class="self-start p-4 bg-white dark:bg-gray-800 border border-gray-200 dark:border-gray-700 rounded-xl hover:bg-gray-50 dark:hover:bg-gray-700 transition-colors"{
+                    h3 class="text-lg font-semibold text-gray-900 dark:text-white" {
+                        (t("journal.pending"))
+                    }
+                }
+
+                a
+                href=(format!("/journal/{}/account", &id))
+                class="self-start p-4 bg-white dark:bg-gray-800 border border-gray-200 dark:border-gray-700 rounded-xl hover:bg-gray-50 dark:hover:bg-gray-700 transition-colors"{
+                    h3 class="text-lg font-semibold text-gray-900 dark:text-white" {
+                        (t("journal.accounts"))
+                    }
+                }
+
+                a
+                href=(format!("/journal/{}/person", &id))
+                class="self-start p-4 bg-white dark:bg-gray-800 border border-gray-200 dark:border-gray-700 rounded-xl hover:bg-gray-50 dark:hover:bg-gray-700 transition-colors"{
+                    h3 class="text-lg font-semibold text-gray-900 dark:text-white" {
+                        (t("journal.people"))
+                    }
+                }
+
+                a
+                href=(format!("/journal/{}/transfer", &id))
+                class="self-start p-4 bg-white dark:bg-gray-800 border border-gray-200 dark:border-gray-700 rounded-xl hover:bg-gray-50 dark:hover:bg-gray-700 transition-colors"{
+                    h3 class="text-lg font-semibold text-gray-900 dark:text-white" {
+                        (t("journal.transfers"))
+                    }
+                }
+
+                a
+                href=(format!("/journal/{}/webhook", &id))
+                class="self-start p-4 bg-white dark:bg-gray-800 border border-gray-200 dark:border-gray-700 rounded-xl hover:bg-gray-50 dark:hover:bg-gray-700 transition-colors"{
+                    h3 class="text-lg font-semibold text-gray-900 dark:text-white" {
+                        (t("journal.webhooks"))
+                    }
+                }
+
+                a
+                href=(format!("/journal/{}/rule", &id))
+                class="self-start p-4 bg-white dark:bg-gray-800 border border-gray-200 dark:border-gray-700 rounded-xl hover:bg-gray-50 dark:hover:bg-gray-700 transition-colors"{
+                    h3 class="text-lg font-semibold text-gray-900 dark:text-white" {
+                        (t("journal.import_rules"))
+                    }
+                }
+
+                a
+                href=(format!("/journal/{}/bankfeed", &id))
+                class="self-start p-4 bg-white dark:bg-gray-800 border border-gray-200 dark:border-gray-700 rounded-xl hover:bg-gray-50 dark:hover:bg-gray-700 transition-colors"{
+                    h3 class="text-lg font-semibold text-gray-900 dark:text-white" {
+                        (t("journal.bank_feeds"))
+                    }
+                }
+
+                a
+                href=(format!("/journal/{}/report/net-worth", &id))
+                class="self-start p-4 bg-white dark:bg-gray-800 border border-gray-200 dark:border-gray-700 rounded-xl hover:bg-gray-50 dark:hover:bg-gray-700 transition-colors"{
+                    h3 class="text-lg font-semibold text-gray-900 dark:text-white" {
+                        (t("journal.net_worth"))
+                    }
+                }
+
+                a
+                href=(format!("/journal/{}/report/payees", &id))
+                class="self-start p-4 bg-white dark:bg-gray-800 border border-gray-200 dark:border-gray-700 rounded-xl hover:bg-gray-50 dark:hover:bg-gray-700 transition-colors"{
+                    h3 class="text-lg font-semibold text-gray-900 dark:text-white" {
+                        (t("journal.payees"))
+                    }
+                }
+
+                a
+                href=(format!("/journal/{}/history", &id))
+                class="self-start p-4 bg-white dark:bg-gray-800 border border-gray-200 dark:border-gray-700 rounded-xl hover:bg-gray-50 dark:hover:bg-gray-700 transition-colors"{
+                    h3 class="text-lg font-semibold text-gray-900 dark:text-white" {
+                        (t("journal.history"))
+                    }
+                }
+            }
+
+            (stats::stats_section(&state, journal_id, &access.authority(), user.timezone().date(state.clock.get_time())).await)
+
+            div class="p-4 bg-gray-50 dark:bg-gray-800 rounded-lg" {
+                div class="space-y-2" {
+                    div class="text-sm text-gray-600 dark:text-gray-400" {
+                        (t("journal.created_by")) " "
+
+                        @match access.created_by.actor() {
+                            Actor::System => {(t("actor.system"))},
+                            Actor::Anonymous => {(t("actor.anonymous"))},
+                            Actor::Deleted => {(t("actor.deleted"))},
+                            Actor::User(creator_id) => {
+                                 @match state.authn_service.get_display_name(*creator_id).await {
+                                    Ok(name) => (name),
+
+                                    Err(e) => (tf("journal.creator_failed", &[("error", &format!("{:?}", e))])),
                                 }
                             }
                         }
 
-                        div class="p-4 bg-gray-50 dark:bg-gray-800 rounded-lg" {
-                            div class="space-y-2" {
-                                div class="text-sm text-gray-600 dark:text-gray-400" {
-                                    "Created by "
-
-                                    @match journal_creator.actor() {
-                                        Actor::System => {"System"},
-                                        Actor::Anonymous => {"Anonymous"},
-                                        Actor::User(creator_id) => {
-                                             @match state.authn_service.fetch_user(*creator_id).await {
-                                                Ok(user) => (user.email.to_string()),
+                        " " (t("journal.created_on")) " "
+                        (user.timezone().format(access.created_at))
+                    }
+                }
+            }
 
-                                                Err(e) => (format!("failed to fetch creator email: {:?}", e)),
-                                            }
-                                        }
-                                    }
+            div class="flex flex-wrap items-center gap-3 text-sm" {
+                span class="text-gray-600 dark:text-gray-400" { (t("journal.export")) }
+                a href=(format!("/journal/{}/export.beancount", &id))
+                class="font-medium text-indigo-600 hover:text-indigo-500 dark:text-indigo-400 dark:hover:text-indigo-300" {
+                    "Beancount"
+                }
+                a href=(format!("/journal/{}/export.ledger", &id))
+                class="font-medium text-indigo-600 hover:text-indigo-500 dark:text-indigo-400 dark:hover:text-indigo-300" {
+                    "Ledger"
+                }
+            }
 
-                                    " on "
-                                    (journal_creation_timestamp.with_timezone(&chrono_tz::America::Chicago).format("%Y-%m-%d %H:%M:%S %Z"))
+            @if access.permissions.contains(Permissions::OWNER) {
+                form method="post" action=(format!("/journal/{}/settings", &id)) class="flex flex-wrap items-end gap-3" {
+                    div {
+                        label for="fiscal_year_start" class="block text-xs font-medium text-gray-700 dark:text-gray-300 mb-1" { (t("journal.fiscal_year_start")) }
+                        select class="rounded-md border border-gray-300 dark:border-gray-600 bg-white dark:bg-gray-800 px-2 py-1 text-sm text-gray-900 dark:text-white"
+                        id="fiscal_year_start" name="fiscal_year_start" {
+                            @for month in FiscalYearStart::all() {
+                                option value=(month.month()) selected[month == journal.fiscal_year_start] { (month) }
+                            }
+                        }
+                    }
+                    // the defaults prefill imports and quick entries that leave an account out
+                    @let accounts = state.journal_service.list_journal_accounts(journal_id, &access.authority()).await.unwrap_or_default();
+                    @for (field, label, default) in [
+                        ("default_cash_account", "journal.default_cash_account", journal.default_cash_account),
+                        ("default_expense_account", "journal.default_expense_account", journal.default_expense_account),
+                    ] {
+                        div {
+                            label for=(field) class="block text-xs font-medium text-gray-700 dark:text-gray-300 mb-1" { (t(label)) }
+                            select class="rounded-md border border-gray-300 dark:border-gray-600 bg-white dark:bg-gray-800 px-2 py-1 text-sm text-gray-900 dark:text-white"
+                            id=(field) name=(field) {
+                                option value="" { (t("journal.no_default_account")) }
+                                @for (account, _, _) in &accounts {
+                                    option value=(account.id) selected[default == Some(account.id)] { (account.name) }
                                 }
                             }
                         }
                     }
+                    (appearance_fields(&journal.appearance))
+                    button class="px-3 py-1 text-sm bg-white dark:bg-gray-700 border border-gray-300 dark:border-gray-600 rounded-md text-gray-700 dark:text-gray-200 hover:bg-gray-100 dark:hover:bg-gray-600" type="submit" {
+                        (t("journal.save"))
+                    }
+                }
 
-                    Err(e) => {
-                        div class="flex justify-center items-center h-full" {
-                            p class="text-gray-500 dark:text-gray-400" {
-                                (format!("Failed to fetch journal: {:?}", e))
-                            }
+                // unlike deleting, archiving keeps the journal readable and can be undone
+                form method="post" action=(format!("/journal/{}/{}", &id, if journal.archived { "unarchive" } else { "archive" })) class="p-4 space-y-3 border border-gray-200 dark:border-gray-700 rounded-lg" {
+                    h3 class="text-sm font-semibold text-gray-900 dark:text-white" { (t("journal.archive_heading")) }
+                    p class="text-sm text-gray-600 dark:text-gray-400" { (t("journal.archive_description")) }
+                    button class="px-3 py-1 text-sm bg-white dark:bg-gray-700 border border-gray-300 dark:border-gray-600 rounded-md text-gray-700 dark:text-gray-200 hover:bg-gray-100 dark:hover:bg-gray-600" type="submit" {
+                        @if journal.archived {
+                            (t("journal.unarchive"))
+                        } @else {
+                            (t("journal.archive"))
                         }
                     }
+                }
 
+                // the journal can't be opened once it's deleted, so this is the last
+                // chance to export it before it's purged
+                form method="post" action=(format!("/journal/{}/delete", &id)) class="p-4 space-y-3 border border-red-200 dark:border-red-900 rounded-lg" {
+                    h3 class="text-sm font-semibold text-red-700 dark:text-red-400" { (t("journal.delete_heading")) }
+                    p class="text-sm text-gray-600 dark:text-gray-400" {
+                        (tf("journal.delete_warning", &[("days", &state.journal_service.retention_days())]))
+                        " "
+                        a href=(format!("/journal/{}/report/trial-balance.pdf", &id))
+                        class="font-medium text-indigo-600 hover:text-indigo-500 dark:text-indigo-400 dark:hover:text-indigo-300" {
+                            (t("journal.delete_export"))
+                        }
+                    }
+                    div class="flex items-center gap-2" {
+                        input id="exported" name="exported" type="checkbox" required
+                        class="h-4 w-4 rounded border-gray-300 text-red-600 focus:ring-red-600 dark:border-gray-700 dark:bg-gray-900";
+                        label for="exported" class="text-sm text-gray-700 dark:text-gray-300" { (t("journal.delete_exported")) }
+                    }
+                    button
+                    type="submit"
+                    onclick=(format!("return confirm('{}')", t("journal.delete_confirm")))
+                    class="px-3 py-1.5 text-sm font-semibold bg-red-600 text-white rounded-md hover:bg-red-500 focus:outline-none focus:ring-2 focus:ring-red-500 focus:ring-offset-1" {
+                        (t("journal.delete"))
+                    }
+                }
+            } @else {
+                form method="post" action=(format!("/journal/{}/leave", &id)) {
+                    button
+                    type="submit"
+                    onclick=(format!("return confirm('{}')", t("journal.leave_confirm")))
+                    class="px-3 py-1.5 text-sm font-semibold bg-red-600 text-white rounded-md hover:bg-red-500 focus:outline-none focus:ring-2 focus:ring-red-500 focus:ring-offset-1" {
+                        (t("journal.leave"))
+                    }
                 }
             }
-        }
-    } else {
-        html! {
-            div class="flex justify-center items-center h-full" {
-                p class="text-gray-500 dark:text-gray-400" {
-                    (format!("Invalid journal id: {:?}", id))
+
+            @if let Some(e) = err.err {
+                p class="text-center text-sm/6 text-gray-500 dark:text-gray-400" {
+                    (tf("error.generic", &[("error", &format!("{:?}", MonkestoError::decode(&e)))]))
                 }
             }
         }
     };
 
-    let journal_name = match journal_state_res {
-        Ok((journal, _, _)) => journal.name.to_string(),
-        Err(e) => {
-            format!("failed to fetch the journal: {e}")
+    Ok(journal_layout(journal, content).into_response())
+}
+
+/// the journal and its accounts, for scripts reading the journal page as json
+async fn journal_json(
+    state: &StateType,
+    access: &JournalAccess,
+) -> Result<Json<JournalJson>, ErrorRedirect> {
+    let journal_id = access.journal.id;
+
+    let accounts = state
+        .journal_service
+        .list_journal_accounts(journal_id, &access.authority())
+        .await
+        .or_redirect(&format!("/journal/{journal_id}"))?
+        .into_iter()
+        .map(|(account, _, _)| account)
+        .collect();
+
+    Ok(Json(JournalJson::new(&access.journal, accounts)))
+}
+
+fn archived_badge() -> Markup {
+    html! {
+        span class="px-2 py-0.5 text-xs font-medium rounded-full bg-gray-100 text-gray-600 dark:bg-gray-700 dark:text-gray-300" {
+            (t("journal.archived"))
         }
-    };
+    }
+}
 
-    Ok(layout(Some(&journal_name), true, Some(&id), content))
+/// the color and icon inputs shared by the create form and the settings form
+fn appearance_fields(appearance: &JournalAppearance) -> Markup {
+    html! {
+        div {
+            label for="color" class="block text-xs font-medium text-gray-700 dark:text-gray-300 mb-1" { (t("journal.color")) }
+            select class="rounded-md border border-gray-300 dark:border-gray-600 bg-white dark:bg-gray-800 px-2 py-1 text-sm text-gray-900 dark:text-white"
+            id="color" name="color" {
+                @for color in JournalColor::ALL {
+                    option value=(color) selected[color == appearance.color] { (t(color.label())) }
+                }
+            }
+        }
+        div {
+            label for="icon" class="block text-xs font-medium text-gray-700 dark:text-gray-300 mb-1" { (t("journal.icon")) }
+            input class="w-20 rounded-md border border-gray-300 dark:border-gray-600 bg-white dark:bg-gray-800 px-2 py-1 text-sm text-gray-900 dark:text-white"
+            type="text" id="icon" name="icon" placeholder=(t("journal.icon_placeholder"))
+            value=[appearance.icon.as_ref().map(|icon| icon.to_string())];
+        }
+    }
 }
@@ -0,0 +1,66 @@
+use crate::StateType;
+use crate::journal::access::{JournalAccess, Owner};
+use crate::journal::webhook::{WebhookId, generate_webhook_secret};
+use crate::monkesto_error::{ErrorRedirect, OrRedirect};
+use crate::time_provider::TimeProvider;
+use axum::extract::Path;
+use axum::extract::State;
+use axum::response::Redirect;
+use axum_extra::extract::Form;
+use serde::Deserialize;
+use std::str::FromStr;
+
+#[derive(Deserialize)]
+pub struct AddWebhookForm {
+    url: String,
+}
+
+pub async fn add_webhook(
+    State(state): State<StateType>,
+    access: JournalAccess<Owner>,
+    Form(form): Form<AddWebhookForm>,
+) -> Result<Redirect, ErrorRedirect> {
+    let callback_url = &format!("/journal/{}/webhook", access.journal.id);
+
+    let event_id = state
+        .journal_service
+        .add_webhook(
+            state.ids.generate(),
+            access.journal.id,
+            form.url.trim().to_string(),
+            generate_webhook_secret(),
+            access.authority(),
+            state.clock.get_time(),
+        )
+        .await
+        .or_redirect(callback_url)?;
+
+    state.journal_service.wait_for(event_id).await;
+
+    Ok(Redirect::to(callback_url))
+}
+
+pub async fn remove_webhook(
+    State(state): State<StateType>,
+    access: JournalAccess<Owner>,
+    Path((_, webhook_id)): Path<(String, String)>,
+) -> Result<Redirect, ErrorRedirect> {
+    let callback_url = &format!("/journal/{}/webhook", access.journal.id);
+
+    let webhook_id = WebhookId::from_str(&webhook_id).or_redirect(callback_url)?;
+
+    let event_id = state
+        .journal_service
+        .remove_webhook(
+            webhook_id,
+            access.journal.id,
+            access.authority(),
+            state.clock.get_time(),
+        )
+        .await
+        .or_redirect(callback_url)?;
+
+    state.journal_service.wait_for(event_id).await;
+
+    Ok(Redirect::to(callback_url))
+}
@@ -0,0 +1,242 @@
+use crate::journal::JournalId;
+use crate::journal::JournalService;
+use crate::journal::domain::JournalDomainEvent;
+use crate::journal::service::PendingWebhookDelivery;
+use crate::journal::webhook::{PublicResolver, deliverable, signing_secret};
+use crate::shutdown;
+use async_trait::async_trait;
+use axum_login::tracing;
+use disintegrate::{EventListener, PersistedEvent, StreamQuery, query};
+use disintegrate_postgres::PgEventId;
+use hmac::{Hmac, Mac};
+use reqwest::header::CONTENT_TYPE;
+use serde::Serialize;
+use sha2::Sha256;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::Notify;
+
+const MAX_DELIVERY_ATTEMPTS: i32 = 8;
+const DELIVERY_BATCH_SIZE: i64 = 50;
+const OUTBOX_POLL_INTERVAL: Duration = Duration::from_secs(30);
+const DELIVERY_TIMEOUT: Duration = Duration::from_secs(10);
+/// how long a claimed batch is kept from other instances. it outlasts a batch whose every
+/// attempt times out, and a batch abandoned by a crash is retried once it runs out
+const DELIVERY_LEASE: Duration = Duration::from_secs(600);
+
+pub const SIGNATURE_HEADER: &str = "X-Monkesto-Signature";
+pub const EVENT_ID_HEADER: &str = "X-Monkesto-Event-Id";
+
+#[derive(Serialize)]
+struct WebhookPayload<'a> {
+    event_id: PgEventId,
+    journal_id: JournalId,
+    event: &'a JournalDomainEvent,
+}
+
+/// signs a delivery body with the webhook's secret so that receivers can verify its origin
+pub fn sign(secret: &str, body: &[u8]) -> String {
+    let mut mac =
+        Hmac::<Sha256>::new_from_slice(secret.as_bytes()).expect("hmac accepts keys of any size");
+    mac.update(body);
+    hex::encode(mac.finalize().into_bytes())
+}
+
+/// writes journal events to the webhook outbox, which the delivery worker drains
+#[derive(Clone)]
+pub struct WebhookDispatcher {
+    query: StreamQuery<PgEventId, JournalDomainEvent>,
+    journal_service: JournalService,
+    enqueued: Arc<Notify>,
+}
+
+impl WebhookDispatcher {
+    pub fn new(journal_service: JournalService) -> Self {
+        Self {
+            query: query!(JournalDomainEvent),
+            journal_service,
+            enqueued: Arc::new(Notify::new()),
+        }
+    }
+
+    /// delivers pending outbox rows until shutdown. a row is only marked delivered after
+    /// the receiver accepts it, so deliveries are retried across restarts. every instance runs
+    /// this, and each claims its rows before sending them
+    pub async fn deliver_pending(self) {
+        // a redirect could point anywhere, including at the server's own network
+        let client = reqwest::Client::builder()
+            .timeout(DELIVERY_TIMEOUT)
+            .dns_resolver(Arc::new(PublicResolver))
+            .redirect(reqwest::redirect::Policy::none())
+            .build()
+            .expect("failed to create the webhook http client");
+
+        let mut shutdown = Box::pin(shutdown());
+
+        loop {
+            match self
+                .journal_service
+                .claim_webhook_deliveries(
+                    MAX_DELIVERY_ATTEMPTS,
+                    DELIVERY_BATCH_SIZE,
+                    DELIVERY_LEASE,
+                )
+                .await
+            {
+                Ok(deliveries) => {
+                    for delivery in deliveries {
+                        self.attempt(&client, delivery).await;
+                    }
+                }
+                Err(error) => tracing::error!(?error, "failed to fetch pending webhook deliveries"),
+            }
+
+            tokio::select! {
+                _ = self.enqueued.notified() => {},
+                _ = tokio::time::sleep(OUTBOX_POLL_INTERVAL) => {},
+                _ = &mut shutdown => return,
+            }
+        }
+    }
+
+    async fn attempt(&self, client: &reqwest::Client, delivery: PendingWebhookDelivery) {
+        let secret = match self
+            .journal_service
+            .secrets()
+            .get(&signing_secret(delivery.webhook_id))
+            .await
+        {
+            Ok(secret) => secret,
+            Err(error) => {
+                tracing::error!(webhook_id = %delivery.webhook_id, %error, "failed to read a webhook's signing secret");
+                None
+            }
+        };
+
+        let res = match secret {
+            Some(secret) if deliverable(&delivery.url) => {
+                let signature = sign(&secret, &delivery.body);
+
+                Some(
+                    client
+                        .post(&delivery.url)
+                        .header(CONTENT_TYPE, "application/json")
+                        .header(SIGNATURE_HEADER, format!("sha256={signature}"))
+                        .header(EVENT_ID_HEADER, delivery.event_id)
+                        .body(delivery.body)
+                        .send()
+                        .await,
+                )
+            }
+            // deliveries to addresses that aren't public are never sent
+            Some(_) => {
+                tracing::warn!(webhook_id = %delivery.webhook_id, delivery.event_id, "webhook url isn't public");
+                None
+            }
+            // unsigned deliveries are never sent
+            None => {
+                tracing::warn!(webhook_id = %delivery.webhook_id, delivery.event_id, "webhook has no signing secret");
+                None
+            }
+        };
+
+        let delivered = match res {
+            None => false,
+            Some(Ok(res)) if res.status().is_success() => true,
+            Some(Ok(res)) => {
+                tracing::warn!(webhook_id = %delivery.webhook_id, delivery.event_id, status = %res.status(), delivery.attempts, "webhook delivery was rejected");
+                false
+            }
+            Some(Err(error)) => {
+                tracing::warn!(webhook_id = %delivery.webhook_id, delivery.event_id, ?error, delivery.attempts, "webhook delivery failed");
+                false
+            }
+        };
+
+        let recorded = if delivered {
+            self.journal_service
+                .mark_webhook_delivered(delivery.id)
+                .await
+        } else {
+            if delivery.attempts + 1 >= MAX_DELIVERY_ATTEMPTS {
+                tracing::error!(webhook_id = %delivery.webhook_id, delivery.event_id, "giving up on webhook delivery");
+            }
+            self.journal_service.mark_webhook_failed(delivery.id).await
+        };
+
+        if let Err(error) = recorded {
+            tracing::error!(
+                ?error,
+                delivery.id,
+                "failed to record a webhook delivery attempt"
+            );
+        }
+    }
+}
+
+#[async_trait]
+impl EventListener<PgEventId, JournalDomainEvent> for WebhookDispatcher {
+    type Error = sqlx::Error;
+
+    fn id(&self) -> &'static str {
+        "webhook dispatcher"
+    }
+
+    fn query(&self) -> &StreamQuery<PgEventId, JournalDomainEvent> {
+        &self.query
+    }
+
+    async fn handle(
+        &self,
+        event: PersistedEvent<PgEventId, JournalDomainEvent>,
+    ) -> Result<(), Self::Error> {
+        let event_id = event.id();
+        let event = event.into_inner();
+
+        // what only the owners may see isn't sent anywhere else
+        if event.owners_only() {
+            return Ok(());
+        }
+
+        let Some(journal_id) = event.journal_id() else {
+            return Ok(());
+        };
+
+        // the journal projection owns the webhooks table, so wait until it has
+        // seen every webhook that was registered before this event
+        self.journal_service.wait_for(event_id).await;
+
+        let body = match serde_json::to_vec(&WebhookPayload {
+            event_id,
+            journal_id,
+            event: &event,
+        }) {
+            Ok(body) => body,
+            Err(error) => {
+                tracing::error!(event_id, ?error, "failed to serialize a webhook payload");
+                return Ok(());
+            }
+        };
+
+        self.journal_service
+            .enqueue_webhook_deliveries(event_id, journal_id, &body)
+            .await?;
+
+        self.enqueued.notify_one();
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn signatures_match_the_hmac_sha256_test_vector() {
+        assert_eq!(
+            sign("key", b"The quick brown fox jumps over the lazy dog"),
+            "f7bc83f430538424b13298e6aa6fb143ef4d59a14946175997479dbc2d1a3cd8"
+        );
+    }
+}
@@ -0,0 +1,424 @@
+pub mod commands;
+pub mod delivery;
+pub mod views;
+
+use axum::Router;
+use axum::routing::{get, post};
+use axum_login::login_required;
+
+pub fn router() -> Router<crate::StateType> {
+    Router::new()
+        .route("/journal/{id}/webhook", get(views::webhook_list_page))
+        .route("/journal/{id}/webhook", post(commands::add_webhook))
+        .route(
+            "/journal/{id}/webhook/{webhook_id}/remove",
+            post(commands::remove_webhook),
+        )
+        .route_layer(login_required!(crate::BackendType, login_url = "/signin"))
+}
+
+use crate::authority::Authority;
+use crate::id;
+use crate::id::Ident;
+use crate::journal::domain::{JournalDomainEvent, WebhookEvent};
+use crate::journal::member::JournalMember;
+use crate::journal::{Journal, Permissions, check_permissions};
+use crate::journal::{JournalError, JournalId};
+use crate::secret::Secret;
+use crate::status::Status;
+use crate::time_provider::Timestamp;
+use disintegrate::{Decision, StateMutate, StateQuery};
+use rand::Rng;
+use reqwest::dns::{Addrs, Name, Resolve, Resolving};
+use serde::Deserialize;
+use serde::Serialize;
+use std::io;
+use std::net::{IpAddr, SocketAddr};
+use url::{Host, Url};
+
+id!(WebhookId, Ident::new16());
+
+/// generates the secret used to sign a webhook's deliveries
+pub fn generate_webhook_secret() -> String {
+    hex::encode(rand::rng().random::<[u8; 32]>())
+}
+
+/// where a webhook's signing secret is kept
+pub fn signing_secret(webhook_id: WebhookId) -> Secret<String> {
+    Secret::new(format!("webhook/{webhook_id}/signing_secret"))
+}
+
+fn validate_webhook_url(url: &str) -> Result<(), JournalError> {
+    match Url::parse(url) {
+        Ok(parsed) if parsed.scheme() == "https" && parsed.host().is_some() => Ok(()),
+        _ => Err(JournalError::InvalidWebhookUrl(url.to_string())),
+    }
+}
+
+/// whether webhooks may be delivered to the address. the loopback, link local and private
+/// ranges belong to the server's own network, and delivering there would let a journal owner
+/// make requests to services that aren't meant to be reachable
+pub fn is_public(ip: IpAddr) -> bool {
+    match ip {
+        IpAddr::V4(ip) => {
+            let [a, b, ..] = ip.octets();
+            !(ip.is_unspecified()
+                || ip.is_loopback()
+                || ip.is_private()
+                || ip.is_link_local()
+                || ip.is_broadcast()
+                || ip.is_multicast()
+                || ip.is_documentation()
+                // shared address space, used by carrier grade NAT
+                || (a == 100 && (64..128).contains(&b)))
+        }
+        IpAddr::V6(ip) => match ip.to_ipv4_mapped() {
+            Some(ip) => is_public(IpAddr::V4(ip)),
+            None => {
+                !(ip.is_unspecified()
+                    || ip.is_loopback()
+                    || ip.is_unique_local()
+                    || ip.is_unicast_link_local()
+                    || ip.is_multicast())
+            }
+        },
+    }
+}
+
+/// the address the url names, when its host is an ip rather than a domain
+fn literal_ip(url: &Url) -> Option<IpAddr> {
+    match url.host()? {
+        Host::Ipv4(ip) => Some(IpAddr::V4(ip)),
+        Host::Ipv6(ip) => Some(IpAddr::V6(ip)),
+        Host::Domain(_) => None,
+    }
+}
+
+/// refuses a webhook whose host isn't public, or doesn't resolve to only public addresses
+pub async fn ensure_public(url: &str) -> Result<(), JournalError> {
+    let invalid = || JournalError::InvalidWebhookUrl(url.to_string());
+    let parsed = Url::parse(url).map_err(|_| invalid())?;
+
+    let addresses: Vec<IpAddr> = match literal_ip(&parsed) {
+        Some(ip) => vec![ip],
+        None => {
+            let host = parsed.host_str().ok_or_else(invalid)?;
+            let port = parsed.port_or_known_default().unwrap_or(443);
+            tokio::net::lookup_host((host, port))
+                .await
+                .map_err(|_| invalid())?
+                .map(|address| address.ip())
+                .collect()
+        }
+    };
+
+    if addresses.is_empty() || !addresses.into_iter().all(is_public) {
+        return Err(invalid());
+    }
+
+    Ok(())
+}
+
+/// whether a delivery may be sent to the url. domains are checked by [`PublicResolver`] as
+/// they're connected to, but an ip in the url is connected to without being resolved
+pub fn deliverable(url: &str) -> bool {
+    Url::parse(url)
+        .ok()
+        .is_some_and(|parsed| literal_ip(&parsed).is_none_or(is_public))
+}
+
+/// resolves the hosts that deliveries are sent to, refusing any that resolve to an address that
+/// isn't public. a host checked only when its webhook was added could resolve elsewhere since
+pub struct PublicResolver;
+
+impl Resolve for PublicResolver {
+    fn resolve(&self, name: Name) -> Resolving {
+        Box::pin(async move {
+            let addresses: Vec<SocketAddr> =
+                tokio::net::lookup_host((name.as_str(), 0)).await?.collect();
+
+            if !addresses.iter().all(|address| is_public(address.ip())) {
+                return Err(io::Error::other(format!(
+                    "{} resolves to an address that isn't public",
+                    name.as_str()
+                ))
+                .into());
+            }
+
+            Ok(Box::new(addresses.into_iter()) as Addrs)
+        })
+    }
+}
+
+#[derive(StateQuery, Clone, Default, Serialize, Deserialize)]
+#[state_query(WebhookEvent)]
+pub struct Webhook {
+    #[id]
+    webhook_id: WebhookId,
+    journal_id: JournalId,
+    status: Status,
+}
+
+impl Webhook {
+    fn new(webhook_id: WebhookId) -> Self {
+        Self {
+            webhook_id,
+            ..Default::default()
+        }
+    }
+}
+
+impl StateMutate for Webhook {
+    fn mutate(&mut self, event: Self::Event) {
+        match event {
+            WebhookEvent::WebhookAdded { journal_id, .. } => {
+                self.journal_id = journal_id;
+                self.status = Status::Valid;
+            }
+            WebhookEvent::WebhookRemoved { .. } => self.status = Status::Deleted,
+        }
+    }
+}
+
+pub struct AddWebhook {
+    webhook_id: WebhookId,
+    journal_id: JournalId,
+    url: String,
+    authority: Authority,
+    timestamp: Timestamp,
+}
+
+impl AddWebhook {
+    pub fn new(
+        webhook_id: WebhookId,
+        journal_id: JournalId,
+        url: String,
+        authority: Authority,
+        timestamp: Timestamp,
+    ) -> Self {
+        Self {
+            webhook_id,
+            journal_id,
+            url,
+            authority,
+            timestamp,
+        }
+    }
+}
+
+impl Decision for AddWebhook {
+    type Event = JournalDomainEvent;
+    type StateQuery = (Webhook, Journal, JournalMember);
+    type Error = JournalError;
+
+    fn state_query(&self) -> Self::StateQuery {
+        (
+            Webhook::new(self.webhook_id),
+            Journal::new(self.journal_id),
+            JournalMember::new(
+                self.journal_id,
+                self.authority.user_id().unwrap_or_default(),
+            ),
+        )
+    }
+
+    fn process(
+        &self,
+        (webhook, journal, actor): &Self::StateQuery,
+    ) -> Result<Vec<Self::Event>, Self::Error> {
+        if webhook.status.found() {
+            return Err(JournalError::WebhookIdCollision(self.webhook_id));
+        }
+
+        if !journal.status.valid() {
+            return Err(JournalError::InvalidJournal(self.journal_id));
+        }
+
+        // webhooks receive every event in the journal, so only owners may register them
+        check_permissions(actor, &self.authority, journal, Permissions::OWNER)?;
+
+        validate_webhook_url(&self.url)?;
+
+        Ok(vec![JournalDomainEvent::WebhookAdded {
+            webhook_id: self.webhook_id,
+            journal_id: self.journal_id,
+            url: self.url.clone(),
+            authority: self.authority.clone(),
+            timestamp: self.timestamp,
+        }])
+    }
+}
+
+pub struct RemoveWebhook {
+    webhook_id: WebhookId,
+    journal_id: JournalId,
+    authority: Authority,
+    timestamp: Timestamp,
+}
+
+impl RemoveWebhook {
+    pub fn new(
+        webhook_id: WebhookId,
+        journal_id: JournalId,
+        authority: Authority,
+        timestamp: Timestamp,
+    ) -> Self {
+        Self {
+            webhook_id,
+            journal_id,
+            authority,
+            timestamp,
+        }
+    }
+}
+
+impl Decision for RemoveWebhook {
+    type Event = JournalDomainEvent;
+    type StateQuery = (Webhook, Journal, JournalMember);
+    type Error = JournalError;
+
+    fn state_query(&self) -> Self::StateQuery {
+        (
+            Webhook::new(self.webhook_id),
+            Journal::new(self.journal_id),
+            JournalMember::new(
+                self.journal_id,
+                self.authority.user_id().unwrap_or_default(),
+            ),
+        )
+    }
+
+    fn process(
+        &self,
+        (webhook, journal, actor): &Self::StateQuery,
+    ) -> Result<Vec<Self::Event>, Self::Error> {
+        if !webhook.status.valid() || webhook.journal_id != self.journal_id {
+            return Err(JournalError::InvalidWebhook(self.webhook_id));
+        }
+
+        if !journal.status.valid() {
+            return Err(JournalError::InvalidJournal(self.journal_id));
+        }
+
+        check_permissions(actor, &self.authority, journal, Permissions::OWNER)?;
+
+        Ok(vec![JournalDomainEvent::WebhookRemoved {
+            webhook_id: self.webhook_id,
+            journal_id: self.journal_id,
+            authority: self.authority.clone(),
+            timestamp: self.timestamp,
+        }])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::authn::UserId;
+    use crate::authority::Actor;
+    use crate::test_app::{TestApp, assert_accepted, assert_denied};
+    use chrono::Utc;
+
+    fn valid_journal(journal_id: JournalId, owner: UserId) -> Journal {
+        let mut journal = Journal::new(journal_id);
+        journal.owner = owner;
+        journal.status = Status::Valid;
+        journal
+    }
+
+    #[test]
+    fn adding_a_plaintext_webhook_fails() {
+        let journal_id = JournalId::new();
+        let owner = UserId::new();
+        let decision = AddWebhook::new(
+            WebhookId::new(),
+            journal_id,
+            "http://example.com/hook".to_string(),
+            Authority::Direct(Actor::User(owner)),
+            Utc::now(),
+        );
+        let (webhook, _, member) = decision.state_query();
+        assert_eq!(
+            decision.process(&(webhook, valid_journal(journal_id, owner), member)),
+            Err(JournalError::InvalidWebhookUrl(
+                "http://example.com/hook".to_string()
+            ))
+        );
+    }
+
+    #[test]
+    fn only_public_addresses_receive_deliveries() {
+        for ip in [
+            "127.0.0.1",
+            "10.1.2.3",
+            "172.16.0.1",
+            "192.168.1.1",
+            "169.254.169.254",
+            "100.64.0.1",
+            "0.0.0.0",
+            "::1",
+            "fe80::1",
+            "fd00::1",
+            "::ffff:127.0.0.1",
+        ] {
+            assert!(!is_public(ip.parse().unwrap()), "{ip} is public");
+        }
+
+        for ip in ["93.184.215.14", "2606:2800:21f:cb07:6820:80da:af6b:8b2c"] {
+            assert!(is_public(ip.parse().unwrap()), "{ip} isn't public");
+        }
+
+        assert!(!deliverable("https://127.0.0.1/hook"));
+        assert!(!deliverable("https://[::1]/hook"));
+        assert!(deliverable("https://93.184.215.14/hook"));
+        assert!(deliverable("https://hooks.example.com/hook"));
+    }
+
+    #[tokio::test]
+    async fn webhooks_on_the_servers_own_network_are_refused() {
+        for url in [
+            "https://127.0.0.1/hook",
+            "https://localhost/hook",
+            "https://169.254.169.254/latest/meta-data",
+        ] {
+            assert_eq!(
+                ensure_public(url).await,
+                Err(JournalError::InvalidWebhookUrl(url.to_string()))
+            );
+        }
+    }
+
+    #[test]
+    fn removing_a_missing_webhook_fails() {
+        let journal_id = JournalId::new();
+        let owner = UserId::new();
+        let webhook_id = WebhookId::new();
+        let decision = RemoveWebhook::new(
+            webhook_id,
+            journal_id,
+            Authority::Direct(Actor::User(owner)),
+            Utc::now(),
+        );
+        let (webhook, _, member) = decision.state_query();
+        assert_eq!(
+            decision.process(&(webhook, valid_journal(journal_id, owner), member)),
+            Err(JournalError::InvalidWebhook(webhook_id))
+        );
+    }
+
+    #[tokio::test]
+    async fn only_owners_see_the_webhooks() {
+        let Some(app) = TestApp::spawn().await else {
+            return;
+        };
+
+        let owner = app.signup("owner").await;
+        let reader = app.signup("reader").await;
+        let journal_id = owner.create_journal(&app, "Ledger").await;
+        assert_accepted(&owner.invite(journal_id, &reader, Permissions::READ).await);
+
+        let page = &format!("/journal/{journal_id}/webhook");
+        owner.client.get(page).await.assert_status_ok();
+        assert_denied(&reader.client.get(page).await);
+    }
+}
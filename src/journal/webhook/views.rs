@@ -0,0 +1,103 @@
+use crate::StateType;
+use crate::journal::access::{JournalAccess, Owner};
+use crate::journal::layout::journal_layout;
+use crate::journal::webhook::delivery::SIGNATURE_HEADER;
+use crate::monkesto_error::MonkestoError;
+use crate::monkesto_error::UrlError;
+use axum::extract::Query;
+use axum::extract::State;
+use maud::Markup;
+use maud::html;
+
+pub async fn webhook_list_page(
+    State(state): State<StateType>,
+    access: JournalAccess<Owner>,
+    Query(err): Query<UrlError>,
+) -> Markup {
+    let id = access.journal.id.to_string();
+
+    let content = html! {
+        @match state.journal_service.list_journal_webhooks(access.journal.id, &access.authority()).await {
+            Ok(webhooks) => {
+                @for webhook in webhooks {
+                    div class="p-4 bg-white dark:bg-gray-800 border border-gray-200 dark:border-gray-700 rounded-xl" {
+                        div class="flex justify-between items-center gap-4" {
+                            div class="min-w-0 space-y-1" {
+                                h3 class="text-base font-semibold text-gray-900 dark:text-white truncate" { (webhook.url) }
+                                p class="text-xs text-gray-500 dark:text-gray-400 break-all" {
+                                    "Signing secret: " code { (webhook.secret) }
+                                }
+                            }
+                            form method="post" action=(format!("/journal/{}/webhook/{}/remove", id, webhook.id)) {
+                                button
+                                type="submit"
+                                class="rounded-md px-3 py-1.5 text-sm font-semibold text-red-600 hover:bg-red-50 dark:text-red-400 dark:hover:bg-red-950" {
+                                    "Remove"
+                                }
+                            }
+                        }
+                    }
+                }
+            },
+            Err(e) => {
+                p {
+                    "failed to get the webhooks: " (e)
+                }
+            }
+        }
+
+        hr class="mt-8 mb-6 border-gray-300 dark:border-gray-600";
+
+        div class="mt-10" {
+            form action=(format!("/journal/{}/webhook", id)) method="post" class="space-y-4" {
+                h3 class="text-base font-semibold text-gray-900 dark:text-gray-100" { "Add Webhook" }
+
+                p class="text-sm text-gray-500 dark:text-gray-400" {
+                    "Every event in this journal is sent as JSON to the url below. "
+                    "Each request is signed with an HMAC-SHA256 of its body in the " code { (SIGNATURE_HEADER) } " header."
+                }
+
+                div {
+                    label
+                    for="url"
+                    class="block text-sm/6 font-medium text-gray-900 dark:text-gray-100" {
+                        "HTTPS URL"
+                    }
+
+                    div class="mt-2" {
+                        input
+                        id="url"
+                        type="url"
+                        name="url"
+                        placeholder="https://"
+                        required
+                        class="block w-full rounded-md bg-white px-3 py-1.5 text-base text-gray-900 outline-1 -outline-offset-1 outline-gray-300 placeholder:text-gray-400 focus:outline-2 focus:-outline-offset-2 focus:outline-indigo-600 sm:text-sm/6 dark:bg-white/5 dark:text-white dark:outline-white/10 dark:placeholder:text-gray-500 dark:focus:outline-indigo-500"
+                        ;
+                    }
+                }
+
+                div {
+                    button
+                    type="submit"
+                    class="flex w-full justify-center rounded-md bg-indigo-600 px-3 py-1.5 text-sm/6 font-semibold text-white shadow-xs hover:bg-indigo-500 focus-visible:outline-2 focus-visible:outline-offset-2 focus-visible:outline-indigo-600 dark:bg-indigo-500 dark:shadow-none dark:hover:bg-indigo-400 dark:focus-visible:outline-indigo-500" {
+                        "Add Webhook"
+                    }
+                }
+            }
+        }
+
+        @if let Some(e) = err.err {
+            p {
+                (format!("An error occurred: {:?}", MonkestoError::decode(&e)))
+            }
+        }
+    };
+
+    let wrapped_content = html! {
+        div class="flex flex-col gap-6 mx-auto w-full max-w-4xl" {
+            (content)
+        }
+    };
+
+    journal_layout(&access.journal, wrapped_content)
+}
@@ -0,0 +1,125 @@
+//! Monkesto as a library, so the app can be run from other binaries and test suites.
+//! [`Server`] connects the stores, then either hands back the router or serves it,
+//! and the `client` feature adds `client::Client`, a typed client for the json api
+
+mod app_env;
+mod asset;
+mod authn;
+mod authority;
+mod authz;
+mod charts;
+#[cfg(feature = "client")]
+pub mod client;
+mod database;
+mod email;
+mod entitlement;
+mod event_id;
+#[cfg(feature = "graphql")]
+mod graphql;
+mod i18n;
+mod id;
+mod journal;
+mod limits;
+mod mailer;
+mod monkesto_error;
+pub mod name;
+mod negotiate;
+mod notfoundpage;
+mod openapi;
+mod parse;
+mod secret;
+mod seed;
+mod serde;
+mod server;
+mod session;
+mod shortcuts;
+mod status;
+#[cfg(test)]
+mod test_app;
+mod theme;
+mod time_provider;
+pub mod util;
+mod validation;
+
+pub use crate::app_env::AppEnv;
+pub use crate::authn::attempt::LockoutConfig;
+pub use crate::authn::oidc::Oidc;
+pub use crate::database::DatabaseConfig;
+pub use crate::id::{IdGenerator, IdProvider, SequentialIdProvider};
+pub use crate::journal::backup::BackupConfig;
+pub use crate::journal::bankfeed::provider::BankFeeds;
+pub use crate::journal::consistency::BalanceCheckConfig;
+pub use crate::journal::quota::Quotas;
+pub use crate::journal::retention::RetentionConfig;
+pub use crate::limits::RequestLimits;
+pub use crate::mailer::Mailer;
+pub use crate::secret::SecretKey;
+pub use crate::server::{Server, ServerError};
+pub use crate::session::SessionConfig;
+pub use crate::time_provider::{Clock, MockClock, TimeProvider};
+
+use crate::authn::AuthnService;
+use crate::authz::AuthzService;
+use crate::journal::JournalService;
+use crate::journal::feed::FeedLimiter;
+use axum::extract::FromRef;
+use tokio::signal;
+
+pub mod proto {
+    pub mod error {
+        include!(concat!(env!("OUT_DIR"), "/proto.error.rs"));
+    }
+}
+
+#[derive(Clone)]
+struct AppState {
+    authn_service: AuthnService,
+    journal_service: JournalService,
+    authz_service: AuthzService,
+    bank_feeds: BankFeeds,
+    clock: Clock,
+    ids: IdGenerator,
+    feed_limiter: FeedLimiter,
+}
+
+impl AppState {
+    fn new(
+        authn_service: AuthnService,
+        authz_service: AuthzService,
+        journal_service: JournalService,
+        bank_feeds: BankFeeds,
+        clock: Clock,
+        ids: IdGenerator,
+    ) -> Self {
+        Self {
+            authn_service,
+            journal_service,
+            authz_service,
+            bank_feeds,
+            clock,
+            ids,
+            feed_limiter: FeedLimiter::default(),
+        }
+    }
+}
+
+impl FromRef<AppState> for JournalService {
+    fn from_ref(input: &AppState) -> Self {
+        input.journal_service.clone()
+    }
+}
+
+impl FromRef<AppState> for AuthzService {
+    fn from_ref(state: &AppState) -> Self {
+        state.authz_service.clone()
+    }
+}
+
+type StateType = AppState;
+type BackendType = AuthnService;
+
+async fn shutdown() {
+    signal::ctrl_c()
+        .await
+        .expect("failed to listen for an interrupt signal")
+}
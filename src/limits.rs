@@ -0,0 +1,93 @@
+use std::env;
+use std::time::Duration;
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+pub enum RequestLimitsError {
+    #[error("{0} must be a whole number of bytes, found {1}")]
+    Bytes(&'static str, String),
+
+    #[error("REQUEST_TIMEOUT_SECS must be a whole number of seconds, found {0}")]
+    Timeout(String),
+}
+
+/// how large and how slow a request can be before it's refused, so a single huge or stalled
+/// upload can't tie the server up
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct RequestLimits {
+    /// the largest body most routes accept, which is plenty for any of their forms
+    pub body: usize,
+    /// the largest body the import routes accept, since they take whole files
+    pub import_body: usize,
+    /// requests that haven't been answered by then get a 408
+    pub timeout: Duration,
+}
+
+impl Default for RequestLimits {
+    fn default() -> Self {
+        Self {
+            body: 256 * 1024,
+            import_body: 10 * 1024 * 1024,
+            timeout: Duration::from_secs(30),
+        }
+    }
+}
+
+fn bytes_from_env(key: &'static str, default: usize) -> Result<usize, RequestLimitsError> {
+    match env::var(key) {
+        Ok(value) => value
+            .parse::<usize>()
+            .map_err(|_| RequestLimitsError::Bytes(key, value)),
+        Err(_) => Ok(default),
+    }
+}
+
+impl RequestLimits {
+    pub fn from_env() -> Result<Self, RequestLimitsError> {
+        let default = Self::default();
+
+        let timeout = match env::var("REQUEST_TIMEOUT_SECS") {
+            Ok(value) => Duration::from_secs(
+                value
+                    .parse::<u64>()
+                    .map_err(|_| RequestLimitsError::Timeout(value))?,
+            ),
+            Err(_) => default.timeout,
+        };
+
+        Ok(Self {
+            body: bytes_from_env("REQUEST_BODY_LIMIT_BYTES", default.body)?,
+            import_body: bytes_from_env("IMPORT_BODY_LIMIT_BYTES", default.import_body)?,
+            timeout,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_app::TestApp;
+    use axum::http::StatusCode;
+
+    #[tokio::test]
+    async fn only_the_imports_accept_large_bodies() {
+        let Some(app) = TestApp::spawn().await else {
+            return;
+        };
+
+        let user = app.signup("uploader").await;
+        let journal_id = user.create_journal(&app, "Uploads").await;
+        let large = "x".repeat(RequestLimits::default().body + 1);
+
+        user.create_account(journal_id, &large)
+            .await
+            .assert_status(StatusCode::PAYLOAD_TOO_LARGE);
+
+        let response = user
+            .client
+            .post(&format!("/journal/{journal_id}/transaction/plaintext"))
+            .form(&[("text", large.as_str())])
+            .await;
+        assert_ne!(response.status_code(), StatusCode::PAYLOAD_TOO_LARGE);
+    }
+}
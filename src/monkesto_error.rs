@@ -2,15 +2,25 @@ use crate::authn::user::UserError;
 use crate::email::EmailError;
 use crate::id::IdentError;
 use crate::journal::JournalError;
+use crate::journal::quota::Quota;
 use crate::name::NameError;
+use crate::negotiate::wants_json;
 use crate::proto::error::ProtoMonkestoError;
 use crate::serde::error::ProtoError;
-use axum::response::Redirect;
+use crate::{notfoundpage, theme};
+use axum::Json;
+use axum::extract::Request;
+use axum::http::StatusCode;
+use axum::middleware::Next;
+use axum::response::{IntoResponse, Redirect, Response};
+use axum_login::tracing;
 use base64::Engine;
 use base64::engine::general_purpose;
 use disintegrate::DecisionError;
+use maud::{Markup, html};
 use prost::Message;
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
+use serde_json::{Value, json};
 use thiserror::Error;
 
 #[derive(Debug, Error, PartialEq)]
@@ -50,6 +60,16 @@ impl From<DecisionError<JournalError>> for MonkestoError {
     }
 }
 
+impl From<DecisionError<UserError>> for MonkestoError {
+    fn from(value: DecisionError<UserError>) -> Self {
+        match value {
+            DecisionError::EventStore(e) => Self::DisintegrateEvent(e.to_string()),
+            DecisionError::StateStore(e) => Self::DisintegrateState(e.to_string()),
+            DecisionError::Domain(e) => Self::User(e),
+        }
+    }
+}
+
 impl MonkestoError {
     pub fn redirect(self, page: &str) -> Redirect {
         let bytes = ProtoMonkestoError::from(self).encode_to_vec();
@@ -60,6 +80,240 @@ impl MonkestoError {
         ))
     }
 
+    pub fn status_code(&self) -> StatusCode {
+        match self {
+            Self::Proto(_) | Self::NameCreation(_) | Self::IdentCreation(_) => {
+                StatusCode::BAD_REQUEST
+            }
+            Self::EmailCreation(_) => StatusCode::BAD_REQUEST,
+            Self::Journal(e) => match e {
+                JournalError::IdCollision(_)
+                | JournalError::AccountIdCollision(_)
+                | JournalError::TransactionIdCollision(_)
+                | JournalError::WebhookIdCollision(_)
+                | JournalError::RuleIdCollision(_)
+                | JournalError::BankFeedIdCollision(_)
+                | JournalError::TemplateIdCollision(_)
+                | JournalError::CommentIdCollision(_)
+                | JournalError::UserAlreadyHasAccess(_) => StatusCode::CONFLICT,
+                JournalError::InvalidJournal(_)
+                | JournalError::InvalidAccount(_)
+                | JournalError::InvalidTransaction(_)
+                | JournalError::InvalidWebhook(_)
+                | JournalError::InvalidRule(_)
+                | JournalError::InvalidBankFeed(_)
+                | JournalError::InvalidTemplate(_)
+                | JournalError::InvalidComment(_)
+                | JournalError::UserDoesntHaveAccess(_) => StatusCode::NOT_FOUND,
+                JournalError::UndoExpired => StatusCode::GONE,
+                JournalError::OwnerCannotLeave(_) | JournalError::JournalArchived(_) => {
+                    StatusCode::CONFLICT
+                }
+                JournalError::Permissions(_) | JournalError::QuotaExceeded(_) => {
+                    StatusCode::FORBIDDEN
+                }
+                JournalError::TransactionValidation(_) | JournalError::InvalidBatch(_) => {
+                    StatusCode::UNPROCESSABLE_ENTITY
+                }
+                JournalError::IdentCreation(_)
+                | JournalError::InvalidWebhookUrl(_)
+                | JournalError::InvalidFiscalYearStart(_)
+                | JournalError::InvalidJournalColor(_)
+                | JournalError::InvalidJournalIcon(_)
+                | JournalError::InvalidRuleCondition(_)
+                | JournalError::UnknownBankFeedProvider(_)
+                | JournalError::TransferWithinJournal(_)
+                | JournalError::CommentLength(_) => StatusCode::BAD_REQUEST,
+                JournalError::BankFeedSync(_) => StatusCode::BAD_GATEWAY,
+                JournalError::Sqlx(_)
+                | JournalError::PermissionDecode(_)
+                | JournalError::EventDecode(_)
+                | JournalError::Secret(_) => StatusCode::INTERNAL_SERVER_ERROR,
+            },
+            Self::User(e) => match e {
+                UserError::EmailConflict(_) | UserError::IdCollision(_) => StatusCode::CONFLICT,
+                UserError::EmailDoesntExist(_) | UserError::UserDoesntExist(_) => {
+                    StatusCode::NOT_FOUND
+                }
+                UserError::SessionNotFound => StatusCode::UNAUTHORIZED,
+                UserError::TotpAlreadyEnabled(_) => StatusCode::CONFLICT,
+                UserError::TotpNotEnabled(_) => StatusCode::BAD_REQUEST,
+                UserError::InvalidTotpCode => StatusCode::UNAUTHORIZED,
+                UserError::InvalidDigestFrequency(_)
+                | UserError::InvalidTimezone(_)
+                | UserError::InvalidTheme(_)
+                | UserError::InvalidShortcut(_)
+                | UserError::DeletionNotConfirmed => StatusCode::BAD_REQUEST,
+                UserError::Sqlx(_)
+                | UserError::SeedFailure(_)
+                | UserError::PasskeyDecode(_)
+                | UserError::EventDecode(_)
+                | UserError::Secret(_) => StatusCode::INTERNAL_SERVER_ERROR,
+            },
+            Self::DisintegrateEvent(_) | Self::DisintegrateState(_) => {
+                StatusCode::INTERNAL_SERVER_ERROR
+            }
+        }
+    }
+
+    /// a stable identifier for the error that api clients can match on
+    pub fn code(&self) -> &'static str {
+        match self {
+            Self::Proto(_) => "error_decode",
+            Self::NameCreation(_) => "invalid_name",
+            Self::IdentCreation(_) => "invalid_id",
+            Self::EmailCreation(_) => "invalid_email",
+            Self::Journal(e) => match e {
+                JournalError::IdCollision(_) => "journal_id_collision",
+                JournalError::AccountIdCollision(_) => "account_id_collision",
+                JournalError::TransactionIdCollision(_) => "transaction_id_collision",
+                JournalError::WebhookIdCollision(_) => "webhook_id_collision",
+                JournalError::InvalidJournal(_) => "journal_not_found",
+                JournalError::InvalidAccount(_) => "account_not_found",
+                JournalError::InvalidTransaction(_) => "transaction_not_found",
+                JournalError::InvalidWebhook(_) => "webhook_not_found",
+                JournalError::InvalidWebhookUrl(_) => "invalid_webhook_url",
+                JournalError::TransactionValidation(_) => "invalid_transaction",
+                JournalError::InvalidBatch(_) => "invalid_batch",
+                JournalError::UndoExpired => "undo_expired",
+                JournalError::OwnerCannotLeave(_) => "owner_cannot_leave",
+                JournalError::InvalidFiscalYearStart(_) => "invalid_fiscal_year_start",
+                JournalError::InvalidJournalColor(_) => "invalid_journal_color",
+                JournalError::InvalidJournalIcon(_) => "invalid_journal_icon",
+                JournalError::RuleIdCollision(_) => "rule_id_collision",
+                JournalError::InvalidRule(_) => "rule_not_found",
+                JournalError::InvalidRuleCondition(_) => "invalid_rule_condition",
+                JournalError::BankFeedIdCollision(_) => "bank_feed_id_collision",
+                JournalError::InvalidBankFeed(_) => "bank_feed_not_found",
+                JournalError::UnknownBankFeedProvider(_) => "unknown_bank_feed_provider",
+                JournalError::BankFeedSync(_) => "bank_feed_sync_failed",
+                JournalError::TransferWithinJournal(_) => "transfer_within_journal",
+                JournalError::TemplateIdCollision(_) => "template_id_collision",
+                JournalError::InvalidTemplate(_) => "template_not_found",
+                JournalError::CommentIdCollision(_) => "comment_id_collision",
+                JournalError::InvalidComment(_) => "comment_not_found",
+                JournalError::CommentLength(_) => "invalid_comment_length",
+                JournalError::JournalArchived(_) => "journal_archived",
+                JournalError::Permissions(_) => "permission_denied",
+                JournalError::QuotaExceeded(_) => "quota_exceeded",
+                JournalError::UserAlreadyHasAccess(_) => "user_already_has_access",
+                JournalError::UserDoesntHaveAccess(_) => "user_doesnt_have_access",
+                JournalError::IdentCreation(_) => "invalid_id",
+                JournalError::Sqlx(_)
+                | JournalError::PermissionDecode(_)
+                | JournalError::EventDecode(_)
+                | JournalError::Secret(_) => "internal",
+            },
+            Self::User(e) => match e {
+                UserError::EmailConflict(_) => "email_conflict",
+                UserError::IdCollision(_) => "user_id_collision",
+                UserError::EmailDoesntExist(_) | UserError::UserDoesntExist(_) => "user_not_found",
+                UserError::SessionNotFound => "unauthenticated",
+                UserError::TotpAlreadyEnabled(_) => "totp_already_enabled",
+                UserError::TotpNotEnabled(_) => "totp_not_enabled",
+                UserError::InvalidTotpCode => "invalid_totp_code",
+                UserError::InvalidDigestFrequency(_) => "invalid_digest_frequency",
+                UserError::InvalidTimezone(_) => "invalid_timezone",
+                UserError::InvalidTheme(_) => "invalid_theme",
+                UserError::InvalidShortcut(_) => "invalid_shortcut",
+                UserError::DeletionNotConfirmed => "deletion_not_confirmed",
+                UserError::Sqlx(_)
+                | UserError::SeedFailure(_)
+                | UserError::PasskeyDecode(_)
+                | UserError::EventDecode(_)
+                | UserError::Secret(_) => "internal",
+            },
+            Self::DisintegrateEvent(_) | Self::DisintegrateState(_) => "internal",
+        }
+    }
+
+    /// structured context for the error, where there is any worth exposing
+    fn details(&self) -> Option<Value> {
+        match self {
+            Self::Journal(e) => match e {
+                JournalError::IdCollision(id)
+                | JournalError::InvalidJournal(id)
+                | JournalError::OwnerCannotLeave(id)
+                | JournalError::JournalArchived(id)
+                | JournalError::TransferWithinJournal(id) => {
+                    Some(json!({ "journal_id": id.to_string() }))
+                }
+                JournalError::AccountIdCollision(id) | JournalError::InvalidAccount(id) => {
+                    Some(json!({ "account_id": id.to_string() }))
+                }
+                JournalError::TransactionIdCollision(id) | JournalError::InvalidTransaction(id) => {
+                    Some(json!({ "transaction_id": id.to_string() }))
+                }
+                JournalError::WebhookIdCollision(id) | JournalError::InvalidWebhook(id) => {
+                    Some(json!({ "webhook_id": id.to_string() }))
+                }
+                JournalError::RuleIdCollision(id) | JournalError::InvalidRule(id) => {
+                    Some(json!({ "rule_id": id.to_string() }))
+                }
+                JournalError::BankFeedIdCollision(id) | JournalError::InvalidBankFeed(id) => {
+                    Some(json!({ "bank_feed_id": id.to_string() }))
+                }
+                JournalError::TemplateIdCollision(id) | JournalError::InvalidTemplate(id) => {
+                    Some(json!({ "template_id": id.to_string() }))
+                }
+                JournalError::CommentIdCollision(id) | JournalError::InvalidComment(id) => {
+                    Some(json!({ "comment_id": id.to_string() }))
+                }
+                JournalError::UserAlreadyHasAccess(id) | JournalError::UserDoesntHaveAccess(id) => {
+                    Some(json!({ "user_id": id.to_string() }))
+                }
+                JournalError::Permissions(required) => Some(json!({
+                    "required": required.iter_names().map(|(name, _)| name).collect::<Vec<_>>()
+                })),
+                JournalError::TransactionValidation(e) => Some(json!({ "reason": e.to_string() })),
+                JournalError::InvalidBatch(rows) => Some(json!({
+                    "rows": rows
+                        .iter()
+                        .map(|row| json!({ "row": row.row, "reason": row.error.to_string() }))
+                        .collect::<Vec<_>>()
+                })),
+                JournalError::QuotaExceeded(quota) => Some(match quota {
+                    Quota::Journals(limit) => json!({ "quota": "journals", "limit": limit }),
+                    Quota::TransactionsPerJournal(limit) => {
+                        json!({ "quota": "transactions_per_journal", "limit": limit })
+                    }
+                }),
+                _ => None,
+            },
+            Self::User(UserError::UserDoesntExist(id) | UserError::IdCollision(id)) => {
+                Some(json!({ "user_id": id.to_string() }))
+            }
+            _ => None,
+        }
+    }
+
+    /// what went wrong, worded for the person who made the request
+    pub fn message(&self) -> String {
+        // internal errors can leak database details, so only their code is exposed
+        if self.status_code().is_server_error() {
+            "an internal error occurred".to_string()
+        } else {
+            // the wrapping variant only says which store failed, which the code already covers
+            std::error::Error::source(self)
+                .map(ToString::to_string)
+                .unwrap_or_else(|| self.to_string())
+        }
+    }
+
+    fn api_error(&self) -> ApiError {
+        let status = self.status_code();
+        let message = self.message();
+
+        ApiError {
+            status,
+            body: ErrorBody {
+                code: self.code(),
+                message,
+                details: self.details(),
+            },
+        }
+    }
+
     pub fn decode(err: &str) -> Self {
         if let Some(Ok(proto_error)) = general_purpose::URL_SAFE_NO_PAD
             .decode(err)
@@ -73,6 +327,167 @@ impl MonkestoError {
     }
 }
 
+impl IntoResponse for MonkestoError {
+    fn into_response(self) -> Response {
+        if self.status_code().is_server_error() {
+            tracing::error!(error = %self, "request failed");
+        }
+
+        self.api_error().into_response()
+    }
+}
+
+/// the json body returned to api clients when a request fails
+#[derive(Clone, Debug, Serialize, PartialEq)]
+pub struct ErrorBody {
+    pub code: &'static str,
+    pub message: String,
+    pub details: Option<Value>,
+}
+
+#[derive(Clone, Debug)]
+struct ApiError {
+    status: StatusCode,
+    body: ErrorBody,
+}
+
+impl IntoResponse for ApiError {
+    fn into_response(self) -> Response {
+        (self.status, Json(self.body)).into_response()
+    }
+}
+
+/// an error raised by a form handler, along with the page that should display it.
+/// browsers are redirected back to the page, while api clients get the error as json
+#[derive(Debug)]
+pub struct ErrorRedirect {
+    error: MonkestoError,
+    page: String,
+}
+
+impl ErrorRedirect {
+    pub fn new(error: impl Into<MonkestoError>, page: &str) -> Self {
+        Self {
+            error: error.into(),
+            page: page.to_string(),
+        }
+    }
+}
+
+impl From<ErrorRedirect> for Redirect {
+    fn from(value: ErrorRedirect) -> Self {
+        value.error.redirect(&value.page)
+    }
+}
+
+impl IntoResponse for ErrorRedirect {
+    fn into_response(self) -> Response {
+        let api_error = self.error.api_error();
+        let mut response = Redirect::from(self).into_response();
+        response.extensions_mut().insert(api_error);
+        response
+    }
+}
+
+/// a form that failed validation, rendered again with the user's input and what was wrong with
+/// it. browsers get the page back, while api clients get the first error as json
+pub struct InvalidForm {
+    api_error: Option<ApiError>,
+    page: Markup,
+}
+
+impl InvalidForm {
+    pub fn new(error: Option<&MonkestoError>, page: Markup) -> Self {
+        Self {
+            api_error: error.map(MonkestoError::api_error),
+            page,
+        }
+    }
+}
+
+impl IntoResponse for InvalidForm {
+    fn into_response(self) -> Response {
+        let mut response = (StatusCode::UNPROCESSABLE_ENTITY, self.page).into_response();
+        if let Some(api_error) = self.api_error {
+            response.extensions_mut().insert(api_error);
+        }
+        response
+    }
+}
+
+/// an error that stops a page from being shown at all. browsers get a page explaining it, while
+/// api clients get the error as json
+#[derive(Debug)]
+pub struct ErrorPage(MonkestoError);
+
+impl ErrorPage {
+    pub fn new(error: impl Into<MonkestoError>) -> Self {
+        Self(error.into())
+    }
+}
+
+impl IntoResponse for ErrorPage {
+    fn into_response(self) -> Response {
+        let status = self.0.status_code();
+        if status.is_server_error() {
+            tracing::error!(error = %self.0, "request failed");
+        }
+
+        // hidden and missing things look the same, so nothing can be learned from which it was
+        let page = if status == StatusCode::NOT_FOUND {
+            notfoundpage::not_found()
+        } else {
+            theme::theme(html! {
+                p { (self.0.message()) }
+            })
+        };
+
+        let mut response = (status, page).into_response();
+        response.extensions_mut().insert(self.0.api_error());
+        response
+    }
+}
+
+/// why a form handler turned a submission away
+pub enum FormRejection {
+    Redirect(ErrorRedirect),
+    Invalid(InvalidForm),
+}
+
+impl From<ErrorRedirect> for FormRejection {
+    fn from(value: ErrorRedirect) -> Self {
+        Self::Redirect(value)
+    }
+}
+
+impl From<InvalidForm> for FormRejection {
+    fn from(value: InvalidForm) -> Self {
+        Self::Invalid(value)
+    }
+}
+
+impl IntoResponse for FormRejection {
+    fn into_response(self) -> Response {
+        match self {
+            Self::Redirect(redirect) => redirect.into_response(),
+            Self::Invalid(invalid) => invalid.into_response(),
+        }
+    }
+}
+
+/// swaps error redirects for json error bodies when the client asked for json
+pub async fn json_errors(request: Request, next: Next) -> Response {
+    let wants_json = wants_json(request.headers());
+
+    let mut response = next.run(request).await;
+
+    if wants_json && let Some(api_error) = response.extensions_mut().remove::<ApiError>() {
+        return api_error.into_response();
+    }
+
+    response
+}
+
 #[derive(Deserialize)]
 pub struct UrlError {
     pub err: Option<String>,
@@ -83,11 +498,73 @@ pub struct UrlError {
 pub type MonkestoResult<T> = Result<T, MonkestoError>;
 
 pub trait OrRedirect<T> {
-    fn or_redirect(self, redirect_url: &str) -> Result<T, Redirect>;
+    fn or_redirect(self, redirect_url: &str) -> Result<T, ErrorRedirect>;
 }
 
 impl<T, E: Into<MonkestoError>> OrRedirect<T> for Result<T, E> {
-    fn or_redirect(self, redirect_url: &str) -> Result<T, Redirect> {
-        self.map_err(|e| e.into().redirect(redirect_url))
+    fn or_redirect(self, redirect_url: &str) -> Result<T, ErrorRedirect> {
+        self.map_err(|e| ErrorRedirect::new(e, redirect_url))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::journal::Permissions;
+    use axum::Router;
+    use axum::http::header::ACCEPT;
+    use axum::middleware;
+    use axum::routing::post;
+    use axum_test::TestServer;
+
+    async fn forbidden() -> Result<Redirect, ErrorRedirect> {
+        Err(JournalError::Permissions(Permissions::OWNER)).or_redirect("/journal")
+    }
+
+    fn server() -> TestServer {
+        TestServer::new(
+            Router::new()
+                .route("/", post(forbidden))
+                .layer(middleware::from_fn(json_errors)),
+        )
+    }
+
+    #[tokio::test]
+    async fn api_clients_receive_json_errors() {
+        let response = server()
+            .post("/")
+            .add_header(ACCEPT, "application/json")
+            .expect_failure()
+            .await;
+
+        response.assert_status(StatusCode::FORBIDDEN);
+        response.assert_json(&json!({
+            "code": "permission_denied",
+            "message": "The user doesn't have the Permissions(OWNER) permission",
+            "details": { "required": ["OWNER"] },
+        }));
+    }
+
+    #[tokio::test]
+    async fn browsers_are_redirected_with_the_error() {
+        let response = server().post("/").await;
+
+        response.assert_status(StatusCode::SEE_OTHER);
+        assert!(
+            response
+                .header("location")
+                .to_str()
+                .unwrap()
+                .starts_with("/journal?err=")
+        );
+    }
+
+    #[test]
+    fn internal_errors_hide_their_message() {
+        let error = MonkestoError::Journal(JournalError::Sqlx("connection refused".to_string()));
+        let api_error = error.api_error();
+
+        assert_eq!(api_error.status, StatusCode::INTERNAL_SERVER_ERROR);
+        assert_eq!(api_error.body.message, "an internal error occurred");
     }
 }
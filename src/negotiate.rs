@@ -0,0 +1,42 @@
+use axum::extract::FromRequestParts;
+use axum::http::HeaderMap;
+use axum::http::header::ACCEPT;
+use axum::http::request::Parts;
+use std::convert::Infallible;
+
+/// whether the client asked for json rather than a page
+pub fn wants_json(headers: &HeaderMap) -> bool {
+    headers
+        .get(ACCEPT)
+        .and_then(|accept| accept.to_str().ok())
+        .is_some_and(|accept| accept.contains("application/json"))
+}
+
+/// lets a page answer with the data it shows as json, for scripts that send
+/// `Accept: application/json`
+pub struct WantsJson(pub bool);
+
+impl<S: Send + Sync> FromRequestParts<S> for WantsJson {
+    type Rejection = Infallible;
+
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+        Ok(Self(wants_json(&parts.headers)))
+    }
+}
+
+/// whether the client only wants the part of the page that changed, as htmx asks with
+/// `HX-Request` and the page scripts ask with `X-Partial`
+pub fn wants_partial(headers: &HeaderMap) -> bool {
+    headers.contains_key("hx-request") || headers.contains_key("x-partial")
+}
+
+/// lets a command answer with the fragment it updated instead of redirecting to the whole page
+pub struct WantsPartial(pub bool);
+
+impl<S: Send + Sync> FromRequestParts<S> for WantsPartial {
+    type Rejection = Infallible;
+
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+        Ok(Self(wants_partial(&parts.headers)))
+    }
+}
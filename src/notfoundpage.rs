@@ -4,6 +4,11 @@ use maud::html;
 use crate::theme;
 
 pub async fn not_found_page() -> Markup {
+    not_found()
+}
+
+/// shared with anything hidden from the user, so it can't be told apart from a missing page
+pub fn not_found() -> Markup {
     theme::theme(html! {
         p {
             "Page not found"
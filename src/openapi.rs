@@ -0,0 +1,461 @@
+use crate::journal::layout::layout;
+use axum::Json;
+use axum::Router;
+use axum::routing::get;
+use maud::Markup;
+use maud::html;
+use serde_json::{Value, json};
+
+pub fn router() -> Router<crate::StateType> {
+    Router::new()
+        .route("/openapi.json", get(openapi_json))
+        .route("/api", get(explorer_page))
+}
+
+/// describes the endpoints that speak json. they're the same routes the pages use, so they
+/// authenticate with the session cookie and only answer with json when the client asks for it
+pub fn document() -> Value {
+    json!({
+        "openapi": "3.1.0",
+        "info": {
+            "title": "Monkesto",
+            "version": env!("CARGO_PKG_VERSION"),
+            "description": "Send `Accept: application/json` to receive errors as an Error body instead of a redirect, and the data behind a page instead of the page. Journals the user can't read answer with a 404 `journal_not_found`, the same as journals that don't exist, while a readable journal answers a missing permission with a 403 `permission_denied`.",
+        },
+        "security": [{ "session": [] }],
+        "paths": {
+            "/journal/{id}": {
+                "get": {
+                    "summary": "Read a journal and its accounts",
+                    "description": "The journal page, as json.",
+                    "parameters": [{
+                        "name": "id",
+                        "in": "path",
+                        "required": true,
+                        "description": "The journal's id",
+                        "schema": { "type": "string" },
+                    }],
+                    "responses": {
+                        "200": {
+                            "description": "The journal",
+                            "content": {
+                                "application/json": {
+                                    "schema": { "$ref": "#/components/schemas/Journal" },
+                                },
+                            },
+                        },
+                        "default": { "$ref": "#/components/responses/Error" },
+                    },
+                },
+            },
+            "/journal/{id}/transaction": {
+                "get": {
+                    "summary": "List a journal's transactions",
+                    "description": "The transaction page, as json. Ordered by effective date, then by the order they were entered.",
+                    "parameters": [{
+                        "name": "id",
+                        "in": "path",
+                        "required": true,
+                        "description": "The journal's id",
+                        "schema": { "type": "string" },
+                    }],
+                    "responses": {
+                        "200": {
+                            "description": "The transactions",
+                            "content": {
+                                "application/json": {
+                                    "schema": {
+                                        "type": "array",
+                                        "items": { "$ref": "#/components/schemas/Transaction" },
+                                    },
+                                },
+                            },
+                        },
+                        "default": { "$ref": "#/components/responses/Error" },
+                    },
+                },
+            },
+            "/journal/{id}/account/search": {
+                "get": {
+                    "summary": "Find accounts by name",
+                    "description": "The accounts whose names match the query, best match first, at most 20 of them. Without `Accept: application/json` the matches come back as the `li` options of the transaction form's account picker.",
+                    "parameters": [
+                        {
+                            "name": "id",
+                            "in": "path",
+                            "required": true,
+                            "description": "The journal's id",
+                            "schema": { "type": "string" },
+                        },
+                        {
+                            "name": "q",
+                            "in": "query",
+                            "required": false,
+                            "description": "Words or part of a name to match. Every account matches an empty query",
+                            "schema": { "type": "string" },
+                        },
+                    ],
+                    "responses": {
+                        "200": {
+                            "description": "The matching accounts",
+                            "content": {
+                                "application/json": {
+                                    "schema": {
+                                        "type": "array",
+                                        "items": { "$ref": "#/components/schemas/Account" },
+                                    },
+                                },
+                            },
+                        },
+                        "default": { "$ref": "#/components/responses/Error" },
+                    },
+                },
+            },
+            "/journal/{id}/transaction/bulk": {
+                "post": {
+                    "summary": "Record several transactions at once",
+                    "description": "Either every transaction is recorded or none are. Invalid rows are listed in the error's details.",
+                    "parameters": [{
+                        "name": "id",
+                        "in": "path",
+                        "required": true,
+                        "description": "The journal's id",
+                        "schema": { "type": "string" },
+                    }],
+                    "requestBody": {
+                        "required": true,
+                        "content": {
+                            "application/json": {
+                                "schema": {
+                                    "type": "array",
+                                    "items": { "$ref": "#/components/schemas/BatchTransaction" },
+                                },
+                            },
+                        },
+                    },
+                    "responses": {
+                        "201": {
+                            "description": "The transactions were recorded",
+                            "content": {
+                                "application/json": {
+                                    "schema": { "$ref": "#/components/schemas/CreatedTransactions" },
+                                },
+                            },
+                        },
+                        "default": { "$ref": "#/components/responses/Error" },
+                    },
+                },
+            },
+            "/api/v1/journal/{id}/events": {
+                "get": {
+                    "summary": "Read a journal's events after a cursor",
+                    "description": "The journal's history in the order it was recorded, a page at a time. Start without `after`, then pass each page's `next` to get the events that followed. A page only ends where every earlier event has been recorded, so following `next` never skips one. Only owners are sent the journal's webhook, bank feed and pin events, so a page can hold fewer events than `limit` while `more` is still true. Each user can ask for 60 pages a minute; past that the feed answers with a 429 `rate_limited` and a Retry-After header.",
+                    "parameters": [
+                        {
+                            "name": "id",
+                            "in": "path",
+                            "required": true,
+                            "description": "The journal's id",
+                            "schema": { "type": "string" },
+                        },
+                        {
+                            "name": "after",
+                            "in": "query",
+                            "required": false,
+                            "description": "The id of the last event already read",
+                            "schema": { "type": "integer", "minimum": 0 },
+                        },
+                        {
+                            "name": "limit",
+                            "in": "query",
+                            "required": false,
+                            "description": "How many events to return, 100 by default",
+                            "schema": { "type": "integer", "minimum": 1, "maximum": 500 },
+                        },
+                    ],
+                    "responses": {
+                        "200": {
+                            "description": "The next page of events",
+                            "content": {
+                                "application/json": {
+                                    "schema": { "$ref": "#/components/schemas/EventPage" },
+                                },
+                            },
+                        },
+                        "default": { "$ref": "#/components/responses/Error" },
+                    },
+                },
+            },
+            "/notifications/unread": {
+                "get": {
+                    "summary": "Count the user's unread notifications",
+                    "responses": {
+                        "200": {
+                            "description": "The number of unread notifications",
+                            "content": {
+                                "application/json": {
+                                    "schema": { "$ref": "#/components/schemas/UnreadCount" },
+                                },
+                            },
+                        },
+                        "default": { "$ref": "#/components/responses/Error" },
+                    },
+                },
+            },
+        },
+        "components": {
+            "securitySchemes": {
+                "session": {
+                    "type": "apiKey",
+                    "in": "cookie",
+                    "name": "monkesto.sid",
+                    "description": "The session cookie set when signing in. SESSION_COOKIE_NAME can rename it.",
+                },
+            },
+            "responses": {
+                "Error": {
+                    "description": "The request failed",
+                    "content": {
+                        "application/json": {
+                            "schema": { "$ref": "#/components/schemas/Error" },
+                        },
+                    },
+                },
+            },
+            "schemas": {
+                "Journal": {
+                    "type": "object",
+                    "required": ["id", "name", "fiscal_year_start", "archived", "color", "accounts"],
+                    "properties": {
+                        "id": { "type": "string" },
+                        "name": { "type": "string" },
+                        "fiscal_year_start": {
+                            "type": "integer",
+                            "minimum": 1,
+                            "maximum": 12,
+                            "description": "The month the fiscal year begins in",
+                        },
+                        "archived": { "type": "boolean" },
+                        "color": { "type": "string", "example": "blue" },
+                        "icon": { "type": ["string", "null"] },
+                        "accounts": {
+                            "type": "array",
+                            "items": { "$ref": "#/components/schemas/Account" },
+                        },
+                    },
+                },
+                "Account": {
+                    "type": "object",
+                    "required": ["id", "name", "balance"],
+                    "properties": {
+                        "id": { "type": "string" },
+                        "name": { "type": "string" },
+                        "balance": { "type": "integer", "description": "In cents, where credits are positive" },
+                        "group": { "type": ["string", "null"], "description": "The heading the account is listed under" },
+                    },
+                },
+                "Transaction": {
+                    "type": "object",
+                    "required": ["id", "number", "effective_date", "entries"],
+                    "properties": {
+                        "id": { "type": "string" },
+                        "number": { "type": "string" },
+                        "effective_date": { "type": "string", "format": "date" },
+                        "reverses": { "type": ["string", "null"] },
+                        "payee": { "type": ["string", "null"] },
+                        "entries": {
+                            "type": "array",
+                            "items": { "$ref": "#/components/schemas/Entry" },
+                        },
+                    },
+                },
+                "Entry": {
+                    "type": "object",
+                    "required": ["account_id", "amount", "entry_type"],
+                    "properties": {
+                        "account_id": { "type": "string" },
+                        "amount": { "type": "integer", "minimum": 0, "description": "In cents" },
+                        "entry_type": { "type": "string", "enum": ["Dr", "Cr"] },
+                        "memo": { "type": ["string", "null"] },
+                    },
+                },
+                "BatchTransaction": {
+                    "type": "object",
+                    "required": ["effective_date", "entries"],
+                    "properties": {
+                        "effective_date": { "type": "string", "format": "date", "example": "2026-01-31" },
+                        "entries": {
+                            "type": "array",
+                            "items": { "$ref": "#/components/schemas/BatchEntry" },
+                        },
+                    },
+                },
+                "BatchEntry": {
+                    "type": "object",
+                    "required": ["account_id", "amount", "entry_type"],
+                    "properties": {
+                        "account_id": { "type": "string" },
+                        "amount": {
+                            "type": "string",
+                            "description": "A positive amount in dollars, with at most two decimal places",
+                            "example": "12.50",
+                        },
+                        "entry_type": { "type": "string", "enum": ["Dr", "Cr"] },
+                    },
+                },
+                "CreatedTransactions": {
+                    "type": "object",
+                    "required": ["transaction_ids"],
+                    "properties": {
+                        "transaction_ids": { "type": "array", "items": { "type": "string" } },
+                    },
+                },
+                "EventPage": {
+                    "type": "object",
+                    "required": ["events", "next", "more"],
+                    "properties": {
+                        "events": {
+                            "type": "array",
+                            "items": {
+                                "type": "object",
+                                "required": ["id", "event"],
+                                "properties": {
+                                    "id": { "type": "integer" },
+                                    "event": { "type": "object", "description": "The event, keyed by its kind" },
+                                },
+                            },
+                        },
+                        "next": { "type": "integer", "description": "The `after` for the next page" },
+                        "more": { "type": "boolean", "description": "Whether the next page already has events" },
+                    },
+                },
+                "UnreadCount": {
+                    "type": "object",
+                    "required": ["unread"],
+                    "properties": {
+                        "unread": { "type": "integer", "minimum": 0 },
+                    },
+                },
+                "Error": {
+                    "type": "object",
+                    "required": ["code", "message"],
+                    "properties": {
+                        "code": {
+                            "type": "string",
+                            "description": "A stable identifier to match on, such as invalid_batch or permission_denied",
+                        },
+                        "message": { "type": "string" },
+                        "details": {
+                            "type": ["object", "null"],
+                            "description": "Context that depends on the code, such as the invalid rows of a batch",
+                        },
+                    },
+                },
+            },
+        },
+    })
+}
+
+pub async fn openapi_json() -> Json<Value> {
+    Json(document())
+}
+
+fn schema_name(schema: &Value) -> Option<&str> {
+    schema
+        .get("$ref")
+        .or_else(|| schema.get("items").and_then(|items| items.get("$ref")))
+        .and_then(Value::as_str)
+        .and_then(|reference| reference.rsplit('/').next())
+}
+
+/// lists each endpoint and the shapes of its payloads, read from the same document
+pub async fn explorer_page() -> Markup {
+    let document = document();
+    let empty = serde_json::Map::new();
+    let paths = document["paths"].as_object().unwrap_or(&empty);
+    let schemas = document["components"]["schemas"]
+        .as_object()
+        .unwrap_or(&empty);
+
+    let content = html! {
+        div class="flex flex-col gap-6 mx-auto w-full max-w-4xl" {
+            p class="text-sm text-gray-600 dark:text-gray-400" {
+                (document["info"]["description"].as_str().unwrap_or_default()) " "
+                "The full description is at "
+                a href="/openapi.json" class="font-medium text-indigo-600 hover:text-indigo-500 dark:text-indigo-400 dark:hover:text-indigo-300" { "/openapi.json" }
+                "."
+            }
+
+            @for (path, operations) in paths {
+                @for (method, operation) in operations.as_object().unwrap_or(&empty) {
+                    div class="p-4 bg-white dark:bg-gray-800 border border-gray-200 dark:border-gray-700 rounded-xl space-y-2" {
+                        div class="flex items-center gap-3" {
+                            span class="px-2 py-0.5 text-xs font-semibold uppercase rounded bg-indigo-100 text-indigo-700 dark:bg-indigo-900 dark:text-indigo-300" { (method) }
+                            code class="text-sm text-gray-900 dark:text-white" { (path) }
+                        }
+                        p class="text-sm text-gray-700 dark:text-gray-300" { (operation["summary"].as_str().unwrap_or_default()) }
+                        @if let Some(description) = operation["description"].as_str() {
+                            p class="text-xs text-gray-500 dark:text-gray-400" { (description) }
+                        }
+                        @if let Some(name) = schema_name(&operation["requestBody"]["content"]["application/json"]["schema"]) {
+                            p class="text-xs text-gray-500 dark:text-gray-400" { "Request: " code { (name) } }
+                        }
+                        @for (status, response) in operation["responses"].as_object().unwrap_or(&empty) {
+                            @if let Some(name) = schema_name(&response["content"]["application/json"]["schema"]) {
+                                p class="text-xs text-gray-500 dark:text-gray-400" { "Response " (status) ": " code { (name) } }
+                            } @else if status == "default" {
+                                p class="text-xs text-gray-500 dark:text-gray-400" { "Errors: " code { "Error" } }
+                            }
+                        }
+                    }
+                }
+            }
+
+            h3 class="text-lg font-semibold text-gray-900 dark:text-white" { "Schemas" }
+            @for (name, schema) in schemas {
+                div class="p-4 bg-gray-50 dark:bg-gray-800 border border-gray-200 dark:border-gray-700 rounded-xl" {
+                    h4 class="text-sm font-semibold text-gray-900 dark:text-white mb-2" { (name) }
+                    pre class="text-xs text-gray-700 dark:text-gray-300 overflow-x-auto" {
+                        (serde_json::to_string_pretty(schema).unwrap_or_default())
+                    }
+                }
+            }
+        }
+    };
+
+    layout(Some("API"), false, None, content)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn references<'a>(value: &'a Value, found: &mut Vec<&'a str>) {
+        match value {
+            Value::Object(object) => {
+                if let Some(reference) = object.get("$ref").and_then(Value::as_str) {
+                    found.push(reference);
+                }
+                object.values().for_each(|value| references(value, found));
+            }
+            Value::Array(array) => array.iter().for_each(|value| references(value, found)),
+            _ => {}
+        }
+    }
+
+    #[test]
+    fn every_reference_resolves() {
+        let document = document();
+        let mut found = Vec::new();
+        references(&document, &mut found);
+
+        assert!(!found.is_empty());
+        for reference in found {
+            let pointer = reference.trim_start_matches('#');
+            assert!(
+                document.pointer(pointer).is_some(),
+                "{reference} doesn't resolve"
+            );
+        }
+    }
+}
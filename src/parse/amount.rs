@@ -0,0 +1,117 @@
+use crate::journal::JournalError;
+use crate::journal::transaction::{TransactionValidationError, parse_amount};
+use rust_decimal::Decimal;
+use std::str::FromStr;
+
+fn invalid(s: &str) -> JournalError {
+    JournalError::TransactionValidation(TransactionValidationError::ParseDecimal(s.to_string()))
+}
+
+/// commas are only allowed between groups of three digits, so `1,2` isn't read as 12
+fn without_separators(s: &str) -> Option<String> {
+    let (whole, fraction) = s.split_once('.').map_or((s, None), |(w, f)| (w, Some(f)));
+
+    let mut groups = whole.split(',');
+    let first = groups.next()?;
+    let rest: Vec<&str> = groups.collect();
+
+    if !rest.is_empty()
+        && (first.is_empty() || first.len() > 3 || rest.iter().any(|g| g.len() != 3))
+    {
+        return None;
+    }
+
+    let mut digits = first.to_string();
+    rest.iter().for_each(|group| digits.push_str(group));
+    if let Some(fraction) = fraction {
+        digits.push('.');
+        digits.push_str(fraction);
+    }
+
+    Some(digits)
+}
+
+/// parses a positive dollar amount into cents, like `parse_amount`, but also accepts a
+/// leading `$`, thousands separators, and a `k` or `m` suffix for thousands and millions
+pub fn amount(s: &str) -> Result<u64, JournalError> {
+    let trimmed = s.trim();
+    let trimmed = trimmed.strip_prefix('$').unwrap_or(trimmed);
+
+    let (number, multiplier) = match trimmed.char_indices().last() {
+        Some((i, 'k' | 'K')) => (&trimmed[..i], Decimal::ONE_THOUSAND),
+        Some((i, 'm' | 'M')) => (&trimmed[..i], Decimal::from(1_000_000)),
+        _ => (trimmed, Decimal::ONE),
+    };
+
+    let number = without_separators(number).ok_or_else(|| invalid(s))?;
+
+    // the characters are checked first, since Decimal also accepts exponents and underscores
+    if number.is_empty()
+        || !number
+            .chars()
+            .all(|c| c.is_ascii_digit() || c == '.' || c == '-')
+    {
+        return Err(invalid(s));
+    }
+
+    let value = Decimal::from_str(&number)
+        .map_err(|_| invalid(s))?
+        .checked_mul(multiplier)
+        .ok_or_else(|| {
+            JournalError::TransactionValidation(TransactionValidationError::OutOfRange(
+                s.to_string(),
+            ))
+        })?;
+
+    parse_amount(&value.normalize().to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn amounts_accept_symbols_separators_and_suffixes() {
+        let cases = [
+            ("4.25", 425),
+            ("$4.25", 425),
+            ("12", 1200),
+            ("1,200", 120000),
+            ("$1,234,567.89", 123456789),
+            ("1.2k", 120000),
+            ("1.5K", 150000),
+            ("2m", 200000000),
+            ("0.01", 1),
+            (" 7 ", 700),
+        ];
+
+        for (input, cents) in cases {
+            assert_eq!(amount(input), Ok(cents), "{input}");
+        }
+    }
+
+    #[test]
+    fn malformed_amounts_are_rejected() {
+        for input in [
+            "",
+            "$",
+            "k",
+            "1,2",
+            ",100",
+            "1234,567",
+            "1,2345",
+            "1.2.3",
+            "1e3",
+            "1_000",
+            "abc",
+            "4.255",
+            "1.234567k",
+            "-5",
+            "0",
+            "0k",
+            "$-1",
+        ] {
+            assert!(amount(input).is_err(), "{input}");
+        }
+    }
+}
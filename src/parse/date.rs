@@ -0,0 +1,128 @@
+use crate::journal::JournalError;
+use crate::journal::transaction::{TransactionValidationError, parse_effective_date};
+use chrono::{Datelike, Days, Month, NaiveDate};
+
+fn month(s: &str) -> Option<u32> {
+    let s = s.to_lowercase();
+    let s = s.trim_end_matches('.');
+
+    // three letters are enough to tell the months apart
+    if s.len() < 3 {
+        return None;
+    }
+
+    (1..=12u32).find(|&number| {
+        Month::try_from(number as u8).is_ok_and(|month| month.name().to_lowercase().starts_with(s))
+    })
+}
+
+fn day(s: &str) -> Option<u32> {
+    let s = s.to_lowercase();
+    let digits = ["st", "nd", "rd", "th"]
+        .iter()
+        .find_map(|suffix| s.strip_suffix(suffix))
+        .unwrap_or(&s);
+
+    digits.parse().ok()
+}
+
+/// a month and day without a year means the latest one that isn't after today, since
+/// transactions are almost always entered after they happen
+fn month_day(month: u32, day: u32, today: NaiveDate) -> Option<NaiveDate> {
+    let this_year = NaiveDate::from_ymd_opt(today.year(), month, day);
+
+    match this_year {
+        Some(date) if date <= today => Some(date),
+        // february 29th is only in some years, so look back until there is one
+        _ => (1..=8).find_map(|years| NaiveDate::from_ymd_opt(today.year() - years, month, day)),
+    }
+}
+
+/// parses a date as YYYY-MM-DD, a word relative to today like `yesterday`, or a month and
+/// day in either order like `mar 3` or `3 March`
+pub fn date(s: &str, today: NaiveDate) -> Result<NaiveDate, JournalError> {
+    let invalid = || {
+        JournalError::TransactionValidation(TransactionValidationError::InvalidEffectiveDate(
+            s.to_string(),
+        ))
+    };
+
+    let words: Vec<&str> = s
+        .split(|c: char| c.is_whitespace() || c == ',')
+        .filter(|word| !word.is_empty())
+        .collect();
+
+    match words.as_slice() {
+        [word] => match word.to_lowercase().as_str() {
+            "today" => Ok(today),
+            "yesterday" => today.checked_sub_days(Days::new(1)).ok_or_else(invalid),
+            "tomorrow" => today.checked_add_days(Days::new(1)).ok_or_else(invalid),
+            _ => parse_effective_date(word),
+        },
+        [first, second] => {
+            let (month, day) = match (month(first), month(second)) {
+                (Some(month), None) => (month, day(second)),
+                (None, Some(month)) => (month, day(first)),
+                _ => return Err(invalid()),
+            };
+
+            day.and_then(|day| month_day(month, day, today))
+                .ok_or_else(invalid)
+        }
+        _ => Err(invalid()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ymd(year: i32, month: u32, day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(year, month, day).expect("invalid date")
+    }
+
+    #[test]
+    fn dates_can_be_relative_to_today() {
+        let today = ymd(2026, 3, 1);
+
+        let cases = [
+            ("today", today),
+            ("Yesterday", ymd(2026, 2, 28)),
+            ("tomorrow", ymd(2026, 3, 2)),
+            ("2025-12-31", ymd(2025, 12, 31)),
+            ("mar 1", today),
+            ("Feb 3", ymd(2026, 2, 3)),
+            ("3 february", ymd(2026, 2, 3)),
+            ("sept. 30th", ymd(2025, 9, 30)),
+            ("dec 25", ymd(2025, 12, 25)),
+            ("mar 2", ymd(2025, 3, 2)),
+            ("feb 29", ymd(2024, 2, 29)),
+            ("Jan 2, ", ymd(2026, 1, 2)),
+        ];
+
+        for (input, expected) in cases {
+            assert_eq!(date(input, today), Ok(expected), "{input}");
+        }
+    }
+
+    #[test]
+    fn malformed_dates_are_rejected() {
+        let today = ymd(2026, 3, 1);
+
+        for input in [
+            "",
+            "someday",
+            "mar",
+            "ma 3",
+            "mar 32",
+            "feb 30",
+            "3 4",
+            "mar mar",
+            "mar 3 2026",
+            "2026-13-01",
+            "03/01/2026",
+        ] {
+            assert!(date(input, today).is_err(), "{input}");
+        }
+    }
+}
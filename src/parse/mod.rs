@@ -0,0 +1,4 @@
+//! lenient parsing of what people type, shared by quick entry and the importers
+
+pub mod amount;
+pub mod date;
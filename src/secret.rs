@@ -0,0 +1,240 @@
+use crate::app_env::AppEnv;
+use axum_login::tracing;
+use chacha20poly1305::aead::{Aead, AeadCore, KeyInit, OsRng};
+use chacha20poly1305::{ChaCha20Poly1305, Key, Nonce};
+use serde::Serialize;
+use serde::de::DeserializeOwned;
+use sqlx::PgPool;
+use std::env;
+use std::marker::PhantomData;
+use thiserror::Error;
+
+const NONCE_LEN: usize = 12;
+
+/// only used when SECRET_KEY isn't set in development, so secrets survive restarts there
+const DEVELOPMENT_KEY: [u8; 32] = *b"monkesto development secret key!";
+
+#[derive(Debug, Error, PartialEq)]
+pub enum SecretKeyError {
+    #[error("SECRET_KEY must be 64 hex characters")]
+    InvalidKey,
+    #[error("SECRET_KEY must be set in production")]
+    Missing,
+}
+
+#[derive(Debug, Error, PartialEq)]
+#[error("failed to decrypt a secret, it may have been encrypted with a different key")]
+pub struct DecryptError;
+
+/// encrypts credentials before they're written to the database, read from `SECRET_KEY`.
+/// each value gets a random nonce, which is stored in front of its ciphertext
+#[derive(Clone)]
+pub struct SecretKey(Key);
+
+impl SecretKey {
+    /// falls back to a fixed key in development, which must never protect real credentials
+    pub fn from_env(app_env: AppEnv) -> Result<Self, SecretKeyError> {
+        match env::var("SECRET_KEY") {
+            Ok(value) => Self::from_hex(&value),
+            Err(_) if app_env.dev_mode() => {
+                tracing::warn!(
+                    "SECRET_KEY isn't set, so secrets are encrypted with the development key"
+                );
+                Ok(Self(DEVELOPMENT_KEY.into()))
+            }
+            Err(_) => Err(SecretKeyError::Missing),
+        }
+    }
+
+    pub fn from_hex(value: &str) -> Result<Self, SecretKeyError> {
+        let bytes: [u8; 32] = hex::decode(value.trim())
+            .ok()
+            .and_then(|bytes| bytes.try_into().ok())
+            .ok_or(SecretKeyError::InvalidKey)?;
+
+        Ok(Self(bytes.into()))
+    }
+
+    /// a fresh random key, for encrypting data under its own key rather than this one
+    pub fn generate() -> Self {
+        Self(ChaCha20Poly1305::generate_key(&mut OsRng))
+    }
+
+    /// encrypts another key with this one, so it can be stored beside what it encrypted
+    pub fn wrap(&self, key: &SecretKey) -> Vec<u8> {
+        self.encrypt(key.0.as_slice())
+    }
+
+    pub fn unwrap(&self, wrapped: &[u8]) -> Result<SecretKey, DecryptError> {
+        let bytes: [u8; 32] = self
+            .decrypt(wrapped)?
+            .try_into()
+            .map_err(|_| DecryptError)?;
+
+        Ok(Self(bytes.into()))
+    }
+
+    pub fn encrypt(&self, plaintext: &[u8]) -> Vec<u8> {
+        let nonce = ChaCha20Poly1305::generate_nonce(&mut OsRng);
+        let ciphertext = ChaCha20Poly1305::new(&self.0)
+            .encrypt(&nonce, plaintext)
+            .expect("encrypting in memory can't fail");
+
+        [nonce.as_slice(), &ciphertext].concat()
+    }
+
+    pub fn decrypt(&self, value: &[u8]) -> Result<Vec<u8>, DecryptError> {
+        if value.len() < NONCE_LEN {
+            return Err(DecryptError);
+        }
+        let (nonce, ciphertext) = value.split_at(NONCE_LEN);
+
+        ChaCha20Poly1305::new(&self.0)
+            .decrypt(Nonce::from_slice(nonce), ciphertext)
+            .map_err(|_| DecryptError)
+    }
+}
+
+#[derive(Debug, Error)]
+pub enum SecretError {
+    #[error("sqlx returned an error: {0}")]
+    Sqlx(#[from] sqlx::Error),
+    #[error(transparent)]
+    Decrypt(#[from] DecryptError),
+    #[error("failed to encode a secret: {0}")]
+    Encode(#[from] serde_json::Error),
+}
+
+/// names a stored secret along with the type of value it holds
+pub struct Secret<T> {
+    name: String,
+    value: PhantomData<fn() -> T>,
+}
+
+impl<T> Secret<T> {
+    pub fn new(name: String) -> Self {
+        Self {
+            name,
+            value: PhantomData,
+        }
+    }
+}
+
+/// keeps the credentials that integrations need server-side, encrypted with the secret key.
+/// values are stored as encrypted json, so anything serializable can be kept
+#[derive(Clone)]
+pub struct SecretStore {
+    pool: PgPool,
+    key: SecretKey,
+}
+
+impl SecretStore {
+    pub async fn try_new(pool: PgPool, key: SecretKey) -> Result<Self, sqlx::Error> {
+        sqlx::query!(
+            r#"
+            CREATE TABLE IF NOT EXISTS secrets (
+                name TEXT PRIMARY KEY,
+                value BYTEA NOT NULL
+            )
+        "#
+        )
+        .execute(&pool)
+        .await?;
+
+        Ok(Self { pool, key })
+    }
+
+    pub async fn get<T: DeserializeOwned>(
+        &self,
+        secret: &Secret<T>,
+    ) -> Result<Option<T>, SecretError> {
+        let Some(value) = sqlx::query_scalar!(
+            r#"
+            SELECT value FROM secrets WHERE name = $1
+            "#,
+            secret.name
+        )
+        .fetch_optional(&self.pool)
+        .await?
+        else {
+            return Ok(None);
+        };
+
+        Ok(Some(serde_json::from_slice(&self.key.decrypt(&value)?)?))
+    }
+
+    pub async fn set<T: Serialize>(
+        &self,
+        secret: &Secret<T>,
+        value: &T,
+    ) -> Result<(), SecretError> {
+        let value = self.key.encrypt(&serde_json::to_vec(value)?);
+
+        sqlx::query!(
+            r#"
+            INSERT INTO secrets (name, value) VALUES ($1, $2)
+            ON CONFLICT (name) DO UPDATE SET value = EXCLUDED.value
+            "#,
+            secret.name,
+            value
+        )
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    pub async fn remove<T>(&self, secret: &Secret<T>) -> Result<(), sqlx::Error> {
+        sqlx::query!(
+            r#"
+            DELETE FROM secrets WHERE name = $1
+            "#,
+            secret.name
+        )
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const KEY: &str = "000102030405060708090a0b0c0d0e0f101112131415161718191a1b1c1d1e1f";
+
+    #[test]
+    fn secrets_only_decrypt_with_the_key_that_encrypted_them() {
+        let key = SecretKey::from_hex(KEY).expect("failed to read the key");
+        let encrypted = key.encrypt(b"hunter2");
+
+        assert_ne!(encrypted, key.encrypt(b"hunter2"));
+        assert_eq!(key.decrypt(&encrypted), Ok(b"hunter2".to_vec()));
+
+        let other = SecretKey::from_hex(&KEY.replace("1f", "ff")).expect("failed to read the key");
+        assert_eq!(other.decrypt(&encrypted), Err(DecryptError));
+        assert_eq!(key.decrypt(&encrypted[..4]), Err(DecryptError));
+    }
+
+    #[test]
+    fn wrapped_keys_unwrap_to_the_same_key() {
+        let key = SecretKey::from_hex(KEY).expect("failed to read the key");
+        let data_key = SecretKey::generate();
+        let unwrapped = key
+            .unwrap(&key.wrap(&data_key))
+            .expect("failed to unwrap the key");
+
+        assert_eq!(
+            unwrapped.decrypt(&data_key.encrypt(b"hunter2")),
+            Ok(b"hunter2".to_vec())
+        );
+        assert!(key.unwrap(&key.encrypt(b"too short")).is_err());
+    }
+
+    #[test]
+    fn keys_must_be_32_bytes_of_hex() {
+        assert!(SecretKey::from_hex(&KEY[2..]).is_err());
+        assert!(SecretKey::from_hex("not hex").is_err());
+    }
+}
@@ -1,24 +1,148 @@
 use crate::AppState;
-use crate::authn::user::{DEV_USERS, UserError};
+use crate::authn::user::UserError;
 use crate::authority::Actor;
 use crate::authority::Authority;
 use crate::authority::UserId;
+use crate::email::Email;
 use crate::journal::account::AccountId;
+use crate::journal::appearance::{JournalAppearance, JournalColor, JournalIcon};
 use crate::journal::transaction::EntryType;
-use crate::journal::transaction::{BalanceUpdate, TransactionId};
+use crate::journal::transaction::{BalanceUpdate, TransactionId, parse_effective_date};
 use crate::journal::{JournalError, JournalId, Permissions};
 use crate::monkesto_error::MonkestoResult;
 use crate::name::Name;
 use crate::time_provider::{IncrementalTimeProvider, TimeProvider};
 use disintegrate::DecisionError;
+use serde::Deserialize;
 use std::str::FromStr;
+use std::sync::LazyLock;
+use webauthn_rs::prelude::Uuid;
 
-pub(crate) async fn seed_dev_data(state: &AppState) -> MonkestoResult<()> {
+/// the users and journals a development instance starts with
+pub static DEV_FIXTURE: LazyLock<Fixture> = LazyLock::new(|| {
+    Fixture::from_json(include_str!("seed/dev.json")).expect("the dev fixture is invalid")
+});
+
+/// users and journals to seed, written as json. ids are fixed, so the same fixture always
+/// seeds the same data and seeding it again skips what's already there
+#[derive(Debug, Default, Deserialize)]
+pub struct Fixture {
+    #[serde(default)]
+    pub users: Vec<UserFixture>,
+    #[serde(default)]
+    pub journals: Vec<JournalFixture>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct UserFixture {
+    pub id: String,
+    pub email: String,
+    pub webauthn_uuid: Uuid,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct JournalFixture {
+    pub id: String,
+    pub name: String,
+    pub owner: String,
+    #[serde(default)]
+    pub color: Option<String>,
+    #[serde(default)]
+    pub icon: Option<String>,
+    #[serde(default)]
+    pub members: Vec<MemberFixture>,
+    #[serde(default)]
+    pub accounts: Vec<AccountFixture>,
+    #[serde(default)]
+    pub transactions: Vec<TransactionFixture>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct MemberFixture {
+    pub user: String,
+    /// flag names joined with `|`, such as `READ | ADD_ACCOUNT`
+    pub permissions: Permissions,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct AccountFixture {
+    pub id: String,
+    pub name: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct TransactionFixture {
+    pub id: String,
+    /// the effective date, as `YYYY-MM-DD`
+    pub date: String,
+    pub entries: Vec<EntryFixture>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct EntryFixture {
+    pub account: String,
+    #[serde(rename = "type")]
+    pub entry_type: EntryType,
+    /// in cents
+    pub amount: u64,
+}
+
+impl Fixture {
+    pub fn from_json(json: &str) -> Result<Self, serde_json::Error> {
+        serde_json::from_str(json)
+    }
+
+    /// each user's email, id and passkey uuid
+    pub fn users(&self) -> MonkestoResult<Vec<(Email, UserId, Uuid)>> {
+        self.users
+            .iter()
+            .map(|user| {
+                Ok((
+                    Email::try_new(user.email.as_str())?,
+                    UserId::from_str(&user.id)?,
+                    user.webauthn_uuid,
+                ))
+            })
+            .collect()
+    }
+}
+
+impl JournalFixture {
+    fn appearance(&self) -> MonkestoResult<JournalAppearance> {
+        Ok(JournalAppearance {
+            color: match &self.color {
+                Some(color) => color.parse()?,
+                None => JournalColor::default(),
+            },
+            icon: self.icon.as_deref().map(JournalIcon::try_new).transpose()?,
+        })
+    }
+}
+
+impl TransactionFixture {
+    fn entries(&self) -> MonkestoResult<Vec<BalanceUpdate>> {
+        self.entries
+            .iter()
+            .map(|entry| {
+                Ok(BalanceUpdate {
+                    account_id: AccountId::from_str(&entry.account)?,
+                    amount: entry.amount,
+                    entry_type: entry.entry_type,
+                    memo: None,
+                })
+            })
+            .collect()
+    }
+}
+
+/// creates the fixture's users, then each journal with its members, accounts and
+/// transactions, all made by the journal's owner one second apart from the unix epoch
+pub(crate) async fn seed(state: &AppState, fixture: &Fixture) -> MonkestoResult<()> {
     let time_provider = IncrementalTimeProvider::new();
 
     let mut latest_user_event = 0;
 
-    for (email, (user_id, webauthn_uuid)) in DEV_USERS.clone() {
+    for (email, user_id, webauthn_uuid) in fixture.users()? {
         match state
             .authn_service
             .create_user(
@@ -39,44 +163,21 @@ pub(crate) async fn seed_dev_data(state: &AppState) -> MonkestoResult<()> {
         }
     }
 
-    let pacioli_id = UserId::from_str("zk8m3p5q7r2n4v6x")?;
-    let wedgwood_id = UserId::from_str("yj7l2o4p6q8s0u1w")?;
-
-    let pacioli_authority = Authority::Direct(Actor::User(pacioli_id));
-
-    let maple_ridge_academy_id = JournalId::from_str("ab1cd2ef3g")?;
-    let smith_and_sons_id = JournalId::from_str("hi4jk5lm6n")?;
-    let green_valley_id = JournalId::from_str("op7qr8st9u")?;
-
-    let assets_id = AccountId::from_str("ac1assets0")?;
-    let revenue_id = AccountId::from_str("ac4revenue")?;
-    let expenses_id = AccountId::from_str("ac5expense")?;
-
     let mut latest_journal_event = 0;
 
-    let journals = [
-        (
-            maple_ridge_academy_id,
-            Name::try_new("Maple Ridge Academy".to_string())?,
-        ),
-        (
-            smith_and_sons_id,
-            Name::try_new("Smith & Sons Bakery".to_string())?,
-        ),
-        (
-            green_valley_id,
-            Name::try_new("Green Valley Farm Co.".to_string())?,
-        ),
-    ];
-
-    for (id, name) in journals {
+    for journal in &fixture.journals {
+        let journal_id = JournalId::from_str(&journal.id)?;
+        let owner_id = UserId::from_str(&journal.owner)?;
+        let owner_authority = Authority::Direct(Actor::User(owner_id));
+
         match state
             .journal_service
             .create_journal(
-                id,
-                pacioli_id,
-                name,
-                pacioli_authority.clone(),
+                journal_id,
+                owner_id,
+                Name::try_new(journal.name.clone())?,
+                journal.appearance()?,
+                owner_authority.clone(),
                 time_provider.get_time(),
             )
             .await
@@ -86,149 +187,61 @@ pub(crate) async fn seed_dev_data(state: &AppState) -> MonkestoResult<()> {
             Err(DecisionError::Domain(JournalError::IdCollision(_))) => {}
             Err(e) => return Err(e.into()),
         }
-    }
 
-    match state
-        .journal_service
-        .add_member(
-            maple_ridge_academy_id,
-            wedgwood_id,
-            Permissions::READ | Permissions::ADD_ACCOUNT | Permissions::APPEND_TRANSACTION,
-            pacioli_authority.clone(),
-            time_provider.get_time(),
-        )
-        .await
-    {
-        Ok(ev_id) => latest_journal_event = ev_id,
-        Err(DecisionError::Domain(JournalError::UserAlreadyHasAccess(_))) => {}
-        Err(e) => return Err(e.into()),
-    }
+        for member in &journal.members {
+            match state
+                .journal_service
+                .add_member(
+                    journal_id,
+                    UserId::from_str(&member.user)?,
+                    member.permissions,
+                    owner_authority.clone(),
+                    time_provider.get_time(),
+                )
+                .await
+            {
+                Ok(ev_id) => latest_journal_event = ev_id,
+                Err(DecisionError::Domain(JournalError::UserAlreadyHasAccess(_))) => {}
+                Err(e) => return Err(e.into()),
+            }
+        }
 
-    let accounts = [
-        (assets_id, Name::try_new("Assets".to_string())?),
-        (
-            AccountId::from_str("ac2liabili")?,
-            Name::try_new("Liabilities".to_string())?,
-        ),
-        (
-            AccountId::from_str("ac3equity0")?,
-            Name::try_new("Equity".to_string())?,
-        ),
-        (revenue_id, Name::try_new("Revenue".to_string())?),
-        (expenses_id, Name::try_new("Expenses".to_string())?),
-    ];
-
-    for (id, name) in accounts {
-        match state
-            .journal_service
-            .create_account(
-                id,
-                maple_ridge_academy_id,
-                name,
-                pacioli_authority.clone(),
-                time_provider.get_time(),
-            )
-            .await
-        {
-            Ok(ev_id) => latest_journal_event = ev_id,
-            Err(DecisionError::Domain(JournalError::AccountIdCollision(_))) => {}
-            Err(e) => return Err(e.into()),
+        for account in &journal.accounts {
+            match state
+                .journal_service
+                .create_account(
+                    AccountId::from_str(&account.id)?,
+                    journal_id,
+                    Name::try_new(account.name.clone())?,
+                    owner_authority.clone(),
+                    time_provider.get_time(),
+                )
+                .await
+            {
+                Ok(ev_id) => latest_journal_event = ev_id,
+                Err(DecisionError::Domain(JournalError::AccountIdCollision(_))) => {}
+                Err(e) => return Err(e.into()),
+            }
         }
-    }
 
-    let transactions = [
-        (
-            TransactionId::from_str("t1tuition0000001")?,
-            vec![
-                BalanceUpdate {
-                    account_id: assets_id,
-                    amount: 500000,
-                    entry_type: EntryType::Debit,
-                },
-                BalanceUpdate {
-                    account_id: revenue_id,
-                    amount: 500000,
-                    entry_type: EntryType::Credit,
-                },
-            ],
-        ),
-        (
-            TransactionId::from_str("t2salary00000002")?,
-            vec![
-                BalanceUpdate {
-                    account_id: expenses_id,
-                    amount: 320000,
-                    entry_type: EntryType::Debit,
-                },
-                BalanceUpdate {
-                    account_id: assets_id,
-                    amount: 320000,
-                    entry_type: EntryType::Credit,
-                },
-            ],
-        ),
-        (
-            TransactionId::from_str("t3textbooks00003")?,
-            vec![
-                BalanceUpdate {
-                    account_id: expenses_id,
-                    amount: 85000,
-                    entry_type: EntryType::Debit,
-                },
-                BalanceUpdate {
-                    account_id: assets_id,
-                    amount: 85000,
-                    entry_type: EntryType::Credit,
-                },
-            ],
-        ),
-        (
-            TransactionId::from_str("t4tuition0000004")?,
-            vec![
-                BalanceUpdate {
-                    account_id: assets_id,
-                    amount: 450000,
-                    entry_type: EntryType::Debit,
-                },
-                BalanceUpdate {
-                    account_id: revenue_id,
-                    amount: 450000,
-                    entry_type: EntryType::Credit,
-                },
-            ],
-        ),
-        (
-            TransactionId::from_str("t6chkdeposit0005")?,
-            vec![
-                BalanceUpdate {
-                    account_id: expenses_id,
-                    amount: 64000,
-                    entry_type: EntryType::Debit,
-                },
-                BalanceUpdate {
-                    account_id: assets_id,
-                    amount: 64000,
-                    entry_type: EntryType::Credit,
-                },
-            ],
-        ),
-    ];
-
-    for (id, entries) in transactions {
-        match state
-            .journal_service
-            .create_transaction(
-                id,
-                maple_ridge_academy_id,
-                entries,
-                pacioli_authority.clone(),
-                time_provider.get_time(),
-            )
-            .await
-        {
-            Ok(ev_id) => latest_journal_event = ev_id,
-            Err(DecisionError::Domain(JournalError::TransactionIdCollision(_))) => {}
-            Err(e) => return Err(e.into()),
+        for transaction in &journal.transactions {
+            match state
+                .journal_service
+                .create_transaction(
+                    TransactionId::from_str(&transaction.id)?,
+                    journal_id,
+                    transaction.entries()?,
+                    parse_effective_date(&transaction.date)?,
+                    None,
+                    owner_authority.clone(),
+                    time_provider.get_time(),
+                )
+                .await
+            {
+                Ok(ev_id) => latest_journal_event = ev_id,
+                Err(DecisionError::Domain(JournalError::TransactionIdCollision(_))) => {}
+                Err(e) => return Err(e.into()),
+            }
         }
     }
 
@@ -238,3 +251,89 @@ pub(crate) async fn seed_dev_data(state: &AppState) -> MonkestoResult<()> {
 
     Ok(())
 }
+
+pub(crate) async fn seed_dev_data(state: &AppState) -> MonkestoResult<()> {
+    seed(state, &DEV_FIXTURE).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_app::TestApp;
+
+    #[test]
+    fn the_dev_fixture_only_posts_to_its_own_accounts() {
+        assert_eq!(DEV_FIXTURE.users().map(|users| users.len()), Ok(2));
+
+        for journal in &DEV_FIXTURE.journals {
+            for transaction in &journal.transactions {
+                for entry in &transaction.entries {
+                    assert!(
+                        journal.accounts.iter().any(|a| a.id == entry.account),
+                        "{} posts to {}, which isn't one of its journal's accounts",
+                        transaction.id,
+                        entry.account
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn permissions_are_written_as_flag_names() {
+        let fixture = Fixture::from_json(
+            r#"{ "journals": [{ "id": "ab1cd2ef3g", "name": "Test", "owner": "zk8m3p5q7r2n4v6x",
+                "members": [{ "user": "yj7l2o4p6q8s0u1w", "permissions": "READ | ADD_ACCOUNT" }] }] }"#,
+        )
+        .expect("failed to read the fixture");
+
+        assert_eq!(
+            fixture.journals[0].members[0].permissions,
+            Permissions::READ | Permissions::ADD_ACCOUNT
+        );
+    }
+
+    #[tokio::test]
+    async fn fixtures_seed_their_journals_once() {
+        let Some(app) = TestApp::spawn().await else {
+            return;
+        };
+
+        let owner = UserId::new();
+        let journal_id = JournalId::new();
+        let cash = AccountId::new();
+        let sales = AccountId::new();
+
+        let fixture = Fixture::from_json(&format!(
+            r#"{{
+                "users": [{{ "id": "{owner}", "email": "seeded-{owner}@example.com", "webauthn_uuid": "{uuid}" }}],
+                "journals": [{{
+                    "id": "{journal_id}", "name": "Lemonade Stand", "owner": "{owner}", "color": "amber",
+                    "accounts": [{{ "id": "{cash}", "name": "Cash" }}, {{ "id": "{sales}", "name": "Sales" }}],
+                    "transactions": [{{
+                        "id": "{transaction_id}", "date": "2026-07-04",
+                        "entries": [
+                            {{ "account": "{cash}", "type": "Debit", "amount": 250 }},
+                            {{ "account": "{sales}", "type": "Credit", "amount": 250 }}
+                        ]
+                    }}]
+                }}]
+            }}"#,
+            uuid = Uuid::new_v4(),
+            transaction_id = TransactionId::new(),
+        ))
+        .expect("failed to read the fixture");
+
+        app.seed(&fixture).await;
+        app.seed(&fixture).await;
+
+        assert_eq!(
+            app.accounts(journal_id).await,
+            vec![
+                Name::try_new("Cash".to_string()).expect("invalid name"),
+                Name::try_new("Sales".to_string()).expect("invalid name"),
+            ]
+        );
+        assert_eq!(app.transaction_count(journal_id).await, 1);
+    }
+}
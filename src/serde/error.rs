@@ -20,16 +20,21 @@ pub enum ProtoError {
 use crate::authn::user::UserError;
 use crate::email::{Email, EmailError};
 use crate::id::IdentError;
+use crate::journal::quota::Quota;
 use crate::journal::transaction::{
-    BalanceUpdate, EntryType, TransactionEntries, TransactionValidationError,
+    BalanceUpdate, BatchRowError, EntryType, TransactionEntries, TransactionValidationError,
 };
 use crate::journal::{JournalError, PermissionDecodeError, Permissions};
 use crate::name::NameError;
 use crate::proto::error::proto_balance_update::{ProtoEntryType, proto_entry_type};
 use crate::proto::error::proto_decode_error::ProtoErrorType;
 use crate::proto::error::proto_ident_error::IdentErrorType;
+use crate::proto::error::proto_journal_error::proto_quota::QuotaType;
 use crate::proto::error::proto_journal_error::proto_transaction_validation_error::TransactionValidationErrorType;
-use crate::proto::error::proto_journal_error::{JournalErrorType, ProtoTransactionValidationError};
+use crate::proto::error::proto_journal_error::{
+    JournalErrorType, ProtoBatchRowError, ProtoInvalidBatch, ProtoQuota,
+    ProtoTransactionValidationError,
+};
 use crate::proto::error::proto_monkesto_error::MonkestoErrorType;
 use crate::proto::error::proto_name_error::NameErrorType;
 use crate::proto::error::proto_user_error::UserErrorType;
@@ -54,6 +59,7 @@ impl TryFrom<RepeatedBalanceUpdates> for TransactionEntries {
                     proto_entry_type::EntryType::Credit(_) => EntryType::Credit,
                     proto_entry_type::EntryType::Debit(_) => EntryType::Debit,
                 },
+                memo: Some(entry.memo).filter(|memo| !memo.is_empty()),
             })
         }
 
@@ -78,12 +84,313 @@ impl From<TransactionEntries> for RepeatedBalanceUpdates {
                             entry_type: Some(proto_entry_type::EntryType::Debit(())),
                         },
                     }),
+                    memo: u.memo.clone().unwrap_or_default(),
                 })
                 .collect(),
         }
     }
 }
 
+impl TryFrom<ProtoJournalError> for JournalError {
+    type Error = ProtoError;
+
+    fn try_from(value: ProtoJournalError) -> Result<Self, Self::Error> {
+        Ok(match value.journal_error_type.ok_or(FieldRequired)? {
+            JournalErrorType::IdCollision(id) => JournalError::IdCollision(id.into()),
+            JournalErrorType::InvalidJournal(id) => JournalError::InvalidJournal(id.into()),
+            JournalErrorType::Permissions(perms) => JournalError::Permissions(
+                Permissions::from_bits(perms).ok_or(PermissionDecode(perms))?,
+            ),
+            JournalErrorType::UserAlreadyHasAccess(id) => {
+                JournalError::UserAlreadyHasAccess(id.into())
+            }
+            JournalErrorType::UserDoesntHaveAccess(id) => {
+                JournalError::UserDoesntHaveAccess(id.into())
+            }
+            JournalErrorType::IdentCreation(e) => match e.ident_error_type.ok_or(FieldRequired)? {
+                IdentErrorType::Parse(s) => JournalError::IdentCreation(IdentError::Parse(s)),
+                IdentErrorType::InvalidId(s) => {
+                    JournalError::IdentCreation(IdentError::InvalidId(s))
+                }
+            },
+            JournalErrorType::Sqlx(s) => JournalError::Sqlx(s),
+            JournalErrorType::PermissionDecode(e) => {
+                JournalError::PermissionDecode(PermissionDecodeError(e))
+            }
+            JournalErrorType::AccountIdCollision(id) => JournalError::AccountIdCollision(id.into()),
+            JournalErrorType::TransactionIdCollision(id) => {
+                JournalError::TransactionIdCollision(id.into())
+            }
+            JournalErrorType::InvalidAccount(id) => JournalError::InvalidAccount(id.into()),
+            JournalErrorType::InvalidTransaction(id) => JournalError::InvalidTransaction(id.into()),
+            JournalErrorType::EventDecode(s) => JournalError::EventDecode(s),
+            JournalErrorType::WebhookIdCollision(id) => JournalError::WebhookIdCollision(id.into()),
+            JournalErrorType::InvalidWebhook(id) => JournalError::InvalidWebhook(id.into()),
+            JournalErrorType::InvalidWebhookUrl(s) => JournalError::InvalidWebhookUrl(s),
+            JournalErrorType::UndoExpired(_) => JournalError::UndoExpired,
+            JournalErrorType::OwnerCannotLeave(id) => JournalError::OwnerCannotLeave(id.into()),
+            JournalErrorType::InvalidFiscalYearStart(s) => JournalError::InvalidFiscalYearStart(s),
+            JournalErrorType::RuleIdCollision(id) => JournalError::RuleIdCollision(id.into()),
+            JournalErrorType::InvalidRule(id) => JournalError::InvalidRule(id.into()),
+            JournalErrorType::InvalidRuleCondition(s) => JournalError::InvalidRuleCondition(s),
+            JournalErrorType::BankFeedIdCollision(id) => {
+                JournalError::BankFeedIdCollision(id.into())
+            }
+            JournalErrorType::InvalidBankFeed(id) => JournalError::InvalidBankFeed(id.into()),
+            JournalErrorType::Secret(s) => JournalError::Secret(s),
+            JournalErrorType::UnknownBankFeedProvider(s) => {
+                JournalError::UnknownBankFeedProvider(s)
+            }
+            JournalErrorType::BankFeedSync(s) => JournalError::BankFeedSync(s),
+            JournalErrorType::TransferWithinJournal(id) => {
+                JournalError::TransferWithinJournal(id.into())
+            }
+            JournalErrorType::TemplateIdCollision(id) => {
+                JournalError::TemplateIdCollision(id.into())
+            }
+            JournalErrorType::InvalidTemplate(id) => JournalError::InvalidTemplate(id.into()),
+            JournalErrorType::CommentIdCollision(id) => JournalError::CommentIdCollision(id.into()),
+            JournalErrorType::InvalidComment(id) => JournalError::InvalidComment(id.into()),
+            JournalErrorType::CommentLength(length) => JournalError::CommentLength(length as usize),
+            JournalErrorType::JournalArchived(id) => JournalError::JournalArchived(id.into()),
+            JournalErrorType::InvalidJournalColor(s) => JournalError::InvalidJournalColor(s),
+            JournalErrorType::InvalidJournalIcon(s) => JournalError::InvalidJournalIcon(s),
+            JournalErrorType::InvalidBatch(batch) => JournalError::InvalidBatch(
+                batch
+                    .rows
+                    .into_iter()
+                    .map(|row| {
+                        Ok(BatchRowError {
+                            row: row.row,
+                            error: row.error.ok_or(FieldRequired)?.try_into()?,
+                        })
+                    })
+                    .collect::<Result<_, ProtoError>>()?,
+            ),
+            JournalErrorType::QuotaExceeded(q) => {
+                JournalError::QuotaExceeded(match q.quota_type.ok_or(FieldRequired)? {
+                    QuotaType::Journals(limit) => Quota::Journals(limit),
+                    QuotaType::TransactionsPerJournal(limit) => {
+                        Quota::TransactionsPerJournal(limit)
+                    }
+                })
+            }
+
+            JournalErrorType::TransactionValidation(e) => {
+                let validation_error =
+                    match e.transaction_validation_error_type.ok_or(FieldRequired)? {
+                        TransactionValidationErrorType::InvalidEntryType(s) => {
+                            TransactionValidationError::InvalidEntryType(s)
+                        }
+                        TransactionValidationErrorType::NoTransactionEntries(_) => {
+                            TransactionValidationError::NoTransactionEntries
+                        }
+                        TransactionValidationErrorType::MissingEntryAmount(_) => {
+                            TransactionValidationError::MissingEntryAmount
+                        }
+                        TransactionValidationErrorType::MissingEntryType(_) => {
+                            TransactionValidationError::MissingEntryType
+                        }
+                        TransactionValidationErrorType::ParseDecimal(s) => {
+                            TransactionValidationError::ParseDecimal(s)
+                        }
+                        TransactionValidationErrorType::PartialCentValue(s) => {
+                            TransactionValidationError::PartialCentValue(s)
+                        }
+                        TransactionValidationErrorType::OutOfRange(s) => {
+                            TransactionValidationError::OutOfRange(s)
+                        }
+                        TransactionValidationErrorType::NegativeEntryAmount(s) => {
+                            TransactionValidationError::NegativeEntryAmount(s)
+                        }
+                        TransactionValidationErrorType::ImbalancedTransaction(updates) => {
+                            TransactionValidationError::ImbalancedTransaction(updates.try_into()?)
+                        }
+                        TransactionValidationErrorType::InvalidEffectiveDate(s) => {
+                            TransactionValidationError::InvalidEffectiveDate(s)
+                        }
+                        TransactionValidationErrorType::UnknownAccount(s) => {
+                            TransactionValidationError::UnknownAccount(s)
+                        }
+                        TransactionValidationErrorType::InvalidQuickEntry(s) => {
+                            TransactionValidationError::InvalidQuickEntry(s)
+                        }
+                        TransactionValidationErrorType::InvalidSplit(s) => {
+                            TransactionValidationError::InvalidSplit(s)
+                        }
+                        TransactionValidationErrorType::InvalidPlaintext(s) => {
+                            TransactionValidationError::InvalidPlaintext(s)
+                        }
+                        TransactionValidationErrorType::InvalidStatement(s) => {
+                            TransactionValidationError::InvalidStatement(s)
+                        }
+                        TransactionValidationErrorType::MemoTooLong(length) => {
+                            TransactionValidationError::MemoTooLong(length as usize)
+                        }
+                    };
+
+                JournalError::TransactionValidation(validation_error)
+            }
+        })
+    }
+}
+
+impl From<JournalError> for ProtoJournalError {
+    fn from(value: JournalError) -> Self {
+        let e = match value {
+            JournalError::IdCollision(id) => JournalErrorType::IdCollision(id.to_string()),
+            JournalError::AccountIdCollision(id) => {
+                JournalErrorType::AccountIdCollision(id.to_string())
+            }
+            JournalError::TransactionIdCollision(id) => {
+                JournalErrorType::TransactionIdCollision(id.to_string())
+            }
+            JournalError::InvalidJournal(id) => JournalErrorType::InvalidJournal(id.to_string()),
+            JournalError::InvalidAccount(id) => JournalErrorType::InvalidAccount(id.to_string()),
+            JournalError::InvalidTransaction(id) => {
+                JournalErrorType::InvalidTransaction(id.to_string())
+            }
+            JournalError::TransactionValidation(e) => {
+                let t_val = match e {
+                    TransactionValidationError::InvalidEntryType(s) => {
+                        TransactionValidationErrorType::InvalidEntryType(s)
+                    }
+                    TransactionValidationError::NoTransactionEntries => {
+                        TransactionValidationErrorType::NoTransactionEntries(())
+                    }
+                    TransactionValidationError::MissingEntryAmount => {
+                        TransactionValidationErrorType::MissingEntryAmount(())
+                    }
+                    TransactionValidationError::MissingEntryType => {
+                        TransactionValidationErrorType::MissingEntryType(())
+                    }
+                    TransactionValidationError::ParseDecimal(s) => {
+                        TransactionValidationErrorType::ParseDecimal(s)
+                    }
+                    TransactionValidationError::PartialCentValue(s) => {
+                        TransactionValidationErrorType::PartialCentValue(s)
+                    }
+                    TransactionValidationError::OutOfRange(s) => {
+                        TransactionValidationErrorType::OutOfRange(s)
+                    }
+                    TransactionValidationError::NegativeEntryAmount(s) => {
+                        TransactionValidationErrorType::NegativeEntryAmount(s)
+                    }
+                    TransactionValidationError::ImbalancedTransaction(updates) => {
+                        TransactionValidationErrorType::ImbalancedTransaction(updates.into())
+                    }
+                    TransactionValidationError::InvalidEffectiveDate(s) => {
+                        TransactionValidationErrorType::InvalidEffectiveDate(s)
+                    }
+                    TransactionValidationError::UnknownAccount(s) => {
+                        TransactionValidationErrorType::UnknownAccount(s)
+                    }
+                    TransactionValidationError::InvalidQuickEntry(s) => {
+                        TransactionValidationErrorType::InvalidQuickEntry(s)
+                    }
+                    TransactionValidationError::InvalidSplit(s) => {
+                        TransactionValidationErrorType::InvalidSplit(s)
+                    }
+                    TransactionValidationError::InvalidPlaintext(s) => {
+                        TransactionValidationErrorType::InvalidPlaintext(s)
+                    }
+                    TransactionValidationError::InvalidStatement(s) => {
+                        TransactionValidationErrorType::InvalidStatement(s)
+                    }
+                    TransactionValidationError::MemoTooLong(length) => {
+                        TransactionValidationErrorType::MemoTooLong(length as u64)
+                    }
+                };
+                JournalErrorType::TransactionValidation(ProtoTransactionValidationError {
+                    transaction_validation_error_type: Some(t_val),
+                })
+            }
+            JournalError::Permissions(perms) => JournalErrorType::Permissions(perms.bits()),
+            JournalError::UserAlreadyHasAccess(id) => {
+                JournalErrorType::UserAlreadyHasAccess(id.to_string())
+            }
+            JournalError::UserDoesntHaveAccess(id) => {
+                JournalErrorType::UserDoesntHaveAccess(id.to_string())
+            }
+            JournalError::IdentCreation(e) => {
+                let e = match e {
+                    IdentError::Parse(s) => IdentErrorType::Parse(s),
+                    IdentError::InvalidId(s) => IdentErrorType::InvalidId(s),
+                };
+
+                JournalErrorType::IdentCreation(ProtoIdentError {
+                    ident_error_type: Some(e),
+                })
+            }
+            JournalError::Sqlx(s) => JournalErrorType::Sqlx(s),
+            JournalError::PermissionDecode(e) => JournalErrorType::PermissionDecode(e.0),
+            JournalError::EventDecode(s) => JournalErrorType::EventDecode(s),
+            JournalError::WebhookIdCollision(id) => {
+                JournalErrorType::WebhookIdCollision(id.to_string())
+            }
+            JournalError::InvalidWebhook(id) => JournalErrorType::InvalidWebhook(id.to_string()),
+            JournalError::InvalidWebhookUrl(s) => JournalErrorType::InvalidWebhookUrl(s),
+            JournalError::UndoExpired => JournalErrorType::UndoExpired(()),
+            JournalError::OwnerCannotLeave(id) => {
+                JournalErrorType::OwnerCannotLeave(id.to_string())
+            }
+            JournalError::InvalidFiscalYearStart(s) => JournalErrorType::InvalidFiscalYearStart(s),
+            JournalError::RuleIdCollision(id) => JournalErrorType::RuleIdCollision(id.to_string()),
+            JournalError::InvalidRule(id) => JournalErrorType::InvalidRule(id.to_string()),
+            JournalError::InvalidRuleCondition(s) => JournalErrorType::InvalidRuleCondition(s),
+            JournalError::BankFeedIdCollision(id) => {
+                JournalErrorType::BankFeedIdCollision(id.to_string())
+            }
+            JournalError::InvalidBankFeed(id) => JournalErrorType::InvalidBankFeed(id.to_string()),
+            JournalError::Secret(s) => JournalErrorType::Secret(s),
+            JournalError::UnknownBankFeedProvider(s) => {
+                JournalErrorType::UnknownBankFeedProvider(s)
+            }
+            JournalError::BankFeedSync(s) => JournalErrorType::BankFeedSync(s),
+            JournalError::TransferWithinJournal(id) => {
+                JournalErrorType::TransferWithinJournal(id.to_string())
+            }
+            JournalError::TemplateIdCollision(id) => {
+                JournalErrorType::TemplateIdCollision(id.to_string())
+            }
+            JournalError::InvalidTemplate(id) => JournalErrorType::InvalidTemplate(id.to_string()),
+            JournalError::CommentIdCollision(id) => {
+                JournalErrorType::CommentIdCollision(id.to_string())
+            }
+            JournalError::InvalidComment(id) => JournalErrorType::InvalidComment(id.to_string()),
+            JournalError::CommentLength(length) => JournalErrorType::CommentLength(length as u64),
+            JournalError::JournalArchived(id) => JournalErrorType::JournalArchived(id.to_string()),
+            JournalError::InvalidJournalColor(s) => JournalErrorType::InvalidJournalColor(s),
+            JournalError::InvalidJournalIcon(s) => JournalErrorType::InvalidJournalIcon(s),
+            JournalError::InvalidBatch(rows) => JournalErrorType::InvalidBatch(ProtoInvalidBatch {
+                rows: rows
+                    .into_iter()
+                    .map(|row| ProtoBatchRowError {
+                        row: row.row,
+                        error: Some(row.error.into()),
+                    })
+                    .collect(),
+            }),
+            JournalError::QuotaExceeded(quota) => {
+                let quota_type = match quota {
+                    Quota::Journals(limit) => QuotaType::Journals(limit),
+                    Quota::TransactionsPerJournal(limit) => {
+                        QuotaType::TransactionsPerJournal(limit)
+                    }
+                };
+
+                JournalErrorType::QuotaExceeded(ProtoQuota {
+                    quota_type: Some(quota_type),
+                })
+            }
+        };
+
+        ProtoJournalError {
+            journal_error_type: Some(e),
+        }
+    }
+}
+
 impl TryFrom<ProtoMonkestoError> for MonkestoError {
     type Error = ProtoError;
 
@@ -114,85 +421,7 @@ impl TryFrom<ProtoMonkestoError> for MonkestoError {
             MonkestoErrorType::EmailCreation(e) => {
                 MonkestoError::EmailCreation(EmailError::RegexViolated(e))
             }
-            MonkestoErrorType::Journal(e) => {
-                let journal_error = match e.journal_error_type.ok_or(FieldRequired)? {
-                    JournalErrorType::IdCollision(id) => JournalError::IdCollision(id.into()),
-                    JournalErrorType::InvalidJournal(id) => JournalError::InvalidJournal(id.into()),
-                    JournalErrorType::Permissions(perms) => JournalError::Permissions(
-                        Permissions::from_bits(perms).ok_or(PermissionDecode(perms))?,
-                    ),
-                    JournalErrorType::UserAlreadyHasAccess(id) => {
-                        JournalError::UserAlreadyHasAccess(id.into())
-                    }
-                    JournalErrorType::UserDoesntHaveAccess(id) => {
-                        JournalError::UserDoesntHaveAccess(id.into())
-                    }
-                    JournalErrorType::IdentCreation(e) => {
-                        match e.ident_error_type.ok_or(FieldRequired)? {
-                            IdentErrorType::Parse(s) => {
-                                JournalError::IdentCreation(IdentError::Parse(s))
-                            }
-                            IdentErrorType::InvalidId(s) => {
-                                JournalError::IdentCreation(IdentError::InvalidId(s))
-                            }
-                        }
-                    }
-                    JournalErrorType::Sqlx(s) => JournalError::Sqlx(s),
-                    JournalErrorType::PermissionDecode(e) => {
-                        JournalError::PermissionDecode(PermissionDecodeError(e))
-                    }
-                    JournalErrorType::AccountIdCollision(id) => {
-                        JournalError::AccountIdCollision(id.into())
-                    }
-                    JournalErrorType::TransactionIdCollision(id) => {
-                        JournalError::TransactionIdCollision(id.into())
-                    }
-                    JournalErrorType::InvalidAccount(id) => JournalError::InvalidAccount(id.into()),
-                    JournalErrorType::InvalidTransaction(id) => {
-                        JournalError::InvalidTransaction(id.into())
-                    }
-                    JournalErrorType::EventDecode(s) => JournalError::EventDecode(s),
-
-                    JournalErrorType::TransactionValidation(e) => {
-                        let validation_error =
-                            match e.transaction_validation_error_type.ok_or(FieldRequired)? {
-                                TransactionValidationErrorType::InvalidEntryType(s) => {
-                                    TransactionValidationError::InvalidEntryType(s)
-                                }
-                                TransactionValidationErrorType::NoTransactionEntries(_) => {
-                                    TransactionValidationError::NoTransactionEntries
-                                }
-                                TransactionValidationErrorType::MissingEntryAmount(_) => {
-                                    TransactionValidationError::MissingEntryAmount
-                                }
-                                TransactionValidationErrorType::MissingEntryType(_) => {
-                                    TransactionValidationError::MissingEntryType
-                                }
-                                TransactionValidationErrorType::ParseDecimal(s) => {
-                                    TransactionValidationError::ParseDecimal(s)
-                                }
-                                TransactionValidationErrorType::PartialCentValue(s) => {
-                                    TransactionValidationError::PartialCentValue(s)
-                                }
-                                TransactionValidationErrorType::OutOfRange(s) => {
-                                    TransactionValidationError::OutOfRange(s)
-                                }
-                                TransactionValidationErrorType::NegativeEntryAmount(s) => {
-                                    TransactionValidationError::NegativeEntryAmount(s)
-                                }
-                                TransactionValidationErrorType::ImbalancedTransaction(updates) => {
-                                    TransactionValidationError::ImbalancedTransaction(
-                                        updates.try_into()?,
-                                    )
-                                }
-                            };
-
-                        JournalError::TransactionValidation(validation_error)
-                    }
-                };
-
-                MonkestoError::Journal(journal_error)
-            }
+            MonkestoErrorType::Journal(e) => MonkestoError::Journal(e.try_into()?),
             MonkestoErrorType::User(e) => {
                 let user_error = match e.user_error_type.ok_or(FieldRequired)? {
                     UserErrorType::EmailConflict(e) => UserError::EmailConflict(Email::try_new(e)?),
@@ -205,6 +434,20 @@ impl TryFrom<ProtoMonkestoError> for MonkestoError {
                     UserErrorType::Sqlx(e) => UserError::Sqlx(e),
                     UserErrorType::SeedFailure(e) => UserError::SeedFailure(Email::try_new(e)?),
                     UserErrorType::PasskeyDecode(s) => UserError::PasskeyDecode(s),
+                    UserErrorType::TotpAlreadyEnabled(id) => {
+                        UserError::TotpAlreadyEnabled(id.into())
+                    }
+                    UserErrorType::TotpNotEnabled(id) => UserError::TotpNotEnabled(id.into()),
+                    UserErrorType::InvalidTotpCode(_) => UserError::InvalidTotpCode,
+                    UserErrorType::InvalidDigestFrequency(s) => {
+                        UserError::InvalidDigestFrequency(s)
+                    }
+                    UserErrorType::InvalidTimezone(s) => UserError::InvalidTimezone(s),
+                    UserErrorType::InvalidTheme(s) => UserError::InvalidTheme(s),
+                    UserErrorType::InvalidShortcut(s) => UserError::InvalidShortcut(s),
+                    UserErrorType::DeletionNotConfirmed(_) => UserError::DeletionNotConfirmed,
+                    UserErrorType::EventDecode(s) => UserError::EventDecode(s),
+                    UserErrorType::Secret(s) => UserError::Secret(s),
                 };
 
                 MonkestoError::User(user_error)
@@ -256,86 +499,7 @@ impl From<MonkestoError> for ProtoMonkestoError {
             MonkestoError::EmailCreation(EmailError::RegexViolated(s)) => {
                 MonkestoErrorType::EmailCreation(s)
             }
-            MonkestoError::Journal(e) => {
-                let e = match e {
-                    JournalError::IdCollision(id) => JournalErrorType::IdCollision(id.to_string()),
-                    JournalError::AccountIdCollision(id) => {
-                        JournalErrorType::AccountIdCollision(id.to_string())
-                    }
-                    JournalError::TransactionIdCollision(id) => {
-                        JournalErrorType::TransactionIdCollision(id.to_string())
-                    }
-                    JournalError::InvalidJournal(id) => {
-                        JournalErrorType::InvalidJournal(id.to_string())
-                    }
-                    JournalError::InvalidAccount(id) => {
-                        JournalErrorType::InvalidAccount(id.to_string())
-                    }
-                    JournalError::InvalidTransaction(id) => {
-                        JournalErrorType::InvalidTransaction(id.to_string())
-                    }
-                    JournalError::TransactionValidation(e) => {
-                        let t_val = match e {
-                            TransactionValidationError::InvalidEntryType(s) => {
-                                TransactionValidationErrorType::InvalidEntryType(s)
-                            }
-                            TransactionValidationError::NoTransactionEntries => {
-                                TransactionValidationErrorType::NoTransactionEntries(())
-                            }
-                            TransactionValidationError::MissingEntryAmount => {
-                                TransactionValidationErrorType::MissingEntryAmount(())
-                            }
-                            TransactionValidationError::MissingEntryType => {
-                                TransactionValidationErrorType::MissingEntryType(())
-                            }
-                            TransactionValidationError::ParseDecimal(s) => {
-                                TransactionValidationErrorType::ParseDecimal(s)
-                            }
-                            TransactionValidationError::PartialCentValue(s) => {
-                                TransactionValidationErrorType::PartialCentValue(s)
-                            }
-                            TransactionValidationError::OutOfRange(s) => {
-                                TransactionValidationErrorType::OutOfRange(s)
-                            }
-                            TransactionValidationError::NegativeEntryAmount(s) => {
-                                TransactionValidationErrorType::NegativeEntryAmount(s)
-                            }
-                            TransactionValidationError::ImbalancedTransaction(updates) => {
-                                TransactionValidationErrorType::ImbalancedTransaction(
-                                    updates.into(),
-                                )
-                            }
-                        };
-                        JournalErrorType::TransactionValidation(ProtoTransactionValidationError {
-                            transaction_validation_error_type: Some(t_val),
-                        })
-                    }
-                    JournalError::Permissions(perms) => JournalErrorType::Permissions(perms.bits()),
-                    JournalError::UserAlreadyHasAccess(id) => {
-                        JournalErrorType::UserAlreadyHasAccess(id.to_string())
-                    }
-                    JournalError::UserDoesntHaveAccess(id) => {
-                        JournalErrorType::UserDoesntHaveAccess(id.to_string())
-                    }
-                    JournalError::IdentCreation(e) => {
-                        let e = match e {
-                            IdentError::Parse(s) => IdentErrorType::Parse(s),
-                            IdentError::InvalidId(s) => IdentErrorType::InvalidId(s),
-                        };
-
-                        JournalErrorType::IdentCreation(ProtoIdentError {
-                            ident_error_type: Some(e),
-                        })
-                    }
-                    JournalError::Sqlx(s) => JournalErrorType::Sqlx(s),
-                    JournalError::PermissionDecode(e) => JournalErrorType::PermissionDecode(e.0),
-                    JournalError::EventDecode(s) => JournalErrorType::EventDecode(s),
-                };
-
-                MonkestoErrorType::Journal(ProtoJournalError {
-                    journal_error_type: Some(e),
-                })
-            }
+            MonkestoError::Journal(e) => MonkestoErrorType::Journal(e.into()),
             MonkestoError::User(e) => {
                 let e = match e {
                     UserError::EmailConflict(em) => UserErrorType::EmailConflict(em.to_string()),
@@ -350,6 +514,20 @@ impl From<MonkestoError> for ProtoMonkestoError {
                     UserError::Sqlx(s) => UserErrorType::Sqlx(s),
                     UserError::SeedFailure(em) => UserErrorType::SeedFailure(em.to_string()),
                     UserError::PasskeyDecode(s) => UserErrorType::PasskeyDecode(s),
+                    UserError::TotpAlreadyEnabled(id) => {
+                        UserErrorType::TotpAlreadyEnabled(id.to_string())
+                    }
+                    UserError::TotpNotEnabled(id) => UserErrorType::TotpNotEnabled(id.to_string()),
+                    UserError::InvalidTotpCode => UserErrorType::InvalidTotpCode(()),
+                    UserError::InvalidDigestFrequency(s) => {
+                        UserErrorType::InvalidDigestFrequency(s)
+                    }
+                    UserError::InvalidTimezone(s) => UserErrorType::InvalidTimezone(s),
+                    UserError::InvalidTheme(s) => UserErrorType::InvalidTheme(s),
+                    UserError::InvalidShortcut(s) => UserErrorType::InvalidShortcut(s),
+                    UserError::DeletionNotConfirmed => UserErrorType::DeletionNotConfirmed(()),
+                    UserError::EventDecode(s) => UserErrorType::EventDecode(s),
+                    UserError::Secret(s) => UserErrorType::Secret(s),
                 };
 
                 MonkestoErrorType::User(ProtoUserError {
@@ -0,0 +1,750 @@
+use crate::app_env::{AppEnv, AppEnvError};
+use crate::authn::attempt::{
+    LockoutConfig, LockoutConfigError, TrustedProxies, TrustedProxiesError,
+};
+use crate::authn::oidc::{Oidc, OidcConfigError};
+use crate::authn::{AuthConfigError, AuthConnectError, AuthnEventStore, AuthnService};
+use crate::authz::{AuthzConnectError, AuthzEventStore, AuthzService, RoleIndex};
+use crate::database::{DatabaseConfig, DatabaseConfigError};
+use crate::id::IdGenerator;
+use crate::journal::backup::{BackupConfig, BackupConfigError};
+use crate::journal::bankfeed::provider::{BankFeeds, GoCardless};
+use crate::journal::consistency::{BalanceCheckConfig, BalanceCheckConfigError};
+use crate::journal::payload::JournalPayloads;
+use crate::journal::quota::{QuotaConfigError, Quotas};
+use crate::journal::rebuild::RebuildError;
+use crate::journal::restore::RestoreError;
+use crate::journal::retention::{RetentionConfig, RetentionConfigError};
+use crate::journal::store::JournalEventStore;
+use crate::journal::{JournalId, JournalService, account, transaction};
+use crate::limits::{RequestLimits, RequestLimitsError};
+use crate::mailer::{LogMailer, Mailer, MailerConfigError};
+use crate::monkesto_error::MonkestoError;
+use crate::secret::{SecretKey, SecretKeyError, SecretStore};
+use crate::seed::seed_dev_data;
+use crate::session::{SessionConfig, SessionConfigError};
+use crate::time_provider::Clock;
+use crate::{
+    AppState, StateType, asset, authn, authz, i18n, journal, mailer, monkesto_error, notfoundpage,
+};
+use crate::{openapi, session, shortcuts, shutdown, theme};
+use axum::extract::DefaultBodyLimit;
+use axum::http::header;
+use axum::http::{Response, StatusCode};
+use axum::middleware;
+use axum::response::IntoResponse;
+use axum::response::Redirect;
+use axum::routing::get;
+use axum::{Extension, Router};
+use axum_login::tracing::Span;
+use axum_login::{AuthManagerLayerBuilder, tracing};
+use sqlx::PgPool;
+use std::env;
+use std::net::SocketAddr;
+use std::path::Path;
+use std::str::FromStr;
+use std::sync::Arc;
+use std::time::Duration;
+use thiserror::Error;
+use tower_http::timeout::TimeoutLayer;
+use tower_http::trace::TraceLayer;
+use tower_sessions_sqlx_store::PostgresStore;
+
+#[derive(Debug, Error)]
+pub enum ServerError {
+    #[error("DATABASE_URL must be set")]
+    MissingDatabaseUrl,
+
+    #[error(transparent)]
+    AppEnv(#[from] AppEnvError),
+
+    #[error(transparent)]
+    DatabaseConfig(#[from] DatabaseConfigError),
+
+    #[error(transparent)]
+    Session(#[from] SessionConfigError),
+
+    #[error(transparent)]
+    Limits(#[from] RequestLimitsError),
+
+    #[error(transparent)]
+    Lockout(#[from] LockoutConfigError),
+
+    #[error(transparent)]
+    TrustedProxies(#[from] TrustedProxiesError),
+
+    #[error(transparent)]
+    Quota(#[from] QuotaConfigError),
+
+    #[error(transparent)]
+    Retention(#[from] RetentionConfigError),
+
+    #[error(transparent)]
+    BalanceCheck(#[from] BalanceCheckConfigError),
+
+    #[error(transparent)]
+    Backup(#[from] BackupConfigError),
+
+    #[error(transparent)]
+    SecretKey(#[from] SecretKeyError),
+
+    #[error(transparent)]
+    Mailer(#[from] MailerConfigError),
+
+    #[error(transparent)]
+    Oidc(#[from] OidcConfigError),
+
+    #[error(transparent)]
+    Auth(#[from] AuthConfigError),
+
+    #[error(transparent)]
+    Connect(#[from] AuthConnectError),
+
+    #[error(transparent)]
+    AuthzConnect(#[from] AuthzConnectError),
+
+    #[error("EVENT_ENCRYPTION_KEY must be 64 hex characters")]
+    EventEncryptionKey,
+
+    #[error("failed to create an event store: {0}")]
+    EventStore(#[from] disintegrate_postgres::Error),
+
+    #[error(transparent)]
+    Database(#[from] sqlx::Error),
+
+    #[error("failed to migrate the session store: {0}")]
+    SessionStore(String),
+
+    #[error("failed to seed the development data: {0}")]
+    Seed(MonkestoError),
+
+    #[error(transparent)]
+    Rebuild(#[from] RebuildError),
+
+    #[error(transparent)]
+    Restore(#[from] RestoreError),
+
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
+}
+
+/// builds a Monkesto instance. `from_env` configures it the way the binary runs, while `new`
+/// starts from the defaults so an embedder or test suite only sets what it cares about
+pub struct Server {
+    database_url: String,
+    replica_url: Option<String>,
+    app_env: AppEnv,
+    database: DatabaseConfig,
+    site_root: String,
+    session: SessionConfig,
+    limits: RequestLimits,
+    lockout: LockoutConfig,
+    trusted_proxies: TrustedProxies,
+    quotas: Quotas,
+    retention: RetentionConfig,
+    balance_check: BalanceCheckConfig,
+    backup: BackupConfig,
+    secret_key: Option<SecretKey>,
+    event_key: Option<SecretKey>,
+    mailer: Arc<dyn Mailer>,
+    bank_feeds: BankFeeds,
+    oidc: Option<Oidc>,
+    clock: Clock,
+    ids: IdGenerator,
+    background_tasks: bool,
+}
+
+/// the stores and services, connected but not yet listening for events
+struct Services {
+    session_store: PostgresStore,
+    authn_event_store: AuthnEventStore,
+    authn_service: AuthnService,
+    journal_event_store: JournalEventStore,
+    journal_service: JournalService,
+    authz_service: AuthzService,
+}
+
+impl Server {
+    /// a production instance on the database, with every setting at its default
+    pub fn new(database_url: impl Into<String>) -> Self {
+        Self {
+            database_url: database_url.into(),
+            replica_url: None,
+            app_env: AppEnv::default(),
+            database: DatabaseConfig::default(),
+            // the dockerfile defines SITE_ROOT for production deployments
+            site_root: "target/site".to_string(),
+            session: SessionConfig::default(),
+            limits: RequestLimits::default(),
+            lockout: LockoutConfig::default(),
+            trusted_proxies: TrustedProxies::default(),
+            quotas: Quotas::default(),
+            retention: RetentionConfig::default(),
+            balance_check: BalanceCheckConfig::default(),
+            backup: BackupConfig::default(),
+            secret_key: None,
+            event_key: None,
+            mailer: Arc::new(LogMailer),
+            bank_feeds: BankFeeds::new(Vec::new()),
+            oidc: None,
+            clock: Clock::default(),
+            ids: IdGenerator::default(),
+            background_tasks: true,
+        }
+    }
+
+    /// configures everything from the environment variables documented in the readme
+    pub async fn from_env() -> Result<Self, ServerError> {
+        let database_url = env::var("DATABASE_URL").map_err(|_| ServerError::MissingDatabaseUrl)?;
+        let app_env = AppEnv::from_env()?;
+
+        let mut server = Self::new(database_url)
+            .app_env(app_env)
+            .database(DatabaseConfig::from_env()?)
+            .session(SessionConfig::from_env()?)
+            .limits(RequestLimits::from_env()?)
+            .lockout(LockoutConfig::from_env()?)
+            .trusted_proxies(TrustedProxies::from_env()?)
+            .quotas(Quotas::from_env()?)
+            .retention(RetentionConfig::from_env()?)
+            .balance_check(BalanceCheckConfig::from_env()?)
+            .backup(BackupConfig::from_env()?)
+            .secret_key(SecretKey::from_env(app_env)?)
+            .mailer(mailer::from_env()?)
+            .bank_feeds(BankFeeds::new(vec![Arc::new(GoCardless::from_env())]));
+
+        if let Ok(event_key) = env::var("EVENT_ENCRYPTION_KEY") {
+            server = server.event_encryption_key(
+                SecretKey::from_hex(&event_key).map_err(|_| ServerError::EventEncryptionKey)?,
+            );
+        }
+
+        if let Ok(replica_url) = env::var("DATABASE_REPLICA_URL") {
+            server = server.replica_url(replica_url);
+        }
+
+        if let Ok(site_root) = env::var("SITE_ROOT") {
+            server = server.site_root(site_root);
+        }
+
+        if let Some(oidc) = Oidc::from_env(&authn::base_url()).await? {
+            server = server.oidc(oidc);
+        }
+
+        Ok(server)
+    }
+
+    /// development seeds demo users and allows dev login
+    pub fn app_env(mut self, app_env: AppEnv) -> Self {
+        self.app_env = app_env;
+        self
+    }
+
+    /// a read only replica of the database for the journal listings and reports. it can lag a
+    /// moment behind, so everything else stays on the primary
+    pub fn replica_url(mut self, replica_url: impl Into<String>) -> Self {
+        self.replica_url = Some(replica_url.into());
+        self
+    }
+
+    /// the pool sizes and timeouts, and how long to wait for postgres to come up
+    pub fn database(mut self, database: DatabaseConfig) -> Self {
+        self.database = database;
+        self
+    }
+
+    /// where the built stylesheet lives, under `pkg/`
+    pub fn site_root(mut self, site_root: impl Into<String>) -> Self {
+        self.site_root = site_root.into();
+        self
+    }
+
+    pub fn session(mut self, session: SessionConfig) -> Self {
+        self.session = session;
+        self
+    }
+
+    pub fn limits(mut self, limits: RequestLimits) -> Self {
+        self.limits = limits;
+        self
+    }
+
+    pub fn lockout(mut self, lockout: LockoutConfig) -> Self {
+        self.lockout = lockout;
+        self
+    }
+
+    /// how many proxies in front of the server to believe about where a request came from
+    pub fn trusted_proxies(mut self, trusted_proxies: TrustedProxies) -> Self {
+        self.trusted_proxies = trusted_proxies;
+        self
+    }
+
+    pub fn quotas(mut self, quotas: Quotas) -> Self {
+        self.quotas = quotas;
+        self
+    }
+
+    pub fn retention(mut self, retention: RetentionConfig) -> Self {
+        self.retention = retention;
+        self
+    }
+
+    pub fn balance_check(mut self, balance_check: BalanceCheckConfig) -> Self {
+        self.balance_check = balance_check;
+        self
+    }
+
+    pub fn backup(mut self, backup: BackupConfig) -> Self {
+        self.backup = backup;
+        self
+    }
+
+    /// without one, development uses its built in key and production refuses to start
+    pub fn secret_key(mut self, secret_key: SecretKey) -> Self {
+        self.secret_key = Some(secret_key);
+        self
+    }
+
+    /// encrypts the journal event payloads at rest. payloads written without it stay readable,
+    /// but once it's set it must be kept, or the encrypted ones can't be read again
+    pub fn event_encryption_key(mut self, event_key: SecretKey) -> Self {
+        self.event_key = Some(event_key);
+        self
+    }
+
+    pub fn mailer(mut self, mailer: Arc<dyn Mailer>) -> Self {
+        self.mailer = mailer;
+        self
+    }
+
+    pub fn bank_feeds(mut self, bank_feeds: BankFeeds) -> Self {
+        self.bank_feeds = bank_feeds;
+        self
+    }
+
+    pub fn oidc(mut self, oidc: Oidc) -> Self {
+        self.oidc = Some(oidc);
+        self
+    }
+
+    /// what the app takes the time to be. tests can pass a [`crate::MockClock`] to move it along
+    pub fn clock(mut self, clock: impl Into<Clock>) -> Self {
+        self.clock = clock.into();
+        self
+    }
+
+    /// where new ids come from. tests can pass a [`crate::SequentialIdProvider`] to know them
+    /// up front
+    pub fn ids(mut self, ids: impl Into<IdGenerator>) -> Self {
+        self.ids = ids.into();
+        self
+    }
+
+    /// whether to run the periodic jobs, like digests, bank feed syncs and the retention purge.
+    /// test suites usually turn them off. the projections are kept up to date either way
+    pub fn background_tasks(mut self, background_tasks: bool) -> Self {
+        self.background_tasks = background_tasks;
+        self
+    }
+
+    async fn connect(&self) -> Result<Services, ServerError> {
+        let database_url = &self.database_url;
+
+        // postgres may still be starting, as when it comes up alongside the server in compose
+        self.database
+            .retry("connect to the database", || async move {
+                let public_pool = PgPool::connect(database_url).await?;
+
+                sqlx::query!("CREATE SCHEMA IF NOT EXISTS authz")
+                    .execute(&public_pool)
+                    .await?;
+
+                sqlx::query!("CREATE SCHEMA IF NOT EXISTS authn")
+                    .execute(&public_pool)
+                    .await?;
+
+                sqlx::query!("CREATE SCHEMA IF NOT EXISTS journal")
+                    .execute(&public_pool)
+                    .await?;
+
+                Ok::<_, sqlx::Error>(())
+            })
+            .await?;
+
+        let authn_pool = self
+            .database
+            .pool_options()
+            .after_connect(|connection, _| {
+                Box::pin(async move {
+                    sqlx::query!("SET search_path TO authn")
+                        .execute(connection)
+                        .await?;
+                    Ok(())
+                })
+            })
+            .connect(&self.database_url)
+            .await?;
+
+        let session_store = PostgresStore::new(authn_pool.clone());
+        self.database
+            .retry("migrate the session store", || session_store.migrate())
+            .await
+            .map_err(|e| ServerError::SessionStore(e.to_string()))?;
+
+        let authn_event_store = AuthnEventStore::try_new(authn_pool.clone()).await?;
+
+        let journal_pool = self
+            .database
+            .pool_options()
+            .after_connect(|connection, _| {
+                Box::pin(async move {
+                    sqlx::query!("SET search_path TO journal")
+                        .execute(connection)
+                        .await?;
+                    Ok(())
+                })
+            })
+            .connect(&self.database_url)
+            .await?;
+
+        let journal_event_store = JournalEventStore::try_new(
+            journal_pool.clone(),
+            JournalPayloads::new(self.event_key.clone()),
+        )
+        .await?;
+
+        let secret_key = match &self.secret_key {
+            Some(secret_key) => secret_key.clone(),
+            None => SecretKey::from_env(self.app_env)?,
+        };
+
+        let secrets = SecretStore::try_new(journal_pool.clone(), secret_key).await?;
+
+        let authn_service = AuthnService::try_new(
+            authn_pool,
+            &authn_event_store,
+            self.lockout,
+            self.clock.clone(),
+            self.ids.clone(),
+            secrets.clone(),
+        )
+        .await?;
+
+        let mut journal_service = JournalService::try_new(
+            journal_pool,
+            journal_event_store.clone(),
+            self.quotas,
+            self.retention,
+            secrets,
+            self.clock.clone(),
+        )
+        .await?;
+
+        if let Some(replica_url) = &self.replica_url {
+            let replica_pool = self
+                .database
+                .pool_options()
+                .after_connect(|connection, _| {
+                    Box::pin(async move {
+                        sqlx::query!("SET search_path TO journal")
+                            .execute(connection)
+                            .await?;
+                        Ok(())
+                    })
+                })
+                .connect(replica_url)
+                .await?;
+
+            journal_service = journal_service.with_replica(replica_pool);
+        }
+
+        // Disintegrate uses unqualified object names and cannot target a schema directly, so
+        // authz needs a schema-scoped pool. Ideally, the backend would qualify its objects with
+        // a configured schema, allowing isolated event stores to share a pool.
+        let authz_pool = self
+            .database
+            .pool_options()
+            .after_connect(|connection, _| {
+                Box::pin(async move {
+                    sqlx::query!("SET search_path TO authz")
+                        .execute(connection)
+                        .await?;
+                    Ok(())
+                })
+            })
+            .connect(&self.database_url)
+            .await?;
+
+        let authz_event_store = AuthzEventStore::try_new(authz_pool.clone()).await?;
+
+        let role_index = RoleIndex::try_new(authz_pool, authz_event_store.clone()).await?;
+
+        let authz_service = AuthzService::new(
+            authz_event_store,
+            role_index,
+            self.clock.clone(),
+            self.ids.clone(),
+        );
+
+        Ok(Services {
+            session_store,
+            authn_event_store,
+            authn_service,
+            journal_event_store,
+            journal_service,
+            authz_service,
+        })
+    }
+
+    /// replays the journal events into fresh projections, without serving anything
+    pub async fn rebuild_projections(self) -> Result<(), ServerError> {
+        let services = self.connect().await?;
+
+        services
+            .journal_service
+            .rebuild_projections(&services.journal_event_store.event_store)
+            .await?
+            .log();
+
+        Ok(())
+    }
+
+    /// restores one journal from a backup written by the backup job, projecting its events
+    /// without delivering webhooks or notifications, then checks the restored balances
+    pub async fn restore_journal(
+        self,
+        archive: &Path,
+        journal_id: &str,
+    ) -> Result<(), ServerError> {
+        let journal_id = JournalId::from_str(journal_id)
+            .map_err(|_| RestoreError::InvalidJournalId(journal_id.to_string()))?;
+        let archive = tokio::fs::read_to_string(archive).await?;
+        let services = self.connect().await?;
+
+        tokio::spawn(journal::domain::projection_listener(
+            services.journal_event_store.clone(),
+            services.journal_service.clone(),
+        ));
+
+        journal::restore::restore_journal(
+            &services.journal_service,
+            &services.journal_event_store.event_store,
+            &self.ids,
+            &archive,
+            journal_id,
+        )
+        .await?
+        .log();
+
+        Ok(())
+    }
+
+    /// connects the stores, starts the projections and background jobs, and returns the app.
+    /// the router can be served, or driven directly by a test suite
+    pub async fn router(self) -> Result<Router, ServerError> {
+        self.build(Router::new()).await.map(|(app, _)| app)
+    }
+
+    /// the app with extra routes behind its middleware, along with the state it was built on
+    pub(crate) async fn build(
+        self,
+        routes: Router<StateType>,
+    ) -> Result<(Router, AppState), ServerError> {
+        let Services {
+            session_store,
+            authn_event_store,
+            authn_service,
+            journal_event_store,
+            journal_service,
+            authz_service,
+        } = self.connect().await?;
+
+        let session_layer = self.session.layer(session_store.clone());
+
+        tokio::spawn(authn::event_listener(
+            authn_event_store,
+            authn_service.clone(),
+        ));
+
+        let backup_event_store = journal_event_store.event_store.clone();
+
+        tokio::spawn(journal_service.clone().follow_journal_changes());
+
+        tokio::spawn(journal::domain::event_listener(
+            journal_event_store,
+            journal_service.clone(),
+            authn_service.clone(),
+            self.mailer.clone(),
+        ));
+
+        if self.background_tasks {
+            tokio::spawn(session::delete_expired_sessions(
+                session_store,
+                self.session.cleanup_interval,
+            ));
+
+            tokio::spawn(journal::consistency::check_balances(
+                journal_service.clone(),
+                self.balance_check,
+            ));
+
+            tokio::spawn(journal::retention::purge_deleted_journals(
+                journal_service.clone(),
+                self.retention,
+            ));
+
+            tokio::spawn(journal::backup::back_up_journals(
+                journal_service.clone(),
+                backup_event_store,
+                self.backup,
+            ));
+
+            tokio::spawn(journal::notification::digest::send_digests(
+                authn_service.clone(),
+                journal_service.clone(),
+            ));
+
+            tokio::spawn(journal::bankfeed::sync::sync_bank_feeds(
+                journal_service.clone(),
+                self.bank_feeds.clone(),
+            ));
+        }
+
+        let state = AppState::new(
+            authn_service.clone(),
+            authz_service,
+            journal_service,
+            self.bank_feeds,
+            self.clock.clone(),
+            self.ids,
+        );
+
+        if self.app_env.dev_mode() {
+            seed_dev_data(&state).await.map_err(ServerError::Seed)?;
+        }
+
+        // use the service's user_store so that the data syncs
+        let auth_layer = AuthManagerLayerBuilder::new(authn_service.clone(), session_layer).build();
+
+        let webauthn_routes = authn::router(authn_service, self.app_env, self.oidc)?;
+
+        let journal_routes = journal::router()
+            .merge(account::router())
+            .merge(transaction::router())
+            .merge(journal::webhook::router())
+            .merge(journal::rule::router())
+            .merge(journal::template::router())
+            .merge(journal::comment::router())
+            .merge(journal::bankfeed::router())
+            .merge(
+                transaction::import_router()
+                    .merge(journal::statement::router())
+                    .layer(DefaultBodyLimit::max(self.limits.import_body)),
+            )
+            .merge(journal::transfer::router())
+            .merge(journal::notification::router())
+            .merge(authz::router())
+            .layer(middleware::from_fn_with_state(
+                state.clone(),
+                journal::etag::conditional_get,
+            ));
+
+        let api_routes = openapi::router();
+        #[cfg(feature = "graphql")]
+        let api_routes = api_routes.merge(crate::graphql::router());
+
+        let app = Router::new()
+            .route("/favicon.ico", get(serve_favicon))
+            .route("/logo.svg", get(serve_logo))
+            .route("/base64.js", get(serve_base64))
+            .route("/shortcuts.js", get(serve_shortcuts))
+            .merge(asset::router(&self.site_root, self.app_env))
+            .route("/", get(Redirect::to("/dashboard")))
+            .merge(webauthn_routes)
+            .merge(journal_routes)
+            .merge(api_routes)
+            .merge(routes)
+            .fallback(notfoundpage::not_found_page)
+            .layer(middleware::from_fn(monkesto_error::json_errors))
+            .layer(middleware::from_fn(i18n::set_locale))
+            .layer(middleware::from_fn(theme::set_theme))
+            .layer(middleware::from_fn(shortcuts::set_shortcuts))
+            .layer(middleware::from_fn_with_state(
+                (self.session, self.clock),
+                session::enforce_absolute_expiry,
+            ))
+            .layer(auth_layer)
+            .layer(Extension(self.trusted_proxies))
+            .layer(DefaultBodyLimit::max(self.limits.body))
+            .layer(TimeoutLayer::with_status_code(
+                StatusCode::REQUEST_TIMEOUT,
+                self.limits.timeout,
+            ))
+            .layer(TraceLayer::new_for_http().on_response(
+                |response: &Response<_>, latency: Duration, _span: &Span| {
+                    tracing::info!(
+                        status = %response.status(),
+                        latency_μs = latency.as_micros(),
+                        "response"
+                    );
+                },
+            ));
+
+        Ok((app.with_state(state.clone()), state))
+    }
+
+    /// serves the app on the address until interrupted
+    pub async fn serve(self, addr: &str) -> Result<(), ServerError> {
+        let app = self.router().await?;
+
+        println!("listening on http://{}", addr);
+        let listener = tokio::net::TcpListener::bind(addr).await?;
+
+        // signin attempts record the client's address when no trusted proxy has forwarded it
+        axum::serve(
+            listener,
+            app.into_make_service_with_connect_info::<SocketAddr>(),
+        )
+        .with_graceful_shutdown(shutdown())
+        .await?;
+
+        Ok(())
+    }
+}
+
+async fn serve_favicon() -> impl IntoResponse {
+    const FAVICON_BYTES: &[u8] = include_bytes!("favicon.ico");
+    (
+        StatusCode::OK,
+        [(header::CONTENT_TYPE, "image/x-icon")],
+        FAVICON_BYTES,
+    )
+}
+
+async fn serve_logo() -> impl IntoResponse {
+    const LOGO_SVG: &str = include_str!("logo.svg");
+    (
+        StatusCode::OK,
+        [(header::CONTENT_TYPE, "image/svg+xml")],
+        LOGO_SVG,
+    )
+}
+
+async fn serve_base64() -> impl IntoResponse {
+    const BASE64_JS: &str = include_str!("base64.js");
+    (
+        StatusCode::OK,
+        [(header::CONTENT_TYPE, "text/javascript")],
+        BASE64_JS,
+    )
+}
+
+async fn serve_shortcuts() -> impl IntoResponse {
+    const SHORTCUTS_JS: &str = include_str!("shortcuts.js");
+    (
+        StatusCode::OK,
+        [(header::CONTENT_TYPE, "text/javascript")],
+        SHORTCUTS_JS,
+    )
+}
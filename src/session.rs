@@ -0,0 +1,159 @@
+use crate::time_provider::{Clock, TimeProvider};
+use axum::extract::Request;
+use axum::extract::State;
+use axum::middleware::Next;
+use axum::response::Response;
+use axum_login::tracing;
+use std::env;
+use std::time::Duration;
+use thiserror::Error;
+use tower_sessions::cookie::SameSite;
+use tower_sessions::cookie::time;
+use tower_sessions::session_store::ExpiredDeletion;
+use tower_sessions::{Expiry, Session, SessionManagerLayer, SessionStore};
+
+const SESSION_CREATED_AT_KEY: &str = "monkesto.created_at";
+
+#[derive(Debug, Error)]
+pub enum SessionConfigError {
+    #[error("{0} must be a whole number of seconds, found {1}")]
+    Seconds(&'static str, String),
+    #[error("{0} must be true or false, found {1}")]
+    Bool(&'static str, String),
+    #[error("SESSION_COOKIE_SAME_SITE must be strict, lax, or none, found {0}")]
+    SameSite(String),
+}
+
+/// cookie and expiry settings for user sessions, read from the environment
+#[derive(Clone, Debug)]
+pub struct SessionConfig {
+    pub cookie_name: String,
+    pub secure: bool,
+    pub same_site: SameSite,
+    /// sessions expire after this long without a request
+    pub idle_timeout: Duration,
+    /// sessions expire this long after they were created, regardless of activity
+    pub absolute_timeout: Duration,
+    /// how often expired sessions are deleted from the store
+    pub cleanup_interval: Duration,
+}
+
+impl Default for SessionConfig {
+    fn default() -> Self {
+        Self {
+            cookie_name: "monkesto.sid".to_string(),
+            secure: true,
+            same_site: SameSite::Lax,
+            idle_timeout: Duration::from_secs(60 * 60 * 24 * 7),
+            absolute_timeout: Duration::from_secs(60 * 60 * 24 * 30),
+            cleanup_interval: Duration::from_secs(60 * 60),
+        }
+    }
+}
+
+fn seconds_from_env(key: &'static str, default: Duration) -> Result<Duration, SessionConfigError> {
+    match env::var(key) {
+        Ok(value) => value
+            .parse::<u64>()
+            .map(Duration::from_secs)
+            .map_err(|_| SessionConfigError::Seconds(key, value)),
+        Err(_) => Ok(default),
+    }
+}
+
+impl SessionConfig {
+    pub fn from_env() -> Result<Self, SessionConfigError> {
+        let default = Self::default();
+
+        let secure = match env::var("SESSION_COOKIE_SECURE") {
+            Ok(value) => value
+                .parse::<bool>()
+                .map_err(|_| SessionConfigError::Bool("SESSION_COOKIE_SECURE", value))?,
+            Err(_) => default.secure,
+        };
+
+        let same_site = match env::var("SESSION_COOKIE_SAME_SITE") {
+            Ok(value) => match value.to_lowercase().as_str() {
+                "strict" => SameSite::Strict,
+                "lax" => SameSite::Lax,
+                "none" => SameSite::None,
+                _ => return Err(SessionConfigError::SameSite(value)),
+            },
+            Err(_) => default.same_site,
+        };
+
+        Ok(Self {
+            cookie_name: env::var("SESSION_COOKIE_NAME").unwrap_or(default.cookie_name),
+            secure,
+            same_site,
+            idle_timeout: seconds_from_env("SESSION_IDLE_TIMEOUT_SECS", default.idle_timeout)?,
+            absolute_timeout: seconds_from_env(
+                "SESSION_ABSOLUTE_TIMEOUT_SECS",
+                default.absolute_timeout,
+            )?,
+            cleanup_interval: seconds_from_env(
+                "SESSION_CLEANUP_INTERVAL_SECS",
+                default.cleanup_interval,
+            )?,
+        })
+    }
+
+    pub fn layer<S: SessionStore + Clone>(&self, store: S) -> SessionManagerLayer<S> {
+        SessionManagerLayer::new(store)
+            .with_name(self.cookie_name.clone())
+            .with_secure(self.secure)
+            .with_same_site(self.same_site)
+            .with_http_only(true)
+            .with_expiry(Expiry::OnInactivity(time::Duration::seconds(
+                self.idle_timeout.as_secs() as i64,
+            )))
+    }
+}
+
+/// ends sessions that have outlived the absolute timeout, even if they were active.
+/// this must run inside the session layer
+pub async fn enforce_absolute_expiry(
+    State((config, clock)): State<(SessionConfig, Clock)>,
+    session: Session,
+    request: Request,
+    next: Next,
+) -> Response {
+    // anonymous sessions aren't persisted, so there's nothing to expire
+    if !session.is_empty().await {
+        let now = clock.get_time().timestamp();
+
+        match session.get::<i64>(SESSION_CREATED_AT_KEY).await {
+            Ok(Some(created_at)) if now - created_at > config.absolute_timeout.as_secs() as i64 => {
+                if let Err(error) = session.flush().await {
+                    tracing::error!(?error, "failed to flush an expired session");
+                }
+            }
+            Ok(Some(_)) => {}
+            Ok(None) => {
+                if let Err(error) = session.insert(SESSION_CREATED_AT_KEY, now).await {
+                    tracing::error!(?error, "failed to record the session creation time");
+                }
+            }
+            Err(error) => tracing::error!(?error, "failed to read the session creation time"),
+        }
+    }
+
+    next.run(request).await
+}
+
+/// periodically removes expired sessions so the session table doesn't grow without bound
+pub async fn delete_expired_sessions<S: ExpiredDeletion>(store: S, interval: Duration) {
+    let mut ticker = tokio::time::interval(interval);
+    let mut shutdown = Box::pin(crate::shutdown());
+
+    loop {
+        tokio::select! {
+            _ = ticker.tick() => {
+                if let Err(error) = store.delete_expired().await {
+                    tracing::error!(?error, "failed to delete expired sessions");
+                }
+            }
+            _ = &mut shutdown => return,
+        }
+    }
+}
@@ -0,0 +1,84 @@
+// listens for the keyboard shortcuts the page lists in #shortcuts. a shortcut is one or two
+// keys pressed in turn, and the second has to follow the first before the sequence is dropped
+(function () {
+    var config = document.getElementById('shortcuts');
+    if (!config) {
+        return;
+    }
+
+    var bindings = JSON.parse(config.textContent);
+    var marker = document.querySelector('[data-journal-id]');
+    var journal = marker ? marker.getAttribute('data-journal-id') : null;
+
+    var SEQUENCE_TIMEOUT = 1500;
+    var pressed = [];
+    var timer = null;
+
+    function typing(target) {
+        return target.isContentEditable ||
+            ['INPUT', 'TEXTAREA', 'SELECT'].indexOf(target.tagName) !== -1;
+    }
+
+    // whether the keys are a shortcut or the start of one
+    function matching(sequence) {
+        return bindings.some(function (binding) {
+            return binding.keys === sequence || binding.keys.indexOf(sequence + ' ') === 0;
+        });
+    }
+
+    function run(binding) {
+        if (binding.dialog) {
+            var dialog = document.getElementById(binding.dialog);
+            if (dialog && !dialog.open) {
+                dialog.showModal();
+            }
+            return;
+        }
+
+        var field = binding.focus ? document.querySelector(binding.focus) : null;
+        if (field) {
+            field.focus();
+            return;
+        }
+
+        if (binding.href.indexOf('{journal}') !== -1) {
+            if (!journal) {
+                return;
+            }
+            window.location.href = binding.href.replace('{journal}', journal);
+        } else {
+            window.location.href = binding.href;
+        }
+    }
+
+    document.addEventListener('keydown', function (event) {
+        if (event.ctrlKey || event.metaKey || event.altKey || typing(event.target) ||
+            event.key.length !== 1) {
+            return;
+        }
+
+        clearTimeout(timer);
+        pressed.push(event.key);
+        // a key that doesn't carry on the sequence may start one of its own
+        if (!matching(pressed.join(' '))) {
+            pressed = [event.key];
+        }
+
+        var sequence = pressed.join(' ');
+        var match = bindings.find(function (binding) {
+            return binding.keys === sequence;
+        });
+
+        if (match) {
+            pressed = [];
+            event.preventDefault();
+            run(match);
+        } else if (matching(sequence)) {
+            timer = setTimeout(function () {
+                pressed = [];
+            }, SEQUENCE_TIMEOUT);
+        } else {
+            pressed = [];
+        }
+    });
+})();
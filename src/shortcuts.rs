@@ -0,0 +1,146 @@
+use crate::BackendType;
+use crate::authn::shortcuts::{ShortcutAction, Shortcuts};
+use crate::i18n::t;
+use axum::extract::Request;
+use axum::middleware::Next;
+use axum::response::Response;
+use axum_login::AuthSession;
+use maud::{Markup, PreEscaped, html};
+use serde::Serialize;
+
+tokio::task_local! {
+    static SHORTCUTS: Shortcuts;
+}
+
+/// the shortcuts of the user being served, or the defaults outside of a request
+pub fn current_shortcuts() -> Shortcuts {
+    SHORTCUTS.try_with(Shortcuts::clone).unwrap_or_default()
+}
+
+/// handles the rest of the request with the signed in user's shortcuts
+pub async fn set_shortcuts(
+    session: AuthSession<BackendType>,
+    request: Request,
+    next: Next,
+) -> Response {
+    let shortcuts = session
+        .user
+        .map(|user| user.shortcuts())
+        .unwrap_or_default();
+
+    SHORTCUTS.scope(shortcuts, next.run(request)).await
+}
+
+const HELP_DIALOG: &str = "shortcut-help";
+
+/// what shortcuts.js does when an action's keys are pressed. `{journal}` in a link is the
+/// journal the page belongs to, and links that need one do nothing on other pages
+#[derive(Serialize)]
+struct Binding<'a> {
+    keys: &'a str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    href: Option<&'static str>,
+    /// a field to focus instead of following the link, when the page has one
+    #[serde(skip_serializing_if = "Option::is_none")]
+    focus: Option<&'static str>,
+    /// a dialog to open
+    #[serde(skip_serializing_if = "Option::is_none")]
+    dialog: Option<&'static str>,
+}
+
+impl<'a> Binding<'a> {
+    fn new(action: ShortcutAction, keys: &'a str) -> Self {
+        let (href, focus, dialog) = match action {
+            ShortcutAction::Dashboard => (Some("/dashboard"), None, None),
+            ShortcutAction::Journals => (Some("/journal"), None, None),
+            ShortcutAction::Notifications => (Some("/notifications"), None, None),
+            ShortcutAction::Search => (
+                Some("/journal/{journal}/search"),
+                Some("input[type=search]"),
+                None,
+            ),
+            ShortcutAction::NewTransaction => (
+                Some("/journal/{journal}/transaction#transaction-form"),
+                None,
+                None,
+            ),
+            ShortcutAction::QuickEntry => (Some("/journal/{journal}/quick"), None, None),
+            ShortcutAction::Help => (None, None, Some(HELP_DIALOG)),
+        };
+
+        Self {
+            keys,
+            href,
+            focus,
+            dialog,
+        }
+    }
+}
+
+/// the signed in user's shortcuts for shortcuts.js to listen for, and the overlay that lists
+/// them. nothing is written when they've turned shortcuts off
+pub fn shortcut_layer() -> Markup {
+    let shortcuts = current_shortcuts();
+
+    let picked: Vec<(ShortcutAction, &str)> = ShortcutAction::ALL
+        .into_iter()
+        .filter_map(|action| Some((action, shortcuts.keys(action)?)))
+        .collect();
+
+    if picked.is_empty() {
+        return html! {};
+    }
+
+    let bindings: Vec<Binding> = picked
+        .iter()
+        .map(|(action, keys)| Binding::new(*action, keys))
+        .collect();
+    // keys are picked by the user, so they mustn't be able to close the script element
+    let bindings = serde_json::to_string(&bindings)
+        .unwrap_or_else(|_| "[]".to_string())
+        .replace("</", "<\\/");
+
+    html! {
+        script type="application/json" id="shortcuts" { (PreEscaped(bindings)) }
+        dialog
+        id=(HELP_DIALOG)
+        aria-labelledby="shortcut-help-title"
+        class="rounded-xl p-6 w-full max-w-sm bg-white dark:bg-gray-800 text-gray-900 dark:text-white border border-gray-200 dark:border-gray-700 shadow-xl backdrop:bg-gray-900/50" {
+            h2 id="shortcut-help-title" class="text-lg font-semibold mb-4" {
+                (t("shortcut.title"))
+            }
+            dl class="space-y-2 text-sm" {
+                @for (action, keys) in &picked {
+                    div class="flex justify-between gap-4" {
+                        dt class="text-gray-600 dark:text-gray-300" { (t(action.label())) }
+                        dd class="flex gap-1" {
+                            @for key in keys.split(' ') {
+                                kbd class="px-1.5 py-0.5 rounded border border-gray-300 dark:border-gray-600 bg-gray-50 dark:bg-gray-700 font-mono text-xs" {
+                                    (key)
+                                }
+                            }
+                        }
+                    }
+                }
+            }
+            p class="mt-4 text-xs text-gray-500 dark:text-gray-400" {
+                (t("shortcut.journal_only"))
+            }
+            div class="mt-6 flex justify-between items-center" {
+                a
+                href="/me#shortcuts"
+                class="text-sm font-medium text-indigo-600 hover:text-indigo-500 dark:text-indigo-400 dark:hover:text-indigo-300" {
+                    (t("shortcut.customise"))
+                }
+                form method="dialog" {
+                    button
+                    type="submit"
+                    class="rounded-md bg-indigo-600 px-3 py-1.5 text-sm font-semibold text-white hover:bg-indigo-500" {
+                        (t("shortcut.close"))
+                    }
+                }
+            }
+        }
+        script src="/shortcuts.js" {}
+    }
+}
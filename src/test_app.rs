@@ -0,0 +1,497 @@
+//! boots the whole app in process for tests that go through its routes. the event stores need
+//! postgres, so the tests run against `TEST_DATABASE_URL`. they're skipped when it isn't set,
+//! except in CI, where skipping would pass them without running anything. each app gets a
+//! database of its own, since a finished test's listeners can keep holding their locks, and
+//! the database is dropped along with the app
+
+use crate::app_env::AppEnv;
+use crate::authn::AuthSession;
+use crate::authn::user::UserId;
+use crate::authority::{Actor, Authority};
+use crate::email::Email;
+use crate::journal::account::AccountId;
+use crate::journal::{JournalId, Permissions};
+use crate::mailer::{Mailer, MailerError};
+use crate::name::Name;
+use crate::secret::SecretKey;
+use crate::seed::{Fixture, seed};
+use crate::server::Server;
+use crate::session::SessionConfig;
+use crate::time_provider::TimeProvider;
+use crate::{AppState, StateType};
+use async_trait::async_trait;
+use axum::Router;
+use axum::extract::{Path, State};
+use axum::http::StatusCode;
+use axum::http::header::ACCEPT;
+use axum::routing::post;
+use axum_test::{TestResponse, TestServer};
+use serde_json::Value;
+use sqlx::PgPool;
+use std::env;
+use std::str::FromStr;
+use std::sync::{Arc, Mutex};
+use url::Url;
+use webauthn_rs::prelude::Uuid;
+
+pub struct TestApp {
+    router: Router,
+    pub state: AppState,
+    pub mailer: Arc<RecordingMailer>,
+    // last, so the app is dropped before its database
+    _database: TestDatabase,
+}
+
+/// keeps the emails the app sends instead of sending them
+#[derive(Default)]
+pub struct RecordingMailer {
+    sent: Mutex<Vec<(Email, String, String)>>,
+}
+
+impl RecordingMailer {
+    /// the subject and body of every email sent to the address so far
+    pub fn sent_to(&self, to: &Email) -> Vec<(String, String)> {
+        self.sent
+            .lock()
+            .expect("the mailer's lock was poisoned")
+            .iter()
+            .filter(|(recipient, _, _)| recipient == to)
+            .map(|(_, subject, body)| (subject.clone(), body.clone()))
+            .collect()
+    }
+}
+
+#[async_trait]
+impl Mailer for RecordingMailer {
+    async fn send(&self, to: &Email, subject: &str, body: String) -> Result<(), MailerError> {
+        self.sent
+            .lock()
+            .expect("the mailer's lock was poisoned")
+            .push((to.clone(), subject.to_string(), body));
+        Ok(())
+    }
+}
+
+/// a signed in user, with their own cookies
+pub struct TestUser {
+    pub id: UserId,
+    pub email: Email,
+    pub client: TestServer,
+}
+
+/// an empty database made for one app, which is dropped with it
+struct TestDatabase {
+    server_url: String,
+    name: String,
+}
+
+impl TestDatabase {
+    /// creates the database on the server in the url
+    async fn create(server_url: &str) -> Self {
+        let name = format!("monkesto_test_{}", Uuid::new_v4().simple());
+
+        let pool = PgPool::connect(server_url)
+            .await
+            .expect("failed to connect to the test database");
+        sqlx::raw_sql(&format!(r#"CREATE DATABASE "{name}""#))
+            .execute(&pool)
+            .await
+            .expect("failed to create a database for the test");
+        pool.close().await;
+
+        Self {
+            server_url: server_url.to_string(),
+            name,
+        }
+    }
+
+    fn url(&self) -> String {
+        let mut url = Url::parse(&self.server_url).expect("TEST_DATABASE_URL isn't a url");
+        url.set_path(&self.name);
+        url.into()
+    }
+}
+
+impl Drop for TestDatabase {
+    fn drop(&mut self) {
+        let (server_url, name) = (self.server_url.clone(), self.name.clone());
+
+        // drop can't await, so the database is dropped from a runtime of its own. the app's
+        // listeners may still be connected, which FORCE ends
+        let dropped = std::thread::spawn(move || {
+            tokio::runtime::Builder::new_current_thread()
+                .enable_all()
+                .build()
+                .expect("failed to start a runtime")
+                .block_on(async {
+                    let pool = PgPool::connect(&server_url).await?;
+                    sqlx::raw_sql(&format!(r#"DROP DATABASE "{name}" WITH (FORCE)"#))
+                        .execute(&pool)
+                        .await?;
+                    pool.close().await;
+                    Ok::<_, sqlx::Error>(())
+                })
+        })
+        .join();
+
+        if !matches!(dropped, Ok(Ok(()))) {
+            eprintln!("failed to drop the test database {}", self.name);
+        }
+    }
+}
+
+/// signs in anyone, since test users have no passkey to sign in with
+async fn signin(
+    State(state): State<StateType>,
+    mut auth_session: AuthSession,
+    Path(user_id): Path<String>,
+) -> StatusCode {
+    let Ok(user_id) = UserId::from_str(&user_id) else {
+        return StatusCode::BAD_REQUEST;
+    };
+
+    match state.authn_service.fetch_user(user_id).await {
+        Ok(user) if auth_session.login(&user).await.is_ok() => StatusCode::NO_CONTENT,
+        _ => StatusCode::UNAUTHORIZED,
+    }
+}
+
+impl TestApp {
+    /// the app, or none when there's no database to run it on
+    pub async fn spawn() -> Option<Self> {
+        let Ok(database_url) = env::var("TEST_DATABASE_URL") else {
+            assert!(
+                env::var_os("CI").is_none(),
+                "TEST_DATABASE_URL must be set in CI"
+            );
+            eprintln!("TEST_DATABASE_URL isn't set, skipping");
+            return None;
+        };
+
+        let mailer = Arc::new(RecordingMailer::default());
+        let database = TestDatabase::create(&database_url).await;
+
+        let (router, state) = Server::new(database.url())
+            .session(SessionConfig {
+                // the test client talks plain http
+                secure: false,
+                ..SessionConfig::default()
+            })
+            .secret_key(
+                SecretKey::from_env(AppEnv::Development).expect("failed to get a secret key"),
+            )
+            .background_tasks(false)
+            .mailer(mailer.clone())
+            .build(Router::new().route("/test/signin/{user_id}", post(signin)))
+            .await
+            .expect("failed to build the app");
+
+        Some(Self {
+            router,
+            state,
+            mailer,
+            _database: database,
+        })
+    }
+
+    /// the app behind a real socket, for clients that can't go through the router directly
+    #[cfg(feature = "client")]
+    pub fn serve_http(&self) -> TestServer {
+        TestServer::builder()
+            .http_transport()
+            .build(self.router.clone())
+    }
+
+    /// a client that isn't signed in
+    pub fn anonymous(&self) -> TestServer {
+        TestServer::builder()
+            .save_cookies()
+            .build(self.router.clone())
+    }
+
+    /// creates a user with a unique email and signs them in. errors come back as json, so
+    /// they can be checked without parsing pages
+    pub async fn signup(&self, label: &str) -> TestUser {
+        let id: UserId = self.state.ids.generate();
+        let email =
+            Email::try_new(format!("{label}-{id}@example.com")).expect("failed to make an email");
+
+        let event_id = self
+            .state
+            .authn_service
+            .create_user(
+                id,
+                email.clone(),
+                Uuid::new_v4(),
+                Authority::Direct(Actor::System),
+                self.state.clock.get_time(),
+            )
+            .await
+            .expect("failed to create the user");
+        self.state.authn_service.wait_for(event_id).await;
+
+        let mut client = self.anonymous();
+        client.add_header(ACCEPT, "application/json");
+        client
+            .post(&format!("/test/signin/{id}"))
+            .await
+            .assert_status(StatusCode::NO_CONTENT);
+
+        TestUser { id, email, client }
+    }
+
+    /// seeds the fixture's users and journals, as the dev data is seeded
+    pub async fn seed(&self, fixture: &Fixture) {
+        seed(&self.state, fixture)
+            .await
+            .expect("failed to seed the fixture");
+    }
+
+    /// the journal's accounts, as the system sees them
+    pub async fn accounts(&self, journal_id: JournalId) -> Vec<Name> {
+        self.state
+            .journal_service
+            .list_journal_accounts(journal_id, &Authority::Direct(Actor::System))
+            .await
+            .expect("failed to list the accounts")
+            .into_iter()
+            .map(|(account, _, _)| account.name)
+            .collect()
+    }
+
+    /// how many transactions the journal has, as the system sees them
+    pub async fn transaction_count(&self, journal_id: JournalId) -> usize {
+        self.state
+            .journal_service
+            .list_journal_transactions(journal_id, &Authority::Direct(Actor::System))
+            .await
+            .expect("failed to list the transactions")
+            .len()
+    }
+
+    /// looks an account up by name, for requests that need its id
+    pub async fn account_id(&self, journal_id: JournalId, name: &str) -> AccountId {
+        self.state
+            .journal_service
+            .list_journal_accounts(journal_id, &Authority::Direct(Actor::System))
+            .await
+            .expect("failed to list the accounts")
+            .into_iter()
+            .find(|(account, _, _)| account.name.to_string() == name)
+            .expect("the account wasn't created")
+            .0
+            .id
+    }
+}
+
+impl TestUser {
+    pub async fn create_journal(&self, app: &TestApp, name: &str) -> JournalId {
+        self.client
+            .post("/createjournal")
+            .form(&[("journal_name", name)])
+            .await
+            .assert_status(StatusCode::SEE_OTHER);
+
+        app.state
+            .journal_service
+            .list_accessible_journals(self.id)
+            .await
+            .expect("failed to list the journals")
+            .into_iter()
+            .map(|(journal, _, _)| journal)
+            .find(|journal| journal.name.to_string() == name)
+            .expect("the journal wasn't created")
+            .id
+    }
+
+    pub async fn create_account(&self, journal_id: JournalId, name: &str) -> TestResponse {
+        self.client
+            .post(&format!("/journal/{journal_id}/createaccount"))
+            .form(&[("account_name", name)])
+            .await
+    }
+
+    /// moves the amount from one account to another
+    pub async fn post_transaction(
+        &self,
+        journal_id: JournalId,
+        from: AccountId,
+        to: AccountId,
+        amount: &str,
+    ) -> TestResponse {
+        let (from, to) = (from.to_string(), to.to_string());
+
+        self.client
+            .post(&format!("/journal/{journal_id}/transaction"))
+            .form(&[
+                ("account", to.as_str()),
+                ("amount", amount),
+                ("entry_type", "Dr"),
+                ("account", from.as_str()),
+                ("amount", amount),
+                ("entry_type", "Cr"),
+                ("effective_date", "2026-10-17"),
+            ])
+            .await
+    }
+
+    pub async fn invite(
+        &self,
+        journal_id: JournalId,
+        invitee: &TestUser,
+        permissions: Permissions,
+    ) -> TestResponse {
+        let email = invitee.email.to_string();
+        let mut form = vec![("email", email.as_str())];
+
+        for (field, permission) in [
+            ("read", Permissions::READ),
+            ("add_account", Permissions::ADD_ACCOUNT),
+            ("append_transaction", Permissions::APPEND_TRANSACTION),
+            ("submit_only", Permissions::SUBMIT_ONLY),
+            ("approve", Permissions::APPROVE),
+            ("invite", Permissions::INVITE),
+        ] {
+            if permissions.contains(permission) {
+                form.push((field, "on"));
+            }
+        }
+
+        self.client
+            .post(&format!("/journal/{journal_id}/invite"))
+            .form(&form)
+            .await
+    }
+}
+
+/// the request went through, and the browser was sent on
+pub fn assert_accepted(response: &TestResponse) {
+    response.assert_status(StatusCode::SEE_OTHER);
+}
+
+/// the request was turned away for lacking a permission
+pub fn assert_denied(response: &TestResponse) {
+    response.assert_status(StatusCode::FORBIDDEN);
+    assert_eq!(
+        response.json::<Value>()["code"],
+        "permission_denied",
+        "unexpected error: {}",
+        response.text()
+    );
+}
+
+/// the journal was hidden, the same way one that doesn't exist is
+pub fn assert_hidden(response: &TestResponse) {
+    response.assert_status(StatusCode::NOT_FOUND);
+    assert_eq!(
+        response.json::<Value>()["code"],
+        "journal_not_found",
+        "unexpected error: {}",
+        response.text()
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn signed_out_clients_are_sent_to_signin() {
+        let Some(app) = TestApp::spawn().await else {
+            return;
+        };
+
+        for url in ["/journal", "/dashboard", "/me"] {
+            let response = app.anonymous().get(url).await;
+
+            assert!(
+                response.status_code().is_redirection(),
+                "{url} wasn't guarded"
+            );
+            assert!(
+                response
+                    .header("location")
+                    .to_str()
+                    .unwrap()
+                    .starts_with("/signin")
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn strangers_cant_change_a_journal() {
+        let Some(app) = TestApp::spawn().await else {
+            return;
+        };
+
+        let owner = app.signup("owner").await;
+        let stranger = app.signup("stranger").await;
+
+        let journal_id = owner.create_journal(&app, "Household").await;
+        assert_accepted(&owner.create_account(journal_id, "Cash").await);
+        assert_accepted(&owner.create_account(journal_id, "Groceries").await);
+        let cash = app.account_id(journal_id, "Cash").await;
+        let groceries = app.account_id(journal_id, "Groceries").await;
+
+        assert_hidden(&stranger.create_account(journal_id, "Stolen").await);
+        assert_hidden(
+            &stranger
+                .post_transaction(journal_id, cash, groceries, "10.00")
+                .await,
+        );
+        assert_hidden(
+            &stranger
+                .invite(journal_id, &stranger, Permissions::all())
+                .await,
+        );
+        assert_hidden(&stranger.client.get(&format!("/journal/{journal_id}")).await);
+
+        assert_eq!(app.accounts(journal_id).await.len(), 2);
+        assert_eq!(app.transaction_count(journal_id).await, 0);
+    }
+
+    #[tokio::test]
+    async fn members_can_only_do_what_they_were_allowed() {
+        let Some(app) = TestApp::spawn().await else {
+            return;
+        };
+
+        let owner = app.signup("owner").await;
+        let member = app.signup("member").await;
+
+        let journal_id = owner.create_journal(&app, "Bakery").await;
+        assert_accepted(&owner.create_account(journal_id, "Till").await);
+        assert_accepted(&owner.create_account(journal_id, "Flour").await);
+        let till = app.account_id(journal_id, "Till").await;
+        let flour = app.account_id(journal_id, "Flour").await;
+
+        assert_accepted(&owner.invite(journal_id, &member, Permissions::READ).await);
+
+        assert_denied(&member.create_account(journal_id, "Sugar").await);
+        assert_denied(
+            &member
+                .post_transaction(journal_id, till, flour, "25.00")
+                .await,
+        );
+
+        assert_accepted(
+            &owner
+                .client
+                .post(&format!(
+                    "/journal/{journal_id}/person/{}/update",
+                    member.id
+                ))
+                .form(&[("read", "on"), ("append_transaction", "on")])
+                .await,
+        );
+
+        assert_accepted(
+            &member
+                .post_transaction(journal_id, till, flour, "25.00")
+                .await,
+        );
+        assert_denied(&member.create_account(journal_id, "Sugar").await);
+
+        assert_eq!(app.transaction_count(journal_id).await, 1);
+    }
+}
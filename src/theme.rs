@@ -1,7 +1,35 @@
+use crate::BackendType;
+use crate::asset::asset_url;
+use crate::authn::appearance::Theme;
+use crate::i18n::Locale;
+use axum::extract::Request;
+use axum::middleware::Next;
+use axum::response::Response;
+use axum_login::AuthSession;
 use maud::DOCTYPE;
 use maud::Markup;
 use maud::html;
 
+tokio::task_local! {
+    static THEME: Theme;
+}
+
+/// the theme of the user being served, or the system's outside of a request
+fn current_theme() -> Theme {
+    THEME.try_with(|theme| *theme).unwrap_or_default()
+}
+
+/// handles the rest of the request in the signed in user's theme
+pub async fn set_theme(
+    session: AuthSession<BackendType>,
+    request: Request,
+    next: Next,
+) -> Response {
+    let theme = session.user.map(|user| user.theme()).unwrap_or_default();
+
+    THEME.scope(theme, next.run(request)).await
+}
+
 pub fn theme(content: Markup) -> Markup {
     theme_with_head(None, html! {}, content)
 }
@@ -9,11 +37,11 @@ pub fn theme(content: Markup) -> Markup {
 pub fn theme_with_head(title: Option<&str>, extra_head: Markup, content: Markup) -> Markup {
     html! {
         (DOCTYPE)
-        html lang="en" class="h-full bg-white dark:bg-gray-900 text-gray-900 dark:text-white" {
+        html lang=(Locale::current()) class={ "h-full bg-white dark:bg-gray-900 text-gray-900 dark:text-white" @if let Some(class) = current_theme().class() { " " (class) } } {
             head {
                 meta charset="UTF-8";
                 meta name="viewport" content="width=device-width, initial-scale=1.0";
-                link rel="stylesheet" href="/monkesto.css";
+                link rel="stylesheet" href=(asset_url("monkesto.css"));
                 @if let Some(title) = title {
                     title { (title) " - Monkesto" }
                 }
@@ -1,7 +1,8 @@
 pub type Timestamp = DateTime<Utc>;
 
-use chrono::{DateTime, Duration, Utc};
+use chrono::{DateTime, Duration, TimeDelta, Utc};
 use std::cell::Cell;
+use std::sync::{Arc, Mutex};
 
 pub trait TimeProvider {
     fn get_time(&self) -> Timestamp;
@@ -51,3 +52,81 @@ impl TimeProvider for DateTime<Utc> {
         *self
     }
 }
+
+/// the time as the app sees it, shared by the handlers, services and background jobs.
+/// it's the system time unless a test swaps in a [`MockClock`]
+#[derive(Clone)]
+pub struct Clock(Arc<dyn TimeProvider + Send + Sync>);
+
+impl Clock {
+    pub fn new(provider: impl TimeProvider + Send + Sync + 'static) -> Self {
+        Self(Arc::new(provider))
+    }
+}
+
+impl Default for Clock {
+    fn default() -> Self {
+        Self::new(DefaultTimeProvider)
+    }
+}
+
+impl TimeProvider for Clock {
+    fn get_time(&self) -> Timestamp {
+        self.0.get_time()
+    }
+}
+
+/// a clock that only moves when it's told to. clones share the same time, so a test can keep
+/// one and hand the app a [`Clock`] made from another
+#[derive(Clone)]
+pub struct MockClock(Arc<Mutex<Timestamp>>);
+
+impl MockClock {
+    pub fn new(start: Timestamp) -> Self {
+        Self(Arc::new(Mutex::new(start)))
+    }
+
+    pub fn set(&self, time: Timestamp) {
+        *self.0.lock().expect("the mock clock was poisoned") = time;
+    }
+
+    pub fn advance(&self, by: TimeDelta) {
+        *self.0.lock().expect("the mock clock was poisoned") += by;
+    }
+}
+
+impl TimeProvider for MockClock {
+    fn get_time(&self) -> Timestamp {
+        *self.0.lock().expect("the mock clock was poisoned")
+    }
+}
+
+impl From<MockClock> for Clock {
+    fn from(clock: MockClock) -> Self {
+        Self::new(clock)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    #[test]
+    fn the_app_sees_the_mock_clock_move() {
+        let start = Utc
+            .with_ymd_and_hms(2026, 10, 17, 12, 0, 0)
+            .single()
+            .expect("invalid time");
+        let mock = MockClock::new(start);
+        let clock = Clock::from(mock.clone());
+
+        assert_eq!(clock.get_time(), start);
+
+        mock.advance(TimeDelta::days(1));
+        assert_eq!(clock.get_time(), start + TimeDelta::days(1));
+
+        mock.set(start);
+        assert_eq!(clock.get_time(), start);
+    }
+}
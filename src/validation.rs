@@ -0,0 +1,121 @@
+use crate::monkesto_error::{InvalidForm, MonkestoError};
+use maud::Markup;
+use maud::html;
+use std::collections::HashMap;
+
+/// what a user entered into a form, and what was wrong with it, so the form can be shown again
+/// with their input and an error beside each field
+#[derive(Default)]
+pub struct FormState {
+    values: HashMap<String, String>,
+    errors: Vec<(Option<String>, MonkestoError)>,
+}
+
+impl FormState {
+    /// remembers a value so the field is filled back in
+    pub fn keep(&mut self, field: &str, value: impl Into<String>) {
+        self.values.insert(field.to_string(), value.into());
+    }
+
+    /// the parsed value, or none once the error has been recorded against the field
+    pub fn check<T, E: Into<MonkestoError>>(
+        &mut self,
+        field: &str,
+        result: Result<T, E>,
+    ) -> Option<T> {
+        result.map_err(|e| self.reject(field, e)).ok()
+    }
+
+    pub fn reject(&mut self, field: &str, error: impl Into<MonkestoError>) {
+        self.errors.push((Some(field.to_string()), error.into()));
+    }
+
+    /// records an error that isn't about any one field
+    pub fn fail(&mut self, error: impl Into<MonkestoError>) {
+        self.errors.push((None, error.into()));
+    }
+
+    pub fn is_valid(&self) -> bool {
+        self.errors.is_empty()
+    }
+
+    pub fn value(&self, field: &str) -> Option<&str> {
+        self.values.get(field).map(String::as_str)
+    }
+
+    pub fn error(&self, field: &str) -> Option<&MonkestoError> {
+        self.errors
+            .iter()
+            .find(|(errored, _)| errored.as_deref() == Some(field))
+            .map(|(_, error)| error)
+    }
+
+    /// the aria-invalid attribute for the field's input
+    pub fn invalid(&self, field: &str) -> Option<&'static str> {
+        self.error(field).map(|_| "true")
+    }
+
+    /// the aria-describedby attribute, pointing the field's input at its error message
+    pub fn described_by(&self, field: &str) -> Option<String> {
+        self.error(field).map(|_| error_id(field))
+    }
+
+    /// the field's error message, if it has one
+    pub fn message(&self, field: &str) -> Markup {
+        html! {
+            @if let Some(error) = self.error(field) {
+                p id=(error_id(field)) class="mt-2 text-sm text-red-600 dark:text-red-400" {
+                    (error.message())
+                }
+            }
+        }
+    }
+
+    /// the errors that aren't about any one field, announced as soon as the page loads
+    pub fn summary(&self) -> Markup {
+        html! {
+            @for (_, error) in self.errors.iter().filter(|(field, _)| field.is_none()) {
+                p role="alert" class="text-sm text-red-600 dark:text-red-400" {
+                    (error.message())
+                }
+            }
+        }
+    }
+
+    /// the response for a submission that failed, which shows the page the form is on again
+    pub fn rejected(&self, page: Markup) -> InvalidForm {
+        InvalidForm::new(self.errors.first().map(|(_, error)| error), page)
+    }
+}
+
+fn error_id(field: &str) -> String {
+    format!("{field}-error")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::name::Name;
+
+    #[test]
+    fn field_errors_describe_their_inputs() {
+        let mut form = FormState::default();
+        form.keep("name", "");
+
+        assert_eq!(form.check("name", Name::try_new(String::new())), None);
+        assert!(!form.is_valid());
+        assert_eq!(form.value("name"), Some(""));
+        assert_eq!(form.invalid("name"), Some("true"));
+        assert_eq!(form.described_by("name"), Some("name-error".to_string()));
+        assert!(
+            form.message("name")
+                .into_string()
+                .contains("id=\"name-error\"")
+        );
+
+        assert_eq!(form.invalid("email"), None);
+        assert_eq!(form.described_by("email"), None);
+        assert_eq!(form.message("email").into_string(), "");
+        assert_eq!(form.summary().into_string(), "");
+    }
+}